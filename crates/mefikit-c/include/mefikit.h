@@ -0,0 +1,264 @@
+/* mefikit C API, generated by cbindgen. Do not edit. */
+
+#ifndef MEFIKIT_H
+#define MEFIKIT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ * Element types, with the same names as in mefikit.
+ *
+ * Their values are part of the ABI and never change.
+ */
+enum MfElementType {
+  MF_ELEMENT_TYPE_VERTEX = 0,
+  MF_ELEMENT_TYPE_SEG2 = 1,
+  MF_ELEMENT_TYPE_SEG3 = 2,
+  MF_ELEMENT_TYPE_SEG4 = 3,
+  MF_ELEMENT_TYPE_SPLINE = 4,
+  MF_ELEMENT_TYPE_TRI3 = 5,
+  MF_ELEMENT_TYPE_TRI6 = 6,
+  MF_ELEMENT_TYPE_TRI7 = 7,
+  MF_ELEMENT_TYPE_QUAD4 = 8,
+  MF_ELEMENT_TYPE_QUAD8 = 9,
+  MF_ELEMENT_TYPE_QUAD9 = 10,
+  MF_ELEMENT_TYPE_PGON = 11,
+  MF_ELEMENT_TYPE_TET4 = 12,
+  MF_ELEMENT_TYPE_TET10 = 13,
+  MF_ELEMENT_TYPE_HEX8 = 14,
+  MF_ELEMENT_TYPE_HEX21 = 15,
+  MF_ELEMENT_TYPE_PHED = 16,
+};
+typedef uint32_t MfElementType;
+
+/*
+ * Status returned by the fallible functions of the API.
+ *
+ * On failure, `mf_last_error` describes what went wrong.
+ */
+enum MfStatus {
+  MF_STATUS_OK = 0,
+  /*
+   * A null pointer, an unknown element type, a node out of range, etc.
+   */
+  MF_STATUS_INVALID_ARGUMENT = 1,
+  /*
+   * A mesh file could not be read or written.
+   */
+  MF_STATUS_IO = 2,
+  /*
+   * An unexpected error inside mefikit.
+   */
+  MF_STATUS_PANIC = 3,
+};
+typedef uint32_t MfStatus;
+
+/*
+ * Opaque mesh handle.
+ */
+typedef struct MfUMesh MfUMesh;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ * Version of the library, as a static string.
+ */
+const char *mf_version(void);
+
+/*
+ * Message of the last failed call of the current thread, or an empty string.
+ *
+ * The string is owned by mefikit and valid until the next call of the thread.
+ */
+const char *mf_last_error(void);
+
+/*
+ * Creates a mesh without elements from `num_nodes` nodes, their coordinates given row by row.
+ *
+ * # Safety
+ * `coords` must point to `num_nodes * space_dimension` values, and `out` to a writable pointer.
+ */
+MfStatus mf_umesh_new(const double *coords,
+                      size_t num_nodes,
+                      size_t space_dimension,
+                      MfUMesh **out);
+
+/*
+ * Releases a mesh. Does nothing on a null pointer.
+ *
+ * # Safety
+ * `mesh` must come from this library and must not be used afterwards.
+ */
+void mf_umesh_free(MfUMesh *mesh);
+
+/*
+ * Reads a mesh file, in the format given by its extension.
+ *
+ * # Safety
+ * `path` must be a nul terminated string, and `out` must point to a writable pointer.
+ */
+MfStatus mf_umesh_read(const char *path, MfUMesh **out);
+
+/*
+ * Writes a mesh file, in the format given by its extension.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh and `path` a nul terminated string.
+ */
+MfStatus mf_umesh_write(const MfUMesh *mesh, const char *path);
+
+/*
+ * Adds a block of elements of a regular type, which must not be in the mesh yet.
+ *
+ * `connectivity` holds the nodes of the elements one after the other, so its length must be a
+ * multiple of the number of nodes of the element type.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh and `connectivity` must point to `len` values.
+ */
+MfStatus mf_umesh_add_regular_block(MfUMesh *mesh,
+                                    uint32_t element_type,
+                                    const size_t *connectivity,
+                                    size_t len);
+
+/*
+ * Adds a block of PGON, PHED or SPLINE elements, which must not be in the mesh yet.
+ *
+ * The nodes of the elements are concatenated in `data` and `offsets` holds the end of each
+ * element in `data`. Faces of PHED elements are separated by `SIZE_MAX`.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, `data` must point to `data_len` values and `offsets` to
+ * `num_elements` values.
+ */
+MfStatus mf_umesh_add_poly_block(MfUMesh *mesh,
+                                 uint32_t element_type,
+                                 const size_t *data,
+                                 size_t data_len,
+                                 const size_t *offsets,
+                                 size_t num_elements);
+
+/*
+ * Adds (or replaces) a field on the block of an element type.
+ *
+ * `values` holds `num_components` values per element of the block, element by element.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, `name` a nul terminated string and `values` must point to `len`
+ * values.
+ */
+MfStatus mf_umesh_add_field(MfUMesh *mesh,
+                            uint32_t element_type,
+                            const char *name,
+                            const double *values,
+                            size_t len,
+                            size_t num_components);
+
+/*
+ * Space dimension of the mesh, or 0 for a null pointer.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh or null.
+ */
+size_t mf_umesh_space_dimension(const MfUMesh *mesh);
+
+/*
+ * Number of nodes of the mesh, or 0 for a null pointer.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh or null.
+ */
+size_t mf_umesh_num_nodes(const MfUMesh *mesh);
+
+/*
+ * Number of elements of the mesh, all types together, or 0 for a null pointer.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh or null.
+ */
+size_t mf_umesh_num_elements(const MfUMesh *mesh);
+
+/*
+ * Number of element blocks of the mesh, or 0 for a null pointer.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh or null.
+ */
+size_t mf_umesh_num_blocks(const MfUMesh *mesh);
+
+/*
+ * Element type and number of elements of the block at `index`, blocks being sorted by type.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, and `element_type` and `num_elements` must be writable.
+ */
+MfStatus mf_umesh_block(const MfUMesh *mesh,
+                        size_t index,
+                        MfElementType *element_type,
+                        size_t *num_elements);
+
+/*
+ * Borrows the node coordinates, row by row.
+ *
+ * The array is owned by the mesh, and valid until the mesh is modified or released.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, and `data` and `len` must be writable.
+ */
+MfStatus mf_umesh_coords(const MfUMesh *mesh, const double **data, size_t *len);
+
+/*
+ * Borrows the connectivity of the block of a regular element type, element by element.
+ *
+ * The array is owned by the mesh, and valid until the mesh is modified or released.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, and `data` and `len` must be writable.
+ */
+MfStatus mf_umesh_regular_connectivity(const MfUMesh *mesh,
+                                       uint32_t element_type,
+                                       const size_t **data,
+                                       size_t *len);
+
+/*
+ * Borrows the connectivity of the block of a poly element type, as in
+ * `mf_umesh_add_poly_block`.
+ *
+ * The arrays are owned by the mesh, and valid until the mesh is modified or released.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, and the output pointers must be writable.
+ */
+MfStatus mf_umesh_poly_connectivity(const MfUMesh *mesh,
+                                    uint32_t element_type,
+                                    const size_t **data,
+                                    size_t *data_len,
+                                    const size_t **offsets,
+                                    size_t *num_elements);
+
+/*
+ * Borrows the values of a field on the block of an element type, element by element.
+ *
+ * The array is owned by the mesh, and valid until the mesh is modified or released.
+ *
+ * # Safety
+ * `mesh` must be a valid mesh, `name` a nul terminated string, and `data` and `len` must be
+ * writable.
+ */
+MfStatus mf_umesh_field(const MfUMesh *mesh,
+                        uint32_t element_type,
+                        const char *name,
+                        const double **data,
+                        size_t *len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MEFIKIT_H */
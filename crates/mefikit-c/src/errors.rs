@@ -0,0 +1,104 @@
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// Status returned by the fallible functions of the API.
+///
+/// On failure, `mf_last_error` describes what went wrong.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MfStatus {
+    Ok = 0,
+    /// A null pointer, an unknown element type, a node out of range, etc.
+    InvalidArgument = 1,
+    /// A mesh file could not be read or written.
+    Io = 2,
+    /// An unexpected error inside mefikit.
+    Panic = 3,
+}
+
+/// Error of a call, turned into a status and a message at the API boundary.
+pub(crate) struct FfiError {
+    status: MfStatus,
+    message: String,
+}
+
+impl FfiError {
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: MfStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self {
+            status: MfStatus::Io,
+            message: message.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: String) {
+    // interior nul bytes would truncate the message anyway
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Runs the body of an API function, catching its errors and panics.
+pub(crate) fn guard(body: impl FnOnce() -> Result<(), FfiError>) -> MfStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => {
+            set_last_error(String::new());
+            MfStatus::Ok
+        }
+        Ok(Err(e)) => {
+            set_last_error(e.message);
+            e.status
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown panic".to_owned());
+            set_last_error(message);
+            MfStatus::Panic
+        }
+    }
+}
+
+/// Message of the last failed call of the current thread, or an empty string.
+///
+/// The string is owned by mefikit and valid until the next call of the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn mf_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(mf_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(guard(|| Err(FfiError::io("no file"))), MfStatus::Io);
+        assert_eq!(last_error(), "no file");
+        assert_eq!(guard(|| panic!("boom")), MfStatus::Panic);
+        assert_eq!(last_error(), "boom");
+        assert_eq!(guard(|| Ok(())), MfStatus::Ok);
+        assert_eq!(last_error(), "");
+    }
+}
@@ -0,0 +1,153 @@
+//! C API of mefikit.
+//!
+//! Meshes are handled through opaque `MfUMesh` pointers, created by `mf_umesh_new` or
+//! `mf_umesh_read` and released by `mf_umesh_free`. Arrays are passed as a pointer and a length,
+//! node coordinates and regular connectivities being flattened row by row. Fallible functions
+//! return an `MfStatus`, with the error message available from `mf_last_error`.
+//!
+//! The header `include/mefikit.h` is generated by cbindgen from this crate.
+
+use std::ffi::c_char;
+
+use mefikit::prelude as mf;
+
+mod errors;
+mod umesh;
+
+pub use errors::{MfStatus, mf_last_error};
+pub use umesh::*;
+
+use errors::FfiError;
+
+/// Element types, with the same names as in mefikit.
+///
+/// Their values are part of the ABI and never change.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MfElementType {
+    Vertex = 0,
+    Seg2 = 1,
+    Seg3 = 2,
+    Seg4 = 3,
+    Spline = 4,
+    Tri3 = 5,
+    Tri6 = 6,
+    Tri7 = 7,
+    Quad4 = 8,
+    Quad8 = 9,
+    Quad9 = 10,
+    Pgon = 11,
+    Tet4 = 12,
+    Tet10 = 13,
+    Hex8 = 14,
+    Hex21 = 15,
+    Phed = 16,
+}
+
+const ELEMENT_TYPES: [(MfElementType, mf::ElementType); 17] = {
+    use MfElementType as C;
+    use mf::ElementType::*;
+    [
+        (C::Vertex, VERTEX),
+        (C::Seg2, SEG2),
+        (C::Seg3, SEG3),
+        (C::Seg4, SEG4),
+        (C::Spline, SPLINE),
+        (C::Tri3, TRI3),
+        (C::Tri6, TRI6),
+        (C::Tri7, TRI7),
+        (C::Quad4, QUAD4),
+        (C::Quad8, QUAD8),
+        (C::Quad9, QUAD9),
+        (C::Pgon, PGON),
+        (C::Tet4, TET4),
+        (C::Tet10, TET10),
+        (C::Hex8, HEX8),
+        (C::Hex21, HEX21),
+        (C::Phed, PHED),
+    ]
+};
+
+/// Converts an element type received as an integer, which may not be a valid `MfElementType`.
+fn to_element_type(code: u32) -> Result<mf::ElementType, FfiError> {
+    ELEMENT_TYPES
+        .iter()
+        .find(|(c, _)| *c as u32 == code)
+        .map(|&(_, et)| et)
+        .ok_or_else(|| FfiError::invalid(format!("Unknown element type {code}")))
+}
+
+fn from_element_type(et: mf::ElementType) -> MfElementType {
+    ELEMENT_TYPES.iter().find(|(_, e)| *e == et).unwrap().0
+}
+
+/// Version of the library, as a static string.
+#[unsafe(no_mangle)]
+pub extern "C" fn mf_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_element_types() {
+        for (c, et) in ELEMENT_TYPES {
+            assert_eq!(to_element_type(c as u32).ok(), Some(et));
+            assert_eq!(from_element_type(et), c);
+        }
+        assert!(to_element_type(17).is_err());
+    }
+
+    /// Names of the functions exported by a source file.
+    fn exported_functions(source: &str) -> impl Iterator<Item = &str> {
+        source
+            .split("extern \"C\" fn ")
+            .skip(1)
+            .map(|s| s.split('(').next().unwrap())
+    }
+
+    /// The header is generated by cbindgen, this checks that it was regenerated after the last
+    /// change of the API.
+    #[test]
+    fn test_header_matches_api() {
+        let header = include_str!("../include/mefikit.h");
+        let sources = [
+            include_str!("lib.rs"),
+            include_str!("errors.rs"),
+            include_str!("umesh.rs"),
+        ];
+        let mut exported: Vec<&str> = sources.into_iter().flat_map(exported_functions).collect();
+        exported.sort_unstable();
+        // Declarations start their line, unlike the comments
+        let mut declared: Vec<&str> = header
+            .lines()
+            .filter(|line| !line.starts_with([' ', '/', '#']))
+            .filter_map(|line| {
+                let start = line.find("mf_")?;
+                line[start..].split('(').next()
+            })
+            .collect();
+        declared.sort_unstable();
+        assert_eq!(declared, exported);
+
+        for (c, et) in ELEMENT_TYPES {
+            let variant = format!("MF_ELEMENT_TYPE_{et:?} = {},", c as u32);
+            assert!(header.contains(&variant), "{variant} is not in the header");
+        }
+        assert_eq!(
+            header.matches("MF_ELEMENT_TYPE_").count(),
+            ELEMENT_TYPES.len()
+        );
+        for (status, name) in [
+            (MfStatus::Ok, "OK"),
+            (MfStatus::InvalidArgument, "INVALID_ARGUMENT"),
+            (MfStatus::Io, "IO"),
+            (MfStatus::Panic, "PANIC"),
+        ] {
+            let variant = format!("MF_STATUS_{name} = {},", status as u32);
+            assert!(header.contains(&variant), "{variant} is not in the header");
+        }
+    }
+}
@@ -0,0 +1,564 @@
+use std::ffi::{CStr, c_char};
+use std::path::Path;
+
+use ndarray as nd;
+
+use mefikit::prelude as mf;
+
+use crate::errors::{FfiError, MfStatus, guard};
+use crate::{MfElementType, from_element_type, to_element_type};
+
+/// Opaque mesh handle.
+pub struct MfUMesh(mf::UMesh);
+
+/// Reads `len` values, allowing a null pointer for an empty array.
+unsafe fn slice<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T], FfiError> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(FfiError::invalid("Null array pointer")),
+        (false, _) => Ok(unsafe { std::slice::from_raw_parts(ptr, len) }),
+    }
+}
+
+unsafe fn mesh_ref<'a>(mesh: *const MfUMesh) -> Result<&'a mf::UMesh, FfiError> {
+    unsafe { mesh.as_ref() }
+        .map(|m| &m.0)
+        .ok_or_else(|| FfiError::invalid("Null mesh pointer"))
+}
+
+unsafe fn mesh_mut<'a>(mesh: *mut MfUMesh) -> Result<&'a mut mf::UMesh, FfiError> {
+    unsafe { mesh.as_mut() }
+        .map(|m| &mut m.0)
+        .ok_or_else(|| FfiError::invalid("Null mesh pointer"))
+}
+
+unsafe fn out_ref<'a, T>(out: *mut T) -> Result<&'a mut T, FfiError> {
+    unsafe { out.as_mut() }.ok_or_else(|| FfiError::invalid("Null output pointer"))
+}
+
+unsafe fn to_path<'a>(path: *const c_char) -> Result<&'a Path, FfiError> {
+    if path.is_null() {
+        return Err(FfiError::invalid("Null path"));
+    }
+    unsafe { CStr::from_ptr(path) }
+        .to_str()
+        .map(Path::new)
+        .map_err(|_| FfiError::invalid("The path is not valid UTF-8"))
+}
+
+/// Returns a borrowed array as a pointer and a length.
+fn export<T>(values: &[T], data: &mut *const T, len: &mut usize) {
+    *data = values.as_ptr();
+    *len = values.len();
+}
+
+fn check_new_block(mesh: &mf::UMesh, et: mf::ElementType) -> Result<(), FfiError> {
+    match mesh.block(et) {
+        Some(_) => Err(FfiError::invalid(format!(
+            "The mesh already has a {et:?} block"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn check_nodes(
+    mesh: &mf::UMesh,
+    connectivity: &[usize],
+    allow_separator: bool,
+) -> Result<(), FfiError> {
+    let num_nodes = mesh.coords().nrows();
+    match connectivity
+        .iter()
+        .find(|&&n| n >= num_nodes && !(allow_separator && n == usize::MAX))
+    {
+        Some(n) => Err(FfiError::invalid(format!(
+            "Node {n} out of range, the mesh has {num_nodes} nodes"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Creates a mesh without elements from `num_nodes` nodes, their coordinates given row by row.
+///
+/// # Safety
+/// `coords` must point to `num_nodes * space_dimension` values, and `out` to a writable pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_new(
+    coords: *const f64,
+    num_nodes: usize,
+    space_dimension: usize,
+    out: *mut *mut MfUMesh,
+) -> MfStatus {
+    guard(|| {
+        let out = unsafe { out_ref(out)? };
+        if !(1..=3).contains(&space_dimension) {
+            return Err(FfiError::invalid(format!(
+                "Invalid space dimension {space_dimension}, expected 1, 2 or 3"
+            )));
+        }
+        let len = num_nodes
+            .checked_mul(space_dimension)
+            .ok_or_else(|| FfiError::invalid("Too many nodes"))?;
+        let coords = unsafe { slice(coords, len)? };
+        let coords = nd::Array2::from_shape_vec((num_nodes, space_dimension), coords.to_vec())
+            .map_err(|e| FfiError::invalid(e.to_string()))?;
+        *out = Box::into_raw(Box::new(MfUMesh(mf::UMesh::new(coords.into_shared()))));
+        Ok(())
+    })
+}
+
+/// Releases a mesh. Does nothing on a null pointer.
+///
+/// # Safety
+/// `mesh` must come from this library and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_free(mesh: *mut MfUMesh) {
+    if !mesh.is_null() {
+        drop(unsafe { Box::from_raw(mesh) });
+    }
+}
+
+/// Reads a mesh file, in the format given by its extension.
+///
+/// # Safety
+/// `path` must be a nul terminated string, and `out` must point to a writable pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_read(path: *const c_char, out: *mut *mut MfUMesh) -> MfStatus {
+    guard(|| {
+        let out = unsafe { out_ref(out)? };
+        let path = unsafe { to_path(path)? };
+        let mesh = mf::read(path)
+            .map_err(|e| FfiError::io(format!("Could not read {}: {e}", path.display())))?;
+        *out = Box::into_raw(Box::new(MfUMesh(mesh)));
+        Ok(())
+    })
+}
+
+/// Writes a mesh file, in the format given by its extension.
+///
+/// # Safety
+/// `mesh` must be a valid mesh and `path` a nul terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_write(mesh: *const MfUMesh, path: *const c_char) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let path = unsafe { to_path(path)? };
+        mf::write(path, mesh.view())
+            .map_err(|e| FfiError::io(format!("Could not write {}: {e}", path.display())))
+    })
+}
+
+/// Adds a block of elements of a regular type, which must not be in the mesh yet.
+///
+/// `connectivity` holds the nodes of the elements one after the other, so its length must be a
+/// multiple of the number of nodes of the element type.
+///
+/// # Safety
+/// `mesh` must be a valid mesh and `connectivity` must point to `len` values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_add_regular_block(
+    mesh: *mut MfUMesh,
+    element_type: u32,
+    connectivity: *const usize,
+    len: usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_mut(mesh)? };
+        let et = to_element_type(element_type)?;
+        let connectivity = unsafe { slice(connectivity, len)? };
+        let Some(num_nodes) = et.num_nodes() else {
+            return Err(FfiError::invalid(format!(
+                "{et:?} is a poly type, use mf_umesh_add_poly_block"
+            )));
+        };
+        if !len.is_multiple_of(num_nodes) {
+            return Err(FfiError::invalid(format!(
+                "The connectivity length {len} is not a multiple of {num_nodes}"
+            )));
+        }
+        check_new_block(mesh, et)?;
+        check_nodes(mesh, connectivity, false)?;
+        let connectivity =
+            nd::Array2::from_shape_vec((len / num_nodes, num_nodes), connectivity.to_vec())
+                .map_err(|e| FfiError::invalid(e.to_string()))?;
+        mesh.add_regular_block(et, connectivity.into_shared(), None);
+        Ok(())
+    })
+}
+
+/// Adds a block of PGON, PHED or SPLINE elements, which must not be in the mesh yet.
+///
+/// The nodes of the elements are concatenated in `data` and `offsets` holds the end of each
+/// element in `data`. Faces of PHED elements are separated by `SIZE_MAX`.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, `data` must point to `data_len` values and `offsets` to
+/// `num_elements` values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_add_poly_block(
+    mesh: *mut MfUMesh,
+    element_type: u32,
+    data: *const usize,
+    data_len: usize,
+    offsets: *const usize,
+    num_elements: usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_mut(mesh)? };
+        let et = to_element_type(element_type)?;
+        let data = unsafe { slice(data, data_len)? };
+        let offsets = unsafe { slice(offsets, num_elements)? };
+        if et.num_nodes().is_some() {
+            return Err(FfiError::invalid(format!(
+                "{et:?} is a regular type, use mf_umesh_add_regular_block"
+            )));
+        }
+        if !offsets.is_sorted() || offsets.last().copied().unwrap_or(0) != data_len {
+            return Err(FfiError::invalid(
+                "Offsets must be sorted and end with the length of data",
+            ));
+        }
+        check_new_block(mesh, et)?;
+        check_nodes(mesh, data, et == mf::ElementType::PHED)?;
+        mesh.add_poly_block(
+            et,
+            nd::Array1::from(data.to_vec()).into_shared(),
+            nd::Array1::from(offsets.to_vec()).into_shared(),
+        );
+        Ok(())
+    })
+}
+
+/// Adds (or replaces) a field on the block of an element type.
+///
+/// `values` holds `num_components` values per element of the block, element by element.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, `name` a nul terminated string and `values` must point to `len`
+/// values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_add_field(
+    mesh: *mut MfUMesh,
+    element_type: u32,
+    name: *const c_char,
+    values: *const f64,
+    len: usize,
+    num_components: usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_mut(mesh)? };
+        let et = to_element_type(element_type)?;
+        if name.is_null() {
+            return Err(FfiError::invalid("Null field name"));
+        }
+        let name = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .map_err(|_| FfiError::invalid("The field name is not valid UTF-8"))?;
+        let values = unsafe { slice(values, len)? };
+        let num_elements = mesh
+            .block(et)
+            .ok_or_else(|| FfiError::invalid(format!("No {et:?} block in the mesh")))?
+            .len();
+        if num_components == 0 || num_elements * num_components != len {
+            return Err(FfiError::invalid(format!(
+                "Expected {num_components} values for each of the {num_elements} elements, got \
+                 {len} values"
+            )));
+        }
+        let shape = match num_components {
+            1 => vec![num_elements],
+            n => vec![num_elements, n],
+        };
+        let values = nd::ArrayD::from_shape_vec(shape, values.to_vec())
+            .map_err(|e| FfiError::invalid(e.to_string()))?;
+        mesh.add_field(et, name, values.into_shared());
+        Ok(())
+    })
+}
+
+/// Space dimension of the mesh, or 0 for a null pointer.
+///
+/// # Safety
+/// `mesh` must be a valid mesh or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_space_dimension(mesh: *const MfUMesh) -> usize {
+    unsafe { mesh_ref(mesh) }.map_or(0, |m| m.space_dimension())
+}
+
+/// Number of nodes of the mesh, or 0 for a null pointer.
+///
+/// # Safety
+/// `mesh` must be a valid mesh or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_num_nodes(mesh: *const MfUMesh) -> usize {
+    unsafe { mesh_ref(mesh) }.map_or(0, |m| m.coords().nrows())
+}
+
+/// Number of elements of the mesh, all types together, or 0 for a null pointer.
+///
+/// # Safety
+/// `mesh` must be a valid mesh or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_num_elements(mesh: *const MfUMesh) -> usize {
+    unsafe { mesh_ref(mesh) }.map_or(0, |m| m.num_elements())
+}
+
+/// Number of element blocks of the mesh, or 0 for a null pointer.
+///
+/// # Safety
+/// `mesh` must be a valid mesh or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_num_blocks(mesh: *const MfUMesh) -> usize {
+    unsafe { mesh_ref(mesh) }.map_or(0, |m| m.blocks().count())
+}
+
+/// Element type and number of elements of the block at `index`, blocks being sorted by type.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, and `element_type` and `num_elements` must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_block(
+    mesh: *const MfUMesh,
+    index: usize,
+    element_type: *mut MfElementType,
+    num_elements: *mut usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let element_type = unsafe { out_ref(element_type)? };
+        let num_elements = unsafe { out_ref(num_elements)? };
+        let (&et, block) = mesh
+            .blocks()
+            .nth(index)
+            .ok_or_else(|| FfiError::invalid(format!("Block {index} out of range")))?;
+        *element_type = from_element_type(et);
+        *num_elements = block.len();
+        Ok(())
+    })
+}
+
+/// Borrows the node coordinates, row by row.
+///
+/// The array is owned by the mesh, and valid until the mesh is modified or released.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, and `data` and `len` must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_coords(
+    mesh: *const MfUMesh,
+    data: *mut *const f64,
+    len: *mut usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let (data, len) = unsafe { (out_ref(data)?, out_ref(len)?) };
+        let coords = mesh
+            .coords()
+            .to_slice()
+            .ok_or_else(|| FfiError::invalid("The coordinates are not contiguous"))?;
+        export(coords, data, len);
+        Ok(())
+    })
+}
+
+/// Borrows the connectivity of the block of a regular element type, element by element.
+///
+/// The array is owned by the mesh, and valid until the mesh is modified or released.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, and `data` and `len` must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_regular_connectivity(
+    mesh: *const MfUMesh,
+    element_type: u32,
+    data: *mut *const usize,
+    len: *mut usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let (data, len) = unsafe { (out_ref(data)?, out_ref(len)?) };
+        let connectivity = mesh
+            .regular_connectivity(to_element_type(element_type)?)
+            .map_err(FfiError::invalid)?;
+        let connectivity = connectivity
+            .to_slice()
+            .ok_or_else(|| FfiError::invalid("The connectivity is not contiguous"))?;
+        export(connectivity, data, len);
+        Ok(())
+    })
+}
+
+/// Borrows the connectivity of the block of a poly element type, as in
+/// `mf_umesh_add_poly_block`.
+///
+/// The arrays are owned by the mesh, and valid until the mesh is modified or released.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, and the output pointers must be writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_poly_connectivity(
+    mesh: *const MfUMesh,
+    element_type: u32,
+    data: *mut *const usize,
+    data_len: *mut usize,
+    offsets: *mut *const usize,
+    num_elements: *mut usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let (data, data_len) = unsafe { (out_ref(data)?, out_ref(data_len)?) };
+        let (offsets, num_elements) = unsafe { (out_ref(offsets)?, out_ref(num_elements)?) };
+        let (conn, offs) = mesh
+            .poly_connectivity(to_element_type(element_type)?)
+            .map_err(FfiError::invalid)?;
+        let not_contiguous = || FfiError::invalid("The connectivity is not contiguous");
+        export(conn.to_slice().ok_or_else(not_contiguous)?, data, data_len);
+        export(
+            offs.to_slice().ok_or_else(not_contiguous)?,
+            offsets,
+            num_elements,
+        );
+        Ok(())
+    })
+}
+
+/// Borrows the values of a field on the block of an element type, element by element.
+///
+/// The array is owned by the mesh, and valid until the mesh is modified or released.
+///
+/// # Safety
+/// `mesh` must be a valid mesh, `name` a nul terminated string, and `data` and `len` must be
+/// writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mf_umesh_field(
+    mesh: *const MfUMesh,
+    element_type: u32,
+    name: *const c_char,
+    data: *mut *const f64,
+    len: *mut usize,
+) -> MfStatus {
+    guard(|| {
+        let mesh = unsafe { mesh_ref(mesh)? };
+        let (data, len) = unsafe { (out_ref(data)?, out_ref(len)?) };
+        let et = to_element_type(element_type)?;
+        if name.is_null() {
+            return Err(FfiError::invalid("Null field name"));
+        }
+        let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+        let values = mesh
+            .block(et)
+            .and_then(|b| b.fields.get(name.as_ref()))
+            .ok_or_else(|| FfiError::invalid(format!("No field {name} on {et:?} elements")))?;
+        let values = values
+            .as_slice()
+            .ok_or_else(|| FfiError::invalid("The field is not contiguous"))?;
+        export(values, data, len);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::ptr;
+
+    fn unit_square() -> *mut MfUMesh {
+        let coords = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        let mut mesh = ptr::null_mut();
+        let status = unsafe { mf_umesh_new(coords.as_ptr(), 4, 2, &mut mesh) };
+        assert_eq!(status, MfStatus::Ok);
+        mesh
+    }
+
+    #[test]
+    fn test_build_and_query() {
+        let mesh = unit_square();
+        let quad = [0, 1, 3, 2];
+        let pgon = [0, 1, 3, 0, 3, 2];
+        unsafe {
+            let quad4 = MfElementType::Quad4 as u32;
+            let pgon_t = MfElementType::Pgon as u32;
+            assert_eq!(
+                mf_umesh_add_regular_block(mesh, quad4, quad.as_ptr(), 4),
+                MfStatus::Ok
+            );
+            assert_eq!(
+                mf_umesh_add_poly_block(mesh, pgon_t, pgon.as_ptr(), 6, [3, 6].as_ptr(), 2),
+                MfStatus::Ok
+            );
+            let field = c"f".as_ptr();
+            assert_eq!(
+                mf_umesh_add_field(mesh, quad4, field, [2.0].as_ptr(), 1, 1),
+                MfStatus::Ok
+            );
+            assert_eq!(mf_umesh_num_nodes(mesh), 4);
+            assert_eq!(mf_umesh_space_dimension(mesh), 2);
+            assert_eq!(mf_umesh_num_elements(mesh), 3);
+            assert_eq!(mf_umesh_num_blocks(mesh), 2);
+
+            let (mut et, mut n) = (MfElementType::Vertex, 0);
+            assert_eq!(mf_umesh_block(mesh, 1, &mut et, &mut n), MfStatus::Ok);
+            assert_eq!((et, n), (MfElementType::Pgon, 2));
+
+            let (mut data, mut len) = (ptr::null(), 0);
+            assert_eq!(
+                mf_umesh_regular_connectivity(mesh, quad4, &mut data, &mut len),
+                MfStatus::Ok
+            );
+            assert_eq!(std::slice::from_raw_parts(data, len), quad);
+            let (mut values, mut len) = (ptr::null(), 0);
+            assert_eq!(
+                mf_umesh_field(mesh, quad4, field, &mut values, &mut len),
+                MfStatus::Ok
+            );
+            assert_eq!(std::slice::from_raw_parts(values, len), [2.0]);
+            mf_umesh_free(mesh);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let mesh = unit_square();
+        unsafe {
+            let tri3 = MfElementType::Tri3 as u32;
+            let status = mf_umesh_add_regular_block(mesh, tri3, [0, 1, 4].as_ptr(), 3);
+            assert_eq!(status, MfStatus::InvalidArgument);
+            let status = mf_umesh_add_regular_block(mesh, tri3, [0, 1].as_ptr(), 2);
+            assert_eq!(status, MfStatus::InvalidArgument);
+            let status = mf_umesh_add_regular_block(mesh, 42, [0, 1, 2].as_ptr(), 3);
+            assert_eq!(status, MfStatus::InvalidArgument);
+            let status = mf_umesh_add_regular_block(mesh, tri3, [0, 1, 2].as_ptr(), 3);
+            assert_eq!(status, MfStatus::Ok);
+            let status = mf_umesh_add_regular_block(mesh, tri3, [1, 2, 3].as_ptr(), 3);
+            assert_eq!(status, MfStatus::InvalidArgument);
+            let status = mf_umesh_add_regular_block(ptr::null_mut(), tri3, ptr::null(), 0);
+            assert_eq!(status, MfStatus::InvalidArgument);
+            let mut other = ptr::null_mut();
+            let status = mf_umesh_read(c"missing.json".as_ptr(), &mut other);
+            assert_eq!(status, MfStatus::Io);
+            assert!(other.is_null());
+            assert_eq!(mf_umesh_num_nodes(ptr::null()), 0);
+            mf_umesh_free(mesh);
+        }
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mesh = unit_square();
+        let file = std::env::temp_dir().join("mefikit_c_api_test.json");
+        let path = CString::new(file.to_str().unwrap()).unwrap();
+        let path = path.as_ptr();
+        unsafe {
+            let tri3 = MfElementType::Tri3 as u32;
+            mf_umesh_add_regular_block(mesh, tri3, [0, 1, 3, 0, 3, 2].as_ptr(), 6);
+            assert_eq!(mf_umesh_write(mesh, path), MfStatus::Ok);
+            let mut read = ptr::null_mut();
+            assert_eq!(mf_umesh_read(path, &mut read), MfStatus::Ok);
+            assert_eq!(mf_umesh_num_elements(read), 2);
+            mf_umesh_free(read);
+            mf_umesh_free(mesh);
+        }
+        std::fs::remove_file(file).unwrap();
+    }
+}
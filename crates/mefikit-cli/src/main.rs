@@ -0,0 +1,250 @@
+//! `mefikit` command-line tool: common mesh operations on files, built on the library API.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+
+use mefikit::prelude as mf;
+use mefikit::tools::{Descendable, MeshSelect, NodeDuplicates};
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+fn input_arg() -> Arg {
+    Arg::new("input")
+        .required(true)
+        .value_parser(value_parser!(PathBuf))
+        .help("Input mesh file")
+}
+
+fn output_arg() -> Arg {
+    Arg::new("output")
+        .required(true)
+        .value_parser(value_parser!(PathBuf))
+        .help("Output mesh file")
+}
+
+fn format_arg() -> Arg {
+    Arg::new("format")
+        .long("format")
+        .value_parser(|s: &str| s.parse::<mf::Format>())
+        .help("Output format, deduced from the extension by default")
+}
+
+fn dim_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_parser(|s: &str| -> Result<mf::Dimension, String> {
+            s.parse::<usize>().map_err(|e| e.to_string())?.try_into()
+        })
+        .help(help)
+}
+
+fn cli() -> Command {
+    Command::new("mefikit")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Unstructured mesh operations on mesh files")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("convert")
+                .about("Converts a mesh to another format")
+                .args([input_arg(), output_arg(), format_arg()])
+                .arg(
+                    Arg::new("compression")
+                        .long("compression")
+                        .value_parser(value_parser!(u8).range(0..=9))
+                        .help("Deflate level of VTKHDF outputs, from 0 to 9"),
+                ),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Prints the element blocks, groups and fields of a mesh")
+                .arg(input_arg()),
+        )
+        .subcommand(
+            Command::new("extract")
+                .about("Extracts the elements matching a selection")
+                .args([input_arg(), output_arg(), format_arg()])
+                .arg(
+                    Arg::new("select")
+                        .long("select")
+                        .required(true)
+                        .value_parser(|s: &str| mf::Selection::parse(s).map_err(|e| e.to_string()))
+                        .help("Selection, e.g. \"dim == 2 && group('wall')\""),
+                )
+                .arg(
+                    Arg::new("no-fields")
+                        .long("no-fields")
+                        .action(ArgAction::SetTrue)
+                        .help("Drops the fields of the extracted elements"),
+                ),
+        )
+        .subcommand(
+            Command::new("boundaries")
+                .about("Extracts the boundary of a mesh")
+                .args([input_arg(), output_arg(), format_arg()])
+                .arg(dim_arg("src-dim", "Dimension of the elements to bound"))
+                .arg(dim_arg("target-dim", "Dimension of the boundary elements")),
+        )
+        .subcommand(
+            Command::new("quality")
+                .about("Prints measure statistics and degenerate elements per element type")
+                .arg(input_arg())
+                .arg(dim_arg(
+                    "dim",
+                    "Dimension of the elements, the highest by default",
+                )),
+        )
+        .subcommand(
+            Command::new("merge-nodes")
+                .about("Merges the nodes closer than a tolerance")
+                .args([input_arg(), output_arg(), format_arg()])
+                .arg(
+                    Arg::new("eps")
+                        .long("eps")
+                        .default_value("1e-12")
+                        .value_parser(value_parser!(f64))
+                        .help("Distance under which nodes are merged"),
+                ),
+        )
+}
+
+fn read(args: &ArgMatches) -> Result<mf::UMesh, Box<dyn Error>> {
+    let path: &PathBuf = args.get_one("input").unwrap();
+    mf::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()).into())
+}
+
+fn write(args: &ArgMatches, mesh: &mf::UMesh, compression: Option<u8>) -> CliResult {
+    let path: &PathBuf = args.get_one("output").unwrap();
+    let mut options = mf::WriteOptions::new();
+    if let Some(&format) = args.get_one::<mf::Format>("format") {
+        options = options.format(format);
+    }
+    if let Some(level) = compression {
+        options = options.compression(level);
+    }
+    mf::write_with(Path::new(path), mesh.view(), &options)
+        .map_err(|e| format!("Could not write {}: {e}", path.display()).into())
+}
+
+/// Measure statistics of the elements of one type.
+#[derive(Debug)]
+struct MeasureStats {
+    count: usize,
+    min: f64,
+    max: f64,
+    mean: f64,
+    /// Elements with a zero or negative measure, degenerate or inverted.
+    non_positive: usize,
+}
+
+impl MeasureStats {
+    fn new(measures: &[f64]) -> Self {
+        Self {
+            count: measures.len(),
+            min: measures.iter().copied().fold(f64::INFINITY, f64::min),
+            max: measures.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean: measures.iter().sum::<f64>() / measures.len().max(1) as f64,
+            non_positive: measures.iter().filter(|&&m| m <= 0.0).count(),
+        }
+    }
+}
+
+fn quality(mesh: &mf::UMesh, dim: Option<mf::Dimension>) {
+    println!(
+        "{:<8} {:>10} {:>12} {:>12} {:>12} {:>12}",
+        "type", "count", "min", "max", "mean", "degenerate"
+    );
+    for (et, measures) in mf::measure(mesh.view(), dim) {
+        let stats = MeasureStats::new(measures.as_slice().unwrap());
+        println!(
+            "{:<8} {:>10} {:>12.4e} {:>12.4e} {:>12.4e} {:>12}",
+            format!("{et:?}"),
+            stats.count,
+            stats.min,
+            stats.max,
+            stats.mean,
+            stats.non_positive
+        );
+    }
+}
+
+fn run(matches: &ArgMatches) -> CliResult {
+    let (name, args) = matches.subcommand().unwrap();
+    let mesh = read(args)?;
+    match name {
+        "convert" => write(args, &mesh, args.get_one::<u8>("compression").copied()),
+        "info" => {
+            print!("{mesh:#}");
+            Ok(())
+        }
+        "extract" => {
+            let selection: &mf::Selection = args.get_one("select").unwrap();
+            let with_fields = !args.get_flag("no-fields");
+            let (_, submesh) = mesh.select(selection.clone(), with_fields);
+            write(args, &submesh, None)
+        }
+        "boundaries" => {
+            let src_dim = args.get_one("src-dim").copied();
+            let target_dim = args.get_one("target-dim").copied();
+            write(args, &mesh.boundaries(src_dim, target_dim), None)
+        }
+        "quality" => {
+            quality(&mesh, args.get_one("dim").copied());
+            Ok(())
+        }
+        "merge-nodes" => {
+            let mut merged = mesh;
+            merged.merge_nodes(*args.get_one::<f64>("eps").unwrap());
+            write(args, &merged, None)
+        }
+        _ => unreachable!("unknown subcommand {name}"),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(&cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli() {
+        cli().debug_assert();
+        let matches = cli()
+            .try_get_matches_from([
+                "mefikit", "extract", "a.vtu", "b.vtu", "--select", "dim == 2",
+            ])
+            .unwrap();
+        let (_, args) = matches.subcommand().unwrap();
+        assert!(args.get_one::<mf::Selection>("select").is_some());
+        let bad = ["mefikit", "extract", "a.vtu", "b.vtu", "--select", "dim =="];
+        assert!(cli().try_get_matches_from(bad).is_err());
+        let bad = [
+            "mefikit",
+            "convert",
+            "a.vtu",
+            "b.vtkhdf",
+            "--compression",
+            "10",
+        ];
+        assert!(cli().try_get_matches_from(bad).is_err());
+    }
+
+    #[test]
+    fn test_measure_stats() {
+        let stats = MeasureStats::new(&[1.0, 0.0, 2.0]);
+        assert_eq!((stats.count, stats.min, stats.max), (3, 0.0, 2.0));
+        assert_eq!((stats.mean, stats.non_positive), (1.0, 1));
+    }
+}
@@ -39,15 +39,15 @@ impl From<ElementIds> for PyElementIds {
 }
 
 impl PyElementIds {
-    pub fn from_dict<'py>(dict: &Bound<'py, PyDict>) -> Self {
+    pub fn from_dict<'py>(dict: &Bound<'py, PyDict>) -> PyResult<Self> {
         let mut eids = ElementIds::new();
         for (key, value) in dict.iter() {
-            let et_str: &str = key.extract().unwrap();
-            let et = str_to_etype(et_str);
-            let ids_array: np::PyReadonlyArray1<usize> = value.extract().unwrap();
+            let et_str: &str = key.extract()?;
+            let et = str_to_etype(et_str)?;
+            let ids_array: np::PyReadonlyArray1<usize> = value.extract()?;
             let ids = ids_array.as_array().to_vec();
             eids.add_block(et, ids);
         }
-        PyElementIds { inner: eids }
+        Ok(PyElementIds { inner: eids })
     }
 }
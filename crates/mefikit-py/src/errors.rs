@@ -0,0 +1,31 @@
+use pyo3::create_exception;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use mefikit::prelude as mf;
+
+create_exception!(
+    mefipy,
+    MefikitError,
+    PyValueError,
+    "Invalid input given to mefikit: unknown element type, invalid dimension, malformed \
+     selection, etc."
+);
+create_exception!(
+    mefipy,
+    MefikitIOError,
+    PyIOError,
+    "Failure to read or write a mesh file."
+);
+
+/// Converts a dimension given as an integer.
+pub fn to_dimension(dim: usize) -> PyResult<mf::Dimension> {
+    mf::Dimension::try_from(dim).map_err(|_| {
+        MefikitError::new_err(format!("Invalid dimension {dim}, expected 0, 1, 2 or 3"))
+    })
+}
+
+/// Converts a mesh format given by name, such as `"json"` or `"vtkhdf"`.
+pub fn to_format(name: &str) -> PyResult<mf::Format> {
+    name.parse().map_err(MefikitError::new_err)
+}
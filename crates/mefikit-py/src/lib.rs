@@ -2,6 +2,10 @@ use pyo3::prelude::*;
 
 mod element;
 mod element_ids;
+mod errors;
+mod medcoupling;
+mod pybuilders;
+mod pyelement;
 mod pyfield;
 mod pyumesh;
 mod select;
@@ -9,7 +13,19 @@ mod select;
 #[pymodule]
 mod sel {
     #[pymodule_export]
-    use super::select::{bbox, circle, ids, nbbox, ncircle, nids, nrect, nsphere, rect, sphere};
+    use super::select::{
+        bbox, circle, dimensions, family, group, ids, nbbox, ncircle, nids, not_family, not_group,
+        nrect, nsphere, parse, rect, sphere, types,
+    };
+}
+
+#[pymodule]
+mod builders {
+    #[pymodule_export]
+    use super::pybuilders::{
+        annulus, box_surface, circular_pattern, cylinder, cylinder_shell, disk, implicit,
+        linear_pattern, mirror, sphere, spherical_shell, voxels,
+    };
 }
 
 /// A Python module implemented in Rust. The name of this function must match
@@ -24,12 +40,26 @@ mod mefipy {
     #[pymodule_export]
     use super::sel;
 
+    #[pymodule_export]
+    use super::builders;
+
     #[pymodule_export]
     use super::pyumesh::PyUMesh;
 
     #[pymodule_export]
     use super::pyfield::PyField;
 
+    #[pymodule_export]
+    use super::pyelement::{PyElement, PyElementIterator};
+
+    #[pymodule_init]
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+        use super::errors::{MefikitError, MefikitIOError};
+        m.add("MefikitError", m.py().get_type::<MefikitError>())?;
+        m.add("MefikitIOError", m.py().get_type::<MefikitIOError>())?;
+        Ok(())
+    }
+
     #[pyfunction]
     #[pyo3(signature = (*args))]
     pub fn build_cmesh(args: &Bound<'_, PyTuple>) -> PyResult<PyUMesh> {
@@ -0,0 +1,106 @@
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+
+use mefikit::medcoupling::{McLevel, McMesh};
+
+use numpy::{self as np, PyReadonlyArray1, PyReadonlyArray2};
+
+/// Cells of one dimension, laid out as a `MEDCouplingUMesh`, converted to a dict.
+#[derive(IntoPyObject)]
+pub struct PyMcLevel<'py> {
+    mesh_dimension: usize,
+    connectivity: Bound<'py, np::PyArray1<i64>>,
+    connectivity_index: Bound<'py, np::PyArray1<i64>>,
+    families: Bound<'py, np::PyArray1<i64>>,
+    fields: BTreeMap<String, Bound<'py, np::PyArray2<f64>>>,
+}
+
+/// Arrays of a `MEDFileUMesh`, converted to a dict.
+#[derive(IntoPyObject)]
+pub struct PyMcMesh<'py> {
+    coords: Bound<'py, np::PyArray2<f64>>,
+    levels: Vec<PyMcLevel<'py>>,
+    node_families: Bound<'py, np::PyArray1<i64>>,
+    groups: BTreeMap<String, Vec<i64>>,
+}
+
+impl<'py> PyMcMesh<'py> {
+    pub fn new(py: Python<'py>, mesh: McMesh) -> Self {
+        let levels = mesh
+            .levels
+            .into_iter()
+            .map(|level| PyMcLevel {
+                mesh_dimension: level.mesh_dimension,
+                connectivity: np::PyArray1::from_vec(py, level.connectivity),
+                connectivity_index: np::PyArray1::from_vec(py, level.connectivity_index),
+                families: np::PyArray1::from_vec(py, level.families),
+                fields: level
+                    .fields
+                    .into_iter()
+                    .map(|(name, values)| (name, np::PyArray2::from_owned_array(py, values)))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            coords: np::PyArray2::from_owned_array(py, mesh.coords),
+            levels,
+            node_families: np::PyArray1::from_vec(py, mesh.node_families),
+            groups: mesh
+                .groups
+                .into_iter()
+                .map(|(name, ids)| (name, ids.into_iter().collect()))
+                .collect(),
+        }
+    }
+}
+
+/// Cells of one dimension given as a dict, with the keys of [`PyMcLevel`].
+#[derive(FromPyObject)]
+#[pyo3(from_item_all)]
+pub struct PyMcLevelInput<'py> {
+    mesh_dimension: usize,
+    connectivity: PyReadonlyArray1<'py, i64>,
+    connectivity_index: PyReadonlyArray1<'py, i64>,
+    families: PyReadonlyArray1<'py, i64>,
+    fields: BTreeMap<String, PyReadonlyArray2<'py, f64>>,
+}
+
+/// Arrays of a `MEDFileUMesh` given as a dict, with the keys of [`PyMcMesh`].
+#[derive(FromPyObject)]
+#[pyo3(from_item_all)]
+pub struct PyMcMeshInput<'py> {
+    coords: PyReadonlyArray2<'py, f64>,
+    levels: Vec<PyMcLevelInput<'py>>,
+    node_families: PyReadonlyArray1<'py, i64>,
+    groups: BTreeMap<String, Vec<i64>>,
+}
+
+impl From<PyMcMeshInput<'_>> for McMesh {
+    fn from(mesh: PyMcMeshInput<'_>) -> Self {
+        let levels = mesh
+            .levels
+            .into_iter()
+            .map(|level| McLevel {
+                mesh_dimension: level.mesh_dimension,
+                connectivity: level.connectivity.as_array().to_vec(),
+                connectivity_index: level.connectivity_index.as_array().to_vec(),
+                families: level.families.as_array().to_vec(),
+                fields: level
+                    .fields
+                    .into_iter()
+                    .map(|(name, values)| (name, values.as_array().to_owned()))
+                    .collect(),
+            })
+            .collect();
+        McMesh {
+            coords: mesh.coords.as_array().to_owned(),
+            levels,
+            node_families: mesh.node_families.as_array().to_vec(),
+            groups: mesh
+                .groups
+                .into_iter()
+                .map(|(name, ids)| (name, ids.into_iter().collect()))
+                .collect(),
+        }
+    }
+}
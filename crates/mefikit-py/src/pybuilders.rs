@@ -0,0 +1,225 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use numpy as np;
+use pyo3::prelude::*;
+
+use mefikit::builders::{self as mb, pattern, primitives};
+
+use crate::errors::MefikitError;
+use crate::pyumesh::PyUMesh;
+
+/// Checks a number of cells given to a builder, which would otherwise panic on it.
+fn check_count(name: &str, value: usize, min: usize) -> PyResult<()> {
+    match value >= min {
+        true => Ok(()),
+        false => Err(MefikitError::new_err(format!(
+            "{name} must be at least {min}, got {value}"
+        ))),
+    }
+}
+
+/// Checks the number of cells around a disk, which must be a multiple of 4 for its O-grid.
+fn check_disk(n_circ: usize, n_radial: usize) -> PyResult<()> {
+    if !(n_circ >= 4 && n_circ.is_multiple_of(4)) {
+        return Err(MefikitError::new_err(format!(
+            "n_circ must be a positive multiple of 4, got {n_circ}"
+        )));
+    }
+    check_count("n_radial", n_radial, 1)
+}
+
+/// Structured quadrangle disk centered at the origin, with its boundary in group `outer`.
+#[pyfunction]
+#[pyo3(signature = (radius, n_circ=16, n_radial=4))]
+pub fn disk(radius: f64, n_circ: usize, n_radial: usize) -> PyResult<PyUMesh> {
+    check_disk(n_circ, n_radial)?;
+    Ok(primitives::disk(radius, n_circ, n_radial).into())
+}
+
+/// Structured quadrangle annulus centered at the origin, with boundaries `inner` and `outer`.
+#[pyfunction]
+#[pyo3(signature = (r_in, r_out, n_circ=16, n_radial=4))]
+pub fn annulus(r_in: f64, r_out: f64, n_circ: usize, n_radial: usize) -> PyResult<PyUMesh> {
+    check_count("n_circ", n_circ, 3)?;
+    check_count("n_radial", n_radial, 1)?;
+    Ok(primitives::annulus(r_in, r_out, n_circ, n_radial).into())
+}
+
+/// Quadrangle lateral surface of a cylinder of axis z, with boundaries `bottom` and `top`.
+#[pyfunction]
+#[pyo3(signature = (radius, height, n_circ=16, n_height=4))]
+pub fn cylinder_shell(
+    radius: f64,
+    height: f64,
+    n_circ: usize,
+    n_height: usize,
+) -> PyResult<PyUMesh> {
+    check_count("n_circ", n_circ, 3)?;
+    check_count("n_height", n_height, 1)?;
+    Ok(primitives::cylinder_shell(radius, height, n_circ, n_height).into())
+}
+
+/// Solid hexahedral cylinder of axis z, with boundaries `bottom`, `top` and `outer`.
+#[pyfunction]
+#[pyo3(signature = (radius, height, n_circ=16, n_radial=4, n_height=4))]
+pub fn cylinder(
+    radius: f64,
+    height: f64,
+    n_circ: usize,
+    n_radial: usize,
+    n_height: usize,
+) -> PyResult<PyUMesh> {
+    check_disk(n_circ, n_radial)?;
+    check_count("n_height", n_height, 1)?;
+    Ok(primitives::cylinder(radius, height, n_circ, n_radial, n_height).into())
+}
+
+/// Triangulated sphere centered at the origin, from a subdivided icosahedron.
+#[pyfunction]
+#[pyo3(signature = (radius, subdivisions=2))]
+pub fn sphere(radius: f64, subdivisions: usize) -> PyResult<PyUMesh> {
+    Ok(primitives::icosphere(radius, subdivisions).into())
+}
+
+/// Hexahedral spherical shell centered at the origin, with boundaries `inner` and `outer`.
+#[pyfunction]
+#[pyo3(signature = (r_in, r_out, n=4, n_layers=2))]
+pub fn spherical_shell(r_in: f64, r_out: f64, n: usize, n_layers: usize) -> PyResult<PyUMesh> {
+    check_count("n", n, 1)?;
+    check_count("n_layers", n_layers, 1)?;
+    Ok(primitives::spherical_shell(r_in, r_out, n, n_layers).into())
+}
+
+/// Quadrangle surface of an axis aligned box, one group per side (`xmin`, `xmax`, etc.).
+#[pyfunction]
+#[pyo3(signature = (min, max, n=[1, 1, 1]))]
+pub fn box_surface(min: [f64; 3], max: [f64; 3], n: [usize; 3]) -> PyResult<PyUMesh> {
+    for k in n {
+        check_count("n", k, 1)?;
+    }
+    Ok(primitives::box_surface(min, max, n).into())
+}
+
+/// Voxel values, as labels or intensities.
+#[derive(FromPyObject)]
+pub enum PyVoxels<'py> {
+    Labels(np::PyReadonlyArray3<'py, u8>),
+    Values(np::PyReadonlyArray3<'py, f64>),
+}
+
+/// HEX8 mesh of the voxels of a 3D image whose value is at least `threshold`.
+///
+/// Voxel values are stored in the cell field `value`.
+#[pyfunction]
+#[pyo3(signature = (image, threshold, spacing=[1.0, 1.0, 1.0], origin=[0.0, 0.0, 0.0]))]
+pub fn voxels(
+    image: PyVoxels<'_>,
+    threshold: f64,
+    spacing: [f64; 3],
+    origin: [f64; 3],
+) -> PyResult<PyUMesh> {
+    let mesh = match image {
+        PyVoxels::Labels(image) => mb::from_voxels(image.as_array(), spacing, origin, threshold),
+        PyVoxels::Values(image) => mb::from_voxels(image.as_array(), spacing, origin, threshold),
+    };
+    Ok(mesh.into())
+}
+
+/// Triangle surface of the zero level set of `f(x, y, z)`, or the tetrahedral mesh of the
+/// region where it is negative with `solid=True`.
+///
+/// `f` is sampled on a grid of `resolution` cells spanning `bbox`, given as `(min, max)`.
+#[pyfunction]
+#[pyo3(signature = (f, bbox, resolution=[20, 20, 20], solid=false))]
+pub fn implicit(
+    f: Bound<'_, PyAny>,
+    bbox: [[f64; 3]; 2],
+    resolution: [usize; 3],
+    solid: bool,
+) -> PyResult<PyUMesh> {
+    for k in resolution {
+        check_count("resolution", k, 1)?;
+    }
+    let error: RefCell<Option<PyErr>> = RefCell::new(None);
+    let eval = |p: &[f64; 3]| {
+        if error.borrow().is_some() {
+            return 1.0;
+        }
+        match f.call1((p[0], p[1], p[2])).and_then(|v| v.extract::<f64>()) {
+            Ok(v) => v,
+            Err(e) => {
+                error.replace(Some(e));
+                1.0
+            }
+        }
+    };
+    let mesh = match solid {
+        true => mb::from_implicit_solid(eval, bbox, resolution),
+        false => mb::from_implicit(eval, bbox, resolution),
+    };
+    match error.into_inner() {
+        Some(e) => Err(e),
+        None => Ok(mesh.into()),
+    }
+}
+
+/// Replicates a mesh `n` times along `direction`, merging the nodes closer than `merge_eps`.
+#[pyfunction]
+#[pyo3(signature = (mesh, direction, n, merge_eps=None))]
+pub fn linear_pattern(
+    mesh: &PyUMesh,
+    direction: Vec<f64>,
+    n: usize,
+    merge_eps: Option<f64>,
+) -> PyResult<PyUMesh> {
+    pattern::linear(mesh.inner(), &direction, n, merge_eps)
+        .map(PyUMesh::from)
+        .map_err(MefikitError::new_err)
+}
+
+/// Converts the kinds of the transformed fields, given by name such as `"vector"` or `"tensor"`.
+fn to_field_kinds(
+    fields: &Option<BTreeMap<String, String>>,
+) -> PyResult<Vec<(&str, pattern::FieldKind)>> {
+    fields
+        .iter()
+        .flatten()
+        .map(|(name, kind)| Ok((name.as_str(), kind.parse().map_err(MefikitError::new_err)?)))
+        .collect()
+}
+
+/// Replicates a mesh `n` times over a full turn around the axis through `origin`, rotating the
+/// vector and tensor `fields`, given as a mapping of field names to kinds.
+#[pyfunction]
+#[pyo3(signature = (mesh, origin, axis, n, merge_eps=None, fields=None))]
+pub fn circular_pattern(
+    mesh: &PyUMesh,
+    origin: Vec<f64>,
+    axis: [f64; 3],
+    n: usize,
+    merge_eps: Option<f64>,
+    fields: Option<BTreeMap<String, String>>,
+) -> PyResult<PyUMesh> {
+    let fields = to_field_kinds(&fields)?;
+    pattern::circular(mesh.inner(), &origin, axis, n, &fields, merge_eps)
+        .map(PyUMesh::from)
+        .map_err(MefikitError::new_err)
+}
+
+/// Appends the mirror image of a mesh through the plane of `origin` and `normal`, mirroring the
+/// vector and tensor `fields`, given as a mapping of field names to kinds.
+#[pyfunction]
+#[pyo3(signature = (mesh, origin, normal, merge_eps=None, fields=None))]
+pub fn mirror(
+    mesh: &PyUMesh,
+    origin: Vec<f64>,
+    normal: Vec<f64>,
+    merge_eps: Option<f64>,
+    fields: Option<BTreeMap<String, String>>,
+) -> PyResult<PyUMesh> {
+    let fields = to_field_kinds(&fields)?;
+    pattern::mirror(mesh.inner(), &origin, &normal, &fields, merge_eps)
+        .map(PyUMesh::from)
+        .map_err(MefikitError::new_err)
+}
@@ -0,0 +1,153 @@
+use numpy as np;
+use numpy::ndarray as nd;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+
+use mefikit::prelude::{self as mf, ElementGeo, ElementLike};
+
+use super::element::etype_to_str;
+use crate::pyumesh::PyUMesh;
+
+/// An element of a mesh, read from the mesh on each access.
+///
+/// It only holds the mesh and the element id, so accessing it fails with an `IndexError` if the
+/// element was removed from the mesh in the meantime.
+#[pyclass(frozen)]
+#[pyo3(name = "Element")]
+pub struct PyElement {
+    mesh: Py<PyUMesh>,
+    id: mf::ElementId,
+}
+
+impl PyElement {
+    /// Returns the element of a mesh, or an `IndexError` if it does not exist.
+    pub fn new(mesh: &Bound<'_, PyUMesh>, id: mf::ElementId) -> PyResult<Self> {
+        check_exists(mesh.borrow().inner(), id)?;
+        Ok(Self {
+            mesh: mesh.clone().unbind(),
+            id,
+        })
+    }
+
+    fn with_element<R>(&self, py: Python<'_>, f: impl FnOnce(&mf::Element) -> R) -> PyResult<R> {
+        let mesh = self.mesh.bind(py).borrow();
+        let mesh = mesh.inner();
+        check_exists(mesh, self.id)?;
+        Ok(f(&mesh.element(self.id)))
+    }
+}
+
+fn check_exists(mesh: &mf::UMesh, id: mf::ElementId) -> PyResult<()> {
+    let len = mesh.block(id.element_type()).map_or(0, |b| b.len());
+    if id.index() >= len {
+        return Err(PyIndexError::new_err(format!(
+            "No {} element {} in the mesh",
+            etype_to_str(id.element_type()),
+            id.index()
+        )));
+    }
+    Ok(())
+}
+
+#[pymethods]
+impl PyElement {
+    #[getter]
+    fn element_type(&self) -> String {
+        etype_to_str(self.id.element_type())
+    }
+
+    #[getter]
+    fn index(&self) -> usize {
+        self.id.index()
+    }
+
+    /// Node indices of the element. Faces of polyhedra are separated by the maximum integer.
+    #[getter]
+    fn connectivity<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, np::PyArray1<usize>>> {
+        let connectivity = self.with_element(py, |e| e.connectivity.to_vec())?;
+        Ok(np::PyArray1::from_vec(py, connectivity))
+    }
+
+    /// Coordinates of the nodes of the element, one row per node.
+    #[getter]
+    fn coords<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, np::PyArray2<f64>>> {
+        let coords = self.with_element(py, |e| {
+            let nodes: Vec<&[f64]> = e
+                .connectivity
+                .iter()
+                .enumerate()
+                .filter(|&(_, &n)| n != usize::MAX)
+                .map(|(i, _)| e.coord(i))
+                .collect();
+            nd::Array2::from_shape_fn((nodes.len(), e.space_dimension()), |(i, j)| nodes[i][j])
+        })?;
+        Ok(np::PyArray2::from_owned_array(py, coords))
+    }
+
+    #[getter]
+    fn groups(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        self.with_element(py, |e| e.groups().clone())
+    }
+
+    #[getter]
+    fn family(&self, py: Python<'_>) -> PyResult<usize> {
+        self.with_element(py, |e| *e.family)
+    }
+
+    /// Mean of the nodes of the element, in the space dimension of the mesh.
+    fn centroid<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, np::PyArray1<f64>>> {
+        let centroid = self.with_element(py, |e| e.node_mean()[..e.space_dimension()].to_vec())?;
+        Ok(np::PyArray1::from_vec(py, centroid))
+    }
+
+    /// Length, area or volume of the element, depending on the space dimension.
+    fn measure(&self, py: Python<'_>) -> PyResult<f64> {
+        self.with_element(py, |e| match e.space_dimension() {
+            1 => e.measure1(),
+            2 => e.measure2(),
+            3 => e.measure3(),
+            _ => 0.0,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Element({}, {})", self.element_type(), self.index())
+    }
+}
+
+/// Iterator over the elements of a mesh, by element type.
+#[pyclass]
+#[pyo3(name = "ElementIterator")]
+pub struct PyElementIterator {
+    mesh: Py<PyUMesh>,
+    ids: std::vec::IntoIter<mf::ElementId>,
+}
+
+impl PyElementIterator {
+    pub fn new(mesh: &Bound<'_, PyUMesh>) -> Self {
+        let ids: Vec<mf::ElementId> = mesh
+            .borrow()
+            .inner()
+            .blocks()
+            .flat_map(|(&et, block)| (0..block.len()).map(move |i| mf::ElementId::new(et, i)))
+            .collect();
+        Self {
+            mesh: mesh.clone().unbind(),
+            ids: ids.into_iter(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyElementIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyElement>> {
+        self.ids
+            .next()
+            .map(|id| PyElement::new(self.mesh.bind(py), id))
+            .transpose()
+    }
+}
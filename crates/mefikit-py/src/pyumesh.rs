@@ -1,14 +1,18 @@
+use pyo3::exceptions::PyKeyError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{Display, Formatter},
 };
 
 use mefikit::{
+    medcoupling::McMesh,
     prelude as mf,
     tools::{
         Descendable, Measurable, MeshSelect, NodeDuplicates,
         fieldexpr::{MeshEvalUpdatable, MeshEvaluable},
+        intersect,
     },
 };
 
@@ -18,7 +22,34 @@ use numpy::ndarray as nd;
 use numpy::{self as np, PyReadonlyArray2};
 
 use super::element::{etype_to_str, str_to_etype};
-use crate::{pyfield::PyField, select::PySelection};
+use crate::element_ids::PyElementIds;
+use crate::errors::{MefikitError, MefikitIOError, to_dimension, to_format};
+use crate::medcoupling::{PyMcMesh, PyMcMeshInput};
+use crate::pyelement::{PyElement, PyElementIterator};
+use crate::{pyfield::PyField, select::PySelectionInput};
+
+/// Parent cells of the cells of a new mesh, per element type, as `(type, index)` pairs.
+type PyParents = BTreeMap<String, Vec<(String, usize)>>;
+
+/// Arrays of a field per element type.
+type PyFieldArrays<'py> = BTreeMap<String, Bound<'py, np::PyArray<f64, nd::IxDyn>>>;
+
+/// Pairs of overlapping cells, as `(type, index)` pairs, with the measure of their overlap.
+type PyOverlaps = Vec<((String, usize), (String, usize), f64)>;
+
+fn element_id_to_py(id: mf::ElementId) -> (String, usize) {
+    (etype_to_str(id.element_type()), id.index())
+}
+
+fn parents_to_py(parents: BTreeMap<mf::ElementType, Vec<mf::ElementId>>) -> PyParents {
+    parents
+        .into_iter()
+        .map(|(et, ids)| {
+            let ids = ids.into_iter().map(element_id_to_py).collect();
+            (etype_to_str(et), ids)
+        })
+        .collect()
+}
 
 #[pyclass(str)]
 #[pyo3(name = "UMesh")]
@@ -27,6 +58,58 @@ pub struct PyUMesh {
     inner: mf::UMesh,
 }
 
+#[derive(IntoPyObject)]
+enum PyFieldData<'py> {
+    Int(Bound<'py, np::PyArray<i64, nd::IxDyn>>),
+    Bool(Bound<'py, np::PyArray<bool, nd::IxDyn>>),
+    Categorical(Vec<String>),
+}
+
+#[derive(FromPyObject)]
+enum PyFieldDataInput<'py> {
+    Bool(np::PyReadonlyArray<'py, bool, nd::IxDyn>),
+    Int(np::PyReadonlyArray<'py, i64, nd::IxDyn>),
+    Categorical(Vec<String>),
+}
+
+impl From<PyFieldDataInput<'_>> for mf::FieldData {
+    fn from(data: PyFieldDataInput<'_>) -> Self {
+        match data {
+            PyFieldDataInput::Bool(arr) => mf::FieldData::Bool(arr.as_array().to_shared()),
+            PyFieldDataInput::Int(arr) => mf::FieldData::Int(arr.as_array().to_shared()),
+            PyFieldDataInput::Categorical(labels) => mf::FieldData::categorical(&labels),
+        }
+    }
+}
+
+/// Keeps alive the Rust memory of a field viewed by numpy arrays.
+#[pyclass(frozen)]
+struct FieldBuffer {
+    data: nd::ArcArray<f64, nd::IxDyn>,
+}
+
+/// Returns a numpy array of a field, a copy or a read-only view of the Rust memory.
+///
+/// The view owns a reference to the field, so it stays valid after the mesh is modified or
+/// dropped. Mesh arrays are copied on write when shared, hence the view never changes, but numpy
+/// must not write into it either.
+fn field_to_numpy<'py>(
+    py: Python<'py>,
+    data: &nd::ArcArray<f64, nd::IxDyn>,
+    copy: bool,
+) -> PyResult<Bound<'py, np::PyArray<f64, nd::IxDyn>>> {
+    if copy {
+        return Ok(np::PyArray::from_array(py, data));
+    }
+    let buffer = Bound::new(py, FieldBuffer { data: data.clone() })?;
+    // SAFETY: the buffer owns the memory for as long as the array lives, and no one writes into
+    // it: the mesh copies shared arrays before mutating them, and the array is made read-only.
+    let owner = buffer.clone().into_any();
+    let array = unsafe { np::PyArray::borrow_from_array(&buffer.get().data, owner) };
+    array.call_method1("setflags", (false,))?;
+    Ok(array)
+}
+
 #[derive(IntoPyObject)]
 enum PyConnectivity<'py> {
     Regular(Bound<'py, np::PyArray2<usize>>),
@@ -74,35 +157,221 @@ impl PyUMesh {
             .collect()
     }
 
+    /// Returns an element of the mesh, to inspect it.
+    fn element(slf: &Bound<'_, Self>, et: &str, index: usize) -> PyResult<PyElement> {
+        PyElement::new(slf, mf::ElementId::new(str_to_etype(et)?, index))
+    }
+
+    /// Iterates over all the elements of the mesh, by element type.
+    fn __iter__(slf: &Bound<'_, Self>) -> PyElementIterator {
+        PyElementIterator::new(slf)
+    }
+
+    /// Returns the node groups of the mesh, as sorted arrays of node indices.
+    fn node_groups<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> BTreeMap<String, Bound<'py, np::PyArray1<usize>>> {
+        self.inner
+            .node_group_names()
+            .map(|name| {
+                let nodes = self.inner.node_group(name).unwrap().iter().copied();
+                (name.to_owned(), np::PyArray1::from_iter(py, nodes))
+            })
+            .collect()
+    }
+
+    /// Inserts or replaces a node group.
+    fn set_node_group(&mut self, name: &str, nodes: Vec<usize>) -> PyResult<()> {
+        let n_nodes = self.inner.coords().nrows();
+        if let Some(&n) = nodes.iter().find(|&&n| n >= n_nodes) {
+            return Err(MefikitError::new_err(format!(
+                "Node group {name} has node {n} out of the mesh"
+            )));
+        }
+        self.inner.set_node_group(name, nodes);
+        Ok(())
+    }
+
+    /// Returns the fields of the mesh, per name and element type.
+    ///
+    /// Arrays are read-only views of the mesh memory, or writeable copies with `copy=True`.
+    #[pyo3(signature = (copy=false))]
     fn fields<'py>(
         &self,
         py: Python<'py>,
-    ) -> BTreeMap<String, BTreeMap<String, Bound<'py, np::PyArray<f64, nd::IxDyn>>>> {
+        copy: bool,
+    ) -> PyResult<BTreeMap<String, PyFieldArrays<'py>>> {
         self.inner
             .fields()
             .map(|(field_name, field)| {
-                (
-                    field_name,
-                    field
-                        .0
-                        .iter()
-                        .map(|(&et, block)| {
-                            let et = etype_to_str(et);
-                            let arr = np::PyArray::from_array(py, block);
-                            (et, arr)
-                        })
-                        .collect(),
-                )
+                let field = field
+                    .0
+                    .keys()
+                    .map(|&et| {
+                        let data = &self.inner.block(et).unwrap().fields[&field_name];
+                        Ok((etype_to_str(et), field_to_numpy(py, data, copy)?))
+                    })
+                    .collect::<PyResult<_>>()?;
+                Ok((field_name, field))
             })
             .collect()
     }
 
-    fn to_json(&self) -> String {
-        serde_json::to_string(&self.inner).unwrap()
+    /// Returns a field per element type, or `None` if it is not defined on all the elements of
+    /// the dimension (the highest one by default).
+    ///
+    /// Arrays are read-only views of the mesh memory, or writeable copies with `copy=True`.
+    #[pyo3(signature = (name, dim=None, copy=false))]
+    fn field<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        dim: Option<usize>,
+        copy: bool,
+    ) -> PyResult<Option<PyFieldArrays<'py>>> {
+        let dim = dim.map(to_dimension).transpose()?;
+        let Some(field) = self.inner.field(name, dim) else {
+            return Ok(None);
+        };
+        field
+            .0
+            .keys()
+            .map(|&et| {
+                let data = &self.inner.block(et).unwrap().fields[name];
+                Ok((etype_to_str(et), field_to_numpy(py, data, copy)?))
+            })
+            .collect::<PyResult<_>>()
+            .map(Some)
     }
 
-    fn to_json_pretty(&self) -> String {
-        serde_json::to_string_pretty(&self.inner).unwrap()
+    /// Adds or replaces a field on the block of an element type.
+    ///
+    /// Values are copied, their first axis must match the number of elements of the block.
+    fn set_field(
+        &mut self,
+        name: &str,
+        et: &str,
+        values: np::PyReadonlyArray<'_, f64, nd::IxDyn>,
+    ) -> PyResult<()> {
+        let et = str_to_etype(et)?;
+        let Some(block) = self.inner.block(et) else {
+            return Err(PyKeyError::new_err(format!(
+                "No {} block",
+                etype_to_str(et)
+            )));
+        };
+        let values = values.as_array();
+        if values.shape().first() != Some(&block.len()) {
+            return Err(MefikitError::new_err(format!(
+                "Field {name} has shape {:?} but there are {} {} elements",
+                values.shape(),
+                block.len(),
+                etype_to_str(et)
+            )));
+        }
+        self.inner.add_field(et, name, values.to_shared());
+        Ok(())
+    }
+
+    /// Returns the integer, boolean and categorical fields of the mesh.
+    ///
+    /// Categorical fields are returned as a list of labels (one per element). Blocks of the
+    /// dimension of a field that do not hold it are left out.
+    fn typed_fields<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> BTreeMap<String, BTreeMap<String, PyFieldData<'py>>> {
+        self.inner
+            .typed_field_names()
+            .into_iter()
+            .map(|(field_name, dim)| {
+                let field = self
+                    .inner
+                    .blocks()
+                    .filter(|(et, _)| et.dimension() == dim)
+                    .filter_map(|(et, b)| b.typed_fields.get(&field_name).map(|f| (*et, f)))
+                    .map(|(et, data)| {
+                        let data = match data {
+                            mf::FieldData::Int(arr) => {
+                                PyFieldData::Int(np::PyArray::from_array(py, arr))
+                            }
+                            mf::FieldData::Bool(arr) => {
+                                PyFieldData::Bool(np::PyArray::from_array(py, arr))
+                            }
+                            mf::FieldData::Categorical { .. } => PyFieldData::Categorical(
+                                data.labels()
+                                    .unwrap()
+                                    .into_iter()
+                                    .map(str::to_owned)
+                                    .collect(),
+                            ),
+                        };
+                        (etype_to_str(et), data)
+                    })
+                    .collect();
+                (field_name, field)
+            })
+            .collect()
+    }
+
+    /// Adds or replaces an integer, boolean or categorical field.
+    ///
+    /// Values are given per element type, as int64 or bool numpy arrays or as a list of labels.
+    /// They must be given, all of the same kind, for every block of one dimension.
+    fn update_typed_field(
+        &mut self,
+        name: &str,
+        field: BTreeMap<String, PyFieldDataInput<'_>>,
+    ) -> PyResult<()> {
+        let field: BTreeMap<mf::ElementType, mf::FieldData> = field
+            .into_iter()
+            .map(|(et, data)| Ok((str_to_etype(&et)?, data.into())))
+            .collect::<PyResult<_>>()?;
+        let dims: BTreeSet<mf::Dimension> = field.keys().map(|et| et.dimension()).collect();
+        let &[&dim] = dims.iter().collect::<Vec<_>>().as_slice() else {
+            return Err(MefikitError::new_err(format!(
+                "Field {name} must be given on element types of one dimension"
+            )));
+        };
+        let kinds: BTreeSet<&str> = field.values().map(mf::FieldData::kind).collect();
+        if kinds.len() > 1 {
+            return Err(MefikitError::new_err(format!(
+                "Field {name} mixes {kinds:?} values"
+            )));
+        }
+        if let Some(&et) = field.keys().find(|&&et| self.inner.block(et).is_none()) {
+            return Err(MefikitError::new_err(format!(
+                "No {} block for field {name}",
+                etype_to_str(et)
+            )));
+        }
+        for (&et, block) in self.inner.blocks().filter(|(et, _)| et.dimension() == dim) {
+            let Some(data) = field.get(&et) else {
+                return Err(MefikitError::new_err(format!(
+                    "Field {name} is missing the {} block",
+                    etype_to_str(et)
+                )));
+            };
+            if data.len() != block.len() {
+                return Err(MefikitError::new_err(format!(
+                    "Field {name} has {} values but there are {} {} elements",
+                    data.len(),
+                    block.len(),
+                    etype_to_str(et)
+                )));
+            }
+        }
+        self.inner.update_typed_field(name, field, Some(dim));
+        Ok(())
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| MefikitError::new_err(e.to_string()))
+    }
+
+    fn to_json_pretty(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.inner).map_err(|e| MefikitError::new_err(e.to_string()))
     }
 
     /// Add a regular block of elements to the mesh.
@@ -112,33 +381,163 @@ impl PyUMesh {
         et: &str,
         block: np::PyReadonlyArray2<'_, usize>,
         fields: Option<BTreeMap<String, np::PyReadonlyArray<'_, f64, nd::IxDyn>>>,
-    ) {
+    ) -> PyResult<()> {
+        let et = str_to_etype(et)?;
         let fields = fields.map(|f| {
             f.iter()
                 .map(|(n, f)| (n.to_owned(), f.as_array().to_shared()))
                 .collect()
         });
         self.inner
-            .add_regular_block(str_to_etype(et), block.as_array().to_shared(), fields);
+            .add_regular_block(et, block.as_array().to_shared(), fields);
+        Ok(())
     }
 
+    /// Add a block of PGON or PHED elements to the mesh.
+    ///
+    /// The connectivities of the elements are concatenated in `data`, and `offsets` holds the
+    /// end of each one in `data`. Faces of PHED elements are separated by the maximum integer.
+    fn add_poly_block(
+        &mut self,
+        et: &str,
+        data: np::PyReadonlyArray1<'_, usize>,
+        offsets: np::PyReadonlyArray1<'_, usize>,
+    ) -> PyResult<()> {
+        let et = str_to_etype(et)?;
+        if et.regularity() != mf::Regularity::Poly {
+            return Err(MefikitError::new_err(format!(
+                "{} is not a poly element type, use add_regular_block",
+                etype_to_str(et)
+            )));
+        }
+        if self.inner.block(et).is_some() {
+            return Err(MefikitError::new_err(format!(
+                "The mesh already has a {} block",
+                etype_to_str(et)
+            )));
+        }
+        let (data, offsets) = (data.as_array(), offsets.as_array());
+        if !offsets.iter().is_sorted() || offsets.last().is_some_and(|&o| o != data.len()) {
+            return Err(MefikitError::new_err(
+                "Offsets must be sorted and end at the length of the data",
+            ));
+        }
+        self.check_nodes(data.iter())?;
+        self.inner
+            .add_poly_block(et, data.to_shared(), offsets.to_shared());
+        Ok(())
+    }
+
+    /// Adds a single element to the mesh, creating its block if needed.
+    ///
+    /// Returns the element type and the index of the new element.
+    #[pyo3(signature = (et, connectivity, family=None))]
+    fn add_element(
+        &mut self,
+        et: &str,
+        connectivity: Vec<usize>,
+        family: Option<usize>,
+    ) -> PyResult<(String, usize)> {
+        let et = str_to_etype(et)?;
+        if let Some(n) = et.num_nodes()
+            && connectivity.len() != n
+        {
+            return Err(MefikitError::new_err(format!(
+                "{} elements have {n} nodes, got {}",
+                etype_to_str(et),
+                connectivity.len()
+            )));
+        }
+        if self.inner.block(et).is_some_and(|b| !b.fields.is_empty()) {
+            return Err(MefikitError::new_err(format!(
+                "Cannot add an element to the {} block, which has fields",
+                etype_to_str(et)
+            )));
+        }
+        self.check_nodes(connectivity.iter())?;
+        let id = self.inner.add_element(et, &connectivity, family, None);
+        Ok(element_id_to_py(id))
+    }
+
+    /// Reads a mesh file, in the format of its extension unless `format` is given.
+    ///
+    /// `fields` restricts the fields read, and `time_step` selects a step of a transient
+    /// VTKHDF file.
     #[staticmethod]
-    fn read(path: &str) -> Self {
-        let path = Path::new(path);
-        mf::read(path).unwrap().into()
+    #[pyo3(signature = (path, format=None, fields=None, time_step=None))]
+    fn read(
+        path: &str,
+        format: Option<&str>,
+        fields: Option<Vec<String>>,
+        time_step: Option<usize>,
+    ) -> PyResult<Self> {
+        let mut options = mf::ReadOptions::new();
+        if let Some(format) = format {
+            options = options.format(to_format(format)?);
+        }
+        if let Some(fields) = fields {
+            options = options.fields(fields);
+        }
+        if let Some(step) = time_step {
+            options = options.time_step(step);
+        }
+        mf::read_with(Path::new(path), &options)
+            .map(Self::from)
+            .map_err(|e| MefikitIOError::new_err(format!("Could not read {path}: {e}")))
+    }
+
+    /// Writes the mesh, in the format of the file extension unless `format` is given.
+    ///
+    /// `fields` restricts the fields written, and `compression` is a deflate level from 0 to
+    /// 9, only for VTKHDF files.
+    #[pyo3(signature = (path, format=None, compression=None, fields=None))]
+    fn write(
+        &self,
+        path: &str,
+        format: Option<&str>,
+        compression: Option<u8>,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let mut options = mf::WriteOptions::new();
+        if let Some(format) = format {
+            options = options.format(to_format(format)?);
+        }
+        if let Some(level) = compression {
+            if level > 9 {
+                return Err(MefikitError::new_err(format!(
+                    "Invalid compression level {level}, expected 0 to 9"
+                )));
+            }
+            options = options.compression(level);
+        }
+        if let Some(fields) = fields {
+            options = options.fields(fields);
+        }
+        mf::write_with(Path::new(path), self.inner.view(), &options)
+            .map_err(|e| MefikitIOError::new_err(format!("Could not write {path}: {e}")))
     }
 
-    fn write(&self, path: &str) {
-        let path = Path::new(path);
-        let mesh = self.inner.view();
-        let _ = mf::write(path, mesh);
+    /// Returns the arrays of the MEDCoupling layout of the mesh, as a dict: `coords`, `levels`
+    /// (one dict per dimension, the highest first), `node_families` and `groups`.
+    fn to_mc_arrays<'py>(&self, py: Python<'py>) -> PyResult<PyMcMesh<'py>> {
+        let mesh = McMesh::from_umesh(&self.inner).map_err(MefikitError::new_err)?;
+        Ok(PyMcMesh::new(py, mesh))
+    }
+
+    /// Builds a mesh from arrays of the MEDCoupling layout, as returned by `to_mc_arrays`.
+    #[staticmethod]
+    fn from_mc_arrays(arrays: PyMcMeshInput<'_>) -> PyResult<Self> {
+        McMesh::from(arrays)
+            .to_umesh()
+            .map(Self::from)
+            .map_err(MefikitError::new_err)
     }
 
     #[pyo3(signature = (src_dim=None, target_dim=None))]
-    fn descend(&self, src_dim: Option<usize>, target_dim: Option<usize>) -> Self {
-        let src_dim = src_dim.map(|i| i.try_into().unwrap());
-        let target_dim = target_dim.map(|i| i.try_into().unwrap());
-        self.inner.descend(src_dim, target_dim).into()
+    fn descend(&self, src_dim: Option<usize>, target_dim: Option<usize>) -> PyResult<Self> {
+        let src_dim = src_dim.map(to_dimension).transpose()?;
+        let target_dim = target_dim.map(to_dimension).transpose()?;
+        Ok(self.inner.descend(src_dim, target_dim).into())
     }
 
     #[pyo3(signature = (src_dim=None, target_dim=None))]
@@ -146,19 +545,20 @@ impl PyUMesh {
         &mut self,
         src_dim: Option<usize>,
         target_dim: Option<usize>,
-    ) -> Option<Self> {
-        let src_dim = src_dim.map(|i| i.try_into().unwrap());
-        let target_dim = target_dim.map(|i| i.try_into().unwrap());
-        self.inner
+    ) -> PyResult<Option<Self>> {
+        let src_dim = src_dim.map(to_dimension).transpose()?;
+        let target_dim = target_dim.map(to_dimension).transpose()?;
+        Ok(self
+            .inner
             .descend_update(src_dim, target_dim)
-            .map(|m| m.into())
+            .map(|m| m.into()))
     }
 
     #[pyo3(signature = (src_dim=None, target_dim=None))]
-    fn boundaries(&self, src_dim: Option<usize>, target_dim: Option<usize>) -> Self {
-        let src_dim = src_dim.map(|i| i.try_into().unwrap());
-        let target_dim = target_dim.map(|i| i.try_into().unwrap());
-        self.inner.boundaries(src_dim, target_dim).into()
+    fn boundaries(&self, src_dim: Option<usize>, target_dim: Option<usize>) -> PyResult<Self> {
+        let src_dim = src_dim.map(to_dimension).transpose()?;
+        let target_dim = target_dim.map(to_dimension).transpose()?;
+        Ok(self.inner.boundaries(src_dim, target_dim).into())
     }
 
     #[pyo3(signature = (src_dim=None, target_dim=None))]
@@ -166,12 +566,13 @@ impl PyUMesh {
         &mut self,
         src_dim: Option<usize>,
         target_dim: Option<usize>,
-    ) -> Option<Self> {
-        let src_dim = src_dim.map(|i| i.try_into().unwrap());
-        let target_dim = target_dim.map(|i| i.try_into().unwrap());
-        self.inner
+    ) -> PyResult<Option<Self>> {
+        let src_dim = src_dim.map(to_dimension).transpose()?;
+        let target_dim = target_dim.map(to_dimension).transpose()?;
+        Ok(self
+            .inner
             .boundaries_update(src_dim, target_dim)
-            .map(|m| m.into())
+            .map(|m| m.into()))
     }
 
     #[pyo3(signature = (src_dim=None, link_dim=None, with_fields=true))]
@@ -180,13 +581,15 @@ impl PyUMesh {
         src_dim: Option<usize>,
         link_dim: Option<usize>,
         with_fields: bool,
-    ) -> Vec<Self> {
-        let src_dim = src_dim.map(|i| i.try_into().unwrap());
-        let link_dim = link_dim.map(|i| i.try_into().unwrap());
-        mf::compute_connected_components(&self.inner, src_dim, link_dim, with_fields)
-            .into_iter()
-            .map(|m| m.into())
-            .collect()
+    ) -> PyResult<Vec<Self>> {
+        let src_dim = src_dim.map(to_dimension).transpose()?;
+        let link_dim = link_dim.map(to_dimension).transpose()?;
+        Ok(
+            mf::compute_connected_components(&self.inner, src_dim, link_dim, with_fields)
+                .into_iter()
+                .map(|m| m.into())
+                .collect(),
+        )
     }
 
     fn measure<'py>(&self, py: Python<'py>) -> BTreeMap<String, Bound<'py, np::PyArray1<f64>>> {
@@ -245,10 +648,181 @@ impl PyUMesh {
         new_mesh.into()
     }
 
+    /// Cuts the 2D cells by a 1D tool mesh, splitting the cells crossed by the tool.
+    ///
+    /// Returns the cut mesh and, per element type, the parent of each of its cells in this mesh.
+    #[pyo3(signature = (tool, tol=0.0))]
+    fn cut(&self, tool: &PyUMesh, tol: f64) -> PyResult<(Self, PyParents)> {
+        let (mesh, parents) =
+            intersect::cut_2d_mesh_with_1d_mesh(self.inner.view(), tool.inner.view(), tol)
+                .map_err(MefikitError::new_err)?;
+        Ok((mesh.into(), parents_to_py(parents)))
+    }
+
+    /// Inserts the intersections with a 1D tool mesh in the edges of the 2D cells, without
+    /// splitting them.
+    ///
+    /// Returns the new mesh and, per element type, the parent of each of its cells in this mesh.
+    #[pyo3(signature = (tool, tol=0.0))]
+    fn cut_edges(&self, tool: &PyUMesh, tol: f64) -> PyResult<(Self, PyParents)> {
+        let (mesh, parents) = intersect::cut_edges(self.inner.view(), tool.inner.view(), tol)
+            .map_err(MefikitError::new_err)?;
+        Ok((mesh.into(), parents_to_py(parents)))
+    }
+
+    /// Returns the intersection points of two 1D meshes, as a mesh of VERTEX elements.
+    #[pyo3(signature = (other, tol=0.0))]
+    fn intersect_1d(&self, other: &PyUMesh, tol: f64) -> PyResult<Self> {
+        intersect::cut_1d_1d(self.inner.view(), other.inner.view(), tol)
+            .map(Self::from)
+            .map_err(MefikitError::new_err)
+    }
+
+    /// Fuses two 1D meshes into a SEG2 mesh conform to both.
+    #[pyo3(signature = (other, tol=0.0))]
+    fn fuse_1d(&self, other: &PyUMesh, tol: f64) -> PyResult<Self> {
+        intersect::cut_add_1d_1d(self.inner.view(), other.inner.view(), tol)
+            .map(Self::from)
+            .map_err(MefikitError::new_err)
+    }
+
+    /// Returns the pairs of overlapping cells of this mesh and `other` (this mesh itself by
+    /// default) as `((type, index), (type, index), measure)` tuples.
+    #[pyo3(signature = (other=None, tol=0.0))]
+    fn detect_overlaps(&self, other: Option<&PyUMesh>, tol: f64) -> PyResult<PyOverlaps> {
+        let other = other.unwrap_or(self);
+        let overlaps = mf::detect_overlaps(self.inner.view(), other.inner.view(), tol)
+            .map_err(MefikitError::new_err)?;
+        Ok(overlaps
+            .into_iter()
+            .map(|(a, b, m)| (element_id_to_py(a), element_id_to_py(b), m))
+            .collect())
+    }
+
+    /// Returns the centroid of each element, per element type.
+    #[pyo3(signature = (dim=None))]
+    fn centroids<'py>(
+        &self,
+        py: Python<'py>,
+        dim: Option<usize>,
+    ) -> PyResult<BTreeMap<String, Bound<'py, np::PyArray2<f64>>>> {
+        let dim = dim.map(to_dimension).transpose()?;
+        Ok(mf::centroids(self.inner.view(), dim)
+            .into_iter()
+            .map(|(et, arr)| (etype_to_str(et), np::PyArray2::from_owned_array(py, arr)))
+            .collect())
+    }
+
+    /// Grows a region from seed elements over face-adjacent elements.
+    ///
+    /// The region only grows to neighbours satisfying all the given criteria: being in a group,
+    /// having the same groups or family, having a field value in a range given as
+    /// `(name, min, max)`, or a normal making an angle of at most `max_angle` radians.
+    #[pyo3(signature = (
+        seeds, dim=None, group=None, same_groups=false, same_family=false, field_range=None,
+        max_angle=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn grow_region(
+        &self,
+        seeds: Bound<'_, PyDict>,
+        dim: Option<usize>,
+        group: Option<String>,
+        same_groups: bool,
+        same_family: bool,
+        field_range: Option<(String, f64, f64)>,
+        max_angle: Option<f64>,
+    ) -> PyResult<Py<PyDict>> {
+        let seeds: mf::ElementIds = PyElementIds::from_dict(&seeds)?.into();
+        let dim = dim.map(to_dimension).transpose()?;
+        let mut criteria = Vec::new();
+        if let Some(group) = group {
+            criteria.push(mf::GrowCriterion::InGroup(group));
+        }
+        if same_groups {
+            criteria.push(mf::GrowCriterion::SameGroups);
+        }
+        if same_family {
+            criteria.push(mf::GrowCriterion::SameFamily);
+        }
+        if let Some((name, min, max)) = field_range {
+            criteria.push(mf::GrowCriterion::FieldRange { name, min, max });
+        }
+        if let Some(angle) = max_angle {
+            criteria.push(mf::GrowCriterion::NormalAngle(angle));
+        }
+        let criterion = mf::GrowCriterion::All(criteria);
+        let region = mf::grow_region(self.inner.view(), &seeds, dim, &criterion);
+        Ok(PyElementIds::from(region).into())
+    }
+
+    /// Projects nodes onto a target line or surface mesh.
+    ///
+    /// Returns the new mesh and the nodes which could not be projected. Corners and sharp edges
+    /// of the target are preserved if `feature_angle` (in radians) is given.
+    #[pyo3(signature = (
+        nodes, target, max_distance=f64::INFINITY, feature_angle=None, feature_distance=0.0
+    ))]
+    fn project_nodes<'py>(
+        &self,
+        py: Python<'py>,
+        nodes: Vec<usize>,
+        target: &PyUMesh,
+        max_distance: f64,
+        feature_angle: Option<f64>,
+        feature_distance: f64,
+    ) -> PyResult<(Self, Bound<'py, np::PyArray1<usize>>)> {
+        if self.inner.space_dimension() != target.inner.space_dimension() {
+            return Err(MefikitError::new_err(
+                "The mesh and the target must have the same space dimension",
+            ));
+        }
+        if let Some(&n) = nodes.iter().find(|&&n| n >= self.inner.coords().nrows()) {
+            return Err(MefikitError::new_err(format!("Node {n} is out of range")));
+        }
+        let mut options = mf::SnapOptions::new(max_distance);
+        if let Some(angle) = feature_angle {
+            options = options.preserve_features(angle, feature_distance);
+        }
+        let mut projected = self.inner.clone();
+        let unsnapped = mf::project_nodes(&mut projected, &nodes, target.inner.view(), &options);
+        Ok((projected.into(), np::PyArray1::from_vec(py, unsnapped)))
+    }
+
+    /// Extracts the elements matching a selection, given as a `Selection` or in text form.
+    #[pyo3(signature = (expr, with_fields=true))]
+    fn select(&self, expr: PySelectionInput, with_fields: bool) -> PyResult<Self> {
+        let (_, submesh) = self.inner.select(expr.try_into()?, with_fields);
+        Ok(submesh.into())
+    }
+
+    /// Returns the ids of the elements matching a selection, per element type.
+    fn select_ids(&self, expr: PySelectionInput) -> PyResult<Py<PyDict>> {
+        let ids: PyElementIds = self.inner.select_ids(expr.try_into()?).into();
+        Ok(ids.into())
+    }
+
+    /// Returns the sorted ids of the nodes matching a selection.
+    fn select_nodes<'py>(
+        &self,
+        py: Python<'py>,
+        expr: PySelectionInput,
+    ) -> PyResult<Bound<'py, np::PyArray1<usize>>> {
+        let nodes = self.inner.select_nodes(expr.try_into()?);
+        Ok(np::PyArray1::from_vec(py, nodes))
+    }
+
+    /// Returns the ids of the elements matching a selection, per element type, and the extracted
+    /// mesh.
     #[pyo3(signature = (expr, with_fields=true))]
-    fn select(&self, expr: PySelection, with_fields: bool) -> Self {
-        let (_, submesh) = self.inner.select(expr.into(), with_fields);
-        submesh.into()
+    fn select_with_ids(
+        &self,
+        expr: PySelectionInput,
+        with_fields: bool,
+    ) -> PyResult<(Py<PyDict>, Self)> {
+        let (ids, submesh) = self.inner.select(expr.try_into()?, with_fields);
+        let ids: PyElementIds = ids.into();
+        Ok((ids.into(), submesh.into()))
     }
 
     fn eval<'py>(
@@ -266,11 +840,33 @@ impl PyUMesh {
     fn eval_update(&mut self, name: &str, expr: PyField) {
         self.inner.eval_update_field(name, None, expr.into());
     }
+
+    fn __repr__(&self) -> String {
+        format!("<{}>", self.inner)
+    }
 }
 
 impl Display for PyUMesh {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:#?}", self.inner)
+        write!(f, "{:#}", self.inner)
+    }
+}
+
+impl PyUMesh {
+    pub(crate) fn inner(&self) -> &mf::UMesh {
+        &self.inner
+    }
+
+    /// Fails if a node index is out of the coordinates, ignoring the face separators of PHED
+    /// connectivities.
+    fn check_nodes<'a>(&self, nodes: impl Iterator<Item = &'a usize>) -> PyResult<()> {
+        let n_nodes = self.inner.coords().nrows();
+        match nodes.filter(|&&n| n != usize::MAX).find(|&&n| n >= n_nodes) {
+            Some(n) => Err(MefikitError::new_err(format!(
+                "Node {n} is out of the {n_nodes} nodes of the mesh"
+            ))),
+            None => Ok(()),
+        }
     }
 }
 
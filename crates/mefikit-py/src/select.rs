@@ -4,7 +4,9 @@ use std::fmt::{Display, Formatter};
 
 use mefikit::prelude as mf;
 
+use super::element::str_to_etype;
 use super::element_ids::PyElementIds;
+use super::errors::{MefikitError, to_dimension};
 
 #[pyclass(str)]
 #[pyo3(name = "Selection")]
@@ -31,6 +33,33 @@ impl From<PySelection> for mf::Selection {
     }
 }
 
+/// A selection given either as a `Selection` object or in its text form.
+#[derive(FromPyObject)]
+pub enum PySelectionInput {
+    Selection(PySelection),
+    Text(String),
+}
+
+impl TryFrom<PySelectionInput> for mf::Selection {
+    type Error = PyErr;
+
+    fn try_from(input: PySelectionInput) -> PyResult<Self> {
+        match input {
+            PySelectionInput::Selection(sel) => Ok(sel.into()),
+            PySelectionInput::Text(text) => {
+                mf::Selection::parse(&text).map_err(|e| MefikitError::new_err(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Parses a selection from its text form, e.g. `dim == 2 && group('wall')`.
+#[pyfunction]
+pub fn parse(text: &str) -> PyResult<PySelection> {
+    let sel: mf::Selection = PySelectionInput::Text(text.to_owned()).try_into()?;
+    Ok(sel.into())
+}
+
 #[pyfunction]
 pub fn nbbox(min: [f64; 3], max: [f64; 3], all: bool) -> PySelection {
     mf::sel::nbbox(min, max, all).into()
@@ -68,19 +97,42 @@ pub fn sphere(center: [f64; 3], r2: f64) -> PySelection {
 pub fn circle(center: [f64; 2], r2: f64) -> PySelection {
     mf::sel::circle(center, r2).into()
 }
-// TODO: Enable ElementType and Dimension exposure to Python
-// #[pyfunction]
-// pub fn types(elems: Vec<ElementType>) -> PySelection {
-//     mf::sel::types(elems).into()
-// }
-// #[pyfunction]
-// pub fn dimensions(dims: Vec<Dimension>) -> PySelection {
-//     mf::sel::dimensions(dims).into()
-// }
-#[pyfunction]
-pub fn ids<'py>(eids: Bound<'py, PyDict>) -> PySelection {
-    let eids = PyElementIds::from_dict(&eids);
-    mf::sel::ids(eids.into()).into()
+#[pyfunction]
+pub fn types(elems: Vec<String>) -> PyResult<PySelection> {
+    let elems = elems
+        .iter()
+        .map(|et| str_to_etype(et))
+        .collect::<PyResult<_>>()?;
+    Ok(mf::sel::types(elems).into())
+}
+#[pyfunction]
+pub fn dimensions(dims: Vec<usize>) -> PyResult<PySelection> {
+    let dims = dims
+        .into_iter()
+        .map(to_dimension)
+        .collect::<PyResult<_>>()?;
+    Ok(mf::sel::dimensions(dims).into())
+}
+#[pyfunction]
+pub fn group(name: &str) -> PySelection {
+    mf::sel::group(name).into()
+}
+#[pyfunction]
+pub fn not_group(name: &str) -> PySelection {
+    mf::sel::not_group(name).into()
+}
+#[pyfunction]
+pub fn family(id: usize) -> PySelection {
+    mf::sel::family(id).into()
+}
+#[pyfunction]
+pub fn not_family(id: usize) -> PySelection {
+    mf::sel::not_family(id).into()
+}
+#[pyfunction]
+pub fn ids<'py>(eids: Bound<'py, PyDict>) -> PyResult<PySelection> {
+    let eids = PyElementIds::from_dict(&eids)?;
+    Ok(mf::sel::ids(eids.into()).into())
 }
 
 #[pymethods]
@@ -0,0 +1,227 @@
+//! JavaScript bindings of mefikit, through wasm-bindgen.
+//!
+//! Element types are given by name (`"QUAD4"`, `"PGON"`, etc.) and arrays are flattened: node
+//! coordinates and regular connectivities row by row. Node indices are `u32`, the separator of
+//! the faces of PHED elements being `0xFFFFFFFF`.
+
+use ndarray as nd;
+use wasm_bindgen::prelude::*;
+
+use mefikit::prelude as mf;
+use mefikit::tools::Descendable;
+
+fn to_element_type(name: &str) -> Result<mf::ElementType, JsError> {
+    serde_json::from_value(serde_json::Value::String(name.to_owned()))
+        .map_err(|_| JsError::new(&format!("Unsupported element type: '{name}'")))
+}
+
+fn to_nodes(nodes: &[u32], num_nodes: usize) -> Result<Vec<usize>, JsError> {
+    nodes
+        .iter()
+        .map(|&n| match n {
+            u32::MAX => Ok(usize::MAX),
+            n if (n as usize) < num_nodes => Ok(n as usize),
+            n => Err(JsError::new(&format!(
+                "Node {n} out of range, the mesh has {num_nodes} nodes"
+            ))),
+        })
+        .collect()
+}
+
+fn from_nodes(nodes: impl IntoIterator<Item = usize>) -> Vec<u32> {
+    nodes
+        .into_iter()
+        .map(|n| u32::try_from(n).unwrap_or(u32::MAX))
+        .collect()
+}
+
+/// An unstructured mesh.
+#[wasm_bindgen(js_name = UMesh)]
+pub struct WasmUMesh {
+    inner: mf::UMesh,
+}
+
+impl From<mf::UMesh> for WasmUMesh {
+    fn from(inner: mf::UMesh) -> Self {
+        Self { inner }
+    }
+}
+
+#[wasm_bindgen(js_class = UMesh)]
+impl WasmUMesh {
+    /// Creates a mesh without elements from node coordinates, given node by node.
+    #[wasm_bindgen(constructor)]
+    pub fn new(coords: Vec<f64>, dim: usize) -> Result<WasmUMesh, JsError> {
+        if !(1..=3).contains(&dim) || !coords.len().is_multiple_of(dim) {
+            return Err(JsError::new(&format!(
+                "Cannot split {} coordinates in nodes of dimension {dim}",
+                coords.len()
+            )));
+        }
+        let coords = nd::Array2::from_shape_vec((coords.len() / dim, dim), coords)?;
+        Ok(mf::UMesh::new(coords.into_shared()).into())
+    }
+
+    /// Cartesian mesh of the tensor product of one to three sorted axes.
+    pub fn regular(x: Vec<f64>, y: Option<Vec<f64>>, z: Option<Vec<f64>>) -> WasmUMesh {
+        let builder = [Some(x), y, z]
+            .into_iter()
+            .flatten()
+            .fold(mf::RegularUMeshBuilder::new(), |b, axis| b.add_axis(axis));
+        builder.build().into()
+    }
+
+    /// Reads a mesh serialized by `toJson`.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmUMesh, JsError> {
+        Ok(serde_json::from_str::<mf::UMesh>(json)?.into())
+    }
+
+    /// Adds a block of a regular element type, its connectivity given element by element.
+    #[wasm_bindgen(js_name = addRegularBlock)]
+    pub fn add_regular_block(
+        &mut self,
+        element_type: &str,
+        connectivity: Vec<u32>,
+    ) -> Result<(), JsError> {
+        let et = to_element_type(element_type)?;
+        let Some(n) = et.num_nodes() else {
+            return Err(JsError::new(&format!("{element_type} is a poly type")));
+        };
+        if !connectivity.len().is_multiple_of(n) || self.inner.block(et).is_some() {
+            return Err(JsError::new(&format!(
+                "Invalid {element_type} connectivity, or block already in the mesh"
+            )));
+        }
+        let nodes = to_nodes(&connectivity, self.num_nodes())?;
+        let connectivity = nd::Array2::from_shape_vec((nodes.len() / n, n), nodes)?;
+        self.inner
+            .add_regular_block(et, connectivity.into_shared(), None);
+        Ok(())
+    }
+
+    /// Adds a block of PGON or PHED elements. `offsets` holds the end of each element in `data`.
+    #[wasm_bindgen(js_name = addPolyBlock)]
+    pub fn add_poly_block(
+        &mut self,
+        element_type: &str,
+        data: Vec<u32>,
+        offsets: Vec<u32>,
+    ) -> Result<(), JsError> {
+        let et = to_element_type(element_type)?;
+        let valid = et.num_nodes().is_none()
+            && self.inner.block(et).is_none()
+            && offsets.is_sorted()
+            && offsets.last().map_or(0, |&o| o as usize) == data.len();
+        if !valid {
+            return Err(JsError::new(&format!(
+                "Invalid {element_type} block, or block already in the mesh"
+            )));
+        }
+        let data = to_nodes(&data, self.num_nodes())?;
+        let offsets: Vec<usize> = offsets.into_iter().map(|o| o as usize).collect();
+        self.inner.add_poly_block(
+            et,
+            nd::Array1::from(data).into_shared(),
+            nd::Array1::from(offsets).into_shared(),
+        );
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = numNodes)]
+    pub fn num_nodes(&self) -> usize {
+        self.inner.coords().nrows()
+    }
+
+    #[wasm_bindgen(getter, js_name = numElements)]
+    pub fn num_elements(&self) -> usize {
+        self.inner.num_elements()
+    }
+
+    #[wasm_bindgen(getter, js_name = spaceDimension)]
+    pub fn space_dimension(&self) -> usize {
+        self.inner.space_dimension()
+    }
+
+    /// Node coordinates, node by node.
+    pub fn coords(&self) -> Vec<f64> {
+        self.inner.coords().iter().copied().collect()
+    }
+
+    /// Element types of the blocks, in the order of the elements.
+    #[wasm_bindgen(js_name = elementTypes)]
+    pub fn element_types(&self) -> Vec<String> {
+        self.inner
+            .blocks()
+            .map(|(et, _)| format!("{et:?}"))
+            .collect()
+    }
+
+    /// Connectivity of a block, as given to `addRegularBlock` or the `data` of `addPolyBlock`.
+    pub fn connectivity(&self, element_type: &str) -> Result<Vec<u32>, JsError> {
+        let et = to_element_type(element_type)?;
+        let block = self
+            .inner
+            .block(et)
+            .ok_or_else(|| JsError::new(&format!("No {element_type} block in the mesh")))?;
+        Ok(from_nodes(block.connectivity.iter().flatten().copied()))
+    }
+
+    /// Length, area or volume of all the elements, in the order of `elementTypes`.
+    pub fn measure(&self) -> Vec<f64> {
+        let measures = mf::measure(self.inner.view(), None);
+        self.inner
+            .blocks()
+            .flat_map(|(et, b)| match measures.get(et) {
+                Some(m) => m.to_vec(),
+                // elements of lower dimension than the mesh are not measured
+                None => vec![f64::NAN; b.len()],
+            })
+            .collect()
+    }
+
+    /// Boundary of the mesh, one dimension lower.
+    pub fn boundaries(&self) -> WasmUMesh {
+        self.inner.boundaries(None, None).into()
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.inner)?)
+    }
+
+    /// ASCII VTU document of the mesh and of its cell fields.
+    #[wasm_bindgen(js_name = toVtu)]
+    pub fn to_vtu(&self) -> Result<String, JsError> {
+        mf::to_vtu_string(self.inner.view()).map_err(|e| JsError::new(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umesh() {
+        let mut mesh = WasmUMesh::new(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0], 2).unwrap();
+        mesh.add_regular_block("TRI3", vec![0, 1, 3, 0, 3, 2])
+            .unwrap();
+        assert_eq!((mesh.num_nodes(), mesh.num_elements()), (4, 2));
+        assert_eq!(mesh.element_types(), ["TRI3"]);
+        assert_eq!(mesh.connectivity("TRI3").unwrap(), [0, 1, 3, 0, 3, 2]);
+        assert_eq!(mesh.measure(), [0.5, 0.5]);
+        assert_eq!(mesh.boundaries().num_elements(), 4);
+        assert!(mesh.to_vtu().unwrap().contains("NumberOfCells=\"2\""));
+
+        let back = WasmUMesh::from_json(&mesh.to_json().unwrap()).unwrap();
+        assert_eq!(back.coords(), mesh.coords());
+    }
+
+    #[test]
+    fn test_regular() {
+        let mesh = WasmUMesh::regular(vec![0.0, 0.5, 1.0], Some(vec![0.0, 1.0]), None);
+        assert_eq!(mesh.space_dimension(), 2);
+        assert_eq!(mesh.element_types(), ["QUAD4"]);
+        assert_eq!(mesh.measure(), [0.5, 0.5]);
+    }
+}
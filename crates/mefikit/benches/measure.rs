@@ -24,5 +24,22 @@ fn measure2(c: &mut Criterion) {
     }
 }
 
-criterion_group!(bench, measure2,);
+fn centroids(c: &mut Criterion) {
+    let mut group = c.benchmark_group("centroids");
+
+    for i in [4, 60, 100] {
+        let mesh = mf::RegularUMeshBuilder::new()
+            .add_axis((0..(i + 1)).map(|k| (k as f64) / (i as f64)).collect())
+            .add_axis((0..(i + 1)).map(|k| (k as f64) / (i as f64)).collect())
+            .add_axis((0..(i + 1)).map(|k| (k as f64) / (i as f64)).collect())
+            .build();
+        group.bench_with_input(BenchmarkId::new("mesh_size", i * i * i), &i, |b, _| {
+            b.iter(|| {
+                std::hint::black_box(mf::centroids(mesh.view(), None));
+            })
+        });
+    }
+}
+
+criterion_group!(bench, measure2, centroids);
 criterion_main!(bench);
@@ -13,7 +13,7 @@ fn merge_nodes(c: &mut Criterion) {
                         .add_axis((0..(i + 1)).map(|k| (k as f64) / (i as f64)).collect())
                         .add_axis((0..(i + 1)).map(|k| (k as f64) / (i as f64)).collect())
                         .build();
-                    let cut = mf::compute_descending(&m1, None, None);
+                    let cut = mf::compute_descending(m1.view(), None, None);
                     mf::crack(m1, cut.view())
                 },
                 |mut cracked| {
@@ -12,7 +12,7 @@ fn descending_mesh(c: &mut Criterion) {
             .build();
         group.bench_with_input(BenchmarkId::new("mesh_size", i * i), &i, |b, _| {
             b.iter(|| {
-                std::hint::black_box(mf::compute_descending(&mesh, None, None));
+                std::hint::black_box(mf::compute_descending(mesh.view(), None, None));
             })
         });
     }
@@ -28,7 +28,7 @@ fn neighbours(c: &mut Criterion) {
             .build();
         group.bench_with_input(BenchmarkId::new("mesh_size", i * i), &i, |b, _| {
             b.iter(|| {
-                std::hint::black_box(mf::compute_neighbours(&mesh, None, None));
+                std::hint::black_box(mf::compute_neighbours(mesh.view(), None, None));
             })
         });
     }
@@ -44,7 +44,7 @@ fn boundaries(c: &mut Criterion) {
             .build();
         group.bench_with_input(BenchmarkId::new("mesh_size", i * i), &i, |b, _| {
             b.iter(|| {
-                std::hint::black_box(mf::compute_boundaries(&mesh, None, None));
+                std::hint::black_box(mf::compute_boundaries(mesh.view(), None, None));
             })
         });
     }
@@ -62,11 +62,34 @@ fn par_neighbours(c: &mut Criterion) {
             .build();
         group.bench_with_input(BenchmarkId::new("mesh_size", i * i), &i, |b, _| {
             b.iter(|| {
-                std::hint::black_box(mf::par_compute_neighbours(&mesh, None, None));
+                std::hint::black_box(mf::par_compute_neighbours(mesh.view(), None, None));
+            })
+        });
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn par_boundaries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("par_boundaries");
+
+    for i in [4, 60, 100] {
+        let mesh = mf::RegularUMeshBuilder::new()
+            .add_axis((0..(i + 1)).map(|i| i as f64).collect::<Vec<f64>>())
+            .add_axis((0..(i + 1)).map(|i| i as f64).collect::<Vec<f64>>())
+            .build();
+        group.bench_with_input(BenchmarkId::new("mesh_size", i * i), &i, |b, _| {
+            b.iter(|| {
+                std::hint::black_box(mf::par_compute_boundaries(mesh.view(), None, None));
             })
         });
     }
 }
 
 criterion_group!(bench, descending_mesh, neighbours, boundaries);
+#[cfg(feature = "rayon")]
+criterion_group!(par_bench, par_boundaries);
+
+#[cfg(not(feature = "rayon"))]
 criterion_main!(bench);
+#[cfg(feature = "rayon")]
+criterion_main!(bench, par_bench);
@@ -3,15 +3,24 @@ use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use mefikit::prelude as mf;
 use mefikit::tools::selector::MeshSelect;
 
+fn make_cube(i: usize) -> mf::UMesh {
+    mf::RegularUMeshBuilder::new()
+        .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
+        .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
+        .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
+        .build()
+}
+
+fn combined_selection() -> mf::Selection {
+    (mf::sel::sphere([0.5, 0.5, 0.5], 0.25) | mf::sel::nbbox([0.0; 3], [0.3; 3], true))
+        ^ (mf::sel::bbox([0.4, 0.0, 0.0], [1.0, 1.0, 0.6]) - mf::sel::nsphere([0.7; 3], 0.2, false))
+}
+
 fn selection_sphere(c: &mut Criterion) {
     let mut group = c.benchmark_group("selection");
 
     for i in [2, 20, 30] {
-        let mesh = mf::RegularUMeshBuilder::new()
-            .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
-            .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
-            .add_axis((0..=i).map(|k| (k as f64) / (i as f64)).collect())
-            .build();
+        let mesh = make_cube(i);
         group.bench_with_input(BenchmarkId::new("mesh_size", i * i * i), &i, |b, _| {
             b.iter(|| {
                 std::hint::black_box(mesh.select(mf::sel::sphere([0.5, 0.5, 0.5], 0.25), false));
@@ -20,5 +29,54 @@ fn selection_sphere(c: &mut Criterion) {
     }
 }
 
-criterion_group!(bench, selection_sphere,);
+/// Scaling of a combined expression with the mesh size, up to a few million elements.
+fn selection_combined(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selection_combined");
+    group.sample_size(10);
+
+    for i in [50, 100, 150] {
+        let mesh = make_cube(i);
+        group.bench_with_input(BenchmarkId::new("mesh_size", i * i * i), &i, |b, _| {
+            b.iter(|| {
+                std::hint::black_box(mesh.select_ids(combined_selection()));
+            })
+        });
+    }
+}
+
+/// Scaling of a combined expression with the number of threads on a 3.4M elements mesh.
+///
+/// Run with `cargo bench --bench select --features rayon`.
+#[cfg(feature = "rayon")]
+fn selection_threads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selection_threads");
+    group.sample_size(10);
+
+    let mesh = make_cube(150);
+    for n_threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(n_threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("threads", n_threads),
+            &n_threads,
+            |b, _| {
+                b.iter(|| {
+                    pool.install(|| std::hint::black_box(mesh.select_ids(combined_selection())));
+                })
+            },
+        );
+    }
+}
+
+#[cfg(feature = "rayon")]
+criterion_group!(
+    bench,
+    selection_sphere,
+    selection_combined,
+    selection_threads,
+);
+#[cfg(not(feature = "rayon"))]
+criterion_group!(bench, selection_sphere, selection_combined,);
 criterion_main!(bench);
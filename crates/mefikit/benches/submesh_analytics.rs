@@ -16,7 +16,7 @@ fn regular_grid(n: usize) -> mf::UMesh {
 #[bench::short(regular_grid(4))]
 #[bench::long(regular_grid(32))]
 fn bench_descending_mesh(mesh: mf::UMesh) {
-    black_box(mf::compute_descending(&mesh, None, None));
+    black_box(mf::compute_descending(mesh.view(), None, None));
 }
 
 library_benchmark_group!(
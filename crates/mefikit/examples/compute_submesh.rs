@@ -13,7 +13,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Computing descending_mesh");
     let now = time::Instant::now();
     for _ in 0..10 {
-        let _ = mf::compute_descending(&mesh, None, None);
+        let _ = mf::compute_descending(mesh.view(), None, None);
     }
     let t_tot = now.elapsed().as_secs_f64() * 100.0;
 
@@ -15,7 +15,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("End:   building mesh");
 
     println!("Start: building descending_mesh");
-    let descending_mesh = mf::compute_descending(&mesh, None, None);
+    let descending_mesh = mf::compute_descending(mesh.view(), None, None);
     // mf::write(Path::new("descending_mesh.vtk"), descending_mesh.view())?;
     println!("End:   building descending_mesh");
 
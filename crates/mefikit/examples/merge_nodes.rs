@@ -12,7 +12,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .add_axis((0..=n).map(|i| i as f64 / (n as f64)).collect::<Vec<f64>>())
         .build();
     // mf::write(Path::new("mesh.vtk"), mesh.view())?;
-    let descending_mesh = mf::compute_descending(&mesh, None, None);
+    let descending_mesh = mf::compute_descending(mesh.view(), None, None);
     let (_, descending_mesh) = descending_mesh.select(mf::sel::sphere([0.5, 0.5, 0.5], 0.5), false);
 
     let mut cracked = mf::crack(mesh, descending_mesh.view());
@@ -0,0 +1,439 @@
+//! Conversion of coordinates, connectivities and fields to and from Arrow record batches.
+//!
+//! Arrays of a [`UMesh`] are shared with Arrow without copy when their layout allows it:
+//! contiguous fields, families and regular connectivities (the latter two on 64 bits targets,
+//! where `usize` and `u64` match). Coordinates are split in `x`, `y` and `z` columns, and
+//! therefore copied.
+
+use std::panic::RefUnwindSafe;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use arrow_array::{
+    Array, ArrayRef, FixedSizeListArray, Float64Array, LargeListArray, RecordBatch, StringArray,
+    UInt64Array,
+};
+use arrow_buffer::{Buffer, NullBuffer, OffsetBuffer, ScalarBuffer};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use ndarray as nd;
+
+#[cfg(target_pointer_width = "64")]
+use crate::mesh::BlockSlices;
+use crate::mesh::{Connectivity, ElementType, UMesh};
+
+const AXES: [&str; 3] = ["x", "y", "z"];
+
+/// Shares the storage of a contiguous array with Arrow, the buffer keeping the array alive.
+fn shared_buffer<A, D>(array: &nd::ArcArray<A, D>) -> Option<Buffer>
+where
+    A: Send + Sync + RefUnwindSafe + 'static,
+    D: nd::Dimension + RefUnwindSafe + 'static,
+{
+    let values = array.as_slice()?;
+    let ptr = NonNull::new(values.as_ptr().cast_mut())?.cast::<u8>();
+    // The clone holds a reference on the data, so a later mutation of the mesh array copies it
+    // instead of writing the shared storage.
+    Some(unsafe {
+        Buffer::from_custom_allocation(ptr, std::mem::size_of_val(values), Arc::new(array.clone()))
+    })
+}
+
+fn f64_buffer<D: nd::Dimension + RefUnwindSafe + 'static>(array: &nd::ArcArray<f64, D>) -> Buffer {
+    shared_buffer(array).unwrap_or_else(|| Buffer::from_vec(array.iter().copied().collect()))
+}
+
+fn u64_buffer<D: nd::Dimension + RefUnwindSafe + 'static>(
+    array: &nd::ArcArray<usize, D>,
+) -> Buffer {
+    #[cfg(target_pointer_width = "64")]
+    if let Some(buffer) = shared_buffer(array) {
+        return buffer;
+    }
+    Buffer::from_vec(array.iter().map(|&n| n as u64).collect::<Vec<u64>>())
+}
+
+fn u64_column(buffer: Buffer, len: usize) -> UInt64Array {
+    UInt64Array::new(ScalarBuffer::new(buffer, 0, len), None)
+}
+
+fn missing(name: &str) -> ArrowError {
+    ArrowError::SchemaError(format!("Missing or invalid column {name}"))
+}
+
+/// Coordinates of the nodes, in `x`, `y` and `z` columns depending on the space dimension.
+pub fn coords_to_record_batch(mesh: &UMesh) -> Result<RecordBatch, ArrowError> {
+    let coords = mesh.coords();
+    let fields: Vec<Field> = AXES[..coords.ncols()]
+        .iter()
+        .map(|&axis| Field::new(axis, DataType::Float64, false))
+        .collect();
+    let columns = coords
+        .columns()
+        .into_iter()
+        .map(|c| Arc::new(Float64Array::from(c.to_vec())) as ArrayRef)
+        .collect();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// Reads node coordinates from the `x`, `y` and `z` columns of a batch, `y` and `z` being
+/// optional.
+///
+/// # Errors
+/// Returns an error if the `x` column is missing, or if a column is not a non null `Float64`.
+pub fn coords_from_record_batch(batch: &RecordBatch) -> Result<nd::Array2<f64>, ArrowError> {
+    let columns: Vec<&Float64Array> = AXES
+        .iter()
+        .map_while(|&axis| batch.column_by_name(axis).map(|c| (axis, c)))
+        .map(|(axis, c)| {
+            c.as_any()
+                .downcast_ref::<Float64Array>()
+                .filter(|c| c.null_count() == 0)
+                .ok_or_else(|| missing(axis))
+        })
+        .collect::<Result<_, _>>()?;
+    if columns.is_empty() {
+        return Err(missing("x"));
+    }
+    Ok(nd::Array2::from_shape_fn(
+        (batch.num_rows(), columns.len()),
+        |(i, j)| columns[j].value(i),
+    ))
+}
+
+/// Connectivity of the block of an element type, with an `element_index` column, a `family`
+/// column and a `connectivity` column holding the node indices of each element.
+///
+/// Regular connectivities are `FixedSizeList<UInt64>` columns, and poly ones are
+/// `LargeList<UInt64>` columns, faces of PHED elements being separated by `u64::MAX`.
+///
+/// # Errors
+/// Returns an error if the mesh has no block of this type.
+pub fn connectivity_to_record_batch(
+    mesh: &UMesh,
+    et: ElementType,
+) -> Result<RecordBatch, ArrowError> {
+    let block = mesh
+        .block(et)
+        .ok_or_else(|| ArrowError::InvalidArgumentError(format!("No {et:?} block in the mesh")))?;
+    let len = block.len();
+    let item = Arc::new(Field::new("item", DataType::UInt64, false));
+    let connectivity: ArrayRef = match &block.connectivity {
+        Connectivity::Regular(conn) => {
+            let values = u64_column(u64_buffer(conn), conn.len());
+            Arc::new(FixedSizeListArray::try_new(
+                item,
+                conn.ncols() as i32,
+                Arc::new(values),
+                None,
+            )?)
+        }
+        Connectivity::Poly(conn) => {
+            let values = u64_column(u64_buffer(&conn.data), conn.data.len());
+            let offsets = std::iter::once(0)
+                .chain(conn.offsets.iter().map(|&o| o as i64))
+                .collect::<Vec<i64>>();
+            let offsets = OffsetBuffer::new(ScalarBuffer::from(offsets));
+            Arc::new(LargeListArray::try_new(
+                item,
+                offsets,
+                Arc::new(values),
+                None,
+            )?)
+        }
+    };
+    let list_type = connectivity.data_type().clone();
+    let schema = Schema::new(vec![
+        Field::new("element_index", DataType::UInt64, false),
+        Field::new("family", DataType::UInt64, false),
+        Field::new("connectivity", list_type, false),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(0..len as u64)),
+        Arc::new(u64_column(u64_buffer(&block.families), len)),
+        connectivity,
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns)
+}
+
+/// Borrows the connectivity and families of a batch made by [`connectivity_to_record_batch`],
+/// to build a [`crate::mesh::UMeshView`] over it without copy.
+///
+/// # Errors
+/// Returns an error if the columns are missing, have nulls, or do not have the expected types.
+#[cfg(target_pointer_width = "64")]
+pub fn block_slices(batch: &RecordBatch, et: ElementType) -> Result<BlockSlices<'_>, ArrowError> {
+    fn as_usize(values: &[u64]) -> &[usize] {
+        // usize and u64 have the same layout on 64 bits targets
+        unsafe { std::slice::from_raw_parts(values.as_ptr().cast(), values.len()) }
+    }
+    fn values(array: &dyn Array) -> Result<&[u64], ArrowError> {
+        array
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .filter(|a| a.null_count() == 0)
+            .map(|a| a.values().as_ref())
+            .ok_or_else(|| missing("connectivity"))
+    }
+
+    let families = batch
+        .column_by_name("family")
+        .map(|c| values(c.as_ref()).map(as_usize))
+        .transpose()?;
+    let column = batch
+        .column_by_name("connectivity")
+        .ok_or_else(|| missing("connectivity"))?;
+    let (connectivity, offsets) = match et.num_nodes() {
+        Some(n) => {
+            let list = column
+                .as_any()
+                .downcast_ref::<FixedSizeListArray>()
+                .filter(|l| l.value_length() as usize == n && l.null_count() == 0)
+                .ok_or_else(|| missing("connectivity"))?;
+            let start = list.value_offset(0) as usize;
+            let conn = &values(list.values().as_ref())?[start..start + n * list.len()];
+            (as_usize(conn), None)
+        }
+        None => {
+            let list = column
+                .as_any()
+                .downcast_ref::<LargeListArray>()
+                .filter(|l| l.null_count() == 0 && l.offsets().first() == Some(&0))
+                .ok_or_else(|| missing("connectivity"))?;
+            let end = *list.offsets().last().unwrap() as usize;
+            let conn = &values(list.values().as_ref())?[..end];
+            // the leading 0 of Arrow offsets is implicit in mefikit
+            let offsets: &[i64] = &list.offsets()[1..];
+            let offsets: &[u64] =
+                unsafe { std::slice::from_raw_parts(offsets.as_ptr().cast(), offsets.len()) };
+            (as_usize(conn), Some(as_usize(offsets)))
+        }
+    };
+    Ok(BlockSlices {
+        element_type: et,
+        connectivity,
+        offsets,
+        families,
+    })
+}
+
+/// Float fields of all the elements, one row per element.
+///
+/// The elements are identified by the `element_type` and `element_index` columns. Fields with
+/// one value per element are `Float64` columns, other fields are `FixedSizeList<Float64>`
+/// columns of their flattened values. Elements of the blocks without a field are null.
+///
+/// # Errors
+/// Returns an error if a field has a different number of components on two blocks.
+pub fn fields_to_record_batch(mesh: &UMesh) -> Result<RecordBatch, ArrowError> {
+    let blocks: Vec<_> = mesh.blocks().collect();
+    let num_elements: usize = blocks.iter().map(|(_, b)| b.len()).sum();
+    // String arrays need the exact number of values up front
+    let types: Vec<String> = blocks
+        .iter()
+        .flat_map(|(et, b)| std::iter::repeat_n(format!("{et:?}"), b.len()))
+        .collect();
+    let indices = blocks.iter().flat_map(|(_, b)| 0..b.len() as u64);
+    let mut schema = vec![
+        Field::new("element_type", DataType::Utf8, false),
+        Field::new("element_index", DataType::UInt64, false),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(types)),
+        Arc::new(UInt64Array::from_iter_values(indices)),
+    ];
+
+    let mut names: Vec<&String> = blocks.iter().flat_map(|(_, b)| b.fields.keys()).collect();
+    names.sort_unstable();
+    names.dedup();
+    for name in names {
+        let shapes: Vec<&[usize]> = blocks
+            .iter()
+            .filter_map(|(_, b)| b.fields.get(name).map(|f| &f.shape()[1..]))
+            .collect();
+        if shapes
+            .windows(2)
+            .any(|s| s[0].iter().product::<usize>() != s[1].iter().product::<usize>())
+        {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Field {name} has different numbers of components on different blocks"
+            )));
+        }
+        let num_components: usize = shapes[0].iter().product();
+        let on_all_blocks = shapes.len() == blocks.len();
+        let (values, nulls) = match (on_all_blocks, blocks.as_slice()) {
+            // a single block: the field storage is shared
+            (true, [(_, b)]) => (f64_buffer(&b.fields[name]), None),
+            _ => {
+                let mut values = Vec::with_capacity(num_elements * num_components);
+                let mut valid = Vec::with_capacity(num_elements);
+                for (_, b) in &blocks {
+                    match b.fields.get(name) {
+                        Some(f) => values.extend(f.iter().copied()),
+                        None => values.extend(std::iter::repeat_n(0.0, b.len() * num_components)),
+                    }
+                    valid.extend(std::iter::repeat_n(b.fields.contains_key(name), b.len()));
+                }
+                let nulls = (!on_all_blocks).then(|| NullBuffer::from(valid));
+                (Buffer::from_vec(values), nulls)
+            }
+        };
+        let values = ScalarBuffer::new(values, 0, num_elements * num_components);
+        let column: ArrayRef = match shapes[0].is_empty() {
+            true => Arc::new(Float64Array::new(values, nulls)),
+            false => Arc::new(FixedSizeListArray::try_new(
+                Arc::new(Field::new("item", DataType::Float64, false)),
+                num_components as i32,
+                Arc::new(Float64Array::new(values, None)),
+                nulls,
+            )?),
+        };
+        schema.push(Field::new(
+            name.as_str(),
+            column.data_type().clone(),
+            !on_all_blocks,
+        ));
+        columns.push(column);
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(schema)), columns)
+}
+
+/// Assigns the `Float64` and `FixedSizeList<Float64>` columns of a batch as fields of a mesh.
+///
+/// The rows are matched with the elements by the `element_type` and `element_index` columns,
+/// as written by [`fields_to_record_batch`]. The elements without a value or with a null one
+/// get NaN. Columns of other types are ignored.
+///
+/// # Errors
+/// Returns an error if the element columns are missing or refer to elements not in the mesh.
+pub fn fields_from_record_batch(mesh: &mut UMesh, batch: &RecordBatch) -> Result<(), ArrowError> {
+    let types = batch
+        .column_by_name("element_type")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| missing("element_type"))?;
+    let indices = batch
+        .column_by_name("element_index")
+        .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+        .ok_or_else(|| missing("element_index"))?;
+    let rows = (0..batch.num_rows())
+        .map(|i| {
+            let et: ElementType = types.value(i).parse().map_err(ArrowError::ParseError)?;
+            let index = indices.value(i) as usize;
+            match mesh.block(et) {
+                Some(b) if index < b.len() => Ok((et, index)),
+                _ => Err(ArrowError::InvalidArgumentError(format!(
+                    "No {et:?} element {index} in the mesh"
+                ))),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut ets: Vec<ElementType> = rows.iter().map(|&(et, _)| et).collect();
+    ets.dedup();
+
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let (values, num_components) = match column.data_type() {
+            DataType::Float64 => (column.as_any().downcast_ref::<Float64Array>(), 1),
+            DataType::FixedSizeList(item, n) if item.data_type() == &DataType::Float64 => {
+                let list = column
+                    .as_any()
+                    .downcast_ref::<FixedSizeListArray>()
+                    .unwrap();
+                (
+                    list.values().as_any().downcast_ref::<Float64Array>(),
+                    *n as usize,
+                )
+            }
+            _ => continue,
+        };
+        let values = values.unwrap();
+        let offset = match column.as_any().downcast_ref::<FixedSizeListArray>() {
+            Some(list) => list.value_offset(0) as usize,
+            None => 0,
+        };
+        for &et in &ets {
+            let len = mesh.block(et).unwrap().len();
+            let mut block_values = nd::Array2::from_elem((len, num_components), f64::NAN);
+            for (row, &(_, index)) in rows.iter().enumerate().filter(|(_, r)| r.0 == et) {
+                if column.is_valid(row) {
+                    for k in 0..num_components {
+                        block_values[[index, k]] = values.value(offset + row * num_components + k);
+                    }
+                }
+            }
+            let block_values = match num_components {
+                1 => block_values.remove_axis(nd::Axis(1)).into_dyn(),
+                _ => block_values.into_dyn(),
+            };
+            mesh.add_field(et, field.name(), block_values.into_shared());
+        }
+    }
+    Ok(())
+}
+
+/// Writes the fields of a mesh as a Parquet file, as laid out by [`fields_to_record_batch`].
+#[cfg(feature = "parquet")]
+pub fn write_fields_parquet(
+    path: &std::path::Path,
+    mesh: &UMesh,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let batch = fields_to_record_batch(mesh)?;
+    let file = std::fs::File::create(path)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{RawValidation, UMeshView};
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_coords_round_trip() {
+        let mesh = me::make_imesh_2d(2);
+        let batch = coords_to_record_batch(&mesh).unwrap();
+        assert_eq!(batch.num_columns(), 2);
+        assert_eq!(coords_from_record_batch(&batch).unwrap(), mesh.coords());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_connectivity_zero_copy() {
+        let mesh = me::make_mesh_2d_multi();
+        let quads = connectivity_to_record_batch(&mesh, ElementType::QUAD4).unwrap();
+        let pgons = connectivity_to_record_batch(&mesh, ElementType::PGON).unwrap();
+        let blocks = [
+            block_slices(&quads, ElementType::QUAD4).unwrap(),
+            block_slices(&pgons, ElementType::PGON).unwrap(),
+        ];
+        let conn = mesh.regular_connectivity(ElementType::QUAD4).unwrap();
+        assert_eq!(blocks[0].connectivity.as_ptr(), conn.as_ptr());
+        assert_eq!(blocks[1].connectivity, &[0, 1, 4, 3, 2]);
+        assert_eq!(blocks[1].offsets, Some(&[5][..]));
+
+        let coords = mesh.coords().to_owned();
+        let view =
+            UMeshView::from_slices(coords.as_slice().unwrap(), 2, &blocks, RawValidation::Full)
+                .unwrap();
+        assert_eq!(view.num_elements(), 2);
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        let mut mesh = me::make_mesh_2d_multi();
+        let n = mesh.block(ElementType::QUAD4).unwrap().len();
+        let values = nd::Array1::from_iter((0..n).map(|i| i as f64)).into_dyn();
+        mesh.add_field(ElementType::QUAD4, "f", values.into_shared());
+        let batch = fields_to_record_batch(&mesh).unwrap();
+        assert_eq!(batch.num_rows(), mesh.num_elements());
+        assert_eq!(batch.column(2).null_count(), mesh.num_elements() - n);
+
+        let mut back = me::make_mesh_2d_multi();
+        fields_from_record_batch(&mut back, &batch).unwrap();
+        let quads = &back.block(ElementType::QUAD4).unwrap().fields["f"];
+        assert_eq!(quads.as_slice().unwrap()[n - 1], (n - 1) as f64);
+        let pgons = &back.block(ElementType::PGON).unwrap().fields["f"];
+        assert!(pgons.iter().all(|v| v.is_nan()));
+    }
+}
@@ -0,0 +1,334 @@
+//! Meshes of the zero level set of an implicit function.
+//!
+//! The function is sampled on a regular background grid whose cubes are split into 6
+//! tetrahedra, and the level set is extracted tetrahedron by tetrahedron (marching tetrahedra).
+//! Intersection points are shared between neighbouring tetrahedra, so the surface is watertight
+//! as long as the function is positive on the boundary of the bounding box.
+
+use nalgebra as na;
+use rustc_hash::FxHashMap;
+
+use crate::mesh::{ElementType, UMesh};
+
+use super::Assembler;
+
+/// Kuhn split of a cube, the same as [`crate::tools::RegularUMeshBuilder`] uses, so that the
+/// diagonals of two neighbouring cubes match.
+const CUBE_TETS: [[usize; 4]; 6] = [
+    [0, 1, 2, 6],
+    [0, 1, 6, 5],
+    [0, 3, 6, 2],
+    [0, 3, 7, 6],
+    [0, 4, 5, 6],
+    [0, 4, 6, 7],
+];
+
+/// Permutations of a prism mapping each vertex to the first position.
+const PRISM_ROTATIONS: [[usize; 6]; 6] = [
+    [0, 1, 2, 3, 4, 5],
+    [1, 2, 0, 4, 5, 3],
+    [2, 0, 1, 5, 3, 4],
+    [3, 5, 4, 0, 2, 1],
+    [4, 3, 5, 1, 0, 2],
+    [5, 4, 3, 2, 1, 0],
+];
+
+/// The function sampled on the nodes of a regular grid.
+struct SampledGrid {
+    min: [f64; 3],
+    step: [f64; 3],
+    n: [usize; 3],
+    values: Vec<f64>,
+}
+
+impl SampledGrid {
+    fn new<F>(f: F, bbox: [[f64; 3]; 2], resolution: [usize; 3]) -> Self
+    where
+        F: Fn(&[f64; 3]) -> f64,
+    {
+        assert!(
+            resolution.iter().all(|&k| k >= 1),
+            "Each axis needs at least one cell"
+        );
+        let [min, max] = bbox;
+        let step = [0, 1, 2].map(|i| (max[i] - min[i]) / resolution[i] as f64);
+        let n = resolution.map(|k| k + 1);
+        let mut grid = Self {
+            min,
+            step,
+            n,
+            values: Vec::with_capacity(n[0] * n[1] * n[2]),
+        };
+        for node in 0..n[0] * n[1] * n[2] {
+            let value = f(&grid.point(node));
+            grid.values.push(value);
+        }
+        grid
+    }
+
+    fn point(&self, node: usize) -> [f64; 3] {
+        let ijk = [
+            node % self.n[0],
+            (node / self.n[0]) % self.n[1],
+            node / (self.n[0] * self.n[1]),
+        ];
+        [0, 1, 2].map(|i| self.min[i] + self.step[i] * ijk[i] as f64)
+    }
+
+    fn inside(&self, node: usize) -> bool {
+        self.values[node] < 0.0
+    }
+
+    /// Background tetrahedra, given by their grid nodes.
+    fn tets(&self) -> impl Iterator<Item = [usize; 4]> + '_ {
+        let [nx, ny, nz] = self.n;
+        let node = move |i: usize, j: usize, k: usize| (k * ny + j) * nx + i;
+        itertools::iproduct!(0..nz - 1, 0..ny - 1, 0..nx - 1).flat_map(move |(k, j, i)| {
+            let cube = [
+                node(i, j, k),
+                node(i + 1, j, k),
+                node(i + 1, j + 1, k),
+                node(i, j + 1, k),
+                node(i, j, k + 1),
+                node(i + 1, j, k + 1),
+                node(i + 1, j + 1, k + 1),
+                node(i, j + 1, k + 1),
+            ];
+            CUBE_TETS.map(|tet| tet.map(|v| cube[v]))
+        })
+    }
+}
+
+/// Output mesh nodes, either grid nodes or points on grid edges, keyed by their grid nodes.
+struct Nodes<'a> {
+    grid: &'a SampledGrid,
+    keys: FxHashMap<(usize, usize), usize>,
+    mesh: Assembler,
+}
+
+impl<'a> Nodes<'a> {
+    fn new(grid: &'a SampledGrid) -> Self {
+        Self {
+            grid,
+            keys: FxHashMap::default(),
+            mesh: Assembler::new(3),
+        }
+    }
+
+    fn grid_node(&mut self, a: usize) -> usize {
+        let (grid, mesh) = (self.grid, &mut self.mesh);
+        *self
+            .keys
+            .entry((a, a))
+            .or_insert_with(|| mesh.node(&grid.point(a)))
+    }
+
+    /// Node where the level set cuts the grid edge `(a, b)`.
+    fn edge_node(&mut self, a: usize, b: usize) -> usize {
+        let (a, b) = (a.min(b), a.max(b));
+        let (grid, mesh) = (self.grid, &mut self.mesh);
+        *self.keys.entry((a, b)).or_insert_with(|| {
+            let (fa, fb) = (grid.values[a], grid.values[b]);
+            let t = fa / (fa - fb);
+            let (pa, pb) = (grid.point(a), grid.point(b));
+            mesh.node(&[0, 1, 2].map(|i| pa[i] + t * (pb[i] - pa[i])))
+        })
+    }
+
+    fn coord(&self, node: usize) -> na::Point3<f64> {
+        let c = &self.mesh.coords[3 * node..3 * node + 3];
+        na::Point3::new(c[0], c[1], c[2])
+    }
+
+    /// Adds a triangle oriented so that its normal points towards `outside`.
+    fn triangle(&mut self, mut tri: [usize; 3], outside: na::Vector3<f64>) {
+        let p0 = self.coord(tri[0]);
+        let normal = (self.coord(tri[1]) - p0).cross(&(self.coord(tri[2]) - p0));
+        if normal.dot(&outside) < 0.0 {
+            tri.swap(1, 2);
+        }
+        self.mesh.cell_in(ElementType::TRI3, &tri, "boundary");
+    }
+
+    /// Adds a tetrahedron with a positive volume.
+    fn tetrahedron(&mut self, mut tet: [usize; 4]) {
+        let p0 = self.coord(tet[0]);
+        let volume = (self.coord(tet[1]) - p0)
+            .cross(&(self.coord(tet[2]) - p0))
+            .dot(&(self.coord(tet[3]) - p0));
+        if volume < 0.0 {
+            tet.swap(2, 3);
+        }
+        self.mesh.cell(ElementType::TET4, &tet);
+    }
+
+    /// Splits a prism (`v[0..3]` below `v[3..6]`) into 3 tetrahedra.
+    ///
+    /// The quadrangular faces are cut along the diagonal holding their smallest node, which
+    /// gives the same cut on both sides of a face.
+    fn prism(&mut self, v: [usize; 6]) {
+        let first = (0..6).min_by_key(|&i| v[i]).unwrap();
+        let v = PRISM_ROTATIONS[first].map(|i| v[i]);
+        let tets = if v[1].min(v[5]) < v[2].min(v[4]) {
+            [
+                [v[0], v[1], v[2], v[5]],
+                [v[0], v[1], v[5], v[4]],
+                [v[0], v[4], v[5], v[3]],
+            ]
+        } else {
+            [
+                [v[0], v[1], v[2], v[4]],
+                [v[0], v[4], v[2], v[5]],
+                [v[0], v[4], v[5], v[3]],
+            ]
+        };
+        for tet in tets {
+            self.tetrahedron(tet);
+        }
+    }
+}
+
+fn implicit_mesh<F>(f: F, bbox: [[f64; 3]; 2], resolution: [usize; 3], solid: bool) -> UMesh
+where
+    F: Fn(&[f64; 3]) -> f64,
+{
+    let grid = SampledGrid::new(f, bbox, resolution);
+    let mut nodes = Nodes::new(&grid);
+    for tet in grid.tets() {
+        let (inside, outside): (Vec<usize>, Vec<usize>) =
+            tet.iter().partition(|&&v| grid.inside(v));
+        if inside.is_empty() || (outside.is_empty() && !solid) {
+            continue;
+        }
+        // Direction from the inside to the outside part of the tetrahedron.
+        let center = |vs: &[usize]| {
+            vs.iter()
+                .map(|&v| na::Point3::from(grid.point(v)).coords)
+                .sum::<na::Vector3<f64>>()
+                / vs.len() as f64
+        };
+        match (inside.as_slice(), outside.as_slice()) {
+            (&[a, b, c, d], []) => {
+                let tet = [a, b, c, d].map(|v| nodes.grid_node(v));
+                nodes.tetrahedron(tet);
+            }
+            (&[a], &[b, c, d]) => {
+                let cut = [(a, b), (a, c), (a, d)].map(|(p, q)| nodes.edge_node(p, q));
+                nodes.triangle(cut, center(&outside) - center(&inside));
+                if solid {
+                    let a = nodes.grid_node(a);
+                    nodes.tetrahedron([a, cut[0], cut[1], cut[2]]);
+                }
+            }
+            (&[a, b, c], &[d]) => {
+                let cut = [(a, d), (b, d), (c, d)].map(|(p, q)| nodes.edge_node(p, q));
+                nodes.triangle(cut, center(&outside) - center(&inside));
+                if solid {
+                    let [a, b, c] = [a, b, c].map(|v| nodes.grid_node(v));
+                    nodes.prism([a, b, c, cut[0], cut[1], cut[2]]);
+                }
+            }
+            (&[a, b], &[c, d]) => {
+                let [ac, bc, bd, ad] =
+                    [(a, c), (b, c), (b, d), (a, d)].map(|(p, q)| nodes.edge_node(p, q));
+                let direction = center(&outside) - center(&inside);
+                // Same diagonal as the one of the prism face.
+                if ac.min(bd) < bc.min(ad) {
+                    nodes.triangle([ac, bc, bd], direction);
+                    nodes.triangle([ac, bd, ad], direction);
+                } else {
+                    nodes.triangle([bc, bd, ad], direction);
+                    nodes.triangle([bc, ad, ac], direction);
+                }
+                if solid {
+                    let [a, b] = [a, b].map(|v| nodes.grid_node(v));
+                    nodes.prism([a, ac, ad, b, bc, bd]);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    nodes.mesh.build()
+}
+
+/// Builds a triangulated surface of the zero level set of `f`.
+///
+/// `f` is negative inside the shape and positive outside. It is sampled on a regular grid of
+/// `resolution` cells spanning `bbox` (`[min, max]`). Triangles are oriented outward and all
+/// belong to the group `boundary`. The surface is watertight if `f` is positive on the boundary
+/// of `bbox`.
+///
+/// ```ignore
+/// let sphere = from_implicit(
+///     |p| p[0] * p[0] + p[1] * p[1] + p[2] * p[2] - 1.0,
+///     [[-1.5; 3], [1.5; 3]],
+///     [30; 3],
+/// );
+/// ```
+pub fn from_implicit<F>(f: F, bbox: [[f64; 3]; 2], resolution: [usize; 3]) -> UMesh
+where
+    F: Fn(&[f64; 3]) -> f64,
+{
+    implicit_mesh(f, bbox, resolution, false)
+}
+
+/// Builds a tetrahedral mesh of the region where `f` is negative, with its boundary.
+///
+/// The tetrahedra are the background grid tetrahedra clipped by the level set, so this does not
+/// need a Delaunay mesher, but elements next to the surface may be badly shaped. The boundary
+/// is the same as the one built by [`from_implicit`], with TRI3 elements in group `boundary`.
+pub fn from_implicit_solid<F>(f: F, bbox: [[f64; 3]; 2], resolution: [usize; 3]) -> UMesh
+where
+    F: Fn(&[f64; 3]) -> f64,
+{
+    implicit_mesh(f, bbox, resolution, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::Descendable;
+    use approx::assert_relative_eq;
+    use std::f64::consts::PI;
+
+    fn sphere(p: &[f64; 3]) -> f64 {
+        p[0] * p[0] + p[1] * p[1] + p[2] * p[2] - 1.0
+    }
+
+    fn tet_volume(mesh: &UMesh, tet: &[usize]) -> f64 {
+        let p = |i: usize| {
+            let c = mesh.coords().row(tet[i]).to_owned();
+            na::Point3::new(c[0], c[1], c[2])
+        };
+        (p(1) - p(0)).cross(&(p(2) - p(0))).dot(&(p(3) - p(0))) / 6.0
+    }
+
+    #[test]
+    fn test_from_implicit() {
+        let mesh = from_implicit(sphere, [[-1.5; 3], [1.5; 3]], [13; 3]);
+        assert!(mesh.block(ElementType::TRI3).unwrap().len() > 100);
+        for p in mesh.coords().rows() {
+            assert_relative_eq!(p.dot(&p).sqrt(), 1.0, epsilon = 0.1);
+        }
+        // Watertight: the surface has no boundary edge.
+        let edges = mesh.boundaries(None, None);
+        assert!(edges.block(ElementType::SEG2).is_none_or(|b| b.len() == 0));
+    }
+
+    #[test]
+    fn test_from_implicit_solid() {
+        let mesh = from_implicit_solid(sphere, [[-1.5; 3], [1.5; 3]], [17; 3]);
+        let tets = mesh.block(ElementType::TET4).unwrap();
+        let volume: f64 = tets
+            .connectivity
+            .iter()
+            .map(|tet| tet_volume(&mesh, tet))
+            .inspect(|&v| assert!(v >= 0.0))
+            .sum();
+        assert_relative_eq!(volume, 4.0 / 3.0 * PI, max_relative = 0.05);
+        assert_eq!(
+            mesh.group_as_element_ids("boundary").len(),
+            mesh.block(ElementType::TRI3).unwrap().len()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Mesh builders.
+//!
+//! This module provides parametric meshes of common shapes, meshes of implicit surfaces and of
+//! voxel images, and patterns replicating a unit cell. Structured grids are built with
+//! [`crate::tools::RegularUMeshBuilder`].
+
+use std::collections::BTreeMap;
+
+use ndarray as nd;
+use rustc_hash::FxHashMap;
+
+use crate::mesh::{ElementIds, ElementType, UMesh};
+
+/// Surface and volume meshes of the zero level set of a function (marching tetrahedra).
+pub mod implicit;
+/// Linear, circular and mirror copies of a unit-cell mesh.
+pub mod pattern;
+/// Parametric meshes of common shapes (disk, annulus, cylinder, sphere, box shell).
+pub mod primitives;
+/// Hexahedral meshes of 3D images.
+pub mod voxels;
+
+pub use implicit::*;
+pub use voxels::*;
+
+/// Collects nodes, cells and groups before building a mesh.
+struct Assembler {
+    dim: usize,
+    coords: Vec<f64>,
+    keys: FxHashMap<[i64; 3], usize>,
+    cells: BTreeMap<ElementType, Vec<usize>>,
+    groups: BTreeMap<&'static str, ElementIds>,
+}
+
+impl Assembler {
+    fn new(dim: usize) -> Self {
+        Self {
+            dim,
+            coords: Vec::new(),
+            keys: FxHashMap::default(),
+            cells: BTreeMap::new(),
+            groups: BTreeMap::new(),
+        }
+    }
+
+    fn node(&mut self, coord: &[f64]) -> usize {
+        debug_assert_eq!(coord.len(), self.dim);
+        self.coords.extend_from_slice(coord);
+        self.coords.len() / self.dim - 1
+    }
+
+    /// Returns the node with the given integer key, creating it at `coord(key)` if needed.
+    fn keyed_node<F>(&mut self, key: [i64; 3], coord: F) -> usize
+    where
+        F: FnOnce([i64; 3]) -> Vec<f64>,
+    {
+        match self.keys.get(&key) {
+            Some(&node) => node,
+            None => {
+                let node = self.node(&coord(key));
+                self.keys.insert(key, node);
+                node
+            }
+        }
+    }
+
+    fn cell(&mut self, et: ElementType, connectivity: &[usize]) -> usize {
+        let cells = self.cells.entry(et).or_default();
+        cells.extend_from_slice(connectivity);
+        cells.len() / connectivity.len() - 1
+    }
+
+    fn cell_in(&mut self, et: ElementType, connectivity: &[usize], group: &'static str) {
+        let index = self.cell(et, connectivity);
+        self.groups.entry(group).or_default().add(et, index);
+    }
+
+    fn build(self) -> UMesh {
+        let n_nodes = self.coords.len() / self.dim;
+        let coords = nd::Array2::from_shape_vec((n_nodes, self.dim), self.coords).unwrap();
+        let mut mesh = UMesh::new(coords.into_shared());
+        for (et, cells) in self.cells {
+            let n = et.num_nodes().unwrap();
+            let connectivity = nd::Array2::from_shape_vec((cells.len() / n, n), cells).unwrap();
+            mesh.add_regular_block(et, connectivity.into_shared(), None);
+        }
+        for (name, ids) in self.groups {
+            mesh.set_group(name, &ids);
+        }
+        mesh
+    }
+}
@@ -0,0 +1,530 @@
+//! Replication of a unit-cell mesh (linear, circular and mirror patterns).
+//!
+//! A pattern appends transformed copies of a mesh: coordinates are stacked copy after copy and
+//! each block holds the elements of all the copies, in the same order. Fields, families and
+//! groups are replicated with the elements, and node groups with the nodes. Nodes on the
+//! interfaces between copies can be merged with a tolerance.
+//!
+//! Fields are copied as they are, unless they are listed with their [`FieldKind`]: the vectors
+//! and tensors of the rotated or mirrored copies are then rotated or mirrored with them. The
+//! values at the element nodes of mirrored copies follow the renumbering of their nodes, values
+//! at integration points cannot be mirrored.
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use nalgebra as na;
+use ndarray as nd;
+
+use crate::mesh::{Connectivity, ElementType, FieldLocation, UMesh};
+use crate::tools::merge_nodes;
+use crate::topology::templates;
+
+/// How the values of a field change when the mesh is rotated or mirrored.
+///
+/// The components of vectors and tensors are the trailing axes of the field values, of the space
+/// dimension of the mesh: a vector field has shape `[n_elem, ..., space_dim]` and a tensor field
+/// `[n_elem, ..., space_dim, space_dim]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    /// Values left unchanged, such as temperatures or pressures.
+    #[default]
+    Scalar,
+    /// Vectors `v`, such as displacements or velocities, mapped to `L v` by the linear part `L` of
+    /// the transformation.
+    Vector,
+    /// Pseudo-vectors, such as angular velocities or magnetic fields, mapped to `det(L) L v`:
+    /// unlike vectors, they are flipped by mirrors.
+    PseudoVector,
+    /// Second order tensors `T`, such as stresses or strains, mapped to `L T Lᵀ`.
+    Tensor,
+}
+
+impl FieldKind {
+    /// The number of trailing axes holding the components.
+    fn order(self) -> usize {
+        match self {
+            FieldKind::Scalar => 0,
+            FieldKind::Vector | FieldKind::PseudoVector => 1,
+            FieldKind::Tensor => 2,
+        }
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = String;
+
+    /// Parses a field kind name, case insensitively.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "scalar" => Ok(FieldKind::Scalar),
+            "vector" => Ok(FieldKind::Vector),
+            "pseudovector" | "pseudo_vector" => Ok(FieldKind::PseudoVector),
+            "tensor" => Ok(FieldKind::Tensor),
+            _ => Err(format!("Unknown field kind: {name}")),
+        }
+    }
+}
+
+/// An affine map `x -> linear * x + translation`, 2D points lying in the `z = 0` plane.
+#[derive(Clone, Copy, Debug)]
+struct Affine {
+    linear: na::Matrix3<f64>,
+    translation: na::Vector3<f64>,
+}
+
+impl Affine {
+    fn identity() -> Self {
+        Self {
+            linear: na::Matrix3::identity(),
+            translation: na::Vector3::zeros(),
+        }
+    }
+
+    fn apply(&self, p: &[f64]) -> Vec<f64> {
+        let mut x = na::Vector3::zeros();
+        x.as_mut_slice()[..p.len()].copy_from_slice(p);
+        let y = self.linear * x + self.translation;
+        y.as_slice()[..p.len()].to_vec()
+    }
+
+    fn reverses_orientation(&self) -> bool {
+        self.linear.determinant() < 0.0
+    }
+
+    /// Maps the components of a vector or a tensor of a field of kind `kind`, in place.
+    fn apply_to_field(&self, mut values: nd::ArrayViewMut1<f64>, kind: FieldKind) {
+        let d = match kind.order() {
+            0 => return,
+            1 => values.len(),
+            _ => values.len().isqrt(),
+        };
+        let linear = nd::Array2::from_shape_fn((d, d), |(i, j)| self.linear[(i, j)]);
+        let sign = match kind == FieldKind::PseudoVector && self.reverses_orientation() {
+            true => -1.0,
+            false => 1.0,
+        };
+        let mapped = match kind.order() {
+            1 => linear.dot(&values) * sign,
+            _ => {
+                let tensor = values.to_owned().into_shape_with_order((d, d)).unwrap();
+                let mapped = linear.dot(&tensor).dot(&linear.t());
+                mapped.into_shape_with_order(d * d).unwrap()
+            }
+        };
+        values.assign(&mapped);
+    }
+}
+
+fn vector3(v: &[f64], space_dim: usize) -> Result<na::Vector3<f64>, String> {
+    if v.len() != space_dim {
+        return Err(format!(
+            "Vectors must have the space dimension of the mesh {space_dim}, got {}",
+            v.len()
+        ));
+    }
+    let mut res = na::Vector3::zeros();
+    res.as_mut_slice()[..v.len()].copy_from_slice(v);
+    Ok(res)
+}
+
+/// Renumbers the values at the element nodes of the copies reversed by their transformation,
+/// as their nodes.
+fn reverse_element_nodes(
+    name: &str,
+    values: &mut nd::ArrayD<f64>,
+    et: ElementType,
+    transforms: &[Affine],
+) -> Result<(), String> {
+    if values.ndim() < 2 {
+        return Err(format!(
+            "Field {name} has shape {:?}, expected values at the element nodes",
+            values.shape()
+        ));
+    }
+    let permutation: Vec<usize> = match (et, templates::reversed(et)) {
+        (_, Some(permutation)) => permutation.to_vec(),
+        (ElementType::PHED, None) => {
+            return Err(format!(
+                "Field {name} at the nodes of polyhedra cannot be mirrored"
+            ));
+        }
+        (_, None) => (0..values.shape()[1]).rev().collect(),
+    };
+    let per_copy = values.len_of(nd::Axis(0)) / transforms.len().max(1);
+    for (k, t) in transforms.iter().enumerate() {
+        if t.reverses_orientation() {
+            let range = nd::Slice::from(k * per_copy..(k + 1) * per_copy);
+            let mut copy = values.slice_axis_mut(nd::Axis(0), range);
+            let permuted = copy.select(nd::Axis(1), &permutation);
+            copy.assign(&permuted);
+        }
+    }
+    Ok(())
+}
+
+/// Maps the values of each copy of a field by its transformation.
+///
+/// An error is returned if the trailing axes of the field do not have the space dimension.
+fn transform_field(
+    name: &str,
+    values: nd::ArrayD<f64>,
+    kind: FieldKind,
+    transforms: &[Affine],
+    space_dim: usize,
+) -> Result<nd::ArrayD<f64>, String> {
+    let order = kind.order();
+    let shape = values.shape().to_vec();
+    if shape.len() <= order || shape[shape.len() - order..].iter().any(|&s| s != space_dim) {
+        return Err(format!(
+            "Field {name} has shape {shape:?}, expected {order} trailing axes of size {space_dim}"
+        ));
+    }
+    let components = space_dim.pow(order as u32);
+    let per_copy = values.len() / (transforms.len() * components).max(1);
+    let mut copies = values
+        .into_shape_with_order((transforms.len(), per_copy, components))
+        .unwrap();
+    for (t, mut copy) in transforms.iter().zip(copies.outer_iter_mut()) {
+        for value in copy.rows_mut() {
+            t.apply_to_field(value, kind);
+        }
+    }
+    Ok(copies.into_shape_with_order(shape).unwrap())
+}
+
+/// Appends the images of `mesh` by each transformation, then merges close nodes if asked.
+///
+/// An error is returned if a field of `fields` is not in the mesh or does not have the shape of
+/// its kind, or if a transformation reversing the orientation would mirror a field at
+/// integration points or at the nodes of polyhedra.
+fn replicate(
+    mesh: &UMesh,
+    transforms: &[Affine],
+    fields: &[(&str, FieldKind)],
+    merge_eps: Option<f64>,
+) -> Result<UMesh, String> {
+    for (name, _) in fields {
+        if !mesh.blocks().any(|(_, b)| b.fields.contains_key(*name)) {
+            return Err(format!("Field {name} is not in the mesh"));
+        }
+    }
+    let reversing = transforms.iter().any(Affine::reverses_orientation);
+    let n_nodes = mesh.coords.nrows();
+    let space_dim = mesh.space_dimension();
+    let coords: Vec<f64> = transforms
+        .iter()
+        .flat_map(|t| {
+            mesh.coords
+                .rows()
+                .into_iter()
+                .flat_map(|p| t.apply(&p.to_vec()))
+        })
+        .collect();
+    let coords =
+        nd::Array2::from_shape_vec((n_nodes * transforms.len(), space_dim), coords).unwrap();
+    let mut res = UMesh::new(coords.into_shared());
+    for (&et, block) in mesh.blocks() {
+        let tiled: Vec<usize> = (0..transforms.len()).flat_map(|_| 0..block.len()).collect();
+        let elements = transforms.iter().enumerate().flat_map(|(k, t)| {
+            block.connectivity.iter().map(move |nodes| {
+                let mut nodes = nodes.to_vec();
+                if t.reverses_orientation() {
+                    templates::reverse(et, &mut nodes);
+                }
+                nodes
+                    .into_iter()
+                    .map(|n| n + k * n_nodes)
+                    .collect::<Vec<_>>()
+            })
+        });
+        let connectivity = match &block.connectivity {
+            Connectivity::Regular(conn) => {
+                let data: Vec<usize> = elements.flatten().collect();
+                let conn = nd::Array2::from_shape_vec((tiled.len(), conn.ncols()), data).unwrap();
+                Connectivity::new_regular(conn.into_shared())
+            }
+            Connectivity::Poly(_) => {
+                let mut data = Vec::new();
+                let mut offsets = Vec::with_capacity(tiled.len());
+                for nodes in elements {
+                    data.extend(nodes);
+                    offsets.push(data.len());
+                }
+                Connectivity::new_poly(nd::ArcArray1::from(data), nd::ArcArray1::from(offsets))
+            }
+        };
+        let mut new_block = block.clone();
+        new_block.connectivity = connectivity;
+        new_block.families = block.families.select(nd::Axis(0), &tiled).into_shared();
+        for (name, field) in new_block.fields.iter_mut() {
+            let mut values = field.select(nd::Axis(0), &tiled);
+            match block.field_location(name) {
+                FieldLocation::ElementNodes if reversing => {
+                    reverse_element_nodes(name, &mut values, et, transforms)?
+                }
+                FieldLocation::GaussPoints { .. } if reversing => {
+                    return Err(format!(
+                        "Field {name} at integration points cannot be mirrored"
+                    ));
+                }
+                _ => {}
+            }
+            if let Some(&(_, kind)) = fields.iter().find(|(n, _)| n == name) {
+                values = transform_field(name, values, kind, transforms, space_dim)?;
+            }
+            *field = values.into_shared();
+        }
+        for field in new_block.typed_fields.values_mut() {
+            *field = field.select(&tiled);
+        }
+        for field in new_block.sparse_fields.values_mut() {
+            *field = field.select(&tiled);
+        }
+        res.element_blocks.insert(et, new_block);
+    }
+    res.node_groups = mesh
+        .node_groups
+        .iter()
+        .map(|(name, nodes)| {
+            let nodes = (0..transforms.len())
+                .flat_map(|k| nodes.iter().map(move |n| n + k * n_nodes))
+                .collect();
+            (name.clone(), nodes)
+        })
+        .collect();
+    if let Some(eps) = merge_eps {
+        merge_nodes(&mut res, eps);
+        res.prune_nodes();
+    }
+    Ok(res)
+}
+
+/// Replicates a mesh `n` times along `direction`.
+///
+/// Copy `k` (from `0` to `n - 1`) is translated by `k * direction`, so the result holds the
+/// original mesh and `n - 1` translated copies. If `merge_eps` is given, nodes closer than this
+/// distance are merged, which connects the copies along their interfaces.
+///
+/// An error is returned if the direction does not have the space dimension of the mesh.
+pub fn linear(
+    mesh: &UMesh,
+    direction: &[f64],
+    n: usize,
+    merge_eps: Option<f64>,
+) -> Result<UMesh, String> {
+    let direction = vector3(direction, mesh.space_dimension())?;
+    let transforms: Vec<_> = (0..n)
+        .map(|k| Affine {
+            translation: direction * k as f64,
+            ..Affine::identity()
+        })
+        .collect();
+    replicate(mesh, &transforms, &[], merge_eps)
+}
+
+/// Replicates a mesh `n` times around an axis, over a full turn.
+///
+/// Copy `k` (from `0` to `n - 1`) is rotated by `2 * k * PI / n` around the axis going through
+/// `origin` with direction `axis` (counterclockwise when looking against `axis`). For 2D meshes,
+/// the rotation is in the plane around `origin` and only the sign of `axis[2]` is used.
+///
+/// The values of the `fields` listed with their kind are rotated with each copy, other fields
+/// are copied unchanged. If `merge_eps` is given, nodes closer than this distance are merged.
+///
+/// # Errors
+/// Returns an error if the origin does not have the space dimension, if the axis is null, or if
+/// a listed field is not in the mesh or does not have the shape of its kind.
+pub fn circular(
+    mesh: &UMesh,
+    origin: &[f64],
+    axis: [f64; 3],
+    n: usize,
+    fields: &[(&str, FieldKind)],
+    merge_eps: Option<f64>,
+) -> Result<UMesh, String> {
+    let origin = vector3(origin, mesh.space_dimension())?;
+    let axis = match mesh.space_dimension() {
+        3 => na::Vector3::from(axis),
+        _ => na::Vector3::z() * axis[2].signum(),
+    };
+    let axis = na::Unit::try_new(axis, f64::EPSILON)
+        .ok_or("The rotation axis must not be null".to_owned())?;
+    let transforms: Vec<_> = (0..n)
+        .map(|k| {
+            let rotation = na::Rotation3::from_axis_angle(&axis, 2.0 * PI * k as f64 / n as f64);
+            Affine {
+                linear: *rotation.matrix(),
+                translation: origin - rotation * origin,
+            }
+        })
+        .collect();
+    replicate(mesh, &transforms, fields, merge_eps)
+}
+
+/// Appends the mirror image of a mesh.
+///
+/// The mirror is the plane (or the line, in 2D) through `origin` orthogonal to `normal`. The
+/// mirrored elements are renumbered to keep their orientation, see
+/// [`crate::topology::templates::reverse`], with their values at the element nodes. The values of the `fields` listed with their
+/// kind are mirrored in the image, pseudo-vectors being flipped. If `merge_eps` is given, nodes
+/// closer than this distance are merged, which connects the mesh to its image along the mirror.
+///
+/// # Errors
+/// Returns an error if the origin or the normal do not have the space dimension, if the normal
+/// is null, if a listed field is not in the mesh or does not have the shape of its kind, or if
+/// the mesh has fields at integration points or at the nodes of polyhedra.
+pub fn mirror(
+    mesh: &UMesh,
+    origin: &[f64],
+    normal: &[f64],
+    fields: &[(&str, FieldKind)],
+    merge_eps: Option<f64>,
+) -> Result<UMesh, String> {
+    let origin = vector3(origin, mesh.space_dimension())?;
+    let normal = na::Unit::try_new(vector3(normal, mesh.space_dimension())?, f64::EPSILON)
+        .ok_or("The mirror normal must not be null".to_owned())?;
+    let linear = na::Matrix3::identity() - 2.0 * normal.into_inner() * normal.transpose();
+    let transforms = [
+        Affine::identity(),
+        Affine {
+            linear,
+            translation: origin - linear * origin,
+        },
+    ];
+    replicate(mesh, &transforms, fields, merge_eps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementIds, FieldBase};
+    use crate::mesh_examples as me;
+    use crate::tools::measure::measure;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_linear() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 0);
+        mesh.set_group("corner", &ids);
+        let res = linear(&mesh, &[1.0, 0.0], 3, None).unwrap();
+        assert_eq!(res.coords().nrows(), 27);
+        assert_eq!(res.block(ElementType::QUAD4).unwrap().len(), 12);
+        assert_eq!(
+            res.group_as_element_ids("corner").get(&ElementType::QUAD4),
+            Some(&vec![0, 4, 8])
+        );
+        let res = linear(&mesh, &[1.0, 0.0], 3, Some(1e-9)).unwrap();
+        assert_eq!(res.coords().nrows(), 21);
+    }
+
+    #[test]
+    fn test_circular() {
+        let mesh = me::make_imesh_2d(2);
+        let res = circular(&mesh, &[0.0, 0.0], [0.0, 0.0, 1.0], 4, &[], Some(1e-9)).unwrap();
+        assert_eq!(res.coords().nrows(), 25);
+        let areas = &measure(res.view(), None)[&ElementType::QUAD4];
+        assert_relative_eq!(areas.sum(), 4.0, epsilon = 1e-12);
+        assert!(areas.iter().all(|&a| a > 0.0));
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mesh = me::make_imesh_2d(2);
+        let res = mirror(&mesh, &[1.0, 0.0], &[1.0, 0.0], &[], Some(1e-9)).unwrap();
+        assert_eq!(res.coords().nrows(), 15);
+        let areas = &measure(res.view(), None)[&ElementType::QUAD4];
+        assert_eq!(areas.len(), 8);
+        assert!(areas.iter().all(|&a| a > 0.0));
+    }
+
+    #[test]
+    fn test_mirror_element_nodes() {
+        let mut mesh = me::make_imesh_2d(1);
+        let nodes: Vec<f64> = mesh.block(ElementType::QUAD4).unwrap().connectivity[0]
+            .iter()
+            .map(|&n| n as f64)
+            .collect();
+        let values = nd::Array2::from_shape_vec((1, 4), nodes).unwrap();
+        let field = FieldBase::new([(ElementType::QUAD4, values.into_dyn().into_shared())].into())
+            .with_location(FieldLocation::ElementNodes);
+        mesh.update_field("nodes", field, None);
+
+        // The values follow the renumbered nodes of the image
+        let res = mirror(&mesh, &[0.0, 0.0], &[1.0, 0.0], &[], None).unwrap();
+        let block = res.block(ElementType::QUAD4).unwrap();
+        let n_nodes = mesh.coords().nrows();
+        let expected: Vec<f64> = block.connectivity[1]
+            .iter()
+            .map(|&n| (n - n_nodes) as f64)
+            .collect();
+        let values: Vec<f64> = block.fields["nodes"]
+            .index_axis(nd::Axis(0), 1)
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(values, expected);
+
+        let values = nd::arr2(&[[1.0]]).into_dyn().into_shared();
+        let field = FieldBase::new([(ElementType::QUAD4, values)].into())
+            .with_location(FieldLocation::GaussPoints { order: 1 });
+        mesh.update_field("gauss", field, None);
+        assert!(mirror(&mesh, &[0.0, 0.0], &[1.0, 0.0], &[], None).is_err());
+        assert!(linear(&mesh, &[1.0, 0.0], 2, None).is_ok());
+        assert!(linear(&mesh, &[1.0, 0.0, 0.0], 2, None).is_err());
+    }
+
+    #[test]
+    fn test_field_kinds() {
+        let mut mesh = me::make_imesh_3d(1);
+        let vector = nd::arr2(&[[1.0, 2.0, 3.0]]).into_dyn();
+        for name in ["u", "w", "t"] {
+            mesh.assign_field(name, None, vector.view()).unwrap();
+        }
+        let stress = nd::arr3(&[[[1.0, 4.0, 0.0], [4.0, 2.0, 0.0], [0.0, 0.0, 3.0]]]).into_dyn();
+        mesh.assign_field("s", None, stress.view()).unwrap();
+        let fields = [
+            ("u", FieldKind::Vector),
+            ("w", FieldKind::PseudoVector),
+            ("s", FieldKind::Tensor),
+        ];
+        let copy = |res: &UMesh, name: &str, k: usize| {
+            let values = &res.block(ElementType::HEX8).unwrap().fields[name];
+            values
+                .index_axis(nd::Axis(0), k)
+                .iter()
+                .copied()
+                .collect::<Vec<f64>>()
+        };
+
+        let res = mirror(&mesh, &[0.0; 3], &[1.0, 0.0, 0.0], &fields, None).unwrap();
+        assert_eq!(copy(&res, "u", 1), [-1.0, 2.0, 3.0]);
+        assert_eq!(copy(&res, "w", 1), [1.0, -2.0, -3.0]);
+        assert_eq!(copy(&res, "t", 1), [1.0, 2.0, 3.0]);
+        assert_eq!(
+            copy(&res, "s", 1),
+            [1.0, -4.0, 0.0, -4.0, 2.0, 0.0, 0.0, 0.0, 3.0]
+        );
+
+        // A quarter turn around z maps x to y, vectors and pseudo-vectors alike
+        let res = circular(&mesh, &[0.0; 3], [0.0, 0.0, 1.0], 4, &fields, None).unwrap();
+        for name in ["u", "w"] {
+            for (value, expected) in copy(&res, name, 1).into_iter().zip([-2.0, 1.0, 3.0]) {
+                assert_relative_eq!(value, expected, epsilon = 1e-12);
+            }
+        }
+        let expected = [2.0, -4.0, 0.0, -4.0, 1.0, 0.0, 0.0, 0.0, 3.0];
+        for (value, expected) in copy(&res, "s", 1).into_iter().zip(expected) {
+            assert_relative_eq!(value, expected, epsilon = 1e-12);
+        }
+        assert_eq!(
+            copy(&res, "s", 0),
+            stress.iter().copied().collect::<Vec<_>>()
+        );
+
+        assert_eq!("Pseudo_Vector".parse(), Ok(FieldKind::PseudoVector));
+        assert!("matrix".parse::<FieldKind>().is_err());
+    }
+}
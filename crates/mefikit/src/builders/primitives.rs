@@ -0,0 +1,505 @@
+//! Parametric meshes of common shapes.
+//!
+//! Every builder takes its resolution as a number of cells along each parametric direction and
+//! names its boundaries with groups of lower dimension elements (for example `inner` and `outer`
+//! segments for an annulus).
+
+use std::f64::consts::{FRAC_PI_4, PI};
+
+use rustc_hash::FxHashMap;
+
+use crate::mesh::{ElementType, UMesh};
+
+use super::Assembler;
+
+/// A planar mesh of quadrangles with its boundary loops.
+struct Layout2 {
+    points: Vec<[f64; 2]>,
+    quads: Vec<[usize; 4]>,
+    /// Closed, counterclockwise node loops.
+    loops: Vec<(&'static str, Vec<usize>)>,
+}
+
+fn circle_point(radius: f64, theta: f64) -> [f64; 2] {
+    [radius * theta.cos(), radius * theta.sin()]
+}
+
+fn annulus_layout(r_in: f64, r_out: f64, n_circ: usize, n_radial: usize) -> Layout2 {
+    assert!(n_circ >= 3, "A circle needs at least 3 cells");
+    assert!(n_radial >= 1, "At least one radial cell is needed");
+    let mut points = Vec::with_capacity((n_radial + 1) * n_circ);
+    let mut quads = Vec::with_capacity(n_radial * n_circ);
+    for l in 0..=n_radial {
+        let r = r_in + (r_out - r_in) * l as f64 / n_radial as f64;
+        for k in 0..n_circ {
+            points.push(circle_point(r, 2.0 * PI * k as f64 / n_circ as f64));
+        }
+    }
+    let ring = |l: usize, k: usize| l * n_circ + k % n_circ;
+    for l in 0..n_radial {
+        for k in 0..n_circ {
+            quads.push([
+                ring(l, k),
+                ring(l + 1, k),
+                ring(l + 1, k + 1),
+                ring(l, k + 1),
+            ]);
+        }
+    }
+    let inner = (0..n_circ).map(|k| ring(0, k)).collect();
+    let outer = (0..n_circ).map(|k| ring(n_radial, k)).collect();
+    Layout2 {
+        points,
+        quads,
+        loops: vec![("inner", inner), ("outer", outer)],
+    }
+}
+
+/// O-grid disk: a central square surrounded by a ring of quadrangles.
+fn disk_layout(radius: f64, n_circ: usize, n_radial: usize) -> Layout2 {
+    assert!(
+        n_circ >= 4 && n_circ.is_multiple_of(4),
+        "The number of cells around a disk must be a multiple of 4"
+    );
+    assert!(n_radial >= 1, "At least one radial cell is needed");
+    let n = n_circ / 4;
+    let a = 0.4 * radius;
+    let mut points = Vec::new();
+    let mut quads = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let (u, v) = (i as f64 / n as f64, j as f64 / n as f64);
+            points.push([-a + 2.0 * a * u, -a + 2.0 * a * v]);
+        }
+    }
+    let square = |i: usize, j: usize| j * (n + 1) + i;
+    for j in 0..n {
+        for i in 0..n {
+            quads.push([
+                square(i, j),
+                square(i + 1, j),
+                square(i + 1, j + 1),
+                square(i, j + 1),
+            ]);
+        }
+    }
+    // Square perimeter, counterclockwise from the (-a, -a) corner.
+    let perimeter: Vec<usize> = (0..n_circ)
+        .map(|k| match k / n {
+            0 => square(k, 0),
+            1 => square(n, k - n),
+            2 => square(3 * n - k, n),
+            _ => square(0, 4 * n - k),
+        })
+        .collect();
+    let mut previous = perimeter.clone();
+    for l in 1..=n_radial {
+        let t = l as f64 / n_radial as f64;
+        let current: Vec<usize> = (0..n_circ)
+            .map(|k| {
+                let s = points[perimeter[k]];
+                let theta = -3.0 * FRAC_PI_4 + 2.0 * PI * k as f64 / n_circ as f64;
+                let c = circle_point(radius, theta);
+                points.push([s[0] + (c[0] - s[0]) * t, s[1] + (c[1] - s[1]) * t]);
+                points.len() - 1
+            })
+            .collect();
+        for k in 0..n_circ {
+            let k1 = (k + 1) % n_circ;
+            quads.push([previous[k], current[k], current[k1], previous[k1]]);
+        }
+        previous = current;
+    }
+    Layout2 {
+        points,
+        quads,
+        loops: vec![("outer", previous)],
+    }
+}
+
+fn planar_mesh(layout: Layout2) -> UMesh {
+    let mut mesh = Assembler::new(2);
+    for p in &layout.points {
+        mesh.node(p);
+    }
+    for q in &layout.quads {
+        mesh.cell(ElementType::QUAD4, q);
+    }
+    for (name, nodes) in &layout.loops {
+        for k in 0..nodes.len() {
+            let seg = [nodes[k], nodes[(k + 1) % nodes.len()]];
+            mesh.cell_in(ElementType::SEG2, &seg, name);
+        }
+    }
+    mesh.build()
+}
+
+/// Extrudes a planar layout along z into hexahedra.
+///
+/// The bottom and top faces go into `bottom` and `top` groups, and the extruded boundary loops
+/// keep their names.
+fn extruded_mesh(layout: Layout2, height: f64, n_height: usize) -> UMesh {
+    assert!(
+        n_height >= 1,
+        "At least one cell along the height is needed"
+    );
+    let mut mesh = Assembler::new(3);
+    let np = layout.points.len();
+    for h in 0..=n_height {
+        let z = height * h as f64 / n_height as f64;
+        for p in &layout.points {
+            mesh.node(&[p[0], p[1], z]);
+        }
+    }
+    let node = |h: usize, p: usize| h * np + p;
+    for h in 0..n_height {
+        for q in &layout.quads {
+            let hexa: Vec<usize> = q
+                .iter()
+                .map(|&p| node(h, p))
+                .chain(q.iter().map(|&p| node(h + 1, p)))
+                .collect();
+            mesh.cell(ElementType::HEX8, &hexa);
+        }
+    }
+    for q in &layout.quads {
+        let [a, b, c, d] = *q;
+        mesh.cell_in(ElementType::QUAD4, &[a, d, c, b], "bottom");
+        let top = [a, b, c, d].map(|p| node(n_height, p));
+        mesh.cell_in(ElementType::QUAD4, &top, "top");
+    }
+    for (name, nodes) in &layout.loops {
+        for h in 0..n_height {
+            for k in 0..nodes.len() {
+                let (p0, p1) = (nodes[k], nodes[(k + 1) % nodes.len()]);
+                let quad = [node(h, p0), node(h, p1), node(h + 1, p1), node(h + 1, p0)];
+                mesh.cell_in(ElementType::QUAD4, &quad, name);
+            }
+        }
+    }
+    mesh.build()
+}
+
+/// Builds a structured quadrangle disk centered at the origin.
+///
+/// The disk is an O-grid: a central square of `(n_circ / 4)²` cells surrounded by `n_radial`
+/// rings of `n_circ` cells. The boundary circle is made of SEG2 elements in group `outer`.
+///
+/// # Panics
+/// Panics if `n_circ` is not a positive multiple of 4 or if `n_radial` is zero.
+pub fn disk(radius: f64, n_circ: usize, n_radial: usize) -> UMesh {
+    planar_mesh(disk_layout(radius, n_circ, n_radial))
+}
+
+/// Builds a structured quadrangle annulus centered at the origin.
+///
+/// The boundary circles are made of SEG2 elements in groups `inner` and `outer`.
+pub fn annulus(r_in: f64, r_out: f64, n_circ: usize, n_radial: usize) -> UMesh {
+    planar_mesh(annulus_layout(r_in, r_out, n_circ, n_radial))
+}
+
+/// Builds the lateral surface of a cylinder of axis z, from `z = 0` to `z = height`.
+///
+/// Quadrangles are oriented outward. The boundary circles are made of SEG2 elements in groups
+/// `bottom` and `top`.
+pub fn cylinder_shell(radius: f64, height: f64, n_circ: usize, n_height: usize) -> UMesh {
+    assert!(n_circ >= 3, "A circle needs at least 3 cells");
+    assert!(
+        n_height >= 1,
+        "At least one cell along the height is needed"
+    );
+    let mut mesh = Assembler::new(3);
+    for h in 0..=n_height {
+        let z = height * h as f64 / n_height as f64;
+        for k in 0..n_circ {
+            let [x, y] = circle_point(radius, 2.0 * PI * k as f64 / n_circ as f64);
+            mesh.node(&[x, y, z]);
+        }
+    }
+    let node = |h: usize, k: usize| h * n_circ + k % n_circ;
+    for h in 0..n_height {
+        for k in 0..n_circ {
+            let quad = [
+                node(h, k),
+                node(h, k + 1),
+                node(h + 1, k + 1),
+                node(h + 1, k),
+            ];
+            mesh.cell(ElementType::QUAD4, &quad);
+        }
+    }
+    for k in 0..n_circ {
+        mesh.cell_in(ElementType::SEG2, &[node(0, k), node(0, k + 1)], "bottom");
+        let top = [node(n_height, k), node(n_height, k + 1)];
+        mesh.cell_in(ElementType::SEG2, &top, "top");
+    }
+    mesh.build()
+}
+
+/// Builds a solid hexahedral cylinder of axis z, from `z = 0` to `z = height`.
+///
+/// Its section is the [`disk`] O-grid. The boundary faces are QUAD4 elements in groups `bottom`,
+/// `top` and `outer`, oriented outward.
+pub fn cylinder(
+    radius: f64,
+    height: f64,
+    n_circ: usize,
+    n_radial: usize,
+    n_height: usize,
+) -> UMesh {
+    extruded_mesh(disk_layout(radius, n_circ, n_radial), height, n_height)
+}
+
+/// Builds a triangulated sphere centered at the origin by subdividing an icosahedron.
+///
+/// Each subdivision splits every triangle into 4, so the mesh has `20 * 4^subdivisions`
+/// triangles, oriented outward. The surface is closed, hence it has no boundary group.
+pub fn icosphere(radius: f64, subdivisions: usize) -> UMesh {
+    let t = (1.0 + 5.0_f64.sqrt()) / 2.0;
+    let mut points: Vec<[f64; 3]> = vec![
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ];
+    let mut triangles: Vec<[usize; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for _ in 0..subdivisions {
+        let mut middles: FxHashMap<(usize, usize), usize> = FxHashMap::default();
+        let mut middle = |a: usize, b: usize, points: &mut Vec<[f64; 3]>| {
+            *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let (pa, pb) = (points[a], points[b]);
+                points.push([0, 1, 2].map(|i| (pa[i] + pb[i]) / 2.0));
+                points.len() - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = middle(a, b, &mut points);
+                let bc = middle(b, c, &mut points);
+                let ca = middle(c, a, &mut points);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    let mut mesh = Assembler::new(3);
+    for p in &points {
+        let norm = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+        mesh.node(&p.map(|x| radius * x / norm));
+    }
+    for tri in &triangles {
+        mesh.cell(ElementType::TRI3, tri);
+    }
+    mesh.build()
+}
+
+/// Quadrangles covering the surface of the integer box `[0, n[0]] x [0, n[1]] x [0, n[2]]`.
+///
+/// Quadrangles are given by their integer corners, oriented outward, with the name of the side
+/// they lie on.
+fn box_faces(n: [usize; 3]) -> Vec<(&'static str, [[i64; 3]; 4])> {
+    const NAMES: [[&str; 2]; 3] = [["xmin", "xmax"], ["ymin", "ymax"], ["zmin", "zmax"]];
+    let mut faces = Vec::new();
+    for axis in 0..3 {
+        for (side, &name) in NAMES[axis].iter().enumerate() {
+            let (t1, t2) = match side {
+                0 => ((axis + 2) % 3, (axis + 1) % 3),
+                _ => ((axis + 1) % 3, (axis + 2) % 3),
+            };
+            let corner = |i: usize, j: usize| {
+                let mut key = [0; 3];
+                key[axis] = (side * n[axis]) as i64;
+                key[t1] = i as i64;
+                key[t2] = j as i64;
+                key
+            };
+            for j in 0..n[t2] {
+                for i in 0..n[t1] {
+                    let quad = [
+                        corner(i, j),
+                        corner(i + 1, j),
+                        corner(i + 1, j + 1),
+                        corner(i, j + 1),
+                    ];
+                    faces.push((name, quad));
+                }
+            }
+        }
+    }
+    faces
+}
+
+/// Builds the quadrangle surface of an axis aligned box.
+///
+/// `n` gives the number of cells along each axis. Quadrangles are oriented outward and each
+/// side is a group named `xmin`, `xmax`, `ymin`, `ymax`, `zmin` or `zmax`.
+pub fn box_surface(min: [f64; 3], max: [f64; 3], n: [usize; 3]) -> UMesh {
+    assert!(
+        n.iter().all(|&k| k >= 1),
+        "Each axis needs at least one cell"
+    );
+    let mut mesh = Assembler::new(3);
+    let coord = |key: [i64; 3]| {
+        (0..3)
+            .map(|i| min[i] + (max[i] - min[i]) * key[i] as f64 / n[i] as f64)
+            .collect::<Vec<f64>>()
+    };
+    for (name, quad) in box_faces(n) {
+        let quad = quad.map(|key| mesh.keyed_node(key, coord));
+        mesh.cell_in(ElementType::QUAD4, &quad, name);
+    }
+    mesh.build()
+}
+
+/// Builds a hexahedral spherical shell centered at the origin.
+///
+/// The sphere is a cubed sphere: each of the 6 faces of a cube is split into `n x n` cells and
+/// projected on the sphere with an equiangular mapping. The shell has `n_layers` layers of cells
+/// between `r_in` and `r_out`. The inner and outer spheres are QUAD4 elements in groups `inner`
+/// and `outer`, oriented outward of the shell.
+pub fn spherical_shell(r_in: f64, r_out: f64, n: usize, n_layers: usize) -> UMesh {
+    assert!(n >= 1, "Each cube face needs at least one cell");
+    assert!(n_layers >= 1, "At least one layer is needed");
+    // Nodes and quadrangles on the unit sphere.
+    let mut directions: Vec<[f64; 3]> = Vec::new();
+    let mut keys: FxHashMap<[i64; 3], usize> = FxHashMap::default();
+    let mut quads: Vec<[usize; 4]> = Vec::new();
+    for (_, quad) in box_faces([n, n, n]) {
+        quads.push(quad.map(|key| {
+            *keys.entry(key).or_insert_with(|| {
+                let d = key.map(|k| (FRAC_PI_4 * (2 * k - n as i64) as f64 / n as f64).tan());
+                let norm = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+                directions.push(d.map(|x| x / norm));
+                directions.len() - 1
+            })
+        }));
+    }
+    let mut mesh = Assembler::new(3);
+    let np = directions.len();
+    for l in 0..=n_layers {
+        let r = r_in + (r_out - r_in) * l as f64 / n_layers as f64;
+        for d in &directions {
+            mesh.node(&d.map(|x| r * x));
+        }
+    }
+    let node = |l: usize, p: usize| l * np + p;
+    for l in 0..n_layers {
+        for q in &quads {
+            let hexa: Vec<usize> = q
+                .iter()
+                .map(|&p| node(l, p))
+                .chain(q.iter().map(|&p| node(l + 1, p)))
+                .collect();
+            mesh.cell(ElementType::HEX8, &hexa);
+        }
+    }
+    for &[a, b, c, d] in &quads {
+        mesh.cell_in(ElementType::QUAD4, &[a, d, c, b], "inner");
+        let outer = [a, b, c, d].map(|p| node(n_layers, p));
+        mesh.cell_in(ElementType::QUAD4, &outer, "outer");
+    }
+    mesh.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::measure::measure;
+    use approx::assert_relative_eq;
+
+    fn group_len(mesh: &UMesh, name: &str) -> usize {
+        mesh.group_as_element_ids(name).len()
+    }
+
+    #[test]
+    fn test_planar_primitives() {
+        let mesh = disk(1.0, 8, 1);
+        assert_eq!(mesh.coords().nrows(), 17);
+        assert_eq!(mesh.block(ElementType::QUAD4).unwrap().len(), 12);
+        assert_eq!(group_len(&mesh, "outer"), 8);
+        let areas = measure(mesh.view(), None);
+        assert!(areas[&ElementType::QUAD4].iter().all(|&a| a > 0.0));
+
+        let mesh = disk(1.0, 64, 8);
+        let area = measure(mesh.view(), None)[&ElementType::QUAD4].sum();
+        assert_relative_eq!(area, PI, max_relative = 1e-2);
+
+        let mesh = annulus(1.0, 2.0, 16, 3);
+        assert_eq!(mesh.block(ElementType::QUAD4).unwrap().len(), 48);
+        assert_eq!(group_len(&mesh, "inner"), 16);
+        assert_eq!(group_len(&mesh, "outer"), 16);
+        let areas = measure(mesh.view(), None);
+        assert!(areas[&ElementType::QUAD4].iter().all(|&a| a > 0.0));
+    }
+
+    #[test]
+    fn test_cylinders() {
+        let mesh = cylinder_shell(1.0, 2.0, 12, 4);
+        assert_eq!(mesh.coords().nrows(), 60);
+        assert_eq!(mesh.block(ElementType::QUAD4).unwrap().len(), 48);
+        assert_eq!(group_len(&mesh, "bottom"), 12);
+        assert_eq!(group_len(&mesh, "top"), 12);
+
+        let mesh = cylinder(1.0, 2.0, 8, 1, 2);
+        assert_eq!(mesh.coords().nrows(), 51);
+        assert_eq!(mesh.block(ElementType::HEX8).unwrap().len(), 24);
+        assert_eq!(group_len(&mesh, "bottom"), 12);
+        assert_eq!(group_len(&mesh, "top"), 12);
+        assert_eq!(group_len(&mesh, "outer"), 16);
+    }
+
+    #[test]
+    fn test_spheres() {
+        let mesh = icosphere(2.0, 1);
+        assert_eq!(mesh.coords().nrows(), 42);
+        assert_eq!(mesh.block(ElementType::TRI3).unwrap().len(), 80);
+        for p in mesh.coords().rows() {
+            assert_relative_eq!(p.dot(&p).sqrt(), 2.0, epsilon = 1e-12);
+        }
+
+        let mesh = spherical_shell(1.0, 2.0, 2, 1);
+        assert_eq!(mesh.coords().nrows(), 52);
+        assert_eq!(mesh.block(ElementType::HEX8).unwrap().len(), 24);
+        assert_eq!(group_len(&mesh, "inner"), 24);
+        assert_eq!(group_len(&mesh, "outer"), 24);
+    }
+
+    #[test]
+    fn test_box_surface() {
+        let mesh = box_surface([0.0; 3], [2.0, 1.0, 1.0], [2, 1, 1]);
+        assert_eq!(mesh.coords().nrows(), 12);
+        assert_eq!(mesh.block(ElementType::QUAD4).unwrap().len(), 10);
+        assert_eq!(group_len(&mesh, "xmin"), 1);
+        assert_eq!(group_len(&mesh, "ymax"), 2);
+        assert_eq!(group_len(&mesh, "zmin"), 2);
+    }
+}
@@ -0,0 +1,91 @@
+//! Hexahedral meshes of 3D images.
+
+use std::collections::BTreeMap;
+
+use ndarray as nd;
+
+use crate::mesh::{Dimension, ElementType, FieldOwned, UMesh};
+
+use super::Assembler;
+
+/// Builds a HEX8 mesh of the voxels of a 3D image whose value is at least `threshold`.
+///
+/// The voxel `[i, j, k]` of the image is the box of size `spacing` with its lower corner at
+/// `origin + [i, j, k] * spacing`. Neighbouring voxels share their nodes. Voxel values are stored
+/// in the cell field `value`.
+///
+/// This is meant to mesh CT scans or segmented images, `u8` labels as well as `f64` intensities.
+pub fn from_voxels<T>(
+    voxels: nd::ArrayView3<T>,
+    spacing: [f64; 3],
+    origin: [f64; 3],
+    threshold: f64,
+) -> UMesh
+where
+    T: Copy + Into<f64>,
+{
+    let mut mesh = Assembler::new(3);
+    let mut values = Vec::new();
+    let coord = |key: [i64; 3]| {
+        (0..3)
+            .map(|i| origin[i] + spacing[i] * key[i] as f64)
+            .collect::<Vec<f64>>()
+    };
+    for ((i, j, k), &value) in voxels.indexed_iter() {
+        let value: f64 = value.into();
+        if value < threshold {
+            continue;
+        }
+        let [i, j, k] = [i, j, k].map(|x| x as i64);
+        let hexa = [
+            [i, j, k],
+            [i + 1, j, k],
+            [i + 1, j + 1, k],
+            [i, j + 1, k],
+            [i, j, k + 1],
+            [i + 1, j, k + 1],
+            [i + 1, j + 1, k + 1],
+            [i, j + 1, k + 1],
+        ]
+        .map(|key| mesh.keyed_node(key, coord));
+        mesh.cell(ElementType::HEX8, &hexa);
+        values.push(value);
+    }
+    let mut mesh = mesh.build();
+    if !values.is_empty() {
+        let field = FieldOwned::new(BTreeMap::from([(
+            ElementType::HEX8,
+            nd::Array1::from(values),
+        )]));
+        mesh.update_field("value", field.into_shared().into_dyn(), Some(Dimension::D3));
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_voxels() {
+        let image = nd::arr3(&[[[3u8], [1]], [[2], [0]]]);
+        let mesh = from_voxels(image.view(), [1.0, 2.0, 0.5], [0.0; 3], 1.0);
+        assert_eq!(mesh.coords().nrows(), 16);
+        let hexs = mesh.block(ElementType::HEX8).unwrap();
+        assert_eq!(hexs.len(), 3);
+        let values = hexs.fields["value"].iter().copied().collect::<Vec<_>>();
+        assert_eq!(values, vec![3.0, 1.0, 2.0]);
+        let has_node = |x: f64, y: f64| {
+            mesh.coords()
+                .rows()
+                .into_iter()
+                .any(|p| p[0] == x && p[1] == y)
+        };
+        assert!(has_node(2.0, 2.0));
+        assert!(!has_node(2.0, 4.0));
+
+        let image = nd::Array3::from_elem((2, 2, 2), 0.5);
+        let mesh = from_voxels(image.view(), [1.0; 3], [0.0; 3], 0.6);
+        assert_eq!(mesh.coords().nrows(), 0);
+    }
+}
@@ -3,7 +3,11 @@
 //! Provides the [`ElementGeo`] trait for coordinate access, measures,
 //! bounding boxes, and centroid calculations.
 
+use super::ElementTopo;
+use super::is_in::{in_polygon, point_in_phed};
 use super::measures as mes;
+use crate::geometry::quadrature::{QuadratureRule, ReferenceShape};
+use crate::geometry::shape_functions;
 use crate::mesh::{ElementLike, ElementType};
 
 use nalgebra as na;
@@ -91,7 +95,9 @@ pub trait ElementGeo<'a>: ElementLike<'a> {
         match self.element_type() {
             VERTEX => 0.0,
             SEG2 => mes::dist1(self.coord1(0), self.coord1(1)),
-            _ => todo!(),
+            _ => self
+                .measure_curved(MEASURE_QUADRATURE_ORDER)
+                .unwrap_or_else(|| linear_measure(self)),
         }
     }
 
@@ -110,7 +116,10 @@ pub trait ElementGeo<'a>: ElementLike<'a> {
                 &self.coord2(2),
                 &self.coord2(3),
             ),
-            _ => todo!(),
+            PGON => mes::surf_pgon2(&self.coords2().copied().collect::<Vec<_>>()),
+            _ => self
+                .measure_curved(MEASURE_QUADRATURE_ORDER)
+                .unwrap_or_else(|| linear_measure(self)),
         }
     }
 
@@ -133,16 +142,223 @@ pub trait ElementGeo<'a>: ElementLike<'a> {
                 self.coord3_ref(2),
                 self.coord3_ref(3),
             ),
-            _ => todo!(),
+            PGON => mes::surf_pgon3(&self.coords3().copied().collect::<Vec<_>>()),
+            PHED => mes::vol_phed(&phed_faces(self)),
+            _ => self
+                .measure_curved(MEASURE_QUADRATURE_ORDER)
+                .unwrap_or_else(|| linear_measure(self)),
         }
     }
 
-    /// Returns `true` if the given point lies inside the element.
+    /// Computes the measure of the element from its shape functions, integrated with the Gauss
+    /// rule of the given order.
+    ///
+    /// High order elements are integrated on their curved geometry. The result is exact for
+    /// affine elements, and more generally when the jacobian determinant is a polynomial of
+    /// degree at most `order`. Returns `None` for poly elements or if no rule of this order is
+    /// available.
+    fn measure_curved(&self, order: usize) -> Option<f64> {
+        let rule = QuadratureRule::gauss(self.element_type(), order)?;
+        Some(self.integration_weights(&rule)?.iter().sum())
+    }
+
+    /// Returns the weights of a quadrature rule on the element: the weights of the reference rule
+    /// scaled by the jacobian measure at their point.
+    ///
+    /// They sum to the measure of the curved element, as computed by [`Self::measure_curved`].
+    /// Returns `None` for poly elements.
+    fn integration_weights(&self, rule: &QuadratureRule) -> Option<Vec<f64>> {
+        rule.points
+            .rows()
+            .into_iter()
+            .zip(&rule.weights)
+            .map(|(xi, w)| {
+                let (_, jac) = isoparametric_map(self, xi.as_slice().unwrap())?;
+                Some(w * jacobian_measure(&jac))
+            })
+            .collect()
+    }
+
+    /// Computes the centroid of the element from its shape functions, padded with zeros to 3D.
+    ///
+    /// This is the center of mass of the curved geometry, whereas [`Self::centroid3`] averages the
+    /// nodes. The integration follows [`Self::measure_curved`].
+    fn centroid_curved(&self, order: usize) -> Option<[f64; 3]> {
+        let rule = QuadratureRule::gauss(self.element_type(), order)?;
+        let mut measure = 0.0;
+        let mut moment = na::Vector3::zeros();
+        for (xi, w) in rule.points.rows().into_iter().zip(&rule.weights) {
+            let (x, jac) = isoparametric_map(self, xi.as_slice().unwrap())?;
+            let dm = w * jacobian_measure(&jac);
+            measure += dm;
+            moment += x * dm;
+        }
+        Some((moment / measure).into())
+    }
+
+    /// Returns the signed jacobian determinants of the mapping of the element from its reference
+    /// element, at the vertices of the reference element.
+    ///
+    /// They are all positive for a valid element, a negative or null determinant reveals an
+    /// inverted, tangled or degenerated element. Returns `None` for poly elements and when the
+    /// element dimension differs from the space dimension, as the sign is not defined then.
+    fn vertex_jacobians(&self) -> Option<Vec<f64>> {
+        let shape = ReferenceShape::of(self.element_type())?;
+        let dim = shape.dimension();
+        if dim == 0 || self.space_dimension() != dim {
+            return None;
+        }
+        shape
+            .vertices()
+            .rows()
+            .into_iter()
+            .map(|xi| {
+                let (_, jac) = isoparametric_map(self, xi.as_slice().unwrap())?;
+                Some(jacobian_determinant(&jac))
+            })
+            .collect()
+    }
+
+    /// Computes the signed measure of the element, negative when the element is inverted.
+    ///
+    /// Only defined when the element dimension is the space dimension, that is for areas in 2D
+    /// and volumes in 3D. Polygons are positive when counter-clockwise and polyhedra when their
+    /// faces are oriented outward, other elements when oriented as their reference element.
+    fn signed_measure(&self) -> Option<f64> {
+        use ElementType::*;
+        let space_dim = self.space_dimension();
+        match self.element_type() {
+            PGON if space_dim == 2 => Some(mes::surf_pgon2_signed(
+                &self.coords2().copied().collect::<Vec<_>>(),
+            )),
+            PHED if space_dim == 3 => Some(mes::vol_phed_signed(&phed_faces(self))),
+            et => {
+                let dim = ReferenceShape::of(et)?.dimension();
+                if dim == 0 || space_dim != dim {
+                    return None;
+                }
+                let rule = QuadratureRule::gauss(et, MEASURE_QUADRATURE_ORDER)?;
+                rule.points
+                    .rows()
+                    .into_iter()
+                    .zip(&rule.weights)
+                    .map(|(xi, w)| {
+                        let (_, jac) = isoparametric_map(self, xi.as_slice().unwrap())?;
+                        Some(w * jacobian_determinant(&jac))
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    /// Returns `true` if the given point lies inside the element, boundaries included.
+    ///
+    /// The point is mapped to reference coordinates with [`Self::to_reference`], so high order
+    /// elements are tested against their curved geometry. An element of lower dimension than the
+    /// space only contains the points lying on it. Polygons are supported in 2D only and
+    /// polyhedra in 3D only, they contain no point of another dimension.
     ///
     /// # Note
-    /// This method is not yet implemented.
-    fn is_point_inside(&self, _point: &[f64]) -> bool {
-        todo!()
+    /// Splines are not supported yet, they contain no point.
+    fn is_point_inside(&self, point: &[f64]) -> bool {
+        let et = self.element_type();
+        if et == ElementType::PGON {
+            let Ok(point) = point.try_into() else {
+                return false;
+            };
+            if self.space_dimension() != 2 {
+                return false;
+            }
+            let pgon: Vec<[f64; 2]> = self.coords2().copied().collect();
+            return in_polygon(point, &pgon);
+        }
+        if et == ElementType::PHED {
+            let Ok(point) = point.try_into() else {
+                return false;
+            };
+            if self.space_dimension() != 3 {
+                return false;
+            }
+            let co = self.connectivity();
+            let coords: Vec<[f64; 3]> = (0..co.len())
+                .map(|i| match co[i] {
+                    usize::MAX => [0.0; 3],
+                    _ => *self.coord3_ref(i),
+                })
+                .collect();
+            let faces: Vec<usize> = (0..co.len())
+                .map(|i| if co[i] == usize::MAX { usize::MAX } else { i })
+                .collect();
+            return point_in_phed(point, &coords, &faces);
+        }
+        let Some(shape) = ReferenceShape::of(et) else {
+            return false;
+        };
+        let Some(xi) = self.to_reference(point) else {
+            return false;
+        };
+        if !shape.contains(&xi, INSIDE_TOLERANCE) {
+            return false;
+        }
+        if shape.dimension() == point.len() {
+            return true;
+        }
+        let origin = padded(self.coord(0));
+        let size = (0..self.connectivity().len())
+            .map(|i| (padded(self.coord(i)) - origin).norm())
+            .fold(0.0, f64::max);
+        match isoparametric_map(self, &xi[..shape.dimension()]) {
+            Some((x, _)) => (x - padded(point)).norm() <= INSIDE_TOLERANCE * size,
+            None => false,
+        }
+    }
+
+    /// Returns the reference coordinates of a physical point.
+    ///
+    /// The reference coordinates are those of [`ReferenceShape`], padded with zeros to 3D. They
+    /// are computed in closed form for linear simplices and by Newton iterations on the mapping
+    /// given by the shape functions of the other elements, so high order elements are mapped
+    /// through their curved geometry. When the element has a lower dimension than the space,
+    /// this is the reference point of the orthogonal projection of `point` on the element.
+    ///
+    /// The point may lie outside of the element, its reference coordinates are then outside of
+    /// the reference element. Returns `None` for poly elements, degenerate elements, or when the
+    /// Newton iterations do not converge.
+    fn to_reference(&self, point: &[f64]) -> Option<[f64; 3]> {
+        let et = self.element_type();
+        let shape = ReferenceShape::of(et)?;
+        let dim = shape.dimension();
+        let point = padded(point);
+        let mut res = [0.0; 3];
+        let xi = match et {
+            ElementType::VERTEX => return Some(res),
+            ElementType::TRI3 | ElementType::TET4 => {
+                let corners: Vec<na::Vector3<f64>> =
+                    (0..=dim).map(|i| padded(self.coord(i))).collect();
+                let jac = na::Matrix3xX::from_fn(dim, |i, j| corners[j + 1][i] - corners[0][i]);
+                least_squares(&jac, &(point - corners[0]))?
+            }
+            _ => {
+                let center = shape.vertices().mean_axis(ndarray::Axis(0)).unwrap();
+                let mut xi = na::DVector::from_iterator(dim, center);
+                let mut converged = false;
+                for _ in 0..MAX_NEWTON_ITERATIONS {
+                    let (x, jac) = isoparametric_map(self, xi.as_slice())?;
+                    let delta = least_squares(&jac, &(point - x))?;
+                    xi += &delta;
+                    if delta.norm() < NEWTON_TOLERANCE {
+                        converged = true;
+                        break;
+                    }
+                }
+                if !converged {
+                    return None;
+                }
+                xi
+            }
+        };
+        res[..dim].copy_from_slice(xi.as_slice());
+        Some(res)
     }
 
     /// Computes the 2D axis-aligned bounding box of the element.
@@ -155,6 +371,33 @@ pub trait ElementGeo<'a>: ElementLike<'a> {
         AABB::from_points(self.coords3())
     }
 
+    /// Returns the coordinates of the simplices decomposing the element.
+    ///
+    /// Coordinates are padded with zeros to 3D, the decomposition is the one of
+    /// [`ElementTopo::to_simplexes`].
+    fn simplex_coords(&self) -> Vec<Vec<[f64; 3]>>
+    where
+        Self: Sized,
+    {
+        let point = |i: usize| {
+            let mut p = [0.0; 3];
+            for (x, &c) in p.iter_mut().zip(self.coord(i)) {
+                *x = c;
+            }
+            p
+        };
+        let connectivity = self.connectivity();
+        self.to_simplexes()
+            .into_iter()
+            .map(|(_, nodes)| {
+                nodes
+                    .iter()
+                    .map(|n| point(connectivity.iter().position(|m| m == n).unwrap()))
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Computes the 2D centroid of the element.
     fn centroid2(&self) -> [f64; 2] {
         let mut p: na::Point2<f64> = na::Point2::origin();
@@ -172,10 +415,117 @@ pub trait ElementGeo<'a>: ElementLike<'a> {
         }
         (p / (self.connectivity().len() as f64)).into()
     }
+
+    /// Computes the mean of the nodes of the element, padded with zeros to 3D.
+    ///
+    /// Unlike [`Self::centroid3`], coordinates may have any space dimension. The nodes of
+    /// polyhedra are counted once, although they appear in several faces.
+    fn node_mean(&self) -> [f64; 3] {
+        let co = self.connectivity();
+        let mut mean = [0.0; 3];
+        let mut num_nodes = 0;
+        for (i, node) in co.iter().enumerate() {
+            if *node == usize::MAX || co[..i].contains(node) {
+                continue;
+            }
+            mean.iter_mut().zip(self.coord(i)).for_each(|(m, x)| *m += x);
+            num_nodes += 1;
+        }
+        mean.map(|x| x / num_nodes as f64)
+    }
 }
 
 impl<'a, T> ElementGeo<'a> for T where T: ElementLike<'a> {}
 
+const MAX_NEWTON_ITERATIONS: usize = 30;
+const NEWTON_TOLERANCE: f64 = 1e-12;
+/// Tolerance of the point-in-element test, in reference coordinates.
+const INSIDE_TOLERANCE: f64 = 1e-10;
+/// Quadrature order of the measures which have no closed form.
+const MEASURE_QUADRATURE_ORDER: usize = 3;
+
+fn padded(coord: &[f64]) -> na::Vector3<f64> {
+    let mut p = na::Vector3::zeros();
+    p.as_mut_slice()[..coord.len()].copy_from_slice(coord);
+    p
+}
+
+/// Solves `jac * x = rhs` in the least squares sense, `None` if `jac` is rank deficient.
+fn least_squares(jac: &na::Matrix3xX<f64>, rhs: &na::Vector3<f64>) -> Option<na::DVector<f64>> {
+    let normal = jac.transpose() * jac;
+    Some(normal.try_inverse()? * jac.transpose() * rhs)
+}
+
+/// Image and jacobian at `xi` of the mapping of an element from its reference element.
+fn isoparametric_map<'a, E>(
+    element: &E,
+    xi: &[f64],
+) -> Option<(na::Vector3<f64>, na::Matrix3xX<f64>)>
+where
+    E: ElementLike<'a> + ?Sized,
+{
+    let (values, gradients) = shape_functions(element.element_type(), xi)?;
+    let mut x = na::Vector3::zeros();
+    let mut jac = na::Matrix3xX::zeros(gradients.ncols());
+    for (i, value) in values.iter().enumerate() {
+        let p = padded(element.coord(i));
+        x += p * *value;
+        for j in 0..gradients.ncols() {
+            jac.column_mut(j).axpy(gradients[[i, j]], &p, 1.0);
+        }
+    }
+    Some((x, jac))
+}
+
+/// Signed determinant of a square jacobian, whose dimension is both the element and the space
+/// dimension.
+fn jacobian_determinant(jac: &na::Matrix3xX<f64>) -> f64 {
+    let dim = jac.ncols();
+    na::DMatrix::from_fn(dim, dim, |i, j| jac[(i, j)]).determinant()
+}
+
+/// Coordinates of the faces of a polyhedron, separated by `usize::MAX` in its connectivity.
+fn phed_faces<'a, E>(element: &E) -> Vec<Vec<[f64; 3]>>
+where
+    E: ElementLike<'a> + ?Sized,
+{
+    let mut faces = vec![Vec::new()];
+    for (i, &node) in element.connectivity().iter().enumerate() {
+        match node {
+            usize::MAX => faces.push(Vec::new()),
+            _ => faces
+                .last_mut()
+                .unwrap()
+                .push(element.coord(i).try_into().unwrap()),
+        }
+    }
+    faces
+}
+
+/// Measure of the elements without a quadrature rule, on straight edges between their nodes.
+///
+/// This is the length of the polyline through the nodes of a spline, and zero for a polygon or
+/// a polyhedron flattened in a space of lower dimension.
+fn linear_measure<'a, E>(element: &E) -> f64
+where
+    E: ElementLike<'a> + ?Sized,
+{
+    match element.element_type() {
+        ElementType::SPLINE => (1..element.connectivity().len())
+            .map(|i| (padded(element.coord(i)) - padded(element.coord(i - 1))).norm())
+            .sum(),
+        _ => 0.0,
+    }
+}
+
+/// Ratio between the measure of an infinitesimal element and of its reference image.
+fn jacobian_measure(jac: &na::Matrix3xX<f64>) -> f64 {
+    match jac.ncols() {
+        0 => 1.0,
+        _ => (jac.transpose() * jac).determinant().max(0.0).sqrt(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,6 +593,272 @@ mod tests {
         assert_eq!(coords.len(), 3);
     }
 
+    #[test]
+    fn test_simplex_coords() {
+        let coords = nd::array![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let conn = &[0, 1, 2, 3];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::PGON,
+        );
+        let simplices = elem.simplex_coords();
+        assert_eq!(simplices.len(), 2);
+        assert_eq!(
+            simplices[1],
+            vec![[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_is_point_inside_pgon() {
+        let coords = nd::array![[0.0, 0.0], [2.0, 0.0], [3.0, 2.0], [0.0, 1.0]];
+        let groups = BTreeMap::new();
+        let pgon = Element::new(
+            0,
+            coords.view(),
+            None,
+            &0,
+            &groups,
+            &[0, 1, 2, 3],
+            ElementType::PGON,
+        );
+        assert!(pgon.is_point_inside(&[1.0, 0.5]));
+        assert!(!pgon.is_point_inside(&[3.0, 0.5]));
+        assert!(!pgon.is_point_inside(&[1.0, 0.5, 0.0]));
+    }
+
+    #[test]
+    fn test_is_point_inside_phed() {
+        let coords = nd::array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0]
+        ];
+        let m = usize::MAX;
+        let conn = &[0, 2, 1, m, 0, 1, 3, m, 1, 2, 3, m, 0, 3, 2];
+        let groups = BTreeMap::new();
+        let phed = Element::new(0, coords.view(), None, &0, &groups, conn, ElementType::PHED);
+        assert!(phed.is_point_inside(&[0.2, 0.2, 0.2]));
+        assert!(!phed.is_point_inside(&[0.5, 0.5, 0.5]));
+        assert!(!phed.is_point_inside(&[0.2, 0.2]));
+    }
+
+    #[test]
+    fn test_to_reference() {
+        let coords = nd::array![[0.0, 0.0], [2.0, 0.0], [3.0, 2.0], [0.0, 1.0]];
+        let conn = &[0, 1, 2, 3];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let quad = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::QUAD4,
+        );
+        let xi = quad.to_reference(&[2.0, 0.0]).unwrap();
+        assert_abs_diff_eq!(xi[0], 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[1], -1.0, epsilon = 1e-12);
+        // Image of (0.5, 0.5) by the bilinear mapping
+        let xi = quad.to_reference(&[2.0625, 1.3125]).unwrap();
+        assert_abs_diff_eq!(xi[0], 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[1], 0.5, epsilon = 1e-12);
+
+        let tri = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            &conn[..3],
+            ElementType::TRI3,
+        );
+        let xi = tri.to_reference(&[2.5, 1.0]).unwrap();
+        assert_abs_diff_eq!(xi[0], 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[1], 0.5, epsilon = 1e-12);
+        assert_eq!(xi[2], 0.0);
+    }
+
+    #[test]
+    fn test_to_reference_3d() {
+        let coords = nd::array![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 2.0],
+            [1.0, 0.0, 2.0],
+            [1.0, 1.0, 2.0],
+            [0.0, 1.0, 2.0]
+        ];
+        let conn = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let hexa = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::HEX8,
+        );
+        let xi = hexa.to_reference(&[0.25, 0.5, 1.5]).unwrap();
+        assert_abs_diff_eq!(xi[0], -0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[1], 0.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[2], 0.5, epsilon = 1e-12);
+
+        // Projection of a point on a triangle of the 3D space
+        let tri = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            &conn[..3],
+            ElementType::TRI3,
+        );
+        let xi = tri.to_reference(&[1.0, 0.5, 3.0]).unwrap();
+        assert_abs_diff_eq!(xi[0], 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(xi[1], 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_curved_tri6() {
+        // The edge (0, 1) is a parabola through (0.5, -0.25)
+        let coords = nd::array![
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [0.5, -0.25],
+            [0.5, 0.5],
+            [0.0, 0.5]
+        ];
+        let conn = &[0, 1, 2, 3, 4, 5];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let tri = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::TRI6,
+        );
+        // Triangle area plus the parabolic segment, 2 / 3 * 1 * 0.25
+        assert_abs_diff_eq!(tri.measure_curved(2).unwrap(), 2.0 / 3.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(tri.measure2(), 2.0 / 3.0, epsilon = 1e-12);
+        assert!(tri.is_point_inside(&[0.5, -0.1]));
+        assert!(!tri.is_point_inside(&[0.5, -0.3]));
+        assert!(tri.is_point_inside(&[0.2, 0.2]));
+        assert!(!tri.is_point_inside(&[0.6, 0.6]));
+        let xi = tri.to_reference(&[0.5, -0.25]).unwrap();
+        assert_abs_diff_eq!(xi[0], 0.5, epsilon = 1e-10);
+        assert_abs_diff_eq!(xi[1], 0.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_vertex_jacobians() {
+        let coords = nd::array![[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [0.0, 1.0]];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let element = |conn: &'static [usize], et| {
+            Element::new(0, coords.view(), None, &family, &groups, conn, et)
+        };
+        let quad = element(&[0, 1, 2, 3], ElementType::QUAD4);
+        assert_eq!(quad.vertex_jacobians().unwrap(), vec![0.5; 4]);
+        // Reversed orientation
+        let quad = element(&[0, 3, 2, 1], ElementType::QUAD4);
+        assert_eq!(quad.vertex_jacobians().unwrap(), vec![-0.5; 4]);
+        // Bow tie, tangled on one side only
+        let quad = element(&[0, 1, 3, 2], ElementType::QUAD4);
+        let jacobians = quad.vertex_jacobians().unwrap();
+        assert!(jacobians.iter().any(|&j| j > 0.0) && jacobians.iter().any(|&j| j < 0.0));
+        let tri = element(&[0, 1, 3], ElementType::TRI3);
+        assert_eq!(tri.vertex_jacobians().unwrap(), vec![2.0; 3]);
+        // No orientation for a segment of the plane
+        assert!(
+            element(&[0, 1], ElementType::SEG2)
+                .vertex_jacobians()
+                .is_none()
+        );
+        assert!(
+            element(&[0, 1, 2], ElementType::PGON)
+                .vertex_jacobians()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_signed_measure() {
+        let coords = nd::array![[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [0.0, 1.0]];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let element = |conn: &'static [usize], et| {
+            Element::new(0, coords.view(), None, &family, &groups, conn, et)
+        };
+        let measure = |conn: &'static [usize], et| element(conn, et).signed_measure().unwrap();
+        assert_abs_diff_eq!(
+            measure(&[0, 1, 2, 3], ElementType::QUAD4),
+            2.0,
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            measure(&[0, 3, 2, 1], ElementType::QUAD4),
+            -2.0,
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            measure(&[0, 2, 1], ElementType::TRI3),
+            -1.0,
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            measure(&[0, 3, 2, 1], ElementType::PGON),
+            -2.0,
+            epsilon = 1e-12
+        );
+        assert!(
+            element(&[0, 1], ElementType::SEG2)
+                .signed_measure()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_curved_seg3() {
+        let coords = nd::array![[0.0, 0.0], [2.0, 0.0], [1.0, 0.0]];
+        let conn = &[0, 1, 2];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let seg = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::SEG3,
+        );
+        assert_abs_diff_eq!(seg.measure2(), 2.0, epsilon = 1e-12);
+        let centroid = seg.centroid_curved(3).unwrap();
+        assert_abs_diff_eq!(centroid[0], 1.0, epsilon = 1e-12);
+        assert!(seg.is_point_inside(&[0.5, 0.0]));
+        assert!(!seg.is_point_inside(&[0.5, 0.1]));
+        assert!(!seg.is_point_inside(&[2.5, 0.0]));
+    }
+
     #[test]
     fn test_coord3() {
         let coords = nd::array![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
@@ -336,6 +952,22 @@ mod tests {
         assert_abs_diff_eq!(elem.measure2(), 1.0, epsilon = 1e-10);
     }
 
+    #[test]
+    fn test_measure2_spline() {
+        let coords = nd::array![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+        let groups = BTreeMap::new();
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &0,
+            &groups,
+            &[0, 1, 2],
+            ElementType::SPLINE,
+        );
+        assert_abs_diff_eq!(elem.measure2(), 2.0, epsilon = 1e-10);
+    }
+
     #[test]
     fn test_centroid2() {
         let coords = nd::array![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
@@ -356,6 +988,21 @@ mod tests {
         assert_abs_diff_eq!(centroid[1], 1.0 / 3.0, epsilon = 1e-10);
     }
 
+    #[test]
+    fn test_node_mean_phed() {
+        let coords = nd::array![
+            [0.0, 0.0, 0.0],
+            [4.0, 0.0, 0.0],
+            [0.0, 4.0, 0.0],
+            [0.0, 0.0, 4.0]
+        ];
+        let m = usize::MAX;
+        let conn = &[0, 2, 1, m, 0, 1, 3, m, 1, 2, 3, m, 0, 3, 2];
+        let groups = BTreeMap::new();
+        let phed = Element::new(0, coords.view(), None, &0, &groups, conn, ElementType::PHED);
+        assert_eq!(phed.node_mean(), [1.0, 1.0, 1.0]);
+    }
+
     #[test]
     fn test_centroid3() {
         let coords = nd::array![
@@ -7,6 +7,7 @@ use ndarray::prelude::*;
 
 use crate::mesh::Connectivity;
 use crate::mesh::{Dimension, ElementLike, ElementType};
+use crate::topology::templates;
 
 /// Topological operations for mesh elements.
 ///
@@ -19,140 +20,22 @@ pub trait ElementTopo<'a>: ElementLike<'a> {
     /// - `codim = D1` returns the 4 edges (SEG2)
     /// - `codim = D2` returns the 4 vertices (VERTEX)
     ///
-    /// If `codim` is `None`, defaults to `D1`.
+    /// If `codim` is `None`, defaults to `D1`. Regular elements follow the local numbering of
+    /// [`templates::subentities`].
     fn subentities(&self, codim: Option<Dimension>) -> Vec<(ElementType, Connectivity)> {
         use ElementType::*;
-        let codim = match codim {
-            None => Dimension::D1,
-            Some(c) => c,
-        };
+        let codim = codim.unwrap_or(Dimension::D1);
         let co = self.connectivity();
+        let et = self.element_type();
+        if let Some(template) = templates::subentities(et, codim) {
+            let conn = template.apply(co);
+            return vec![(
+                template.element_type,
+                Connectivity::new_regular(conn.to_shared()),
+            )];
+        }
         let mut res = Vec::new();
-        match self.element_type() {
-            SEG2 | SEG3 | SEG4 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[[co[0]], [co[1]]]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => panic!("It is not possible to ask for codim different from D1 on SEG"),
-            },
-            TRI3 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[[co[0], co[1]], [co[1], co[2]], [co[2], co[0]]]);
-                    res.push((SEG2, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D2 => {
-                    let conn = arr2(&[[co[0]], [co[1]], [co[2]]]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => panic!("It is not possible to ask for codim diff from D1 and D2 on TRI3"),
-            },
-            TRI6 | TRI7 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[
-                        [co[0], co[1], co[3]],
-                        [co[1], co[2], co[4]],
-                        [co[2], co[0], co[5]],
-                    ]);
-                    res.push((SEG3, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D2 => {
-                    let conn = arr2(&[[co[0]], [co[1]], [co[2]]]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => panic!("It is not possible to ask for codim diff from D1 and D2 on TRI3"),
-            },
-            QUAD4 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[
-                        [co[0], co[1]],
-                        [co[1], co[2]],
-                        [co[2], co[3]],
-                        [co[3], co[0]],
-                    ]);
-                    res.push((SEG2, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D2 => {
-                    let conn = arr2(&[[co[0]], [co[1]], [co[2]], [co[3]]]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => panic!("It is not possible to ask for codim diff from D1 and D2 on QUAD"),
-            },
-            TET4 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[
-                        [co[0], co[1], co[2]],
-                        [co[1], co[2], co[3]],
-                        [co[2], co[3], co[0]],
-                        [co[3], co[0], co[1]],
-                    ]);
-                    res.push((TRI3, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D2 => {
-                    let conn = arr2(&[
-                        [co[0], co[1]],
-                        [co[0], co[2]],
-                        [co[0], co[3]],
-                        [co[1], co[2]],
-                        [co[1], co[3]],
-                        [co[2], co[3]],
-                    ]);
-                    res.push((SEG2, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D3 => {
-                    let conn = arr2(&[[co[0]], [co[1]], [co[2]], [co[3]]]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => {
-                    panic!("It is not possible to ask for codim diff from D1, D2 or D3 on TET")
-                }
-            },
-            HEX8 => match codim {
-                Dimension::D1 => {
-                    let conn = arr2(&[
-                        [co[0], co[1], co[2], co[3]],
-                        [co[0], co[3], co[7], co[4]],
-                        [co[0], co[4], co[5], co[1]],
-                        [co[1], co[5], co[6], co[2]],
-                        [co[2], co[6], co[7], co[3]],
-                        [co[4], co[7], co[6], co[5]],
-                    ]);
-                    res.push((QUAD4, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D2 => {
-                    let conn = arr2(&[
-                        [co[0], co[1]],
-                        [co[0], co[3]],
-                        [co[0], co[4]],
-                        [co[1], co[2]],
-                        [co[1], co[5]],
-                        [co[2], co[3]],
-                        [co[2], co[6]],
-                        [co[3], co[7]],
-                        [co[4], co[5]],
-                        [co[4], co[7]],
-                        [co[5], co[6]],
-                        [co[6], co[7]],
-                    ]);
-                    res.push((SEG2, Connectivity::new_regular(conn.to_shared())));
-                }
-                Dimension::D3 => {
-                    let conn = arr2(&[
-                        [co[0]],
-                        [co[1]],
-                        [co[2]],
-                        [co[3]],
-                        [co[4]],
-                        [co[5]],
-                        [co[6]],
-                        [co[7]],
-                    ]);
-                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
-                }
-                _ => {
-                    panic!("It is not possible to ask for codim diff from D1, D2 or D3 on HEX")
-                }
-            },
+        match et {
             PGON => match codim {
                 Dimension::D1 => {
                     let mut conn: Vec<_> = co.windows(2).flatten().cloned().collect();
@@ -171,13 +54,10 @@ pub trait ElementTopo<'a>: ElementLike<'a> {
                 Dimension::D1 => {
                     let mut conn = Vec::new();
                     let mut offsets = Vec::new();
-                    let mut offset = 0;
-                    co.split_inclusive(|&e| e == usize::MAX).for_each(|a| {
-                        let len = a.len() - 1;
-                        offset += len;
-                        offsets.push(offset);
-                        conn.append(&mut a[..len].to_vec())
-                    });
+                    for face in polyhedron_faces(co) {
+                        conn.extend_from_slice(face);
+                        offsets.push(conn.len());
+                    }
                     let offsets = Array1::from_vec(offsets);
                     let conn = Array::from_vec(conn);
                     res.push((
@@ -185,11 +65,34 @@ pub trait ElementTopo<'a>: ElementLike<'a> {
                         Connectivity::new_poly(conn.to_shared(), offsets.to_shared()),
                     ));
                 }
+                Dimension::D2 => {
+                    // Edges are the sides of the faces, each edge being shared by two faces.
+                    let mut edges: Vec<[usize; 2]> = Vec::new();
+                    for face in polyhedron_faces(co) {
+                        for (i, &a) in face.iter().enumerate() {
+                            let b = face[(i + 1) % face.len()];
+                            if !edges.iter().any(|e| *e == [a, b] || *e == [b, a]) {
+                                edges.push([a, b]);
+                            }
+                        }
+                    }
+                    res.push((SEG2, Connectivity::new_regular(arr2(&edges).to_shared())));
+                }
+                Dimension::D3 => {
+                    let mut nodes: Vec<usize> = Vec::new();
+                    for &n in co.iter().filter(|&&n| n != usize::MAX) {
+                        if !nodes.contains(&n) {
+                            nodes.push(n);
+                        }
+                    }
+                    let conn = Array2::from_shape_vec([nodes.len(), 1], nodes).unwrap();
+                    res.push((VERTEX, Connectivity::new_regular(conn.to_shared())));
+                }
                 _ => {
-                    todo!()
+                    panic!("It is not possible to ask for codim diff from D1, D2 or D3 on PHED")
                 }
             },
-            _ => todo!(), // For other types, return empty vector
+            _ => panic!("{et:?} elements have no subentities of codimension {codim:?}"),
         };
         res
     }
@@ -199,32 +102,47 @@ pub trait ElementTopo<'a>: ElementLike<'a> {
     /// Returns a list of (element type, connectivity) tuples representing
     /// the simplex decomposition. For example, a QUAD4 is decomposed into
     /// two TRI3 elements.
+    ///
+    /// Polygons are split in a fan of triangles from their first node, and polyhedra in
+    /// tetrahedra joining their first node to the fan triangulations of the faces which do not
+    /// contain it. Both decompositions are valid for convex elements. Splines have no
+    /// decomposition and give an empty list.
     fn to_simplexes(&self) -> Vec<(ElementType, Vec<usize>)> {
         use ElementType::*;
         let co = self.connectivity();
+        if let Some(template) = templates::simplices(self.element_type()) {
+            return template
+                .apply(co)
+                .rows()
+                .into_iter()
+                .map(|simplex| (template.element_type, simplex.to_vec()))
+                .collect();
+        }
         match self.element_type() {
-            VERTEX => vec![(VERTEX, vec![co[0]])],
-            SEG2 | SEG3 | SEG4 => vec![(SEG2, vec![co[0], co[1]])],
-            TRI3 | TRI6 | TRI7 => vec![(TRI3, vec![co[0], co[1], co[2]])],
-            QUAD4 | QUAD8 | QUAD9 => vec![
-                (TRI3, vec![co[0], co[1], co[3]]),
-                (TRI3, vec![co[2], co[3], co[1]]),
-            ],
-            TET4 | TET10 => vec![(TET4, vec![co[0], co[1], co[2], co[3]])],
-            HEX8 | HEX21 => vec![
-                (TET4, vec![co[0], co[1], co[3], co[4]]),
-                (TET4, vec![co[2], co[3], co[1], co[6]]),
-                (TET4, vec![co[7], co[6], co[4], co[3]]),
-                (TET4, vec![co[5], co[4], co[6], co[1]]),
-                (TET4, vec![co[4], co[6], co[3], co[1]]),
-            ],
-            _ => todo!(),
+            PGON => (1..co.len() - 1)
+                .map(|i| (TRI3, vec![co[0], co[i], co[i + 1]]))
+                .collect(),
+            PHED => polyhedron_faces(co)
+                .filter(|face| !face.contains(&co[0]))
+                .flat_map(|face| {
+                    (1..face.len() - 1).map(|i| (TET4, vec![co[0], face[0], face[i], face[i + 1]]))
+                })
+                .collect(),
+            _ => Vec::new(),
         }
     }
 }
 
 impl<'a, T> ElementTopo<'a> for T where T: ElementLike<'a> {}
 
+/// Faces of a polyhedron connectivity, where faces are separated by `usize::MAX`.
+///
+/// A trailing separator after the last face is optional.
+fn polyhedron_faces(co: &[usize]) -> impl Iterator<Item = &[usize]> {
+    co.split(|&n| n == usize::MAX)
+        .filter(|face| !face.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -343,6 +261,54 @@ mod tests {
         assert_eq!(connectivity.len(), 2);
     }
 
+    #[test]
+    fn test_subentities_tet10_edges() {
+        let coords = nd::Array2::zeros((10, 3));
+        let conn = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::TET10,
+        );
+        let (et, connectivity) = &elem.subentities(Some(crate::mesh::Dimension::D2))[0];
+        assert_eq!(*et, ElementType::SEG3);
+        assert_eq!(connectivity.len(), 6);
+        assert_eq!(connectivity[4].to_vec(), vec![1, 3, 8]);
+    }
+
+    #[test]
+    fn test_subentities_phed_edges() {
+        let coords = nd::Array2::zeros((8, 3));
+        let m = usize::MAX;
+        #[rustfmt::skip]
+        let conn = &[
+            0, 3, 2, 1, m, 4, 5, 6, 7, m, 0, 1, 5, 4, m,
+            1, 2, 6, 5, m, 2, 3, 7, 6, m, 3, 0, 4, 7, m,
+        ];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::PHED,
+        );
+        let (et, edges) = &elem.subentities(Some(crate::mesh::Dimension::D2))[0];
+        assert_eq!(*et, ElementType::SEG2);
+        assert_eq!(edges.len(), 12);
+        let (_, vertices) = &elem.subentities(Some(crate::mesh::Dimension::D3))[0];
+        assert_eq!(vertices.len(), 8);
+    }
+
     #[test]
     fn test_to_simplexes_quad4() {
         let coords = nd::array![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
@@ -384,4 +350,47 @@ mod tests {
         assert_eq!(simplexes.len(), 1); // TRI3 -> 1 TRI3
         assert_eq!(simplexes[0].0, ElementType::TRI3);
     }
+
+    #[test]
+    fn test_to_simplexes_poly() {
+        let coords = nd::Array2::zeros((8, 3));
+        let m = usize::MAX;
+        #[rustfmt::skip]
+        let conn = &[
+            0, 3, 2, 1, m, 4, 5, 6, 7, m, 0, 1, 5, 4, m,
+            1, 2, 6, 5, m, 2, 3, 7, 6, m, 3, 0, 4, 7, m,
+        ];
+        let groups = BTreeMap::new();
+        let family = 0;
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            conn,
+            ElementType::PHED,
+        );
+        let simplexes = elem.to_simplexes();
+        assert_eq!(simplexes.len(), 6);
+        assert!(
+            simplexes
+                .iter()
+                .all(|(et, co)| *et == ElementType::TET4 && co[0] == 0)
+        );
+        assert_eq!(simplexes[0].1, vec![0, 4, 5, 6]);
+
+        let elem = Element::new(
+            0,
+            coords.view(),
+            None,
+            &family,
+            &groups,
+            &conn[..4],
+            ElementType::PGON,
+        );
+        let simplexes = elem.to_simplexes();
+        assert_eq!(simplexes.len(), 2);
+        assert_eq!(simplexes[1], (ElementType::TRI3, vec![0, 2, 1]));
+    }
 }
@@ -71,10 +71,10 @@ pub fn surf_tri2_signed(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
 #[inline]
 pub fn surf_tri3(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
     let u0 = b[0] - a[0];
-    let u1 = b[1] - a[0];
+    let u1 = b[1] - a[1];
     let u2 = b[2] - a[2];
     let v0 = c[0] - a[0];
-    let v1 = c[1] - a[0];
+    let v1 = c[1] - a[1];
     let v2 = c[2] - a[2];
     0.5 * ((u0 * v1 - u1 * v0).powi(2) + (u0 * v2 - u2 * v0).powi(2) + (u1 * v2 - u2 * v1).powi(2))
         .sqrt()
@@ -109,9 +109,67 @@ pub fn surf_quad2_signed(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2], d: &[f64; 2])
     0.5 * (u0 * v1 - u1 * v0 + x0 * y1 - x1 * y0)
 }
 
-/// Computes the area of a 3D quadrilateral.
-pub fn surf_quad3(_a: &[f64; 3], _b: &[f64; 3], _c: &[f64; 3], _d: &[f64; 3]) -> f64 {
-    todo!()
+/// Computes the area of a 3D quadrilateral, as half the norm of the cross product of its
+/// diagonals.
+///
+/// This is exact for planar quadrilaterals and the area of the mean plane projection for warped
+/// ones.
+pub fn surf_quad3(a: &[f64; 3], b: &[f64; 3], c: &[f64; 3], d: &[f64; 3]) -> f64 {
+    let ac = na::Vector3::from(*c) - na::Vector3::from(*a);
+    let bd = na::Vector3::from(*d) - na::Vector3::from(*b);
+    0.5 * ac.cross(&bd).norm()
+}
+
+/// Computes the area of a 2D polygon (shoelace formula).
+pub fn surf_pgon2(points: &[[f64; 2]]) -> f64 {
+    surf_pgon2_signed(points).abs()
+}
+
+/// Computes the signed area of a 2D polygon, positive for a counter-clockwise polygon.
+pub fn surf_pgon2_signed(points: &[[f64; 2]]) -> f64 {
+    let n = points.len();
+    let twice: f64 = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a[0] * b[1] - a[1] * b[0]
+        })
+        .sum();
+    0.5 * twice
+}
+
+/// Computes the area of a planar 3D polygon, as the norm of its vector area.
+pub fn surf_pgon3(points: &[[f64; 3]]) -> f64 {
+    0.5 * pgon_normal3(points).norm()
+}
+
+/// Twice the vector area of a 3D polygon, relative to its first point.
+fn pgon_normal3(points: &[[f64; 3]]) -> na::Vector3<f64> {
+    let o = na::Vector3::from(points[0]);
+    (1..points.len().saturating_sub(1))
+        .map(|i| (na::Vector3::from(points[i]) - o).cross(&(na::Vector3::from(points[i + 1]) - o)))
+        .sum()
+}
+
+/// Computes the volume of a polyhedron from the coordinates of its faces.
+///
+/// The volume is the flux of `x / 3` through the faces (divergence theorem). Faces must be
+/// consistently oriented, all outward or all inward, but need not be convex.
+pub fn vol_phed(faces: &[Vec<[f64; 3]>]) -> f64 {
+    vol_phed_signed(faces).abs()
+}
+
+/// Computes the signed volume of a polyhedron, positive when its faces are oriented outward.
+pub fn vol_phed_signed(faces: &[Vec<[f64; 3]>]) -> f64 {
+    let Some(o) = faces.iter().flatten().next() else {
+        return 0.0;
+    };
+    let o = na::Vector3::from(*o);
+    let flux: f64 = faces
+        .iter()
+        .filter(|face| face.len() >= 3)
+        .map(|face| (na::Vector3::from(face[0]) - o).dot(&pgon_normal3(face)))
+        .sum();
+    flux / 6.0
 }
 
 /// Computes the volume of a tetrahedron.
@@ -207,8 +265,57 @@ mod tests {
         assert_abs_diff_eq!(area, 0.5, epsilon = 1e-10);
     }
 
+    #[test]
+    fn test_surf_pgon() {
+        let square = [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]];
+        assert_abs_diff_eq!(surf_pgon2(&square), 4.0, epsilon = 1e-12);
+        let mut clockwise = square;
+        clockwise.reverse();
+        assert_abs_diff_eq!(surf_pgon2(&clockwise), 4.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(surf_pgon2_signed(&clockwise), -4.0, epsilon = 1e-12);
+        let l_shape = [
+            [0.0, 0.0, 1.0],
+            [2.0, 0.0, 1.0],
+            [2.0, 1.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [1.0, 2.0, 1.0],
+            [0.0, 2.0, 1.0],
+        ];
+        assert_abs_diff_eq!(surf_pgon3(&l_shape), 3.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_vol_phed() {
+        // Unit cube with outward faces
+        let p = |i: usize| [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64];
+        let faces: Vec<Vec<[f64; 3]>> = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ]
+        .iter()
+        .map(|f| f.iter().map(|&i| p(i)).collect())
+        .collect();
+        assert_abs_diff_eq!(vol_phed(&faces), 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(vol_phed_signed(&faces), 1.0, epsilon = 1e-12);
+        let inward: Vec<Vec<[f64; 3]>> = faces
+            .iter()
+            .map(|f| f.iter().rev().copied().collect())
+            .collect();
+        assert_abs_diff_eq!(vol_phed(&inward), 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(vol_phed_signed(&inward), -1.0, epsilon = 1e-12);
+    }
+
     #[test]
     fn test_surf_quad3() {
-        // This will panic with todo!() - skipping
+        let a = [1.0, 1.0, 1.0];
+        let b = [3.0, 1.0, 1.0];
+        let c = [3.0, 1.0, 2.0];
+        let d = [1.0, 1.0, 2.0];
+        assert_abs_diff_eq!(surf_quad3(&a, &b, &c, &d), 2.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(surf_tri3(a, b, c), 1.0, epsilon = 1e-12);
     }
 }
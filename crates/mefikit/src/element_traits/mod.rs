@@ -14,5 +14,9 @@ mod utils;
 
 pub use element_geo::ElementGeo;
 pub use element_topo::ElementTopo;
-pub use seg_intersect::{Intersection, Intersections, PointId, intersect_seg_seg};
+pub use seg_intersect::{
+    Intersection, Intersections, PointId, intersect_seg_seg, intersect_seg_seg_with,
+};
+pub use symmetry::ElementEquality;
+pub(crate) use symmetry::node_set_key;
 pub use utils::SortedVecKey;
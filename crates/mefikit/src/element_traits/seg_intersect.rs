@@ -1,10 +1,14 @@
 //! Segment-segment intersection algorithms.
 //!
 //! Provides robust intersection detection between 2D line segments,
-//! handling edge cases like collinear segments and endpoint coincidences.
+//! handling edge cases like collinear segments and endpoint coincidences. Crossings are decided
+//! with the exact predicates of [`crate::geometry::predicates`].
 
 use nalgebra::Point2;
-use nalgebra::{self as na, Vector2};
+
+#[cfg(feature = "exact")]
+use crate::geometry::exact;
+use crate::geometry::predicates::{Tolerances, orient2d};
 
 /// Represents an intersection point, either at an existing endpoint or a new point.
 #[derive(Copy, Debug, PartialEq, Clone, PartialOrd)]
@@ -41,81 +45,98 @@ pub enum Intersections {
     Segment([PointId; 2]),
 }
 
-#[inline(always)]
-fn cross_prod2(v1: Vector2<f64>, v2: Vector2<f64>) -> f64 {
-    v1[0] * v2[1] - v1[1] * v2[0]
-}
-
 /// Computes the intersection between two 2D line segments.
 ///
 /// Returns [`Intersections`] describing whether and where the segments intersect.
 /// The result is symmetric: swapping the segment pairs produces an equivalent result.
+///
+/// This uses the default [`Tolerances`], see [`intersect_seg_seg_with`].
 pub fn intersect_seg_seg(
     p1: Point2<f64>,
     p2: Point2<f64>,
     p3: Point2<f64>,
     p4: Point2<f64>,
+) -> Intersections {
+    intersect_seg_seg_with(p1, p2, p3, p4, &Tolerances::default())
+}
+
+fn same_side(o1: f64, o2: f64) -> bool {
+    (o1 > 0.0 && o2 > 0.0) || (o1 < 0.0 && o2 < 0.0)
+}
+
+/// Computes the intersection between two 2D line segments with the given tolerances.
+///
+/// Whether the segments cross, touch or overlap is decided with exact orientation predicates. A
+/// crossing point closer to one of the four end points than the tolerance (at the scale of the
+/// segments) is reported as this end point.
+pub fn intersect_seg_seg_with(
+    p1: Point2<f64>,
+    p2: Point2<f64>,
+    p3: Point2<f64>,
+    p4: Point2<f64>,
+    tol: &Tolerances,
 ) -> Intersections {
     let v1 = p2 - p1;
     let v2 = p4 - p3;
 
-    let cross12 = cross_prod2(v1, v2);
     let scale = v1[0]
         .abs()
         .max(v1[1].abs())
         .max(v2[0].abs())
         .max(v2[1].abs())
         .max(1.0);
-    // As this error is used on cross or dot product, it should scale with the length of the
-    // segments.
-    let eps = 64.0 * scale * f64::EPSILON;
+    let eps = tol.distance_at(scale);
 
     // If one of the edges is degenerated, there is no intersection. This is simplist, but there
     // should be no degenerated segments in a proper mesh.
-    if (v2.norm_squared() < eps) || (v1.norm_squared() < eps) {
+    if (v2.norm() <= eps) || (v1.norm() <= eps) {
         return Intersections::None;
     }
-    let v3 = p3 - p1;
-    let cross31 = cross_prod2(v3, v1);
-
-    if cross12.abs() < eps {
-        if cross31.abs() > eps {
-            // Segments are // but do not cross
-            Intersections::None
-        } else {
-            colinear_seg_intersection(p1, p2, p3, p4)
-        }
+    let [a, b, c, d] = [p1, p2, p3, p4].map(|p| [p[0], p[1]]);
+    let o1 = orient2d(&a, &b, &c);
+    let o2 = orient2d(&a, &b, &d);
+    let o3 = orient2d(&c, &d, &a);
+    let o4 = orient2d(&c, &d, &b);
+
+    if o1 == 0.0 && o2 == 0.0 {
+        return colinear_seg_intersection(p1, p2, p3, p4);
+    }
+    if same_side(o1, o2) || same_side(o3, o4) {
+        return Intersections::None;
+    }
+    // An end point exactly on the other segment
+    if o3 == 0.0 {
+        return Intersections::One(Intersection::Existing(PointId::P1));
+    } else if o4 == 0.0 {
+        return Intersections::One(Intersection::Existing(PointId::P2));
+    } else if o1 == 0.0 {
+        return Intersections::One(Intersection::Existing(PointId::P3));
+    } else if o2 == 0.0 {
+        return Intersections::One(Intersection::Existing(PointId::P4));
+    }
+    // The signed distances of p1 and p2 to (p3, p4) are proportional to o3 and o4
+    let t = o3 / (o3 - o4);
+    let intersection = crossing_point(p1, p2, p3, p4, t);
+    if (p1 - intersection).norm() < eps {
+        Intersections::One(Intersection::Existing(PointId::P1))
+    } else if (p2 - intersection).norm() < eps {
+        Intersections::One(Intersection::Existing(PointId::P2))
+    } else if (p3 - intersection).norm() < eps {
+        Intersections::One(Intersection::Existing(PointId::P3))
+    } else if (p4 - intersection).norm() < eps {
+        Intersections::One(Intersection::Existing(PointId::P4))
     } else {
-        let cross32 = cross_prod2(v3, v2);
-        let t = cross32 / cross12;
-        let u = cross31 / cross12;
-        let intersection = p1 + t * v1;
-        if !((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)) {
-            Intersections::None
-        } else if (p1 - intersection).norm_squared() < eps {
-            Intersections::One(Intersection::Existing(PointId::P1))
-        } else if (p2 - intersection).norm_squared() < eps {
-            Intersections::One(Intersection::Existing(PointId::P2))
-        } else if (p3 - intersection).norm_squared() < eps {
-            Intersections::One(Intersection::Existing(PointId::P3))
-        } else if (p4 - intersection).norm_squared() < eps {
-            Intersections::One(Intersection::Existing(PointId::P4))
-        } else {
-            Intersections::One(Intersection::New(intersection.into()))
-        }
+        Intersections::One(Intersection::New(intersection.into()))
     }
 }
 
-fn colinear_seg_intersection(
-    p1: Point2<f64>,
-    p2: Point2<f64>,
-    p3: Point2<f64>,
-    p4: Point2<f64>,
-) -> Intersections {
-    let or = na::Point2::origin();
+/// Order of the four points along the direction of the first segment.
+#[cfg(not(feature = "exact"))]
+fn order_along(p1: Point2<f64>, p2: Point2<f64>, p3: Point2<f64>, p4: Point2<f64>) -> [usize; 4] {
+    let or = Point2::origin();
     let o = or + ((p1 - or) + (p2 - or) + (p3 - or) + (p4 - or)) / 4.0;
     let dir = p2 - p1;
-    let ts = if dir[0] > dir[1] {
+    let ts = if dir[0].abs() > dir[1].abs() {
         [
             (p1[0] - o[0]) / dir[0],
             (p2[0] - o[0]) / dir[0],
@@ -130,9 +151,65 @@ fn colinear_seg_intersection(
             (p4[1] - o[1]) / dir[1],
         ]
     };
-    use PointId::*;
     let mut ord = [0, 1, 2, 3];
     ord.sort_unstable_by(|&i, &j| ts[i].partial_cmp(&ts[j]).unwrap());
+    ord
+}
+
+/// Order of the four points along the direction of the first segment, compared exactly.
+#[cfg(feature = "exact")]
+fn order_along(p1: Point2<f64>, p2: Point2<f64>, p3: Point2<f64>, p4: Point2<f64>) -> [usize; 4] {
+    let ps = [p1, p2, p3, p4].map(|p| [p[0], p[1]]);
+    let mut ord = [0, 1, 2, 3];
+    ord.sort_by(|&i, &j| exact::compare_along(&ps[0], &ps[1], &ps[i], &ps[j]));
+    ord
+}
+
+/// Intersection point of two crossing segments, computed from the parameter `t` along the first.
+///
+/// Without the `exact` feature, this is the floating point result.
+#[cfg(not(feature = "exact"))]
+fn crossing_point(
+    p1: Point2<f64>,
+    p2: Point2<f64>,
+    _: Point2<f64>,
+    _: Point2<f64>,
+    t: f64,
+) -> Point2<f64> {
+    p1 + t * (p2 - p1)
+}
+
+/// Intersection point of two crossing segments, computed from the parameter `t` along the first.
+///
+/// For almost parallel segments, the floating point result can fall outside of the segments. It is
+/// then computed again with rational arithmetic.
+#[cfg(feature = "exact")]
+fn crossing_point(
+    p1: Point2<f64>,
+    p2: Point2<f64>,
+    p3: Point2<f64>,
+    p4: Point2<f64>,
+    t: f64,
+) -> Point2<f64> {
+    let p = p1 + t * (p2 - p1);
+    let in_box = |a: Point2<f64>, b: Point2<f64>| {
+        (0..2).all(|i| a[i].min(b[i]) <= p[i] && p[i] <= a[i].max(b[i]))
+    };
+    if in_box(p1, p2) && in_box(p3, p4) {
+        return p;
+    }
+    let [a, b, c, d] = [p1, p2, p3, p4].map(|p| [p[0], p[1]]);
+    exact::line_intersection(&a, &b, &c, &d).map_or(p, Point2::from)
+}
+
+fn colinear_seg_intersection(
+    p1: Point2<f64>,
+    p2: Point2<f64>,
+    p3: Point2<f64>,
+    p4: Point2<f64>,
+) -> Intersections {
+    let ord = order_along(p1, p2, p3, p4);
+    use PointId::*;
     const PS: [PointId; 4] = [P1, P2, P3, P4];
     let ps = [PS[ord[0]], PS[ord[1]], PS[ord[2]], PS[ord[3]]];
     match ps {
@@ -140,6 +217,7 @@ fn colinear_seg_intersection(
         [P1, a, P2, _] => Intersections::Segment([a, P2]),
         [P1, a, b, P2] => Intersections::Segment([a, b]),
         [_, P1, a, P2] => Intersections::Segment([P1, a]),
+        [_, P1, P2, _] => Intersections::Segment([P1, P2]),
         _ => {
             panic!(
                 "This situation should not be possible as P1 is before P2 along the P2 - P1 vec."
@@ -393,4 +471,14 @@ mod tests {
             assert!(point_on_segment(p, p3, p4, scale));
         }
     }
+
+    #[test]
+    fn test_colinear_seg_inside_other() {
+        let p1 = [0.0, 1.0];
+        let p2 = [-1.0, 1.0];
+        let p3 = [1.0, 1.0];
+        let p4 = [-2.0, 1.0];
+        let res = intersect_seg_seg(p1.into(), p2.into(), p3.into(), p4.into());
+        assert_eq!(res, Intersections::Segment([PointId::P1, PointId::P2]));
+    }
 }
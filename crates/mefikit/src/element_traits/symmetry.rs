@@ -1,8 +1,15 @@
+use super::utils::SortedVecKey;
 use crate::mesh::ElementLike;
-use rustc_hash::FxHashSet as HashSet;
 
-#[allow(unused)]
+/// Key of a set of nodes regardless of their order, equal for the elements compared equal by
+/// [`ElementEquality::node_set_equality`].
+pub(crate) fn node_set_key(nodes: &[usize]) -> SortedVecKey {
+    SortedVecKey::new(nodes.into())
+}
+
+/// Comparison of elements by their nodes.
 pub trait ElementEquality<'a>: ElementLike<'a> {
+    /// Whether the elements have the same nodes in the same order.
     fn strict_equality(&self, other: &Self) -> bool {
         if self.connectivity().len() != other.connectivity().len() {
             return false;
@@ -12,11 +19,20 @@ pub trait ElementEquality<'a>: ElementLike<'a> {
             .zip(other.connectivity().iter())
             .all(|(a, b)| a == b)
     }
+
+    /// The key of the nodes of the element regardless of their order, to look elements up by
+    /// [`Self::node_set_equality`] in a hash map.
+    fn node_set_key(&self) -> SortedVecKey {
+        node_set_key(self.connectivity())
+    }
+
+    /// Whether the elements have the same nodes, whatever their order.
+    ///
+    /// Nodes repeated in the connectivity, as in the faces of polyhedra, count as many times.
     fn node_set_equality(&self, other: &Self) -> bool {
-        let self_nodes: HashSet<usize> = self.connectivity().iter().copied().collect();
-        let other_nodes: HashSet<usize> = other.connectivity().iter().copied().collect();
-        self_nodes == other_nodes
+        self.node_set_key() == other.node_set_key()
     }
+
     // fn strict_equivalence(&self, other: &Self) -> bool {
     //     if self.connectivity().len() != other.connectivity().len() {
     //         return false;
@@ -37,6 +53,8 @@ pub trait ElementEquality<'a>: ElementLike<'a> {
     // }
 }
 
+impl<'a, E: ElementLike<'a>> ElementEquality<'a> for E {}
+
 // define_symmetries! {
 //     QUAD4 => {
 //         order: 4,
@@ -57,3 +75,24 @@ pub trait ElementEquality<'a>: ElementLike<'a> {
 //         ]
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementId, ElementType};
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_node_set_equality() {
+        let mesh = me::make_imesh_2d(2);
+        let mut reversed = mesh.clone();
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        reversed.element_mut(quad(0)).connectivity.reverse();
+        let (a, b) = (mesh.element(quad(0)), reversed.element(quad(0)));
+        assert!(a.strict_equality(&a));
+        assert!(!a.strict_equality(&b));
+        assert!(a.node_set_equality(&b));
+        assert!(!a.node_set_equality(&mesh.element(quad(1))));
+        assert_eq!(a.node_set_key(), node_set_key(&[4, 3, 1, 0]));
+    }
+}
@@ -5,8 +5,8 @@ use smallvec::SmallVec;
 /// A wrapper that ensures a vector of indices is always sorted.
 ///
 /// Useful as a hash map key where order-independent equality is needed
-/// (e.g., identifying shared edges between elements).
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// (e.g., identifying shared edges between elements). Keys are ordered lexicographically.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SortedVecKey(SmallVec<[usize; 4]>);
 
 impl SortedVecKey {
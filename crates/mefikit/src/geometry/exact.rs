@@ -0,0 +1,103 @@
+//! Rational arithmetic fallback of the intersection computations.
+//!
+//! Floating point coordinates are converted exactly to rationals, so the results below are exact
+//! up to the final rounding to `f64`. They are much slower than their floating point
+//! counterparts and are only used when the latter are ambiguous, e.g. for almost parallel
+//! segments. This module is only available with the `exact` feature.
+
+use std::cmp::Ordering;
+
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+fn rational(x: f64) -> BigRational {
+    BigRational::from_float(x).expect("Coordinates must be finite")
+}
+
+fn point(p: &[f64; 2]) -> [BigRational; 2] {
+    [rational(p[0]), rational(p[1])]
+}
+
+fn sub(a: &[BigRational; 2], b: &[BigRational; 2]) -> [BigRational; 2] {
+    [&a[0] - &b[0], &a[1] - &b[1]]
+}
+
+fn cross(u: &[BigRational; 2], v: &[BigRational; 2]) -> BigRational {
+    &u[0] * &v[1] - &u[1] * &v[0]
+}
+
+fn dot(u: &[BigRational; 2], v: &[BigRational; 2]) -> BigRational {
+    &u[0] * &v[0] + &u[1] * &v[1]
+}
+
+/// Intersection point of the lines `(p1, p2)` and `(p3, p4)`, rounded to the closest `f64`.
+///
+/// Returns `None` when the lines are exactly parallel.
+pub fn line_intersection(
+    p1: &[f64; 2],
+    p2: &[f64; 2],
+    p3: &[f64; 2],
+    p4: &[f64; 2],
+) -> Option<[f64; 2]> {
+    let [p1, p2, p3, p4] = [p1, p2, p3, p4].map(point);
+    let v1 = sub(&p2, &p1);
+    let v2 = sub(&p4, &p3);
+    let denom = cross(&v1, &v2);
+    if denom.is_zero() {
+        return None;
+    }
+    let t = cross(&sub(&p3, &p1), &v2) / denom;
+    let x = &p1[0] + &t * &v1[0];
+    let y = &p1[1] + &t * &v1[1];
+    Some([x.to_f64()?, y.to_f64()?])
+}
+
+/// Exact comparison of the positions of `p` and `q` along the direction `b - a`.
+pub fn compare_along(a: &[f64; 2], b: &[f64; 2], p: &[f64; 2], q: &[f64; 2]) -> Ordering {
+    let [a, b, p, q] = [a, b, p, q].map(point);
+    let d = dot(&sub(&p, &q), &sub(&b, &a));
+    match (d.is_positive(), d.is_negative()) {
+        (true, _) => Ordering::Greater,
+        (_, true) => Ordering::Less,
+        _ => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_intersection() {
+        let p = line_intersection(&[0.0, 0.0], &[2.0, 2.0], &[0.0, 2.0], &[2.0, 0.0]);
+        assert_eq!(p, Some([1.0, 1.0]));
+        let p = line_intersection(&[0.0, 0.0], &[1.0, 0.0], &[0.0, 1.0], &[1.0, 1.0]);
+        assert_eq!(p, None);
+        // Almost parallel lines, the intersection stays on the first line
+        let p = line_intersection(&[0.0, 0.0], &[1.0, 1.0], &[0.0, 1e-17], &[1.0, 1.0 - 1e-16]);
+        let [x, y] = p.unwrap();
+        assert!((x - y).abs() <= f64::EPSILON);
+        assert!(x > 0.0 && x < 1.0);
+    }
+
+    #[test]
+    fn test_compare_along() {
+        let (a, b) = ([0.0, 0.0], [1.0, 1.0]);
+        assert_eq!(
+            compare_along(&a, &b, &[0.3, 0.3], &[0.2, 0.2]),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_along(&a, &b, &[0.1, 0.3], &[0.3, 0.1]),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_along(&b, &a, &[0.3, 0.3], &[0.2, 0.2]),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_along(&a, &b, &[0.1, 0.2], &[0.1, 0.2 + 1e-16]),
+            Ordering::Less
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Mass properties and oriented bounding boxes of meshes.
+//!
+//! Mass properties are integrated exactly over the simplices decomposing the cells, with a unit
+//! density. Coordinates of 1D and 2D meshes are padded with zeros, so all quantities are given in
+//! 3D.
+
+use crate::element_traits::ElementGeo;
+use crate::mesh::UMeshView;
+
+use nalgebra as na;
+
+/// Measure, center of mass and inertia of the cells of a mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MassProperties {
+    /// Total length, area or volume of the cells.
+    pub measure: f64,
+    /// Center of mass.
+    pub center: [f64; 3],
+    /// Inertia tensor about the center of mass.
+    pub inertia: [[f64; 3]; 3],
+    /// Principal moments of inertia, in increasing order.
+    pub principal_moments: [f64; 3],
+    /// Principal axes of inertia, matching `principal_moments` and forming a direct basis.
+    pub principal_axes: [[f64; 3]; 3],
+}
+
+/// A box aligned on arbitrary orthogonal axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrientedBox {
+    /// Center of the box.
+    pub center: [f64; 3],
+    /// Axes of the box, forming a direct orthonormal basis.
+    pub axes: [[f64; 3]; 3],
+    /// Half sizes of the box along each axis.
+    pub half_extents: [f64; 3],
+}
+
+impl OrientedBox {
+    /// Volume of the box, zero for flat boxes.
+    pub fn volume(&self) -> f64 {
+        8.0 * self.half_extents.iter().product::<f64>()
+    }
+
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        let center = na::Vector3::from(self.center);
+        std::array::from_fn(|k| {
+            let corner = (0..3).fold(center, |c, i| {
+                let sign = if (k >> i) & 1 == 0 { -1.0 } else { 1.0 };
+                c + na::Vector3::from(self.axes[i]) * sign * self.half_extents[i]
+            });
+            corner.into()
+        })
+    }
+}
+
+/// Measure of a simplex of dimension `points.len() - 1`.
+fn simplex_measure(points: &[na::Vector3<f64>]) -> f64 {
+    match points {
+        [_] => 1.0,
+        [a, b] => (b - a).norm(),
+        [a, b, c] => (b - a).cross(&(c - a)).norm() / 2.0,
+        [a, b, c, d] => (b - a).dot(&(c - a).cross(&(d - a))).abs() / 6.0,
+        _ => unreachable!(),
+    }
+}
+
+/// Eigen decomposition of a symmetric matrix, eigenvalues in increasing order and eigenvectors
+/// forming a direct basis.
+fn sorted_eigen(matrix: na::Matrix3<f64>) -> ([f64; 3], [na::Vector3<f64>; 3]) {
+    let eigen = matrix.symmetric_eigen();
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| eigen.eigenvalues[i].total_cmp(&eigen.eigenvalues[j]));
+    let values = order.map(|i| eigen.eigenvalues[i]);
+    let mut vectors = order.map(|i| eigen.eigenvectors.column(i).into_owned());
+    vectors[2] = vectors[0].cross(&vectors[1]);
+    (values, vectors)
+}
+
+/// Computes the mass properties of the cells of a mesh, with a unit density.
+///
+/// Cells are the elements of the topological dimension of the mesh, so the measure is a length,
+/// an area or a volume. The inertia of a 0D mesh is the one of unit point masses. An empty mesh
+/// has a null measure and a null inertia, centered at the origin.
+pub fn mass_properties(mesh: UMeshView) -> MassProperties {
+    let mut measure = 0.0;
+    let mut first = na::Vector3::zeros();
+    let mut second = na::Matrix3::zeros();
+    if let Some(dim) = mesh.topological_dimension() {
+        for element in mesh.elements_of_dim(dim) {
+            for simplex in element.simplex_coords() {
+                let points: Vec<na::Vector3<f64>> =
+                    simplex.iter().map(|p| na::Vector3::from(*p)).collect();
+                let m = simplex_measure(&points);
+                let n = points.len() as f64;
+                let sum: na::Vector3<f64> = points.iter().sum();
+                let squares: na::Matrix3<f64> = points.iter().map(|p| p * p.transpose()).sum();
+                measure += m;
+                first += m * sum / n;
+                // Exact second moment of a simplex of dimension n - 1
+                second += m / (n * (n + 1.0)) * (squares + sum * sum.transpose());
+            }
+        }
+    }
+    let center = if measure > 0.0 {
+        first / measure
+    } else {
+        na::Vector3::zeros()
+    };
+    let covariance = second - measure * center * center.transpose();
+    let inertia = na::Matrix3::identity() * covariance.trace() - covariance;
+    let (principal_moments, axes) = sorted_eigen(inertia);
+    MassProperties {
+        measure,
+        center: center.into(),
+        inertia: inertia.transpose().into(),
+        principal_moments,
+        principal_axes: axes.map(Into::into),
+    }
+}
+
+/// Computes a bounding box of the nodes of a mesh aligned on their principal directions.
+///
+/// The axes are the eigenvectors of the covariance of the node coordinates, the direction of
+/// largest spread first. This box is not the smallest one in general, but it is close for
+/// elongated meshes and much tighter than the axis aligned box for rotated ones.
+pub fn oriented_bounding_box(mesh: UMeshView) -> OrientedBox {
+    let points: Vec<na::Vector3<f64>> = mesh
+        .coords()
+        .rows()
+        .into_iter()
+        .map(|p| {
+            let mut x = na::Vector3::zeros();
+            x.as_mut_slice()[..p.len()].copy_from_slice(&p.to_vec());
+            x
+        })
+        .collect();
+    if points.is_empty() {
+        return OrientedBox {
+            center: [0.0; 3],
+            axes: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            half_extents: [0.0; 3],
+        };
+    }
+    let mean = points.iter().sum::<na::Vector3<f64>>() / points.len() as f64;
+    let covariance: na::Matrix3<f64> = points
+        .iter()
+        .map(|p| (p - mean) * (p - mean).transpose())
+        .sum();
+    let (_, vectors) = sorted_eigen(-covariance);
+    let mut center = mean;
+    let mut half_extents = [0.0; 3];
+    for (axis, half_extent) in vectors.iter().zip(&mut half_extents) {
+        let (min, max) = points
+            .iter()
+            .map(|p| (p - mean).dot(axis))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            });
+        center += axis * (min + max) / 2.0;
+        *half_extent = (max - min) / 2.0;
+    }
+    OrientedBox {
+        center: center.into(),
+        axes: vectors.map(Into::into),
+        half_extents,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::UMesh;
+    use crate::mesh_examples as me;
+    use approx::assert_relative_eq;
+    use ndarray as nd;
+
+    #[test]
+    fn test_mass_properties_cube() {
+        let mesh = me::make_imesh_3d(2);
+        let props = mass_properties(mesh.view());
+        assert_relative_eq!(props.measure, 1.0, epsilon = 1e-12);
+        for i in 0..3 {
+            assert_relative_eq!(props.center[i], 0.5, epsilon = 1e-12);
+            assert_relative_eq!(props.principal_moments[i], 1.0 / 6.0, epsilon = 1e-12);
+            for j in 0..3 {
+                let expected = if i == j { 1.0 / 6.0 } else { 0.0 };
+                assert_relative_eq!(props.inertia[i][j], expected, epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mass_properties_rectangle() {
+        let mesh = crate::tools::RegularUMeshBuilder::new()
+            .add_axis(vec![0.0, 1.0, 2.0, 3.0, 4.0])
+            .add_axis(vec![0.0, 1.0])
+            .build();
+        let props = mass_properties(mesh.view());
+        assert_relative_eq!(props.measure, 4.0, epsilon = 1e-12);
+        assert_relative_eq!(props.center[0], 2.0, epsilon = 1e-12);
+        // Moments of a 4 x 1 plate: 4 / 12 around x, 16 * 4 / 12 around y, their sum around z
+        assert_relative_eq!(props.principal_moments[0], 1.0 / 3.0, epsilon = 1e-12);
+        assert_relative_eq!(props.principal_moments[1], 16.0 / 3.0, epsilon = 1e-12);
+        assert_relative_eq!(props.principal_moments[2], 17.0 / 3.0, epsilon = 1e-12);
+        assert_relative_eq!(props.principal_axes[0][0].abs(), 1.0, epsilon = 1e-12);
+
+        let empty = UMesh::new(nd::Array2::<f64>::zeros((0, 2)).into_shared());
+        assert_eq!(mass_properties(empty.view()).measure, 0.0);
+    }
+
+    #[test]
+    fn test_oriented_bounding_box() {
+        // A 2 x 1 rectangle rotated by 45 degrees
+        let (c, s) = (0.5_f64.sqrt(), 0.5_f64.sqrt());
+        let corners = [[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [0.0, 1.0]];
+        let coords = nd::Array2::from_shape_fn((4, 2), |(i, j)| {
+            let [x, y] = corners[i];
+            [c * x - s * y, s * x + c * y][j]
+        });
+        let mesh = UMesh::new(coords.into_shared());
+        let obb = oriented_bounding_box(mesh.view());
+        assert_relative_eq!(obb.half_extents[0], 1.0, epsilon = 1e-12);
+        assert_relative_eq!(obb.half_extents[1], 0.5, epsilon = 1e-12);
+        assert_relative_eq!(obb.half_extents[2], 0.0, epsilon = 1e-12);
+        assert_relative_eq!(obb.axes[0][0].abs(), c, epsilon = 1e-12);
+        assert_relative_eq!(obb.center[0], c * 1.0 - s * 0.5, epsilon = 1e-12);
+        assert_relative_eq!(obb.center[1], s * 1.0 + c * 0.5, epsilon = 1e-12);
+        assert_eq!(obb.volume(), 0.0);
+        assert_eq!(obb.corners().len(), 8);
+    }
+}
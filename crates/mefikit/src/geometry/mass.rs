@@ -0,0 +1,281 @@
+//! Lumped mass matrices and L2 projection of element fields to the nodes.
+//!
+//! Mass matrices are lumped with the HRZ scheme (Hinton, Rock and Zienkiewicz): the diagonal of
+//! the consistent mass matrix of each element, `∫ N_i²`, is scaled so that it sums to the measure
+//! of the element. Unlike the row sums of the matrix, it stays positive on quadratic simplices.
+//! Poly elements, which have no shape functions, share their measure equally between their
+//! nodes, and 0D elements weigh one on their node.
+//!
+//! A field is projected to the nodes by a lumped L2 projection: the value of a node is the
+//! average of the values of the elements around it, weighted by its lumped mass in each of them,
+//! which preserves the integral of the field.
+
+use std::collections::BTreeSet;
+
+use ndarray::{self as nd, Axis};
+
+use super::quadrature::{QuadratureRule, gauss_rules};
+use super::shape_functions;
+use crate::element_traits::ElementGeo;
+use crate::mesh::{Dimension, ElementLike, ElementType, FieldBase, UMeshView};
+use crate::tools::measure;
+
+/// Order of the Gauss rule integrating the element mass matrices, exact for affine quadratic
+/// elements. Tetrahedra use the highest available order, 3.
+const MASS_QUADRATURE_ORDER: usize = 4;
+
+fn mass_rule(et: ElementType) -> Option<QuadratureRule> {
+    (0..=MASS_QUADRATURE_ORDER)
+        .rev()
+        .find_map(|order| QuadratureRule::gauss(et, order))
+}
+
+/// Values of the shape functions of an element type at each point of a rule, `[n_gp, n_nodes]`.
+fn shape_values(et: ElementType, rule: &QuadratureRule) -> nd::Array2<f64> {
+    let rows: Vec<nd::Array1<f64>> = rule
+        .points
+        .rows()
+        .into_iter()
+        .map(|xi| shape_functions(et, xi.as_slice().unwrap()).unwrap().0)
+        .collect();
+    let views: Vec<nd::ArrayView1<f64>> = rows.iter().map(|r| r.view()).collect();
+    nd::stack(Axis(0), &views).unwrap()
+}
+
+/// The lumped masses of the nodes of each element of a block, as `(node, mass)` pairs.
+///
+/// # Panics
+/// Panics if the element type is not in the mesh.
+fn block_masses(mesh: &UMeshView, et: ElementType) -> Vec<Vec<(usize, f64)>> {
+    let block = mesh
+        .block(et)
+        .unwrap_or_else(|| panic!("Element type {et:?} is not in the mesh"));
+    if et.dimension() == Dimension::D0 {
+        return block
+            .connectivity
+            .iter()
+            .map(|nodes| nodes.iter().map(|&n| (n, 1.0)).collect())
+            .collect();
+    }
+    let Some(rule) = mass_rule(et) else {
+        let measures = &measure(mesh.view(), Some(et.dimension()))[&et];
+        return block
+            .connectivity
+            .iter()
+            .zip(measures)
+            .map(|(nodes, &m)| {
+                let nodes: BTreeSet<usize> =
+                    nodes.iter().copied().filter(|&n| n != usize::MAX).collect();
+                let share = m / nodes.len() as f64;
+                nodes.into_iter().map(|n| (n, share)).collect()
+            })
+            .collect();
+    };
+    let squares = shape_values(et, &rule).mapv(|n| n * n);
+    block
+        .iter(mesh.coords())
+        .map(|element| {
+            let weights = nd::Array1::from(element.integration_weights(&rule).unwrap());
+            let diagonal = weights.dot(&squares);
+            let total = diagonal.sum();
+            let scale = if total > 0.0 {
+                weights.sum() / total
+            } else {
+                0.0
+            };
+            element
+                .connectivity()
+                .iter()
+                .zip(diagonal)
+                .map(|(&n, d)| (n, d * scale))
+                .collect()
+        })
+        .collect()
+}
+
+/// Computes the lumped mass of each node, from the elements of dimension `dim`.
+///
+/// `dim` defaults to the topological dimension of the mesh. The masses sum to the measure of the
+/// elements, with a unit density. Nodes of no such element have a null mass.
+pub fn lumped_mass(mesh: UMeshView, dim: Option<Dimension>) -> nd::Array1<f64> {
+    let dim = dim.or_else(|| mesh.topological_dimension());
+    let mut mass = nd::Array1::zeros(mesh.coords().nrows());
+    let types: Vec<ElementType> = mesh
+        .element_types()
+        .filter(|et| Some(et.dimension()) == dim)
+        .copied()
+        .collect();
+    for et in types {
+        for (node, m) in block_masses(&mesh, et).into_iter().flatten() {
+            mass[node] += m;
+        }
+    }
+    mass
+}
+
+/// Divides the accumulated values of each node by its mass, leaving nodes without mass to zero.
+fn normalize(mut values: nd::ArrayD<f64>, mass: &[f64]) -> nd::ArrayD<f64> {
+    for (mut value, &m) in values.outer_iter_mut().zip(mass) {
+        if m > 0.0 {
+            value.mapv_inplace(|x| x / m);
+        }
+    }
+    values
+}
+
+/// Projects a cell field to the mesh nodes, by a lumped L2 projection.
+///
+/// The field has shape `[n_elem, ...]` and the result `[n_nodes, ...]`. Each node takes the
+/// average of the values of the field elements around it, weighted by its lumped mass in each
+/// of them (see [`lumped_mass`]), so that larger elements weigh more. Nodes not used by the
+/// field elements are set to zero.
+///
+/// # Panics
+/// Panics if the field references element types absent from the mesh.
+pub fn project_to_nodes<S>(mesh: UMeshView, field: &FieldBase<S, nd::IxDyn>) -> nd::ArrayD<f64>
+where
+    S: nd::Data<Elem = f64>,
+{
+    let n_nodes = mesh.coords().nrows();
+    let mut shape = vec![n_nodes];
+    shape.extend_from_slice(&field.full_dim()[1..]);
+    let mut res = nd::ArrayD::<f64>::zeros(shape);
+    let mut mass = vec![0.0; n_nodes];
+    for (&et, values) in &field.0 {
+        for (e, masses) in block_masses(&mesh, et).into_iter().enumerate() {
+            let value = values.index_axis(Axis(0), e);
+            for (node, m) in masses {
+                res.index_axis_mut(Axis(0), node).scaled_add(m, &value);
+                mass[node] += m;
+            }
+        }
+    }
+    normalize(res, &mass)
+}
+
+/// Projects a field located at integration points to the mesh nodes, by a lumped L2
+/// projection.
+///
+/// The field has shape `[n_elem, n_gp, ...]` and the result `[n_nodes, ...]`. The values of
+/// each element are first averaged at each of its nodes with the weights `N_i²` of the node
+/// shape function, then the nodes average the elements around them as in [`project_to_nodes`].
+/// Unlike [`super::quadrature::gauss_to_nodes`], all the integration points contribute and
+/// element sizes are taken into account.
+///
+/// # Panics
+/// Panics if the field is not located at integration points (see [`gauss_rules`]) or if it
+/// references element types absent from the mesh.
+pub fn project_gauss_to_nodes<S>(
+    mesh: UMeshView,
+    field: &FieldBase<S, nd::IxDyn>,
+) -> nd::ArrayD<f64>
+where
+    S: nd::Data<Elem = f64>,
+{
+    let rules = gauss_rules(field).expect("Field is not located at integration points");
+    let n_nodes = mesh.coords().nrows();
+    let mut shape = vec![n_nodes];
+    shape.extend_from_slice(&field.full_dim()[2..]);
+    let mut res = nd::ArrayD::<f64>::zeros(shape);
+    let mut mass = vec![0.0; n_nodes];
+    for (&et, values) in &field.0 {
+        let rule = &rules[&et];
+        let squares = shape_values(et, rule).mapv(|n| n * n);
+        let masses = block_masses(&mesh, et);
+        let block = mesh.block(et).unwrap();
+        for ((e, element), masses) in block.iter(mesh.coords()).enumerate().zip(masses) {
+            let weights = nd::Array1::from(element.integration_weights(rule).unwrap());
+            let element_values = values.index_axis(Axis(0), e);
+            for (i, (node, m)) in masses.into_iter().enumerate() {
+                let mut local_weights = &weights * &squares.column(i);
+                if local_weights.sum() <= 0.0 {
+                    local_weights = weights.clone();
+                }
+                local_weights /= local_weights.sum();
+                let mut dst = res.index_axis_mut(Axis(0), node);
+                for (g, &w) in local_weights.iter().enumerate() {
+                    dst.scaled_add(m * w, &element_values.index_axis(Axis(0), g));
+                }
+                mass[node] += m;
+            }
+        }
+    }
+    normalize(res, &mass)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{FieldLocation, UMesh};
+    use crate::mesh_examples as me;
+    use approx::assert_abs_diff_eq;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_lumped_mass() {
+        let mesh = me::make_imesh_2d(2);
+        let mass = lumped_mass(mesh.view(), None);
+        assert_abs_diff_eq!(mass.sum(), 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(mass[0], 1.0 / 16.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(mass[4], 0.25, epsilon = 1e-12);
+
+        // The row sums of the TRI6 mass matrix vanish at the corners
+        let coords = nd::arr2(&[
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [0.5, 0.0],
+            [0.5, 0.5],
+            [0.0, 0.5],
+        ]);
+        let mut tri6 = UMesh::new(coords.into_shared());
+        tri6.add_regular_block(
+            ElementType::TRI6,
+            nd::arr2(&[[0, 1, 2, 3, 4, 5]]).into_shared(),
+            None,
+        );
+        let mass = lumped_mass(tri6.view(), None);
+        assert!(mass.iter().all(|&m| m > 0.0));
+        assert_abs_diff_eq!(mass.sum(), 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(mass[0], mass[1], epsilon = 1e-12);
+        assert!(mass[3] > mass[0]);
+    }
+
+    #[test]
+    fn test_project_to_nodes() {
+        let mesh = me::make_imesh_2d(2);
+        let values = nd::arr1(&[0.0, 1.0, 2.0, 3.0]).into_dyn();
+        let field = FieldBase::new(BTreeMap::from([(ElementType::QUAD4, values)]));
+        let nodes = project_to_nodes(mesh.view(), &field);
+        assert_eq!(nodes.shape(), &[9]);
+        assert_abs_diff_eq!(nodes[[4]], 1.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(nodes[[0]], 0.0, epsilon = 1e-12);
+        // The integral of the field is preserved
+        let mass = lumped_mass(mesh.view(), None);
+        let integral: f64 = nodes.iter().zip(&mass).map(|(u, m)| u * m).sum();
+        assert_abs_diff_eq!(integral, 1.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_project_gauss_to_nodes() {
+        let mesh = me::make_mesh_2d_quad();
+        let rule = QuadratureRule::gauss(ElementType::QUAD4, 3).unwrap();
+        let location = FieldLocation::GaussPoints { order: 3 };
+        let constant = nd::Array3::from_elem((1, 4, 1), 2.0).into_dyn();
+        let field = FieldBase::new(BTreeMap::from([(ElementType::QUAD4, constant)]))
+            .with_location(location);
+        let nodes = project_gauss_to_nodes(mesh.view(), &field);
+        assert_eq!(nodes.shape(), &[4, 1]);
+        assert!(nodes.iter().all(|&u| (u - 2.0).abs() < 1e-12));
+
+        // f = 2x - 1, the first reference coordinate
+        let linear = rule.points.column(0).to_owned();
+        let linear = linear.into_shape_with_order((1, 4, 1)).unwrap().into_dyn();
+        let field =
+            FieldBase::new(BTreeMap::from([(ElementType::QUAD4, linear)])).with_location(location);
+        let nodes = project_gauss_to_nodes(mesh.view(), &field);
+        assert!(nodes[[0, 0]] < 0.0 && nodes[[1, 0]] > 0.0);
+        assert_abs_diff_eq!(nodes[[0, 0]], -nodes[[1, 0]], epsilon = 1e-12);
+        assert_abs_diff_eq!(nodes[[2, 0]], nodes[[0, 0]], epsilon = 1e-12);
+    }
+}
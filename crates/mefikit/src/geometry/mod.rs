@@ -0,0 +1,24 @@
+//! Reference element geometry and geometric predicates.
+//!
+//! This module provides reference element data, shape functions and numerical integration rules
+//! used to handle fields located at integration points, as well as the exact predicates and
+//! tolerances used by the geometric operations, and global geometric quantities of meshes (mass
+//! properties, oriented bounding boxes, lumped mass matrices).
+
+/// Rational arithmetic fallback of the intersection computations.
+#[cfg(feature = "exact")]
+pub mod exact;
+/// Mass properties and oriented bounding boxes of meshes.
+pub mod inertia;
+/// Lumped mass matrices and L2 projection of element fields to the nodes.
+pub mod mass;
+/// Exact orientation predicates and tolerances of the geometric operations.
+pub mod predicates;
+/// Gauss quadrature rules and integration point fields.
+pub mod quadrature;
+/// Shape functions of the regular element types.
+pub mod shape;
+
+pub use inertia::{MassProperties, OrientedBox, mass_properties, oriented_bounding_box};
+pub use mass::{lumped_mass, project_gauss_to_nodes, project_to_nodes};
+pub use shape::{reference_nodes, shape_functions};
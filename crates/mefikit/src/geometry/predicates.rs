@@ -0,0 +1,125 @@
+//! Exact geometric predicates and tolerances of the geometric operations.
+//!
+//! Topological decisions (on which side of a line a point lies, whether two segments cross) are
+//! taken with the adaptive precision predicates of the `robust` crate, whose sign is always
+//! exact. Tolerances are only used to decide when two points are the same, and are gathered in
+//! [`Tolerances`] so that operations chaining intersections and node merging use the same values.
+
+use robust as ro;
+
+/// Twice the signed area of the triangle `(a, b, c)`.
+///
+/// Positive when `c` is on the left of `(a, b)` (counterclockwise triangle), negative when on the
+/// right and exactly zero when the points are aligned.
+pub fn orient2d(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
+    ro::orient2d(coord(a), coord(b), coord(c))
+}
+
+/// Six times the signed volume of the tetrahedron `(a, b, c, d)`, with the sign convention of
+/// `robust`.
+///
+/// Positive when `d` is below the plane `(a, b, c)`, seen counterclockwise from above, negative
+/// when above and exactly zero when the points are coplanar.
+pub fn orient3d(a: &[f64; 3], b: &[f64; 3], c: &[f64; 3], d: &[f64; 3]) -> f64 {
+    ro::orient3d(coord3(a), coord3(b), coord3(c), coord3(d))
+}
+
+/// Positive when `d` is inside the circle through the counterclockwise points `a`, `b` and `c`,
+/// negative when outside and exactly zero when the four points are cocircular.
+pub fn incircle(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2], d: &[f64; 2]) -> f64 {
+    ro::incircle(coord(a), coord(b), coord(c), coord(d))
+}
+
+/// Positive when `e` is inside the sphere through `a`, `b`, `c` and `d` (positively oriented for
+/// [`orient3d`]), negative when outside and exactly zero when the five points are cospherical.
+pub fn insphere(a: &[f64; 3], b: &[f64; 3], c: &[f64; 3], d: &[f64; 3], e: &[f64; 3]) -> f64 {
+    ro::insphere(coord3(a), coord3(b), coord3(c), coord3(d), coord3(e))
+}
+
+fn coord(p: &[f64; 2]) -> ro::Coord<f64> {
+    ro::Coord { x: p[0], y: p[1] }
+}
+
+fn coord3(p: &[f64; 3]) -> ro::Coord3D<f64> {
+    ro::Coord3D {
+        x: p[0],
+        y: p[1],
+        z: p[2],
+    }
+}
+
+/// Tolerances of the geometric operations (intersections, node merging).
+///
+/// Two points are the same when closer than `distance`, or than `relative` times the size of the
+/// entities they come from, whichever is larger. The relative part absorbs the rounding errors of
+/// computed points such as intersections, the absolute part merges the nodes of meshes which are
+/// not exactly conform.
+///
+/// A plain `f64` converts into tolerances with this absolute distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerances {
+    /// Absolute distance under which two points are the same.
+    pub distance: f64,
+    /// Distance under which two points are the same, relative to the size of the entities.
+    pub relative: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Self {
+            distance: 0.0,
+            relative: 64.0 * f64::EPSILON,
+        }
+    }
+}
+
+impl From<f64> for Tolerances {
+    fn from(distance: f64) -> Self {
+        Self::new(distance)
+    }
+}
+
+impl Tolerances {
+    /// Creates tolerances with the given absolute distance and the default relative one.
+    pub fn new(distance: f64) -> Self {
+        Self {
+            distance,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the relative tolerance.
+    pub fn relative(mut self, relative: f64) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    /// Distance under which two points coming from entities of size `scale` are the same.
+    pub fn distance_at(&self, scale: f64) -> f64 {
+        self.distance.max(self.relative * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orient2d() {
+        let (a, b) = ([0.0, 0.0], [1.0, 0.0]);
+        assert!(orient2d(&a, &b, &[0.5, 1e-300]) > 0.0);
+        assert!(orient2d(&a, &b, &[0.5, -1e-300]) < 0.0);
+        assert_eq!(orient2d(&a, &b, &[3.0, 0.0]), 0.0);
+        // Points which are only aligned up to rounding errors
+        let c = [0.1 * 3.0, 0.3];
+        assert_ne!(orient2d(&[0.0, 0.0], &[0.7, 0.7], &c), 0.0);
+    }
+
+    #[test]
+    fn test_tolerances() {
+        let tol: Tolerances = 1e-3.into();
+        assert_eq!(tol.distance_at(1.0), 1e-3);
+        assert_eq!(tol.relative(1e-2).distance_at(1.0), 1e-2);
+        assert_eq!(Tolerances::default().distance_at(0.0), 0.0);
+    }
+}
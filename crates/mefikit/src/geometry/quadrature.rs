@@ -0,0 +1,560 @@
+//! Gauss quadrature rules on reference elements.
+//!
+//! Rules are given on the reference element of each element family:
+//! - SEG: `[-1, 1]`
+//! - QUAD: `[-1, 1]^2`
+//! - HEX: `[-1, 1]^3`
+//! - TRI: triangle `(0, 0), (1, 0), (0, 1)`
+//! - TET: tetrahedron `(0, 0, 0), (1, 0, 0), (0, 1, 0), (0, 0, 1)`
+//!
+//! Quadratic and cubic elements (TRI6, QUAD9, HEX21, ...) share the reference element of their
+//! linear counterpart. Poly elements have no reference element and hence no rule.
+//!
+//! Fields located at integration points are stored as regular fields of shape
+//! `[n_elem, n_gp, ...]`, with the location [`FieldLocation::GaussPoints`] giving the order of
+//! their rule. Such fields can be projected to cell averages or to nodes.
+
+use ndarray::{self as nd, Axis};
+use std::collections::BTreeMap;
+
+use crate::mesh::{ElementType, FieldBase, FieldLocation, FieldOwnedD, UMeshView};
+
+/// Reference shape of an element type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ReferenceShape {
+    /// A single point.
+    Point,
+    /// Segment `[-1, 1]`.
+    Seg,
+    /// Triangle `(0, 0), (1, 0), (0, 1)`.
+    Tri,
+    /// Square `[-1, 1]^2`.
+    Quad,
+    /// Tetrahedron `(0, 0, 0), (1, 0, 0), (0, 1, 0), (0, 0, 1)`.
+    Tet,
+    /// Cube `[-1, 1]^3`.
+    Hex,
+}
+
+impl ReferenceShape {
+    /// Returns the reference shape of an element type, or `None` for poly elements.
+    pub fn of(et: ElementType) -> Option<Self> {
+        use ElementType::*;
+        match et {
+            VERTEX => Some(Self::Point),
+            SEG2 | SEG3 | SEG4 => Some(Self::Seg),
+            TRI3 | TRI6 | TRI7 => Some(Self::Tri),
+            QUAD4 | QUAD8 | QUAD9 => Some(Self::Quad),
+            TET4 | TET10 => Some(Self::Tet),
+            HEX8 | HEX21 => Some(Self::Hex),
+            SPLINE | PGON | PHED => None,
+        }
+    }
+
+    /// Returns the number of reference coordinates.
+    pub fn dimension(&self) -> usize {
+        match self {
+            Self::Point => 0,
+            Self::Seg => 1,
+            Self::Tri | Self::Quad => 2,
+            Self::Tet | Self::Hex => 3,
+        }
+    }
+
+    /// Returns the measure (length, area, volume) of the reference element.
+    pub fn measure(&self) -> f64 {
+        match self {
+            Self::Point => 1.0,
+            Self::Seg => 2.0,
+            Self::Tri => 0.5,
+            Self::Quad => 4.0,
+            Self::Tet => 1.0 / 6.0,
+            Self::Hex => 8.0,
+        }
+    }
+
+    /// Returns `true` if the reference point `xi` lies in the reference element, up to `tol`.
+    ///
+    /// Only the first [`Self::dimension`] coordinates of `xi` are used.
+    pub fn contains(&self, xi: &[f64], tol: f64) -> bool {
+        let xi = &xi[..self.dimension()];
+        match self {
+            Self::Point => true,
+            Self::Seg | Self::Quad | Self::Hex => xi.iter().all(|x| x.abs() <= 1.0 + tol),
+            Self::Tri | Self::Tet => {
+                xi.iter().all(|&x| x >= -tol) && xi.iter().sum::<f64>() <= 1.0 + tol
+            }
+        }
+    }
+
+    /// Returns the corner nodes of the reference element, in the element node ordering.
+    pub fn vertices(&self) -> nd::Array2<f64> {
+        match self {
+            Self::Point => nd::Array2::zeros((1, 0)),
+            Self::Seg => nd::arr2(&[[-1.0], [1.0]]),
+            Self::Tri => nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]),
+            Self::Quad => nd::arr2(&[[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]),
+            Self::Tet => nd::arr2(&[
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+            ]),
+            Self::Hex => nd::arr2(&[
+                [-1.0, -1.0, -1.0],
+                [1.0, -1.0, -1.0],
+                [1.0, 1.0, -1.0],
+                [-1.0, 1.0, -1.0],
+                [-1.0, -1.0, 1.0],
+                [1.0, -1.0, 1.0],
+                [1.0, 1.0, 1.0],
+                [-1.0, 1.0, 1.0],
+            ]),
+        }
+    }
+}
+
+/// A quadrature rule on a reference element.
+///
+/// `points` has shape `[n_gp, ref_dim]` and `weights` has shape `[n_gp]`. The weights sum to the
+/// measure of the reference element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadratureRule {
+    /// Reference shape the rule is defined on.
+    pub shape: ReferenceShape,
+    /// Polynomial degree integrated exactly.
+    pub order: usize,
+    /// Integration points in reference coordinates.
+    pub points: nd::Array2<f64>,
+    /// Integration weights.
+    pub weights: nd::Array1<f64>,
+}
+
+/// Gauss-Legendre points and weights on `[-1, 1]` with `n` points (1 to 4).
+fn gauss_legendre(n: usize) -> (Vec<f64>, Vec<f64>) {
+    match n {
+        1 => (vec![0.0], vec![2.0]),
+        2 => {
+            let a = 1.0 / 3.0_f64.sqrt();
+            (vec![-a, a], vec![1.0, 1.0])
+        }
+        3 => {
+            let a = (3.0_f64 / 5.0).sqrt();
+            (vec![-a, 0.0, a], vec![5.0 / 9.0, 8.0 / 9.0, 5.0 / 9.0])
+        }
+        4 => {
+            let a = 0.339_981_043_584_856_3;
+            let b = 0.861_136_311_594_052_6;
+            let wa = 0.652_145_154_862_546_1;
+            let wb = 0.347_854_845_137_453_9;
+            (vec![-b, -a, a, b], vec![wb, wa, wa, wb])
+        }
+        _ => panic!("Gauss-Legendre rules are only available up to 4 points"),
+    }
+}
+
+/// Maximum order available for each reference shape.
+fn max_order(shape: ReferenceShape) -> usize {
+    match shape {
+        ReferenceShape::Point => usize::MAX,
+        ReferenceShape::Seg | ReferenceShape::Quad | ReferenceShape::Hex => 7,
+        ReferenceShape::Tri => 5,
+        ReferenceShape::Tet => 3,
+    }
+}
+
+/// Builds a tensor product rule of Gauss-Legendre points.
+fn tensor_rule(shape: ReferenceShape, order: usize) -> QuadratureRule {
+    let dim = shape.dimension();
+    let n = order.div_ceil(2).max(1);
+    let n = if 2 * n - 1 < order { n + 1 } else { n };
+    let (x, w) = gauss_legendre(n);
+    let n_gp = n.pow(dim as u32);
+    let mut points = nd::Array2::zeros((n_gp, dim));
+    let mut weights = nd::Array1::ones(n_gp);
+    for g in 0..n_gp {
+        let mut rest = g;
+        for d in 0..dim {
+            let i = rest % n;
+            rest /= n;
+            points[[g, d]] = x[i];
+            weights[g] *= w[i];
+        }
+    }
+    QuadratureRule {
+        shape,
+        order: 2 * n - 1,
+        points,
+        weights,
+    }
+}
+
+/// Builds a rule from fully symmetric triangle orbits `(a, weight)` and the centroid weight.
+fn tri_rule(order: usize, centroid: Option<f64>, orbits: &[(f64, f64)]) -> QuadratureRule {
+    let mut points = Vec::new();
+    let mut weights = Vec::new();
+    if let Some(w) = centroid {
+        points.extend([1.0 / 3.0, 1.0 / 3.0]);
+        weights.push(w / 2.0);
+    }
+    for &(a, w) in orbits {
+        let b = 1.0 - 2.0 * a;
+        points.extend([a, a, b, a, a, b]);
+        weights.extend([w / 2.0; 3]);
+    }
+    QuadratureRule {
+        shape: ReferenceShape::Tri,
+        order,
+        points: nd::Array2::from_shape_vec((weights.len(), 2), points).unwrap(),
+        weights: nd::Array1::from(weights),
+    }
+}
+
+fn simplex_rule(shape: ReferenceShape, order: usize) -> QuadratureRule {
+    match (shape, order) {
+        (ReferenceShape::Tri, 0 | 1) => tri_rule(1, Some(1.0), &[]),
+        (ReferenceShape::Tri, 2) => tri_rule(2, None, &[(1.0 / 6.0, 1.0 / 3.0)]),
+        (ReferenceShape::Tri, 3 | 4) => tri_rule(
+            4,
+            None,
+            &[
+                (0.445_948_490_915_965, 0.223_381_589_678_011),
+                (0.091_576_213_509_771, 0.109_951_743_655_322),
+            ],
+        ),
+        (ReferenceShape::Tri, 5) => tri_rule(
+            5,
+            Some(0.225),
+            &[
+                (0.470_142_064_105_115, 0.132_394_152_788_506),
+                (0.101_286_507_323_456, 0.125_939_180_544_827),
+            ],
+        ),
+        (ReferenceShape::Tet, 0 | 1) => QuadratureRule {
+            shape,
+            order: 1,
+            points: nd::arr2(&[[0.25, 0.25, 0.25]]),
+            weights: nd::arr1(&[1.0 / 6.0]),
+        },
+        (ReferenceShape::Tet, 2) => {
+            let a = 0.138_196_601_125_010_5;
+            let b = 0.585_410_196_624_968_5;
+            QuadratureRule {
+                shape,
+                order: 2,
+                points: nd::arr2(&[[a, a, a], [b, a, a], [a, b, a], [a, a, b]]),
+                weights: nd::arr1(&[1.0 / 24.0; 4]),
+            }
+        }
+        (ReferenceShape::Tet, 3) => {
+            let a = 1.0 / 6.0;
+            let b = 0.5;
+            QuadratureRule {
+                shape,
+                order: 3,
+                points: nd::arr2(&[
+                    [0.25, 0.25, 0.25],
+                    [a, a, a],
+                    [b, a, a],
+                    [a, b, a],
+                    [a, a, b],
+                ]),
+                weights: nd::arr1(&[-2.0 / 15.0, 3.0 / 40.0, 3.0 / 40.0, 3.0 / 40.0, 3.0 / 40.0]),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+impl QuadratureRule {
+    /// Returns the Gauss rule integrating exactly polynomials of degree `order` on the reference
+    /// element of `et`.
+    ///
+    /// Returns `None` for poly elements or if no rule of this order is available (orders up to 7
+    /// on segments, quadrangles and hexahedra, 5 on triangles and 3 on tetrahedra).
+    pub fn gauss(et: ElementType, order: usize) -> Option<Self> {
+        let shape = ReferenceShape::of(et)?;
+        if order > max_order(shape) {
+            return None;
+        }
+        Some(match shape {
+            ReferenceShape::Point => QuadratureRule {
+                shape,
+                order,
+                points: nd::Array2::zeros((1, 0)),
+                weights: nd::arr1(&[1.0]),
+            },
+            ReferenceShape::Seg | ReferenceShape::Quad | ReferenceShape::Hex => {
+                tensor_rule(shape, order)
+            }
+            ReferenceShape::Tri | ReferenceShape::Tet => simplex_rule(shape, order),
+        })
+    }
+
+    /// Returns the lowest order Gauss rule of `et` having exactly `n_gp` points.
+    ///
+    /// This finds the rule of values read from a format storing only their number of points, to
+    /// give them a [`FieldLocation::GaussPoints`].
+    pub fn with_num_points(et: ElementType, n_gp: usize) -> Option<Self> {
+        let shape = ReferenceShape::of(et)?;
+        let max = max_order(shape).min(7);
+        (0..=max)
+            .filter_map(|o| Self::gauss(et, o))
+            .find(|r| r.len() == n_gp)
+    }
+
+    /// Returns the number of integration points.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Returns `true` if the rule has no point.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Integrates a function given in reference coordinates.
+    pub fn integrate<F: Fn(&[f64]) -> f64>(&self, f: F) -> f64 {
+        self.points
+            .rows()
+            .into_iter()
+            .zip(self.weights.iter())
+            .map(|(p, w)| w * f(p.as_slice().unwrap()))
+            .sum()
+    }
+
+    /// Returns the index of the integration point closest to each corner of the reference element.
+    fn nearest_points_to_vertices(&self) -> Vec<usize> {
+        self.shape
+            .vertices()
+            .rows()
+            .into_iter()
+            .map(|v| {
+                self.points
+                    .rows()
+                    .into_iter()
+                    .map(|p| (&p - &v).mapv(|x| x * x).sum())
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(i, _)| i)
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Returns the Gauss rule of each element type of a field located at integration points.
+///
+/// The field must be located at [`FieldLocation::GaussPoints`], with arrays of shape
+/// `[n_elem, n_gp, ...]`. Returns `None` for other locations, if an element type has no Gauss
+/// rule of the order of the field, or if this rule does not have `n_gp` points.
+pub fn gauss_rules<S>(
+    field: &FieldBase<S, nd::IxDyn>,
+) -> Option<BTreeMap<ElementType, QuadratureRule>>
+where
+    S: nd::Data<Elem = f64>,
+{
+    let FieldLocation::GaussPoints { order } = field.location() else {
+        return None;
+    };
+    field
+        .0
+        .iter()
+        .map(|(&et, arr)| {
+            let rule = QuadratureRule::gauss(et, order)?;
+            (arr.shape().get(1) == Some(&rule.len())).then_some((et, rule))
+        })
+        .collect()
+}
+
+/// Averages a field located at integration points over each cell.
+///
+/// A field of shape `[n_elem, n_gp, ...]` is turned into a cell field of shape `[n_elem, ...]`
+/// using the quadrature weights of its Gauss rule.
+///
+/// # Panics
+/// Panics if the field is not located at integration points (see [`gauss_rules`]).
+pub fn gauss_to_cells<S>(field: &FieldBase<S, nd::IxDyn>) -> FieldOwnedD
+where
+    S: nd::Data<Elem = f64>,
+{
+    let rules = gauss_rules(field).expect("Field is not located at integration points");
+    FieldBase::new(
+        field
+            .0
+            .iter()
+            .map(|(et, arr)| {
+                let rule = &rules[et];
+                let w = &rule.weights / rule.weights.sum();
+                let mut res = nd::ArrayD::<f64>::zeros(arr.index_axis(Axis(1), 0).shape());
+                for (g, &wg) in w.iter().enumerate() {
+                    res.scaled_add(wg, &arr.index_axis(Axis(1), g));
+                }
+                (*et, res)
+            })
+            .collect(),
+    )
+}
+
+/// Projects a field located at integration points to the mesh nodes.
+///
+/// Each corner node of an element takes the value of the closest integration point, other nodes
+/// (mid-edge, face or volume nodes) take the cell average. Contributions of all elements sharing a
+/// node are then averaged. The result has shape `[n_nodes, ...]`; nodes not used by the field
+/// elements are set to zero.
+///
+/// # Panics
+/// Panics if the field is not located at integration points (see [`gauss_rules`]) or if it
+/// references element types absent from the mesh.
+pub fn gauss_to_nodes<S>(mesh: UMeshView, field: &FieldBase<S, nd::IxDyn>) -> nd::ArrayD<f64>
+where
+    S: nd::Data<Elem = f64>,
+{
+    let rules = gauss_rules(field).expect("Field is not located at integration points");
+    let averages = gauss_to_cells(field);
+    let n_nodes = mesh.coords().nrows();
+    let mut shape = vec![n_nodes];
+    shape.extend_from_slice(&field.full_dim()[2..]);
+    let mut res = nd::ArrayD::<f64>::zeros(shape);
+    let mut count = vec![0usize; n_nodes];
+    for (et, arr) in &field.0 {
+        let block = mesh
+            .block(*et)
+            .unwrap_or_else(|| panic!("Element type {et:?} is not in the mesh"));
+        let nearest = rules[et].nearest_points_to_vertices();
+        let avg = &averages.0[et];
+        for e in 0..block.len() {
+            for (i, &node) in block.element_connectivity(e).iter().enumerate() {
+                let mut dst = res.index_axis_mut(Axis(0), node);
+                match nearest.get(i) {
+                    Some(&g) => dst += &arr.index_axis(Axis(0), e).index_axis(Axis(0), g),
+                    None => dst += &avg.index_axis(Axis(0), e),
+                }
+                count[node] += 1;
+            }
+        }
+    }
+    for (node, &c) in count.iter().enumerate() {
+        if c > 1 {
+            res.index_axis_mut(Axis(0), node)
+                .mapv_inplace(|x| x / c as f64);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_weights_sum_to_reference_measure() {
+        use ElementType::*;
+        for et in [VERTEX, SEG2, TRI3, QUAD4, TET4, HEX8, TRI6, HEX21] {
+            let shape = ReferenceShape::of(et).unwrap();
+            for order in 0..=max_order(shape).min(7) {
+                let rule = QuadratureRule::gauss(et, order).unwrap();
+                assert_abs_diff_eq!(rule.weights.sum(), shape.measure(), epsilon = 1e-12);
+                assert_eq!(rule.points.ncols(), shape.dimension());
+            }
+        }
+        assert!(QuadratureRule::gauss(PGON, 1).is_none());
+        assert!(QuadratureRule::gauss(TET4, 4).is_none());
+    }
+
+    #[test]
+    fn test_seg_exactness() {
+        for order in 0..=7 {
+            let rule = QuadratureRule::gauss(ElementType::SEG2, order).unwrap();
+            assert!(rule.order >= order);
+            // \int_{-1}^{1} x^k = 2 / (k + 1) for even k
+            let res = rule.integrate(|x| x[0].powi(order as i32));
+            let expected = if order % 2 == 0 {
+                2.0 / (order as f64 + 1.0)
+            } else {
+                0.0
+            };
+            assert_abs_diff_eq!(res, expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_tri_exactness() {
+        // \int_T x^a y^b = a! b! / (a + b + 2)!
+        let fact = |n: i32| (1..=n).product::<i32>() as f64;
+        for order in 0..=5 {
+            let rule = QuadratureRule::gauss(ElementType::TRI3, order).unwrap();
+            for a in 0..=order as i32 {
+                let b = order as i32 - a;
+                let res = rule.integrate(|x| x[0].powi(a) * x[1].powi(b));
+                assert_abs_diff_eq!(res, fact(a) * fact(b) / fact(a + b + 2), epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tet_exactness() {
+        // \int_T x^a y^b z^c = a! b! c! / (a + b + c + 3)!
+        let fact = |n: i32| (1..=n).product::<i32>() as f64;
+        for order in 0..=3 {
+            let rule = QuadratureRule::gauss(ElementType::TET4, order).unwrap();
+            let res = rule.integrate(|x| x[0].powi(order as i32));
+            assert_abs_diff_eq!(
+                res,
+                fact(order as i32) / fact(order as i32 + 3),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_tensor_rule() {
+        let rule = QuadratureRule::gauss(ElementType::HEX8, 3).unwrap();
+        assert_eq!(rule.len(), 8);
+        let res = rule.integrate(|x| x[0].powi(2) * x[1].powi(2) * x[2].powi(2));
+        assert_abs_diff_eq!(res, 8.0 / 27.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_with_num_points() {
+        let rule = QuadratureRule::with_num_points(ElementType::QUAD4, 9).unwrap();
+        assert_eq!(rule.order, 5);
+        let rule = QuadratureRule::with_num_points(ElementType::TRI3, 3).unwrap();
+        assert_eq!(rule.order, 2);
+        assert!(QuadratureRule::with_num_points(ElementType::TRI3, 2).is_none());
+    }
+
+    #[test]
+    fn test_gauss_to_cells_and_nodes() {
+        let mesh = crate::mesh_examples::make_mesh_2d_quad();
+        let rule = QuadratureRule::gauss(ElementType::QUAD4, 3).unwrap();
+        // f(x, y) = x at integration points, one component
+        let values = rule.points.column(0).to_owned();
+        let arr = values.into_shape_with_order((1, 4, 1)).unwrap().into_dyn();
+        let field = FieldBase::new(BTreeMap::from([(ElementType::QUAD4, arr)]));
+        // The same values at the cells, as 4 components
+        assert!(gauss_rules(&field).is_none());
+        let field = field.with_location(FieldLocation::GaussPoints { order: 3 });
+        assert_eq!(
+            gauss_rules(&field.view()).unwrap()[&ElementType::QUAD4],
+            rule
+        );
+        let wrong_order = field
+            .clone()
+            .with_location(FieldLocation::GaussPoints { order: 5 });
+        assert!(gauss_rules(&wrong_order).is_none());
+
+        let cells = gauss_to_cells(&field);
+        assert_eq!(cells.0[&ElementType::QUAD4].shape(), &[1, 1]);
+        assert_abs_diff_eq!(cells.0[&ElementType::QUAD4][[0, 0]], 0.0, epsilon = 1e-12);
+
+        let nodes = gauss_to_nodes(mesh.view(), &field);
+        assert_eq!(nodes.shape(), &[4, 1]);
+        let a = 1.0 / 3.0_f64.sqrt();
+        assert_abs_diff_eq!(nodes[[0, 0]], -a, epsilon = 1e-12);
+        assert_abs_diff_eq!(nodes[[1, 0]], a, epsilon = 1e-12);
+        assert_abs_diff_eq!(nodes[[3, 0]], a, epsilon = 1e-12);
+        assert_abs_diff_eq!(nodes[[2, 0]], -a, epsilon = 1e-12);
+    }
+}
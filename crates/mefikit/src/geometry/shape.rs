@@ -0,0 +1,316 @@
+//! Shape functions of the regular element types.
+//!
+//! Shape functions are given on the reference elements of [`ReferenceShape`]. Nodes are ordered
+//! as in the element connectivity: the corners first, then the middles of the edges (or the
+//! interior nodes of SEG4), then the center of the element if any. Element families use:
+//! - Lagrange tensor products for SEG, QUAD4, QUAD9 and HEX8,
+//! - serendipity functions for QUAD8, and for HEX21 which adds a center bubble to them,
+//! - barycentric polynomials for TRI and TET, TRI7 adding a center bubble to TRI6.
+//!
+//! Each shape function is one on its node and zero on the others, and they sum to one.
+
+use ndarray as nd;
+
+use super::quadrature::ReferenceShape;
+use crate::mesh::ElementType;
+use crate::topology::templates;
+
+/// Corners of the edges carrying a middle node, in the node ordering of the element.
+fn mid_edges(et: ElementType) -> impl Iterator<Item = [usize; 2]> {
+    templates::edges(et)
+        .filter(|edges| edges.element_type == ElementType::SEG3)
+        .map_or(&[][..], |edges| edges.nodes)
+        .iter()
+        .map(|edge| [edge[0], edge[1]])
+}
+
+fn has_center(et: ElementType) -> bool {
+    matches!(
+        et,
+        ElementType::TRI7 | ElementType::QUAD9 | ElementType::HEX21
+    )
+}
+
+/// Returns the reference coordinates of the nodes of an element type, or `None` for poly
+/// elements.
+///
+/// The result has shape `[n_nodes, ref_dim]`.
+pub fn reference_nodes(et: ElementType) -> Option<nd::Array2<f64>> {
+    let shape = ReferenceShape::of(et)?;
+    let vertices = shape.vertices();
+    let mut nodes: Vec<Vec<f64>> = vertices.rows().into_iter().map(|r| r.to_vec()).collect();
+    if et == ElementType::SEG4 {
+        nodes.extend([vec![-1.0 / 3.0], vec![1.0 / 3.0]]);
+    }
+    for [a, b] in mid_edges(et) {
+        nodes.push(((&vertices.row(a) + &vertices.row(b)) / 2.0).to_vec());
+    }
+    if has_center(et) {
+        nodes.push(vertices.mean_axis(nd::Axis(0)).unwrap().to_vec());
+    }
+    let n_nodes = nodes.len();
+    Some(nd::Array2::from_shape_vec((n_nodes, shape.dimension()), nodes.concat()).unwrap())
+}
+
+/// Evaluates the shape functions of an element type and their gradients at a reference point.
+///
+/// Returns the values, of shape `[n_nodes]`, and the gradients with respect to the reference
+/// coordinates, of shape `[n_nodes, ref_dim]`. Only the first `ref_dim` coordinates of `xi` are
+/// used, so padded coordinates such as those of [`crate::element_traits::ElementGeo::to_reference`]
+/// can be passed directly.
+///
+/// Returns `None` for poly elements, which have no reference element.
+///
+/// # Panics
+/// Panics if `xi` has less coordinates than the reference element.
+pub fn shape_functions(et: ElementType, xi: &[f64]) -> Option<(nd::Array1<f64>, nd::Array2<f64>)> {
+    use ElementType::*;
+    let nodes = reference_nodes(et)?;
+    let dim = nodes.ncols();
+    assert!(
+        xi.len() >= dim,
+        "{et:?} shape functions need {dim} reference coordinates"
+    );
+    let xi = &xi[..dim];
+    let res = match et {
+        TRI3 | TET4 | TRI6 | TET10 => simplex(et, xi),
+        TRI7 => {
+            let center = [1.0 / 3.0; 2];
+            let l = [1.0 - xi[0] - xi[1], xi[0], xi[1]];
+            let bubble = 27.0 * l[0] * l[1] * l[2];
+            let gradient = nd::arr1(&[27.0 * (l[0] - l[1]) * l[2], 27.0 * (l[0] - l[2]) * l[1]]);
+            with_bubble(
+                simplex(TRI6, xi),
+                simplex(TRI6, &center).0,
+                bubble,
+                gradient,
+            )
+        }
+        QUAD8 => serendipity(nodes.view(), xi),
+        HEX21 => {
+            let corners_and_edges = nodes.slice(nd::s![..20, ..]);
+            let bubble = xi.iter().map(|x| 1.0 - x * x).product();
+            let gradient = nd::Array1::from_shape_fn(3, |i| {
+                (0..3)
+                    .map(|j| match j == i {
+                        true => -2.0 * xi[j],
+                        false => 1.0 - xi[j] * xi[j],
+                    })
+                    .product::<f64>()
+            });
+            with_bubble(
+                serendipity(corners_and_edges, xi),
+                serendipity(corners_and_edges, &[0.0; 3]).0,
+                bubble,
+                gradient,
+            )
+        }
+        _ => tensor(nodes.view(), xi),
+    };
+    Some(res)
+}
+
+/// Value and derivative at `x` of the Lagrange polynomial of `points` which is one on `points[k]`.
+fn lagrange_1d(points: &[f64], k: usize, x: f64) -> (f64, f64) {
+    let factor = |m: usize| (x - points[m]) / (points[k] - points[m]);
+    let others = || (0..points.len()).filter(move |&m| m != k);
+    let value = others().map(factor).product();
+    let derivative = others()
+        .map(|n| {
+            others().filter(|&m| m != n).map(factor).product::<f64>() / (points[k] - points[n])
+        })
+        .sum();
+    (value, derivative)
+}
+
+/// Tensor products of the 1D Lagrange polynomials on the node coordinates of each axis.
+fn tensor(nodes: nd::ArrayView2<f64>, xi: &[f64]) -> (nd::Array1<f64>, nd::Array2<f64>) {
+    let dim = xi.len();
+    let points: Vec<Vec<f64>> = nodes
+        .columns()
+        .into_iter()
+        .map(|c| {
+            let mut p = c.to_vec();
+            p.sort_by(f64::total_cmp);
+            p.dedup();
+            p
+        })
+        .collect();
+    let mut values = nd::Array1::ones(nodes.nrows());
+    let mut gradients = nd::Array2::ones((nodes.nrows(), dim));
+    for (i, node) in nodes.rows().into_iter().enumerate() {
+        for j in 0..dim {
+            let k = points[j].iter().position(|&p| p == node[j]).unwrap();
+            let (l, dl) = lagrange_1d(&points[j], k, xi[j]);
+            values[i] *= l;
+            for m in 0..dim {
+                gradients[[i, m]] *= if m == j { dl } else { l };
+            }
+        }
+    }
+    (values, gradients)
+}
+
+/// Serendipity functions of the corner and edge nodes of a quadrangle or a hexahedron.
+fn serendipity(nodes: nd::ArrayView2<f64>, xi: &[f64]) -> (nd::Array1<f64>, nd::Array2<f64>) {
+    let dim = xi.len();
+    let scale = 0.5_f64.powi(dim as i32);
+    let mut values = nd::Array1::zeros(nodes.nrows());
+    let mut gradients = nd::Array2::zeros((nodes.nrows(), dim));
+    for (i, v) in nodes.rows().into_iter().enumerate() {
+        let factors: Vec<f64> = (0..dim).map(|j| 1.0 + v[j] * xi[j]).collect();
+        let product_except = |skip: &[usize]| -> f64 {
+            (0..dim)
+                .filter(|j| !skip.contains(j))
+                .map(|j| factors[j])
+                .product()
+        };
+        match (0..dim).find(|&j| v[j] == 0.0) {
+            None => {
+                let s = (0..dim).map(|j| v[j] * xi[j]).sum::<f64>() - (dim - 1) as f64;
+                values[i] = scale * product_except(&[]) * s;
+                for j in 0..dim {
+                    gradients[[i, j]] =
+                        scale * v[j] * (product_except(&[j]) * s + product_except(&[]));
+                }
+            }
+            Some(k) => {
+                let bubble = 1.0 - xi[k] * xi[k];
+                values[i] = 2.0 * scale * bubble * product_except(&[k]);
+                for j in 0..dim {
+                    gradients[[i, j]] = match j == k {
+                        true => -4.0 * scale * xi[k] * product_except(&[k]),
+                        false => 2.0 * scale * bubble * v[j] * product_except(&[j, k]),
+                    };
+                }
+            }
+        }
+    }
+    (values, gradients)
+}
+
+/// Linear or quadratic functions of the barycentric coordinates of a triangle or a tetrahedron.
+fn simplex(et: ElementType, xi: &[f64]) -> (nd::Array1<f64>, nd::Array2<f64>) {
+    let dim = xi.len();
+    let mut l = vec![1.0 - xi.iter().sum::<f64>()];
+    l.extend_from_slice(xi);
+    // Derivative of the barycentric coordinate `k` with respect to `xi[j]`
+    let dl = |k: usize, j: usize| match k {
+        0 => -1.0,
+        _ if k == j + 1 => 1.0,
+        _ => 0.0,
+    };
+    let quadratic = mid_edges(et).next().is_some();
+    // Values and derivatives with respect to the barycentric coordinates
+    let mut nodes: Vec<(f64, Vec<f64>)> = (0..=dim)
+        .map(|i| {
+            let mut dn = vec![0.0; dim + 1];
+            match quadratic {
+                true => {
+                    dn[i] = 4.0 * l[i] - 1.0;
+                    (l[i] * (2.0 * l[i] - 1.0), dn)
+                }
+                false => {
+                    dn[i] = 1.0;
+                    (l[i], dn)
+                }
+            }
+        })
+        .collect();
+    for [a, b] in mid_edges(et) {
+        let mut dn = vec![0.0; dim + 1];
+        dn[a] = 4.0 * l[b];
+        dn[b] = 4.0 * l[a];
+        nodes.push((4.0 * l[a] * l[b], dn));
+    }
+    let values = nodes.iter().map(|(n, _)| *n).collect();
+    let gradients = nd::Array2::from_shape_fn((nodes.len(), dim), |(i, j)| {
+        (0..=dim).map(|k| nodes[i].1[k] * dl(k, j)).sum()
+    });
+    (values, gradients)
+}
+
+/// Adds a center bubble to shape functions, correcting them to stay zero at the center.
+///
+/// `at_center` are the values of the original functions at the center, where the bubble is one.
+fn with_bubble(
+    (values, gradients): (nd::Array1<f64>, nd::Array2<f64>),
+    at_center: nd::Array1<f64>,
+    bubble: f64,
+    bubble_gradient: nd::Array1<f64>,
+) -> (nd::Array1<f64>, nd::Array2<f64>) {
+    let mut values = (values - &at_center * bubble).to_vec();
+    values.push(bubble);
+    let mut gradients = gradients
+        - &at_center.insert_axis(nd::Axis(1)) * &bubble_gradient.view().insert_axis(nd::Axis(0));
+    gradients.push_row(bubble_gradient.view()).unwrap();
+    (nd::Array1::from(values), gradients)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ElementType::*;
+    use approx::assert_abs_diff_eq;
+
+    const TYPES: [ElementType; 14] = [
+        VERTEX, SEG2, SEG3, SEG4, TRI3, TRI6, TRI7, QUAD4, QUAD8, QUAD9, TET4, TET10, HEX8, HEX21,
+    ];
+
+    #[test]
+    fn test_kronecker() {
+        for et in TYPES {
+            let nodes = reference_nodes(et).unwrap();
+            assert_eq!(Some(nodes.nrows()), et.num_nodes(), "{et:?}");
+            for (i, node) in nodes.rows().into_iter().enumerate() {
+                let (values, _) = shape_functions(et, node.as_slice().unwrap()).unwrap();
+                for (j, &v) in values.iter().enumerate() {
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert_abs_diff_eq!(v, expected, epsilon = 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition_of_unity_and_gradients() {
+        let xi = [0.21, 0.13, 0.32];
+        let h = 1e-6;
+        for et in TYPES {
+            let (values, gradients) = shape_functions(et, &xi).unwrap();
+            assert_abs_diff_eq!(values.sum(), 1.0, epsilon = 1e-12);
+            for j in 0..gradients.ncols() {
+                assert_abs_diff_eq!(gradients.column(j).sum(), 0.0, epsilon = 1e-12);
+                // Centered finite differences
+                let (mut plus, mut minus) = (xi, xi);
+                plus[j] += h;
+                minus[j] -= h;
+                let (vp, _) = shape_functions(et, &plus).unwrap();
+                let (vm, _) = shape_functions(et, &minus).unwrap();
+                for i in 0..values.len() {
+                    let fd = (vp[i] - vm[i]) / (2.0 * h);
+                    assert_abs_diff_eq!(gradients[[i, j]], fd, epsilon = 1e-8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        let (values, gradients) = shape_functions(QUAD4, &[0.0, 0.0]).unwrap();
+        assert_eq!(values, nd::arr1(&[0.25; 4]));
+        assert_eq!(gradients.row(0), nd::arr1(&[-0.25, -0.25]));
+        assert_eq!(gradients.row(2), nd::arr1(&[0.25, 0.25]));
+
+        let (_, gradients) = shape_functions(TET4, &[0.1, 0.2, 0.3]).unwrap();
+        assert_eq!(gradients.row(0), nd::arr1(&[-1.0, -1.0, -1.0]));
+        assert_eq!(gradients.row(3), nd::arr1(&[0.0, 0.0, 1.0]));
+
+        // Middle node of SEG3
+        let (values, gradients) = shape_functions(SEG3, &[0.5]).unwrap();
+        assert_abs_diff_eq!(values[2], 0.75, epsilon = 1e-15);
+        assert_abs_diff_eq!(gradients[[2, 0]], -1.0, epsilon = 1e-15);
+
+        assert!(shape_functions(PGON, &[0.0, 0.0]).is_none());
+    }
+}
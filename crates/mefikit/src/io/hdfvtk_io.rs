@@ -1,9 +1,11 @@
-use crate::mesh::{ElementLike, ElementType, UMesh, UMeshView};
+use crate::mesh::{ElementLike, ElementType, Regularity, UMesh, UMeshView};
+use crate::stream::CellChunk;
 use hdf5_metno::{
-    File,
+    Dataset, File, H5Type,
     types::{FixedAscii, FixedUnicode, TypeDescriptor, VarLenAscii, VarLenUnicode},
 };
-use ndarray::{Array1, Array2, arr1, s};
+use ndarray::{ArcArray2, Array1, Array2, ArrayView, Dimension, arr1, s};
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
 
 fn el_to_usize(code: usize) -> Result<ElementType, Box<dyn std::error::Error>> {
@@ -34,12 +36,91 @@ fn usize_to_el(el_type: ElementType) -> Result<usize, Box<dyn std::error::Error>
     }
 }
 
-fn handle_unstructured(block: &hdf5_metno::Group) -> Result<UMesh, Box<dyn std::error::Error>> {
+/// Position of a time step in the datasets of a transient VTKHDF unstructured grid.
+///
+/// Only the first partition of the step is read.
+struct StepRange {
+    points: (usize, usize),
+    cells: (usize, usize),
+    offsets: usize,
+    connectivity: (usize, usize),
+}
+
+fn read_at(dataset: &Dataset, i: usize) -> Result<usize, Box<dyn std::error::Error>> {
+    let values: Array1<i64> = dataset.read_slice_1d(s![i..i + 1])?;
+    Ok(values[0] as usize)
+}
+
+fn read_usize_attr(
+    group: &hdf5_metno::Group,
+    name: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    Ok(group.attr(name)?.read_scalar::<i64>()? as usize)
+}
+
+fn step_range(
+    block: &hdf5_metno::Group,
+    step: usize,
+) -> Result<StepRange, Box<dyn std::error::Error>> {
+    let Ok(steps) = block.group("Steps") else {
+        if step > 0 {
+            return Err("Not a transient VTKHDF file, only time step 0 can be read".into());
+        }
+        let num_cells = block.dataset("Types")?.size();
+        return Ok(StepRange {
+            points: (0, block.dataset("Points")?.shape()[0]),
+            cells: (0, num_cells),
+            offsets: 0,
+            connectivity: (0, block.dataset("Connectivity")?.size()),
+        });
+    };
+    let num_steps = read_usize_attr(&steps, "NSteps")?;
+    if step >= num_steps {
+        return Err(
+            format!("Time step {step} out of range, the file has {num_steps} steps").into(),
+        );
+    }
+    let part = read_at(&steps.dataset("PartOffsets")?, step)?;
+    let cell_offset = read_at(&steps.dataset("CellOffsets")?, step)?;
+    let point_offset = read_at(&steps.dataset("PointOffsets")?, step)?;
+    let conn_offset = read_at(&steps.dataset("ConnectivityIdOffsets")?, step)?;
+    Ok(StepRange {
+        points: (
+            point_offset,
+            read_at(&block.dataset("NumberOfPoints")?, part)?,
+        ),
+        cells: (
+            cell_offset,
+            read_at(&block.dataset("NumberOfCells")?, part)?,
+        ),
+        // each partition stores one more offset than its number of cells
+        offsets: cell_offset + part,
+        connectivity: (
+            conn_offset,
+            read_at(&block.dataset("NumberOfConnectivityIds")?, part)?,
+        ),
+    })
+}
+
+fn handle_unstructured(
+    block: &hdf5_metno::Group,
+    step: usize,
+    keep: &dyn Fn(ElementType) -> bool,
+) -> Result<UMesh, Box<dyn std::error::Error>> {
+    let range = step_range(block, step)?;
+    let (p0, np) = range.points;
+    let (c0, nc) = range.cells;
+    let (k0, nk) = range.connectivity;
+
     // read data from file
-    let points: Array2<f64> = block.dataset("Points")?.read()?;
-    let offsets: Array1<usize> = block.dataset("Offsets")?.read()?;
-    let conn: Array1<i64> = block.dataset("Connectivity")?.read()?;
-    let types: Array1<usize> = block.dataset("Types")?.read()?;
+    let points: Array2<f64> = block.dataset("Points")?.read_slice(s![p0..p0 + np, ..])?;
+    let offsets: Array1<usize> = block
+        .dataset("Offsets")?
+        .read_slice_1d(s![range.offsets..range.offsets + nc + 1])?;
+    let conn: Array1<i64> = block
+        .dataset("Connectivity")?
+        .read_slice_1d(s![k0..k0 + nk])?;
+    let types: Array1<usize> = block.dataset("Types")?.read_slice_1d(s![c0..c0 + nc])?;
 
     // transform data into mesh
     let mut mesh = UMesh::new(points.into());
@@ -47,6 +128,9 @@ fn handle_unstructured(block: &hdf5_metno::Group) -> Result<UMesh, Box<dyn std::
         let start = offsets[i];
         let end = offsets[i + 1];
         let el_type = el_to_usize(types[i])?;
+        if !keep(el_type) {
+            continue;
+        }
         let cell_conn: Vec<usize> = conn
             .slice(s![start..end])
             .iter()
@@ -83,19 +167,26 @@ fn read_type_attr(group: &hdf5_metno::Group) -> Result<String, Box<dyn std::erro
     }
 }
 
-pub fn read(path: &Path) -> Result<UMesh, Box<dyn std::error::Error>> {
+/// Reads the first unstructured grid of a VTKHDF file, at the given time step.
+///
+/// Only the cells whose type is kept by `keep` are added to the mesh, the nodes are all read.
+pub fn read(
+    path: &Path,
+    step: usize,
+    keep: &dyn Fn(ElementType) -> bool,
+) -> Result<UMesh, Box<dyn std::error::Error>> {
     let file = File::open(path)?;
     let vtk = file.group("VTKHDF").map_err(|_| "Not a VTKHDF file")?;
 
     match read_type_attr(&vtk)?.as_str() {
-        "UnstructuredGrid" => return handle_unstructured(&vtk),
+        "UnstructuredGrid" => return handle_unstructured(&vtk, step, keep),
         "PartitionedDataSetCollection" | "MultiBlockDataSet" => {
             for name in vtk.member_names()? {
                 let block = vtk.group(name.as_str())?;
                 dbg!(&block);
                 let Ok(_) = block.attr("Type") else { continue };
                 match read_type_attr(&block)?.as_str() {
-                    "UnstructuredGrid" => return handle_unstructured(&block),
+                    "UnstructuredGrid" => return handle_unstructured(&block, step, keep),
                     _ => continue,
                 }
             }
@@ -105,7 +196,157 @@ pub fn read(path: &Path) -> Result<UMesh, Box<dyn std::error::Error>> {
     Err(format!("No VTKHDF group found in {}", path.display()).into())
 }
 
-pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn std::error::Error>> {
+/// Whether an HDF5 file is a MED file, which has an `INFOS_GENERALES` group at its root.
+pub fn is_med(path: &Path) -> bool {
+    File::open(path).is_ok_and(|file| file.link_exists("INFOS_GENERALES"))
+}
+
+/// Finds the first unstructured grid of a VTKHDF file.
+fn unstructured_group(file: &File) -> Result<hdf5_metno::Group, Box<dyn std::error::Error>> {
+    let vtk = file.group("VTKHDF").map_err(|_| "Not a VTKHDF file")?;
+    if read_type_attr(&vtk)? == "UnstructuredGrid" {
+        return Ok(vtk);
+    }
+    for name in vtk.member_names()? {
+        let block = vtk.group(name.as_str())?;
+        if block.attr("Type").is_ok() && read_type_attr(&block)? == "UnstructuredGrid" {
+            return Ok(block);
+        }
+    }
+    Err("No VTKHDF unstructured grid found".into())
+}
+
+/// Iterator over the cells of a VTKHDF unstructured grid, reading `chunk_size` cells at a time.
+pub struct HdfVtkChunks {
+    coords: ArcArray2<f64>,
+    types: Dataset,
+    offsets: Dataset,
+    connectivity: Dataset,
+    num_cells: usize,
+    chunk_size: usize,
+    next_cell: usize,
+    read_per_type: BTreeMap<ElementType, usize>,
+    pending: VecDeque<(ElementType, CellChunk)>,
+}
+
+impl HdfVtkChunks {
+    /// Reads the cells `[start, end)` and splits them into runs of cells of the same type.
+    fn read_chunk(&mut self, start: usize, end: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let types: Array1<usize> = self.types.read_slice_1d(s![start..end])?;
+        let offsets: Array1<usize> = self.offsets.read_slice_1d(s![start..end + 1])?;
+        let conn: Array1<i64> = self
+            .connectivity
+            .read_slice_1d(s![offsets[0]..offsets[end - start]])?;
+        let mut i = 0;
+        while i < types.len() {
+            let run_end = (i..types.len())
+                .find(|&j| types[j] != types[i])
+                .unwrap_or(types.len());
+            let el_type = el_to_usize(types[i])?;
+            let data: Vec<usize> = conn
+                .slice(s![offsets[i] - offsets[0]..offsets[run_end] - offsets[0]])
+                .iter()
+                .map(|&x| x as usize)
+                .collect();
+            let mut mesh = UMesh::new(self.coords.clone());
+            match el_type.regularity() {
+                Regularity::Regular => {
+                    let conn =
+                        Array2::from_shape_vec((run_end - i, data.len() / (run_end - i)), data)?;
+                    mesh.add_regular_block(el_type, conn.into_shared(), None);
+                }
+                Regularity::Poly => {
+                    let ends: Array1<usize> =
+                        offsets.slice(s![i + 1..=run_end]).mapv(|o| o - offsets[i]);
+                    mesh.add_poly_block(
+                        el_type,
+                        Array1::from(data).into_shared(),
+                        ends.into_shared(),
+                    );
+                }
+            }
+            let first = self.read_per_type.entry(el_type).or_default();
+            self.pending.push_back((
+                el_type,
+                CellChunk {
+                    first: *first,
+                    mesh,
+                },
+            ));
+            *first += run_end - i;
+            i = run_end;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for HdfVtkChunks {
+    type Item = Result<(ElementType, CellChunk), Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && self.next_cell < self.num_cells {
+            let start = self.next_cell;
+            let end = (start + self.chunk_size).min(self.num_cells);
+            self.next_cell = end;
+            if let Err(e) = self.read_chunk(start, end) {
+                self.next_cell = self.num_cells;
+                return Some(Err(e));
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Opens a VTKHDF file for reading its cells by chunks of `chunk_size` cells.
+///
+/// Only the coordinates are read at once, connectivities being read chunk by chunk.
+pub fn read_chunks(
+    path: &Path,
+    chunk_size: usize,
+) -> Result<HdfVtkChunks, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let group = unstructured_group(&file)?;
+    let points: Array2<f64> = group.dataset("Points")?.read()?;
+    let types = group.dataset("Types")?;
+    Ok(HdfVtkChunks {
+        coords: points.into_shared(),
+        num_cells: types.size(),
+        types,
+        offsets: group.dataset("Offsets")?,
+        connectivity: group.dataset("Connectivity")?,
+        chunk_size: chunk_size.max(1),
+        next_cell: 0,
+        read_per_type: BTreeMap::new(),
+        pending: VecDeque::new(),
+    })
+}
+
+/// Writes a dataset, chunked along its first axis and deflated if `compression` is given.
+fn write_dataset<T: H5Type, D: Dimension>(
+    group: &hdf5_metno::Group,
+    name: &str,
+    data: ArrayView<T, D>,
+    compression: Option<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let builder = group.new_dataset_builder().with_data(data.view());
+    match compression {
+        // deflate needs chunks, which cannot be empty
+        Some(level) if !data.is_empty() => {
+            let mut chunk = data.shape().to_vec();
+            chunk[0] = chunk[0].min(1 << 16);
+            builder.chunk(chunk).deflate(level).create(name)?
+        }
+        _ => builder.create(name)?,
+    };
+    Ok(())
+}
+
+/// Writes a mesh as a VTKHDF unstructured grid, deflating the datasets at the given level.
+pub fn write(
+    path: &Path,
+    mesh: UMeshView,
+    compression: Option<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // create file
     let file = File::create(path)?;
     // create VTKHDF group
@@ -139,26 +380,15 @@ pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn std::error::Err
     }
 
     // write datasets
-    // coords
-    vtk.new_dataset::<f64>()
-        .shape(coords.shape())
-        .create("Points")?
-        .write(&coords)?;
-    // types
-    vtk.new_dataset::<u8>()
-        .shape([types.len()])
-        .create("Types")?
-        .write(&Array1::from(types))?;
-    // offsets
-    vtk.new_dataset::<usize>()
-        .shape([offsets.len()])
-        .create("Offsets")?
-        .write(&Array1::from(offsets))?;
-    // connectivity
-    vtk.new_dataset::<usize>()
-        .shape([connectivity.len()])
-        .create("Connectivity")?
-        .write(&Array1::from(connectivity))?;
+    write_dataset(&vtk, "Points", coords.view(), compression)?;
+    write_dataset(&vtk, "Types", Array1::from(types).view(), compression)?;
+    write_dataset(&vtk, "Offsets", Array1::from(offsets).view(), compression)?;
+    write_dataset(
+        &vtk,
+        "Connectivity",
+        Array1::from(connectivity).view(),
+        compression,
+    )?;
 
     Ok(())
 }
@@ -166,6 +396,7 @@ pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn std::error::Err
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mesh::Dimension as TopoDim;
     use crate::mesh_examples as me;
     use std::path::PathBuf;
 
@@ -184,7 +415,7 @@ mod tests {
     fn test_write_hdfvtk() {
         let path = PathBuf::from("test_write.vtkhdf");
         let mesh = me::make_mesh_2d_multi();
-        assert!(write(&path, mesh.view()).is_ok());
+        assert!(write(&path, mesh.view(), None).is_ok());
         std::fs::remove_file(path).unwrap();
     }
 
@@ -192,11 +423,26 @@ mod tests {
     fn test_roundtrip_hdfvtk() {
         let path = PathBuf::from("test_roundtrip.vtkhdf");
         let mesh = me::make_mesh_2d_multi();
-        assert!(write(&path, mesh.view()).is_ok());
-        let mesh2 = read(&path).unwrap();
+        assert!(write(&path, mesh.view(), None).is_ok());
+        let mesh2 = read(&path, 0, &|_: ElementType| true).unwrap();
         std::fs::remove_file(path).unwrap();
         for (e1, e2) in mesh.elements().zip(mesh2.elements()) {
             assert_eq!(e1.connectivity, e2.connectivity);
         }
     }
+
+    #[test]
+    fn test_compressed_hdfvtk() {
+        let path = PathBuf::from("test_compressed.vtkhdf");
+        let mesh = me::make_imesh_3d(4);
+        write(&path, mesh.view(), Some(6)).unwrap();
+        let mesh2 = read(&path, 0, &|_: ElementType| true).unwrap();
+        assert!(read(&path, 1, &|_: ElementType| true).is_err());
+        let surface = read(&path, 0, &|et: ElementType| et.dimension() == TopoDim::D2).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(surface.coords(), mesh.coords());
+        assert_eq!(surface.num_elements(), 0);
+        assert_eq!(mesh.coords(), mesh2.coords());
+        assert_eq!(mesh.num_elements(), mesh2.num_elements());
+    }
 }
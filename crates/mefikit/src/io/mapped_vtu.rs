@@ -0,0 +1,665 @@
+//! Memory-mapped reading of VTU files with appended raw data.
+//!
+//! Large meshes are read without copying their arrays: the file is mapped in memory and the mesh
+//! view borrows the coordinates, the connectivities and the cell fields from the mapping. Arrays
+//! which cannot be borrowed, because of their value type, their alignment or their layout, are
+//! copied. VTU files written by [`super::write_vtu`] with appended data can be mapped without
+//! copies.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+use ndarray as nd;
+
+use super::vtu_string::element_type;
+use crate::mesh::{BlockSlices, ElementType, RawValidation, Regularity, UMeshView};
+
+/// A value type for which any bit pattern is a valid value, so that it can be read in place.
+trait Plain: Copy {}
+
+impl Plain for f64 {}
+impl Plain for usize {}
+
+/// Reinterprets bytes as values, if they are aligned for them.
+fn cast<T: Plain>(bytes: &[u8]) -> Option<&[T]> {
+    // SAFETY: any bit pattern is a valid value of a `Plain` type
+    match unsafe { bytes.align_to::<T>() } {
+        ([], values, []) => Some(values),
+        _ => None,
+    }
+}
+
+macro_rules! le {
+    ($t:ty, $bytes:expr) => {
+        <$t>::from_le_bytes($bytes.try_into().unwrap())
+    };
+}
+
+/// The type of the values of a VTK data array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self, String> {
+        use ScalarType::*;
+        match name {
+            "Int8" => Ok(Int8),
+            "UInt8" => Ok(UInt8),
+            "Int16" => Ok(Int16),
+            "UInt16" => Ok(UInt16),
+            "Int32" => Ok(Int32),
+            "UInt32" => Ok(UInt32),
+            "Int64" => Ok(Int64),
+            "UInt64" => Ok(UInt64),
+            "Float32" => Ok(Float32),
+            "Float64" => Ok(Float64),
+            other => Err(format!("Unsupported VTK data type: {other}")),
+        }
+    }
+
+    fn size(self) -> usize {
+        use ScalarType::*;
+        match self {
+            Int8 | UInt8 => 1,
+            Int16 | UInt16 => 2,
+            Int32 | UInt32 | Float32 => 4,
+            Int64 | UInt64 | Float64 => 8,
+        }
+    }
+
+    /// Decodes a little endian value as a float.
+    fn to_f64(self, bytes: &[u8]) -> f64 {
+        use ScalarType::*;
+        match self {
+            Int8 => le!(i8, bytes).into(),
+            UInt8 => le!(u8, bytes).into(),
+            Int16 => le!(i16, bytes).into(),
+            UInt16 => le!(u16, bytes).into(),
+            Int32 => le!(i32, bytes).into(),
+            UInt32 => le!(u32, bytes).into(),
+            Int64 => le!(i64, bytes) as f64,
+            UInt64 => le!(u64, bytes) as f64,
+            Float32 => le!(f32, bytes).into(),
+            Float64 => le!(f64, bytes),
+        }
+    }
+
+    /// Decodes a little endian value as an integer, `None` for floats and too large values.
+    fn to_i64(self, bytes: &[u8]) -> Option<i64> {
+        use ScalarType::*;
+        match self {
+            Int8 => Some(le!(i8, bytes).into()),
+            UInt8 => Some(le!(u8, bytes).into()),
+            Int16 => Some(le!(i16, bytes).into()),
+            UInt16 => Some(le!(u16, bytes).into()),
+            Int32 => Some(le!(i32, bytes).into()),
+            UInt32 => Some(le!(u32, bytes).into()),
+            Int64 => Some(le!(i64, bytes)),
+            UInt64 => i64::try_from(le!(u64, bytes)).ok(),
+            Float32 | Float64 => None,
+        }
+    }
+}
+
+/// A data array of the appended data of a VTU file.
+struct RawArray {
+    name: String,
+    scalar: ScalarType,
+    components: usize,
+    /// The bytes of the values in the file.
+    data: Range<usize>,
+}
+
+impl RawArray {
+    fn len(&self) -> usize {
+        self.data.len() / self.scalar.size()
+    }
+
+    /// The bytes of the values in `range`.
+    fn bytes(&self, range: &Range<usize>) -> Range<usize> {
+        let size = self.scalar.size();
+        self.data.start + range.start * size..self.data.start + range.end * size
+    }
+
+    /// Decodes the values in `ranges` as integers.
+    fn integers(&self, file: &[u8], ranges: &[Range<usize>]) -> Result<Vec<i64>, String> {
+        let size = self.scalar.size();
+        ranges
+            .iter()
+            .flat_map(|r| file[self.bytes(r)].chunks_exact(size))
+            .map(|b| self.scalar.to_i64(b))
+            .collect::<Option<_>>()
+            .ok_or(format!("Data array {} does not hold integers", self.name))
+    }
+
+    /// Decodes the values in `ranges` as indices.
+    fn indices(&self, file: &[u8], ranges: &[Range<usize>]) -> Result<Vec<usize>, String> {
+        self.integers(file, ranges)?
+            .into_iter()
+            .map(usize::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Negative index in data array {}", self.name))
+    }
+}
+
+/// Values borrowed from the mapped file, or copied from it.
+enum Values<T> {
+    /// The bytes of the values in the file.
+    Mapped(Range<usize>),
+    Copied(Vec<T>),
+}
+
+impl<T> Values<T> {
+    fn is_mapped(&self) -> bool {
+        matches!(self, Values::Mapped(_))
+    }
+}
+
+/// The values of an array in `ranges` as floats, borrowed if they are a single aligned range of
+/// Float64.
+fn floats(file: &[u8], array: &RawArray, ranges: &[Range<usize>]) -> Values<f64> {
+    if let ([range], ScalarType::Float64) = (ranges, array.scalar) {
+        let bytes = array.bytes(range);
+        if cfg!(target_endian = "little") && cast::<f64>(&file[bytes.clone()]).is_some() {
+            return Values::Mapped(bytes);
+        }
+    }
+    let size = array.scalar.size();
+    let values = ranges
+        .iter()
+        .flat_map(|r| file[array.bytes(r)].chunks_exact(size))
+        .map(|b| array.scalar.to_f64(b));
+    Values::Copied(values.collect())
+}
+
+/// The values of an array in `ranges` as indices, borrowed if they are a single aligned range of
+/// 64 bits integers on a 64 bits little endian target.
+fn indices(
+    file: &[u8],
+    array: &RawArray,
+    ranges: &[Range<usize>],
+) -> Result<Values<usize>, String> {
+    let native = cfg!(all(target_pointer_width = "64", target_endian = "little"));
+    if let ([range], ScalarType::Int64 | ScalarType::UInt64) = (ranges, array.scalar) {
+        let bytes = array.bytes(range);
+        if native && cast::<usize>(&file[bytes.clone()]).is_some() {
+            // Negative Int64 indices become out of range indices, rejected by the validation
+            return Ok(Values::Mapped(bytes));
+        }
+    }
+    array.indices(file, ranges).map(Values::Copied)
+}
+
+/// Finds the value of an attribute in an XML tag.
+fn attribute<'x>(tag: &'x str, name: &str) -> Option<&'x str> {
+    let pattern = format!("{name}=\"");
+    tag.match_indices(&pattern)
+        .find(|&(i, _)| tag[..i].ends_with(char::is_whitespace))
+        .and_then(|(i, _)| tag[i + pattern.len()..].split('"').next())
+}
+
+/// The positions and the texts of the opening tags of an XML element.
+fn tags<'x>(xml: &'x str, name: &str) -> Vec<(usize, &'x str)> {
+    let pattern = format!("<{name}");
+    xml.match_indices(&pattern)
+        .filter(|&(i, _)| {
+            let next = xml[i + pattern.len()..].chars().next();
+            next.is_some_and(|c| c.is_whitespace() || c == '>' || c == '/')
+        })
+        .filter_map(|(i, _)| xml[i..].find('>').map(|end| (i, &xml[i..=i + end])))
+        .collect()
+}
+
+/// The range of the text of the first XML element of the given name.
+fn section(xml: &str, name: &str) -> Option<Range<usize>> {
+    let (start, _) = *tags(xml, name).first()?;
+    let end = xml[start..].find(&format!("</{name}>"))?;
+    Some(start..start + end)
+}
+
+fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes.windows(pattern.len()).position(|w| w == pattern)
+}
+
+/// Locates a data array in the appended data, which starts at `data_start` in the file.
+fn raw_array(file: &[u8], tag: &str, data_start: usize, header: usize) -> Result<RawArray, String> {
+    let name = attribute(tag, "Name").unwrap_or("").to_owned();
+    if attribute(tag, "format") != Some("appended") {
+        return Err(format!("Data array {name} is not in the appended data"));
+    }
+    let scalar = ScalarType::parse(attribute(tag, "type").unwrap_or(""))?;
+    let components = attribute(tag, "NumberOfComponents")
+        .map_or(Ok(1), str::parse)
+        .map_err(|_| format!("Invalid number of components of data array {name}"))?;
+    let start = attribute(tag, "offset")
+        .and_then(|o| o.parse::<usize>().ok())
+        .and_then(|o| o.checked_add(data_start))
+        .ok_or(format!("Invalid offset of data array {name}"))?;
+    let out_of_file = || format!("Data array {name} is out of the file");
+    let data_begin = start.checked_add(header).ok_or_else(out_of_file)?;
+    let len = match file.get(start..data_begin) {
+        Some(size) if header == 4 => le!(u32, size) as usize,
+        Some(size) => le!(u64, size) as usize,
+        None => return Err(out_of_file()),
+    };
+    let data = data_begin..data_begin.checked_add(len).ok_or_else(out_of_file)?;
+    if data.end > file.len() || len % scalar.size() != 0 {
+        return Err(out_of_file());
+    }
+    Ok(RawArray {
+        name,
+        scalar,
+        components,
+        data,
+    })
+}
+
+/// Rebuilds the connectivity of polyhedra, their faces separated by `usize::MAX`, from the
+/// faces and face offsets of a VTU file.
+fn polyhedra(
+    cells: &[Range<usize>],
+    faces: &[i64],
+    face_offsets: &[i64],
+) -> Result<(Vec<usize>, Vec<usize>), String> {
+    let mut end = 0;
+    let face_starts: Vec<usize> = face_offsets
+        .iter()
+        .map(|&o| {
+            let start = end;
+            end = usize::try_from(o).unwrap_or(end);
+            start
+        })
+        .collect();
+    let mut connectivity = Vec::new();
+    let mut offsets = Vec::new();
+    for cell in cells.iter().flat_map(|r| r.clone()) {
+        let invalid = || format!("Invalid faces of polyhedron {cell}");
+        let end = usize::try_from(face_offsets[cell]).map_err(|_| invalid())?;
+        let stream = faces.get(face_starts[cell]..end).ok_or_else(invalid)?;
+        let stream: Vec<usize> = stream
+            .iter()
+            .map(|&n| usize::try_from(n))
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid())?;
+        let (&n_faces, mut rest) = stream.split_first().ok_or_else(invalid)?;
+        for f in 0..n_faces {
+            let (&n, tail) = rest.split_first().ok_or_else(invalid)?;
+            let face = tail.get(..n).ok_or_else(invalid)?;
+            if f > 0 {
+                connectivity.push(usize::MAX);
+            }
+            connectivity.extend_from_slice(face);
+            rest = &tail[n..];
+        }
+        offsets.push(connectivity.len());
+    }
+    Ok((connectivity, offsets))
+}
+
+/// An element block of a mapped file.
+struct MappedBlock {
+    element_type: ElementType,
+    connectivity: Values<usize>,
+    /// End of each element in the connectivity, for poly blocks.
+    offsets: Option<Vec<usize>>,
+    /// Name, number of components and values of the cell fields.
+    fields: Vec<(String, usize, Values<f64>)>,
+}
+
+/// A VTU file mapped in memory, whose mesh is viewed without copying its arrays.
+///
+/// Only single piece unstructured grids with uncompressed appended raw data can be mapped.
+/// Coordinates, connectivities and float cell fields are borrowed from the mapping when they are
+/// 64 bits values, aligned on 8 bytes, and when the cells of each type are contiguous in the
+/// file; they are copied otherwise, as the offsets, the cell types and the polyhedra. As in VTU
+/// files, coordinates have 3 components.
+///
+/// ```ignore
+/// // SAFETY: the file is not modified while mapped
+/// let mapped = unsafe { MappedVtu::open(Path::new("large.vtu"))? };
+/// let mesh = mapped.view();
+/// ```
+pub struct MappedVtu {
+    mmap: Mmap,
+    points: Values<f64>,
+    blocks: Vec<MappedBlock>,
+}
+
+impl MappedVtu {
+    /// Maps a VTU file in memory and locates the arrays of its mesh.
+    ///
+    /// The mesh is checked once, node indices included, so that [`MappedVtu::view`] is cheap.
+    ///
+    /// # Safety
+    /// The file must not be modified nor truncated while it is mapped, by this process or by
+    /// another one.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be mapped, is not a VTU file with appended raw data
+    /// or holds an invalid mesh.
+    pub unsafe fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        // SAFETY: the caller guarantees that the file is not modified while mapped
+        let mmap = unsafe { Mmap::map(&file)? };
+        let (points, blocks) = Self::locate(&mmap)?;
+        let mapped = MappedVtu {
+            mmap,
+            points,
+            blocks,
+        };
+        mapped.try_view(RawValidation::Full)?;
+        Ok(mapped)
+    }
+
+    /// Locates the coordinates, the element blocks and the cell fields in the file.
+    fn locate(file: &[u8]) -> Result<(Values<f64>, Vec<MappedBlock>), String> {
+        let appended = find(file, b"<AppendedData")
+            .ok_or("Only VTU files with appended data can be mapped")?;
+        let xml = std::str::from_utf8(&file[..appended]).map_err(|_| "Invalid VTU header")?;
+        let tag_end = find(&file[appended..], b">").ok_or("Invalid VTU header")?;
+        let appended_tag = std::str::from_utf8(&file[appended..=appended + tag_end])
+            .map_err(|_| "Invalid VTU header")?;
+        if attribute(appended_tag, "encoding") != Some("raw") {
+            return Err("Only VTU files with raw appended data can be mapped".to_owned());
+        }
+        let data_start = appended
+            + find(&file[appended..], b"_").ok_or("Missing start of the appended data")?
+            + 1;
+
+        let &(_, file_tag) = tags(xml, "VTKFile").first().ok_or("Not a VTK XML file")?;
+        if attribute(file_tag, "type") != Some("UnstructuredGrid") {
+            return Err("Not a VTU unstructured grid".to_owned());
+        }
+        if attribute(file_tag, "byte_order") != Some("LittleEndian") {
+            return Err("Only little endian VTU files can be mapped".to_owned());
+        }
+        if attribute(file_tag, "compressor").is_some() {
+            return Err("Compressed VTU files cannot be mapped, they must be read".to_owned());
+        }
+        let header = match attribute(file_tag, "header_type").unwrap_or("UInt32") {
+            "UInt32" => 4,
+            "UInt64" => 8,
+            other => return Err(format!("Unsupported VTU header type: {other}")),
+        };
+        let pieces = tags(xml, "Piece");
+        let [(_, piece)] = pieces.as_slice() else {
+            return Err("Only VTU files with a single piece can be mapped".to_owned());
+        };
+        let count = |name| {
+            attribute(piece, name)
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or(format!("Invalid {name} of the VTU piece"))
+        };
+        let (n_points, n_cells) = (count("NumberOfPoints")?, count("NumberOfCells")?);
+
+        let sections = [
+            section(xml, "Points"),
+            section(xml, "Cells"),
+            section(xml, "CellData"),
+        ];
+        let mut arrays: [Vec<RawArray>; 3] = Default::default();
+        for (position, tag) in tags(xml, "DataArray") {
+            let in_section =
+                |s: &Option<Range<usize>>| s.as_ref().is_some_and(|r| r.contains(&position));
+            if let Some(i) = sections.iter().position(in_section) {
+                arrays[i].push(raw_array(file, tag, data_start, header)?);
+            }
+        }
+        let [points, cell_arrays, field_arrays] = arrays;
+        let cell_array = |name: &str| {
+            cell_arrays
+                .iter()
+                .find(|a| a.name == name)
+                .ok_or(format!("Missing {name} array in the VTU cells"))
+        };
+
+        let points = match points.as_slice() {
+            [points] if points.components == 3 && points.len() == 3 * n_points => points,
+            _ => return Err("Invalid VTU points".to_owned()),
+        };
+        let points = floats(file, points, std::slice::from_ref(&(0..3 * n_points)));
+        let all_cells = 0..n_cells;
+        let all_cells = std::slice::from_ref(&all_cells);
+        let offsets = cell_array("offsets")?.indices(file, all_cells)?;
+        let types = cell_array("types")?.integers(file, all_cells)?;
+        let connectivity = cell_array("connectivity")?;
+        if offsets.len() != n_cells
+            || types.len() != n_cells
+            || !offsets.is_sorted()
+            || offsets.last().is_some_and(|&end| end != connectivity.len())
+        {
+            return Err("Invalid VTU cells".to_owned());
+        }
+        let start = |c: usize| if c == 0 { 0 } else { offsets[c - 1] };
+
+        // The runs of cells of the same type, per type
+        let mut runs: BTreeMap<ElementType, Vec<Range<usize>>> = BTreeMap::new();
+        let mut run_start = 0;
+        for c in 1..=n_cells {
+            if c == n_cells || types[c] != types[run_start] {
+                let cell_type = u8::try_from(types[run_start]).unwrap_or(u8::MAX);
+                runs.entry(element_type(cell_type)?)
+                    .or_default()
+                    .push(run_start..c);
+                run_start = c;
+            }
+        }
+
+        let mut blocks = Vec::with_capacity(runs.len());
+        for (et, cells) in runs {
+            let nodes: Vec<Range<usize>> =
+                cells.iter().map(|r| start(r.start)..start(r.end)).collect();
+            let (connectivity, offsets) = match (et, et.regularity()) {
+                (ElementType::PHED, _) => {
+                    let faces = cell_array("faces")?;
+                    let faces = faces.integers(file, std::slice::from_ref(&(0..faces.len())))?;
+                    let face_offsets = cell_array("faceoffsets")?.integers(file, all_cells)?;
+                    if face_offsets.len() != n_cells {
+                        return Err("Invalid VTU face offsets".to_owned());
+                    }
+                    let (connectivity, offsets) = polyhedra(&cells, &faces, &face_offsets)?;
+                    (Values::Copied(connectivity), Some(offsets))
+                }
+                (_, Regularity::Regular) => (indices(file, connectivity, &nodes)?, None),
+                (_, Regularity::Poly) => {
+                    let mut end = 0;
+                    let ends = cells
+                        .iter()
+                        .flat_map(|r| r.clone())
+                        .map(|c| {
+                            end += offsets[c] - start(c);
+                            end
+                        })
+                        .collect();
+                    (indices(file, connectivity, &nodes)?, Some(ends))
+                }
+            };
+            let mut fields = Vec::new();
+            for array in &field_arrays {
+                if matches!(array.scalar, ScalarType::Float32 | ScalarType::Float64) {
+                    let k = array.components;
+                    if array.len() != k * n_cells {
+                        return Err(format!("Invalid length of the cell field {}", array.name));
+                    }
+                    let values: Vec<Range<usize>> =
+                        cells.iter().map(|r| k * r.start..k * r.end).collect();
+                    fields.push((array.name.clone(), k, floats(file, array, &values)));
+                }
+            }
+            blocks.push(MappedBlock {
+                element_type: et,
+                connectivity,
+                offsets,
+                fields,
+            });
+        }
+        Ok((points, blocks))
+    }
+
+    fn values<'a, T: Plain>(&'a self, values: &'a Values<T>) -> &'a [T] {
+        match values {
+            Values::Mapped(bytes) => cast(&self.mmap[bytes.clone()]).expect("aligned when mapped"),
+            Values::Copied(values) => values,
+        }
+    }
+
+    fn try_view(&self, validation: RawValidation) -> Result<UMeshView<'_>, String> {
+        let blocks: Vec<BlockSlices> = self
+            .blocks
+            .iter()
+            .map(|b| BlockSlices {
+                element_type: b.element_type,
+                connectivity: self.values(&b.connectivity),
+                offsets: b.offsets.as_deref(),
+                families: None,
+            })
+            .collect();
+        let mut mesh = UMeshView::from_slices(self.values(&self.points), 3, &blocks, validation)?;
+        for b in &self.blocks {
+            let block = mesh.element_blocks.get_mut(&b.element_type).unwrap();
+            for (name, components, values) in &b.fields {
+                let values = self.values(values);
+                let shape = match *components {
+                    1 => vec![values.len()],
+                    k => vec![values.len() / k, k],
+                };
+                let field = nd::ArrayViewD::from_shape(nd::IxDyn(&shape), values).unwrap();
+                block.fields.insert(name.clone(), field);
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// A view of the mesh of the file, borrowing the mapped arrays.
+    pub fn view(&self) -> UMeshView<'_> {
+        self.try_view(RawValidation::Lengths)
+            .expect("checked when the file was mapped")
+    }
+
+    /// Whether the coordinates, the connectivities and the cell fields are all borrowed from the
+    /// mapping, without copy.
+    pub fn is_zero_copy(&self) -> bool {
+        self.points.is_mapped()
+            && self.blocks.iter().all(|b| {
+                b.connectivity.is_mapped() && b.fields.iter().all(|(_, _, v)| v.is_mapped())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{VtkCompression, VtkEncoding, VtkWriteOptions, write_vtu};
+    use crate::mesh::ElementLike;
+    use crate::mesh_examples as me;
+
+    fn connectivities(mesh: &UMeshView) -> Vec<Vec<usize>> {
+        mesh.elements().map(|e| e.connectivity().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_mapped_vtu() {
+        let path = std::env::temp_dir().join("mefikit_mapped_vtu_test.vtu");
+        let mut mesh = me::make_imesh_3d(2);
+        let values = nd::Array2::from_shape_fn((8, 2), |(i, j)| (2 * i + j) as f64);
+        mesh.assign_field("f", None, values.view().into_dyn())
+            .unwrap();
+        let appended = VtkWriteOptions::new().encoding(VtkEncoding::Appended);
+        write_vtu(&path, mesh.view(), &appended).unwrap();
+
+        let mapped = unsafe { MappedVtu::open(&path) }.unwrap();
+        assert!(mapped.is_zero_copy());
+        let view = mapped.view();
+        assert_eq!(view.coords(), mesh.coords());
+        assert_eq!(connectivities(&view), connectivities(&mesh.view()));
+        let field = &view.field("f", None).unwrap().0[&ElementType::HEX8];
+        assert_eq!(field, &values.into_dyn());
+
+        let compressed = VtkWriteOptions::new().compression(VtkCompression::Lz4);
+        if write_vtu(&path, mesh.view(), &compressed).is_ok() {
+            assert!(unsafe { MappedVtu::open(&path) }.is_err());
+        }
+        write_vtu(&path, mesh.view(), &VtkWriteOptions::new()).unwrap();
+        assert!(unsafe { MappedVtu::open(&path) }.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_vtu_copies() {
+        let path = std::env::temp_dir().join("mefikit_mapped_vtu_copies_test.vtu");
+        let mut mesh = me::make_mesh_2d_multi();
+        // A tetrahedron as a polyhedron, with outward faces
+        mesh.add_element(
+            ElementType::PHED,
+            &[
+                0,
+                2,
+                1,
+                usize::MAX,
+                0,
+                1,
+                4,
+                usize::MAX,
+                1,
+                2,
+                4,
+                usize::MAX,
+                2,
+                0,
+                4,
+            ],
+            None,
+            None,
+        );
+        let appended = VtkWriteOptions::new().encoding(VtkEncoding::Appended);
+        write_vtu(&path, mesh.view(), &appended).unwrap();
+
+        let mapped = unsafe { MappedVtu::open(&path) }.unwrap();
+        assert!(!mapped.is_zero_copy());
+        let view = mapped.view();
+        assert_eq!(view.coords().ncols(), 3);
+        assert_eq!(view.coords().column(1), mesh.coords().column(1));
+        assert_eq!(connectivities(&view), connectivities(&mesh.view()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_attribute() {
+        let tag = r#"<DataArray type="Int64" Name="offsets" format="appended" offset="8"/>"#;
+        assert_eq!(attribute(tag, "offset"), Some("8"));
+        assert_eq!(attribute(tag, "Name"), Some("offsets"));
+        assert_eq!(attribute(tag, "compressor"), None);
+        assert_eq!(tags("<Points><PointData>", "Points").len(), 1);
+    }
+
+    #[test]
+    fn test_raw_array_out_of_file() {
+        let mut file = vec![0u8; 16];
+        file[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let tag = r#"<DataArray type="Float64" Name="x" format="appended" offset="0"/>"#;
+        assert!(raw_array(&file, tag, 0, 8).is_err());
+        let tag = format!(
+            r#"<DataArray type="Float64" Name="x" format="appended" offset="{}"/>"#,
+            usize::MAX
+        );
+        assert!(raw_array(&file, &tag, 1, 8).is_err());
+        file[..8].copy_from_slice(&8u64.to_le_bytes());
+        let tag = r#"<DataArray type="Float64" Name="x" format="appended" offset="0"/>"#;
+        assert_eq!(raw_array(&file, tag, 0, 8).unwrap().data, 8..16);
+    }
+}
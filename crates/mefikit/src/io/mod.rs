@@ -1,50 +1,152 @@
 //! Mesh I/O operations for reading and writing mesh files.
 //!
-//! Supports JSON, YAML, and VTK/VTU formats.
+//! Supports JSON, YAML, VTK/VTU (with the `io` feature) and VTKHDF (with the `hdf5` feature)
+//! formats, OpenFOAM `polyMesh` directories, Triangle and TetGen files, XYZ and CSV point clouds,
+//! the import of Nastran bulk data, and the formats of other crates added to the
+//! [`FormatRegistry`]. Partitioned results are written as PVTU files, one VTU file per part. With
+//! the `mmap` feature, large VTU files are mapped in memory and viewed without copy by
+//! [`MappedVtu`]. Statistics of a mesh are exported as CSV or JSON by [`export_report`].
 
 use crate::mesh::{UMesh, UMeshView};
 use std::path::Path;
 
+#[cfg(feature = "hdf5")]
 mod hdfvtk_io;
+#[cfg(feature = "mmap")]
+mod mapped_vtu;
+mod nastran;
+mod openfoam;
+mod options;
+mod point_cloud;
+mod pvtu;
+mod registry;
+mod report;
 mod serde_io;
+mod tetgen;
+#[cfg(feature = "io")]
 mod vtk_io;
+mod vtu_string;
+
+#[cfg(feature = "hdf5")]
+pub(crate) use hdfvtk_io::read_chunks;
+#[cfg(feature = "mmap")]
+pub use mapped_vtu::MappedVtu;
+pub use options::{
+    Format, ReadOptions, VtkCompression, VtkEncoding, VtkWriteOptions, WriteOptions,
+};
+pub use pvtu::{write_pvtu, write_pvtu_by_field};
+pub use registry::{Capabilities, FormatRegistry, MeshFormat, register_format, registry};
+pub use report::{ReportKind, export_report};
+pub use vtu_string::{to_vtu_bytes, to_vtu_string};
 
 /// Reads a mesh from the given file path.
 ///
-/// The file format is determined by the file extension.
-/// Supported formats: JSON, YAML, VTK, VTU.
+/// The file format is determined by the file extension, or by the content of the file if the
+/// extension is unknown, and a file whose content is of another format is an error. Supported
+/// formats: JSON, YAML, VTK, VTU, VTKHDF, OpenFOAM, Triangle and TetGen, XYZ and CSV point
+/// clouds, Nastran bulk data and the formats added with [`register_format`]. OpenFOAM meshes are
+/// read from a `.foam` file in the case directory, or from the `polyMesh` directory with the
+/// `openfoam` format in the options.
 pub fn read(path: &Path) -> Result<UMesh, Box<dyn std::error::Error>> {
-    match path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase()
-        .as_str()
-    {
-        "json" => serde_io::read_json(path),
-        "yaml" | "yml" => serde_io::read_yaml(path),
-        "vtk" | "vtu" => vtk_io::read(path),
-        "vtkhdf" | "h5" | "hdf5" => hdfvtk_io::read(path),
-        _ => Err(format!("Unsupported file extension: {path:?}").into()),
-    }
+    read_with(path, &ReadOptions::new())
+}
+
+/// Reads a mesh from the given file path, with [`ReadOptions`].
+pub fn read_with(path: &Path, options: &ReadOptions) -> Result<UMesh, Box<dyn std::error::Error>> {
+    registry().read_with(path, options)
 }
 
 /// Writes a mesh to the given file path.
 ///
-/// The file format is determined by the file extension.
-/// Supported formats: JSON, YAML, VTK, VTU.
+/// The file format is determined by the file extension. Supported formats: JSON, YAML, VTK, VTU,
+/// VTKHDF, OpenFOAM, Triangle and TetGen, XYZ and CSV point clouds, and the formats added with
+/// [`register_format`].
 pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn std::error::Error>> {
-    match path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase()
-        .as_str()
-    {
-        "json" => serde_io::write_json(path, mesh),
-        "yaml" | "yml" => serde_io::write_yaml(path, mesh),
-        "vtk" | "vtu" => vtk_io::write(path, mesh),
-        "vtkhdf" | "h5" | "hdf5" => hdfvtk_io::write(path, mesh),
-        _ => Err(format!("Unsupported file extension: {path:?}").into()),
+    write_with(path, mesh, &WriteOptions::new())
+}
+
+/// Writes a mesh to the given file path, with [`WriteOptions`].
+pub fn write_with(
+    path: &Path,
+    mesh: UMeshView,
+    options: &WriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    registry().write_with(path, mesh, options)
+}
+
+/// Writes a mesh as a VTU file, with the data arrays encoded as set by [`VtkWriteOptions`].
+///
+/// [`write`] and [`write_with`] write `.vtu` files through it, with ASCII data arrays or zlib
+/// compressed ones. Only compression needs the `io` feature, see [`to_vtu_bytes`].
+pub fn write_vtu(
+    path: &Path,
+    mesh: UMeshView,
+    options: &VtkWriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vtu = to_vtu_bytes(mesh, options)?;
+    std::fs::write(path, vtu)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Dimension, ElementIds, ElementType};
+    use crate::mesh_examples as me;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_read_write_options() {
+        let path = PathBuf::from("test_options.data");
+        let mut mesh = me::make_imesh_2d(2);
+        let values = ndarray::Array1::from_iter((0..4).map(f64::from)).into_dyn();
+        mesh.assign_field("a", None, values.view()).unwrap();
+        mesh.assign_field("b", None, values.view()).unwrap();
+
+        assert!(write(&path, mesh.view()).is_err());
+        let options = WriteOptions::new().format(Format::Json).fields(["a", "c"]);
+        write_with(&path, mesh.view(), &options).unwrap();
+        let compressed = WriteOptions::new().format(Format::Json).compression(4);
+        assert!(write_with(&path, mesh.view(), &compressed).is_err());
+
+        let json = ReadOptions::new().format(Format::Json);
+        let read_back = read_with(&path, &json).unwrap();
+        assert!(read_back.field("a", None).is_some());
+        assert!(read_back.field("b", None).is_none());
+        let read_back = read_with(&path, &json.clone().fields(Vec::<String>::new())).unwrap();
+        assert!(read_back.field("a", None).is_none());
+        assert!(read_with(&path, &json.time_step(1)).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_partial_read() {
+        let path = PathBuf::from("test_partial_read.json");
+        let mut mesh = me::make_imesh_2d(2);
+        let values = ndarray::Array1::from_iter((0..4).map(f64::from)).into_dyn();
+        mesh.assign_field("a", None, values.view()).unwrap();
+        let segments = ndarray::arr2(&[[0, 1], [1, 2]]).into_shared();
+        mesh.add_regular_block(ElementType::SEG2, segments, None);
+        let mut left = ElementIds::new();
+        left.add_block(ElementType::QUAD4, vec![0, 2]);
+        left.add_block(ElementType::SEG2, vec![0]);
+        mesh.set_group("left", &left);
+        write(&path, mesh.view()).unwrap();
+
+        let surface = ReadOptions::new().dimensions([Dimension::D2]);
+        let read_back = read_with(&path, &surface).unwrap();
+        assert_eq!(read_back.element_types().count(), 1);
+        assert_eq!(read_back.num_elements(), 4);
+
+        let read_back = read_with(&path, &surface.groups(["left", "right"])).unwrap();
+        assert_eq!(read_back.num_elements(), 2);
+        let values = &read_back.field("a", None).unwrap().0[&ElementType::QUAD4];
+        assert_eq!(values.as_slice().unwrap(), &[0.0, 2.0]);
+        assert_eq!(read_back.group_as_element_ids("left").len(), 2);
+
+        let read_back = read_with(&path, &ReadOptions::new().groups(["left"])).unwrap();
+        assert_eq!(read_back.num_elements(), 3);
+        assert_eq!(read_back.coords().nrows(), 9);
+        std::fs::remove_file(path).unwrap();
     }
 }
@@ -0,0 +1,287 @@
+//! Import of Nastran bulk data files (`.bdf`, `.nas`).
+//!
+//! GRID points and CTRIA3, CQUAD4, CTETRA and CHEXA elements are read, in the small field, large
+//! field and free field formats, with their continuation lines. Elements are grouped by property,
+//! in groups named after the property card (`PSHELL_1`, `PSOLID_2`) or `PID_3` when the property
+//! is not defined in the file. Other cards are ignored.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use ndarray as nd;
+
+use crate::mesh::{ElementType, UMesh};
+use crate::trace;
+
+/// A bulk data card: its name and its data fields, continuation lines included.
+#[derive(Debug, PartialEq)]
+struct Card {
+    name: String,
+    fields: Vec<String>,
+}
+
+impl Card {
+    fn field(&self, i: usize) -> &str {
+        self.fields.get(i).map_or("", |f| f.trim())
+    }
+
+    fn integer(&self, i: usize) -> Result<Option<i64>, String> {
+        match self.field(i) {
+            "" => Ok(None),
+            f => f
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid integer {f} in {} card", self.name)),
+        }
+    }
+
+    fn id(&self, i: usize) -> Result<i64, String> {
+        self.integer(i)?
+            .ok_or(format!("Missing identifier in {} card", self.name))
+    }
+
+    fn real(&self, i: usize) -> Result<f64, String> {
+        match self.field(i) {
+            "" => Ok(0.0),
+            f => parse_real(f).ok_or(format!("Invalid real {f} in {} card", self.name)),
+        }
+    }
+}
+
+/// Parses a Nastran real, whose exponent may omit the `E` (`1.5-3` for `1.5E-3`).
+fn parse_real(field: &str) -> Option<f64> {
+    let field = field.replace(['D', 'd'], "E");
+    if let Ok(value) = field.parse() {
+        return Some(value);
+    }
+    let sign = field.rfind(['+', '-']).filter(|&i| i > 0)?;
+    format!("{}E{}", &field[..sign], &field[sign..])
+        .parse()
+        .ok()
+}
+
+/// Splits a line in fields of the given widths, dropping the continuation marker after them.
+fn fixed_fields(line: &str, widths: &[usize]) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut start = 0;
+    widths
+        .iter()
+        .map(|&w| {
+            let field = chars[start.min(chars.len())..(start + w).min(chars.len())].iter();
+            start += w;
+            field.collect()
+        })
+        .collect()
+}
+
+/// Splits the bulk data in cards, joining continuation lines.
+fn cards(text: &str) -> Vec<Card> {
+    let bulk = match text.find("BEGIN BULK") {
+        Some(i) => &text[i..],
+        None => text,
+    };
+    let mut cards: Vec<Card> = Vec::new();
+    for line in bulk.lines().skip_while(|l| l.starts_with("BEGIN BULK")) {
+        let line = line.split('$').next().unwrap_or("").trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with("ENDDATA") {
+            break;
+        }
+        // The name (or continuation marker) field, followed by the data fields of the line
+        let fields: Vec<String> = if line.contains(',') {
+            let mut fields: Vec<String> = line.split(',').map(str::to_owned).collect();
+            fields.resize(9, String::new());
+            fields
+        } else if line.starts_with('*') || line.split_whitespace().next().unwrap().ends_with('*') {
+            fixed_fields(line, &[8, 16, 16, 16, 16])
+        } else {
+            fixed_fields(line, &[8, 8, 8, 8, 8, 8, 8, 8, 8])
+        };
+        let name = fields[0].trim();
+        let is_continuation = name.is_empty() || name.starts_with(['+', '*']);
+        match cards.last_mut() {
+            Some(card) if is_continuation => card.fields.extend(fields.into_iter().skip(1)),
+            _ => cards.push(Card {
+                name: name.trim_end_matches('*').to_uppercase(),
+                fields: fields.into_iter().skip(1).collect(),
+            }),
+        }
+    }
+    cards
+}
+
+/// Builds the mesh of Nastran bulk data.
+fn parse(text: &str) -> Result<UMesh, String> {
+    let cards = cards(text);
+    let mut grids: BTreeMap<i64, [f64; 3]> = BTreeMap::new();
+    let mut properties: BTreeMap<i64, String> = BTreeMap::new();
+    // Grid ids and property of the elements of each type
+    let mut elements: BTreeMap<ElementType, (Vec<i64>, Vec<i64>)> = BTreeMap::new();
+    for card in &cards {
+        let (et, num_nodes) = match card.name.as_str() {
+            "GRID" => {
+                if card.integer(1)?.is_some_and(|cp| cp != 0) {
+                    return Err(format!(
+                        "GRID {} is in a local coordinate system, which is not supported",
+                        card.id(0)?
+                    ));
+                }
+                let xyz = [card.real(2)?, card.real(3)?, card.real(4)?];
+                grids.insert(card.id(0)?, xyz);
+                continue;
+            }
+            "PSHELL" | "PSOLID" => {
+                properties.insert(card.id(0)?, format!("{}_{}", card.name, card.id(0)?));
+                continue;
+            }
+            "CTRIA3" => (ElementType::TRI3, 3),
+            "CQUAD4" => (ElementType::QUAD4, 4),
+            "CTETRA" => (ElementType::TET4, 4),
+            "CHEXA" => (ElementType::HEX8, 8),
+            _ => continue,
+        };
+        let eid = card.id(0)?;
+        let solid = matches!(et, ElementType::TET4 | ElementType::HEX8);
+        if solid
+            && card
+                .fields
+                .iter()
+                .skip(2 + num_nodes)
+                .any(|f| !f.trim().is_empty())
+        {
+            return Err(format!(
+                "{} {eid} has mid-side nodes, which are not supported",
+                card.name
+            ));
+        }
+        let (nodes, pids) = elements.entry(et).or_default();
+        for i in 0..num_nodes {
+            nodes.push(card.id(2 + i)?);
+        }
+        pids.push(card.integer(1)?.unwrap_or(eid));
+    }
+
+    let index: BTreeMap<i64, usize> = grids.keys().enumerate().map(|(i, &id)| (id, i)).collect();
+    let coords: Vec<f64> = grids.values().flatten().copied().collect();
+    let coords = nd::Array2::from_shape_vec((grids.len(), 3), coords).unwrap();
+    let mut mesh = UMesh::new(coords.into_shared());
+    let all_pids: BTreeSet<i64> = elements
+        .values()
+        .flat_map(|(_, p)| p.iter().copied())
+        .collect();
+    let family: BTreeMap<i64, usize> = all_pids.iter().enumerate().map(|(f, &p)| (p, f)).collect();
+    for (et, (grid_ids, pids)) in elements {
+        let nodes = grid_ids
+            .iter()
+            .map(|id| {
+                index
+                    .get(id)
+                    .copied()
+                    .ok_or(format!("Missing GRID {id} of a {et:?} element"))
+            })
+            .collect::<Result<Vec<usize>, String>>()?;
+        let conn =
+            nd::Array2::from_shape_vec((pids.len(), et.num_nodes().unwrap()), nodes).unwrap();
+        mesh.add_regular_block(et, conn.into_shared(), None);
+        let block = mesh.element_blocks.get_mut(&et).unwrap();
+        block.families = pids
+            .iter()
+            .map(|p| family[p])
+            .collect::<nd::Array1<_>>()
+            .into_shared();
+        for pid in pids.iter().collect::<BTreeSet<_>>() {
+            let name = properties.get(pid).cloned().unwrap_or(format!("PID_{pid}"));
+            block.groups.insert(name, BTreeSet::from([family[pid]]));
+        }
+    }
+    trace::debug!(
+        grids = grids.len(),
+        elements = mesh.num_elements(),
+        cards = cards.len(),
+        "nastran bulk data read"
+    );
+    Ok(mesh)
+}
+
+/// Reads the mesh of a Nastran bulk data file.
+pub fn read(path: &Path) -> Result<UMesh, Box<dyn std::error::Error>> {
+    trace::span!("read_nastran");
+    let text = std::fs::read_to_string(path)?;
+    Ok(parse(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BULK: &str = "\
+SOL 101
+CEND
+BEGIN BULK
+$ Small field, large field and free field grids
+GRID    1               0.      0.      0.
+GRID    2               1.      0.      0.
+GRID*                  3                             1.0             1.0
+*                    0.0
+GRID,4,,0.,1.,0.
+GRID    5               0.      0.      1.+0  $ implicit exponent
+GRID,6,,1.,0.,1.
+GRID,7,,1.,1.,1.
+GRID,8,,0.,1.,1.
+PSHELL  1       1       .01
+PSOLID  3       1
+CQUAD4  10      1       1       2       3       4
+CTRIA3  11      7       1       2       5
+CTETRA  20              1       2       4       5
+CHEXA   30      3       1       2       3       4       5       6       +H30
++H30    7       8
+FORCE   1       3               1.      0.      0.      -1.
+ENDDATA
+GRID    9               2.      0.      0.
+";
+
+    #[test]
+    fn test_parse_real() {
+        assert_eq!(parse_real("1.5-3"), Some(1.5e-3));
+        assert_eq!(parse_real("-2.+1"), Some(-20.0));
+        assert_eq!(parse_real("1.0D2"), Some(100.0));
+        assert_eq!(parse_real(".5"), Some(0.5));
+        assert_eq!(parse_real("-"), None);
+    }
+
+    #[test]
+    fn test_cards() {
+        let cards = cards(BULK);
+        assert_eq!(cards.len(), 15);
+        assert_eq!(cards[2].name, "GRID");
+        assert_eq!(cards[2].field(0), "3");
+        assert_eq!(cards[2].field(4), "0.0");
+        assert_eq!(cards[13].name, "CHEXA");
+        assert_eq!(cards[13].field(9), "8");
+    }
+
+    #[test]
+    fn test_parse() {
+        let mesh = parse(BULK).unwrap();
+        assert_eq!(mesh.coords().nrows(), 8);
+        assert_eq!(mesh.coords().row(2).to_vec(), vec![1.0, 1.0, 0.0]);
+        assert_eq!(mesh.coords().row(4).to_vec(), vec![0.0, 0.0, 1.0]);
+        let tri = mesh.regular_connectivity(ElementType::TRI3).unwrap();
+        assert_eq!(tri.row(0).to_vec(), vec![0, 1, 4]);
+        let hex = mesh.regular_connectivity(ElementType::HEX8).unwrap();
+        assert_eq!(hex.row(0).to_vec(), (0..8).collect::<Vec<_>>());
+        assert_eq!(mesh.num_elements(), 4);
+        assert_eq!(
+            mesh.group_names(),
+            vec!["PID_20", "PID_7", "PSHELL_1", "PSOLID_3"]
+        );
+        assert_eq!(mesh.group_as_element_ids("PSOLID_3").len(), 1);
+
+        // Local coordinate system, missing grid and mid-side nodes
+        assert!(parse("GRID,1,5,0.,0.,0.").is_err());
+        assert!(parse("CTRIA3,1,1,1,2,3").is_err());
+        assert!(parse("GRID,1\nCTETRA,1,1,1,1,1,1,1").is_err());
+    }
+}
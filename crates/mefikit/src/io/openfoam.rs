@@ -0,0 +1,591 @@
+//! Import and export of OpenFOAM meshes, stored in a `constant/polyMesh` directory.
+//!
+//! OpenFOAM describes a mesh by its faces: the `points`, `faces`, `owner` and `neighbour` files
+//! give the nodes of each face and the cells on both sides, and the `boundary` file splits the
+//! boundary faces in patches. Cells are read as PHED elements, and the faces of each patch as
+//! PGON elements in a group named after the patch. Only ASCII files are supported.
+//!
+//! A path is either the `polyMesh` directory, the case directory, or a `.foam` file in the case
+//! directory, as opened by ParaView.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ndarray as nd;
+use rustc_hash::FxHashMap;
+
+use crate::element_traits::{ElementTopo, SortedVecKey};
+use crate::mesh::{Dimension, ElementLike, ElementType, UMesh, UMeshView};
+use crate::trace;
+
+/// Name of the patch of the boundary faces which are not in any group.
+const DEFAULT_PATCH: &str = "defaultFaces";
+
+/// The `polyMesh` directory of a path.
+fn poly_mesh_dir(path: &Path) -> PathBuf {
+    let case = match path.extension() {
+        Some(ext) if ext == "foam" => path.parent().unwrap_or(Path::new(".")),
+        _ => path,
+    };
+    let poly_mesh = case.join("constant").join("polyMesh");
+    if case != path || poly_mesh.is_dir() {
+        poly_mesh
+    } else {
+        path.to_owned()
+    }
+}
+
+/// The tokens of an OpenFOAM file, without its comments.
+struct Tokens<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut rest = text;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let len = if rest.starts_with("//") {
+                rest.find('\n').unwrap_or(rest.len())
+            } else if rest.starts_with("/*") {
+                rest.find("*/").map_or(rest.len(), |i| i + 2)
+            } else if let Some(quoted) = rest.strip_prefix('"') {
+                let len = quoted.find('"').map_or(rest.len(), |i| i + 2);
+                tokens.push(&rest[..len]);
+                len
+            } else if rest.starts_with(['(', ')', '{', '}', ';']) {
+                tokens.push(&rest[..1]);
+                1
+            } else {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || "(){};\"".contains(c))
+                    .unwrap_or(rest.len());
+                tokens.push(&rest[..len]);
+                len
+            };
+            rest = &rest[len..];
+        }
+        Self { tokens, pos: 0 }
+    }
+
+    /// Tokenizes a file and skips its `FoamFile` header.
+    fn file(text: &'a str) -> Result<Self, String> {
+        let mut tokens = Self::new(text);
+        if tokens.peek() == Some("FoamFile") {
+            tokens.next()?;
+            tokens.expect("{")?;
+            let header = tokens.dictionary()?;
+            if header.get("format").is_some_and(|f| f == "binary") {
+                return Err("Binary OpenFOAM files are not supported".to_owned());
+            }
+        }
+        Ok(tokens)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<&'a str, String> {
+        let token = self.peek().ok_or("Unexpected end of OpenFOAM file")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(format!(
+                "Expected {expected} in OpenFOAM file, found {token}"
+            )),
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self) -> Result<T, String> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| format!("Invalid value {token} in OpenFOAM file"))
+    }
+
+    /// Parses the entries of a dictionary, after its opening brace, skipping sub-dictionaries.
+    fn dictionary(&mut self) -> Result<BTreeMap<String, String>, String> {
+        let mut entries = BTreeMap::new();
+        loop {
+            let key = self.next()?;
+            if key == "}" {
+                return Ok(entries);
+            }
+            if self.peek() == Some("{") {
+                self.next()?;
+                self.dictionary()?;
+                continue;
+            }
+            let mut value = Vec::new();
+            loop {
+                match self.next()? {
+                    ";" => break,
+                    token => value.push(token),
+                }
+            }
+            entries.insert(key.to_owned(), value.join(" "));
+        }
+    }
+
+    /// Parses a list, `n(a b c)` or `n{a}` for a uniform list, with the given parser of items.
+    fn list<T: Clone>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        let count = match self.peek() {
+            Some("(") => None,
+            _ => Some(self.parse::<usize>()?),
+        };
+        if let (Some(count), Some("{")) = (count, self.peek()) {
+            self.next()?;
+            let value = item(self)?;
+            self.expect("}")?;
+            return Ok(vec![value; count]);
+        }
+        self.expect("(")?;
+        let mut items = Vec::with_capacity(count.unwrap_or(0));
+        while self.peek() != Some(")") {
+            items.push(item(self)?);
+        }
+        self.next()?;
+        match count {
+            Some(count) if count != items.len() => Err(format!(
+                "OpenFOAM list of {count} items has {} items",
+                items.len()
+            )),
+            _ => Ok(items),
+        }
+    }
+}
+
+/// A boundary patch: its name and its range of faces.
+#[derive(Clone, Debug, PartialEq)]
+struct Patch {
+    name: String,
+    start: usize,
+    len: usize,
+}
+
+fn parse_points(text: &str) -> Result<Vec<[f64; 3]>, String> {
+    Tokens::file(text)?.list(|t| {
+        t.expect("(")?;
+        let point = [t.parse()?, t.parse()?, t.parse()?];
+        t.expect(")")?;
+        Ok(point)
+    })
+}
+
+fn parse_faces(text: &str) -> Result<Vec<Vec<usize>>, String> {
+    Tokens::file(text)?.list(|t| t.list(Tokens::parse))
+}
+
+fn parse_labels(text: &str) -> Result<Vec<usize>, String> {
+    Tokens::file(text)?.list(Tokens::parse)
+}
+
+fn parse_boundary(text: &str) -> Result<Vec<Patch>, String> {
+    Tokens::file(text)?.list(|t| {
+        let name = t.next()?.to_owned();
+        t.expect("{")?;
+        let entries = t.dictionary()?;
+        let get = |key: &str| {
+            entries
+                .get(key)
+                .and_then(|v| v.parse().ok())
+                .ok_or(format!("Missing {key} in OpenFOAM patch {name}"))
+        };
+        Ok(Patch {
+            start: get("startFace")?,
+            len: get("nFaces")?,
+            name,
+        })
+    })
+}
+
+/// Builds the mesh of the faces of an OpenFOAM mesh.
+fn build(
+    points: &[[f64; 3]],
+    faces: &[Vec<usize>],
+    owner: &[usize],
+    neighbour: &[usize],
+    patches: &[Patch],
+) -> Result<UMesh, String> {
+    if owner.len() != faces.len() || neighbour.len() > faces.len() {
+        return Err(format!(
+            "OpenFOAM mesh of {} faces has {} owners and {} neighbours",
+            faces.len(),
+            owner.len(),
+            neighbour.len()
+        ));
+    }
+    if faces.iter().flatten().any(|&p| p >= points.len()) {
+        return Err("OpenFOAM face with a missing point".to_owned());
+    }
+    let num_cells = owner.iter().chain(neighbour).max().map_or(0, |&c| c + 1);
+    // Faces are oriented from their owner to their neighbour
+    let mut cell_faces: Vec<Vec<(usize, bool)>> = vec![Vec::new(); num_cells];
+    for (f, &c) in owner.iter().enumerate() {
+        cell_faces[c].push((f, false));
+    }
+    for (f, &c) in neighbour.iter().enumerate() {
+        cell_faces[c].push((f, true));
+    }
+    let mut connectivity = Vec::new();
+    let mut offsets = Vec::with_capacity(num_cells);
+    for faces_of_cell in &cell_faces {
+        for (i, &(f, reversed)) in faces_of_cell.iter().enumerate() {
+            if i > 0 {
+                connectivity.push(usize::MAX);
+            }
+            if reversed {
+                connectivity.extend(faces[f].iter().rev());
+            } else {
+                connectivity.extend(&faces[f]);
+            }
+        }
+        offsets.push(connectivity.len());
+    }
+
+    let coords: Vec<f64> = points.iter().flatten().copied().collect();
+    let coords = nd::Array2::from_shape_vec((points.len(), 3), coords).unwrap();
+    let mut mesh = UMesh::new(coords.into_shared());
+    if num_cells > 0 {
+        mesh.add_poly_block(ElementType::PHED, connectivity.into(), offsets.into());
+    }
+
+    let mut connectivity = Vec::new();
+    let mut offsets = Vec::new();
+    let mut families = Vec::new();
+    let mut groups = BTreeMap::new();
+    for (family, patch) in patches.iter().enumerate() {
+        let patch_faces = faces
+            .get(patch.start..patch.start + patch.len)
+            .ok_or(format!("OpenFOAM patch {} has missing faces", patch.name))?;
+        for face in patch_faces {
+            connectivity.extend(face);
+            offsets.push(connectivity.len());
+            families.push(family);
+        }
+        if patch.len > 0 {
+            groups.insert(patch.name.clone(), [family].into());
+        }
+    }
+    if !families.is_empty() {
+        mesh.add_poly_block(ElementType::PGON, connectivity.into(), offsets.into());
+        let block = mesh.element_blocks.get_mut(&ElementType::PGON).unwrap();
+        block.families = families.into();
+        block.groups = groups;
+    }
+    Ok(mesh)
+}
+
+/// Reads an OpenFOAM mesh.
+pub fn read(path: &Path) -> Result<UMesh, Box<dyn Error>> {
+    trace::span!("read_openfoam");
+    let dir = poly_mesh_dir(path);
+    let file = |name: &str| {
+        let path = dir.join(name);
+        std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {path:?}: {e}"))
+    };
+    let points = parse_points(&file("points")?)?;
+    let faces = parse_faces(&file("faces")?)?;
+    let owner = parse_labels(&file("owner")?)?;
+    let neighbour = parse_labels(&file("neighbour")?)?;
+    let patches = parse_boundary(&file("boundary")?)?;
+    trace::debug!(
+        points = points.len(),
+        faces = faces.len(),
+        patches = patches.len(),
+        "openfoam mesh read"
+    );
+    Ok(build(&points, &faces, &owner, &neighbour, &patches)?)
+}
+
+/// The faces of the cells of a mesh, in the order of an OpenFOAM mesh: internal faces first,
+/// by owner and neighbour, then the boundary faces by patch.
+#[derive(Debug, Default)]
+struct PolyMesh {
+    faces: Vec<Vec<usize>>,
+    owner: Vec<usize>,
+    neighbour: Vec<usize>,
+    patches: Vec<Patch>,
+}
+
+/// The nodes of a face, oriented away from the center of its cell.
+fn outward(mut face: Vec<usize>, center: [f64; 3], coords: nd::ArrayView2<'_, f64>) -> Vec<usize> {
+    let point = |n: usize| [coords[[n, 0]], coords[[n, 1]], coords[[n, 2]]];
+    let mut normal = [0.0; 3];
+    let mut centroid = [0.0; 3];
+    for (i, &a) in face.iter().enumerate() {
+        let (a, b) = (point(a), point(face[(i + 1) % face.len()]));
+        // Newell's method
+        normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+        (0..3).for_each(|k| centroid[k] += a[k] / face.len() as f64);
+    }
+    let outwards: f64 = (0..3).map(|k| normal[k] * (centroid[k] - center[k])).sum();
+    if outwards < 0.0 {
+        face.reverse();
+    }
+    face
+}
+
+impl PolyMesh {
+    fn new(mesh: &UMeshView) -> Result<Self, String> {
+        if mesh.space_dimension() != 3 {
+            return Err("OpenFOAM meshes are three-dimensional".to_owned());
+        }
+        let coords = mesh.coords();
+        // The faces, by their nodes, with their orientation from the first cell having them
+        let mut faces: FxHashMap<SortedVecKey, (Vec<usize>, usize, Option<usize>)> =
+            FxHashMap::default();
+        for (cell, element) in mesh.elements_of_dim(Dimension::D3).enumerate() {
+            let et = element.element_type();
+            if !matches!(
+                et,
+                ElementType::TET4 | ElementType::HEX8 | ElementType::PHED
+            ) {
+                return Err(format!("{et:?} cells cannot be written in OpenFOAM meshes"));
+            }
+            let mut nodes = element.connectivity().to_vec();
+            nodes.retain(|&n| n != usize::MAX);
+            nodes.sort_unstable();
+            nodes.dedup();
+            let mut center = [0.0; 3];
+            for &n in &nodes {
+                (0..3).for_each(|k| center[k] += coords[[n, k]] / nodes.len() as f64);
+            }
+            for (_, conn) in element.subentities(Some(Dimension::D1)) {
+                for face in conn.iter() {
+                    let face = outward(face.to_vec(), center, coords);
+                    let entry = faces
+                        .entry(SortedVecKey::new(face.as_slice().into()))
+                        .or_insert((face, cell, None));
+                    if entry.1 != cell {
+                        if entry.2.is_some() {
+                            return Err(format!("Face {:?} has more than two cells", entry.0));
+                        }
+                        entry.2 = Some(cell);
+                    }
+                }
+            }
+        }
+
+        // Patches of the boundary faces, from the groups of the faces of the mesh
+        let mut face_groups: FxHashMap<SortedVecKey, String> = FxHashMap::default();
+        for element in mesh.elements_of_dim(Dimension::D2) {
+            if let Some(group) = element.groups().first() {
+                let key = SortedVecKey::new(element.connectivity().into());
+                face_groups.insert(key, group.clone());
+            }
+        }
+        let mut internal = Vec::new();
+        let mut boundary: BTreeMap<String, Vec<(usize, Vec<usize>)>> = BTreeMap::new();
+        for (key, (face, owner, neighbour)) in faces {
+            match neighbour {
+                Some(neighbour) => internal.push((owner, neighbour, face)),
+                None => {
+                    let patch = face_groups.remove(&key);
+                    let patch = patch.unwrap_or_else(|| DEFAULT_PATCH.to_owned());
+                    boundary.entry(patch).or_default().push((owner, face));
+                }
+            }
+        }
+        internal.sort_unstable();
+        let mut poly_mesh = Self::default();
+        for (owner, neighbour, face) in internal {
+            poly_mesh.faces.push(face);
+            poly_mesh.owner.push(owner);
+            poly_mesh.neighbour.push(neighbour);
+        }
+        // The default patch goes last
+        let default = boundary.remove(DEFAULT_PATCH);
+        let default = default.map(|f| (DEFAULT_PATCH.to_owned(), f));
+        for (name, mut patch_faces) in boundary.into_iter().chain(default) {
+            patch_faces.sort_unstable();
+            poly_mesh.patches.push(Patch {
+                name,
+                start: poly_mesh.faces.len(),
+                len: patch_faces.len(),
+            });
+            for (owner, face) in patch_faces {
+                poly_mesh.faces.push(face);
+                poly_mesh.owner.push(owner);
+            }
+        }
+        Ok(poly_mesh)
+    }
+}
+
+/// The `FoamFile` header of a file of a `polyMesh` directory.
+fn header(class: &str, object: &str, note: Option<&str>) -> String {
+    let mut header = String::from("FoamFile\n{\n    version     2.0;\n    format      ascii;\n");
+    let _ = writeln!(header, "    class       {class};");
+    if let Some(note) = note {
+        let _ = writeln!(header, "    note        \"{note}\";");
+    }
+    let _ = writeln!(header, "    location    \"constant/polyMesh\";");
+    let _ = writeln!(header, "    object      {object};\n}}\n");
+    header
+}
+
+/// Writes the items of a list, one per line.
+fn list<T>(mut text: String, items: &[T], mut item: impl FnMut(&mut String, &T)) -> String {
+    let _ = writeln!(text, "{}\n(", items.len());
+    for value in items {
+        item(&mut text, value);
+        text.push('\n');
+    }
+    text.push_str(")\n");
+    text
+}
+
+/// Writes a mesh as an OpenFOAM mesh.
+///
+/// Boundary faces are put in the patch of the first group of the face element with the same
+/// nodes, or in the `defaultFaces` patch.
+pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn Error>> {
+    trace::span!("write_openfoam");
+    let poly_mesh = PolyMesh::new(&mesh)?;
+    let coords = mesh.coords();
+    let num_cells = poly_mesh.owner.iter().max().map_or(0, |&c| c + 1);
+    let note = format!(
+        "nPoints:{} nCells:{num_cells} nFaces:{} nInternalFaces:{}",
+        coords.nrows(),
+        poly_mesh.faces.len(),
+        poly_mesh.neighbour.len()
+    );
+    let points: Vec<_> = coords.rows().into_iter().collect();
+    let points = list(header("vectorField", "points", None), &points, |t, p| {
+        let _ = write!(t, "({} {} {})", p[0], p[1], p[2]);
+    });
+    let faces = header("faceList", "faces", None);
+    let faces = list(faces, &poly_mesh.faces, |t, f| {
+        let nodes: Vec<String> = f.iter().map(usize::to_string).collect();
+        let _ = write!(t, "{}({})", f.len(), nodes.join(" "));
+    });
+    let label = |t: &mut String, l: &usize| {
+        let _ = write!(t, "{l}");
+    };
+    let owner = header("labelList", "owner", Some(&note));
+    let owner = list(owner, &poly_mesh.owner, label);
+    let neighbour = header("labelList", "neighbour", Some(&note));
+    let neighbour = list(neighbour, &poly_mesh.neighbour, label);
+    let boundary = header("polyBoundaryMesh", "boundary", None);
+    let boundary = list(boundary, &poly_mesh.patches, |t, p| {
+        let _ = write!(
+            t,
+            "    {}\n    {{\n        type            patch;\n        nFaces          {};\n        \
+             startFace       {};\n    }}",
+            p.name, p.len, p.start
+        );
+    });
+
+    let dir = poly_mesh_dir(path);
+    std::fs::create_dir_all(&dir)?;
+    for (name, text) in [
+        ("points", points),
+        ("faces", faces),
+        ("owner", owner),
+        ("neighbour", neighbour),
+        ("boundary", boundary),
+    ] {
+        std::fs::write(dir.join(name), text)?;
+    }
+    if path.extension().is_some_and(|ext| ext == "foam") && !path.exists() {
+        std::fs::File::create(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementIds;
+    use crate::mesh_examples as me;
+    use crate::tools::measure;
+
+    #[test]
+    fn test_tokens() {
+        let mut tokens = Tokens::file(
+            "/* banner */\nFoamFile\n{\n    format ascii;\n    note \"a b\";\n}\n\
+             // comment\n3(1 2 3) 2{4}",
+        )
+        .unwrap();
+        assert_eq!(tokens.peek(), Some("3"));
+        assert_eq!(tokens.list(Tokens::parse::<usize>), Ok(vec![1, 2, 3]));
+        assert_eq!(tokens.list(Tokens::parse::<usize>), Ok(vec![4, 4]));
+        assert!(Tokens::file("FoamFile { format binary; }").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let boundary = "1\n(\n    inlet\n    {\n        type patch;\n        \
+                        inGroups List<word> 1(inflow);\n        nFaces 2;\n        \
+                        startFace 5;\n    }\n)";
+        let patches = parse_boundary(boundary).unwrap();
+        assert_eq!(patches[0].name, "inlet");
+        assert_eq!((patches[0].start, patches[0].len), (5, 2));
+        assert_eq!(parse_faces("2(3(0 1 2) 4(0 1 2 3))").unwrap()[1].len(), 4);
+        assert_eq!(
+            parse_points("1((0 1e-3 -2))").unwrap(),
+            vec![[0.0, 1e-3, -2.0]]
+        );
+        assert!(parse_labels("3(0 1)").is_err());
+    }
+
+    #[test]
+    fn test_write_read() {
+        let mut mesh = me::make_imesh_3d(2);
+        let skin = crate::tools::compute_skin(mesh.view()).mesh;
+        let mut inlet = ElementIds::new();
+        for face in skin
+            .regular_connectivity(ElementType::QUAD4)
+            .unwrap()
+            .rows()
+        {
+            if face.iter().all(|&n| mesh.coords()[[n, 0]] == 0.0) {
+                let id = mesh.add_element(ElementType::QUAD4, face.as_slice().unwrap(), None, None);
+                inlet.add(id.element_type(), id.index());
+            }
+        }
+        mesh.set_group("inlet", &inlet);
+        let dir = std::env::temp_dir().join("mefikit_openfoam_test");
+        let case = dir.join("case.foam");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        write(&case, mesh.view()).unwrap();
+        assert!(case.exists());
+
+        let read_mesh = read(&case).unwrap();
+        assert_eq!(read_mesh.block(ElementType::PHED).unwrap().len(), 8);
+        assert_eq!(read_mesh.block(ElementType::PGON).unwrap().len(), 24);
+        assert_eq!(read_mesh.group_names(), vec![DEFAULT_PATCH, "inlet"]);
+        assert_eq!(read_mesh.group_as_element_ids("inlet").len(), 4);
+        let volumes = measure(read_mesh.view(), None);
+        assert!((volumes[&ElementType::PHED].sum() - 1.0).abs() < 1e-12);
+        assert!(volumes[&ElementType::PHED].iter().all(|&v| v > 0.0));
+
+        // Written again from the case directory, the mesh is the same
+        write(&dir, read_mesh.view()).unwrap();
+        let again = read(&dir).unwrap();
+        assert_eq!(again.coords(), read_mesh.coords());
+        assert_eq!(again.num_elements(), read_mesh.num_elements());
+        assert_eq!(again.group_names(), read_mesh.group_names());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+//! Options of [`super::read_with`], [`super::write_with`] and of the VTU writer.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use ndarray as nd;
+
+use crate::mesh::{Dimension, UMesh, UMeshBase, UMeshView};
+
+/// A built-in mesh file format.
+///
+/// Each format is a backend of the [`super::FormatRegistry`], named as displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Serialized mesh, in JSON.
+    Json,
+    /// Serialized mesh, in YAML.
+    Yaml,
+    /// VTK unstructured grid, legacy or XML.
+    Vtk,
+    /// VTKHDF unstructured grid, in an HDF5 file.
+    VtkHdf,
+    /// Nastran bulk data, read only.
+    Nastran,
+    /// OpenFOAM `polyMesh` directory.
+    OpenFoam,
+    /// Triangle and TetGen files (`.node`, `.ele`, `.poly`).
+    TetGen,
+    /// Point clouds, as XYZ or CSV text files.
+    Points,
+}
+
+impl Format {
+    /// Guesses the format from the extension of a file.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .parse()
+            .map_err(|_| format!("Unsupported file extension: {path:?}"))
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    /// Parses a format name or a file extension, case insensitively.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "vtk" | "vtu" => Ok(Format::Vtk),
+            "vtkhdf" | "h5" | "hdf5" => Ok(Format::VtkHdf),
+            "nastran" | "bdf" | "nas" | "bulk" => Ok(Format::Nastran),
+            "openfoam" | "foam" => Ok(Format::OpenFoam),
+            "tetgen" | "triangle" | "node" | "ele" | "poly" => Ok(Format::TetGen),
+            "points" | "xyz" | "csv" => Ok(Format::Points),
+            _ => Err(format!("Unknown mesh format: {name}")),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Vtk => "vtk",
+            Format::VtkHdf => "vtkhdf",
+            Format::Nastran => "nastran",
+            Format::OpenFoam => "openfoam",
+            Format::TetGen => "tetgen",
+            Format::Points => "points",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Options of [`super::read_with`].
+///
+/// By default, the format is guessed from the file extension, all the elements and fields are
+/// read and the first time step of transient files is read.
+///
+/// Fields, groups and dimensions restrict what is kept from large files. Only the VTKHDF reader,
+/// which reads neither fields nor groups, skips the cells of the other dimensions while reading.
+/// Otherwise the whole file is read first and the rest is dropped afterwards, so the filters make
+/// the resulting mesh smaller but neither reading faster nor its peak memory lower.
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptions {
+    pub(super) format: Option<String>,
+    pub(super) fields: Option<Vec<String>>,
+    pub(super) groups: Option<Vec<String>>,
+    pub(super) dimensions: Option<Vec<Dimension>>,
+    pub(super) time_step: Option<usize>,
+}
+
+impl ReadOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the file in the given format, whatever its extension.
+    ///
+    /// The format is a [`Format`] or the name of a format of the [`super::FormatRegistry`].
+    pub fn format(mut self, format: impl fmt::Display) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// Only keeps the fields with the given names.
+    pub fn fields<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.fields = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only keeps the elements of the groups with the given names.
+    ///
+    /// Nodes are all kept, see [`UMesh::prune_nodes`] to remove the unused ones.
+    pub fn groups<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.groups = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only keeps the elements of the given dimensions.
+    pub fn dimensions(mut self, dimensions: impl IntoIterator<Item = Dimension>) -> Self {
+        self.dimensions = Some(dimensions.into_iter().collect());
+        self
+    }
+
+    /// Reads the given time step of a transient VTKHDF file.
+    pub fn time_step(mut self, step: usize) -> Self {
+        self.time_step = Some(step);
+        self
+    }
+
+    /// The names of the fields to keep, all of them if `None`.
+    pub fn field_names(&self) -> Option<&[String]> {
+        self.fields.as_deref()
+    }
+
+    /// The names of the groups whose elements are kept, all the elements if `None`.
+    pub fn group_names(&self) -> Option<&[String]> {
+        self.groups.as_deref()
+    }
+
+    /// The dimensions of the elements kept, all of them if `None`.
+    pub fn kept_dimensions(&self) -> Option<&[Dimension]> {
+        self.dimensions.as_deref()
+    }
+
+    /// The time step to read, 0 by default.
+    pub fn step(&self) -> usize {
+        self.time_step.unwrap_or(0)
+    }
+}
+
+/// Options of [`super::write_with`].
+///
+/// By default, the format is guessed from the file extension, all the fields are written and
+/// nothing is compressed. Formats requiring full arrays write sparse fields with NaN out of their
+/// selection.
+#[derive(Clone, Debug, Default)]
+pub struct WriteOptions {
+    pub(super) format: Option<String>,
+    pub(super) fields: Option<Vec<String>>,
+    pub(super) compression: Option<u8>,
+    pub(super) fill: Option<f64>,
+}
+
+impl WriteOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the file in the given format, whatever its extension.
+    ///
+    /// The format is a [`Format`] or the name of a format of the [`super::FormatRegistry`].
+    pub fn format(mut self, format: impl fmt::Display) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// Only writes the fields with the given names.
+    pub fn fields<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.fields = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Compresses the datasets with deflate, from level 0 (none) to 9 (best).
+    ///
+    /// Only formats with the compression capability, as VTKHDF, can be compressed.
+    pub fn compression(mut self, level: u8) -> Self {
+        self.compression = Some(level);
+        self
+    }
+
+    /// Writes sparse fields with `fill` on the elements out of their selection, in formats
+    /// requiring full arrays.
+    pub fn fill_value(mut self, fill: f64) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// The names of the fields to write, all of them if `None`.
+    pub fn field_names(&self) -> Option<&[String]> {
+        self.fields.as_deref()
+    }
+
+    /// The deflate level, if the datasets are compressed.
+    pub fn compression_level(&self) -> Option<u8> {
+        self.compression
+    }
+
+    /// The value of sparse fields out of their selection, NaN by default.
+    pub fn sparse_fill(&self) -> f64 {
+        self.fill.unwrap_or(f64::NAN)
+    }
+}
+
+/// Encoding of the data arrays of a VTU file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VtkEncoding {
+    /// Values written as text inside each data array, readable but large and slow to write.
+    #[default]
+    Ascii,
+    /// Raw little endian values appended at the end of the file, referenced by the data arrays.
+    Appended,
+}
+
+/// Compression of the appended data arrays of a VTU file, as understood by VTK readers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VtkCompression {
+    /// zlib compression, from level 0 (none) to 9 (best).
+    Zlib(u8),
+    /// LZ4 compression, faster but compressing less than zlib.
+    Lz4,
+}
+
+/// Options of the VTU writer, see [`super::write_vtu`].
+///
+/// By default, data arrays are written in ASCII. Appended raw binary data, possibly compressed,
+/// gives much smaller files, faster to write for large meshes. Sparse fields are written with NaN
+/// out of their selection.
+#[derive(Clone, Debug, Default)]
+pub struct VtkWriteOptions {
+    pub(super) encoding: VtkEncoding,
+    pub(super) compression: Option<VtkCompression>,
+    pub(super) fill: Option<f64>,
+}
+
+impl VtkWriteOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes the data arrays with the given encoding.
+    pub fn encoding(mut self, encoding: VtkEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Compresses the data arrays, which are then appended raw binary data.
+    ///
+    /// Compression needs the `io` feature.
+    pub fn compression(mut self, compression: VtkCompression) -> Self {
+        self.encoding = VtkEncoding::Appended;
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Writes sparse fields with `fill` on the elements out of their selection.
+    pub fn fill_value(mut self, fill: f64) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// The value of sparse fields out of their selection, NaN by default.
+    pub fn sparse_fill(&self) -> f64 {
+        self.fill.unwrap_or(f64::NAN)
+    }
+}
+
+/// Removes the elements of dimensions not in `dimensions` and the elements in none of the groups
+/// `names`, with the blocks left empty.
+pub(super) fn keep_elements(
+    mesh: &mut UMesh,
+    names: Option<&[String]>,
+    dimensions: Option<&[Dimension]>,
+) {
+    mesh.invalidate_cache();
+    if let Some(dimensions) = dimensions {
+        mesh.element_blocks
+            .retain(|et, _| dimensions.contains(&et.dimension()));
+    }
+    let Some(names) = names else {
+        return;
+    };
+    for (et, block) in std::mem::take(&mut mesh.element_blocks) {
+        let families: BTreeSet<usize> = names
+            .iter()
+            .filter_map(|name| block.groups.get(name))
+            .flatten()
+            .copied()
+            .collect();
+        let kept: Vec<usize> = (0..block.len())
+            .filter(|&i| families.contains(&block.families[i]))
+            .collect();
+        match kept.len() {
+            0 => {}
+            n if n == block.len() => {
+                mesh.element_blocks.insert(et, block);
+            }
+            _ => {
+                mesh.element_blocks.insert(et, block.select(&kept));
+            }
+        }
+    }
+}
+
+/// Removes the float, typed and sparse fields whose name is not in `names`.
+pub(super) fn keep_fields<N, C, F, G>(mesh: &mut UMeshBase<N, C, F, G>, names: &[String])
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    let keep = |name: &String| names.contains(name);
+    for block in mesh.element_blocks.values_mut() {
+        block.fields.retain(|name, _| keep(name));
+        block.typed_fields.retain(|name, _| keep(name));
+        block.sparse_fields.retain(|name, _| keep(name));
+        block.field_locations.retain(|name, _| keep(name));
+    }
+}
+
+/// Returns a copy of the mesh with its sparse fields made full with `fill`, for the formats
+/// requiring full arrays, or `None` if it has no sparse field.
+pub(super) fn dense_fields(mesh: &UMeshView, fill: f64) -> Option<UMesh> {
+    mesh.blocks()
+        .any(|(_, b)| !b.sparse_fields.is_empty())
+        .then(|| {
+            let mut dense = mesh.to_shared();
+            dense.densify_sparse_fields(fill);
+            dense
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!("H5".parse(), Ok(Format::VtkHdf));
+        assert_eq!("bdf".parse(), Ok(Format::Nastran));
+        assert_eq!("poly".parse(), Ok(Format::TetGen));
+        assert_eq!("csv".parse(), Ok(Format::Points));
+        assert_eq!(Format::from_path(Path::new("a/mesh.yml")), Ok(Format::Yaml));
+        assert!(Format::from_path(Path::new("mesh.med")).is_err());
+        assert_eq!(Format::Vtk.to_string().parse(), Ok(Format::Vtk));
+    }
+}
@@ -0,0 +1,222 @@
+//! Import and export of point clouds, as XYZ or CSV text files.
+//!
+//! Each line holds the coordinates of a point followed by its values, separated by commas,
+//! semicolons or whitespace. A point cloud is read as a mesh with one VERTEX element per node,
+//! the extra columns being fields of the VERTEX block, ready for the Delaunay and nearest
+//! neighbour tools.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::path::Path;
+
+use ndarray as nd;
+
+use crate::mesh::{ElementType, UMesh, UMeshView};
+use crate::trace;
+
+/// The values of a line.
+fn values(line: &str) -> Vec<&str> {
+    line.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Builds the point cloud of the text of a file.
+///
+/// A first line that is not numeric names the columns: the leading `x`, `y` and `z` columns are
+/// the coordinates. Without it, the first three columns are the coordinates (or all of them if
+/// there are fewer), and the other columns are named `column_3`, `column_4`, etc.
+fn parse(text: &str) -> Result<UMesh, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, values(l)))
+        .filter(|(_, v)| !v.is_empty() && !v[0].starts_with('#') && !v[0].starts_with("//"))
+        .peekable();
+    let header: Option<Vec<String>> = match lines.peek() {
+        Some((_, first)) if first.iter().any(|v| v.parse::<f64>().is_err()) => {
+            let names = first
+                .iter()
+                .map(|v| v.trim_matches('"').to_owned())
+                .collect();
+            lines.next();
+            Some(names)
+        }
+        _ => None,
+    };
+    let num_columns = match (&header, lines.peek()) {
+        (Some(names), _) => names.len(),
+        (None, Some((_, first))) => first.len(),
+        (None, None) => 3,
+    };
+    let dim = match &header {
+        Some(names) => names
+            .iter()
+            .zip(["x", "y", "z"])
+            .take_while(|(name, axis)| name.eq_ignore_ascii_case(axis))
+            .count(),
+        None => num_columns.min(3),
+    };
+    if dim == 0 {
+        return Err("Point cloud without x column".to_owned());
+    }
+
+    let mut values = Vec::new();
+    for (line, row) in lines {
+        if row.len() != num_columns {
+            return Err(format!(
+                "Line {line} has {} values instead of {num_columns}",
+                row.len()
+            ));
+        }
+        for v in row {
+            let value: f64 = v
+                .parse()
+                .map_err(|_| format!("Invalid value {v} at line {line}"))?;
+            values.push(value);
+        }
+    }
+    let num_points = values.len() / num_columns;
+    let table = nd::Array2::from_shape_vec((num_points, num_columns), values).unwrap();
+    let coords = table.slice(nd::s![.., ..dim]).to_owned();
+    let mut mesh = UMesh::new(coords.into_shared());
+    let fields: BTreeMap<_, _> = (dim..num_columns)
+        .map(|k| {
+            let name = match &header {
+                Some(names) => names[k].clone(),
+                None => format!("column_{k}"),
+            };
+            (name, table.column(k).to_owned().into_dyn().into_shared())
+        })
+        .collect();
+    if num_points > 0 {
+        let vertices = nd::Array2::from_shape_fn((num_points, 1), |(i, _)| i);
+        mesh.add_regular_block(ElementType::VERTEX, vertices.into_shared(), Some(fields));
+    }
+    Ok(mesh)
+}
+
+/// Reads a point cloud from an XYZ or CSV file.
+pub fn read(path: &Path) -> Result<UMesh, Box<dyn Error>> {
+    trace::span!("read_point_cloud");
+    let text = std::fs::read_to_string(path)?;
+    Ok(parse(&text)?)
+}
+
+/// Formats the nodes of a mesh with the float fields of its VERTEX block, one line per node.
+///
+/// Coordinates after the third one are named `coord_{k}`, and read back as fields. Vector fields
+/// give a column per component, named `{name}_{component}`. Nodes without a VERTEX element have
+/// NaN values. The header line is always written with the comma separator,
+/// and only with fields otherwise.
+fn to_text(mesh: &UMeshView, separator: &str) -> String {
+    let coords = mesh.coords();
+    let mut names: Vec<String> = (0..coords.ncols())
+        .map(|k| match ["x", "y", "z"].get(k) {
+            Some(axis) => axis.to_string(),
+            None => format!("coord_{k}"),
+        })
+        .collect();
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    if let Some(block) = mesh.block(ElementType::VERTEX) {
+        for (name, field) in &block.fields {
+            let n = field.len() / block.len().max(1);
+            let Ok(field) = field.view().into_shape_with_order((block.len(), n)) else {
+                continue;
+            };
+            for (k, component) in field.columns().into_iter().enumerate() {
+                names.push(match field.ncols() {
+                    1 => name.clone(),
+                    _ => format!("{name}_{k}"),
+                });
+                let mut column = vec![f64::NAN; coords.nrows()];
+                for (nodes, &value) in block.connectivity.iter().zip(&component) {
+                    column[nodes[0]] = value;
+                }
+                columns.push(column);
+            }
+        }
+    }
+
+    let mut text = String::new();
+    if separator == "," || !columns.is_empty() {
+        let _ = writeln!(text, "{}", names.join(separator));
+    }
+    for (i, point) in coords.rows().into_iter().enumerate() {
+        let row: Vec<String> = point
+            .iter()
+            .chain(columns.iter().map(|c| &c[i]))
+            .map(f64::to_string)
+            .collect();
+        let _ = writeln!(text, "{}", row.join(separator));
+    }
+    text
+}
+
+/// Writes the nodes of a mesh as a point cloud, with the fields of its VERTEX block.
+///
+/// CSV files are separated by commas and start with a header line, XYZ files are separated by
+/// spaces and only have a header line when there are fields.
+pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn Error>> {
+    trace::span!("write_point_cloud");
+    let csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let text = to_text(&mesh, if csv { "," } else { " " });
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mesh = parse("# scan\n0 0 0 1.5\n1 0 0 2.5\n\n1 1 0.5 3.5\n").unwrap();
+        assert_eq!(mesh.coords().shape(), &[3, 3]);
+        assert_eq!(mesh.coords()[[2, 2]], 0.5);
+        let block = mesh.block(ElementType::VERTEX).unwrap();
+        assert_eq!(block.len(), 3);
+        assert_eq!(
+            block.fields["column_3"].as_slice().unwrap(),
+            &[1.5, 2.5, 3.5]
+        );
+
+        let mesh = parse("x,y,\"temperature\"\n0,0,10\n1,0,20\n").unwrap();
+        assert_eq!(mesh.coords().shape(), &[2, 2]);
+        let block = mesh.block(ElementType::VERTEX).unwrap();
+        assert_eq!(
+            block.fields["temperature"].as_slice().unwrap(),
+            &[10.0, 20.0]
+        );
+
+        assert!(parse("0 0 0\n1 0\n").is_err());
+        assert!(parse("0 0 0\n1 0 a\n").is_err());
+        assert!(parse("a,b\n0,1\n").is_err());
+        assert_eq!(parse("").unwrap().coords().nrows(), 0);
+    }
+
+    #[test]
+    fn test_write_read() {
+        let mut mesh = parse("x y z t\n0 0 0 1\n1 0 0 2\n0 1 0 3\n").unwrap();
+        let vector = nd::arr2(&[[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+        mesh.add_field(ElementType::VERTEX, "v", vector.into_dyn().into_shared());
+        let text = to_text(&mesh.view(), ",");
+        assert_eq!(text.lines().next(), Some("x,y,z,t,v_0,v_1"));
+
+        let path = std::env::temp_dir().join("mefikit_point_cloud_test.csv");
+        write(&path, mesh.view()).unwrap();
+        let read_back = read(&path).unwrap();
+        assert_eq!(read_back.coords(), mesh.coords());
+        let block = read_back.block(ElementType::VERTEX).unwrap();
+        assert_eq!(block.fields["v_1"].as_slice().unwrap(), &[0.0, 1.0, 1.0]);
+        std::fs::remove_file(&path).unwrap();
+
+        let points = UMesh::new(nd::arr2(&[[0.0, 0.0], [1.0, 0.0]]).into_shared());
+        assert_eq!(to_text(&points.view(), " "), "0 0\n1 0\n");
+        let points = UMesh::new(nd::arr2(&[[0.0, 0.0, 0.0, 4.0]]).into_shared());
+        assert_eq!(to_text(&points.view(), ","), "x,y,z,coord_3\n0,0,0,4\n");
+    }
+}
@@ -0,0 +1,192 @@
+//! Partitioned VTK output: one VTU file per part and a PVTU index, as read by ParaView.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::options::{VtkWriteOptions, dense_fields};
+use super::vtu_string::{cell_fields, to_vtu_bytes};
+use crate::mesh::{ElementIds, FieldData, UMesh, UMeshView};
+use crate::trace;
+
+/// Writes the parts of a mesh as a PVTU file.
+///
+/// Each part is written as a VTU file, encoded as set by the options, next to `path` and named
+/// after it with the index of the part (`result_0.vtu`, `result_1.vtu`, ... for `result.pvtu`),
+/// in parallel with the `rayon` feature. The PVTU index lists them, with the cell fields of the parts, which must
+/// be the same in all parts. Sparse fields are written in full, with the fill value of the
+/// options. Nodes on the interfaces are written once per part.
+///
+/// # Errors
+/// Returns an error if a part has an element type without VTK equivalent, if the parts have
+/// different cell fields, or if a file cannot be written.
+pub fn write_pvtu(
+    path: &Path,
+    parts: &[UMeshView],
+    options: &VtkWriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    trace::span!("write_pvtu");
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| format!("Invalid PVTU file name: {path:?}"))?;
+    let dense: Vec<Option<UMesh>> = parts
+        .iter()
+        .map(|p| dense_fields(p, options.sparse_fill()))
+        .collect();
+    if dense.iter().any(Option::is_some) {
+        let dense: Vec<UMesh> = parts
+            .iter()
+            .zip(dense)
+            .map(|(p, d)| d.unwrap_or_else(|| p.to_shared()))
+            .collect();
+        let views: Vec<UMeshView> = dense.iter().map(|m| m.view()).collect();
+        return write_pvtu(path, &views, options);
+    }
+    let fields = parts.first().map_or(Vec::new(), |p| cell_fields(p));
+    if let Some(i) = parts.iter().position(|p| cell_fields(p) != fields) {
+        return Err(format!("Part {i} does not have the cell fields of part 0").into());
+    }
+    let sources: Vec<String> = (0..parts.len())
+        .map(|i| format!("{stem}_{i}.vtu"))
+        .collect();
+
+    let write_part = |(part, source): (&UMeshView, &String)| -> Result<(), String> {
+        let vtu = to_vtu_bytes(part.view(), options)?;
+        std::fs::write(path.with_file_name(source), vtu).map_err(|e| e.to_string())
+    };
+    #[cfg(feature = "rayon")]
+    let written: Vec<Result<(), String>> = parts.par_iter().zip(&sources).map(write_part).collect();
+    #[cfg(not(feature = "rayon"))]
+    let written: Vec<Result<(), String>> = parts.iter().zip(&sources).map(write_part).collect();
+    written.into_iter().collect::<Result<(), String>>()?;
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\"?>\n<VTKFile type=\"PUnstructuredGrid\" version=\"1.0\" \
+         byte_order=\"LittleEndian\">\n<PUnstructuredGrid GhostLevel=\"0\">\n",
+    );
+    out.push_str(
+        "<PPoints>\n<PDataArray type=\"Float64\" NumberOfComponents=\"3\"/>\n</PPoints>\n",
+    );
+    if !fields.is_empty() {
+        out.push_str("<PCellData>\n");
+        for (name, num_components) in &fields {
+            let _ = writeln!(
+                out,
+                r#"<PDataArray type="Float64" Name="{name}" NumberOfComponents="{num_components}"/>"#
+            );
+        }
+        out.push_str("</PCellData>\n");
+    }
+    for source in &sources {
+        let _ = writeln!(out, r#"<Piece Source="{source}"/>"#);
+    }
+    out.push_str("</PUnstructuredGrid>\n</VTKFile>\n");
+    std::fs::write(path, out)?;
+    trace::debug!(parts = parts.len(), "pvtu written");
+    Ok(())
+}
+
+/// Writes a mesh as a PVTU file, split into parts by a partition field.
+///
+/// The partition field holds one integer per cell, as an integer typed field or a float field,
+/// and the cells of each value of the field make a part, in increasing order of the values.
+/// Unused nodes are removed from each part. Cells of lower dimension than the field are not
+/// written. See [`write_pvtu`] for the files written.
+///
+/// # Errors
+/// Returns an error if the partition field does not exist, has more than one value per cell or
+/// non integral float values, and the errors of [`write_pvtu`].
+pub fn write_pvtu_by_field(
+    path: &Path,
+    mesh: UMeshView,
+    partition: &str,
+    options: &VtkWriteOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cells: BTreeMap<i64, ElementIds> = BTreeMap::new();
+    if let Some(field) = mesh.typed_field(partition, None) {
+        for (et, data) in field {
+            let FieldData::Int(values) = data else {
+                return Err(format!("Partition field {partition} is not an integer field").into());
+            };
+            if values.ndim() != 1 {
+                return Err(format!("Partition field {partition} has several components").into());
+            }
+            for (i, &p) in values.iter().enumerate() {
+                cells.entry(p).or_default().add(et, i);
+            }
+        }
+    } else if let Some(field) = mesh.field(partition, None) {
+        for (et, values) in &field.0 {
+            if values.ndim() != 1 {
+                return Err(format!("Partition field {partition} has several components").into());
+            }
+            for (i, &p) in values.iter().enumerate() {
+                if p.fract() != 0.0 || !(i64::MIN as f64..i64::MAX as f64).contains(&p) {
+                    return Err(format!(
+                        "Partition field {partition} has the non integral value {p}"
+                    )
+                    .into());
+                }
+                cells.entry(p as i64).or_default().add(*et, i);
+            }
+        }
+    } else {
+        return Err(format!("No partition field named {partition} in the mesh").into());
+    }
+
+    let parts: Vec<UMesh> = cells
+        .values()
+        .map(|ids| {
+            let mut part = mesh.extract_to_owned(ids, true);
+            part.prune_nodes();
+            part
+        })
+        .collect();
+    let views: Vec<UMeshView> = parts.iter().map(|p| p.view()).collect();
+    write_pvtu(path, &views, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementType;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_write_pvtu_by_field() {
+        let dir = std::env::temp_dir();
+        let path = dir.join("test_partition.pvtu");
+        let mut mesh = me::make_imesh_2d(2);
+        let values = ndarray::arr1(&[0.0, 1.0, 2.0, 3.0]).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let rank = ndarray::arr1(&[1, 1, 0, 0]).into_dyn().into_shared();
+        let rank = BTreeMap::from([(ElementType::QUAD4, FieldData::Int(rank))]);
+        mesh.update_typed_field("rank", rank, None);
+
+        let options = VtkWriteOptions::new();
+        write_pvtu_by_field(&path, mesh.view(), "rank", &options).unwrap();
+        let pvtu = std::fs::read_to_string(&path).unwrap();
+        assert!(pvtu.contains(r#"<Piece Source="test_partition_1.vtu"/>"#));
+        assert!(pvtu.contains(r#"Name="f" NumberOfComponents="1"/>"#));
+        // The cells of rank 0 are the top row of the grid
+        let part = std::fs::read_to_string(dir.join("test_partition_0.vtu")).unwrap();
+        assert!(part.contains(r#"<Piece NumberOfPoints="6" NumberOfCells="2">"#));
+        assert!(part.contains(r#"format="ascii">2 3<"#));
+        for file in ["test_partition_0.vtu", "test_partition_1.vtu"] {
+            std::fs::remove_file(dir.join(file)).unwrap();
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(write_pvtu_by_field(&path, mesh.view(), "g", &options).is_err());
+        let values = ndarray::arr1(&[0.0, 0.5, 1.0, 1.0]).into_dyn();
+        mesh.assign_field("half", None, values.view()).unwrap();
+        assert!(write_pvtu_by_field(&path, mesh.view(), "half", &options).is_err());
+        assert!(!dir.join("test_partition_0.vtu").exists());
+        let other = me::make_imesh_2d(1);
+        assert!(write_pvtu(&path, &[mesh.view(), other.view()], &options).is_err());
+    }
+}
@@ -0,0 +1,925 @@
+//! Registry of the mesh file formats known to [`super::read_with`] and [`super::write_with`].
+//!
+//! Each format is a backend implementing [`MeshFormat`], which declares its file extensions,
+//! how to recognize its files from their first bytes and what it can store. Files are checked
+//! against their content before being read, so that a file with a misleading extension gives a
+//! clear error instead of a parsing error, and so that files of common unsupported formats (gmsh,
+//! STL, MED) are named in the error. MED files are HDF5 files like VTKHDF ones, so they are only
+//! told apart when read, from the `INFOS_GENERALES` group at their root. The built-in formats are
+//! always registered, and those whose feature is disabled return an error. Other crates add their
+//! own formats at runtime with [`register_format`].
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+
+#[cfg(feature = "hdf5")]
+use super::hdfvtk_io;
+use super::nastran;
+use super::openfoam;
+use super::options::{self, ReadOptions, VtkCompression, VtkWriteOptions, WriteOptions};
+use super::point_cloud;
+use super::serde_io;
+use super::tetgen;
+#[cfg(feature = "io")]
+use super::vtk_io;
+use crate::mesh::{UMesh, UMeshView};
+
+/// Number of bytes at the start of a file given to [`MeshFormat::sniff`].
+const SNIFF_LEN: usize = 512;
+
+const HDF5_SIGNATURE: &[u8] = b"\x89HDF\r\n\x1a\n";
+
+/// Recognizes the files of common mesh formats that mefikit does not support, from their first
+/// bytes and their size.
+fn sniff_unsupported(header: &[u8], len: u64) -> Option<&'static str> {
+    let text = header.trim_ascii_start();
+    if text.starts_with(b"$MeshFormat") {
+        return Some("gmsh");
+    }
+    if text.starts_with(b"solid") && header.windows(5).any(|w| w == b"facet") {
+        return Some("STL");
+    }
+    // Binary STL: an 80 bytes header, the number of triangles and 50 bytes per triangle
+    let triangles = header
+        .get(80..84)
+        .map(|n| u32::from_le_bytes(n.try_into().unwrap()));
+    if triangles.is_some_and(|n| 84 + 50 * u64::from(n) == len) {
+        return Some("STL");
+    }
+    None
+}
+
+/// The first bytes of a file and its size.
+fn read_header(path: &Path) -> Result<(Vec<u8>, u64), Box<dyn Error>> {
+    // Formats stored as directories are only known by name
+    if path.is_dir() {
+        return Ok((Vec::new(), 0));
+    }
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    file.take(SNIFF_LEN as u64).read_to_end(&mut header)?;
+    Ok((header, len))
+}
+
+/// What a mesh format can read, write and store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Files can be read.
+    pub read: bool,
+    /// Files can be written.
+    pub write: bool,
+    /// Element fields are stored.
+    pub fields: bool,
+    /// Element and node groups are stored.
+    pub groups: bool,
+    /// Polygons and polyhedra are stored.
+    pub poly: bool,
+    /// Files can hold several time steps.
+    pub time_steps: bool,
+    /// Files can be compressed.
+    pub compression: bool,
+}
+
+/// A backend reading or writing mesh files in one format.
+///
+/// Only the name, the extensions and the capabilities are required: a format that cannot be
+/// read or written keeps the default methods, which return an error.
+pub trait MeshFormat: Send + Sync {
+    /// The name of the format, as given to [`ReadOptions::format`] and
+    /// [`WriteOptions::format`].
+    fn name(&self) -> &str;
+
+    /// The file extensions of the format, without dot and in lower case.
+    fn extensions(&self) -> &[&str];
+
+    /// Whether a file is in this format, from its first bytes (at most 512).
+    ///
+    /// Returns `Some(true)` for a file of this format, `Some(false)` for a file of another
+    /// format and `None` if the format cannot tell. Used to find the format of files whose
+    /// extension is unknown, and to detect files whose content does not match the requested
+    /// format. Formats without signature keep the default, which cannot tell.
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        let _ = header;
+        None
+    }
+
+    /// What the format can read, write and store.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Reads a mesh from a file.
+    ///
+    /// Elements and fields are filtered afterwards by the registry, so a format may ignore the
+    /// fields, groups and dimensions of the options, or use them to read less.
+    fn read(&self, path: &Path, options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        let _ = (path, options);
+        Err(format!("{} files cannot be read", self.name()).into())
+    }
+
+    /// Writes a mesh to a file.
+    ///
+    /// Fields are filtered beforehand by the registry, so `options` only matters for the
+    /// compression.
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        let _ = (path, mesh, options);
+        Err(format!("{} files cannot be written", self.name()).into())
+    }
+}
+
+/// Error of a format whose support was not compiled in.
+#[cfg(not(all(feature = "io", feature = "hdf5")))]
+fn disabled(format: &str, feature: &str) -> Box<dyn Error> {
+    format!("{format} files need mefikit to be built with the `{feature}` feature").into()
+}
+
+struct JsonFormat;
+
+impl MeshFormat for JsonFormat {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        Some(header.trim_ascii_start().starts_with(b"{"))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            fields: true,
+            groups: true,
+            poly: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        serde_io::read_json(path)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        _options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        serde_io::write_json(path, mesh)
+    }
+}
+
+struct YamlFormat;
+
+impl MeshFormat for YamlFormat {
+    fn name(&self) -> &str {
+        "yaml"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        // Almost any text is valid YAML, only the start of serialized meshes is recognized
+        (header.starts_with(b"---") || header.starts_with(b"coords:")).then_some(true)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        JsonFormat.capabilities()
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        serde_io::read_yaml(path)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        _options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        serde_io::write_yaml(path, mesh)
+    }
+}
+
+struct VtkFormat;
+
+impl MeshFormat for VtkFormat {
+    fn name(&self) -> &str {
+        "vtk"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vtk", "vtu"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        Some(header.starts_with(b"# vtk DataFile") || header.windows(8).any(|w| w == b"<VTKFile"))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: cfg!(feature = "io"),
+            // VTU files are written without the `io` feature, legacy VTK files need it
+            write: true,
+            poly: true,
+            compression: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        #[cfg(feature = "io")]
+        {
+            vtk_io::read(path)
+        }
+        #[cfg(not(feature = "io"))]
+        {
+            let _ = path;
+            Err(disabled(self.name(), "io"))
+        }
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        let vtu = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("vtu"));
+        if vtu {
+            let mut vtk_options = VtkWriteOptions::new().fill_value(options.sparse_fill());
+            if let Some(level) = options.compression_level() {
+                vtk_options = vtk_options.compression(VtkCompression::Zlib(level));
+            }
+            return super::write_vtu(path, mesh, &vtk_options);
+        }
+        if options.compression_level().is_some() {
+            return Err("Legacy VTK files cannot be compressed, write a VTU file".into());
+        }
+        #[cfg(feature = "io")]
+        {
+            match options::dense_fields(&mesh, options.sparse_fill()) {
+                Some(dense) => vtk_io::write(path, dense.view()),
+                None => vtk_io::write(path, mesh),
+            }
+        }
+        #[cfg(not(feature = "io"))]
+        {
+            let _ = (path, mesh);
+            Err(disabled(self.name(), "io"))
+        }
+    }
+}
+
+struct VtkHdfFormat;
+
+impl MeshFormat for VtkHdfFormat {
+    fn name(&self) -> &str {
+        "vtkhdf"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vtkhdf", "h5", "hdf5"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        // MED files are HDF5 files too, they are told apart when read
+        Some(header.starts_with(HDF5_SIGNATURE))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: cfg!(feature = "hdf5"),
+            write: cfg!(feature = "hdf5"),
+            time_steps: true,
+            compression: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        #[cfg(feature = "hdf5")]
+        {
+            if hdfvtk_io::is_med(path) {
+                return Err(
+                    format!("{path:?} is a MED file, which is not a supported format").into(),
+                );
+            }
+            let dimensions = options.kept_dimensions();
+            let keep = |et: crate::mesh::ElementType| {
+                dimensions.is_none_or(|d| d.contains(&et.dimension()))
+            };
+            hdfvtk_io::read(path, options.step(), &keep)
+        }
+        #[cfg(not(feature = "hdf5"))]
+        {
+            let _ = options;
+            Err(format!(
+                "{path:?} is an HDF5 file (VTKHDF, or possibly MED, which is not supported): {}",
+                disabled(self.name(), "hdf5")
+            )
+            .into())
+        }
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        #[cfg(feature = "hdf5")]
+        {
+            let compression = options.compression_level();
+            match options::dense_fields(&mesh, options.sparse_fill()) {
+                Some(dense) => hdfvtk_io::write(path, dense.view(), compression),
+                None => hdfvtk_io::write(path, mesh, compression),
+            }
+        }
+        #[cfg(not(feature = "hdf5"))]
+        {
+            let _ = (path, mesh, options);
+            Err(disabled(self.name(), "hdf5"))
+        }
+    }
+}
+
+struct NastranFormat;
+
+impl MeshFormat for NastranFormat {
+    fn name(&self) -> &str {
+        "nastran"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bdf", "nas", "bulk"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        // Executive and case control sections may come first, and cards are in any order
+        let text = String::from_utf8_lossy(header);
+        (text.contains("BEGIN BULK") || text.lines().any(|l| l.starts_with("GRID"))).then_some(true)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            groups: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        nastran::read(path)
+    }
+}
+
+struct OpenFoamFormat;
+
+impl MeshFormat for OpenFoamFormat {
+    fn name(&self) -> &str {
+        "openfoam"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        // The empty file marking a case for ParaView
+        &["foam"]
+    }
+
+    fn sniff(&self, header: &[u8]) -> Option<bool> {
+        header.windows(8).any(|w| w == b"FoamFile").then_some(true)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            groups: true,
+            poly: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        openfoam::read(path)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        _options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        openfoam::write(path, mesh)
+    }
+}
+
+struct TetGenFormat;
+
+impl MeshFormat for TetGenFormat {
+    fn name(&self) -> &str {
+        "tetgen"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["node", "ele", "poly"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            fields: true,
+            groups: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        tetgen::read(path)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        _options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        tetgen::write(path, mesh)
+    }
+}
+
+struct PointsFormat;
+
+impl MeshFormat for PointsFormat {
+    fn name(&self) -> &str {
+        "points"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xyz", "csv"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            write: true,
+            fields: true,
+            ..Default::default()
+        }
+    }
+
+    fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        point_cloud::read(path)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        mesh: UMeshView,
+        _options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        point_cloud::write(path, mesh)
+    }
+}
+
+/// A set of mesh formats, looked up by name, by file extension or by the content of a file.
+///
+/// Formats registered last take precedence when several of them share an extension or
+/// recognize the same file.
+#[derive(Clone)]
+pub struct FormatRegistry {
+    formats: Vec<Arc<dyn MeshFormat>>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormatRegistry {
+    /// Creates a registry of the built-in formats.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(JsonFormat);
+        registry.register(YamlFormat);
+        registry.register(VtkFormat);
+        registry.register(VtkHdfFormat);
+        registry.register(NastranFormat);
+        registry.register(OpenFoamFormat);
+        registry.register(TetGenFormat);
+        registry.register(PointsFormat);
+        registry
+    }
+
+    /// Creates a registry without any format.
+    pub fn empty() -> Self {
+        Self {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Adds a format, replacing the format of the same name if any.
+    pub fn register(&mut self, format: impl MeshFormat + 'static) {
+        let name = format.name().to_lowercase();
+        self.formats.retain(|f| f.name().to_lowercase() != name);
+        self.formats.push(Arc::new(format));
+    }
+
+    /// The registered formats, in registration order.
+    pub fn formats(&self) -> impl DoubleEndedIterator<Item = &dyn MeshFormat> {
+        self.formats.iter().map(|f| f.as_ref())
+    }
+
+    /// Finds a format by name or by extension, case insensitively.
+    pub fn get(&self, name: &str) -> Option<&dyn MeshFormat> {
+        let name = name.to_lowercase();
+        let by_name = self
+            .formats()
+            .rev()
+            .find(|f| f.name().to_lowercase() == name);
+        by_name.or_else(|| self.by_extension(&name))
+    }
+
+    fn by_extension(&self, extension: &str) -> Option<&dyn MeshFormat> {
+        let extension = extension.to_lowercase();
+        self.formats()
+            .rev()
+            .find(|f| f.extensions().contains(&extension.as_str()))
+    }
+
+    /// Finds the format of a file from its extension.
+    pub fn from_path(&self, path: &Path) -> Result<&dyn MeshFormat, String> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| self.by_extension(e))
+            .ok_or_else(|| format!("Unsupported file extension: {path:?}"))
+    }
+
+    /// Finds the format of an existing file from its first bytes.
+    pub fn sniff(&self, path: &Path) -> Result<Option<&dyn MeshFormat>, Box<dyn Error>> {
+        let (header, _) = read_header(path)?;
+        Ok(self.sniff_header(&header))
+    }
+
+    fn sniff_header(&self, header: &[u8]) -> Option<&dyn MeshFormat> {
+        self.formats().rev().find(|f| f.sniff(header) == Some(true))
+    }
+
+    fn format_of(&self, name: Option<&str>, path: &Path) -> Result<&dyn MeshFormat, String> {
+        match name {
+            Some(name) => self
+                .get(name)
+                .ok_or_else(|| format!("Unknown mesh format: {name}")),
+            None => self.from_path(path),
+        }
+    }
+
+    /// Finds the format to read a file in, checking the requested format against the content of
+    /// the file.
+    fn read_format(&self, path: &Path, requested: Option<&str>) -> Result<&dyn MeshFormat, String> {
+        let (header, len) = read_header(path).map_err(|e| format!("Cannot read {path:?}: {e}"))?;
+        let sniffed = self.sniff_header(&header);
+        let detected = match sniffed {
+            Some(format) => Some(format.name()),
+            None => sniff_unsupported(&header, len),
+        };
+        let format = match (self.format_of(requested, path), sniffed, detected) {
+            (Ok(format), _, _) => format,
+            (Err(e), _, _) if requested.is_some() => return Err(e),
+            (Err(_), Some(format), _) => return Ok(format),
+            (Err(_), None, Some(detected)) => {
+                return Err(format!(
+                    "{path:?} looks like a {detected} file, which is not a supported format"
+                ));
+            }
+            (Err(e), None, None) => return Err(e),
+        };
+        match detected {
+            Some(detected) if format.sniff(&header) == Some(false) => {
+                let origin = match requested {
+                    Some(_) => "was requested",
+                    None => "is expected from the extension",
+                };
+                let name = format.name();
+                Err(format!(
+                    "Format mismatch for {path:?}: {name} {origin}, but it looks like {detected}"
+                ))
+            }
+            _ => Ok(format),
+        }
+    }
+
+    /// Reads a mesh from a file, with [`ReadOptions`].
+    ///
+    /// Without a format in the options, the format is found from the file extension, or from
+    /// the content of the file if the extension is unknown. The format is checked against the
+    /// content of the file: a file recognized as another format, supported or not, is an error.
+    pub fn read_with(&self, path: &Path, options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+        let format = self.read_format(path, options.format.as_deref())?;
+        let name = format.name();
+        if options.time_step.is_some_and(|step| step > 0) && !format.capabilities().time_steps {
+            return Err(format!("{name} files have a single time step").into());
+        }
+        let mut mesh = format.read(path, options)?;
+        if options.groups.is_some() || options.dimensions.is_some() {
+            options::keep_elements(
+                &mut mesh,
+                options.groups.as_deref(),
+                options.dimensions.as_deref(),
+            );
+        }
+        if let Some(names) = &options.fields {
+            options::keep_fields(&mut mesh, names);
+        }
+        Ok(mesh)
+    }
+
+    /// Writes a mesh to a file, with [`WriteOptions`].
+    ///
+    /// Without a format in the options, the format is found from the file extension.
+    pub fn write_with(
+        &self,
+        path: &Path,
+        mut mesh: UMeshView,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        let format = self.format_of(options.format.as_deref(), path)?;
+        let name = format.name();
+        if options.compression.is_some() && !format.capabilities().compression {
+            return Err(format!("{name} files cannot be compressed").into());
+        }
+        if let Some(names) = &options.fields {
+            options::keep_fields(&mut mesh, names);
+        }
+        format.write(path, mesh, options)
+    }
+}
+
+/// The formats used by [`super::read_with`] and [`super::write_with`].
+static REGISTRY: Lazy<RwLock<FormatRegistry>> = Lazy::new(|| RwLock::new(FormatRegistry::new()));
+
+/// Adds a format to the global registry, replacing the format of the same name if any.
+///
+/// The format is then used by [`super::read`], [`super::write`] and their variants with
+/// options, as the built-in formats.
+pub fn register_format(format: impl MeshFormat + 'static) {
+    REGISTRY
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(format);
+}
+
+/// A copy of the global registry of formats.
+pub fn registry() -> FormatRegistry {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Format;
+    use crate::mesh_examples as me;
+    use ndarray as nd;
+    use std::path::PathBuf;
+
+    /// Node coordinates as lines of text, read back as a mesh without elements.
+    struct XyzFormat;
+
+    impl MeshFormat for XyzFormat {
+        fn name(&self) -> &str {
+            "xyz"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["xyz", "txt"]
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                read: true,
+                write: true,
+                ..Default::default()
+            }
+        }
+
+        fn read(&self, path: &Path, _options: &ReadOptions) -> Result<UMesh, Box<dyn Error>> {
+            let text = std::fs::read_to_string(path)?;
+            let rows: Vec<Vec<f64>> = text
+                .lines()
+                .map(|l| l.split(' ').map(str::parse).collect())
+                .collect::<Result<_, _>>()?;
+            let dim = rows.first().map_or(0, Vec::len);
+            let coords = nd::Array2::from_shape_vec((rows.len(), dim), rows.concat())?;
+            Ok(UMesh::new(coords.into_shared()))
+        }
+
+        fn write(
+            &self,
+            path: &Path,
+            mesh: UMeshView,
+            _options: &WriteOptions,
+        ) -> Result<(), Box<dyn Error>> {
+            let lines: Vec<String> = mesh
+                .coords()
+                .outer_iter()
+                .map(|x| x.iter().map(f64::to_string).collect::<Vec<_>>().join(" "))
+                .collect();
+            std::fs::write(path, lines.join("\n"))?;
+            Ok(())
+        }
+    }
+
+    /// A format recognizing the same files as YAML.
+    struct YamlCopy;
+
+    impl MeshFormat for YamlCopy {
+        fn name(&self) -> &str {
+            "yaml-copy"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &[]
+        }
+
+        fn sniff(&self, header: &[u8]) -> Option<bool> {
+            YamlFormat.sniff(header)
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_formats() {
+        let registry = FormatRegistry::new();
+        assert_eq!(registry.get("H5").unwrap().name(), "vtkhdf");
+        assert_eq!(registry.get("yml").unwrap().name(), "yaml");
+        assert!(registry.get("med").is_none());
+        let vtk = registry.from_path(Path::new("a/mesh.VTU")).unwrap();
+        assert!(vtk.capabilities().poly);
+        assert!(!vtk.capabilities().time_steps);
+        assert!(registry.from_path(Path::new("mesh")).is_err());
+        let hdf5 = registry.get("hdf5").unwrap();
+        assert_eq!(hdf5.sniff(b"\x89HDF\r\n\x1a\n\0\0"), Some(true));
+        assert_eq!(hdf5.sniff(b"<VTKFile"), Some(false));
+        assert_eq!(registry.get("yaml").unwrap().sniff(b"a: 1"), None);
+        let nastran = registry.get("bdf").unwrap();
+        assert!(!nastran.capabilities().write);
+        assert_eq!(nastran.sniff(b"SOL 101\nCEND\nBEGIN BULK\n"), Some(true));
+        assert_eq!(nastran.sniff(b"{}"), None);
+    }
+
+    #[test]
+    fn test_vtk_write_options() {
+        let registry = FormatRegistry::new();
+        let path = std::env::temp_dir().join("test_vtk_write_options.vtu");
+        let mesh = crate::mesh_examples::make_imesh_2d(2);
+        registry
+            .write_with(&path, mesh.view(), &WriteOptions::new())
+            .unwrap();
+        let ascii = std::fs::read(&path).unwrap();
+        assert!(ascii.windows(7).any(|w| w == b"\"ascii\""));
+        let compressed = WriteOptions::new().compression(6);
+        let written = registry.write_with(&path, mesh.view(), &compressed);
+        assert_eq!(written.is_ok(), cfg!(feature = "io"));
+        if written.is_ok() {
+            let zlib = std::fs::read(&path).unwrap();
+            assert!(zlib.windows(21).any(|w| w == b"vtkZLibDataCompressor"));
+        }
+        std::fs::remove_file(&path).unwrap();
+        let legacy = std::env::temp_dir().join("test_vtk_write_options.vtk");
+        assert!(
+            registry
+                .write_with(&legacy, mesh.view(), &compressed)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_hdf5_signature() {
+        let path = std::env::temp_dir().join("test_read_hdf5_signature.med");
+        std::fs::write(&path, b"\x89HDF\r\n\x1a\n\0\0").unwrap();
+        let error = FormatRegistry::new()
+            .read_with(&path, &ReadOptions::new())
+            .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        if !cfg!(feature = "hdf5") {
+            assert!(error.to_string().contains("possibly MED"));
+        }
+    }
+
+    #[test]
+    fn test_sniff_unsupported() {
+        assert_eq!(
+            sniff_unsupported(b"$MeshFormat\n4.1 0 8\n", 20),
+            Some("gmsh")
+        );
+        let ascii = b"solid cube\n  facet normal 0 0 1\n";
+        assert_eq!(sniff_unsupported(ascii, 40), Some("STL"));
+        let mut binary = vec![0u8; 84];
+        binary[80] = 2;
+        assert_eq!(sniff_unsupported(&binary, 184), Some("STL"));
+        assert_eq!(sniff_unsupported(&binary, 185), None);
+        assert_eq!(sniff_unsupported(b"{}", 2), None);
+    }
+
+    #[test]
+    fn test_sniff() {
+        let path = PathBuf::from("test_sniff.data");
+        let registry = FormatRegistry::new();
+        let mesh = me::make_imesh_2d(1);
+        let json = WriteOptions::new().format("JSON");
+        registry.write_with(&path, mesh.view(), &json).unwrap();
+        assert_eq!(registry.sniff(&path).unwrap().unwrap().name(), "json");
+        let read_back = registry.read_with(&path, &ReadOptions::new()).unwrap();
+        assert_eq!(read_back, mesh);
+
+        let yaml = WriteOptions::new().format("yaml");
+        registry.write_with(&path, mesh.view(), &yaml).unwrap();
+        assert_eq!(registry.sniff(&path).unwrap().unwrap().name(), "yaml");
+
+        // A format registered later is tried first
+        let mut custom = registry.clone();
+        custom.register(YamlCopy);
+        assert_eq!(custom.sniff(&path).unwrap().unwrap().name(), "yaml-copy");
+
+        std::fs::write(&path, "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n").unwrap();
+        let error = registry.read_with(&path, &ReadOptions::new()).unwrap_err();
+        assert!(error.to_string().contains("gmsh"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_format_mismatch() {
+        let path = PathBuf::from("test_format_mismatch.vtu");
+        let registry = FormatRegistry::new();
+        let mesh = me::make_imesh_2d(1);
+        let json = WriteOptions::new().format(Format::Json);
+        registry.write_with(&path, mesh.view(), &json).unwrap();
+        let error = registry.read_with(&path, &ReadOptions::new()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("vtk is expected from the extension")
+        );
+        assert!(error.to_string().contains("looks like json"));
+        let read_back = registry.read_with(&path, &ReadOptions::new().format(Format::Json));
+        assert!(read_back.is_ok());
+        let hdf = ReadOptions::new().format(Format::VtkHdf);
+        let error = registry.read_with(&path, &hdf).unwrap_err();
+        assert!(error.to_string().contains("vtkhdf was requested"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_register_format() {
+        let path = PathBuf::from("test_register_format.txt");
+        let mesh = me::make_imesh_2d(1);
+        let mut registry = FormatRegistry::new();
+        assert!(
+            registry
+                .write_with(&path, mesh.view(), &WriteOptions::new())
+                .is_err()
+        );
+        registry.register(XyzFormat);
+        registry
+            .write_with(&path, mesh.view(), &WriteOptions::new())
+            .unwrap();
+        let read_back = registry.read_with(&path, &ReadOptions::new()).unwrap();
+        assert_eq!(read_back.coords(), mesh.coords());
+        let step = ReadOptions::new().time_step(1);
+        assert!(registry.read_with(&path, &step).is_err());
+        let compressed = WriteOptions::new().compression(1);
+        assert!(
+            registry
+                .write_with(&path, mesh.view(), &compressed)
+                .is_err()
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(registry.get("xyz").unwrap().name(), "xyz");
+        assert_eq!(
+            registry.formats().count(),
+            FormatRegistry::new().formats().count() + 1
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Export of mesh statistics, to compare meshes between the runs of a pipeline.
+//!
+//! A report is a table with one row per element type of each quality metric, group or field,
+//! holding the statistics of its values. It is written as CSV or JSON, the rows being sorted so
+//! that the reports of two meshes can be diffed line by line.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write;
+use std::path::Path;
+
+use ndarray as nd;
+use serde::Serialize;
+
+use crate::mesh::{Dimension, ElementType, UMeshView};
+use crate::stream::Stats;
+use crate::tools::{check_orientation, measure};
+use crate::trace;
+
+/// The sections of a report written by [`export_report`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReportKind {
+    /// The jacobian ratio of the cells whose dimension is the space dimension, see
+    /// [`crate::tools::OrientationCheck::quality`]. Poly cells, which have none, are left out.
+    Quality,
+    /// The measures (lengths, areas or volumes) of the elements of each group.
+    GroupMeasures,
+    /// The values of each field, all components together.
+    FieldStats,
+}
+
+impl ReportKind {
+    fn name(self) -> &'static str {
+        match self {
+            ReportKind::Quality => "quality",
+            ReportKind::GroupMeasures => "group_measures",
+            ReportKind::FieldStats => "field_stats",
+        }
+    }
+}
+
+/// The statistics of the values of a group, a field or a metric, for one element type.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct Row {
+    section: &'static str,
+    name: String,
+    element_type: String,
+    count: usize,
+    min: f64,
+    max: f64,
+    mean: f64,
+    sum: f64,
+}
+
+impl Row {
+    fn new(kind: ReportKind, name: &str, et: ElementType, stats: Stats) -> Self {
+        Self {
+            section: kind.name(),
+            name: name.to_owned(),
+            element_type: format!("{et:?}"),
+            count: stats.count,
+            min: stats.min,
+            max: stats.max,
+            mean: stats.mean().unwrap_or(f64::NAN),
+            sum: stats.sum,
+        }
+    }
+}
+
+fn quality_rows(mesh: &UMeshView) -> Vec<Row> {
+    check_orientation(mesh.view(), 0.0)
+        .quality
+        .into_iter()
+        .filter_map(|(et, quality)| {
+            let mut stats = Stats::default();
+            stats.extend(quality.iter().copied().filter(|q| !q.is_nan()));
+            (stats.count > 0).then(|| Row::new(ReportKind::Quality, "", et, stats))
+        })
+        .collect()
+}
+
+fn group_rows(mesh: &UMeshView) -> Vec<Row> {
+    let dims: BTreeSet<Dimension> = mesh.element_types().map(|et| et.dimension()).collect();
+    let measures: BTreeMap<ElementType, nd::Array1<f64>> = dims
+        .into_iter()
+        .flat_map(|dim| measure(mesh.view(), Some(dim)))
+        .collect();
+    let mut rows = Vec::new();
+    for name in mesh.group_names() {
+        for (et, indices) in mesh.group_as_element_ids(&name).iter_blocks() {
+            let mut stats = Stats::default();
+            stats.extend(indices.iter().map(|&i| measures[et][i]));
+            rows.push(Row::new(ReportKind::GroupMeasures, &name, *et, stats));
+        }
+    }
+    rows
+}
+
+fn field_rows(mesh: &UMeshView) -> Vec<Row> {
+    let mut fields: BTreeMap<&str, BTreeMap<ElementType, Stats>> = BTreeMap::new();
+    for (&et, block) in mesh.blocks() {
+        for (name, values) in &block.fields {
+            let stats = fields
+                .entry(name.as_str())
+                .or_default()
+                .entry(et)
+                .or_default();
+            stats.extend(values.iter().copied());
+        }
+    }
+    fields
+        .into_iter()
+        .flat_map(|(name, stats)| {
+            stats
+                .into_iter()
+                .map(move |(et, stats)| Row::new(ReportKind::FieldStats, name, et, stats))
+        })
+        .collect()
+}
+
+/// Computes the rows of the requested sections, in the order of `what`.
+fn rows(mesh: &UMeshView, what: &[ReportKind]) -> Vec<Row> {
+    what.iter()
+        .flat_map(|kind| match kind {
+            ReportKind::Quality => quality_rows(mesh),
+            ReportKind::GroupMeasures => group_rows(mesh),
+            ReportKind::FieldStats => field_rows(mesh),
+        })
+        .collect()
+}
+
+/// Quotes a CSV value containing a comma, a quote or a line break, doubling its quotes, as
+/// described by RFC 4180.
+fn csv_value(value: &str) -> Cow<'_, str> {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => Cow::Owned(format!("\"{}\"", value.replace('"', "\"\""))),
+        false => Cow::Borrowed(value),
+    }
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut text = String::from("section,name,element_type,count,min,max,mean,sum\n");
+    for r in rows {
+        let _ = writeln!(
+            text,
+            "{},{},{},{},{},{},{},{}",
+            r.section,
+            csv_value(&r.name),
+            r.element_type,
+            r.count,
+            r.min,
+            r.max,
+            r.mean,
+            r.sum
+        );
+    }
+    text
+}
+
+/// Writes quality metrics, per group measures and field statistics of a mesh to a report file.
+///
+/// The report is written as CSV or JSON according to the extension of `path`, with a row per
+/// section, name and element type holding the count, minimum, maximum, mean and sum of the
+/// values. Sections are written in the order of `what`. In JSON, the rows are an array of
+/// objects and undefined values (the mean of an empty group) are `null`.
+///
+/// # Errors
+/// Returns an error if the extension is neither `csv` nor `json`, or if the file cannot be
+/// written.
+pub fn export_report(
+    path: &Path,
+    mesh: UMeshView,
+    what: &[ReportKind],
+) -> Result<(), Box<dyn Error>> {
+    trace::span!("export_report");
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let rows = rows(&mesh, what);
+    let text = match extension.as_str() {
+        "csv" => to_csv(&rows),
+        "json" => serde_json::to_string_pretty(&rows)?,
+        _ => return Err(format!("Unsupported report extension: {path:?}").into()),
+    };
+    std::fs::write(path, text)?;
+    trace::debug!(rows = rows.len(), "report exported");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementIds;
+    use crate::mesh_examples as me;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_rows() {
+        let mut mesh = me::make_imesh_2d(4);
+        let values = nd::Array1::from_iter((0..16).map(f64::from)).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let mut left = ElementIds::new();
+        for i in 0..4 {
+            left.add(ElementType::QUAD4, 4 * i);
+        }
+        mesh.set_group("left", &left);
+
+        let rows = rows(
+            &mesh.view(),
+            &[
+                ReportKind::FieldStats,
+                ReportKind::Quality,
+                ReportKind::GroupMeasures,
+            ],
+        );
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            (rows[0].section, rows[0].name.as_str()),
+            ("field_stats", "f")
+        );
+        assert_eq!((rows[0].min, rows[0].max, rows[0].mean), (0.0, 15.0, 7.5));
+        assert_eq!(rows[1].element_type, "QUAD4");
+        assert_relative_eq!(rows[1].min, 1.0, epsilon = 1e-12);
+        assert_eq!((rows[2].name.as_str(), rows[2].count), ("left", 4));
+        assert_relative_eq!(rows[2].sum, 0.25, epsilon = 1e-12);
+
+        let csv = to_csv(&rows);
+        assert_eq!(csv.lines().count(), 4);
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("field_stats,f,QUAD4,16,0,15,7.5,")
+        );
+    }
+
+    #[test]
+    fn test_csv_names() {
+        // A curved cell in a group whose name needs quoting
+        let coords = nd::arr2(&[
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [0.5, -0.1],
+            [0.5, 0.5],
+            [0.0, 0.5],
+        ]);
+        let mut mesh = crate::mesh::UMesh::new(coords.into_shared());
+        let tri6 = nd::arr2(&[[0, 1, 2, 3, 4, 5]]);
+        mesh.add_regular_block(ElementType::TRI6, tri6.into_shared(), None);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::TRI6, 0);
+        mesh.set_group("wall, \"left\"", &ids);
+
+        let rows = rows(&mesh.view(), &[ReportKind::GroupMeasures]);
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].sum > 0.5);
+        let csv = to_csv(&rows);
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("group_measures,\"wall, \"\"left\"\"\",TRI6,1,")
+        );
+    }
+
+    #[test]
+    fn test_export_report() {
+        let mesh = me::make_imesh_3d(2);
+        let path = std::env::temp_dir().join("mefikit_report_test.json");
+        export_report(&path, mesh.view(), &[ReportKind::Quality]).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json[0]["section"], "quality");
+        assert_eq!(json[0]["count"], 8);
+
+        let path = Path::new("report.txt");
+        assert!(export_report(path, mesh.view(), &[ReportKind::Quality]).is_err());
+    }
+}
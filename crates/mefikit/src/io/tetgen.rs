@@ -0,0 +1,663 @@
+//! Import and export of the Triangle and TetGen file family.
+//!
+//! A mesh is stored in files sharing a base name: `.node` for the nodes, `.ele` for the
+//! triangles or tetrahedra, `.edge` and `.face` for the boundary edges and faces, and `.poly` for
+//! the input geometry of the mesher, with its nodes and its segments (2D) or facets (3D). Element
+//! attributes are read as fields named `attribute_0`, `attribute_1`, etc., and nonzero boundary
+//! markers as groups named `marker_1`, `marker_2`, etc., of nodes and of boundary elements. Node
+//! attributes are read as the fields of a VERTEX block holding one element per node, as the
+//! fields of point clouds, and these fields are written back as node attributes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use ndarray as nd;
+
+use crate::mesh::{Dimension, ElementLike, ElementType, UMesh, UMeshView};
+use crate::trace;
+
+/// The records of a file: the values of its lines, without comments and blank lines.
+struct Records<'a> {
+    extension: &'static str,
+    lines: std::vec::IntoIter<Vec<&'a str>>,
+}
+
+impl<'a> Records<'a> {
+    fn new(extension: &'static str, text: &'a str) -> Self {
+        let lines: Vec<Vec<&str>> = text
+            .lines()
+            .map(|l| {
+                l.split('#')
+                    .next()
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .collect()
+            })
+            .filter(|r: &Vec<&str>| !r.is_empty())
+            .collect();
+        Self {
+            extension,
+            lines: lines.into_iter(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Vec<&'a str>, String> {
+        self.lines
+            .next()
+            .ok_or(format!("Unexpected end of .{} file", self.extension))
+    }
+
+    /// Reads a header record of counts, the missing ones being 0.
+    fn header<const N: usize>(&mut self) -> Result<[usize; N], String> {
+        let record = self.next()?;
+        let mut header = [0; N];
+        for (count, value) in header.iter_mut().zip(&record) {
+            *count = self.parse(value)?;
+        }
+        Ok(header)
+    }
+
+    fn parse<T: FromStr>(&self, value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("Invalid value {value} in .{} file", self.extension))
+    }
+
+    fn value<T: FromStr>(&self, record: &[&str], i: usize) -> Result<T, String> {
+        let value = record
+            .get(i)
+            .ok_or(format!("Missing value in .{} file", self.extension))?;
+        self.parse(value)
+    }
+}
+
+/// The nodes of a `.node` file, or of the first section of a `.poly` file.
+struct Nodes {
+    dim: usize,
+    coords: Vec<f64>,
+    /// The values of each attribute of the nodes.
+    attributes: Vec<Vec<f64>>,
+    /// The boundary marker of each node, empty without markers.
+    markers: Vec<i64>,
+    /// The number of the first node, 0 or 1, by which nodes are numbered in all the files.
+    first: usize,
+}
+
+impl Nodes {
+    fn read(records: &mut Records) -> Result<Self, String> {
+        let [count, dim, num_attributes, num_markers] = records.header()?;
+        if !(2..=3).contains(&dim) {
+            return Err(format!("Nodes of dimension {dim} are not supported"));
+        }
+        let mut nodes = Self {
+            dim,
+            coords: Vec::with_capacity(count * dim),
+            attributes: vec![Vec::with_capacity(count); num_attributes],
+            markers: Vec::new(),
+            first: 0,
+        };
+        for i in 0..count {
+            let record = records.next()?;
+            if i == 0 {
+                nodes.first = records.value(&record, 0)?;
+            }
+            for k in 1..=dim {
+                nodes.coords.push(records.value(&record, k)?);
+            }
+            for (a, column) in nodes.attributes.iter_mut().enumerate() {
+                column.push(records.value(&record, 1 + dim + a)?);
+            }
+            if num_markers > 0 {
+                let marker = records.value(&record, 1 + dim + num_attributes)?;
+                nodes.markers.push(marker);
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn len(&self) -> usize {
+        self.coords.len() / self.dim
+    }
+
+    /// The index of the node numbered by the value at the given position of a record.
+    fn index(&self, records: &Records, record: &[&str], i: usize) -> Result<usize, String> {
+        let number: usize = records.value(record, i)?;
+        number
+            .checked_sub(self.first)
+            .filter(|&index| index < self.len())
+            .ok_or(format!(
+                "Missing node {number} in .{} file",
+                records.extension
+            ))
+    }
+}
+
+/// The cells of an `.ele` file, with their attributes.
+struct Cells {
+    element_type: ElementType,
+    connectivity: Vec<usize>,
+    attributes: Vec<Vec<f64>>,
+}
+
+/// Orders of the nodes of the element types, as written in the files.
+fn file_order(et: ElementType) -> &'static [usize] {
+    match et {
+        // Mid-side nodes are listed in the order of the opposite corners
+        ElementType::TRI6 => &[0, 1, 2, 4, 5, 3],
+        ElementType::TET10 => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        ElementType::TRI3 => &[0, 1, 2],
+        _ => &[0, 1, 2, 3],
+    }
+}
+
+impl Cells {
+    fn read(records: &mut Records, nodes: &Nodes) -> Result<Self, String> {
+        let [count, nodes_per_cell, num_attributes] = records.header()?;
+        let element_type = match (nodes.dim, nodes_per_cell) {
+            (2, 3) => ElementType::TRI3,
+            (2, 6) => ElementType::TRI6,
+            (3, 4) => ElementType::TET4,
+            (3, 10) => ElementType::TET10,
+            (dim, n) => return Err(format!("Cells of {n} nodes in {dim}D are not supported")),
+        };
+        let mut connectivity = vec![0; count * nodes_per_cell];
+        let mut attributes = vec![Vec::with_capacity(count); num_attributes];
+        for cell in connectivity.chunks_mut(nodes_per_cell) {
+            let record = records.next()?;
+            for (k, &local) in file_order(element_type).iter().enumerate() {
+                cell[local] = nodes.index(records, &record, 1 + k)?;
+            }
+            for (a, column) in attributes.iter_mut().enumerate() {
+                column.push(records.value(&record, 1 + nodes_per_cell + a)?);
+            }
+        }
+        Ok(Self {
+            element_type,
+            connectivity,
+            attributes,
+        })
+    }
+}
+
+/// The connectivity, offsets and markers of the boundary elements of a type.
+type BoundaryBlock = (Vec<usize>, Vec<usize>, Vec<i64>);
+
+/// The boundary elements of each type, with their markers.
+#[derive(Default)]
+struct Boundary {
+    blocks: BTreeMap<ElementType, BoundaryBlock>,
+}
+
+impl Boundary {
+    fn push(&mut self, nodes: Vec<usize>, marker: i64) {
+        let et = match nodes.len() {
+            1 => ElementType::VERTEX,
+            2 => ElementType::SEG2,
+            3 => ElementType::TRI3,
+            4 => ElementType::QUAD4,
+            _ => ElementType::PGON,
+        };
+        let (connectivity, offsets, markers) = self.blocks.entry(et).or_default();
+        connectivity.extend(nodes);
+        offsets.push(connectivity.len());
+        markers.push(marker);
+    }
+
+    /// Reads the edges of an `.edge` file, the faces of a `.face` file or the segments of a
+    /// `.poly` file.
+    fn read(&mut self, records: &mut Records, nodes: &Nodes, size: usize) -> Result<(), String> {
+        let [count, num_markers] = records.header()?;
+        for _ in 0..count {
+            let record = records.next()?;
+            let element = (1..=size)
+                .map(|k| nodes.index(records, &record, k))
+                .collect::<Result<_, _>>()?;
+            let marker = match num_markers {
+                0 => 0,
+                _ => records.value(&record, 1 + size)?,
+            };
+            self.push(element, marker);
+        }
+        Ok(())
+    }
+
+    /// Reads the facets of a 3D `.poly` file, each polygon of a facet being an element.
+    fn read_facets(&mut self, records: &mut Records, nodes: &Nodes) -> Result<(), String> {
+        let [count, num_markers] = records.header()?;
+        for _ in 0..count {
+            let record = records.next()?;
+            let num_polygons: usize = records.value(&record, 0)?;
+            let num_holes: usize = match record.len() {
+                1 => 0,
+                _ => records.value(&record, 1)?,
+            };
+            let marker = match num_markers {
+                0 => 0,
+                _ => records.value(&record, 2)?,
+            };
+            for _ in 0..num_polygons {
+                let polygon = records.next()?;
+                let num_corners: usize = records.value(&polygon, 0)?;
+                let element = (1..=num_corners)
+                    .map(|k| nodes.index(records, &polygon, k))
+                    .collect::<Result<_, _>>()?;
+                self.push(element, marker);
+            }
+            for _ in 0..num_holes {
+                records.next()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The fields `attribute_0`, `attribute_1`, etc. of the columns of attributes.
+fn attributes_fields(attributes: Vec<Vec<f64>>) -> BTreeMap<String, nd::ArcArray<f64, nd::IxDyn>> {
+    attributes
+        .into_iter()
+        .enumerate()
+        .map(|(a, column)| {
+            let values = nd::Array1::from(column).into_dyn().into_shared();
+            (format!("attribute_{a}"), values)
+        })
+        .collect()
+}
+
+/// Builds the mesh of the content of the files.
+fn build(nodes: Nodes, cells: Option<Cells>, boundary: Boundary) -> UMesh {
+    let (num_nodes, dim) = (nodes.len(), nodes.dim);
+    let coords = nd::Array2::from_shape_vec((num_nodes, dim), nodes.coords).unwrap();
+    let mut mesh = UMesh::new(coords.into_shared());
+    if let Some(cells) = cells {
+        let et = cells.element_type;
+        let size = et.num_nodes().unwrap();
+        let shape = (cells.connectivity.len() / size, size);
+        let connectivity = nd::Array2::from_shape_vec(shape, cells.connectivity).unwrap();
+        let fields = attributes_fields(cells.attributes);
+        mesh.add_regular_block(et, connectivity.into_shared(), Some(fields));
+    }
+    if !nodes.attributes.is_empty() {
+        let vertices = nd::Array2::from_shape_fn((num_nodes, 1), |(i, _)| i);
+        let fields = attributes_fields(nodes.attributes);
+        mesh.add_regular_block(ElementType::VERTEX, vertices.into_shared(), Some(fields));
+    }
+
+    let markers: BTreeSet<i64> = boundary
+        .blocks
+        .values()
+        .flat_map(|(_, _, markers)| markers.iter().copied())
+        .collect();
+    let family: BTreeMap<i64, usize> = markers.iter().enumerate().map(|(f, &m)| (m, f)).collect();
+    for (et, (connectivity, offsets, markers)) in boundary.blocks {
+        match et.num_nodes() {
+            Some(size) => {
+                let shape = (offsets.len(), size);
+                let connectivity = nd::Array2::from_shape_vec(shape, connectivity).unwrap();
+                mesh.add_regular_block(et, connectivity.into_shared(), None);
+            }
+            None => mesh.add_poly_block(et, connectivity.into(), offsets.into()),
+        }
+        let block = mesh.element_blocks.get_mut(&et).unwrap();
+        block.families = markers.iter().map(|m| family[m]).collect::<Vec<_>>().into();
+        for &marker in markers.iter().filter(|&&m| m != 0) {
+            let name = format!("marker_{marker}");
+            block
+                .groups
+                .entry(name)
+                .or_default()
+                .insert(family[&marker]);
+        }
+    }
+
+    let mut node_groups: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (node, &marker) in nodes.markers.iter().enumerate() {
+        if marker != 0 {
+            node_groups.entry(marker).or_default().push(node);
+        }
+    }
+    for (marker, group) in node_groups {
+        mesh.set_node_group(&format!("marker_{marker}"), group);
+    }
+    mesh
+}
+
+/// Reads a mesh from Triangle or TetGen files.
+///
+/// From a `.poly` file, the nodes and the segments or facets are read. From a `.node` or `.ele`
+/// file, the nodes, the cells and the boundary elements of the `.edge` or `.face` file are read,
+/// each file being optional except the `.node` file.
+pub fn read(path: &Path) -> Result<UMesh, Box<dyn Error>> {
+    trace::span!("read_tetgen");
+    let read_file = |extension: &str| {
+        let path = path.with_extension(extension);
+        std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {path:?}: {e}"))
+    };
+    let node_text = read_file("node");
+    let mut boundary = Boundary::default();
+    if path.extension().is_some_and(|ext| ext == "poly") {
+        let text = read_file("poly")?;
+        let mut records = Records::new("poly", &text);
+        let mut nodes = Nodes::read(&mut records)?;
+        if nodes.coords.is_empty() {
+            nodes = Nodes::read(&mut Records::new("node", &node_text?))?;
+        }
+        match nodes.dim {
+            2 => boundary.read(&mut records, &nodes, 2)?,
+            _ => boundary.read_facets(&mut records, &nodes)?,
+        }
+        return Ok(build(nodes, None, boundary));
+    }
+
+    let nodes = Nodes::read(&mut Records::new("node", &node_text?))?;
+    let cells = match read_file("ele") {
+        Ok(text) => Some(Cells::read(&mut Records::new("ele", &text), &nodes)?),
+        Err(_) => None,
+    };
+    let (extension, size) = match nodes.dim {
+        2 => ("edge", 2),
+        _ => ("face", 3),
+    };
+    if let Ok(text) = read_file(extension) {
+        boundary.read(&mut Records::new(extension, &text), &nodes, size)?;
+    }
+    trace::debug!(nodes = nodes.len(), "triangle/tetgen files read");
+    Ok(build(nodes, cells, boundary))
+}
+
+/// The boundary marker of an element or a node, from the first of its groups named after a
+/// marker, or 0.
+fn marker<'a>(groups: impl IntoIterator<Item = &'a String>) -> i64 {
+    groups
+        .into_iter()
+        .find_map(|g| g.strip_prefix("marker_")?.parse().ok())
+        .unwrap_or(0)
+}
+
+/// The scalar fields of the VERTEX block holding one element per node in node order, written as
+/// node attributes.
+fn node_attributes<'a>(mesh: &'a UMeshView) -> Vec<nd::ArrayView1<'a, f64>> {
+    let Some(block) = mesh.block(ElementType::VERTEX) else {
+        return Vec::new();
+    };
+    let per_node = block.len() == mesh.coords().nrows()
+        && block.connectivity.iter().enumerate().all(|(i, n)| n == [i]);
+    if !per_node {
+        return Vec::new();
+    }
+    block
+        .fields
+        .values()
+        .filter_map(|f| f.view().into_dimensionality::<nd::Ix1>().ok())
+        .collect()
+}
+
+/// Writes the node section of a `.node` or `.poly` file, numbering nodes from 1.
+fn write_nodes(text: &mut String, mesh: &UMeshView) {
+    let coords = mesh.coords();
+    let attributes = node_attributes(mesh);
+    let mut markers = vec![0; coords.nrows()];
+    let mut has_markers = false;
+    for name in mesh.node_group_names() {
+        let value = marker([name]);
+        if value != 0 {
+            has_markers = true;
+            for &node in mesh.node_group(name).unwrap() {
+                if markers[node] == 0 {
+                    markers[node] = value;
+                }
+            }
+        }
+    }
+    let _ = writeln!(
+        text,
+        "{} {} {} {}",
+        coords.nrows(),
+        coords.ncols(),
+        attributes.len(),
+        usize::from(has_markers)
+    );
+    for (i, row) in coords.rows().into_iter().enumerate() {
+        let _ = write!(text, "{}", i + 1);
+        for x in row {
+            let _ = write!(text, " {x}");
+        }
+        for attribute in &attributes {
+            let _ = write!(text, " {}", attribute[i]);
+        }
+        if has_markers {
+            let _ = write!(text, " {}", markers[i]);
+        }
+        text.push('\n');
+    }
+}
+
+/// Writes the boundary elements (the elements of the dimension below the space dimension) of a
+/// mesh as edges, faces, segments or facets, with their markers.
+fn write_boundary(text: &mut String, mesh: &UMeshView, facets: bool) -> Result<(), String> {
+    let dim = Dimension::try_from(mesh.space_dimension() - 1).unwrap();
+    let mut elements = Vec::new();
+    for element in mesh.elements_of_dim(dim) {
+        let et = element.element_type();
+        let supported = match dim {
+            Dimension::D1 => et == ElementType::SEG2,
+            _ => facets || et == ElementType::TRI3,
+        };
+        if !supported {
+            return Err(format!(
+                "{et:?} elements cannot be written in a TetGen file"
+            ));
+        }
+        elements.push((element.connectivity().to_vec(), marker(element.groups())));
+    }
+    let _ = writeln!(text, "{} 1", elements.len());
+    for (i, (nodes, value)) in elements.iter().enumerate() {
+        let numbers: Vec<String> = nodes.iter().map(|n| (n + 1).to_string()).collect();
+        let numbers = numbers.join(" ");
+        if facets {
+            let _ = writeln!(text, "1 0 {value}\n{} {numbers}", nodes.len());
+        } else {
+            let _ = writeln!(text, "{} {numbers} {value}", i + 1);
+        }
+    }
+    Ok(())
+}
+
+/// Writes the cells (the elements of the space dimension) of a mesh as an `.ele` file, with their
+/// scalar fields as attributes.
+fn write_cells(text: &mut String, mesh: &UMeshView) -> Result<bool, String> {
+    let dim = Dimension::try_from(mesh.space_dimension()).unwrap();
+    let mut blocks = mesh.blocks().filter(|(et, _)| et.dimension() == dim);
+    let Some((&et, block)) = blocks.next() else {
+        return Ok(false);
+    };
+    if !matches!(
+        et,
+        ElementType::TRI3 | ElementType::TRI6 | ElementType::TET4 | ElementType::TET10
+    ) || blocks.next().is_some()
+    {
+        return Err("Only cells of a single simplex type can be written in an .ele file".into());
+    }
+    let attributes: Vec<_> = block
+        .fields
+        .values()
+        .filter_map(|f| f.view().into_dimensionality::<nd::Ix1>().ok())
+        .collect();
+    let size = et.num_nodes().unwrap();
+    let _ = writeln!(text, "{} {size} {}", block.len(), attributes.len());
+    for (i, nodes) in block.connectivity.iter().enumerate() {
+        let _ = write!(text, "{}", i + 1);
+        for &local in file_order(et) {
+            let _ = write!(text, " {}", nodes[local] + 1);
+        }
+        for attribute in &attributes {
+            let _ = write!(text, " {}", attribute[i]);
+        }
+        text.push('\n');
+    }
+    Ok(true)
+}
+
+/// Writes a mesh as Triangle or TetGen files.
+///
+/// To a `.poly` file, the nodes and the boundary elements are written. Otherwise, the nodes are
+/// written in a `.node` file, the cells in an `.ele` file and the boundary elements in an
+/// `.edge` or `.face` file, when the mesh has such elements. Boundary markers are taken from
+/// the groups named `marker_1`, `marker_2`, etc.
+pub fn write(path: &Path, mesh: UMeshView) -> Result<(), Box<dyn Error>> {
+    trace::span!("write_tetgen");
+    let dim = mesh.space_dimension();
+    if !(2..=3).contains(&dim) {
+        return Err(format!("Meshes in {dim}D cannot be written in TetGen files").into());
+    }
+    let mut files = Vec::new();
+    let mut nodes = String::new();
+    write_nodes(&mut nodes, &mesh);
+    if path.extension().is_some_and(|ext| ext == "poly") {
+        write_boundary(&mut nodes, &mesh, dim == 3)?;
+        // No holes
+        nodes.push_str("0\n");
+        files.push(("poly", nodes));
+    } else {
+        files.push(("node", nodes));
+        let mut cells = String::new();
+        if write_cells(&mut cells, &mesh)? {
+            files.push(("ele", cells));
+        }
+        let boundary_dim = Dimension::try_from(dim - 1).unwrap();
+        if mesh.elements_of_dim(boundary_dim).next().is_some() {
+            let mut boundary = String::new();
+            write_boundary(&mut boundary, &mesh, false)?;
+            files.push((if dim == 2 { "edge" } else { "face" }, boundary));
+        }
+    }
+    for (extension, text) in files {
+        std::fs::write(path.with_extension(extension), text)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: &str = "# square with a center node
+5 2 1 1
+1 0.0 0.0 10.0 1
+2 1.0 0.0 10.0 1
+3 1.0 1.0 10.0 2
+4 0.0 1.0 10.0 1
+5 0.5 0.5 10.0 0
+";
+
+    const ELE: &str = "4 3 1
+1 1 2 5 1.0
+2 2 3 5 1.0
+3 3 4 5 2.0 # comment
+4 4 1 5 2.0
+";
+
+    #[test]
+    fn test_read_2d() {
+        let nodes = Nodes::read(&mut Records::new("node", NODE)).unwrap();
+        assert_eq!(nodes.first, 1);
+        assert_eq!(nodes.markers, vec![1, 1, 2, 1, 0]);
+        assert_eq!(nodes.attributes, vec![vec![10.0; 5]]);
+        let cells = Cells::read(&mut Records::new("ele", ELE), &nodes).unwrap();
+        let mut boundary = Boundary::default();
+        let edges = "2 1\n1 1 2 3\n2 3 4 0\n";
+        boundary
+            .read(&mut Records::new("edge", edges), &nodes, 2)
+            .unwrap();
+        let mut missing_node = Records::new("ele", "1 3 0\n1 1 2 9\n");
+        assert!(Cells::read(&mut missing_node, &nodes).is_err());
+        let mut quads = Records::new("ele", "1 4 0\n1 1 2 3 4\n");
+        assert!(Cells::read(&mut quads, &nodes).is_err());
+        let mesh = build(nodes, Some(cells), boundary);
+
+        let tri = mesh.regular_connectivity(ElementType::TRI3).unwrap();
+        assert_eq!(tri.row(2).to_vec(), vec![2, 3, 4]);
+        let attribute = &mesh.block(ElementType::TRI3).unwrap().fields["attribute_0"];
+        assert_eq!(attribute.as_slice().unwrap(), &[1.0, 1.0, 2.0, 2.0]);
+        assert_eq!(mesh.group_names(), vec!["marker_3"]);
+        assert_eq!(mesh.group_as_element_ids("marker_3").len(), 1);
+        assert_eq!(mesh.node_group("marker_2").unwrap().len(), 1);
+        assert_eq!(mesh.node_group("marker_1").unwrap().len(), 3);
+        let vertices = mesh.block(ElementType::VERTEX).unwrap();
+        assert_eq!(vertices.len(), 5);
+        assert_eq!(vertices.fields["attribute_0"].sum(), 50.0);
+    }
+
+    #[test]
+    fn test_read_poly_3d() {
+        let poly = "4 3 0 0
+0 0 0 0
+1 1 0 0
+2 0 1 0
+3 0 0 1
+2 1
+1 0 5
+3 0 2 1
+2 0 0
+4 0 1 3 2 # quadrilateral facet
+3 1 2 3
+0
+";
+        let mut records = Records::new("poly", poly);
+        let nodes = Nodes::read(&mut records).unwrap();
+        assert_eq!(nodes.first, 0);
+        let mut boundary = Boundary::default();
+        boundary.read_facets(&mut records, &nodes).unwrap();
+        let mesh = build(nodes, None, boundary);
+        assert_eq!(mesh.num_elements(), 3);
+        assert_eq!(mesh.block(ElementType::QUAD4).unwrap().len(), 1);
+        assert_eq!(mesh.group_names(), vec!["marker_5"]);
+
+        // A facet marker must be given when the facets have markers
+        let mut records = Records::new("poly", poly);
+        let nodes = Nodes::read(&mut records).unwrap();
+        let mut facets = Records::new("poly", "1 1\n1\n3 0 1 2\n");
+        let read = Boundary::default().read_facets(&mut facets, &nodes);
+        assert!(read.is_err());
+    }
+
+    #[test]
+    fn test_write_read() {
+        let dir = std::env::temp_dir().join("mefikit_tetgen_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let nodes = Nodes::read(&mut Records::new("node", NODE)).unwrap();
+        let cells = Cells::read(&mut Records::new("ele", ELE), &nodes).unwrap();
+        let mut boundary = Boundary::default();
+        boundary.push(vec![0, 1], 4);
+        let mesh = build(nodes, Some(cells), boundary);
+
+        let path = dir.join("square.1.node");
+        write(&path, mesh.view()).unwrap();
+        assert!(dir.join("square.1.edge").exists());
+        let read_mesh = read(&dir.join("square.1.ele")).unwrap();
+        assert_eq!(read_mesh.coords(), mesh.coords());
+        assert_eq!(
+            read_mesh.regular_connectivity(ElementType::TRI3).unwrap(),
+            mesh.regular_connectivity(ElementType::TRI3).unwrap()
+        );
+        assert_eq!(read_mesh.group_names(), vec!["marker_4"]);
+        assert_eq!(
+            read_mesh.node_group("marker_2"),
+            mesh.node_group("marker_2")
+        );
+        let field = |m: &UMesh| m.block(ElementType::TRI3).unwrap().fields["attribute_0"].clone();
+        assert_eq!(field(&read_mesh), field(&mesh));
+        let node_field =
+            |m: &UMesh| m.block(ElementType::VERTEX).unwrap().fields["attribute_0"].clone();
+        assert_eq!(node_field(&read_mesh), node_field(&mesh));
+
+        let poly = dir.join("square.poly");
+        write(&poly, mesh.view()).unwrap();
+        let read_poly = read(&poly).unwrap();
+        assert_eq!(read_poly.block(ElementType::SEG2).unwrap().len(), 1);
+        assert_eq!(read_poly.group_names(), vec!["marker_4"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,435 @@
+//! Dependency free export of a mesh as a VTU document, usable without file access.
+//!
+//! Data arrays are written in ASCII, or as appended raw binary data compressed by blocks as
+//! VTK does. Compression needs the `io` feature.
+
+use std::fmt::Write;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::options::{VtkCompression, VtkEncoding, VtkWriteOptions, dense_fields};
+use crate::mesh::{ElementLike, ElementType, UMeshView};
+
+/// Size of the blocks of data compressed independently, the default of VTK.
+const COMPRESSION_BLOCK: usize = 1 << 15;
+
+fn vtk_cell_type(et: ElementType) -> Result<u8, String> {
+    use ElementType::*;
+    match et {
+        VERTEX => Ok(1),
+        SEG2 => Ok(3),
+        TRI3 => Ok(5),
+        PGON => Ok(7),
+        QUAD4 => Ok(9),
+        TET4 => Ok(10),
+        HEX8 => Ok(12),
+        PHED => Ok(42),
+        other => Err(format!("Unsupported element type for VTU: {other:?}")),
+    }
+}
+
+/// The element type of a VTK cell type, the inverse of [`vtk_cell_type`].
+#[cfg(feature = "mmap")]
+pub(super) fn element_type(cell_type: u8) -> Result<ElementType, String> {
+    use ElementType::*;
+    [VERTEX, SEG2, TRI3, PGON, QUAD4, TET4, HEX8, PHED]
+        .into_iter()
+        .find(|&et| vtk_cell_type(et) == Ok(cell_type))
+        .ok_or(format!("Unsupported VTK cell type: {cell_type}"))
+}
+
+/// A value type of the VTK data arrays.
+trait VtkScalar: Copy + std::fmt::Display {
+    /// The name of the type in VTK files.
+    const NAME: &'static str;
+
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>);
+}
+
+macro_rules! vtk_scalar {
+    ($t:ty, $name:literal) => {
+        impl VtkScalar for $t {
+            const NAME: &'static str = $name;
+
+            fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+    };
+}
+
+vtk_scalar!(f64, "Float64");
+vtk_scalar!(i64, "Int64");
+vtk_scalar!(u8, "UInt8");
+
+/// Compresses one block of data.
+#[cfg(feature = "io")]
+fn compress(block: &[u8], compression: VtkCompression) -> Result<Vec<u8>, String> {
+    use std::io::Write as _;
+    match compression {
+        VtkCompression::Zlib(level) => {
+            let level = flate2::Compression::new(u32::from(level.min(9)));
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(block).map_err(|e| e.to_string())?;
+            encoder.finish().map_err(|e| e.to_string())
+        }
+        VtkCompression::Lz4 => Ok(lz4_flex::block::compress(block)),
+    }
+}
+
+/// Compresses one block of data.
+#[cfg(not(feature = "io"))]
+fn compress(_block: &[u8], _compression: VtkCompression) -> Result<Vec<u8>, String> {
+    Err("Compressed VTU files need mefikit to be built with the `io` feature".to_owned())
+}
+
+/// Appends the bytes of a data array to the appended data, after a header of UInt64 sizes.
+///
+/// Without compression, the header is the number of bytes. With compression, the data is split
+/// in blocks compressed in parallel with the `rayon` feature, and the header holds the number of
+/// blocks, the size of a block, the size of the last block if it is partial (0 otherwise) and the
+/// compressed size of each block.
+fn append_array(
+    appended: &mut Vec<u8>,
+    raw: &[u8],
+    compression: Option<VtkCompression>,
+) -> Result<(), String> {
+    let Some(compression) = compression else {
+        appended.extend_from_slice(&(raw.len() as u64).to_le_bytes());
+        appended.extend_from_slice(raw);
+        return Ok(());
+    };
+    #[cfg(feature = "rayon")]
+    let blocks: Vec<Result<Vec<u8>, String>> = raw
+        .par_chunks(COMPRESSION_BLOCK)
+        .map(|b| compress(b, compression))
+        .collect();
+    #[cfg(not(feature = "rayon"))]
+    let blocks: Vec<Result<Vec<u8>, String>> = raw
+        .chunks(COMPRESSION_BLOCK)
+        .map(|b| compress(b, compression))
+        .collect();
+    let blocks: Vec<Vec<u8>> = blocks.into_iter().collect::<Result<_, _>>()?;
+    let header = [
+        blocks.len(),
+        COMPRESSION_BLOCK,
+        raw.len() % COMPRESSION_BLOCK,
+    ];
+    for size in header.into_iter().chain(blocks.iter().map(Vec::len)) {
+        appended.extend_from_slice(&(size as u64).to_le_bytes());
+    }
+    blocks.iter().for_each(|b| appended.extend_from_slice(b));
+    Ok(())
+}
+
+/// Builds the XML part of a VTU document and its appended data.
+struct VtuWriter<'a> {
+    options: &'a VtkWriteOptions,
+    xml: String,
+    appended: Vec<u8>,
+}
+
+impl VtuWriter<'_> {
+    fn write_array<T: VtkScalar>(
+        &mut self,
+        name: Option<&str>,
+        num_components: usize,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<(), String> {
+        let name = name.map_or(String::new(), |n| format!(r#" Name="{n}""#));
+        let _ = write!(
+            self.xml,
+            r#"<DataArray type="{}"{name} NumberOfComponents="{num_components}" "#,
+            T::NAME
+        );
+        match self.options.encoding {
+            VtkEncoding::Ascii => {
+                self.xml.push_str(r#"format="ascii">"#);
+                for (i, v) in values.into_iter().enumerate() {
+                    let sep = if i == 0 { "" } else { " " };
+                    let _ = write!(self.xml, "{sep}{v}");
+                }
+                self.xml.push_str("</DataArray>\n");
+            }
+            VtkEncoding::Appended => {
+                if self.options.compression.is_none() {
+                    // Aligned on 8 bytes so that the data can be mapped in memory without copy
+                    // (see `MappedVtu`)
+                    self.appended
+                        .resize(self.appended.len().next_multiple_of(8), 0);
+                }
+                let offset = self.appended.len();
+                let _ = writeln!(self.xml, r#"format="appended" offset="{offset}"/>"#);
+                let mut raw = Vec::new();
+                values.into_iter().for_each(|v| v.extend_le_bytes(&mut raw));
+                append_array(&mut self.appended, &raw, self.options.compression)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the float fields defined on all the element blocks with the same shape, exported as
+/// cell data, with their number of components.
+pub(super) fn cell_fields<'a>(mesh: &'a UMeshView) -> Vec<(&'a str, usize)> {
+    let blocks: Vec<_> = mesh.blocks().map(|(_, b)| b).collect();
+    let Some(first) = blocks.first() else {
+        return Vec::new();
+    };
+    first
+        .fields
+        .iter()
+        .filter(|(name, field)| {
+            let shape = &field.shape()[1..];
+            blocks.iter().all(|b| {
+                b.fields
+                    .get(*name)
+                    .is_some_and(|f| &f.shape()[1..] == shape)
+            })
+        })
+        .map(|(name, field)| (name.as_str(), field.shape()[1..].iter().product()))
+        .collect()
+}
+
+/// Exports a mesh as an ASCII VTU (VTK XML unstructured grid) document.
+///
+/// Float fields defined on all the element blocks are exported as cell data. Unlike the VTK
+/// writer of the `io` feature, it needs no file system, which makes it usable in WASM.
+///
+/// # Errors
+/// Returns an error if the mesh has an element type without VTK equivalent.
+pub fn to_vtu_string(mesh: UMeshView) -> Result<String, String> {
+    let vtu = to_vtu_bytes(mesh, &VtkWriteOptions::new())?;
+    Ok(String::from_utf8(vtu).expect("ASCII VTU documents are valid UTF-8"))
+}
+
+/// Exports a mesh as a VTU (VTK XML unstructured grid) document, encoded as requested by the
+/// options.
+///
+/// Float fields defined on all the element blocks are exported as cell data, sparse fields being
+/// filled with the fill value of the options out of their selection. Appended data arrays are
+/// written in the raw binary encoding of VTK with UInt64 headers, so the document is
+/// not valid UTF-8.
+///
+/// # Errors
+/// Returns an error if the mesh has an element type without VTK equivalent, or if compression
+/// is requested for ASCII data arrays or without the `io` feature.
+pub fn to_vtu_bytes(mesh: UMeshView, options: &VtkWriteOptions) -> Result<Vec<u8>, String> {
+    if options.compression.is_some() && options.encoding == VtkEncoding::Ascii {
+        return Err("ASCII data arrays cannot be compressed".to_owned());
+    }
+    if let Some(dense) = dense_fields(&mesh, options.sparse_fill()) {
+        return to_vtu_bytes(dense.view(), options);
+    }
+    let coords = mesh.coords();
+    let dim = coords.ncols();
+    let num_cells = mesh.num_elements();
+
+    let mut connectivity: Vec<i64> = Vec::new();
+    let mut offsets: Vec<i64> = Vec::with_capacity(num_cells);
+    let mut types = Vec::with_capacity(num_cells);
+    // polyhedra are described by their faces
+    let mut faces: Vec<i64> = Vec::new();
+    let mut face_offsets: Vec<i64> = Vec::with_capacity(num_cells);
+    let has_polyhedra = mesh.block(ElementType::PHED).is_some();
+    for e in mesh.elements() {
+        types.push(vtk_cell_type(e.element_type())?);
+        let conn = e.connectivity();
+        if e.element_type() == ElementType::PHED {
+            let face_list: Vec<&[usize]> = conn.split(|&n| n == usize::MAX).collect();
+            let mut nodes: Vec<usize> = conn.iter().copied().filter(|&n| n != usize::MAX).collect();
+            nodes.sort_unstable();
+            nodes.dedup();
+            connectivity.extend(nodes.iter().map(|&n| n as i64));
+            faces.push(face_list.len() as i64);
+            for face in face_list {
+                faces.push(face.len() as i64);
+                faces.extend(face.iter().map(|&n| n as i64));
+            }
+            face_offsets.push(faces.len() as i64);
+        } else {
+            connectivity.extend(conn.iter().map(|&n| n as i64));
+            face_offsets.push(-1);
+        }
+        offsets.push(connectivity.len() as i64);
+    }
+
+    let mut header = String::from(
+        "<?xml version=\"1.0\"?>\n<VTKFile type=\"UnstructuredGrid\" version=\"1.0\" \
+         byte_order=\"LittleEndian\"",
+    );
+    if options.encoding == VtkEncoding::Appended {
+        header.push_str(r#" header_type="UInt64""#);
+    }
+    match options.compression {
+        Some(VtkCompression::Zlib(_)) => header.push_str(r#" compressor="vtkZLibDataCompressor""#),
+        Some(VtkCompression::Lz4) => header.push_str(r#" compressor="vtkLZ4DataCompressor""#),
+        None => {}
+    }
+    header.push_str(">\n<UnstructuredGrid>\n");
+    let mut out = VtuWriter {
+        options,
+        xml: header,
+        appended: Vec::new(),
+    };
+    let _ = writeln!(
+        out.xml,
+        r#"<Piece NumberOfPoints="{}" NumberOfCells="{num_cells}">"#,
+        coords.nrows()
+    );
+    out.xml.push_str("<Points>\n");
+    let points = coords
+        .outer_iter()
+        .flat_map(|x| (0..3).map(move |i| if i < dim { x[i] } else { 0.0 }));
+    out.write_array(None, 3, points)?;
+    out.xml.push_str("</Points>\n<Cells>\n");
+    out.write_array(Some("connectivity"), 1, connectivity)?;
+    out.write_array(Some("offsets"), 1, offsets)?;
+    out.write_array(Some("types"), 1, types)?;
+    if has_polyhedra {
+        out.write_array(Some("faces"), 1, faces)?;
+        out.write_array(Some("faceoffsets"), 1, face_offsets)?;
+    }
+    out.xml.push_str("</Cells>\n");
+
+    let fields = cell_fields(&mesh);
+    if !fields.is_empty() {
+        out.xml.push_str("<CellData>\n");
+        for (name, num_components) in fields {
+            let values = mesh
+                .blocks()
+                .flat_map(|(_, b)| b.fields[name].iter().copied());
+            out.write_array(Some(name), num_components, values)?;
+        }
+        out.xml.push_str("</CellData>\n");
+    }
+    out.xml.push_str("</Piece>\n</UnstructuredGrid>\n");
+    let mut vtu = out.xml.into_bytes();
+    if options.encoding == VtkEncoding::Appended {
+        // The appended data starts on 8 bytes, as its arrays
+        let tag = b"<AppendedData encoding=\"raw\">\n_";
+        let padding = (vtu.len() + tag.len()).next_multiple_of(8) - vtu.len() - tag.len();
+        vtu.resize(vtu.len() + padding, b' ');
+        vtu.extend_from_slice(tag);
+        vtu.extend_from_slice(&out.appended);
+        vtu.extend_from_slice(b"\n</AppendedData>\n");
+    }
+    vtu.extend_from_slice(b"</VTKFile>\n");
+    Ok(vtu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::SparseField;
+    use crate::mesh_examples as me;
+    use ndarray as nd;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_to_vtu_string() {
+        let mut mesh = me::make_imesh_2d(2);
+        let values = ndarray::Array1::from_iter((0..4).map(f64::from)).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let vtu = to_vtu_string(mesh.view()).unwrap();
+        assert!(vtu.contains(r#"<Piece NumberOfPoints="9" NumberOfCells="4">"#));
+        assert!(vtu.contains(r#"Name="types" NumberOfComponents="1" format="ascii">9 9 9 9<"#));
+        assert!(vtu.contains(r#"Name="f" NumberOfComponents="1" format="ascii">0 1 2 3<"#));
+        assert!(!vtu.contains("faces"));
+    }
+
+    #[test]
+    fn test_to_vtu_sparse_field() {
+        let mut mesh = me::make_imesh_2d(2);
+        let ids = BTreeMap::from([(ElementType::QUAD4, vec![1, 2])]);
+        let values = nd::arr1(&[1.0, 2.0]).into_dyn().into_shared();
+        let sensor = SparseField::new(ids, BTreeMap::from([(ElementType::QUAD4, values)]));
+        mesh.update_sparse_field("sensor", sensor);
+        let options = VtkWriteOptions::new().fill_value(-1.0);
+        let vtu = String::from_utf8(to_vtu_bytes(mesh.view(), &options).unwrap()).unwrap();
+        assert!(vtu.contains(r#"Name="sensor" NumberOfComponents="1" format="ascii">-1 1 2 -1<"#));
+        assert!(mesh.sparse_field("sensor", None).is_some());
+    }
+
+    /// Returns the appended data of a VTU document and the offset of each data array in it.
+    fn appended_arrays(vtu: &[u8]) -> (&[u8], Vec<usize>) {
+        let start = vtu.windows(2).position(|w| w == b"\n_").unwrap() + 2;
+        let xml = String::from_utf8_lossy(&vtu[..start]);
+        let offsets = xml
+            .split(r#"offset=""#)
+            .skip(1)
+            .map(|s| s.split('"').next().unwrap().parse().unwrap())
+            .collect();
+        (&vtu[start..], offsets)
+    }
+
+    fn read_u64(bytes: &[u8], i: usize) -> usize {
+        u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_to_vtu_bytes_appended() {
+        let mesh = me::make_imesh_2d(2);
+        let options = VtkWriteOptions::new().encoding(VtkEncoding::Appended);
+        let vtu = to_vtu_bytes(mesh.view(), &options).unwrap();
+        let text = String::from_utf8_lossy(&vtu);
+        assert!(text.contains(r#"header_type="UInt64""#));
+        assert!(text.contains(r#"NumberOfComponents="3" format="appended" offset="0"/>"#));
+        let (appended, offsets) = appended_arrays(&vtu);
+        assert_eq!((vtu.len() - appended.len()) % 8, 0);
+        assert_eq!(offsets.len(), 4);
+        // 9 points of 3 Float64
+        assert_eq!(read_u64(appended, 0), 9 * 3 * 8);
+        assert_eq!(offsets[1], 8 + 9 * 3 * 8);
+        let x1 = f64::from_le_bytes(appended[8 + 24..8 + 32].try_into().unwrap());
+        assert_eq!(x1, 0.5);
+        // types of the 4 QUAD4
+        assert_eq!(read_u64(appended, offsets[3] / 8), 4);
+        assert_eq!(&appended[offsets[3] + 8..offsets[3] + 12], &[9, 9, 9, 9]);
+
+        let compressed = VtkWriteOptions::new()
+            .compression(VtkCompression::Lz4)
+            .encoding(VtkEncoding::Ascii);
+        assert!(to_vtu_bytes(mesh.view(), &compressed).is_err());
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn test_to_vtu_bytes_compressed() {
+        use std::io::Read;
+
+        let mesh = me::make_imesh_3d(20);
+        let raw_options = VtkWriteOptions::new().encoding(VtkEncoding::Appended);
+        let raw = to_vtu_bytes(mesh.view(), &raw_options).unwrap();
+        let points: Vec<u8> = mesh.coords().iter().flat_map(|x| x.to_le_bytes()).collect();
+        assert!(points.len() > COMPRESSION_BLOCK);
+        let n_blocks = points.len().div_ceil(COMPRESSION_BLOCK);
+
+        for compression in [VtkCompression::Zlib(6), VtkCompression::Lz4] {
+            let options = VtkWriteOptions::new().compression(compression);
+            let vtu = to_vtu_bytes(mesh.view(), &options).unwrap();
+            assert!(vtu.len() < raw.len());
+            let (appended, _) = appended_arrays(&vtu);
+            assert_eq!(read_u64(appended, 0), n_blocks);
+            assert_eq!(read_u64(appended, 1), COMPRESSION_BLOCK);
+            assert_eq!(read_u64(appended, 2), points.len() % COMPRESSION_BLOCK);
+            // The first block of the points
+            let start = 8 * (3 + n_blocks);
+            let block = &appended[start..start + read_u64(appended, 3)];
+            let decompressed = match compression {
+                VtkCompression::Zlib(_) => {
+                    let mut out = Vec::new();
+                    flate2::read::ZlibDecoder::new(block)
+                        .read_to_end(&mut out)
+                        .unwrap();
+                    out
+                }
+                VtkCompression::Lz4 => {
+                    lz4_flex::block::decompress(block, COMPRESSION_BLOCK).unwrap()
+                }
+            };
+            assert_eq!(decompressed, points[..COMPRESSION_BLOCK]);
+        }
+    }
+}
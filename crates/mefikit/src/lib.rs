@@ -10,15 +10,50 @@
 //!
 //! - [`mesh`] - Core mesh data structures (`UMesh`, `UMeshView`, element blocks)
 //! - [`element_traits`] - Geometric and topological operations on elements
+//! - [`geometry`] - Reference elements, quadrature rules and geometric predicates
+//! - [`topology`] - Subentity numbering, subdivision templates and node numbering conventions
+//!   of the element types
+//! - [`builders`] - Parametric meshes of common shapes
 //! - [`tools`] - Mesh algorithms (selection, cracking, extrusion, etc.)
 //! - [`io`] - File I/O for various mesh formats
+//! - [`stream`] - Chunked processing of huge meshes
+//! - `testing` - Example and random meshes for tests, with the `testing` feature
+//!
+//! ## Features
+//!
+//! - `io` (default) - VTK file formats, and compression of VTU files
+//! - `hdf5` (default) - VTKHDF file format, through the HDF5 C library. Without it and `io`,
+//!   mefikit builds for `wasm32-unknown-unknown`
+//! - `mmap` - Memory-mapped reading of large VTU files without copy, see `prelude::MappedVtu`
+//! - `arrow` - Conversion to and from Arrow record batches, see the `arrow` module
+//! - `parquet` - Parquet export of the fields, implies `arrow`
+//! - `rayon` - Parallel versions of the algorithms
+//! - `exact` - Exact geometric predicates with rational arithmetic
+//! - `testing` - Mesh generators and proptest strategies to fuzz algorithms, see the `testing`
+//!   module
+//! - `tracing` - Debug spans and events around the phases of the algorithms, collected with the
+//!   `tracing` crate
 
+/// This module converts coordinates, connectivities and fields to and from Arrow record
+/// batches, for exchange with polars, pandas or columnar file formats.
+#[cfg(feature = "arrow")]
+pub mod arrow;
+/// This module provides builders of parametric meshes (disk, cylinder, sphere, etc.) and of
+/// implicit surfaces and voxel images.
+pub mod builders;
 /// This module defines geometrical operations on elements.
 ///
 /// The operations are provided through the `ElementGeo` trait.
 pub mod element_traits;
+/// This module defines reference elements and Gauss quadrature rules.
+///
+/// It also provides projections of fields located at integration points to cells or nodes.
+pub mod geometry;
 /// This module defines a `read` and a `write` functions that can use various mesh formats
 mod io;
+/// This module converts meshes to and from the memory layout of MEDCoupling, for in-memory
+/// exchange with Salome.
+pub mod medcoupling;
 /// This module serves as the **central container** for all mesh-related data and logic in the
 /// library.
 ///
@@ -40,10 +75,11 @@ mod io;
 ///
 /// Schematically, a `UMesh` consists of:
 ///
-/// ```rust
+/// ```ignore
 /// pub struct UMesh {
 ///     coords: ArcArray2<f64>,
-///     element_blocks: BTreeMap<ElementType, ElementBlock>
+///     element_blocks: BTreeMap<ElementType, ElementBlock>,
+///     node_groups: BTreeMap<String, BTreeSet<usize>>,
 /// }
 /// ```
 ///
@@ -67,23 +103,32 @@ mod io;
 /// - **Fields** associated with the elements (e.g., temperature, velocity)
 /// - **Groups** associated with the elements (through families)
 ///
-/// ```rust
+/// ```ignore
 /// pub struct ElementBlock {
 ///     pub element_type: ElementType,
 ///     pub connectivity: Connectivity,
 ///     pub fields: BTreeMap<String, ArrayD<f64>>,
+///     pub field_locations: BTreeMap<String, FieldLocation>,
+///     pub typed_fields: BTreeMap<String, FieldData>,
+///     pub sparse_fields: BTreeMap<String, SparseData>,
 ///     families: Vec<usize>,
 ///     pub groups: BTreeMap<String, BTreeSet<usize>>,
 /// }
 /// ```
 ///
-/// Field data is stored as `ndarray::ArrayD<f64>`, and identified by a naming
-/// convention supporting time-dependent fields, e.g.:
+/// Field data is stored as `ndarray::ArrayD<f64>`, with one value per element unless its
+/// `FieldLocation` says otherwise (element nodes or integration points). Integer, boolean and
+/// categorical (string labels such as material names) data are stored aside as `FieldData`.
+/// Fields defined only on a subset of the elements are stored as `SparseData`. Fields are
+/// identified by a naming convention supporting time-dependent fields, e.g.:
 ///
-/// ```
+/// ```text
 /// "temperature_iter_3_time_0.01"
 /// ```
 ///
+/// Node groups (named sets of node indices, often used for boundary conditions) are stored at
+/// the mesh level and follow node merging and renumbering.
+///
 /// Group data is stored as families groups. Families always form a partition of
 /// the elements based on the groups (ie two element not pertaining to the same
 /// groups have a different family id).
@@ -147,7 +192,8 @@ mod io;
 /// `UMeshView` is a **zero-copy, non-owning** view over existing mesh data. It
 /// holds references (slices or `ndarray::ArrayView`) to continuous memory block
 /// that is managed elsewhere (e.g., passed from a C/C++/Python array or owned
-/// by a Rust UMesh).
+/// by a Rust UMesh). Foreign arrays are wrapped with `UMeshView::from_slices`,
+/// or `UMeshView::from_raw_parts` when they come as raw pointers.
 ///
 /// This is ideal for:
 /// - Foreign function interface (FFI)
@@ -161,12 +207,23 @@ mod io;
 ///
 /// ---
 ///
+/// ### ✏️ `UMeshViewMut<'a>` – Mutable Mesh View
+///
+/// `UMeshViewMut` is a **zero-copy, non-owning** view whose coordinates and
+/// fields are borrowed mutably, while connectivities and families stay
+/// read-only. It lets mefikit modify coordinates and field values owned by a
+/// caller (e.g. a Python array) in place, without changing the mesh topology.
+/// It is obtained with `UMesh::view_mut()` or built from foreign arrays.
+///
+/// ---
+///
 /// ### 🔄 Summary
 ///
 /// | Type           | Ownership | Mutable | Use Case                                 | Copies |
 /// |----------------|-----------|---------|------------------------------------------|--------|
 /// | `UMesh`        | Yes       | Yes     | Full ownership, long-term usage          | Yes    |
 /// | `UMeshView`    | No        | No      | Read-only access to foreign/borrowed data| No     |
+/// | `UMeshViewMut` | No        | Partly  | In-place edition of coordinates, fields  | No     |
 ///
 /// This model ensures performance, safety, and clear interoperability boundaries.
 ///
@@ -181,9 +238,23 @@ mod io;
 /// require the array to diverge.
 /// To ensure safety and performance, coordinates data are wrapped into an ArcArray2
 /// when owned.
-/// A more fine-grained copy-on-write mechanism could be implemented in the future if
-/// needed. It could for example allow in-place shared mutability of the coordinates when the user
-/// really knows what he is doing.
+/// `UMesh::coords_mut()` gives a mutable view of the coordinates, copying them first only if they
+/// are shared with another mesh. `UMesh::is_shared()` and `UMesh::make_unique()` allow to check
+/// for and trigger this copy explicitly, so that in-place transformations never copy silently.
+///
+/// ## Cached Derived Data
+///
+/// Element bounding boxes, the node to elements map, the element locator and the element graph
+/// are computed on first request and cached in the mesh (see `MeshCache`). Clones and views share
+/// the cache, and the methods of `UMesh` modifying coordinates or connectivities drop it.
+///
+/// ## Deterministic Results
+///
+/// Algorithms give the same output from run to run, whatever the number of threads used with the
+/// `rayon` feature: parallel reductions keep the order of the elements, outputs built from hash
+/// maps are sorted, and hash maps use the seedless `FxHash` hasher. Parallel versions of the
+/// algorithms, e.g. `par_compute_neighbours`, give the result of the serial ones, so output meshes
+/// can be compared exactly in tests.
 ///
 /// ---
 ///
@@ -239,19 +310,40 @@ mod io;
 /// - `io` — file import/export (serde_json, serde_yaml, MED, CGNS, etc.)
 pub mod mesh;
 #[cfg(test)]
-pub mod mesh_examples;
+use testing as mesh_examples;
+/// This module reads and processes meshes chunk by chunk, to handle meshes that do not fit in
+/// memory.
+pub mod stream;
+/// This module generates example and random meshes, and proptest strategies, to test algorithms
+/// on meshes.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 /// This module groups all tools/algorithms operating on one or more meshes.
 ///
 /// Most of the algorithms take a &UMesh when using optimizations (sharing coordinates) or a
 /// UMeshView when not needed and produce a new owned UMesh.
 pub mod tools;
+/// This module defines the local numbering of the subentities of the element types, their
+/// subdivision templates and the node numbering conventions of the mesh formats.
+pub mod topology;
+/// This module instruments the algorithms when the `tracing` feature is enabled.
+mod trace;
 
 pub mod prelude {
     pub use crate::element_traits::{ElementGeo, ElementTopo};
-    pub use crate::io::{read, write};
+    #[cfg(feature = "mmap")]
+    pub use crate::io::MappedVtu;
+    pub use crate::io::{
+        Capabilities, Format, FormatRegistry, MeshFormat, ReadOptions, ReportKind, VtkCompression,
+        VtkEncoding, VtkWriteOptions, WriteOptions, export_report, read, read_with,
+        register_format, to_vtu_bytes, to_vtu_string, write, write_pvtu, write_pvtu_by_field,
+        write_vtu, write_with,
+    };
     pub use crate::mesh::{
         Connectivity, Dimension, Element, ElementId, ElementIds, ElementLike, ElementMut,
-        ElementType, FieldOwned, FieldOwnedD, Regularity, UMesh, UMeshBase, UMeshView,
+        ElementType, FieldData, FieldLocation, FieldOwned, FieldOwnedD, MeshSummary, Regularity,
+        SparseField, UMesh, UMeshBase, UMeshView, UMeshViewMut,
     };
     pub use crate::tools::*;
+    pub use crate::topology::{Convention, convert_convention};
 }
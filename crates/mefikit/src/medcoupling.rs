@@ -0,0 +1,412 @@
+//! In-memory exchange of meshes with MEDCoupling, the mesh library of Salome.
+//!
+//! A [`McMesh`] holds the arrays of a `MEDFileUMesh`: the coordinates (a `DataArrayDouble`), one
+//! `MEDCouplingUMesh` per dimension, given by its nodal connectivity and connectivity index, and
+//! the family arrays. Building the MEDCoupling objects from these arrays, or the arrays from the
+//! objects, is left to the caller (the Python package does it), so that no file is needed.
+//!
+//! Connectivities are renumbered between the VTK numbering of mefikit and the MED numbering,
+//! whose tetrahedra and hexahedra have the opposite orientation.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ndarray as nd;
+
+use crate::mesh::{ElementType, UMesh};
+use crate::topology::conventions::{Convention, permutation};
+
+/// Returns the MEDCoupling type tag (`INTERP_KERNEL::NormalizedCellType`) of an element type.
+pub fn mc_cell_type(et: ElementType) -> Option<i64> {
+    use ElementType::*;
+    match et {
+        VERTEX => Some(0),
+        SEG2 => Some(1),
+        SEG3 => Some(2),
+        SEG4 => Some(10),
+        SPLINE => Some(33),
+        TRI3 => Some(3),
+        TRI6 => Some(6),
+        TRI7 => Some(7),
+        QUAD4 => Some(4),
+        QUAD8 => Some(8),
+        QUAD9 => Some(9),
+        PGON => Some(5),
+        TET4 => Some(14),
+        TET10 => Some(20),
+        HEX8 => Some(18),
+        HEX21 => None,
+        PHED => Some(31),
+    }
+}
+
+/// Returns the element type of a MEDCoupling type tag.
+pub fn from_mc_cell_type(tag: i64) -> Option<ElementType> {
+    use ElementType::*;
+    [
+        VERTEX, SEG2, SEG3, SEG4, SPLINE, TRI3, TRI6, TRI7, QUAD4, QUAD8, QUAD9, PGON, TET4, TET10,
+        HEX8, PHED,
+    ]
+    .into_iter()
+    .find(|&et| mc_cell_type(et) == Some(tag))
+}
+
+/// The cells of one dimension, laid out as a `MEDCouplingUMesh`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct McLevel {
+    pub mesh_dimension: usize,
+    /// Type tag of each cell followed by its nodes, the faces of polyhedra being separated by
+    /// `-1`.
+    pub connectivity: Vec<i64>,
+    /// Start of each cell in `connectivity`, followed by the length of `connectivity`.
+    pub connectivity_index: Vec<i64>,
+    /// MED family of each cell, zero or negative.
+    pub families: Vec<i64>,
+    /// Float fields on the cells, one row per cell as in a `DataArrayDouble`.
+    pub fields: BTreeMap<String, nd::Array2<f64>>,
+}
+
+impl McLevel {
+    pub fn num_cells(&self) -> usize {
+        self.connectivity_index.len().saturating_sub(1)
+    }
+}
+
+/// A mesh laid out as a `MEDFileUMesh`.
+///
+/// MED families are numbered over the whole mesh, negative for cells and positive for nodes, `0`
+/// being the family of the entities in no group.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct McMesh {
+    pub coords: nd::Array2<f64>,
+    /// Levels by decreasing dimension, the first one being the level 0 of MED.
+    pub levels: Vec<McLevel>,
+    /// MED family of each node, zero or positive.
+    pub node_families: Vec<i64>,
+    /// MED families of each group, negative ones holding cells and positive ones nodes.
+    pub groups: BTreeMap<String, BTreeSet<i64>>,
+}
+
+/// The connectivity, offsets and level indices of the cells of a type.
+type CellBlock = (Vec<usize>, Vec<usize>, Vec<usize>);
+
+/// Gives the same family to the entities belonging to the same groups.
+struct FamilyNumbering {
+    ids: BTreeMap<Vec<String>, i64>,
+    sign: i64,
+}
+
+impl FamilyNumbering {
+    fn new(sign: i64) -> Self {
+        let mut ids = BTreeMap::new();
+        ids.insert(Vec::new(), 0);
+        Self { ids, sign }
+    }
+
+    fn id(&mut self, groups: Vec<String>) -> i64 {
+        let next = self.sign * self.ids.len() as i64;
+        *self.ids.entry(groups).or_insert(next)
+    }
+
+    fn add_groups(self, groups: &mut BTreeMap<String, BTreeSet<i64>>) {
+        for (names, id) in self.ids {
+            for name in names {
+                groups.entry(name).or_default().insert(id);
+            }
+        }
+    }
+}
+
+impl McMesh {
+    /// Lays out a mesh as MEDCoupling does, its element blocks being gathered by dimension.
+    ///
+    /// Cells are ordered by element type, then by index in their block. Fields are exported on
+    /// the levels where at least one block holds them, with NaN on the other blocks, and are
+    /// flattened to one row per cell.
+    ///
+    /// # Errors
+    /// Returns an error if the mesh holds elements without MEDCoupling equivalent (HEX21), or if
+    /// a field has a different number of components on two blocks of a level.
+    pub fn from_umesh(mesh: &UMesh) -> Result<Self, String> {
+        let mut cell_families = FamilyNumbering::new(-1);
+        let mut levels: BTreeMap<u8, McLevel> = BTreeMap::new();
+        for (&et, block) in mesh.blocks() {
+            let tag = mc_cell_type(et)
+                .ok_or_else(|| format!("No MEDCoupling equivalent to {et:?} elements"))?;
+            let dim = u8::from(et.dimension());
+            let level = levels.entry(dim).or_insert_with(|| McLevel {
+                mesh_dimension: dim as usize,
+                connectivity_index: vec![0],
+                ..Default::default()
+            });
+            let first_cell = level.num_cells();
+            let order = permutation(et, Convention::Vtk, Convention::Med);
+            for cell in block.connectivity.iter() {
+                level.connectivity.push(tag);
+                level.connectivity.extend((0..cell.len()).map(|i| {
+                    match cell[order.as_ref().map_or(i, |order| order[i])] {
+                        usize::MAX => -1,
+                        n => n as i64,
+                    }
+                }));
+                level
+                    .connectivity_index
+                    .push(level.connectivity.len() as i64);
+            }
+            let mut by_family: BTreeMap<usize, i64> = BTreeMap::new();
+            for &f in block.families.iter() {
+                let id = *by_family.entry(f).or_insert_with(|| {
+                    let groups = block
+                        .groups
+                        .iter()
+                        .filter(|(_, fams)| fams.contains(&f))
+                        .map(|(g, _)| g.clone())
+                        .collect();
+                    cell_families.id(groups)
+                });
+                level.families.push(id);
+            }
+
+            let num_cells = level.num_cells();
+            for (name, values) in &block.fields {
+                let num_components: usize = values.shape()[1..].iter().product();
+                let field = level.fields.entry(name.clone()).or_insert_with(|| {
+                    nd::Array2::from_elem((first_cell, num_components), f64::NAN)
+                });
+                if field.ncols() != num_components {
+                    return Err(format!(
+                        "Field {name} has different numbers of components on the {dim}D blocks"
+                    ));
+                }
+                let values = values.to_shape((block.len(), num_components)).unwrap();
+                field.append(nd::Axis(0), values.view()).unwrap();
+            }
+            // fields of the previous blocks missing on this one
+            for field in level.fields.values_mut() {
+                let nan =
+                    nd::Array2::from_elem((num_cells - field.nrows(), field.ncols()), f64::NAN);
+                field.append(nd::Axis(0), nan.view()).unwrap();
+            }
+        }
+
+        let mut groups = BTreeMap::new();
+        cell_families.add_groups(&mut groups);
+        let mut node_families = FamilyNumbering::new(1);
+        let mut node_groups: Vec<Vec<String>> = vec![Vec::new(); mesh.coords().nrows()];
+        for name in mesh.node_group_names() {
+            for &n in mesh.node_group(name).unwrap() {
+                node_groups[n].push(name.clone());
+            }
+        }
+        let node_families_array = node_groups
+            .into_iter()
+            .map(|g| node_families.id(g))
+            .collect();
+        node_families.add_groups(&mut groups);
+
+        Ok(Self {
+            coords: mesh.coords().to_owned(),
+            levels: levels.into_values().rev().collect(),
+            node_families: node_families_array,
+            groups,
+        })
+    }
+
+    /// Builds a mesh from the MEDCoupling layout.
+    ///
+    /// The cells of each level are gathered in blocks by element type, keeping their relative
+    /// order. MED families become the element families, with their sign dropped, and node groups
+    /// are built from the positive families of the groups. Empty `families` or `node_families`
+    /// arrays stand for family `0` everywhere.
+    ///
+    /// # Errors
+    /// Returns an error if the arrays are inconsistent: invalid index, unknown type tag, node out
+    /// of range, or a cell with a wrong number of nodes.
+    pub fn to_umesh(&self) -> Result<UMesh, String> {
+        let num_nodes = self.coords.nrows();
+        let mut mesh = UMesh::new(self.coords.to_shared());
+        let mut dimensions = BTreeSet::new();
+        for level in &self.levels {
+            if !dimensions.insert(level.mesh_dimension) {
+                return Err(format!(
+                    "Several levels of dimension {}",
+                    level.mesh_dimension
+                ));
+            }
+            let num_cells = level.num_cells();
+            let index = &level.connectivity_index;
+            let valid_index = index.first().is_none_or(|&i| i == 0)
+                && index.windows(2).all(|w| w[0] < w[1])
+                && index.last().map_or(0, |&i| i as usize) == level.connectivity.len();
+            if !valid_index {
+                return Err("Invalid connectivity index".to_owned());
+            }
+            if !level.families.is_empty() && level.families.len() != num_cells {
+                return Err(format!("Expected {num_cells} cell families"));
+            }
+            if let Some((name, _)) = level.fields.iter().find(|(_, f)| f.nrows() != num_cells) {
+                return Err(format!("Expected {num_cells} values of field {name}"));
+            }
+
+            let mut blocks: BTreeMap<ElementType, CellBlock> = BTreeMap::new();
+            for (i, w) in index.windows(2).enumerate() {
+                let cell = &level.connectivity[w[0] as usize..w[1] as usize];
+                let et = from_mc_cell_type(cell[0])
+                    .ok_or_else(|| format!("Unsupported MEDCoupling cell type {}", cell[0]))?;
+                let separator = et == ElementType::PHED;
+                let nodes = cell[1..]
+                    .iter()
+                    .map(|&n| match n {
+                        -1 if separator => Ok(usize::MAX),
+                        n if n >= 0 && (n as usize) < num_nodes => Ok(n as usize),
+                        n => Err(format!("Node {n} of cell {i} out of range")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if et.num_nodes().is_some_and(|n| n != nodes.len()) {
+                    return Err(format!("Cell {i} of type {et:?} has {} nodes", nodes.len()));
+                }
+                let (conn, offsets, cells) = blocks.entry(et).or_default();
+                conn.extend(nodes);
+                offsets.push(conn.len());
+                cells.push(i);
+            }
+
+            for (et, (conn, offsets, cells)) in blocks {
+                match et.num_nodes() {
+                    Some(n) => {
+                        let mut conn = nd::Array2::from_shape_vec((cells.len(), n), conn).unwrap();
+                        if let Some(order) = permutation(et, Convention::Med, Convention::Vtk) {
+                            conn = conn.select(nd::Axis(1), &order);
+                        }
+                        mesh.add_regular_block(et, conn.into_shared(), None);
+                    }
+                    None => mesh.add_poly_block(et, conn.into(), offsets.into()),
+                }
+                for (name, values) in &level.fields {
+                    let values = values.select(nd::Axis(0), &cells);
+                    let values = match values.ncols() {
+                        1 => values.remove_axis(nd::Axis(1)).into_dyn(),
+                        _ => values.into_dyn(),
+                    };
+                    mesh.add_field(et, name, values.into_shared());
+                }
+                if level.families.is_empty() {
+                    continue;
+                }
+                let block = mesh.element_blocks.get_mut(&et).unwrap();
+                let families: Vec<i64> = cells.iter().map(|&i| level.families[i]).collect();
+                block.families = families.iter().map(|f| f.unsigned_abs() as usize).collect();
+                let present: BTreeSet<i64> = families.into_iter().collect();
+                for (name, ids) in &self.groups {
+                    let fams: BTreeSet<usize> = ids
+                        .intersection(&present)
+                        .map(|f| f.unsigned_abs() as usize)
+                        .collect();
+                    if !fams.is_empty() {
+                        block.groups.insert(name.clone(), fams);
+                    }
+                }
+            }
+        }
+
+        if !self.node_families.is_empty() {
+            if self.node_families.len() != num_nodes {
+                return Err(format!("Expected {num_nodes} node families"));
+            }
+            for (name, ids) in &self.groups {
+                let nodes: Vec<usize> = (0..num_nodes)
+                    .filter(|&n| self.node_families[n] > 0 && ids.contains(&self.node_families[n]))
+                    .collect();
+                if !nodes.is_empty() {
+                    mesh.set_node_group(name, nodes);
+                }
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementIds, ElementLike};
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_from_umesh() {
+        let mut mesh = me::make_mesh_2d_multi();
+        let values = nd::arr1(&[1.0]).into_dyn().into_shared();
+        mesh.add_field(ElementType::QUAD4, "f", values);
+        mesh.set_group(
+            "quad",
+            &ElementIds::from(BTreeMap::from([(ElementType::QUAD4, vec![0])])),
+        );
+        mesh.set_node_group("corner", [4]);
+
+        let mc = McMesh::from_umesh(&mesh).unwrap();
+        assert_eq!(mc.levels.len(), 2);
+        let cells = &mc.levels[0];
+        assert_eq!(cells.mesh_dimension, 2);
+        assert_eq!(cells.connectivity, [4, 0, 1, 3, 2, 5, 0, 1, 4, 3, 2]);
+        assert_eq!(cells.connectivity_index, [0, 5, 11]);
+        assert_eq!(cells.families, [-1, 0]);
+        assert_eq!(cells.fields["f"][[0, 0]], 1.0);
+        assert!(cells.fields["f"][[1, 0]].is_nan());
+        assert_eq!(mc.levels[1].connectivity, [1, 0, 1, 1, 1, 3]);
+        assert_eq!(mc.node_families, [0, 0, 0, 0, 1]);
+        assert_eq!(mc.groups["quad"], BTreeSet::from([-1]));
+        assert_eq!(mc.groups["corner"], BTreeSet::from([1]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut mesh = me::make_mesh_2d_multi();
+        mesh.set_group(
+            "quad",
+            &ElementIds::from(BTreeMap::from([(ElementType::QUAD4, vec![0])])),
+        );
+        mesh.set_node_group("corner", [4]);
+        let back = McMesh::from_umesh(&mesh).unwrap().to_umesh().unwrap();
+        assert_eq!(back.num_elements(), mesh.num_elements());
+        let types: Vec<_> = back.element_types().copied().collect();
+        assert_eq!(
+            types,
+            [ElementType::SEG2, ElementType::QUAD4, ElementType::PGON]
+        );
+        let pgon = back.elements().last().unwrap();
+        assert_eq!(pgon.connectivity(), &[0, 1, 4, 3, 2]);
+        assert_eq!(back.group_as_element_ids("quad").len(), 1);
+        assert_eq!(back.node_group("corner"), Some(&BTreeSet::from([4])));
+    }
+
+    #[test]
+    fn test_med_numbering() {
+        let mesh = me::make_imesh_3d(1);
+        let hex = mesh.regular_connectivity(ElementType::HEX8).unwrap();
+        let mc = McMesh::from_umesh(&mesh).unwrap();
+        let corners: Vec<i64> = [0, 3, 2, 1].iter().map(|&i| hex[[0, i]] as i64).collect();
+        assert_eq!(mc.levels[0].connectivity[1..5], corners[..]);
+        let back = mc.to_umesh().unwrap();
+        assert_eq!(back.regular_connectivity(ElementType::HEX8).unwrap(), hex);
+    }
+
+    #[test]
+    fn test_to_umesh_errors() {
+        let mc = McMesh {
+            coords: nd::Array2::zeros((3, 2)),
+            levels: vec![McLevel {
+                mesh_dimension: 2,
+                connectivity: vec![3, 0, 1, 3],
+                connectivity_index: vec![0, 4],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(mc.to_umesh().is_err());
+        let mut wrong_type = mc.clone();
+        wrong_type.levels[0].connectivity = vec![4, 0, 1, 2];
+        assert!(wrong_type.to_umesh().is_err());
+        let mut valid = mc;
+        valid.levels[0].connectivity = vec![3, 0, 1, 2];
+        assert_eq!(valid.to_umesh().unwrap().num_elements(), 1);
+    }
+}
@@ -0,0 +1,386 @@
+//! Bitset masks of selected elements, one bitset per element block.
+//!
+//! [`BlockMask`] is the dense counterpart of [`ElementIds`] and [`ElementIdsSet`]: each block
+//! uses one bit per element whatever the number of selected elements, and boolean operations
+//! work on whole words. It is used by the selection engine, where most selections keep a large
+//! part of the mesh.
+//!
+//! [`ElementIdsSet`]: super::ElementIdsSet
+
+use std::collections::BTreeMap;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Not, SubAssign};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use super::element::{ElementId, ElementType};
+use super::element_ids::ElementIds;
+
+const WORD: usize = u64::BITS as usize;
+
+/// A fixed size bitset over the elements of one block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bits {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl Bits {
+    fn new(len: usize, value: bool) -> Self {
+        let fill = if value { u64::MAX } else { 0 };
+        let mut bits = Self {
+            len,
+            words: vec![fill; len.div_ceil(WORD)],
+        };
+        bits.clear_tail();
+        bits
+    }
+
+    /// Clears the bits past `len` in the last word, so that counts and comparisons hold.
+    fn clear_tail(&mut self) {
+        if let Some(last) = self.words.last_mut()
+            && !self.len.is_multiple_of(WORD)
+        {
+            *last &= (1 << (self.len % WORD)) - 1;
+        }
+    }
+
+    fn get(&self, index: usize) -> bool {
+        index < self.len && self.words[index / WORD] & (1 << (index % WORD)) != 0
+    }
+
+    fn set(&mut self, index: usize, value: bool) {
+        let mask = 1 << (index % WORD);
+        match value {
+            true => self.words[index / WORD] |= mask,
+            false => self.words[index / WORD] &= !mask,
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    i * WORD + bit
+                })
+            })
+        })
+    }
+
+    /// Clears the set bits of `word` (the word at position `i`) for which `f` is `false`.
+    fn filter_word<F: Fn(usize) -> bool>(i: usize, word: &mut u64, f: &F) {
+        let mut rest = *word;
+        while rest != 0 {
+            let bit = rest.trailing_zeros() as usize;
+            rest &= rest - 1;
+            if !f(i * WORD + bit) {
+                *word &= !(1 << bit);
+            }
+        }
+    }
+
+    fn zip_with(&mut self, other: &Self, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.len, other.len,
+            "Masks of one block must have the same size"
+        );
+        for (a, &b) in self.words.iter_mut().zip(&other.words) {
+            *a = op(*a, b);
+        }
+    }
+}
+
+/// Selected elements of a mesh as one bitset per element type.
+///
+/// Each bitset has the size of its block, so a mask is built from the block sizes of a mesh
+/// ([`Self::full`], [`Self::empty`], [`Self::from_ids`]). Boolean operators combine masks of
+/// the same mesh: `&=`, `|=`, `^=` and `-=` with another mask, and `!` complements each block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockMask(BTreeMap<ElementType, Bits>);
+
+impl BlockMask {
+    /// Creates a mask selecting every element of blocks of the given sizes.
+    pub fn full<I: IntoIterator<Item = (ElementType, usize)>>(sizes: I) -> Self {
+        Self(
+            sizes
+                .into_iter()
+                .map(|(et, len)| (et, Bits::new(len, true)))
+                .collect(),
+        )
+    }
+
+    /// Creates a mask selecting no element of blocks of the given sizes.
+    pub fn empty<I: IntoIterator<Item = (ElementType, usize)>>(sizes: I) -> Self {
+        Self(
+            sizes
+                .into_iter()
+                .map(|(et, len)| (et, Bits::new(len, false)))
+                .collect(),
+        )
+    }
+
+    /// Creates a mask of blocks of the given sizes selecting the elements of `ids`.
+    ///
+    /// Ids of types absent from `sizes` or out of their block are ignored.
+    pub fn from_ids<I>(ids: &ElementIds, sizes: I) -> Self
+    where
+        I: IntoIterator<Item = (ElementType, usize)>,
+    {
+        let mut mask = Self::empty(sizes);
+        for (et, bits) in mask.0.iter_mut() {
+            let Some(indices) = ids.get(et) else {
+                continue;
+            };
+            let len = bits.len;
+            for &i in indices.iter().take_while(|&&i| i < len) {
+                bits.set(i, true);
+            }
+        }
+        mask
+    }
+
+    /// Returns the element types of the mask and the size of their block.
+    pub fn sizes(&self) -> impl Iterator<Item = (ElementType, usize)> + '_ {
+        self.0.iter().map(|(&et, bits)| (et, bits.len))
+    }
+
+    /// Returns the element types of the mask.
+    pub fn keys(&self) -> impl Iterator<Item = ElementType> + '_ {
+        self.0.keys().copied()
+    }
+
+    /// Returns `true` if the element is selected.
+    pub fn contains(&self, element_id: ElementId) -> bool {
+        self.0
+            .get(&element_id.element_type())
+            .is_some_and(|bits| bits.get(element_id.index()))
+    }
+
+    /// Selects or deselects an element.
+    ///
+    /// # Panics
+    /// Panics if the element type is not in the mask or the index is out of its block.
+    pub fn set(&mut self, element_id: ElementId, value: bool) {
+        let bits = self
+            .0
+            .get_mut(&element_id.element_type())
+            .expect("Element type not in the mask");
+        assert!(element_id.index() < bits.len, "Element index out of block");
+        bits.set(element_id.index(), value);
+    }
+
+    /// Returns `true` if the mask has a block of the given type.
+    pub fn contains_type(&self, element_type: ElementType) -> bool {
+        self.0.contains_key(&element_type)
+    }
+
+    /// Removes the block of the given type from the mask.
+    pub fn remove_type(&mut self, element_type: ElementType) {
+        self.0.remove(&element_type);
+    }
+
+    /// Keeps only the blocks whose type satisfies `f`.
+    pub fn retain_types<F: FnMut(ElementType) -> bool>(&mut self, mut f: F) {
+        self.0.retain(|&et, _| f(et));
+    }
+
+    /// Returns the number of selected elements.
+    pub fn len(&self) -> usize {
+        self.0.values().map(Bits::count).sum()
+    }
+
+    /// Returns `true` if no element is selected.
+    pub fn is_empty(&self) -> bool {
+        self.0
+            .values()
+            .all(|bits| bits.words.iter().all(|&w| w == 0))
+    }
+
+    /// Iterates over the selected elements, by type then index.
+    pub fn iter(&self) -> impl Iterator<Item = ElementId> + '_ {
+        self.0
+            .iter()
+            .flat_map(|(&et, bits)| bits.ones().map(move |i| ElementId::new(et, i)))
+    }
+
+    /// Deselects the elements for which `f` returns `false`.
+    ///
+    /// Words of each block are filtered in parallel (requires `rayon` feature).
+    #[cfg(feature = "rayon")]
+    pub fn filter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ElementId) -> bool + Sync,
+    {
+        for (&et, bits) in self.0.iter_mut() {
+            let f = |i| f(ElementId::new(et, i));
+            bits.words
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(i, word)| Bits::filter_word(i, word, &f));
+        }
+        self
+    }
+
+    /// Deselects the elements for which `f` returns `false`.
+    #[cfg(not(feature = "rayon"))]
+    pub fn filter<F>(mut self, f: F) -> Self
+    where
+        F: Fn(ElementId) -> bool + Sync,
+    {
+        for (&et, bits) in self.0.iter_mut() {
+            let f = |i| f(ElementId::new(et, i));
+            for (i, word) in bits.words.iter_mut().enumerate() {
+                Bits::filter_word(i, word, &f);
+            }
+        }
+        self
+    }
+}
+
+/// Keeps elements selected in both masks, a block absent from `rhs` being emptied.
+impl BitAndAssign<&BlockMask> for BlockMask {
+    fn bitand_assign(&mut self, rhs: &BlockMask) {
+        for (et, bits) in self.0.iter_mut() {
+            match rhs.0.get(et) {
+                Some(other) => bits.zip_with(other, |a, b| a & b),
+                None => bits.words.fill(0),
+            }
+        }
+    }
+}
+
+/// Selects elements selected in either mask, blocks absent from `self` being copied.
+impl BitOrAssign<&BlockMask> for BlockMask {
+    fn bitor_assign(&mut self, rhs: &BlockMask) {
+        for (et, other) in &rhs.0 {
+            match self.0.get_mut(et) {
+                Some(bits) => bits.zip_with(other, |a, b| a | b),
+                None => {
+                    self.0.insert(*et, other.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Selects elements selected in exactly one mask, blocks absent from `self` being copied.
+impl BitXorAssign<&BlockMask> for BlockMask {
+    fn bitxor_assign(&mut self, rhs: &BlockMask) {
+        for (et, other) in &rhs.0 {
+            match self.0.get_mut(et) {
+                Some(bits) => bits.zip_with(other, |a, b| a ^ b),
+                None => {
+                    self.0.insert(*et, other.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Deselects the elements selected in `rhs`.
+impl SubAssign<&BlockMask> for BlockMask {
+    fn sub_assign(&mut self, rhs: &BlockMask) {
+        for (et, bits) in self.0.iter_mut() {
+            if let Some(other) = rhs.0.get(et) {
+                bits.zip_with(other, |a, b| a & !b);
+            }
+        }
+    }
+}
+
+/// Complements the selection inside each block of the mask.
+impl Not for BlockMask {
+    type Output = BlockMask;
+
+    fn not(mut self) -> Self::Output {
+        for bits in self.0.values_mut() {
+            bits.words.iter_mut().for_each(|w| *w = !*w);
+            bits.clear_tail();
+        }
+        self
+    }
+}
+
+/// Lists the selected elements, dropping the blocks with no selected element.
+impl From<&BlockMask> for ElementIds {
+    fn from(mask: &BlockMask) -> Self {
+        ElementIds(
+            mask.0
+                .iter()
+                .map(|(&et, bits)| (et, bits.ones().collect::<Vec<_>>()))
+                .filter(|(_, indices)| !indices.is_empty())
+                .collect(),
+        )
+    }
+}
+
+impl From<BlockMask> for ElementIds {
+    fn from(mask: BlockMask) -> Self {
+        Self::from(&mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes() -> [(ElementType, usize); 2] {
+        [(ElementType::QUAD4, 70), (ElementType::SEG2, 3)]
+    }
+
+    fn quads(indices: &[usize]) -> BlockMask {
+        let ids = ElementIds::from(BTreeMap::from([(ElementType::QUAD4, indices.to_vec())]));
+        BlockMask::from_ids(&ids, sizes())
+    }
+
+    #[test]
+    fn test_block_mask() {
+        let full = BlockMask::full(sizes());
+        assert_eq!(full.len(), 73);
+        assert!(BlockMask::empty(sizes()).is_empty());
+        assert_eq!(!full.clone(), BlockMask::empty(sizes()));
+
+        let mask = quads(&[0, 64, 69, 100]);
+        assert_eq!(mask.len(), 3);
+        assert!(mask.contains(ElementId::new(ElementType::QUAD4, 64)));
+        assert!(!mask.contains(ElementId::new(ElementType::QUAD4, 100)));
+        let ids = ElementIds::from(&mask);
+        assert_eq!(ids.get(&ElementType::QUAD4), Some(&vec![0, 64, 69]));
+        assert!(!ids.contains_type(ElementType::SEG2));
+
+        let not = !mask.clone();
+        assert_eq!(not.len(), 70);
+        assert!(!not.contains(ElementId::new(ElementType::QUAD4, 69)));
+        assert!(not.contains(ElementId::new(ElementType::SEG2, 2)));
+    }
+
+    #[test]
+    fn test_block_mask_operators() {
+        let mut and = quads(&[0, 1, 65]);
+        and &= &quads(&[1, 65, 66]);
+        assert_eq!(and, quads(&[1, 65]));
+
+        let mut or = quads(&[0, 1]);
+        or |= &quads(&[1, 66]);
+        assert_eq!(or, quads(&[0, 1, 66]));
+
+        let mut xor = quads(&[0, 1]);
+        xor ^= &quads(&[1, 66]);
+        assert_eq!(xor, quads(&[0, 66]));
+
+        let mut diff = quads(&[0, 1, 66]);
+        diff -= &quads(&[1]);
+        assert_eq!(diff, quads(&[0, 66]));
+
+        let filtered = BlockMask::full(sizes()).filter(|eid| eid.index() % 2 == 1);
+        assert_eq!(filtered.len(), 36);
+        assert!(filtered.contains(ElementId::new(ElementType::QUAD4, 69)));
+    }
+}
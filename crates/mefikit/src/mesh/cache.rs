@@ -0,0 +1,159 @@
+//! Cache of the data derived from the geometry and the topology of a mesh.
+//!
+//! Bounding boxes of the elements, the node to elements map, the locator tree, the element
+//! graph and the face index are computed on first use and kept until the coordinates or the
+//! connectivities of the mesh change. The cache is shared by the clones and the views of a mesh, and mesh methods
+//! mutating coordinates or connectivities replace it with an empty one. Code mutating them by
+//! other means must call [`UMeshBase::invalidate_cache`].
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ndarray as nd;
+use once_cell::sync::OnceCell;
+use petgraph::prelude::UnGraphMap;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{AABB, RTree};
+
+use super::element::{ElementId, ElementLike, ElementType};
+use super::umesh::UMeshBase;
+use crate::tools::EntityIndex;
+
+/// The bounding box of an element, as stored in the locator tree.
+pub type ElementBox = GeomWithData<Rectangle<[f64; 3]>, ElementId>;
+
+/// Lazily computed data derived from a mesh.
+#[derive(Default)]
+pub struct MeshCache {
+    bounding_boxes: OnceCell<BTreeMap<ElementType, Vec<AABB<[f64; 3]>>>>,
+    node_to_elements: OnceCell<Vec<Vec<ElementId>>>,
+    locator: OnceCell<RTree<ElementBox>>,
+    element_graph: OnceCell<UnGraphMap<ElementId, ElementId>>,
+    face_index: OnceCell<EntityIndex>,
+}
+
+impl<N, C, F, G> UMeshBase<N, C, F, G>
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    /// Drops the cached derived data, to be called after mutating coordinates or connectivities.
+    pub fn invalidate_cache(&mut self) {
+        self.cache = Arc::default();
+    }
+
+    /// Axis aligned bounding boxes of the elements, per block, in 3D.
+    ///
+    /// Coordinates of 1D and 2D meshes are padded with zeros.
+    pub fn element_bounding_boxes(&self) -> &BTreeMap<ElementType, Vec<AABB<[f64; 3]>>> {
+        self.cache.bounding_boxes.get_or_init(|| {
+            let point = |n: usize| {
+                let mut p = [0.0; 3];
+                for (x, &c) in p.iter_mut().zip(self.coords.row(n)) {
+                    *x = c;
+                }
+                p
+            };
+            self.element_blocks
+                .iter()
+                .map(|(&et, block)| {
+                    let boxes = block
+                        .connectivity
+                        .iter()
+                        .map(|co| {
+                            // Faces of polyhedra are separated by usize::MAX
+                            let nodes = co.iter().filter(|&&n| n != usize::MAX);
+                            AABB::from_points(nodes.map(|&n| point(n)).collect::<Vec<_>>().iter())
+                        })
+                        .collect();
+                    (et, boxes)
+                })
+                .collect()
+        })
+    }
+
+    /// For each node, the elements it belongs to, in the order of the elements.
+    pub fn node_to_elements(&self) -> &[Vec<ElementId>] {
+        self.cache.node_to_elements.get_or_init(|| {
+            let mut res = vec![Vec::new(); self.coords.nrows()];
+            for element in self.elements() {
+                for &n in element.connectivity {
+                    if n != usize::MAX && res[n].last() != Some(&element.id()) {
+                        res[n].push(element.id());
+                    }
+                }
+            }
+            res
+        })
+    }
+
+    /// A tree of the bounding boxes of all the elements, to locate elements near a point or a
+    /// region.
+    pub fn element_locator(&self) -> &RTree<ElementBox> {
+        self.cache.locator.get_or_init(|| {
+            let boxes = self
+                .element_bounding_boxes()
+                .iter()
+                .flat_map(|(&et, boxes)| {
+                    boxes.iter().enumerate().map(move |(i, aabb)| {
+                        GeomWithData::new(Rectangle::from_aabb(*aabb), ElementId::new(et, i))
+                    })
+                })
+                .collect();
+            RTree::bulk_load(boxes)
+        })
+    }
+
+    /// The faces of the cells of highest dimension (edges in 2D), with the cells having them.
+    ///
+    /// See [`crate::tools::build_face_index`].
+    pub fn face_index(&self) -> &EntityIndex {
+        self.cache
+            .face_index
+            .get_or_init(|| crate::tools::build_face_index(self.view()))
+    }
+
+    /// The element to element graph of the cells of highest dimension, elements being linked
+    /// by their shared faces.
+    ///
+    /// See [`crate::tools::compute_neighbours`].
+    pub fn element_graph(&self) -> &UnGraphMap<ElementId, ElementId> {
+        self.cache
+            .element_graph
+            .get_or_init(|| crate::tools::compute_neighbours(self.view(), None, None).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{ElementId, ElementType};
+    use crate::mesh_examples as me;
+    use rstar::AABB;
+
+    #[test]
+    fn test_mesh_cache() {
+        let mut mesh = me::make_mesh_2d_multi();
+        let pgon = ElementId::new(ElementType::PGON, 0);
+        let boxes = mesh.element_bounding_boxes();
+        assert_eq!(boxes[&ElementType::PGON][0].upper(), [1.5, 1.0, 0.0]);
+        assert_eq!(mesh.node_to_elements()[4], vec![pgon]);
+        let located: Vec<ElementId> = mesh
+            .element_locator()
+            .locate_in_envelope_intersecting(&AABB::from_point([1.2, 0.5, 0.0]))
+            .map(|b| b.data)
+            .collect();
+        assert_eq!(located, vec![pgon]);
+        assert_eq!(mesh.view().node_to_elements().len(), 5);
+
+        // Clones share the cache until they are mutated
+        let clone = mesh.clone();
+        mesh.add_element(ElementType::SEG2, &[3, 4], None, None);
+        assert_eq!(mesh.node_to_elements()[4].len(), 2);
+        assert_eq!(clone.node_to_elements()[4].len(), 1);
+
+        let grid = me::make_imesh_2d(2);
+        assert_eq!(grid.element_graph().edge_count(), 4);
+    }
+}
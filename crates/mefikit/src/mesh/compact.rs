@@ -0,0 +1,189 @@
+//! Compact storage of meshes with 32 bits indices.
+//!
+//! Node indices, poly offsets and families are stored on `u32`, which halves the memory used by
+//! connectivities on 64 bits platforms for meshes under 4 billion nodes. Algorithms work on
+//! [`UMesh`] and [`UMeshView`], so a [`CompactUMesh`] is a storage format: it is converted back
+//! before processing. Coordinates and fields are kept as is.
+
+use ndarray as nd;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::connectivity::ConnectivityBase;
+use super::element::ElementType;
+use super::fields::{FieldData, FieldLocation, SparseData};
+use super::umesh::{UMesh, UMeshView};
+
+/// Connectivity of a block with 32 bits node indices.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompactConnectivity {
+    /// One row of nodes per element.
+    Regular(nd::Array2<u32>),
+    /// Nodes of all elements in `data`, `offsets` giving the end of each element.
+    Poly {
+        data: nd::Array1<u32>,
+        offsets: nd::Array1<u32>,
+    },
+}
+
+/// An element block with 32 bits connectivity and families.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactBlock {
+    pub connectivity: CompactConnectivity,
+    pub fields: BTreeMap<String, nd::ArcArray<f64, nd::IxDyn>>,
+    #[serde(default)]
+    pub field_locations: BTreeMap<String, FieldLocation>,
+    pub typed_fields: BTreeMap<String, FieldData>,
+    pub sparse_fields: BTreeMap<String, SparseData>,
+    pub families: nd::Array1<u32>,
+    pub groups: BTreeMap<String, BTreeSet<usize>>,
+}
+
+/// An unstructured mesh whose indices are stored on 32 bits.
+///
+/// It is built from any mesh with [`TryFrom`], and converted back to a [`UMesh`] with [`From`].
+/// The face separator of polyhedra (`usize::MAX`) is stored as `u32::MAX`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactUMesh {
+    pub coords: nd::ArcArray2<f64>,
+    pub blocks: BTreeMap<ElementType, CompactBlock>,
+    pub node_groups: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl CompactUMesh {
+    /// Returns the total number of elements.
+    pub fn num_elements(&self) -> usize {
+        self.blocks
+            .values()
+            .map(|b| match &b.connectivity {
+                CompactConnectivity::Regular(conn) => conn.nrows(),
+                CompactConnectivity::Poly { offsets, .. } => offsets.len(),
+            })
+            .sum()
+    }
+}
+
+fn compact(index: usize) -> Result<u32, String> {
+    match u32::try_from(index) {
+        _ if index == usize::MAX => Ok(u32::MAX),
+        Ok(i) if i != u32::MAX => Ok(i),
+        _ => Err(format!("Index {index} does not fit on 32 bits")),
+    }
+}
+
+fn expand(index: u32) -> usize {
+    match index {
+        u32::MAX => usize::MAX,
+        i => i as usize,
+    }
+}
+
+fn compact_array<D: nd::Dimension>(
+    array: nd::ArrayView<'_, usize, D>,
+) -> Result<nd::Array<u32, D>, String> {
+    let values = array
+        .iter()
+        .map(|&i| compact(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(nd::Array::from_shape_vec(array.raw_dim(), values).unwrap())
+}
+
+impl TryFrom<UMeshView<'_>> for CompactUMesh {
+    type Error = String;
+
+    /// Compacts a mesh, failing if one of its indices does not fit on 32 bits.
+    fn try_from(mesh: UMeshView<'_>) -> Result<Self, Self::Error> {
+        let blocks = mesh
+            .element_blocks
+            .iter()
+            .map(|(&et, block)| {
+                let connectivity = match &block.connectivity {
+                    ConnectivityBase::Regular(conn) => {
+                        CompactConnectivity::Regular(compact_array(conn.view())?)
+                    }
+                    ConnectivityBase::Poly(conn) => CompactConnectivity::Poly {
+                        data: compact_array(conn.data.view())?,
+                        offsets: compact_array(conn.offsets.view())?,
+                    },
+                };
+                let compact_block = CompactBlock {
+                    connectivity,
+                    fields: block
+                        .fields
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.to_shared()))
+                        .collect(),
+                    field_locations: block.field_locations.clone(),
+                    typed_fields: block.typed_fields.clone(),
+                    sparse_fields: block.sparse_fields.clone(),
+                    families: compact_array(block.families.view())?,
+                    groups: block.groups.clone(),
+                };
+                Ok((et, compact_block))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            coords: mesh.coords.to_shared(),
+            blocks,
+            node_groups: mesh.node_groups.clone(),
+        })
+    }
+}
+
+impl From<&CompactUMesh> for UMesh {
+    fn from(compact: &CompactUMesh) -> Self {
+        let mut mesh = UMesh::new(compact.coords.clone());
+        for (&et, block) in &compact.blocks {
+            match &block.connectivity {
+                CompactConnectivity::Regular(conn) => {
+                    mesh.add_regular_block(et, conn.mapv(expand).into_shared(), None)
+                }
+                CompactConnectivity::Poly { data, offsets } => mesh.add_poly_block(
+                    et,
+                    data.mapv(expand).into_shared(),
+                    offsets.mapv(expand).into_shared(),
+                ),
+            }
+            let mesh_block = mesh.element_blocks.get_mut(&et).unwrap();
+            mesh_block.fields = block.fields.clone();
+            mesh_block.field_locations = block.field_locations.clone();
+            mesh_block.typed_fields = block.typed_fields.clone();
+            mesh_block.sparse_fields = block.sparse_fields.clone();
+            mesh_block.families = block.families.mapv(expand).into_shared();
+            mesh_block.groups = block.groups.clone();
+        }
+        mesh.node_groups = compact.node_groups.clone();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_compact_roundtrip() {
+        let mut mesh = me::make_mesh_2d_multi();
+        mesh.add_element(
+            ElementType::PHED,
+            &[0, 1, 2, usize::MAX, 0, 1, 4, usize::MAX],
+            Some(3),
+            None,
+        );
+        mesh.set_node_group("n", [4]);
+        let compact = CompactUMesh::try_from(mesh.view()).unwrap();
+        assert_eq!(compact.num_elements(), 5);
+        let CompactConnectivity::Poly { data, .. } =
+            &compact.blocks[&ElementType::PHED].connectivity
+        else {
+            panic!("PHED should have a poly connectivity");
+        };
+        assert_eq!(data[3], u32::MAX);
+        assert_eq!(UMesh::from(&compact), mesh);
+
+        let mut large = UMesh::new(mesh.coords.clone());
+        large.add_element(ElementType::SEG2, &[0, 1 << 32], None, None);
+        assert!(CompactUMesh::try_from(large.view()).is_err());
+    }
+}
@@ -79,6 +79,14 @@ impl Connectivity {
             }
         }
     }
+
+    /// Replaces every node index `n` by `f(n)`.
+    pub fn map_nodes<F: Fn(usize) -> usize>(&mut self, f: F) {
+        match self {
+            Connectivity::Regular(conn) => conn.mapv_inplace(f),
+            Connectivity::Poly(conn) => conn.data.mapv_inplace(f),
+        }
+    }
 }
 
 impl IndexMut<usize> for Connectivity {
@@ -2,8 +2,10 @@
 //!
 //! Provides the [`Dimension`] enum representing 0D, 1D, 2D, and 3D spaces.
 
+use serde::{Deserialize, Serialize};
+
 /// Represents the topological dimension of a mesh element or field.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum Dimension {
     /// Zero-dimensional (points/vertices).
     D0,
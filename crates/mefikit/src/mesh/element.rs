@@ -183,6 +183,35 @@ impl ElementType {
     }
 }
 
+impl std::str::FromStr for ElementType {
+    type Err = String;
+
+    /// Parses the name of an element type, as printed by `Debug` (`"QUAD4"`, `"PGON"`, etc.).
+    fn from_str(name: &str) -> Result<Self, String> {
+        use ElementType::*;
+        Ok(match name {
+            "VERTEX" => VERTEX,
+            "SEG2" => SEG2,
+            "SEG3" => SEG3,
+            "SEG4" => SEG4,
+            "SPLINE" => SPLINE,
+            "TRI3" => TRI3,
+            "TRI6" => TRI6,
+            "TRI7" => TRI7,
+            "QUAD4" => QUAD4,
+            "QUAD8" => QUAD8,
+            "QUAD9" => QUAD9,
+            "PGON" => PGON,
+            "TET4" => TET4,
+            "TET10" => TET10,
+            "HEX8" => HEX8,
+            "HEX21" => HEX21,
+            "PHED" => PHED,
+            _ => return Err(format!("Unknown element type: {name}")),
+        })
+    }
+}
+
 /// Unique identifier for an element, combining its type and index.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct ElementId(ElementType, usize);
@@ -450,6 +479,13 @@ mod tests {
     use super::*;
     use ndarray::array;
 
+    #[test]
+    fn test_element_type_from_str() {
+        assert_eq!("QUAD4".parse(), Ok(ElementType::QUAD4));
+        assert_eq!("PHED".parse(), Ok(ElementType::PHED));
+        assert!("quad4".parse::<ElementType>().is_err());
+    }
+
     #[test]
     fn test_element_tri3_2d_basics() {
         let coords = array![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
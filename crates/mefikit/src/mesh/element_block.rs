@@ -1,5 +1,5 @@
 use derive_where::derive_where;
-use ndarray as nd;
+use ndarray::{self as nd, ShapeBuilder};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use std::collections::BTreeMap;
@@ -7,6 +7,7 @@ use std::collections::BTreeSet;
 
 use super::connectivity::{Connectivity, ConnectivityBase, ConnectivityView};
 use super::element::{Element, ElementMut, ElementType};
+use super::fields::{FieldData, FieldLocation, SparseData};
 use super::indirect_index::IndirectIndex;
 
 /// The part of a mesh constituted by one kind of element.
@@ -26,6 +27,15 @@ where
     pub cell_type: ElementType,
     pub connectivity: ConnectivityBase<C>,
     pub fields: BTreeMap<String, nd::ArrayBase<F, nd::IxDyn>>,
+    /// Location of the float fields whose values are not one per element.
+    #[serde(default)]
+    pub field_locations: BTreeMap<String, FieldLocation>,
+    /// Integer, boolean and categorical fields. They are always shared, even in views.
+    #[serde(default)]
+    pub typed_fields: BTreeMap<String, FieldData>,
+    /// Floating point fields defined on a subset of the block elements.
+    #[serde(default)]
+    pub sparse_fields: BTreeMap<String, SparseData>,
     pub families: nd::ArrayBase<G, nd::Ix1>,
     pub groups: BTreeMap<String, BTreeSet<usize>>,
 }
@@ -36,6 +46,9 @@ pub type ElementBlock =
 pub type ElementBlockView<'a> =
     ElementBlockBase<nd::ViewRepr<&'a usize>, nd::ViewRepr<&'a f64>, nd::ViewRepr<&'a usize>>;
 
+pub type ElementBlockViewMut<'a> =
+    ElementBlockBase<nd::ViewRepr<&'a usize>, nd::ViewRepr<&'a mut f64>, nd::ViewRepr<&'a usize>>;
+
 impl<C, F, G> ElementBlockBase<C, F, G>
 where
     C: nd::Data<Elem = usize>,
@@ -52,6 +65,86 @@ where
         &self.connectivity[index]
     }
 
+    /// Returns where the values of a float field are located, cells by default.
+    pub fn field_location(&self, name: &str) -> FieldLocation {
+        self.field_locations.get(name).copied().unwrap_or_default()
+    }
+
+    /// Returns a view of the values of a float field, of shape `[len, ...]`.
+    pub fn field_view(&self, name: &str) -> Option<nd::ArrayViewD<'_, f64>> {
+        self.fields.get(name).map(|f| f.view())
+    }
+
+    /// Returns one component of a float field, for every element of the block.
+    ///
+    /// Fields with one value per element only have component `0`. Returns `None` if the field
+    /// does not exist, has more than one axis of components or has no such component.
+    pub fn field_column(&self, name: &str, component: usize) -> Option<nd::ArrayView1<'_, f64>> {
+        let values = self.fields.get(name)?.view();
+        match values.ndim() {
+            1 if component == 0 => values.into_dimensionality().ok(),
+            2 if component < values.shape()[1] => values
+                .into_dimensionality::<nd::Ix2>()
+                .ok()
+                .map(|v| v.index_axis_move(nd::Axis(1), component)),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the connectivity of each element together with its values of a float field.
+    pub fn iter_with_field<'a>(
+        &'a self,
+        name: &str,
+    ) -> Option<impl ExactSizeIterator<Item = (&'a [usize], nd::ArrayViewD<'a, f64>)> + 'a> {
+        let values = self.fields.get(name)?;
+        Some(self.connectivity.iter().zip(values.outer_iter()))
+    }
+
+    /// Copies the elements at the given indices into a new block, in the order of `indices`, with
+    /// their fields and their locations, families and the groups of the block.
+    pub fn select(&self, indices: &[usize]) -> ElementBlock {
+        let mut selected = match &self.connectivity {
+            ConnectivityBase::Regular(conn) => ElementBlock::new_regular(
+                self.cell_type,
+                conn.select(nd::Axis(0), indices).into_shared(),
+                None,
+                None,
+            ),
+            ConnectivityBase::Poly(_) => {
+                let mut data = Vec::new();
+                let mut offsets = Vec::with_capacity(indices.len());
+                for &i in indices {
+                    data.extend_from_slice(&self.connectivity[i]);
+                    offsets.push(data.len());
+                }
+                ElementBlock::new_poly(
+                    self.cell_type,
+                    nd::ArcArray1::from(data),
+                    nd::ArcArray1::from(offsets),
+                )
+            }
+        };
+        selected.fields = self
+            .fields
+            .iter()
+            .map(|(n, f)| (n.clone(), f.select(nd::Axis(0), indices).into_shared()))
+            .collect();
+        selected.field_locations = self.field_locations.clone();
+        selected.typed_fields = self
+            .typed_fields
+            .iter()
+            .map(|(n, f)| (n.clone(), f.select(indices)))
+            .collect();
+        selected.sparse_fields = self
+            .sparse_fields
+            .iter()
+            .map(|(n, f)| (n.clone(), f.select(indices)))
+            .collect();
+        selected.families = self.families.select(nd::Axis(0), indices).into_shared();
+        selected.groups = self.groups.clone();
+        selected
+    }
+
     /// Returns an immutable view of the element at `index`.
     pub fn get<'a>(&'a self, index: usize, coords: nd::ArrayView2<'a, f64>) -> Element<'a> {
         // let fields = self
@@ -134,6 +227,20 @@ where
     }
 }
 
+impl<C, F, G> ElementBlockBase<C, F, G>
+where
+    C: nd::Data<Elem = usize>,
+    F: nd::DataMut<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    /// Returns a mutable view of the values of a float field, of shape `[len, ...]`.
+    ///
+    /// Values shared with another mesh are copied first.
+    pub fn field_mut(&mut self, name: &str) -> Option<nd::ArrayViewMutD<'_, f64>> {
+        self.fields.get_mut(name).map(|f| f.view_mut())
+    }
+}
+
 impl ElementBlock {
     /// Create a new regular element block.
     ///
@@ -163,6 +270,9 @@ impl ElementBlock {
             cell_type,
             connectivity: Connectivity::Regular(connectivity),
             fields,
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
             families: families.unwrap(),
             groups: BTreeMap::new(),
         }
@@ -183,12 +293,15 @@ impl ElementBlock {
         connectivity: nd::ArcArray1<usize>,
         offsets: nd::ArcArray1<usize>,
     ) -> Self {
-        let conn_len = connectivity.len();
+        let n_elements = offsets.len();
         Self {
             cell_type,
             connectivity: Connectivity::new_poly(connectivity, offsets),
             fields: BTreeMap::new(),
-            families: nd::ArcArray1::from(vec![0; conn_len]),
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
+            families: nd::ArcArray1::from(vec![0; n_elements]),
             groups: BTreeMap::new(),
         }
     }
@@ -255,15 +368,15 @@ impl<'a> ElementBlockView<'a> {
         connectivity: nd::ArrayView2<'a, usize>,
         families: Option<nd::ArrayView1<'a, usize>>,
     ) -> Self {
-        let families = match families {
-            Some(fams) => Some(fams),
-            None => todo!("Implement something meaningful?"),
-        };
+        let families = families.unwrap_or_else(|| zero_families(connectivity.nrows()));
         Self {
             cell_type,
             connectivity: ConnectivityView::Regular(connectivity),
             fields: BTreeMap::new(),
-            families: families.unwrap(),
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
+            families,
             groups: BTreeMap::new(),
         }
     }
@@ -283,8 +396,7 @@ impl<'a> ElementBlockView<'a> {
         connectivity: nd::ArrayView1<'a, usize>,
         offsets: nd::ArrayView1<'a, usize>,
     ) -> Self {
-        let conn_len = connectivity.len();
-        let reg_vec = Box::new(nd::Array1::from(vec![0; conn_len]));
+        let n_elements = offsets.len();
         Self {
             cell_type,
             connectivity: ConnectivityView::Poly(IndirectIndex {
@@ -292,13 +404,40 @@ impl<'a> ElementBlockView<'a> {
                 offsets,
             }),
             fields: BTreeMap::new(),
-            families: Box::leak(reg_vec).view(),
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
+            families: zero_families(n_elements),
             groups: BTreeMap::new(),
         }
     }
     pub fn into_entry(self) -> (ElementType, ElementBlockView<'a>) {
         (self.cell_type, self)
     }
+
+    /// Converts a block without fields into a block whose fields may be mutated.
+    ///
+    /// # Panics
+    /// Panics if the block has fields, as they cannot be borrowed mutably.
+    pub fn into_view_mut(self) -> ElementBlockViewMut<'a> {
+        assert!(self.fields.is_empty(), "Fields of a view cannot be mutated");
+        ElementBlockBase {
+            cell_type: self.cell_type,
+            connectivity: self.connectivity,
+            fields: BTreeMap::new(),
+            field_locations: BTreeMap::new(),
+            typed_fields: self.typed_fields,
+            sparse_fields: self.sparse_fields,
+            families: self.families,
+            groups: self.groups,
+        }
+    }
+}
+
+/// Family 0 for `n` elements, as a view repeating a single static value.
+fn zero_families<'a>(n: usize) -> nd::ArrayView1<'a, usize> {
+    static ZERO: [usize; 1] = [0];
+    nd::ArrayView1::from_shape(n.strides(0), &ZERO).unwrap()
 }
 
 /// Trait for converting an element block into an (ElementType, block) tuple.
@@ -333,6 +472,9 @@ mod tests {
             cell_type: ElementType::TRI3,
             connectivity,
             fields,
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
             families: families.into(),
             groups,
         };
@@ -354,6 +496,9 @@ mod tests {
             cell_type: ElementType::TRI3,
             connectivity,
             fields,
+            field_locations: BTreeMap::new(),
+            typed_fields: BTreeMap::new(),
+            sparse_fields: BTreeMap::new(),
             families: families.into(),
             groups,
         };
@@ -363,4 +508,65 @@ mod tests {
 
         assert_eq!(elements.len(), 3);
     }
+
+    #[test]
+    fn test_element_block_fields() {
+        let mut block = ElementBlock::new_regular(
+            ElementType::SEG2,
+            array![[0, 1], [1, 2], [2, 3]].to_shared(),
+            None,
+            Some(BTreeMap::from([
+                (
+                    "t".to_owned(),
+                    array![1.0, 2.0, 3.0].into_dyn().into_shared(),
+                ),
+                (
+                    "v".to_owned(),
+                    array![[1.0, 0.0], [2.0, 0.0], [3.0, 1.0]]
+                        .into_dyn()
+                        .into_shared(),
+                ),
+            ])),
+        );
+        let shared = block.fields["v"].clone();
+        assert_eq!(block.field_view("v").unwrap().shape(), &[3, 2]);
+        assert_eq!(block.field_column("v", 1).unwrap(), array![0.0, 0.0, 1.0]);
+        assert_eq!(block.field_column("t", 0).unwrap(), array![1.0, 2.0, 3.0]);
+        assert!(block.field_column("v", 2).is_none());
+        assert!(block.field_column("t", 1).is_none());
+
+        let rows: Vec<_> = block.iter_with_field("t").unwrap().collect();
+        assert_eq!(rows[2].0, &[2, 3]);
+        assert_eq!(rows[2].1.sum(), 3.0);
+
+        block
+            .field_locations
+            .insert("v".to_owned(), FieldLocation::ElementNodes);
+        let selected = block.select(&[2]);
+        assert_eq!(selected.fields["v"].shape(), &[1, 2]);
+        assert_eq!(selected.field_location("v"), FieldLocation::ElementNodes);
+
+        block.field_mut("v").unwrap().fill(0.0);
+        assert_eq!(block.fields["v"].sum(), 0.0);
+        assert_eq!(shared.sum(), 7.0);
+        assert!(block.field_mut("w").is_none());
+    }
+
+    #[test]
+    fn test_poly_block_families() {
+        let mut block = ElementBlock::new_poly(
+            ElementType::PGON,
+            array![0, 1, 2, 1, 3, 4, 2].to_shared(),
+            array![3, 7].to_shared(),
+        );
+        assert_eq!(block.families.len(), 2);
+        block.add_element(array![0, 2, 5].view(), Some(4), None);
+        assert_eq!(block.len(), 3);
+        assert_eq!(block.families.to_vec(), vec![0, 0, 4]);
+
+        let selected = block.select(&[2, 0]);
+        assert_eq!(selected.element_connectivity(0), &[0, 2, 5]);
+        assert_eq!(selected.element_connectivity(1), &[0, 1, 2]);
+        assert_eq!(selected.families.to_vec(), vec![4, 0]);
+    }
 }
@@ -2,10 +2,16 @@
 //!
 //! [`ElementIds`] stores element indices in a [`BTreeMap`] keyed by [`ElementType`],
 //! allowing efficient lookup and iteration over elements of specific types.
+//!
+//! The indices of each type are kept sorted and without duplicates, so that lookups are binary
+//! searches and set operations are linear merges.
 
+use itertools::{EitherOrBoth, Itertools};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::ops::Range;
 
 use crate::prelude::ElementId;
 use crate::prelude::ElementType;
@@ -14,7 +20,11 @@ use crate::prelude::ElementType;
 ///
 /// This struct stores element indices grouped by their [`ElementType`], enabling
 /// type-specific queries and iterations.
-#[derive(Debug, Clone)]
+///
+/// Indices of each type are sorted in increasing order and unique. All methods keep this
+/// invariant, code filling the inner map directly must keep it too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<ElementType, Vec<usize>>")]
 pub struct ElementIds(pub BTreeMap<ElementType, Vec<usize>>);
 
 impl Default for ElementIds {
@@ -23,6 +33,14 @@ impl Default for ElementIds {
     }
 }
 
+/// Sorts and deduplicates indices, skipping the sort when they already are.
+fn normalize(indices: &mut Vec<usize>) {
+    if !indices.is_sorted() {
+        indices.sort_unstable();
+    }
+    indices.dedup();
+}
+
 impl ElementIds {
     /// Creates a new empty collection of element IDs.
     pub fn new() -> Self {
@@ -30,23 +48,42 @@ impl ElementIds {
     }
 
     /// Adds a single element ID to the collection.
+    ///
+    /// Adding indices in increasing order is amortized constant time.
     pub fn add(&mut self, element_type: ElementType, index: usize) {
-        self.0.entry(element_type).or_default().push(index);
+        let indices = self.0.entry(element_type).or_default();
+        match indices.last() {
+            Some(&last) if last >= index => {
+                if let Err(pos) = indices.binary_search(&index) {
+                    indices.insert(pos, index);
+                }
+            }
+            _ => indices.push(index),
+        }
     }
 
     /// Adds a block of element indices for the given element type.
     pub fn add_block(&mut self, element_type: ElementType, indices: Vec<usize>) {
-        self.0.entry(element_type).or_default().extend(indices);
+        let entry = self.0.entry(element_type).or_default();
+        entry.extend(indices);
+        normalize(entry);
+    }
+
+    /// Adds the contiguous range of element indices `range` for the given element type.
+    pub fn add_range(&mut self, element_type: ElementType, range: Range<usize>) {
+        let indices = self.0.entry(element_type).or_default();
+        if indices.last().is_none_or(|&last| last < range.start) {
+            indices.extend(range);
+        } else {
+            *indices = indices.iter().copied().merge(range).dedup().collect();
+        }
     }
 
     /// Removes a specific element index from the collection.
     pub fn remove(&mut self, element_type: ElementType, index: usize) -> Option<usize> {
-        if let Some(indices) = self.0.get_mut(&element_type)
-            && let Some(pos) = indices.iter().position(|&i| i == index)
-        {
-            return Some(indices.remove(pos));
-        }
-        None
+        let indices = self.0.get_mut(&element_type)?;
+        let pos = indices.binary_search(&index).ok()?;
+        Some(indices.remove(pos))
     }
 
     /// Returns the indices for a specific element type, if present.
@@ -54,6 +91,14 @@ impl ElementIds {
         self.0.get(element_type)
     }
 
+    /// Returns the position of an element among the indices of its type, if present.
+    pub fn position(&self, element_id: ElementId) -> Option<usize> {
+        self.0
+            .get(&element_id.element_type())?
+            .binary_search(&element_id.index())
+            .ok()
+    }
+
     /// Returns `true` if the collection contains any elements of the given type.
     pub fn contains_type(&self, element_type: ElementType) -> bool {
         self.0.contains_key(&element_type)
@@ -61,11 +106,7 @@ impl ElementIds {
 
     /// Returns `true` if the collection contains the given element ID.
     pub fn contains(&self, element_id: ElementId) -> bool {
-        if let Some(indices) = self.0.get(&element_id.element_type()) {
-            indices.contains(&element_id.index())
-        } else {
-            false
-        }
+        self.position(element_id).is_some()
     }
 
     /// Iterates over element type and index pairs.
@@ -73,6 +114,14 @@ impl ElementIds {
         self.0.iter()
     }
 
+    /// Iterates over the runs of contiguous indices of the given element type.
+    pub fn ranges(&self, element_type: ElementType) -> impl Iterator<Item = Range<usize>> + '_ {
+        let indices = self.0.get(&element_type).map_or(&[][..], |v| v.as_slice());
+        indices
+            .chunk_by(|a, b| a + 1 == *b)
+            .map(|run| run[0]..run[run.len() - 1] + 1)
+    }
+
     /// Iterates over all element IDs as flattened [`ElementId`] values.
     pub fn iter(&self) -> impl Iterator<Item = ElementId> {
         self.0
@@ -120,10 +169,75 @@ impl ElementIds {
     pub fn element_types(&self) -> Vec<ElementType> {
         self.0.keys().cloned().collect()
     }
+
+    /// Adds all element IDs from another collection to this one.
+    pub fn union(&mut self, other: &Self) {
+        for (et, theirs) in &other.0 {
+            let Some(mine) = self.0.get_mut(et) else {
+                self.0.insert(*et, theirs.clone());
+                continue;
+            };
+            *mine = mine.iter().merge(theirs).dedup().copied().collect();
+        }
+    }
+
+    /// Retains only element IDs that are also present in another collection, dropping emptied
+    /// types.
+    pub fn intersection(&mut self, other: &Self) {
+        self.0.retain(|et, mine| {
+            let Some(theirs) = other.0.get(et) else {
+                return false;
+            };
+            *mine = mine
+                .iter()
+                .merge_join_by(theirs, |a, b| a.cmp(b))
+                .filter_map(|e| e.both().map(|(&a, _)| a))
+                .collect();
+            !mine.is_empty()
+        });
+    }
+
+    /// Removes all element IDs that are present in another collection, dropping emptied types.
+    pub fn difference(&mut self, other: &Self) {
+        self.0.retain(|et, mine| {
+            let Some(theirs) = other.0.get(et) else {
+                return true;
+            };
+            *mine = mine
+                .iter()
+                .merge_join_by(theirs, |a, b| a.cmp(b))
+                .filter_map(|e| match e {
+                    EitherOrBoth::Left(&a) => Some(a),
+                    _ => None,
+                })
+                .collect();
+            !mine.is_empty()
+        });
+    }
+
+    /// Keeps element IDs that are in either collection but not in both, dropping emptied types.
+    pub fn symmetric_difference(&mut self, other: &Self) {
+        for (et, theirs) in &other.0 {
+            let mine = self.0.remove(et).unwrap_or_default();
+            let diff: Vec<usize> = mine
+                .iter()
+                .merge_join_by(theirs, |a, b| a.cmp(b))
+                .filter_map(|e| match e {
+                    EitherOrBoth::Both(..) => None,
+                    EitherOrBoth::Left(&i) | EitherOrBoth::Right(&i) => Some(i),
+                })
+                .collect();
+            if !diff.is_empty() {
+                self.0.insert(*et, diff);
+            }
+        }
+    }
 }
 
+/// Sorts and deduplicates the indices of each type.
 impl From<BTreeMap<ElementType, Vec<usize>>> for ElementIds {
-    fn from(map: BTreeMap<ElementType, Vec<usize>>) -> Self {
+    fn from(mut map: BTreeMap<ElementType, Vec<usize>>) -> Self {
+        map.values_mut().for_each(normalize);
         ElementIds(map)
     }
 }
@@ -144,19 +258,100 @@ impl FromParallelIterator<ElementId> for ElementIds {
     where
         T: IntoParallelIterator<Item = ElementId>,
     {
-        par_iter
+        let mut all: Vec<ElementId> = par_iter.into_par_iter().collect();
+        all.par_sort_unstable();
+        let mut ids = ElementIds::new();
+        for (et, run) in &all.into_iter().dedup().chunk_by(|id| id.element_type()) {
+            ids.0.insert(et, run.map(|id| id.index()).collect());
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(indices: &[usize]) -> ElementIds {
+        ElementIds::from(BTreeMap::from([(ElementType::QUAD4, indices.to_vec())]))
+    }
+
+    #[test]
+    fn test_sorted_unique() {
+        let mut ids = ids(&[3, 1, 3]);
+        assert_eq!(ids.get(&ElementType::QUAD4), Some(&vec![1, 3]));
+        ids.add(ElementType::QUAD4, 2);
+        ids.add(ElementType::QUAD4, 5);
+        ids.add(ElementType::QUAD4, 1);
+        ids.add_block(ElementType::QUAD4, vec![0, 5]);
+        assert_eq!(ids.get(&ElementType::QUAD4), Some(&vec![0, 1, 2, 3, 5]));
+        assert!(ids.contains(ElementId::new(ElementType::QUAD4, 3)));
+        assert_eq!(ids.position(ElementId::new(ElementType::QUAD4, 5)), Some(4));
+        assert_eq!(ids.remove(ElementType::QUAD4, 2), Some(2));
+        assert_eq!(ids.remove(ElementType::QUAD4, 2), None);
+        assert!(!ids.contains(ElementId::new(ElementType::QUAD4, 2)));
+
+        let collected: ElementIds = [5, 0, 5, 2]
+            .into_iter()
+            .map(|i| ElementId::new(ElementType::TRI3, i))
+            .collect();
+        assert_eq!(collected.get(&ElementType::TRI3), Some(&vec![0, 2, 5]));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut ids = ids(&[0, 1, 2, 5, 7, 8]);
+        assert_eq!(
+            ids.ranges(ElementType::QUAD4).collect::<Vec<_>>(),
+            vec![0..3, 5..6, 7..9]
+        );
+        ids.add_range(ElementType::QUAD4, 3..6);
+        ids.add_range(ElementType::QUAD4, 10..12);
+        assert_eq!(
+            ids.ranges(ElementType::QUAD4).collect::<Vec<_>>(),
+            vec![0..6, 7..9, 10..12]
+        );
+        assert_eq!(ids.ranges(ElementType::TRI3).count(), 0);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut union = ids(&[0, 2, 4]);
+        union.union(&ids(&[1, 2]));
+        assert_eq!(union, ids(&[0, 1, 2, 4]));
+
+        let mut inter = ids(&[0, 2, 4]);
+        inter.intersection(&ids(&[2, 3, 4]));
+        assert_eq!(inter, ids(&[2, 4]));
+        inter.intersection(&ids(&[0]));
+        assert!(inter.is_empty());
+
+        let mut diff = ids(&[0, 2, 4]);
+        diff.difference(&ids(&[2]));
+        assert_eq!(diff, ids(&[0, 4]));
+
+        let mut sym = ids(&[0, 2, 4]);
+        sym.symmetric_difference(&ids(&[2, 3]));
+        assert_eq!(sym, ids(&[0, 3, 4]));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_from_par_iter() {
+        let ids: ElementIds = (0..1000usize)
             .into_par_iter()
-            .fold(ElementIds::new, |mut acc, id| {
-                acc.add(id.element_type(), id.index());
-                acc
-            })
-            .reduce(ElementIds::new, |mut acc, other| {
-                for (et, indices) in other.0 {
-                    for index in indices {
-                        acc.add(et, index);
-                    }
-                }
-                acc
+            .rev()
+            .flat_map_iter(|i| {
+                [
+                    ElementId::new(ElementType::QUAD4, i % 10),
+                    ElementId::new(ElementType::TRI3, i),
+                ]
             })
+            .collect();
+        assert_eq!(
+            ids.get(&ElementType::QUAD4),
+            Some(&(0..10).collect::<Vec<_>>())
+        );
+        assert_eq!(ids.get(&ElementType::TRI3).unwrap().len(), 1000);
     }
 }
@@ -4,6 +4,8 @@
 //! union, intersection, difference, and membership operations.
 
 use itertools::Itertools;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use rustc_hash::FxHashSet;
 use std::collections::BTreeMap;
 
@@ -13,7 +15,7 @@ use super::element_ids::ElementIds;
 /// A set of element identifiers organized by element type.
 ///
 /// Unlike [`ElementIds`], this uses [`FxHashSet`] for indices, making it
-/// suitable for set operations like union, intersection, and difference.
+/// suitable for many insertions and removals in random order, as done by selectors.
 #[derive(Debug, Clone)]
 pub struct ElementIdsSet(pub BTreeMap<ElementType, FxHashSet<usize>>);
 
@@ -105,14 +107,15 @@ impl ElementIdsSet {
     /// Keeps element IDs that are in either set but not in both.
     pub fn symmetric_difference(&mut self, other: &Self) {
         for (et, other_indices_set) in &other.0 {
-            if let Some(indices_set) = self.0.remove(et) {
-                let diff: FxHashSet<usize> = indices_set
+            let diff: FxHashSet<usize> = match self.0.remove(et) {
+                Some(indices_set) => indices_set
                     .symmetric_difference(other_indices_set)
                     .cloned()
-                    .collect();
-                if !diff.is_empty() {
-                    self.0.insert(*et, diff);
-                }
+                    .collect(),
+                None => other_indices_set.clone(),
+            };
+            if !diff.is_empty() {
+                self.0.insert(*et, diff);
             }
         }
     }
@@ -136,6 +139,48 @@ impl ElementIdsSet {
         })
     }
 
+    /// Keeps only the element IDs for which `f` returns `true`, dropping emptied types.
+    ///
+    /// Blocks and the indices inside each block are filtered in parallel (requires `rayon`
+    /// feature).
+    #[cfg(feature = "rayon")]
+    pub fn filter<F>(self, f: F) -> Self
+    where
+        F: Fn(ElementId) -> bool + Sync,
+    {
+        ElementIdsSet(
+            self.0
+                .into_par_iter()
+                .map(|(et, indices_set)| {
+                    let filtered: FxHashSet<usize> = indices_set
+                        .into_par_iter()
+                        .filter(|&index| f(ElementId::new(et, index)))
+                        .collect();
+                    (et, filtered)
+                })
+                .filter(|(_, indices_set)| !indices_set.is_empty())
+                .collect(),
+        )
+    }
+
+    /// Keeps only the element IDs for which `f` returns `true`, dropping emptied types.
+    #[cfg(not(feature = "rayon"))]
+    pub fn filter<F>(self, f: F) -> Self
+    where
+        F: Fn(ElementId) -> bool + Sync,
+    {
+        ElementIdsSet(
+            self.0
+                .into_iter()
+                .map(|(et, mut indices_set)| {
+                    indices_set.retain(|&index| f(ElementId::new(et, index)));
+                    (et, indices_set)
+                })
+                .filter(|(_, indices_set)| !indices_set.is_empty())
+                .collect(),
+        )
+    }
+
     /// Returns `true` if the set contains the given element ID.
     pub fn contains(&self, element_id: ElementId) -> bool {
         if let Some(indices_set) = self.0.get(&element_id.element_type()) {
@@ -224,4 +269,19 @@ mod tests {
         assert_eq!(set1.0.get(&ElementType::TRI3).unwrap().len(), 1);
         assert!(!set1.0.contains_key(&ElementType::QUAD4));
     }
+
+    #[test]
+    fn test_filter() {
+        let mut set = ElementIdsSet::new();
+        set.0
+            .entry(ElementType::TRI3)
+            .or_default()
+            .extend([0, 1, 2, 3]);
+        set.0.entry(ElementType::QUAD4).or_default().insert(1);
+
+        let set = set.filter(|eid| eid.index() % 2 == 0);
+
+        assert_eq!(set.0.get(&ElementType::TRI3).unwrap().len(), 2);
+        assert!(!set.contains_type(ElementType::QUAD4));
+    }
 }
@@ -2,24 +2,49 @@
 //!
 //! Fields associate data arrays with element types, enabling storage of
 //! scalar, vector, or tensor values on mesh elements.
+//!
+//! Floating point fields are handled by [`FieldBase`], with their [`FieldLocation`] in the
+//! elements. Integer, boolean and categorical (string
+//! labels) data are stored per block as [`FieldData`]. Fields defined only on a selection of
+//! elements are handled by [`SparseField`].
 
 use derive_where::derive_where;
 use ndarray::{self as nd, ArrayBase, Axis};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashSet},
     ops::{Add, Div, Mul, Sub},
 };
 
-use crate::mesh::{Dimension, ElementIds, ElementType};
+use crate::mesh::{Dimension, ElementId, ElementIds, ElementType};
+
+/// Where the values of a floating point field are located in its elements.
+///
+/// The location gives the meaning of the first axes of the field arrays, which cannot be told
+/// from their shape alone: a vector field of 3 components on triangles has the shape of a scalar
+/// field at 3 integration points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldLocation {
+    /// One value per element, arrays of shape `[n_elem, ...]`.
+    #[default]
+    Cells,
+    /// One value per node of each element, arrays of shape `[n_elem, n_nodes, ...]`.
+    ElementNodes,
+    /// One value per integration point of the Gauss rule of `order` (see
+    /// [`crate::geometry::QuadratureRule::gauss`]), arrays of shape `[n_elem, n_gp, ...]`.
+    GaussPoints { order: usize },
+}
 
 /// A generic field container mapping element types to data arrays.
 ///
 /// Fields store per-element data (e.g., temperature, displacement) organized
 /// by element type. The data arrays have shape `(num_elements, ...)` where
-/// trailing dimensions represent the field's tensor structure.
+/// trailing dimensions represent the field's tensor structure. Values are located at the cells
+/// unless another [`FieldLocation`] is given with [`FieldBase::with_location`].
 #[derive_where(Clone, Debug; S: nd::RawDataClone)]
 pub struct FieldBase<S: nd::Data<Elem = f64>, D: nd::Dimension>(
     pub BTreeMap<ElementType, nd::ArrayBase<S, D>>,
+    FieldLocation,
 );
 /// A view into a field with borrowed data.
 pub type FieldView<'a, D> = FieldBase<nd::ViewRepr<&'a f64>, D>;
@@ -48,11 +73,22 @@ where
     /// # Panics
     /// Panics if the field map is empty or if arrays have incompatible shapes.
     pub fn new(map: BTreeMap<ElementType, nd::ArrayBase<S, D>>) -> Self {
-        let res = Self(map);
+        let res = Self(map, FieldLocation::Cells);
         res.is_coherent();
         res
     }
 
+    /// Sets where the values are located in the elements.
+    pub fn with_location(mut self, location: FieldLocation) -> Self {
+        self.1 = location;
+        self
+    }
+
+    /// Returns where the values are located in the elements.
+    pub fn location(&self) -> FieldLocation {
+        self.1
+    }
+
     /// Returns a view of this field.
     pub fn view(&self) -> FieldView<'_, D> {
         FieldView::new(
@@ -61,6 +97,7 @@ where
                 .map(|(k, v)| (*k, v.view()))
                 .collect::<BTreeMap<_, _>>(),
         )
+        .with_location(self.1)
     }
 
     /// Returns the topological dimension of the field's elements, or `None` if empty.
@@ -154,24 +191,22 @@ where
             let mapped_array = array.mapv(&mut f);
             result.insert(*elem_type, mapped_array.into_owned());
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 
     /// Applies a binary function element-wise to this field and another.
+    ///
+    /// Arrays of each element type are broadcast against each other.
     pub fn map_zip<F>(&self, other: &Self, mut f: F) -> FieldOwned<nd::IxDyn>
     where
         F: FnMut(f64, f64) -> f64,
     {
         self.panic_if_incompatible_with(other);
         let mut result = BTreeMap::new();
-        let greatest_dim = if self.ndim() > other.ndim() {
-            self.dim()
-        } else {
-            other.dim()
-        };
         for (elem_type, left_array) in &self.0 {
             if let Some(right_array) = other.0.get(elem_type) {
-                let mut res = nd::ArrayD::<f64>::zeros(greatest_dim.clone());
+                let shape = broadcast_shape(left_array.shape(), right_array.shape());
+                let mut res = nd::ArrayD::<f64>::zeros(shape);
                 nd::Zip::from(&mut res)
                     .and_broadcast(left_array)
                     .and_broadcast(right_array)
@@ -179,24 +214,22 @@ where
                 result.insert(*elem_type, res.into_owned());
             }
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 
     /// Returns element IDs where a binary predicate holds.
+    ///
+    /// For multi-component fields, the predicate must hold on all components.
     pub fn map_zip_where<F>(&self, other: &Self, mut f: F) -> ElementIds
     where
         F: FnMut(f64, f64) -> bool,
     {
         self.panic_if_incompatible_with(other);
         let mut result = BTreeMap::new();
-        let greatest_dim = if self.ndim() > other.ndim() {
-            self.full_dim()
-        } else {
-            other.full_dim()
-        };
         for (elem_type, left_array) in &self.0 {
             if let Some(right_array) = other.0.get(elem_type) {
-                let mut res = nd::ArrayD::<bool>::from_elem(greatest_dim, false);
+                let shape = broadcast_shape(left_array.shape(), right_array.shape());
+                let mut res = nd::ArrayD::<bool>::from_elem(shape, false);
                 nd::Zip::from(&mut res)
                     .and_broadcast(left_array)
                     .and_broadcast(right_array)
@@ -276,7 +309,7 @@ where
         for (elem_type, array) in &self.0 {
             result.insert(*elem_type, array.to_owned());
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 
     /// Converts this field to a shared (reference-counted) field.
@@ -285,7 +318,7 @@ where
         for (elem_type, array) in &self.0 {
             result.insert(*elem_type, array.to_shared());
         }
-        FieldArc::new(result)
+        FieldArc::new(result).with_location(self.1)
     }
 
     /// Consumes this field and returns a shared version.
@@ -293,11 +326,12 @@ where
     where
         S: nd::DataOwned,
     {
+        let location = self.1;
         let mut result = BTreeMap::new();
         for (elem_type, array) in self.0 {
             result.insert(elem_type, array.into_shared());
         }
-        FieldArc::new(result)
+        FieldArc::new(result).with_location(location)
     }
 
     /// Creates a field by broadcasting a single array to multiple element types.
@@ -314,31 +348,359 @@ where
 
     /// Converts this field to use dynamic dimensions.
     pub fn into_dyn(self) -> FieldBase<S, nd::IxDyn> {
+        let location = self.1;
         let mut result = BTreeMap::new();
         for (elem_type, array) in self.0 {
             result.insert(elem_type, array.into_dyn());
         }
-        FieldBase::new(result)
+        FieldBase::new(result).with_location(location)
+    }
+}
+
+/// Returns the shape obtained by broadcasting two shapes together (numpy rules).
+fn broadcast_shape(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let n = a.len().max(b.len());
+    (0..n)
+        .map(|i| {
+            let da = if i + a.len() >= n {
+                a[i + a.len() - n]
+            } else {
+                1
+            };
+            let db = if i + b.len() >= n {
+                b[i + b.len() - n]
+            } else {
+                1
+            };
+            if da == 1 { db } else { da }
+        })
+        .collect()
+}
+
+/// Non floating point values attached to the elements of one block.
+///
+/// The first axis of the arrays always runs over the elements of the block. Categorical data
+/// (material names, partition labels, ...) is stored as integer codes into a list of categories.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FieldData {
+    /// Integer values (ids, partition numbers, ...).
+    Int(nd::ArcArray<i64, nd::IxDyn>),
+    /// Boolean values (masks, flags, ...).
+    Bool(nd::ArcArray<bool, nd::IxDyn>),
+    /// String labels stored as codes into `categories`.
+    Categorical {
+        codes: nd::ArcArray1<u32>,
+        categories: Vec<String>,
+    },
+}
+
+impl FieldData {
+    /// Builds a categorical field from one label per element.
+    ///
+    /// Categories are stored in order of first appearance.
+    pub fn categorical<S: AsRef<str>>(labels: &[S]) -> Self {
+        let mut categories: Vec<String> = Vec::new();
+        let codes = labels
+            .iter()
+            .map(|l| {
+                let l = l.as_ref();
+                match categories.iter().position(|c| c == l) {
+                    Some(i) => i as u32,
+                    None => {
+                        categories.push(l.to_owned());
+                        (categories.len() - 1) as u32
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        Self::Categorical {
+            codes: nd::ArcArray1::from(codes),
+            categories,
+        }
+    }
+
+    /// Returns the number of elements this data is defined on.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Int(arr) => arr.shape().first().copied().unwrap_or(0),
+            Self::Bool(arr) => arr.shape().first().copied().unwrap_or(0),
+            Self::Categorical { codes, .. } => codes.len(),
+        }
+    }
+
+    /// Returns `true` if this data is defined on no element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a short name for the kind of data stored.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "int",
+            Self::Bool(_) => "bool",
+            Self::Categorical { .. } => "categorical",
+        }
+    }
+
+    /// Returns the label of the element at `index` for categorical data, or `None` if the index
+    /// is out of range.
+    pub fn label(&self, index: usize) -> Option<&str> {
+        match self {
+            Self::Categorical { codes, categories } => codes
+                .get(index)
+                .and_then(|&c| categories.get(c as usize))
+                .map(|s| s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the labels of all elements for categorical data.
+    pub fn labels(&self) -> Option<Vec<&str>> {
+        match self {
+            Self::Categorical { codes, categories } => Some(
+                codes
+                    .iter()
+                    .map(|&c| categories[c as usize].as_str())
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns a new `FieldData` holding only the elements at the given indices.
+    pub fn select(&self, indices: &[usize]) -> Self {
+        match self {
+            Self::Int(arr) => Self::Int(arr.select(Axis(0), indices).into_shared()),
+            Self::Bool(arr) => Self::Bool(arr.select(Axis(0), indices).into_shared()),
+            Self::Categorical { codes, categories } => Self::Categorical {
+                codes: codes.select(Axis(0), indices).into_shared(),
+                categories: categories.clone(),
+            },
+        }
+    }
+
+    /// Returns the elements of `self` followed by the elements of `other`.
+    ///
+    /// Categories of categorical data are merged. Returns `None` if the kinds of data or the
+    /// shapes of their values differ.
+    pub fn concat(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Int(a), Self::Int(b)) => Some(Self::Int(
+                nd::concatenate(Axis(0), &[a.view(), b.view()])
+                    .ok()?
+                    .into_shared(),
+            )),
+            (Self::Bool(a), Self::Bool(b)) => Some(Self::Bool(
+                nd::concatenate(Axis(0), &[a.view(), b.view()])
+                    .ok()?
+                    .into_shared(),
+            )),
+            (
+                Self::Categorical { codes, categories },
+                Self::Categorical {
+                    codes: other_codes,
+                    categories: other_categories,
+                },
+            ) => {
+                let mut categories = categories.clone();
+                let new_codes: Vec<u32> = other_categories
+                    .iter()
+                    .map(|c| match categories.iter().position(|k| k == c) {
+                        Some(i) => i as u32,
+                        None => {
+                            categories.push(c.clone());
+                            (categories.len() - 1) as u32
+                        }
+                    })
+                    .collect();
+                let codes = codes
+                    .iter()
+                    .copied()
+                    .chain(other_codes.iter().map(|&c| new_codes[c as usize]))
+                    .collect::<Vec<_>>();
+                Some(Self::Categorical {
+                    codes: nd::ArcArray1::from(codes),
+                    categories,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Floating point values attached to a subset of the elements of one block.
+///
+/// `values` has shape `[indices.len(), ...]`, its first axis running over `indices`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SparseData {
+    /// Indices of the elements, in the block, the values are defined on, in increasing order.
+    pub indices: Vec<usize>,
+    /// Values on the selected elements.
+    pub values: nd::ArcArray<f64, nd::IxDyn>,
+}
+
+impl SparseData {
+    /// Returns the dense array of a block of `len` elements, using `fill` where undefined.
+    pub fn densify(&self, len: usize, fill: f64) -> nd::ArrayD<f64> {
+        let mut shape = self.values.shape().to_vec();
+        shape[0] = len;
+        let mut res = nd::ArrayD::from_elem(shape, fill);
+        for (i, &index) in self.indices.iter().enumerate() {
+            res.index_axis_mut(Axis(0), index)
+                .assign(&self.values.index_axis(Axis(0), i));
+        }
+        res
+    }
+
+    /// Restricts the data to the elements at the given block indices.
+    ///
+    /// Elements are renumbered by their position in `indices`, as done when extracting a
+    /// sub-mesh.
+    pub fn select(&self, indices: &[usize]) -> Self {
+        let (new_indices, pos): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .enumerate()
+            .filter_map(|(new, old)| self.indices.binary_search(old).ok().map(|p| (new, p)))
+            .unzip();
+        Self {
+            indices: new_indices,
+            values: self.values.select(Axis(0), &pos).into_shared(),
+        }
+    }
+
+    /// Returns the values of `self` followed by the values of `other`, whose indices are shifted
+    /// by `offset`.
+    ///
+    /// Returns `None` if the shapes of the values differ.
+    pub fn concat(&self, other: &Self, offset: usize) -> Option<Self> {
+        Some(Self {
+            indices: self
+                .indices
+                .iter()
+                .copied()
+                .chain(other.indices.iter().map(|i| i + offset))
+                .collect(),
+            values: nd::concatenate(Axis(0), &[self.values.view(), other.values.view()])
+                .ok()?
+                .into_shared(),
+        })
+    }
+}
+
+/// A floating point field defined only on a selection of elements.
+///
+/// This is useful for data living on small parts of a mesh (contact patches, local sensors,
+/// ...). Values of each element type have shape `[n_selected, ...]` and follow the order of
+/// `ids`, which is the increasing order of the element indices.
+#[derive(Clone, Debug)]
+pub struct SparseField {
+    /// Elements the field is defined on.
+    pub ids: ElementIds,
+    /// Values on the selected elements.
+    pub values: BTreeMap<ElementType, nd::ArcArray<f64, nd::IxDyn>>,
+}
+
+impl SparseField {
+    /// Creates a new sparse field from the indices of its elements, per type, and their values.
+    ///
+    /// The values of each type follow the order of its indices, which need not be sorted: both
+    /// are reordered together.
+    ///
+    /// # Panics
+    /// Panics if `indices` and `values` do not have the same element types or the same number of
+    /// elements per type, or if an element is given twice.
+    pub fn new(
+        indices: BTreeMap<ElementType, Vec<usize>>,
+        mut values: BTreeMap<ElementType, nd::ArcArray<f64, nd::IxDyn>>,
+    ) -> Self {
+        assert!(
+            indices.keys().eq(values.keys()),
+            "Sparse field ids and values must have the same element types"
+        );
+        let blocks = indices
+            .into_iter()
+            .map(|(et, indices)| {
+                let values = values.remove(&et).unwrap();
+                assert_eq!(
+                    indices.len(),
+                    values.shape()[0],
+                    "Sparse field values of {et:?} do not match the number of ids"
+                );
+                (et, SparseData { indices, values })
+            })
+            .collect();
+        Self::from_blocks(blocks)
+    }
+
+    /// Returns the value of the field on one element, if defined.
+    pub fn get(&self, id: ElementId) -> Option<nd::ArrayViewD<'_, f64>> {
+        let pos = self.ids.position(id)?;
+        Some(self.values[&id.element_type()].index_axis(Axis(0), pos))
+    }
+
+    /// Splits this field into per block data.
+    pub fn into_blocks(self) -> BTreeMap<ElementType, SparseData> {
+        let mut values = self.values;
+        self.ids
+            .0
+            .into_iter()
+            .map(|(et, indices)| {
+                let values = values.remove(&et).unwrap();
+                (et, SparseData { indices, values })
+            })
+            .collect()
+    }
+
+    /// Builds a field from per block data, reordering the values of blocks whose indices are not
+    /// sorted.
+    ///
+    /// # Panics
+    /// Panics if an element index appears twice in a block.
+    pub fn from_blocks(blocks: BTreeMap<ElementType, SparseData>) -> Self {
+        let mut ids = ElementIds::new();
+        let mut values = BTreeMap::new();
+        for (et, data) in blocks {
+            let (indices, block_values) = match data.indices.is_sorted() {
+                true => (data.indices, data.values),
+                false => {
+                    let mut order: Vec<usize> = (0..data.indices.len()).collect();
+                    order.sort_unstable_by_key(|&i| data.indices[i]);
+                    (
+                        order.iter().map(|&i| data.indices[i]).collect(),
+                        data.values.select(Axis(0), &order).into_shared(),
+                    )
+                }
+            };
+            assert!(
+                indices.windows(2).all(|w| w[0] < w[1]),
+                "Sparse field ids of {et:?} must be unique"
+            );
+            ids.add_block(et, indices);
+            values.insert(et, block_values);
+        }
+        Self { ids, values }
     }
 }
 
 impl<'a, D: nd::Dimension> From<FieldView<'a, D>> for FieldCow<'a, D> {
     fn from(value: FieldView<'a, D>) -> Self {
         let mut result: BTreeMap<ElementType, nd::CowArray<_, _>> = BTreeMap::new();
+        let location = value.1;
         for (elem_type, array) in value.0 {
             result.insert(elem_type, array.into());
         }
-        FieldCow::new(result)
+        FieldCow::new(result).with_location(location)
     }
 }
 
 impl<'a, D: nd::Dimension> From<FieldOwned<D>> for FieldCow<'a, D> {
     fn from(value: FieldOwned<D>) -> Self {
         let mut result: BTreeMap<ElementType, nd::CowArray<_, _>> = BTreeMap::new();
+        let location = value.1;
         for (elem_type, array) in value.0 {
             result.insert(elem_type, array.into());
         }
-        FieldCow::new(result)
+        FieldCow::new(result).with_location(location)
     }
 }
 
@@ -359,7 +721,7 @@ where
                 result.insert(*elem_type, sum_array.into_owned());
             }
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 }
 
@@ -380,7 +742,7 @@ where
                 result.insert(*elem_type, diff_array.into_owned());
             }
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 }
 
@@ -401,7 +763,7 @@ where
                 result.insert(*elem_type, prod_array.into_owned());
             }
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 }
 
@@ -422,7 +784,7 @@ where
                 result.insert(*elem_type, div_array.into_owned());
             }
         }
-        FieldOwned::new(result)
+        FieldOwned::new(result).with_location(self.1)
     }
 }
 
@@ -503,4 +865,61 @@ mod tests {
         assert_eq!(result[1], 4.0);
         assert_eq!(result[2], 6.0);
     }
+
+    #[test]
+    fn test_field_data_categorical() {
+        let data = FieldData::categorical(&["steel", "water", "steel"]);
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.kind(), "categorical");
+        assert_eq!(data.label(2), Some("steel"));
+        assert_eq!(data.label(3), None);
+        assert_eq!(data.labels().unwrap(), vec!["steel", "water", "steel"]);
+        match &data {
+            FieldData::Categorical { codes, categories } => {
+                assert_eq!(codes.to_vec(), vec![0, 1, 0]);
+                assert_eq!(categories.len(), 2);
+            }
+            _ => panic!("Expected categorical data"),
+        }
+    }
+
+    #[test]
+    fn test_field_data_select() {
+        let data = FieldData::Int(nd::arr1(&[4, 5, 6]).into_dyn().into_shared());
+        let selected = data.select(&[2, 0]);
+        assert_eq!(selected.len(), 2);
+        match selected {
+            FieldData::Int(arr) => assert_eq!(arr.iter().copied().collect::<Vec<_>>(), vec![6, 4]),
+            _ => panic!("Expected int data"),
+        }
+        let labels = FieldData::categorical(&["a", "b", "c"]).select(&[1]);
+        assert_eq!(labels.labels().unwrap(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_sparse_data() {
+        let data = SparseData {
+            indices: vec![1, 3],
+            values: nd::arr1(&[10.0, 30.0]).into_dyn().into_shared(),
+        };
+        let dense = data.densify(4, -1.0);
+        assert_eq!(
+            dense.iter().copied().collect::<Vec<_>>(),
+            vec![-1.0, 10.0, -1.0, 30.0]
+        );
+        let selected = data.select(&[3, 0, 1]);
+        assert_eq!(selected.indices, vec![0, 2]);
+        assert_eq!(
+            selected.values.iter().copied().collect::<Vec<_>>(),
+            vec![30.0, 10.0]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_field_duplicate_ids() {
+        let ids = BTreeMap::from([(ElementType::QUAD4, vec![1, 1])]);
+        let values = nd::arr1(&[1.0, 2.0]).into_dyn().into_shared();
+        SparseField::new(ids, BTreeMap::from([(ElementType::QUAD4, values)]));
+    }
 }
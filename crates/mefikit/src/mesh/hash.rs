@@ -0,0 +1,214 @@
+//! Stable fingerprint of the content of a mesh, for caching and change detection.
+
+use ndarray as nd;
+
+use super::connectivity::ConnectivityBase;
+use super::fields::{FieldData, FieldLocation};
+use super::umesh::UMeshBase;
+
+/// 128 bits FNV-1a hasher.
+///
+/// Unlike the hashers of the standard library, its output only depends on the hashed bytes, so
+/// digests can be stored and compared across runs, platforms and versions of the compiler.
+struct Fnv128(u128);
+
+impl Fnv128 {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    /// Hashes a float, all zeros and all NaN values being hashed the same.
+    fn f64(&mut self, value: f64) {
+        let value = match value {
+            v if v.is_nan() => f64::NAN,
+            0.0 => 0.0,
+            v => v,
+        };
+        self.u64(value.to_bits());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes(value.as_bytes());
+    }
+
+    fn usizes<'a>(&mut self, values: impl ExactSizeIterator<Item = &'a usize>) {
+        self.usize(values.len());
+        values.for_each(|&v| self.usize(v));
+    }
+
+    fn location(&mut self, location: FieldLocation) {
+        match location {
+            FieldLocation::Cells => self.usize(0),
+            FieldLocation::ElementNodes => self.usize(1),
+            FieldLocation::GaussPoints { order } => {
+                self.usize(2);
+                self.usize(order);
+            }
+        }
+    }
+
+    fn array<S, D>(&mut self, array: &nd::ArrayBase<S, D>)
+    where
+        S: nd::Data<Elem = f64>,
+        D: nd::Dimension,
+    {
+        self.usizes(array.shape().iter());
+        array.iter().for_each(|&v| self.f64(v));
+    }
+}
+
+impl<N, C, F, G> UMeshBase<N, C, F, G>
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    /// Computes a stable digest of the content of the mesh: coordinates, connectivities, fields
+    /// with their locations, and groups.
+    ///
+    /// The digest is the same across runs and platforms, for caching pipelines and change
+    /// detection. It depends on the order of the nodes and of the elements, but not on the
+    /// numbering of the families nor on the cache. With a `tolerance`, coordinates are rounded to
+    /// the nearest multiple of it, so that meshes whose nodes moved by less than the tolerance
+    /// usually keep their digest: nodes close to a rounding boundary may still change it.
+    pub fn content_hash(&self, tolerance: Option<f64>) -> u128 {
+        let mut h = Fnv128::new();
+        h.usizes(self.coords.shape().iter());
+        match tolerance {
+            Some(tol) => self
+                .coords
+                .iter()
+                .for_each(|&x| h.u64((x / tol).round() as i64 as u64)),
+            None => self.coords.iter().for_each(|&x| h.f64(x)),
+        }
+        h.usize(self.node_groups.len());
+        for (name, nodes) in &self.node_groups {
+            h.str(name);
+            h.usizes(nodes.iter());
+        }
+
+        h.usize(self.element_blocks.len());
+        for (et, block) in &self.element_blocks {
+            h.str(&format!("{et:?}"));
+            match &block.connectivity {
+                ConnectivityBase::Regular(conn) => {
+                    h.usizes(conn.shape().iter());
+                    conn.iter().for_each(|&n| h.usize(n));
+                }
+                ConnectivityBase::Poly(conn) => {
+                    h.usizes(conn.offsets.iter());
+                    h.usizes(conn.data.iter());
+                }
+            }
+            h.usize(block.fields.len());
+            for (name, values) in &block.fields {
+                h.str(name);
+                h.location(block.field_location(name));
+                h.array(values);
+            }
+            h.usize(block.typed_fields.len());
+            for (name, data) in &block.typed_fields {
+                h.str(name);
+                match data {
+                    FieldData::Int(values) => {
+                        h.usizes(values.shape().iter());
+                        values.iter().for_each(|&v| h.u64(v as u64));
+                    }
+                    FieldData::Bool(values) => {
+                        h.usizes(values.shape().iter());
+                        values.iter().for_each(|&v| h.bytes(&[v as u8]));
+                    }
+                    FieldData::Categorical { codes, categories } => {
+                        h.usize(codes.len());
+                        codes.iter().for_each(|&c| h.u64(c as u64));
+                        h.usize(categories.len());
+                        categories.iter().for_each(|c| h.str(c));
+                    }
+                }
+            }
+            h.usize(block.sparse_fields.len());
+            for (name, sparse) in &block.sparse_fields {
+                h.str(name);
+                h.usizes(sparse.indices.iter());
+                h.array(&sparse.values);
+            }
+            // Groups are hashed by their elements, whatever the families
+            h.usize(block.groups.len());
+            for (name, families) in &block.groups {
+                h.str(name);
+                let elements: Vec<usize> = block
+                    .families
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, f)| families.contains(*f))
+                    .map(|(i, _)| i)
+                    .collect();
+                h.usizes(elements.iter());
+            }
+        }
+        h.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{ElementIds, ElementType, FieldBase, FieldLocation};
+    use crate::mesh_examples as me;
+    use ndarray as nd;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_content_hash() {
+        let mesh = me::make_imesh_2d(2);
+        let hash = mesh.content_hash(None);
+        assert_eq!(me::make_imesh_2d(2).content_hash(None), hash);
+        assert_eq!(mesh.view().content_hash(None), hash);
+        assert_ne!(me::make_imesh_2d(3).content_hash(None), hash);
+
+        let mut moved = mesh.clone();
+        moved.coords_mut()[[4, 0]] += 1e-9;
+        assert_ne!(moved.content_hash(None), hash);
+        assert_eq!(
+            moved.content_hash(Some(1e-6)),
+            mesh.content_hash(Some(1e-6))
+        );
+
+        let mut grouped = mesh.clone();
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 1);
+        grouped.set_group("a", &ids);
+        let with_group = grouped.content_hash(None);
+        assert_ne!(with_group, hash);
+        grouped.set_group("b", &ids);
+        assert_ne!(grouped.content_hash(None), with_group);
+
+        // The same values at the cells or at the nodes of the elements
+        let values = nd::Array2::<f64>::ones((4, 4)).into_dyn().into_shared();
+        let field = FieldBase::new(BTreeMap::from([(ElementType::QUAD4, values)]));
+        let mut at_cells = mesh.clone();
+        at_cells.update_field("f", field.clone(), None);
+        let mut at_nodes = mesh.clone();
+        at_nodes.update_field("f", field.with_location(FieldLocation::ElementNodes), None);
+        assert_ne!(at_cells.content_hash(None), at_nodes.content_hash(None));
+    }
+}
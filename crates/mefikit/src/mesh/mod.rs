@@ -3,6 +3,9 @@
 //! This module provides the fundamental types for representing unstructured meshes,
 //! including connectivity, element blocks, fields, and the main [`UMesh`] type.
 
+mod block_mask;
+mod cache;
+mod compact;
 mod connectivity;
 mod dimension;
 mod element;
@@ -10,20 +13,32 @@ mod element_block;
 mod element_ids;
 mod element_ids_set;
 mod fields;
+mod hash;
 mod indirect_index;
+mod raw_parts;
+mod summary;
 mod umesh;
+mod umesh_f32;
 
+pub use block_mask::BlockMask;
+pub use cache::{ElementBox, MeshCache};
+pub use compact::{CompactBlock, CompactConnectivity, CompactUMesh};
 pub use connectivity::Connectivity;
 pub use dimension::Dimension;
 pub use element::{Element, ElementId, ElementLike, ElementMut, ElementType, Regularity};
 pub use element_ids::ElementIds;
 pub use element_ids_set::ElementIdsSet;
 pub use fields::{
-    FieldArc, FieldArcD, FieldBase, FieldCow, FieldCowD, FieldOwned, FieldOwnedD, FieldView,
-    FieldViewD,
+    FieldArc, FieldArcD, FieldBase, FieldCow, FieldCowD, FieldData, FieldLocation, FieldOwned,
+    FieldOwnedD, FieldView, FieldViewD, SparseData, SparseField,
 };
 pub use indirect_index::{
     IndirectIndexIntoIter, IndirectIndexIter, IndirectIndexIterMut, IndirectIndexOwned,
     IndirectIndexShared, IndirectIndexView,
 };
-pub use umesh::{UMesh, UMeshBase, UMeshView};
+pub use raw_parts::{BlockSlices, RawBlockDesc, RawValidation};
+pub use summary::MeshSummary;
+pub use umesh::{
+    FamilyIssue, FieldValues, GroupsMode, NameCollision, UMesh, UMeshBase, UMeshView, UMeshViewMut,
+};
+pub use umesh_f32::UMeshF32;
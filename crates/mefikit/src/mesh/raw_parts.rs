@@ -0,0 +1,286 @@
+//! Construction of mesh views over foreign memory, for FFI.
+
+use ndarray as nd;
+
+use super::element::{ElementType, Regularity};
+use super::umesh::UMeshView;
+
+/// An element block stored in foreign memory, for [`UMeshView::from_raw_parts`].
+///
+/// Regular blocks hold `num_elements` rows of node indices in `connectivity`. Poly blocks hold
+/// the concatenated node indices of their elements in `connectivity`, and the end of each
+/// element in `offsets`.
+#[derive(Clone, Copy, Debug)]
+pub struct RawBlockDesc {
+    pub element_type: ElementType,
+    pub num_elements: usize,
+    pub connectivity: *const usize,
+    pub connectivity_len: usize,
+    /// `num_elements` offsets for poly blocks, ignored (and possibly null) for regular ones.
+    pub offsets: *const usize,
+    /// `num_elements` families, or null for family 0 everywhere.
+    pub families: *const usize,
+}
+
+/// An element block borrowed from slices, for [`UMeshView::from_slices`].
+#[derive(Clone, Copy, Debug)]
+pub struct BlockSlices<'a> {
+    pub element_type: ElementType,
+    /// Node indices, row by row for regular blocks or concatenated for poly blocks.
+    pub connectivity: &'a [usize],
+    /// End of each element in `connectivity`, for poly blocks only.
+    pub offsets: Option<&'a [usize]>,
+    /// Family of each element, family 0 being used when `None`.
+    pub families: Option<&'a [usize]>,
+}
+
+/// How much of the foreign data is checked when a view is built over it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RawValidation {
+    /// Only the array lengths, which is needed to build the view.
+    #[default]
+    Lengths,
+    /// Also the node indices and the poly offsets, in linear time. Without it, invalid indices
+    /// make the algorithms panic later on.
+    Full,
+}
+
+/// Returns a slice over `len` values, allowing a null pointer for an empty array.
+///
+/// # Safety
+/// A non null `ptr` must point to `len` values valid for `'a`.
+unsafe fn raw_slice<'a, T>(ptr: *const T, len: usize, what: &str) -> Result<&'a [T], String> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(format!("Null pointer for {what}")),
+        (false, _) => Ok(unsafe { std::slice::from_raw_parts(ptr, len) }),
+    }
+}
+
+impl<'a> UMeshView<'a> {
+    /// Builds a view over coordinates and element blocks stored in foreign memory, typically
+    /// arrays given by C, C++ or Fortran code. Nothing is copied.
+    ///
+    /// `coords_ptr` points to `n_nodes * dim` coordinates, stored node by node.
+    ///
+    /// # Safety
+    /// All the non null pointers of the arguments must point to arrays of the announced length,
+    /// which must stay alive and unmodified for `'a`.
+    ///
+    /// # Errors
+    /// Returns an error if a pointer is null or if the lengths are inconsistent, and with
+    /// [`RawValidation::Full`] if a node index or an offset is invalid.
+    pub unsafe fn from_raw_parts(
+        coords_ptr: *const f64,
+        n_nodes: usize,
+        dim: usize,
+        blocks: &[RawBlockDesc],
+        validation: RawValidation,
+    ) -> Result<Self, String> {
+        let len = n_nodes.checked_mul(dim).ok_or("Too many coordinates")?;
+        let coords = unsafe { raw_slice(coords_ptr, len, "coordinates")? };
+        let blocks = blocks
+            .iter()
+            .map(|b| {
+                let et = b.element_type;
+                let families = match b.families.is_null() {
+                    true => None,
+                    false => Some(unsafe { raw_slice(b.families, b.num_elements, "families")? }),
+                };
+                let offsets = match et.regularity() {
+                    Regularity::Regular => {
+                        let n = et.num_nodes().unwrap_or(0);
+                        if b.num_elements.checked_mul(n) != Some(b.connectivity_len) {
+                            return Err(format!(
+                                "The {et:?} connectivity length {} does not match {} elements",
+                                b.connectivity_len, b.num_elements
+                            ));
+                        }
+                        None
+                    }
+                    Regularity::Poly => {
+                        Some(unsafe { raw_slice(b.offsets, b.num_elements, "offsets")? })
+                    }
+                };
+                Ok(BlockSlices {
+                    element_type: et,
+                    connectivity: unsafe {
+                        raw_slice(b.connectivity, b.connectivity_len, "connectivity")?
+                    },
+                    offsets,
+                    families,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Self::from_slices(coords, dim, &blocks, validation)
+    }
+
+    /// Builds a view over coordinates and element blocks given as slices. Nothing is copied.
+    ///
+    /// `coords` holds the coordinates node by node, `dim` values per node.
+    ///
+    /// # Errors
+    /// Returns an error if the lengths are inconsistent, and with [`RawValidation::Full`] if a
+    /// node index or an offset is invalid.
+    pub fn from_slices(
+        coords: &'a [f64],
+        dim: usize,
+        blocks: &[BlockSlices<'a>],
+        validation: RawValidation,
+    ) -> Result<Self, String> {
+        if !(1..=3).contains(&dim) {
+            return Err(format!("Invalid space dimension {dim}, expected 1, 2 or 3"));
+        }
+        if !coords.len().is_multiple_of(dim) {
+            return Err(format!(
+                "{} coordinates cannot be split in nodes of dimension {dim}",
+                coords.len()
+            ));
+        }
+        let n_nodes = coords.len() / dim;
+        let coords = nd::ArrayView2::from_shape((n_nodes, dim), coords).unwrap();
+        let mut mesh = UMeshView::new(coords);
+        for block in blocks {
+            let et = block.element_type;
+            if mesh.element_blocks.contains_key(&et) {
+                return Err(format!("Several {et:?} blocks"));
+            }
+            let conn = block.connectivity;
+            let num_elements = match (et.num_nodes(), block.offsets) {
+                (Some(n), _) if conn.len() % n == 0 => conn.len() / n,
+                (Some(n), _) => {
+                    return Err(format!(
+                        "The {et:?} connectivity length {} is not a multiple of {n}",
+                        conn.len()
+                    ));
+                }
+                (None, Some(offsets)) => {
+                    if offsets.last().copied().unwrap_or(0) != conn.len() {
+                        return Err(format!(
+                            "The last {et:?} offset must be the connectivity length"
+                        ));
+                    }
+                    offsets.len()
+                }
+                (None, None) => return Err(format!("Missing offsets of the {et:?} block")),
+            };
+            if block.families.is_some_and(|f| f.len() != num_elements) {
+                return Err(format!("Expected {num_elements} families for {et:?}"));
+            }
+            if validation == RawValidation::Full {
+                let separator = et == ElementType::PHED;
+                if let Some(&n) = conn
+                    .iter()
+                    .find(|&&n| n >= n_nodes && !(separator && n == usize::MAX))
+                {
+                    return Err(format!("Node {n} of {et:?} out of range"));
+                }
+                if block.offsets.is_some_and(|o| !o.is_sorted()) {
+                    return Err(format!("The {et:?} offsets are not sorted"));
+                }
+            }
+            let families = block.families.map(nd::ArrayView1::from);
+            match et.num_nodes() {
+                Some(n) => {
+                    let conn = nd::ArrayView2::from_shape((num_elements, n), conn).unwrap();
+                    mesh.add_regular_block(et, conn, families);
+                }
+                None => {
+                    let offsets = block.offsets.unwrap();
+                    mesh.add_poly_block(et, conn.into(), offsets.into());
+                    if let Some(families) = families {
+                        mesh.element_blocks.get_mut(&et).unwrap().families = families;
+                    }
+                }
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementLike;
+
+    #[test]
+    fn test_from_slices() {
+        let coords = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        let quads = [0, 1, 3, 2];
+        let pgon = [0, 1, 3, 0, 3, 2];
+        let families = [7, 8];
+        let blocks = [
+            BlockSlices {
+                element_type: ElementType::QUAD4,
+                connectivity: &quads,
+                offsets: None,
+                families: None,
+            },
+            BlockSlices {
+                element_type: ElementType::PGON,
+                connectivity: &pgon,
+                offsets: Some(&[3, 6]),
+                families: Some(&families),
+            },
+        ];
+        let mesh = UMeshView::from_slices(&coords, 2, &blocks, RawValidation::Full).unwrap();
+        assert_eq!(mesh.coords().as_ptr(), coords.as_ptr());
+        assert_eq!(mesh.num_elements(), 3);
+        let pgons: Vec<_> = mesh
+            .elements()
+            .filter(|e| e.element_type() == ElementType::PGON)
+            .collect();
+        assert_eq!(pgons[1].connectivity(), &[0, 3, 2]);
+        assert_eq!(*pgons[1].family, 8);
+        let quad = mesh.elements().next().unwrap();
+        assert_eq!((quad.element_type(), *quad.family), (ElementType::QUAD4, 0));
+
+        let bad = [BlockSlices {
+            connectivity: &[0, 1, 4, 2],
+            ..blocks[0]
+        }];
+        assert!(UMeshView::from_slices(&coords, 2, &bad, RawValidation::Lengths).is_ok());
+        assert!(UMeshView::from_slices(&coords, 2, &bad, RawValidation::Full).is_err());
+        let bad = [BlockSlices {
+            connectivity: &[0, 1, 3],
+            ..blocks[0]
+        }];
+        assert!(UMeshView::from_slices(&coords, 2, &bad, RawValidation::Lengths).is_err());
+        assert!(UMeshView::from_slices(&coords, 3, &[], RawValidation::Lengths).is_err());
+    }
+
+    #[test]
+    fn test_from_raw_parts() {
+        let coords = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        let tri = [0, 1, 2];
+        let desc = RawBlockDesc {
+            element_type: ElementType::TRI3,
+            num_elements: 1,
+            connectivity: tri.as_ptr(),
+            connectivity_len: 3,
+            offsets: std::ptr::null(),
+            families: std::ptr::null(),
+        };
+        let mesh = unsafe {
+            UMeshView::from_raw_parts(coords.as_ptr(), 3, 2, &[desc], RawValidation::Full)
+        }
+        .unwrap();
+        assert_eq!(mesh.num_elements(), 1);
+        let null = RawBlockDesc {
+            connectivity: std::ptr::null(),
+            ..desc
+        };
+        let res = unsafe {
+            UMeshView::from_raw_parts(coords.as_ptr(), 3, 2, &[null], RawValidation::Lengths)
+        };
+        assert!(res.is_err());
+        let two = RawBlockDesc {
+            num_elements: 2,
+            ..desc
+        };
+        let res = unsafe {
+            UMeshView::from_raw_parts(coords.as_ptr(), 3, 2, &[two], RawValidation::Lengths)
+        };
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! Short description of a mesh, for printing.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ndarray as nd;
+
+use super::dimension::Dimension;
+use super::element::ElementType;
+use super::umesh::UMeshBase;
+
+/// Counts, bounding box, fields of every kind and groups of a mesh, without its arrays.
+///
+/// Computing it is linear in the number of nodes and elements, and allocates nothing of that
+/// size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshSummary {
+    pub num_nodes: usize,
+    pub space_dimension: usize,
+    /// Number of elements of each block.
+    pub blocks: BTreeMap<ElementType, usize>,
+    /// Number of elements of each dimension.
+    pub dimensions: BTreeMap<Dimension, usize>,
+    /// Minimum and maximum node coordinates, `None` for a mesh without nodes.
+    pub bounding_box: Option<(Vec<f64>, Vec<f64>)>,
+    /// Shape of the values of each float field, per element type holding it.
+    pub fields: BTreeMap<String, BTreeMap<ElementType, Vec<usize>>>,
+    /// Kind of the data of each typed field (`int`, `bool` or `categorical`), per element type
+    /// holding it.
+    pub typed_fields: BTreeMap<String, BTreeMap<ElementType, &'static str>>,
+    /// Number of elements holding a value of each sparse field, per element type.
+    pub sparse_fields: BTreeMap<String, BTreeMap<ElementType, usize>>,
+    /// Number of elements of each group.
+    pub groups: BTreeMap<String, usize>,
+    /// Number of nodes of each node group.
+    pub node_groups: BTreeMap<String, usize>,
+}
+
+impl MeshSummary {
+    pub fn num_elements(&self) -> usize {
+        self.blocks.values().sum()
+    }
+}
+
+impl<N, C, F, G> UMeshBase<N, C, F, G>
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    /// Returns the counts, bounding box, fields and groups of the mesh.
+    pub fn summary(&self) -> MeshSummary {
+        let coords = &self.coords;
+        let bounding_box = (coords.nrows() > 0).then(|| {
+            let min = coords.fold_axis(nd::Axis(0), f64::INFINITY, |&a, &b| a.min(b));
+            let max = coords.fold_axis(nd::Axis(0), f64::NEG_INFINITY, |&a, &b| a.max(b));
+            (min.to_vec(), max.to_vec())
+        });
+
+        let mut dimensions = BTreeMap::new();
+        let mut fields: BTreeMap<String, BTreeMap<ElementType, Vec<usize>>> = BTreeMap::new();
+        let mut typed_fields: BTreeMap<String, BTreeMap<ElementType, &'static str>> =
+            BTreeMap::new();
+        let mut sparse_fields: BTreeMap<String, BTreeMap<ElementType, usize>> = BTreeMap::new();
+        let mut groups = BTreeMap::new();
+        for (&et, block) in &self.element_blocks {
+            *dimensions.entry(et.dimension()).or_insert(0) += block.len();
+            for (name, values) in &block.fields {
+                let shape = values.shape()[1..].to_vec();
+                fields.entry(name.clone()).or_default().insert(et, shape);
+            }
+            for (name, data) in &block.typed_fields {
+                typed_fields
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(et, data.kind());
+            }
+            for (name, data) in &block.sparse_fields {
+                let count = data.indices.len();
+                sparse_fields
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(et, count);
+            }
+            if block.groups.is_empty() {
+                continue;
+            }
+            let mut family_sizes: BTreeMap<usize, usize> = BTreeMap::new();
+            for &f in &block.families {
+                *family_sizes.entry(f).or_insert(0) += 1;
+            }
+            for (name, families) in &block.groups {
+                let size: usize = families.iter().filter_map(|f| family_sizes.get(f)).sum();
+                *groups.entry(name.clone()).or_insert(0) += size;
+            }
+        }
+
+        MeshSummary {
+            num_nodes: coords.nrows(),
+            space_dimension: coords.ncols(),
+            blocks: self
+                .element_blocks
+                .iter()
+                .map(|(&et, block)| (et, block.len()))
+                .collect(),
+            dimensions,
+            bounding_box,
+            fields,
+            typed_fields,
+            sparse_fields,
+            groups,
+            node_groups: self
+                .node_groups
+                .iter()
+                .map(|(name, nodes)| (name.clone(), nodes.len()))
+                .collect(),
+        }
+    }
+}
+
+/// One line with the counts of nodes and elements per type, and with the alternate flag (`{:#}`)
+/// one line per element type, then the bounding box, fields and groups.
+impl fmt::Display for MeshSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "UMesh: {} nodes in {}D, {} elements",
+            self.num_nodes,
+            self.space_dimension,
+            self.num_elements()
+        )?;
+        if !f.alternate() {
+            let blocks: Vec<String> = self
+                .blocks
+                .iter()
+                .map(|(et, count)| format!("{et:?}: {count}"))
+                .collect();
+            return match blocks.is_empty() {
+                true => Ok(()),
+                false => write!(f, " ({})", blocks.join(", ")),
+            };
+        }
+        writeln!(f)?;
+        if let Some((min, max)) = &self.bounding_box {
+            writeln!(f, "  bounding box: {min:?} - {max:?}")?;
+        }
+        for (dim, count) in &self.dimensions {
+            writeln!(f, "  {}D: {count} elements", u8::from(*dim))?;
+            for (et, count) in self.blocks.iter().filter(|(et, _)| et.dimension() == *dim) {
+                writeln!(f, "    {et:?}: {count}")?;
+            }
+        }
+        if !self.fields.is_empty() {
+            writeln!(f, "  fields:")?;
+            for (name, shapes) in &self.fields {
+                let shapes: Vec<String> = shapes
+                    .iter()
+                    .map(|(et, shape)| format!("{et:?} {shape:?}"))
+                    .collect();
+                writeln!(f, "    {name}: {}", shapes.join(", "))?;
+            }
+        }
+        if !self.typed_fields.is_empty() {
+            writeln!(f, "  typed fields:")?;
+            for (name, kinds) in &self.typed_fields {
+                let kinds: Vec<String> = kinds
+                    .iter()
+                    .map(|(et, kind)| format!("{et:?} {kind}"))
+                    .collect();
+                writeln!(f, "    {name}: {}", kinds.join(", "))?;
+            }
+        }
+        if !self.sparse_fields.is_empty() {
+            writeln!(f, "  sparse fields:")?;
+            for (name, counts) in &self.sparse_fields {
+                let counts: Vec<String> = counts
+                    .iter()
+                    .map(|(et, count)| format!("{et:?} on {count}"))
+                    .collect();
+                writeln!(f, "    {name}: {}", counts.join(", "))?;
+            }
+        }
+        for (title, sizes) in [("groups", &self.groups), ("node groups", &self.node_groups)] {
+            if sizes.is_empty() {
+                continue;
+            }
+            writeln!(f, "  {title}:")?;
+            for (name, size) in sizes {
+                writeln!(f, "    {name}: {size}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the [`MeshSummary`] of the mesh instead of its arrays, `{:#}` giving the details.
+impl<N, C, F, G> fmt::Display for UMeshBase<N, C, F, G>
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.summary(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementIds, FieldData, SparseField};
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_summary() {
+        let mut mesh = me::make_mesh_2d_multi();
+        let values = nd::Array2::<f64>::zeros((1, 2)).into_dyn().into_shared();
+        mesh.add_field(ElementType::QUAD4, "v", values);
+        let ids = ElementIds::from(BTreeMap::from([(ElementType::SEG2, vec![0, 1])]));
+        mesh.set_group("edges", &ids);
+        mesh.set_node_group("corner", [4]);
+        let rank = |r: i64| FieldData::Int(nd::arr1(&[r]).into_dyn().into_shared());
+        let rank = BTreeMap::from([(ElementType::QUAD4, rank(1)), (ElementType::PGON, rank(2))]);
+        mesh.update_typed_field("rank", rank, None);
+        let sensor = SparseField::new(
+            BTreeMap::from([(ElementType::SEG2, vec![1])]),
+            BTreeMap::from([(ElementType::SEG2, nd::arr1(&[3.0]).into_dyn().into_shared())]),
+        );
+        mesh.update_sparse_field("sensor", sensor);
+
+        let summary = mesh.summary();
+        assert_eq!(summary.num_elements(), 4);
+        assert_eq!(summary.dimensions[&Dimension::D2], 2);
+        assert_eq!(summary.blocks[&ElementType::SEG2], 2);
+        assert_eq!(summary.bounding_box, Some((vec![0.0, 0.0], vec![1.5, 1.0])));
+        assert_eq!(summary.fields["v"][&ElementType::QUAD4], [2]);
+        assert_eq!(summary.typed_fields["rank"][&ElementType::QUAD4], "int");
+        assert_eq!(summary.sparse_fields["sensor"][&ElementType::SEG2], 1);
+        assert_eq!(summary.groups["edges"], 2);
+        assert_eq!(summary.node_groups["corner"], 1);
+
+        assert_eq!(
+            mesh.to_string(),
+            "UMesh: 5 nodes in 2D, 4 elements (SEG2: 2, QUAD4: 1, PGON: 1)"
+        );
+        let text = format!("{mesh:#}");
+        assert!(text.starts_with("UMesh: 5 nodes in 2D, 4 elements\n"));
+        assert!(text.contains("    QUAD4: 1\n"));
+        assert!(text.contains("    v: QUAD4 [2]\n"));
+        assert!(text.contains("  typed fields:\n    rank: QUAD4 int, PGON int\n"));
+        assert!(text.contains("  sparse fields:\n    sensor: SEG2 on 1\n"));
+        assert!(text.contains("  groups:\n    edges: 2\n"));
+    }
+}
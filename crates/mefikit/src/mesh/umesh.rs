@@ -1,20 +1,26 @@
-use crate::mesh::{ElementLike, FieldBase, FieldView};
+use crate::mesh::{
+    FieldBase, FieldData, FieldLocation, FieldOwnedD, FieldView, SparseData, SparseField,
+};
 
 use super::dimension::Dimension;
 use super::element::{Element, ElementId, ElementMut, ElementType, Regularity};
 use super::element_ids::ElementIds;
+use crate::tools::{MeshSelect, Selection};
 
 use derive_where::derive_where;
 use ndarray::{self as nd};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use rustc_hash::FxHashSet;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 
-use super::connectivity::ConnectivityBase;
+use super::cache::MeshCache;
+use super::connectivity::{Connectivity, ConnectivityBase};
 use super::element_block::{
-    ElementBlock, ElementBlockBase, ElementBlockView, IntoElementBlockEntry,
+    ElementBlock, ElementBlockBase, ElementBlockView, ElementBlockViewMut, IntoElementBlockEntry,
 };
+use super::indirect_index::IndirectIndex;
 
 /// An unstrustured mesh.
 ///
@@ -32,6 +38,104 @@ where
 {
     pub(crate) coords: nd::ArrayBase<N, nd::Ix2>,
     pub(crate) element_blocks: BTreeMap<ElementType, ElementBlockBase<C, F, G>>,
+    /// Named sets of node indices (boundary conditions are often defined on nodes).
+    #[serde(default)]
+    pub(crate) node_groups: BTreeMap<String, BTreeSet<usize>>,
+    /// Derived data computed on demand, see [`MeshCache`].
+    #[derive_where(skip)]
+    #[serde(skip)]
+    pub(crate) cache: Arc<MeshCache>,
+}
+
+/// How elements are gathered from several groups.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GroupsMode {
+    /// Elements belonging to at least one of the groups.
+    Any,
+    /// Elements belonging to all the groups.
+    All,
+}
+
+/// How names of groups, node groups and fields already used in a mesh are handled when another
+/// mesh is appended to it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NameCollision {
+    /// Groups with the same name are merged and fields with the same name are concatenated.
+    #[default]
+    Merge,
+    /// Groups and fields of the appended mesh are dropped if their name is already used.
+    Keep,
+    /// Groups and fields of the appended mesh are renamed with a `_1`, `_2`, ... suffix if their
+    /// name is already used.
+    Rename,
+}
+
+impl NameCollision {
+    /// Maps each of the `names` of the appended mesh to its new name, or to `None` if it is
+    /// dropped. `taken` are the names used by the mesh appended to.
+    fn resolve<'n>(
+        self,
+        taken: BTreeSet<String>,
+        names: impl IntoIterator<Item = &'n String>,
+    ) -> BTreeMap<String, Option<String>> {
+        let names: BTreeSet<&String> = names.into_iter().collect();
+        let mut used: BTreeSet<String> =
+            taken.iter().chain(names.iter().copied()).cloned().collect();
+        names
+            .into_iter()
+            .map(|name| {
+                let new = match (self, taken.contains(name)) {
+                    (_, false) | (NameCollision::Merge, true) => Some(name.clone()),
+                    (NameCollision::Keep, true) => None,
+                    (NameCollision::Rename, true) => {
+                        let new = (1..)
+                            .map(|k| format!("{name}_{k}"))
+                            .find(|n| !used.contains(n))
+                            .unwrap();
+                        used.insert(new.clone());
+                        Some(new)
+                    }
+                };
+                (name.clone(), new)
+            })
+            .collect()
+    }
+}
+
+/// Values assigned to the selected elements by [`UMesh::assign_field_on`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValues {
+    /// The same value, scalar or not, for every element.
+    Uniform(nd::ArrayD<f64>),
+    /// One value per element, in the order of the selected element ids.
+    PerElement(nd::ArrayD<f64>),
+}
+
+impl From<f64> for FieldValues {
+    fn from(value: f64) -> Self {
+        FieldValues::Uniform(nd::arr0(value).into_dyn())
+    }
+}
+
+/// An inconsistency of the families of a block, reported by [`UMeshBase::check_families`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FamilyIssue {
+    /// The families array does not have one entry per element.
+    LengthMismatch {
+        element_type: ElementType,
+        families: usize,
+        elements: usize,
+    },
+    /// Several families belong to exactly the same groups and should be merged.
+    DuplicateFamilies {
+        element_type: ElementType,
+        families: Vec<usize>,
+    },
+    /// A family is referenced by a group but used by no element.
+    UnusedFamily {
+        element_type: ElementType,
+        family: usize,
+    },
 }
 
 /// An owned unstructured mesh with reference-counted data.
@@ -50,6 +154,17 @@ pub type UMeshView<'a> = UMeshBase<
     nd::ViewRepr<&'a usize>,
 >;
 
+/// A view into an unstructured mesh whose coordinates and fields may be mutated in place.
+///
+/// Connectivities, families and groups are borrowed read-only, so the topology of the mesh cannot
+/// change.
+pub type UMeshViewMut<'a> = UMeshBase<
+    nd::ViewRepr<&'a mut f64>,
+    nd::ViewRepr<&'a usize>,
+    nd::ViewRepr<&'a mut f64>,
+    nd::ViewRepr<&'a usize>,
+>;
+
 impl<N, C, F, G> UMeshBase<N, C, F, G>
 where
     N: nd::Data<Elem = f64>,
@@ -76,12 +191,20 @@ where
                     view.add_poly_block(et, conn.data.view(), conn.offsets.view())
                 }
             };
-            view.element_blocks.get_mut(&et).unwrap().fields = block
+            let view_block = view.element_blocks.get_mut(&et).unwrap();
+            view_block.fields = block
                 .fields
                 .iter()
                 .map(|(k, v)| (k.clone(), v.view()))
                 .collect();
+            view_block.field_locations = block.field_locations.clone();
+            view_block.typed_fields = block.typed_fields.clone();
+            view_block.sparse_fields = block.sparse_fields.clone();
+            view_block.families = block.families.view();
+            view_block.groups = block.groups.clone();
         }
+        view.node_groups = self.node_groups.clone();
+        view.cache = self.cache.clone();
         view
     }
 
@@ -256,6 +379,26 @@ where
         self.element_blocks.get(&element_type)
     }
 
+    /// Returns a view of the values of a float field on the block of the given element type.
+    ///
+    /// Unlike [`Self::field`], no field map over the blocks of a dimension is built.
+    pub fn block_field(&self, et: ElementType, name: &str) -> Option<nd::ArrayViewD<'_, f64>> {
+        self.element_blocks.get(&et)?.field_view(name)
+    }
+
+    /// Returns one component of a float field on the block of the given element type.
+    ///
+    /// Fields with one value per element only have component `0`. Returns `None` if the field
+    /// does not exist, has more than one axis of components or has no such component.
+    pub fn block_field_column(
+        &self,
+        et: ElementType,
+        name: &str,
+        component: usize,
+    ) -> Option<nd::ArrayView1<'_, f64>> {
+        self.element_blocks.get(&et)?.field_column(name, component)
+    }
+
     /// Returns a sorted list of node indices that are referenced by elements.
     pub fn used_nodes(&self) -> Vec<usize> {
         let mut used_nodes = FxHashSet::default();
@@ -296,7 +439,17 @@ where
             .filter(|et| et.dimension() == dim)
             .map(|et| (*et, self.element_blocks[et].fields[name].view()))
             .collect();
-        Some(FieldBase::new(field_map))
+        let location = self.field_location(name, dim);
+        Some(FieldBase::new(field_map).with_location(location))
+    }
+
+    /// Returns where the values of a field are located, cells by default.
+    fn field_location(&self, name: &str, dim: Dimension) -> FieldLocation {
+        self.element_blocks
+            .iter()
+            .find(|(et, _)| et.dimension() == dim)
+            .map(|(_, b)| b.field_location(name))
+            .unwrap_or_default()
     }
 
     /// Get a view of a field if it exists in mesh.
@@ -342,21 +495,16 @@ where
         if !field_ok {
             return None;
         }
+        let location = self.field_location(name, dim);
         let field_map: BTreeMap<_, _> = etypes
             .into_iter()
             .map(|et| {
-                (
-                    et,
-                    self.element_blocks
-                        .get_mut(&et)
-                        .unwrap()
-                        .fields
-                        .remove(name)
-                        .unwrap(),
-                )
+                let block = self.element_blocks.get_mut(&et).unwrap();
+                block.field_locations.remove(name);
+                (et, block.fields.remove(name).unwrap())
             })
             .collect();
-        Some(FieldBase::new(field_map))
+        Some(FieldBase::new(field_map).with_location(location))
     }
 
     /// Replaces an existing field with new data.
@@ -383,6 +531,14 @@ where
         let field_replaced = etypes
             .iter()
             .all(|et| self.element_blocks[et].fields.contains_key(name));
+        let old_location = self.field_location(name, dim);
+        for et in &etypes {
+            let locations = &mut self.element_blocks.get_mut(et).unwrap().field_locations;
+            match field.location() {
+                FieldLocation::Cells => locations.remove(name),
+                location => locations.insert(name.to_owned(), location),
+            };
+        }
         if !field_replaced {
             for et in etypes {
                 self.element_blocks
@@ -407,7 +563,420 @@ where
                 )
             })
             .collect();
-        Some(FieldBase::new(old_field_map))
+        Some(FieldBase::new(old_field_map).with_location(old_location))
+    }
+
+    /// Get an integer, boolean or categorical field if it exists in mesh.
+    ///
+    /// As for [`Self::field`], the field is searched at the higher topological dimension of the
+    /// mesh by default.
+    pub fn typed_field(
+        &self,
+        name: &str,
+        dim: Option<Dimension>,
+    ) -> Option<BTreeMap<ElementType, &FieldData>> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self.topological_dimension()?,
+        };
+        self.element_blocks
+            .iter()
+            .filter(|(et, _)| et.dimension() == dim)
+            .map(|(et, b)| b.typed_fields.get(name).map(|f| (*et, f)))
+            .collect()
+    }
+
+    /// Returns the names of the integer, boolean and categorical fields with their dimension.
+    pub fn typed_field_names(&self) -> Vec<(String, Dimension)> {
+        let names: FxHashSet<(String, Dimension)> = self
+            .blocks()
+            .flat_map(|(et, b)| {
+                b.typed_fields
+                    .keys()
+                    .cloned()
+                    .zip(std::iter::repeat(et.dimension()))
+            })
+            .collect();
+        let mut names: Vec<_> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    /// Removes an integer, boolean or categorical field from the mesh at the given dimension.
+    ///
+    /// Returns the removed field if it existed, or `None` if the field was not found.
+    pub fn remove_typed_field(
+        &mut self,
+        name: &str,
+        dim: Option<Dimension>,
+    ) -> Option<BTreeMap<ElementType, FieldData>> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self.topological_dimension()?,
+        };
+        self.typed_field(name, Some(dim))?;
+        Some(
+            self.element_blocks
+                .iter_mut()
+                .filter(|(et, _)| et.dimension() == dim)
+                .map(|(et, b)| (*et, b.typed_fields.remove(name).unwrap()))
+                .collect(),
+        )
+    }
+
+    /// Inserts or replaces an integer, boolean or categorical field.
+    ///
+    /// The field must be given for every element type of the target dimension. Returns the old
+    /// field if it existed.
+    ///
+    /// # Panics
+    /// Panics if an element type of the target dimension is missing in `field` or if the data
+    /// length does not match the block length.
+    pub fn update_typed_field(
+        &mut self,
+        name: &str,
+        mut field: BTreeMap<ElementType, FieldData>,
+        dim: Option<Dimension>,
+    ) -> Option<BTreeMap<ElementType, FieldData>> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self
+                .topological_dimension()
+                .expect("This mesh should not be empty"),
+        };
+        let old = self.remove_typed_field(name, Some(dim));
+        for (et, block) in self
+            .element_blocks
+            .iter_mut()
+            .filter(|(et, _)| et.dimension() == dim)
+        {
+            let data = field
+                .remove(et)
+                .unwrap_or_else(|| panic!("Field {name} is missing element type {et:?}"));
+            assert_eq!(
+                data.len(),
+                block.len(),
+                "Field {name} length does not match block {et:?} length"
+            );
+            block.typed_fields.insert(name.to_owned(), data);
+        }
+        old
+    }
+
+    /// Returns the names of all element groups, sorted.
+    pub fn group_names(&self) -> Vec<String> {
+        let names: BTreeSet<&String> = self
+            .element_blocks
+            .values()
+            .flat_map(|b| b.groups.keys())
+            .collect();
+        names.into_iter().cloned().collect()
+    }
+
+    /// Returns the ids of the elements belonging to a group.
+    ///
+    /// Element types with no element in the group are omitted.
+    pub fn group_as_element_ids(&self, name: &str) -> ElementIds {
+        let mut ids = ElementIds::new();
+        for (et, block) in &self.element_blocks {
+            let Some(fams) = block.groups.get(name) else {
+                continue;
+            };
+            let indices: Vec<usize> = block
+                .families
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| fams.contains(*f))
+                .map(|(i, _)| i)
+                .collect();
+            if !indices.is_empty() {
+                ids.add_block(*et, indices);
+            }
+        }
+        ids
+    }
+
+    /// Checks that families form a minimal partition of the elements by group membership.
+    ///
+    /// Returns the list of inconsistencies found, empty if the families are consistent. They can
+    /// be repaired with [`UMesh::recompute_families`].
+    pub fn check_families(&self) -> Vec<FamilyIssue> {
+        let mut issues = Vec::new();
+        for (&et, block) in &self.element_blocks {
+            if block.families.len() != block.len() {
+                issues.push(FamilyIssue::LengthMismatch {
+                    element_type: et,
+                    families: block.families.len(),
+                    elements: block.len(),
+                });
+                continue;
+            }
+            let used: BTreeSet<usize> = block.families.iter().copied().collect();
+            let referenced: BTreeSet<usize> = block.groups.values().flatten().copied().collect();
+            for &family in referenced.difference(&used) {
+                issues.push(FamilyIssue::UnusedFamily {
+                    element_type: et,
+                    family,
+                });
+            }
+            let mut by_groups: BTreeMap<Vec<&String>, Vec<usize>> = BTreeMap::new();
+            for &f in &used {
+                let groups = block
+                    .groups
+                    .iter()
+                    .filter(|(_, fams)| fams.contains(&f))
+                    .map(|(g, _)| g)
+                    .collect();
+                by_groups.entry(groups).or_default().push(f);
+            }
+            for families in by_groups.into_values().filter(|fams| fams.len() > 1) {
+                issues.push(FamilyIssue::DuplicateFamilies {
+                    element_type: et,
+                    families,
+                });
+            }
+        }
+        issues
+    }
+
+    /// Returns the nodes of a node group if it exists.
+    pub fn node_group(&self, name: &str) -> Option<&BTreeSet<usize>> {
+        self.node_groups.get(name)
+    }
+
+    /// Returns the names of all node groups.
+    pub fn node_group_names(&self) -> impl Iterator<Item = &String> {
+        self.node_groups.keys()
+    }
+
+    /// Inserts or replaces a node group.
+    ///
+    /// Returns the old group if it existed.
+    ///
+    /// # Panics
+    /// Panics if a node index is out of the coordinates array.
+    pub fn set_node_group(
+        &mut self,
+        name: &str,
+        nodes: impl IntoIterator<Item = usize>,
+    ) -> Option<BTreeSet<usize>> {
+        let n_nodes = self.coords.nrows();
+        let nodes: BTreeSet<usize> = nodes.into_iter().collect();
+        if let Some(&last) = nodes.last() {
+            assert!(
+                last < n_nodes,
+                "Node group {name} has nodes out of the mesh"
+            );
+        }
+        self.node_groups.insert(name.to_owned(), nodes)
+    }
+
+    /// Removes a node group, returning its nodes if it existed.
+    pub fn remove_node_group(&mut self, name: &str) -> Option<BTreeSet<usize>> {
+        self.node_groups.remove(name)
+    }
+
+    /// Renumbers the nodes of all node groups.
+    ///
+    /// `new_id` gives the new index of each node, or `None` if the node is removed. This must be
+    /// called by any operation renumbering, merging or removing nodes.
+    pub(crate) fn renumber_node_groups<M>(&mut self, new_id: M)
+    where
+        M: Fn(usize) -> Option<usize>,
+    {
+        for nodes in self.node_groups.values_mut() {
+            *nodes = nodes.iter().filter_map(|&n| new_id(n)).collect();
+        }
+    }
+
+    /// Get a field defined on a selection of elements if it exists in mesh.
+    ///
+    /// As for [`Self::field`], the field is searched at the higher topological dimension of the
+    /// mesh by default.
+    pub fn sparse_field(&self, name: &str, dim: Option<Dimension>) -> Option<SparseField> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self.topological_dimension()?,
+        };
+        let blocks: BTreeMap<_, _> = self
+            .element_blocks
+            .iter()
+            .filter(|(et, _)| et.dimension() == dim)
+            .filter_map(|(et, b)| b.sparse_fields.get(name).map(|f| (*et, f.clone())))
+            .collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        Some(SparseField::from_blocks(blocks))
+    }
+
+    /// Removes a field defined on a selection of elements from the mesh.
+    ///
+    /// Returns the removed field if it existed, or `None` if the field was not found.
+    pub fn remove_sparse_field(
+        &mut self,
+        name: &str,
+        dim: Option<Dimension>,
+    ) -> Option<SparseField> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self.topological_dimension()?,
+        };
+        let blocks: BTreeMap<_, _> = self
+            .element_blocks
+            .iter_mut()
+            .filter(|(et, _)| et.dimension() == dim)
+            .filter_map(|(et, b)| b.sparse_fields.remove(name).map(|f| (*et, f)))
+            .collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        Some(SparseField::from_blocks(blocks))
+    }
+
+    /// Inserts or replaces a field defined on a selection of elements.
+    ///
+    /// The field dimension is the one of its element types. Returns the old field if it existed.
+    ///
+    /// # Panics
+    /// Panics if the field references an element type absent from the mesh or an element index
+    /// out of its block.
+    pub fn update_sparse_field(&mut self, name: &str, field: SparseField) -> Option<SparseField> {
+        let dim = field.ids.0.keys().next()?.dimension();
+        let old = self.remove_sparse_field(name, Some(dim));
+        for (et, data) in field.into_blocks() {
+            let block = self
+                .element_blocks
+                .get_mut(&et)
+                .unwrap_or_else(|| panic!("Element type {et:?} is not in the mesh"));
+            assert!(
+                data.indices.iter().all(|&i| i < block.len()),
+                "Field {name} has indices out of block {et:?}"
+            );
+            block.sparse_fields.insert(name.to_owned(), data);
+        }
+        old
+    }
+
+    /// Returns a field defined on a selection of elements as a full field.
+    ///
+    /// Elements out of the selection take the `fill` value. This is what formats requiring full
+    /// arrays use when exporting sparse fields.
+    pub fn densify_sparse_field(
+        &self,
+        name: &str,
+        fill: f64,
+        dim: Option<Dimension>,
+    ) -> Option<FieldOwnedD> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self.topological_dimension()?,
+        };
+        let field = self.sparse_field(name, Some(dim))?;
+        let trailing = field.values.values().next()?.shape()[1..].to_vec();
+        let blocks = field.into_blocks();
+        Some(FieldBase::new(
+            self.element_blocks
+                .iter()
+                .filter(|(et, _)| et.dimension() == dim)
+                .map(|(et, b)| {
+                    let dense = match blocks.get(et) {
+                        Some(data) => data.densify(b.len(), fill),
+                        None => {
+                            let mut shape = vec![b.len()];
+                            shape.extend_from_slice(&trailing);
+                            nd::ArrayD::from_elem(shape, fill)
+                        }
+                    };
+                    (*et, dense)
+                })
+                .collect(),
+        ))
+    }
+
+    /// Extracts a sub-mesh from this mesh, or view, based on the provided element IDs.
+    ///
+    /// This method creates a new `UMesh`, owning its data (with copy) containing only the elements
+    /// specified by the IDs. Coordinates are copied, unless they are already shared as in a
+    /// [`UMesh`], see [`UMesh::extract`].
+    /// This method is low level and error prone in the case where `ElementsIds` are not directly
+    /// issued from a Selector. Please use Selector API if possible.
+    pub fn extract_to_owned(&self, ids: &ElementIds, with_fields: bool) -> UMesh {
+        let mut extracted = UMesh::new(self.coords.to_shared());
+        extracted.node_groups = self.node_groups.clone();
+        // TODO: conditionnaly extract fields
+        for (t, block) in ids.iter_blocks() {
+            if !self.element_blocks.contains_key(t) {
+                continue;
+            }
+            match &self.element_blocks[t] {
+                ElementBlockBase {
+                    connectivity: ConnectivityBase::Regular(arr),
+                    fields,
+                    ..
+                } => extracted.add_regular_block(
+                    *t,
+                    arr.select(nd::Axis(0), block.as_slice()).into_shared(),
+                    match with_fields {
+                        true => Some(
+                            fields
+                                .iter()
+                                .map(|(n, f)| {
+                                    (
+                                        n.clone(),
+                                        f.select(nd::Axis(0), block.as_slice()).into_shared(),
+                                    )
+                                })
+                                .collect(),
+                        ),
+                        false => None,
+                    },
+                ),
+                ElementBlockBase {
+                    connectivity: ConnectivityBase::Poly(conn),
+                    fields,
+                    ..
+                } => {
+                    let mut data = Vec::new();
+                    let mut offsets = Vec::with_capacity(block.len());
+                    for &i in block {
+                        data.extend_from_slice(&conn[i]);
+                        offsets.push(data.len());
+                    }
+                    extracted.add_poly_block(
+                        *t,
+                        nd::ArcArray1::from(data),
+                        nd::ArcArray1::from(offsets),
+                    );
+                    if with_fields {
+                        extracted.element_blocks.get_mut(t).unwrap().fields = fields
+                            .iter()
+                            .map(|(n, f)| {
+                                (
+                                    n.clone(),
+                                    f.select(nd::Axis(0), block.as_slice()).into_shared(),
+                                )
+                            })
+                            .collect();
+                    }
+                }
+            };
+            if with_fields {
+                extracted.element_blocks.get_mut(t).unwrap().field_locations =
+                    self.element_blocks[t].field_locations.clone();
+                extracted.element_blocks.get_mut(t).unwrap().typed_fields = self.element_blocks[t]
+                    .typed_fields
+                    .iter()
+                    .map(|(n, f)| (n.clone(), f.select(block.as_slice())))
+                    .collect();
+                extracted.element_blocks.get_mut(t).unwrap().sparse_fields = self.element_blocks[t]
+                    .sparse_fields
+                    .iter()
+                    .map(|(n, f)| (n.clone(), f.select(block.as_slice())))
+                    .collect();
+            }
+        }
+        extracted
     }
 }
 
@@ -417,6 +986,8 @@ impl<'a> UMeshView<'a> {
         Self {
             coords,
             element_blocks: BTreeMap::new(),
+            node_groups: BTreeMap::new(),
+            cache: Arc::default(),
         }
     }
 
@@ -425,13 +996,25 @@ impl<'a> UMeshView<'a> {
         let mut umesh = UMesh::new(self.coords.to_shared());
         for (&et, eb) in &self.element_blocks {
             match &eb.connectivity {
-                // TODO: pass fields and families
                 ConnectivityBase::Regular(r) => umesh.add_regular_block(et, r.to_shared(), None),
                 ConnectivityBase::Poly(conn) => {
                     umesh.add_poly_block(et, conn.data.to_shared(), conn.offsets.to_shared())
                 }
             }
+            let block = umesh.element_blocks.get_mut(&et).unwrap();
+            block.fields = eb
+                .fields
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_shared()))
+                .collect();
+            block.field_locations = eb.field_locations.clone();
+            block.typed_fields = eb.typed_fields.clone();
+            block.sparse_fields = eb.sparse_fields.clone();
+            block.families = eb.families.to_shared();
+            block.groups = eb.groups.clone();
         }
+        umesh.node_groups = self.node_groups.clone();
+        umesh.cache = self.cache.clone();
         umesh
     }
 
@@ -442,6 +1025,7 @@ impl<'a> UMeshView<'a> {
         connectivity: nd::ArrayView2<'a, usize>,
         families: Option<nd::ArrayView1<'a, usize>>,
     ) {
+        self.invalidate_cache();
         let block = ElementBlockView::new_regular(et, connectivity, families);
         let (key, wrapped) = block.into_entry();
         self.element_blocks.entry(key).or_insert(wrapped);
@@ -454,51 +1038,603 @@ impl<'a> UMeshView<'a> {
         conn: nd::ArrayView1<'a, usize>,
         offsets: nd::ArrayView1<'a, usize>,
     ) {
+        self.invalidate_cache();
         let block = ElementBlockView::new_poly(et, conn, offsets);
         let (key, wrapped) = block.into_entry();
         self.element_blocks.entry(key).or_insert(wrapped);
     }
 }
 
-impl UMesh {
-    /// Creates a new empty mesh with the given coordinates.
-    pub fn new(coords: nd::ArcArray2<f64>) -> Self {
+impl<'a> UMeshViewMut<'a> {
+    /// Creates a new empty mutable mesh view with the given coordinates.
+    pub fn new(coords: nd::ArrayViewMut2<'a, f64>) -> Self {
         Self {
             coords,
             element_blocks: BTreeMap::new(),
+            node_groups: BTreeMap::new(),
+            cache: Arc::default(),
         }
     }
 
-    /// Add a full regular block to the mesh (inplace)
-    ///
-    /// If the et given already has a block, this block is replaced with the new one.
-    /// This method has a low level API to manage a mesh content blockwise.
+    /// Adds a regular element block to this view.
     pub fn add_regular_block(
         &mut self,
         et: ElementType,
-        connectivity: nd::ArcArray2<usize>,
-        fields: Option<BTreeMap<String, nd::ArcArray<f64, nd::IxDyn>>>,
+        connectivity: nd::ArrayView2<'a, usize>,
+        families: Option<nd::ArrayView1<'a, usize>>,
     ) {
-        // TODO: optionnaly add families and fields
-        let block = ElementBlock::new_regular(et, connectivity, None, fields);
-        let (key, wrapped) = block.into_entry();
-        self.element_blocks.entry(key).or_insert(wrapped);
+        self.invalidate_cache();
+        let block = ElementBlockView::new_regular(et, connectivity, families);
+        self.element_blocks
+            .entry(et)
+            .or_insert(block.into_view_mut());
     }
 
-    /// Adds a poly (variable-size) element block to the mesh.
-    ///
-    /// If the element type already has a block, it is replaced.
+    /// Adds a poly element block to this view.
     pub fn add_poly_block(
         &mut self,
         et: ElementType,
-        conn: nd::ArcArray1<usize>,
-        offsets: nd::ArcArray1<usize>,
+        conn: nd::ArrayView1<'a, usize>,
+        offsets: nd::ArrayView1<'a, usize>,
     ) {
-        let block = ElementBlock::new_poly(et, conn, offsets);
-        let (key, wrapped) = block.into_entry();
+        self.invalidate_cache();
+        let block = ElementBlockView::new_poly(et, conn, offsets);
+        self.element_blocks
+            .entry(et)
+            .or_insert(block.into_view_mut());
+    }
+
+    /// Adds (or replaces) a field on the block of the given element type, located at the cells.
+    ///
+    /// # Panics
+    /// Panics if there is no block of this type or if the first axis of `values` does not match
+    /// its number of elements.
+    pub fn add_field(&mut self, et: ElementType, name: &str, values: nd::ArrayViewMutD<'a, f64>) {
+        let block = self
+            .element_blocks
+            .get_mut(&et)
+            .expect("No block of this type");
+        assert_eq!(values.shape().first(), Some(&block.len()));
+        block.field_locations.remove(name);
+        block.fields.insert(name.to_owned(), values);
+    }
+
+    /// Returns a mutable view of the node coordinates.
+    pub fn coords_mut(&mut self) -> nd::ArrayViewMut2<'_, f64> {
+        self.invalidate_cache();
+        self.coords.view_mut()
+    }
+
+    /// Returns a mutable view of a field on the block of the given element type.
+    pub fn field_mut(&mut self, et: ElementType, name: &str) -> Option<nd::ArrayViewMutD<'_, f64>> {
+        self.element_blocks
+            .get_mut(&et)?
+            .fields
+            .get_mut(name)
+            .map(|f| f.view_mut())
+    }
+
+    /// Overwrites the values of an existing field on all the elements of a dimension.
+    ///
+    /// Unlike [`UMesh::assign_field`], the borrowed storage of the field is written in place, so
+    /// the field must already exist on every block of that dimension with the shape of `values`.
+    ///
+    /// # Errors
+    /// Returns an error if the field is missing or if the shape of `values` does not match.
+    pub fn assign_field(
+        &mut self,
+        name: &str,
+        dim: Option<Dimension>,
+        values: nd::ArrayViewD<f64>,
+    ) -> Result<(), String> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self
+                .topological_dimension()
+                .ok_or("This mesh should not be empty")?,
+        };
+        let num_elements = self.num_elements_of_dim(dim);
+        if values.ndim() == 0 || values.shape()[0] != num_elements {
+            return Err(format!(
+                "Field {name} has shape {:?} but there are {num_elements} elements of dimension {dim:?}",
+                values.shape()
+            ));
+        }
+        for block in self
+            .element_blocks
+            .values()
+            .filter(|b| b.cell_type.dimension() == dim)
+        {
+            match block.fields.get(name) {
+                Some(field) if field.shape()[1..] == values.shape()[1..] => (),
+                Some(field) => {
+                    return Err(format!(
+                        "Field {name} has values of shape {:?}, got {:?}",
+                        &field.shape()[1..],
+                        &values.shape()[1..]
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "Field {name} is missing on {:?} elements",
+                        block.cell_type
+                    ));
+                }
+            }
+        }
+        let mut start = 0;
+        for block in self
+            .element_blocks
+            .values_mut()
+            .filter(|b| b.cell_type.dimension() == dim)
+        {
+            let end = start + block.len();
+            let block_values = values.slice_axis(nd::Axis(0), (start..end).into());
+            block.fields.get_mut(name).unwrap().assign(&block_values);
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+impl UMesh {
+    /// Creates a new empty mesh with the given coordinates.
+    pub fn new(coords: nd::ArcArray2<f64>) -> Self {
+        Self {
+            coords,
+            element_blocks: BTreeMap::new(),
+            node_groups: BTreeMap::new(),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Returns a view of this mesh whose coordinates and fields may be mutated in place.
+    ///
+    /// Shared coordinates and fields are copied first, as with [`Self::coords_mut`].
+    pub fn view_mut(&mut self) -> UMeshViewMut<'_> {
+        self.invalidate_cache();
+        let element_blocks = self
+            .element_blocks
+            .iter_mut()
+            .map(|(&et, block)| {
+                let block_view: ElementBlockViewMut = ElementBlockBase {
+                    cell_type: et,
+                    connectivity: block.connectivity.view(),
+                    fields: block
+                        .fields
+                        .iter_mut()
+                        .map(|(k, v)| (k.clone(), v.view_mut()))
+                        .collect(),
+                    field_locations: block.field_locations.clone(),
+                    typed_fields: block.typed_fields.clone(),
+                    sparse_fields: block.sparse_fields.clone(),
+                    families: block.families.view(),
+                    groups: block.groups.clone(),
+                };
+                (et, block_view)
+            })
+            .collect();
+        UMeshViewMut {
+            coords: self.coords.view_mut(),
+            element_blocks,
+            node_groups: self.node_groups.clone(),
+            cache: Arc::default(),
+        }
+    }
+
+    /// Returns a mutable view of the node coordinates.
+    ///
+    /// If the coordinates are shared with another mesh, they are copied first so that the other
+    /// mesh is left untouched. Use [`Self::is_shared`] to check beforehand.
+    pub fn coords_mut(&mut self) -> nd::ArrayViewMut2<'_, f64> {
+        self.invalidate_cache();
+        self.coords.view_mut()
+    }
+
+    /// Returns `true` if the node coordinates are shared with another mesh.
+    pub fn is_shared(&self) -> bool {
+        !self.coords.is_unique()
+    }
+
+    /// Copies the node coordinates if they are shared, so that this mesh owns them alone.
+    pub fn make_unique(&mut self) {
+        if self.is_shared() {
+            self.coords = self.coords.to_owned().into_shared();
+        }
+    }
+
+    /// Add a full regular block to the mesh (inplace)
+    ///
+    /// If the et given already has a block, this block is replaced with the new one.
+    /// This method has a low level API to manage a mesh content blockwise.
+    pub fn add_regular_block(
+        &mut self,
+        et: ElementType,
+        connectivity: nd::ArcArray2<usize>,
+        fields: Option<BTreeMap<String, nd::ArcArray<f64, nd::IxDyn>>>,
+    ) {
+        self.invalidate_cache();
+        // TODO: optionnaly add families and fields
+        let block = ElementBlock::new_regular(et, connectivity, None, fields);
+        let (key, wrapped) = block.into_entry();
         self.element_blocks.entry(key).or_insert(wrapped);
     }
 
+    /// Adds a poly (variable-size) element block to the mesh.
+    ///
+    /// If the element type already has a block, it is replaced.
+    pub fn add_poly_block(
+        &mut self,
+        et: ElementType,
+        conn: nd::ArcArray1<usize>,
+        offsets: nd::ArcArray1<usize>,
+    ) {
+        self.invalidate_cache();
+        let block = ElementBlock::new_poly(et, conn, offsets);
+        let (key, wrapped) = block.into_entry();
+        self.element_blocks.entry(key).or_insert(wrapped);
+    }
+
+    /// Adds (or replaces) a field on the block of the given element type, located at the cells.
+    ///
+    /// # Panics
+    /// Panics if there is no block of this type or if the first axis of `values` does not match
+    /// its number of elements.
+    pub fn add_field(&mut self, et: ElementType, name: &str, values: nd::ArcArray<f64, nd::IxDyn>) {
+        let block = self
+            .element_blocks
+            .get_mut(&et)
+            .expect("No block of this type");
+        assert_eq!(values.shape().first(), Some(&block.len()));
+        block.field_locations.remove(name);
+        block.fields.insert(name.to_owned(), values);
+    }
+
+    /// Returns a mutable view of a field on the block of the given element type.
+    ///
+    /// Values shared with another mesh are copied first.
+    pub fn field_mut(&mut self, et: ElementType, name: &str) -> Option<nd::ArrayViewMutD<'_, f64>> {
+        self.element_blocks.get_mut(&et)?.field_mut(name)
+    }
+
+    /// Removes the nodes not used by any element, renumbering connectivities and node groups.
+    ///
+    /// Kept nodes preserve their relative order.
+    pub fn prune_nodes(&mut self) {
+        self.invalidate_cache();
+        let used = self.used_nodes();
+        if used.len() == self.coords.nrows() {
+            return;
+        }
+        let mut new_ids = vec![usize::MAX; self.coords.nrows()];
+        for (new, &old) in used.iter().enumerate() {
+            new_ids[old] = new;
+        }
+        self.coords = self.coords.select(nd::Axis(0), &used).into_shared();
+        for block in self.element_blocks.values_mut() {
+            block.connectivity.map_nodes(|n| new_ids[n]);
+        }
+        self.renumber_node_groups(|n| Some(new_ids[n]).filter(|&i| i != usize::MAX));
+    }
+
+    /// Extracts the elements of a group into a new mesh, with their fields.
+    ///
+    /// Unused nodes are pruned from the extracted mesh.
+    pub fn extract_group(&self, name: &str) -> UMesh {
+        self.extract_groups(&[name], GroupsMode::Any)
+    }
+
+    /// Extracts the elements belonging to any or all of the given groups into a new mesh, with
+    /// their fields.
+    ///
+    /// Unused nodes are pruned from the extracted mesh.
+    pub fn extract_groups(&self, names: &[&str], mode: GroupsMode) -> UMesh {
+        let mut sets = names.iter().map(|n| self.group_as_element_ids(n));
+        let mut ids = sets.next().unwrap_or_default();
+        for set in sets {
+            match mode {
+                GroupsMode::Any => ids.union(&set),
+                GroupsMode::All => ids.intersection(&set),
+            }
+        }
+        let mut extracted = self.extract(&ids, true);
+        extracted.prune_nodes();
+        extracted
+    }
+
+    /// Rebuilds a minimal set of families from the current groups.
+    ///
+    /// In each block, elements belonging to the same groups share one family, family `0` being
+    /// the family of elements in no group. Other families are numbered in order of first
+    /// appearance.
+    pub fn recompute_families(&mut self) {
+        for block in self.element_blocks.values_mut() {
+            let mut by_groups: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+            by_groups.insert(Vec::new(), 0);
+            let mut old_to_new: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut families = Vec::with_capacity(block.len());
+            for &f in block.families.iter() {
+                let new = *old_to_new.entry(f).or_insert_with(|| {
+                    let groups: Vec<String> = block
+                        .groups
+                        .iter()
+                        .filter(|(_, fams)| fams.contains(&f))
+                        .map(|(g, _)| g.clone())
+                        .collect();
+                    let next = by_groups.len();
+                    *by_groups.entry(groups).or_insert(next)
+                });
+                families.push(new);
+            }
+            let mut groups: BTreeMap<String, BTreeSet<usize>> = block
+                .groups
+                .keys()
+                .map(|g| (g.clone(), BTreeSet::new()))
+                .collect();
+            for (names, fam) in by_groups {
+                for name in names {
+                    groups.get_mut(&name).unwrap().insert(fam);
+                }
+            }
+            block.families = nd::ArcArray1::from(families);
+            block.groups = groups;
+        }
+    }
+
+    /// Sets the elements of a group, replacing its previous content.
+    ///
+    /// Families are split as needed so that families keep partitioning elements by group
+    /// membership. Unused families may remain and can be compacted afterwards.
+    pub fn set_group(&mut self, name: &str, ids: &ElementIds) {
+        for (et, block) in self.element_blocks.iter_mut() {
+            block.groups.remove(name);
+            let Some(indices) = ids.get(et) else {
+                continue;
+            };
+            // Families of elements entering the group are replaced by a twin family belonging to
+            // the same groups plus the new one.
+            let mut next = block.families.iter().max().map_or(0, |m| m + 1);
+            let mut twins: BTreeMap<usize, usize> = BTreeMap::new();
+            for &i in indices {
+                let f = block.families[i];
+                let twin = *twins.entry(f).or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                });
+                block.families[i] = twin;
+            }
+            for fams in block.groups.values_mut() {
+                let added: Vec<usize> = twins
+                    .iter()
+                    .filter(|(f, _)| fams.contains(*f))
+                    .map(|(_, t)| *t)
+                    .collect();
+                fams.extend(added);
+            }
+            block
+                .groups
+                .insert(name.to_owned(), twins.into_values().collect());
+        }
+    }
+
+    /// Creates (or replaces) a group with the elements matching a selection.
+    ///
+    /// Returns the ids of the selected elements.
+    pub fn create_group_from_selection(&mut self, name: &str, selection: Selection) -> ElementIds {
+        let ids = self.select_ids(selection);
+        self.set_group(name, &ids);
+        ids
+    }
+
+    /// Creates (or replaces) a node group with the nodes matching a selection.
+    ///
+    /// See [`MeshSelect::select_nodes`] for how selections are evaluated on nodes. Returns the
+    /// ids of the selected nodes.
+    pub fn create_node_group_from_selection(
+        &mut self,
+        name: &str,
+        selection: Selection,
+    ) -> Vec<usize> {
+        let nodes = self.select_nodes(selection);
+        self.set_node_group(name, nodes.iter().copied());
+        nodes
+    }
+
+    /// Creates (or replaces) group `name` with the elements of group `a` or group `b`.
+    pub fn group_union(&mut self, name: &str, a: &str, b: &str) {
+        self.group_combine(name, a, b, ElementIds::union);
+    }
+
+    /// Creates (or replaces) group `name` with the elements of both group `a` and group `b`.
+    pub fn group_intersection(&mut self, name: &str, a: &str, b: &str) {
+        self.group_combine(name, a, b, ElementIds::intersection);
+    }
+
+    /// Creates (or replaces) group `name` with the elements of group `a` not in group `b`.
+    pub fn group_difference(&mut self, name: &str, a: &str, b: &str) {
+        self.group_combine(name, a, b, ElementIds::difference);
+    }
+
+    fn group_combine<O>(&mut self, name: &str, a: &str, b: &str, op: O)
+    where
+        O: Fn(&mut ElementIds, &ElementIds),
+    {
+        let mut left = self.group_as_element_ids(a);
+        let right = self.group_as_element_ids(b);
+        op(&mut left, &right);
+        self.set_group(name, &left);
+    }
+
+    /// Renames a group. Returns `false` if the group does not exist.
+    ///
+    /// # Panics
+    /// Panics if a group named `new` already exists.
+    pub fn rename_group(&mut self, old: &str, new: &str) -> bool {
+        assert!(
+            old == new
+                || self
+                    .element_blocks
+                    .values()
+                    .all(|b| !b.groups.contains_key(new)),
+            "Group {new} already exists"
+        );
+        let mut found = false;
+        for block in self.element_blocks.values_mut() {
+            if let Some(fams) = block.groups.remove(old) {
+                block.groups.insert(new.to_owned(), fams);
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Removes a group, keeping its elements. Returns `false` if the group does not exist.
+    pub fn remove_group(&mut self, name: &str) -> bool {
+        let mut found = false;
+        for block in self.element_blocks.values_mut() {
+            found |= block.groups.remove(name).is_some();
+        }
+        found
+    }
+
+    /// Converts all fields defined on a selection of elements into full fields.
+    ///
+    /// Elements out of the selection take the `fill` value. This should be used before exporting
+    /// to formats that require full arrays.
+    pub fn densify_sparse_fields(&mut self, fill: f64) {
+        let names: FxHashSet<(String, Dimension)> = self
+            .blocks()
+            .flat_map(|(et, b)| {
+                b.sparse_fields
+                    .keys()
+                    .cloned()
+                    .zip(std::iter::repeat(et.dimension()))
+            })
+            .collect();
+        for (name, dim) in names {
+            let field = self.densify_sparse_field(&name, fill, Some(dim)).unwrap();
+            self.remove_sparse_field(&name, Some(dim));
+            self.update_field(&name, field.into_shared(), Some(dim));
+        }
+    }
+
+    /// Assigns a field on all the elements of a dimension, replacing any previous values.
+    ///
+    /// The first axis of `values` runs over the elements of the blocks of that dimension, in the
+    /// order of the element types, and the field is located at the cells. As for [`Self::field`], the highest topological dimension of
+    /// the mesh is used by default.
+    ///
+    /// # Errors
+    /// Returns an error if the first axis of `values` does not match the number of elements.
+    pub fn assign_field(
+        &mut self,
+        name: &str,
+        dim: Option<Dimension>,
+        values: nd::ArrayViewD<f64>,
+    ) -> Result<(), String> {
+        let dim = match dim {
+            Some(d) => d,
+            None => self
+                .topological_dimension()
+                .ok_or("This mesh should not be empty")?,
+        };
+        let num_elements = self.num_elements_of_dim(dim);
+        if values.ndim() == 0 || values.shape()[0] != num_elements {
+            return Err(format!(
+                "Field {name} has shape {:?} but there are {num_elements} elements of dimension {dim:?}",
+                values.shape()
+            ));
+        }
+        let mut start = 0;
+        for block in self
+            .element_blocks
+            .values_mut()
+            .filter(|b| b.cell_type.dimension() == dim)
+        {
+            let end = start + block.len();
+            let block_values = values.slice_axis(nd::Axis(0), (start..end).into());
+            block.field_locations.remove(name);
+            block
+                .fields
+                .insert(name.to_owned(), block_values.to_shared());
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Assigns a field on the elements matching a selection only.
+    ///
+    /// Blocks of the dimensions of the selected elements lacking the field get it, filled with
+    /// `NaN` out of the selection. Returns the ids of the selected elements.
+    ///
+    /// # Errors
+    /// Returns an error if the values do not match the number of selected elements, or if their
+    /// shape does not match the one of the existing field.
+    pub fn assign_field_on(
+        &mut self,
+        selection: Selection,
+        name: &str,
+        values: impl Into<FieldValues>,
+    ) -> Result<ElementIds, String> {
+        let ids = self.select_ids(selection);
+        let values = match values.into() {
+            FieldValues::Uniform(value) => {
+                let shape: Vec<usize> = std::iter::once(ids.len())
+                    .chain(value.shape().iter().copied())
+                    .collect();
+                value.broadcast(shape).unwrap().to_owned()
+            }
+            FieldValues::PerElement(values) if values.shape().first() == Some(&ids.len()) => values,
+            FieldValues::PerElement(values) => {
+                return Err(format!(
+                    "Field {name} has shape {:?} but {} elements are selected",
+                    values.shape(),
+                    ids.len()
+                ));
+            }
+        };
+        let value_shape = &values.shape()[1..];
+        let dims: BTreeSet<Dimension> = ids.iter_blocks().map(|(et, _)| et.dimension()).collect();
+        for block in self.element_blocks.values() {
+            if let Some(field) = block.fields.get(name)
+                && dims.contains(&block.cell_type.dimension())
+                && &field.shape()[1..] != value_shape
+            {
+                return Err(format!(
+                    "Field {name} has values of shape {:?}, got {value_shape:?}",
+                    &field.shape()[1..]
+                ));
+            }
+        }
+        for block in self
+            .element_blocks
+            .values_mut()
+            .filter(|b| dims.contains(&b.cell_type.dimension()))
+        {
+            let shape: Vec<usize> = std::iter::once(block.len())
+                .chain(value_shape.iter().copied())
+                .collect();
+            block
+                .fields
+                .entry(name.to_owned())
+                .or_insert_with(|| nd::ArcArray::from_elem(shape, f64::NAN));
+        }
+        let mut rows = values.outer_iter();
+        for (et, indices) in ids.iter_blocks() {
+            let field = self
+                .element_blocks
+                .get_mut(et)
+                .unwrap()
+                .fields
+                .get_mut(name)
+                .unwrap();
+            for (&i, row) in indices.iter().zip(&mut rows) {
+                field.index_axis_mut(nd::Axis(0), i).assign(&row);
+            }
+        }
+        Ok(ids)
+    }
+
     /// Returns this mesh as an owned mesh (identity operation for `UMesh`).
     pub fn into_owned(self) -> UMesh {
         self
@@ -514,6 +1650,7 @@ impl UMesh {
         family: Option<usize>,
         fields: Option<BTreeMap<String, nd::ArrayViewD<f64>>>,
     ) -> ElementId {
+        self.invalidate_cache();
         match element_type.regularity() {
             Regularity::Regular => {
                 if connectivity.len() != element_type.num_nodes().unwrap() {
@@ -562,6 +1699,7 @@ impl UMesh {
         &mut self,
         added_coord: nd::ArrayView1<'_, f64>,
     ) -> Result<(), nd::ShapeError> {
+        self.invalidate_cache();
         let mut coords = std::mem::take(&mut self.coords).into_owned();
         coords.push(nd::Axis(0), added_coord)?;
         self.coords = coords.into_shared();
@@ -574,6 +1712,7 @@ impl UMesh {
         &mut self,
         added_coords: nd::ArrayView2<'_, f64>,
     ) -> Result<(), nd::ShapeError> {
+        self.invalidate_cache();
         let mut coords = std::mem::take(&mut self.coords).into_owned();
         coords.append(nd::Axis(0), added_coords)?;
         self.coords = coords.into_shared();
@@ -583,69 +1722,61 @@ impl UMesh {
     /// Extracts a sub-mesh from the current mesh based on the provided element IDs.
     ///
     /// This method creates a new `UMesh`, owning its data (with copy) containing only the elements
-    /// specified by the IDs.
+    /// specified by the IDs. Coordinates are shared with the current mesh.
     /// This method is low level and error prone in the case where `ElementsIds` are not directly
     /// issued from a Selector. Please use Selector API if possible.
     pub fn extract(&self, ids: &ElementIds, with_fields: bool) -> UMesh {
-        let mut extracted = UMesh::new(self.coords.clone());
-        // TODO: conditionnaly extract fields
-        for (t, block) in ids.iter_blocks() {
-            if !self.element_blocks.contains_key(t) {
-                continue;
-            }
-            match &self.element_blocks[t] {
-                ElementBlockBase {
-                    connectivity: ConnectivityBase::Regular(arr),
-                    fields,
-                    ..
-                } => extracted.add_regular_block(
-                    *t,
-                    arr.select(nd::Axis(0), block.as_slice()).into_shared(),
-                    match with_fields {
-                        true => Some(
-                            fields
-                                .iter()
-                                .map(|(n, f)| {
-                                    (
-                                        n.clone(),
-                                        f.select(nd::Axis(0), block.as_slice()).into_shared(),
-                                    )
-                                })
-                                .collect(),
-                        ),
-                        false => None,
-                    },
-                ),
-                _ => todo!(),
-            };
-        }
-        extracted
+        self.extract_to_owned(ids, with_fields)
     }
 
     /// This method is used to replace elements in the current mesh with another mesh, producing a
     /// new mesh. The number of elements in ElementIds must be the number of elements in the
-    /// replace_mesh. With this method new nodes cannot be added into poly elements (all elements
-    /// must keep the same number of nodes).
+    /// replace_mesh. Regular elements keep their number of nodes, while poly elements may change
+    /// it: the data and offsets arrays of poly blocks are then rebuilt.
     ///
-    /// If you want to change the number of elements or the number of nodes in a mesh, please use
-    /// delete and add.
+    /// If you want to change the number of elements in a mesh, please use delete and add.
     ///
     /// Please mind what you are doing, this method wont check for mesh consistency.
     pub fn replace(mut self, ids: &ElementIds, replace_mesh: UMeshView) -> UMesh {
-        for new_elem in replace_mesh.elements() {
-            let old_eid = ElementId::new(
-                new_elem.element_type(),
-                ids.get(&new_elem.element_type()).unwrap()[new_elem.index()],
-            );
-            let element = self.element_mut(old_eid);
-            element.connectivity.copy_from_slice(new_elem.connectivity);
-            *element.family = *new_elem.family;
+        self.invalidate_cache();
+        for (&et, new_block) in replace_mesh.blocks() {
+            let old_ids = ids.get(&et).unwrap();
+            let block = self.element_blocks.get_mut(&et).unwrap();
+            let mut families = block.families.to_owned();
+            for (&old, &family) in old_ids.iter().zip(new_block.families.iter()) {
+                families[old] = family;
+            }
+            block.families = families.into_shared();
+            match &mut block.connectivity {
+                regular @ ConnectivityBase::Regular(_) => {
+                    for (&old, new) in old_ids.iter().zip(new_block.connectivity.iter()) {
+                        regular[old].copy_from_slice(new);
+                    }
+                }
+                ConnectivityBase::Poly(conn) => {
+                    let mut replaced: Vec<Option<&[usize]>> = vec![None; conn.len()];
+                    for (&old, new) in old_ids.iter().zip(new_block.connectivity.iter()) {
+                        replaced[old] = Some(new);
+                    }
+                    let mut data = Vec::with_capacity(conn.num_elems_tot());
+                    let mut offsets = Vec::with_capacity(conn.len());
+                    for (i, nodes) in replaced.into_iter().enumerate() {
+                        data.extend_from_slice(nodes.unwrap_or(&conn[i]));
+                        offsets.push(data.len());
+                    }
+                    block.connectivity = Connectivity::new_poly(
+                        nd::ArcArray1::from(data),
+                        nd::ArcArray1::from(offsets),
+                    );
+                }
+            }
         }
         self
     }
 
     /// Returns a mutable view of the element with the given ID.
     pub fn element_mut(&mut self, id: ElementId) -> ElementMut<'_> {
+        self.invalidate_cache();
         self.element_blocks
             .get_mut(&id.element_type())
             .unwrap()
@@ -656,6 +1787,7 @@ impl UMesh {
     ///
     /// Returns the old blocks that were replaced, or `None` if no blocks were replaced.
     pub fn update(&mut self, mut other: Self) -> Option<Self> {
+        self.invalidate_cache();
         let target_dims = other
             .element_types()
             .map(|et| et.dimension())
@@ -682,6 +1814,250 @@ impl UMesh {
         }
         old_mesh
     }
+
+    /// Appends the nodes and elements of another mesh, merging same-name groups and fields.
+    ///
+    /// This is [`Self::append_with`] with [`NameCollision::Merge`].
+    pub fn append(&mut self, other: UMeshView) {
+        self.append_with(other, NameCollision::Merge);
+    }
+
+    /// Appends the nodes and elements of another mesh.
+    ///
+    /// Coordinates of `other` are added after the ones of `self` and its connectivities are
+    /// offset accordingly. Elements of the same type are gathered in one block, the elements of
+    /// `other` coming last. Node groups, groups and fields of `other` are added to `self`,
+    /// `policy` deciding what happens to the ones whose name is already used in `self`. Nodes
+    /// are not merged, see [`crate::tools::merge_nodes`].
+    ///
+    /// When a floating point field is only defined on one of two merged blocks, it is filled with
+    /// NaN on the elements of the other one. Typed fields are only kept if they are defined on
+    /// both blocks with the same kind of data.
+    ///
+    /// # Panics
+    /// Panics if the meshes do not have the same space dimension, or if same-name fields have
+    /// different shapes or locations.
+    pub fn append_with(&mut self, other: UMeshView, policy: NameCollision) {
+        assert_eq!(
+            self.space_dimension(),
+            other.space_dimension(),
+            "Appended meshes must have the same space dimension"
+        );
+        let node_offset = self.coords.nrows();
+        self.append_coords(other.coords.view()).unwrap();
+
+        let node_groups = policy.resolve(
+            self.node_groups.keys().cloned().collect(),
+            other.node_groups.keys(),
+        );
+        for (name, nodes) in &other.node_groups {
+            if let Some(new) = &node_groups[name] {
+                self.node_groups
+                    .entry(new.clone())
+                    .or_default()
+                    .extend(nodes.iter().map(|n| n + node_offset));
+            }
+        }
+        let groups = policy.resolve(
+            self.group_names().into_iter().collect(),
+            other.element_blocks.values().flat_map(|b| b.groups.keys()),
+        );
+        let fields = policy.resolve(
+            self.element_blocks
+                .values()
+                .flat_map(|b| {
+                    b.fields
+                        .keys()
+                        .chain(b.typed_fields.keys())
+                        .chain(b.sparse_fields.keys())
+                })
+                .cloned()
+                .collect(),
+            other.element_blocks.values().flat_map(|b| {
+                b.fields
+                    .keys()
+                    .chain(b.typed_fields.keys())
+                    .chain(b.sparse_fields.keys())
+            }),
+        );
+
+        for (&et, other_block) in &other.element_blocks {
+            let other_len = other_block.len();
+            let connectivity: Vec<Vec<usize>> = other_block
+                .connectivity
+                .iter()
+                .map(|c| c.iter().map(|n| n + node_offset).collect())
+                .collect();
+            let other_fields = other_block
+                .fields
+                .iter()
+                .filter_map(|(name, field)| Some((fields[name].clone()?, field.view())));
+            let other_typed = other_block
+                .typed_fields
+                .iter()
+                .filter_map(|(name, field)| Some((fields[name].clone()?, field)));
+            let other_sparse = other_block
+                .sparse_fields
+                .iter()
+                .filter_map(|(name, field)| Some((fields[name].clone()?, field)));
+
+            let block = self
+                .element_blocks
+                .entry(et)
+                .or_insert_with(|| match et.regularity() {
+                    Regularity::Regular => ElementBlock::new_regular(
+                        et,
+                        nd::ArcArray2::zeros((0, et.num_nodes().unwrap())),
+                        None,
+                        None,
+                    ),
+                    Regularity::Poly => {
+                        ElementBlock::new_poly(et, nd::ArcArray1::zeros(0), nd::ArcArray1::zeros(0))
+                    }
+                });
+            let len = block.len();
+
+            block.connectivity = match &block.connectivity {
+                ConnectivityBase::Regular(conn) => {
+                    let data: Vec<usize> = conn
+                        .iter()
+                        .copied()
+                        .chain(connectivity.into_iter().flatten())
+                        .collect();
+                    let conn =
+                        nd::Array2::from_shape_vec((len + other_len, conn.ncols()), data).unwrap();
+                    ConnectivityBase::Regular(conn.into_shared())
+                }
+                ConnectivityBase::Poly(conn) => {
+                    let mut data = conn.data.to_vec();
+                    let mut offsets = conn.offsets.to_vec();
+                    for c in connectivity {
+                        data.extend(c);
+                        offsets.push(data.len());
+                    }
+                    ConnectivityBase::Poly(IndirectIndex {
+                        data: data.into(),
+                        offsets: offsets.into(),
+                    })
+                }
+            };
+
+            // Families of the other block are shifted after the ones of this block.
+            let family_offset = match len {
+                0 => 0,
+                _ => block.families.iter().max().map_or(0, |m| m + 1),
+            };
+            block.families = block
+                .families
+                .iter()
+                .copied()
+                .chain(other_block.families.iter().map(|f| f + family_offset))
+                .collect();
+            for (name, families) in &other_block.groups {
+                if let Some(new) = &groups[name] {
+                    block
+                        .groups
+                        .entry(new.clone())
+                        .or_default()
+                        .extend(families.iter().map(|f| f + family_offset));
+                }
+            }
+
+            for name in other_block.fields.keys() {
+                let Some(new) = &fields[name] else {
+                    continue;
+                };
+                let location = other_block.field_location(name);
+                if block.fields.contains_key(new) {
+                    assert_eq!(
+                        block.field_location(new),
+                        location,
+                        "Field {new} has different locations in appended meshes"
+                    );
+                } else if location != FieldLocation::Cells {
+                    block.field_locations.insert(new.clone(), location);
+                }
+            }
+            let mut other_fields: BTreeMap<String, nd::ArrayViewD<f64>> = other_fields.collect();
+            let names: BTreeSet<String> = block
+                .fields
+                .keys()
+                .chain(other_fields.keys())
+                .cloned()
+                .collect();
+            for name in names {
+                let mine = block.fields.remove(&name);
+                let theirs = other_fields.remove(&name);
+                let shape = mine
+                    .as_ref()
+                    .map(|f| f.shape().to_vec())
+                    .or_else(|| theirs.as_ref().map(|f| f.shape().to_vec()))
+                    .unwrap();
+                let filled = |n: usize| {
+                    let mut shape = shape.clone();
+                    shape[0] = n;
+                    nd::ArrayD::from_elem(shape, f64::NAN)
+                };
+                let mine = mine.map_or_else(|| filled(len), |f| f.to_owned());
+                let theirs = theirs.map_or_else(|| filled(other_len), |f| f.to_owned());
+                let field = nd::concatenate(nd::Axis(0), &[mine.view(), theirs.view()])
+                    .unwrap_or_else(|_| {
+                        panic!("Field {name} has different shapes in appended meshes")
+                    });
+                block.fields.insert(name, field.into_shared());
+            }
+
+            let mut other_typed: BTreeMap<String, &FieldData> = other_typed.collect();
+            let typed = std::mem::take(&mut block.typed_fields);
+            block.typed_fields = typed
+                .into_iter()
+                .filter_map(|(name, field)| {
+                    let theirs = other_typed.remove(&name);
+                    let field = match len {
+                        0 => theirs.cloned(),
+                        _ => field.concat(theirs?),
+                    }?;
+                    Some((name, field))
+                })
+                .collect();
+            if len == 0 {
+                block
+                    .typed_fields
+                    .extend(other_typed.into_iter().map(|(n, f)| (n, f.clone())));
+            }
+
+            for (name, field) in other_sparse {
+                let field = match block.sparse_fields.get(&name) {
+                    Some(mine) => mine.concat(field, len).unwrap_or_else(|| {
+                        panic!("Field {name} has different shapes in appended meshes")
+                    }),
+                    None => SparseData {
+                        indices: field.indices.iter().map(|i| i + len).collect(),
+                        values: field.values.clone(),
+                    },
+                };
+                block.sparse_fields.insert(name, field);
+            }
+        }
+    }
+
+    /// Concatenates meshes into a new one, merging same-name groups and fields.
+    ///
+    /// See [`Self::append_with`] for details.
+    ///
+    /// # Panics
+    /// Panics if `meshes` is empty or if the meshes do not have the same space dimension.
+    pub fn concat(meshes: &[UMeshView]) -> UMesh {
+        let space_dimension = meshes
+            .first()
+            .expect("At least one mesh is needed")
+            .space_dimension();
+        let mut res = UMesh::new(nd::ArcArray2::zeros((0, space_dimension)));
+        for mesh in meshes {
+            res.append(mesh.clone());
+        }
+        res
+    }
 }
 
 #[cfg(test)]
@@ -744,9 +2120,410 @@ mod tests {
     //     assert_eq!(sub_mesh.coords().shape(), &[4, 2]);
     // }
 
+    #[test]
+    fn test_umesh_extract_replace_poly() {
+        let mut mesh = me::make_mesh_2d_multi();
+        mesh.add_element(ElementType::PGON, &[1, 4, 3], None, None);
+        let ids = ElementIds::from(BTreeMap::from([
+            (ElementType::QUAD4, vec![0]),
+            (ElementType::PGON, vec![1]),
+        ]));
+        let extracted = mesh.extract(&ids, false);
+        let pgon = extracted.block(ElementType::PGON).unwrap();
+        assert_eq!(pgon.len(), 1);
+        assert_eq!(&pgon.connectivity[0], &[1, 4, 3]);
+
+        // Replacing a poly element with one having more nodes
+        let mut replacement = UMesh::new(mesh.coords.clone());
+        replacement.add_element(ElementType::PGON, &[0, 1, 4, 3], None, None);
+        let ids = ElementIds::from(BTreeMap::from([(ElementType::PGON, vec![1])]));
+        let replaced = mesh.replace(&ids, replacement.view());
+        let pgon = replaced.block(ElementType::PGON).unwrap();
+        assert_eq!(&pgon.connectivity[0], &[0, 1, 4, 3, 2]);
+        assert_eq!(&pgon.connectivity[1], &[0, 1, 4, 3]);
+    }
+
+    #[test]
+    fn test_umesh_coords_cow() {
+        let mut mesh = me::make_imesh_2d(1);
+        assert!(!mesh.is_shared());
+        let copy = mesh.clone();
+        assert!(mesh.is_shared());
+        mesh.coords_mut()[[0, 0]] = -1.0;
+        assert!(!mesh.is_shared());
+        assert_eq!(mesh.coords()[[0, 0]], -1.0);
+        assert_eq!(copy.coords()[[0, 0]], 0.0);
+
+        let mut other = copy.clone();
+        other.make_unique();
+        assert!(!other.is_shared() && !copy.is_shared());
+    }
+
+    #[test]
+    fn test_umesh_view_mut() {
+        let mut mesh = me::make_imesh_2d(2);
+        let zeros = nd::Array2::<f64>::zeros((4, 2)).into_dyn();
+        mesh.assign_field("f", None, zeros.view()).unwrap();
+        {
+            let mut view = mesh.view_mut();
+            view.coords_mut().mapv_inplace(|x| 2.0 * x);
+            let ones = nd::Array2::<f64>::ones((4, 2)).into_dyn();
+            view.assign_field("f", None, ones.view()).unwrap();
+            view.field_mut(ElementType::QUAD4, "f").unwrap()[[3, 1]] = 3.0;
+            assert!(view.assign_field("g", None, ones.view()).is_err());
+            let column = ones.index_axis(nd::Axis(1), 0);
+            assert!(view.assign_field("f", None, column).is_err());
+        }
+        assert_eq!(mesh.coords()[[8, 0]], 2.0);
+        let field = mesh.field("f", None).unwrap();
+        assert_eq!(field.0[&ElementType::QUAD4][[0, 0]], 1.0);
+        assert_eq!(field.0[&ElementType::QUAD4][[3, 1]], 3.0);
+
+        // Mutating foreign data
+        let mut coords = nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        let mut temperature = nd::arr1(&[0.0]).into_dyn();
+        let conn = nd::arr2(&[[0, 1, 2]]);
+        let families = nd::arr1(&[0]);
+        {
+            let mut view = UMeshViewMut::new(coords.view_mut());
+            view.add_regular_block(ElementType::TRI3, conn.view(), Some(families.view()));
+            view.add_field(ElementType::TRI3, "t", temperature.view_mut());
+            view.coords_mut()[[2, 1]] = 2.0;
+            let values = nd::arr1(&[5.0]).into_dyn();
+            view.assign_field("t", None, values.view()).unwrap();
+        }
+        assert_eq!(coords[[2, 1]], 2.0);
+        assert_eq!(temperature[[0]], 5.0);
+    }
+
     #[test]
     fn test_umesh_view() {
         let mesh = me::make_imesh_3d(40);
         mesh.view();
     }
+
+    #[test]
+    fn test_umesh_typed_fields() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut materials = BTreeMap::new();
+        materials.insert(
+            ElementType::QUAD4,
+            FieldData::categorical(&["steel", "water", "water", "steel"]),
+        );
+        assert!(
+            mesh.update_typed_field("material", materials, None)
+                .is_none()
+        );
+        let mut flags = BTreeMap::new();
+        flags.insert(
+            ElementType::QUAD4,
+            FieldData::Bool(
+                nd::arr1(&[true, false, true, false])
+                    .into_dyn()
+                    .into_shared(),
+            ),
+        );
+        mesh.update_typed_field("flag", flags, None);
+        assert_eq!(mesh.typed_field_names().len(), 2);
+
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![1, 3]);
+        let extracted = mesh.extract(&ids, true);
+        let material = extracted.typed_field("material", None).unwrap();
+        assert_eq!(
+            material[&ElementType::QUAD4].labels().unwrap(),
+            vec!["water", "steel"]
+        );
+        assert!(mesh.view().typed_field("flag", None).is_some());
+
+        let json = serde_json::to_string(&extracted).unwrap();
+        let read: UMesh = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, extracted);
+
+        assert!(mesh.remove_typed_field("flag", None).is_some());
+        assert!(mesh.typed_field("flag", None).is_none());
+    }
+
+    #[test]
+    fn test_umesh_assign_field() {
+        let mut mesh = me::make_mesh_2d_multi();
+        let values = nd::arr1(&[1.0, 2.0]).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let field = mesh.field("f", None).unwrap();
+        assert_eq!(field.0[&ElementType::PGON][[0]], 2.0);
+        for block in mesh.element_blocks.values_mut() {
+            block
+                .field_locations
+                .insert("f".to_owned(), FieldLocation::ElementNodes);
+        }
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let field = mesh.field("f", None).unwrap();
+        assert_eq!(field.location(), FieldLocation::Cells);
+        assert!(
+            mesh.assign_field("f", Some(Dimension::D1), values.view())
+                .is_ok()
+        );
+        assert!(
+            mesh.assign_field("g", None, nd::arr1(&[1.0]).into_dyn().view())
+                .is_err()
+        );
+
+        let pgon = crate::tools::sel::types(vec![ElementType::PGON]);
+        let ids = mesh.assign_field_on(pgon.clone(), "f", 5.0).unwrap();
+        assert_eq!(ids.len(), 1);
+        let field = mesh.field("f", None).unwrap();
+        assert_eq!(field.0[&ElementType::QUAD4][[0]], 1.0);
+        assert_eq!(field.0[&ElementType::PGON][[0]], 5.0);
+
+        let vectors = FieldValues::PerElement(nd::arr2(&[[1.0, 2.0]]).into_dyn());
+        mesh.assign_field_on(pgon.clone(), "v", vectors).unwrap();
+        let field = mesh.field("v", None).unwrap();
+        assert_eq!(field.0[&ElementType::QUAD4].shape(), &[1, 2]);
+        assert!(field.0[&ElementType::QUAD4][[0, 0]].is_nan());
+        assert_eq!(field.0[&ElementType::PGON][[0, 1]], 2.0);
+        assert!(mesh.assign_field_on(pgon, "v", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_umesh_sparse_fields() {
+        let mut mesh = me::make_imesh_2d(2);
+        let ids = BTreeMap::from([(ElementType::QUAD4, vec![2, 0])]);
+        let mut values = BTreeMap::new();
+        values.insert(
+            ElementType::QUAD4,
+            nd::arr1(&[2.0, 1.0]).into_dyn().into_shared(),
+        );
+        let sensor = SparseField::new(ids, values);
+        assert_eq!(
+            sensor
+                .get(ElementId::new(ElementType::QUAD4, 0))
+                .unwrap()
+                .first()
+                .copied(),
+            Some(1.0)
+        );
+        let unsorted = SparseData {
+            indices: vec![2, 0],
+            values: nd::arr1(&[2.0, 1.0]).into_dyn().into_shared(),
+        };
+        let reordered = SparseField::from_blocks(BTreeMap::from([(ElementType::QUAD4, unsorted)]));
+        assert_eq!(reordered.values, sensor.values);
+        assert!(mesh.update_sparse_field("sensor", sensor).is_none());
+
+        let dense = mesh.densify_sparse_field("sensor", 0.0, None).unwrap();
+        assert_eq!(
+            dense.0[&ElementType::QUAD4]
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![1.0, 0.0, 2.0, 0.0]
+        );
+
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![1, 2]);
+        let extracted = mesh.extract(&ids, true);
+        let sensor = extracted.sparse_field("sensor", None).unwrap();
+        assert_eq!(sensor.ids.get(&ElementType::QUAD4).unwrap(), &vec![1]);
+
+        mesh.densify_sparse_fields(f64::NAN);
+        assert!(mesh.sparse_field("sensor", None).is_none());
+        assert!(mesh.field("sensor", None).unwrap().0[&ElementType::QUAD4][1].is_nan());
+    }
+
+    #[test]
+    fn test_umesh_node_groups() {
+        let mut mesh = me::make_imesh_2d(2);
+        assert!(mesh.set_node_group("left", [0, 3, 6]).is_none());
+        assert_eq!(mesh.node_group_names().collect::<Vec<_>>(), vec!["left"]);
+
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0]);
+        let extracted = mesh.extract(&ids, false);
+        assert_eq!(extracted.node_group("left"), mesh.node_group("left"));
+        assert_eq!(mesh.view().node_group("left").unwrap().len(), 3);
+
+        mesh.renumber_node_groups(|n| if n == 3 { None } else { Some(n + 1) });
+        assert_eq!(
+            mesh.node_group("left")
+                .unwrap()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![1, 7]
+        );
+
+        let json = serde_json::to_string(&mesh).unwrap();
+        let read: UMesh = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.node_group("left"), mesh.node_group("left"));
+        assert!(mesh.remove_node_group("left").is_some());
+    }
+
+    #[test]
+    fn test_umesh_group_algebra() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0, 1]);
+        mesh.set_group("bottom", &ids);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![1, 3]);
+        mesh.set_group("right", &ids);
+        assert_eq!(mesh.group_names(), vec!["bottom", "right"]);
+        assert_eq!(
+            mesh.group_as_element_ids("bottom").get(&ElementType::QUAD4),
+            Some(&vec![0, 1])
+        );
+
+        mesh.group_union("u", "bottom", "right");
+        mesh.group_intersection("i", "bottom", "right");
+        mesh.group_difference("d", "bottom", "right");
+        let get = |m: &UMesh, n: &str| m.group_as_element_ids(n).get(&ElementType::QUAD4).cloned();
+        assert_eq!(get(&mesh, "u"), Some(vec![0, 1, 3]));
+        assert_eq!(get(&mesh, "i"), Some(vec![1]));
+        assert_eq!(get(&mesh, "d"), Some(vec![0]));
+        // Earlier groups are untouched by later ones
+        assert_eq!(get(&mesh, "bottom"), Some(vec![0, 1]));
+        assert_eq!(get(&mesh, "right"), Some(vec![1, 3]));
+
+        assert!(mesh.rename_group("d", "left_bottom"));
+        assert!(mesh.remove_group("u"));
+        assert!(!mesh.remove_group("u"));
+        assert_eq!(
+            mesh.group_names(),
+            vec!["bottom", "i", "left_bottom", "right"]
+        );
+
+        let ids = mesh
+            .create_group_from_selection("all", crate::tools::sel::types(vec![ElementType::QUAD4]));
+        assert_eq!(ids.len(), 4);
+        assert_eq!(mesh.group_as_element_ids("all").len(), 4);
+    }
+
+    #[test]
+    fn test_umesh_extract_group() {
+        let mut mesh = me::make_imesh_2d(2);
+        mesh.set_node_group("corner", [0, 8]);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![3]);
+        mesh.set_group("top_right", &ids);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![1, 3]);
+        mesh.set_group("right", &ids);
+
+        let extracted = mesh.extract_group("top_right");
+        assert_eq!(extracted.num_elements(), 1);
+        assert_eq!(extracted.coords().nrows(), 4);
+        assert_eq!(extracted.used_nodes(), vec![0, 1, 2, 3]);
+        assert_eq!(
+            extracted
+                .node_group("corner")
+                .unwrap()
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        let any = mesh.extract_groups(&["top_right", "right"], GroupsMode::Any);
+        assert_eq!(any.num_elements(), 2);
+        assert_eq!(any.coords().nrows(), 6);
+        let all = mesh.extract_groups(&["top_right", "right"], GroupsMode::All);
+        assert_eq!(all.num_elements(), 1);
+    }
+
+    #[test]
+    fn test_umesh_families_repair() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0, 1]);
+        mesh.set_group("bottom", &ids);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0]);
+        mesh.set_group("first", &ids);
+        assert!(mesh.check_families().is_empty());
+        mesh.remove_group("first");
+        assert_eq!(
+            mesh.check_families(),
+            vec![FamilyIssue::DuplicateFamilies {
+                element_type: ElementType::QUAD4,
+                families: vec![1, 2]
+            }]
+        );
+
+        mesh.recompute_families();
+        assert!(mesh.check_families().is_empty());
+        let block = mesh.block(ElementType::QUAD4).unwrap();
+        assert_eq!(block.families.to_vec(), vec![1, 1, 0, 0]);
+        assert_eq!(
+            mesh.group_as_element_ids("bottom").get(&ElementType::QUAD4),
+            Some(&vec![0, 1])
+        );
+    }
+
+    #[test]
+    fn test_umesh_append() {
+        let mut a = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 0);
+        a.set_group("g", &ids);
+        a.set_node_group("n", [0]);
+        let values = nd::arr1(&[1.0, 2.0, 3.0, 4.0]).into_dyn().into_shared();
+        a.update_field(
+            "f",
+            FieldBase::new(BTreeMap::from([(ElementType::QUAD4, values)])),
+            None,
+        );
+        let mut b = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 3);
+        b.set_group("g", &ids);
+        b.set_node_group("n", [0]);
+        let nodal = nd::Array2::<f64>::ones((4, 4)).into_dyn().into_shared();
+        b.update_field(
+            "e",
+            FieldBase::new(BTreeMap::from([(ElementType::QUAD4, nodal)]))
+                .with_location(FieldLocation::ElementNodes),
+            None,
+        );
+        let get = |m: &UMesh, n: &str| m.group_as_element_ids(n).get(&ElementType::QUAD4).cloned();
+
+        let mut merged = a.clone();
+        merged.append(b.view());
+        assert_eq!(merged.coords().nrows(), 18);
+        assert_eq!(merged.block(ElementType::QUAD4).unwrap().len(), 8);
+        let first = ElementId::new(ElementType::QUAD4, 0);
+        let shifted: Vec<usize> = b
+            .element(first)
+            .connectivity
+            .iter()
+            .map(|n| n + 9)
+            .collect();
+        let appended = ElementId::new(ElementType::QUAD4, 4);
+        assert_eq!(merged.element(appended).connectivity, shifted.as_slice());
+        assert_eq!(get(&merged, "g"), Some(vec![0, 7]));
+        assert_eq!(merged.node_group("n"), Some(&BTreeSet::from([0, 9])));
+        let field: Vec<f64> = merged.block(ElementType::QUAD4).unwrap().fields["f"]
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(field[3], 4.0);
+        assert!(field[4].is_nan());
+        let block = merged.block(ElementType::QUAD4).unwrap();
+        assert_eq!(block.field_location("e"), FieldLocation::ElementNodes);
+        assert_eq!(block.fields["e"].shape(), &[8, 4]);
+
+        let mut renamed = a.clone();
+        renamed.append_with(b.view(), NameCollision::Rename);
+        assert_eq!(get(&renamed, "g"), Some(vec![0]));
+        assert_eq!(get(&renamed, "g_1"), Some(vec![7]));
+        assert_eq!(renamed.node_group("n_1"), Some(&BTreeSet::from([9])));
+
+        let mut kept = a.clone();
+        kept.append_with(b.view(), NameCollision::Keep);
+        assert_eq!(kept.group_names(), vec!["g"]);
+        assert_eq!(get(&kept, "g"), Some(vec![0]));
+
+        let concatenated = UMesh::concat(&[a.view(), b.view(), b.view()]);
+        assert_eq!(concatenated.block(ElementType::QUAD4).unwrap().len(), 12);
+        assert_eq!(get(&concatenated, "g"), Some(vec![0, 7, 11]));
+    }
 }
@@ -0,0 +1,113 @@
+//! Single precision storage of meshes.
+//!
+//! Visualization pipelines often care more about memory than about precision. [`UMeshF32`] stores
+//! node coordinates and fields on `f32`, and shares the connectivities, families and groups of the
+//! mesh it was built from. Algorithms work on [`UMesh`]: the conversion back to double precision
+//! is lossless, so any algorithm reading the geometry gives the same result on both, up to the
+//! initial rounding.
+
+use ndarray as nd;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::element::ElementType;
+use super::umesh::UMesh;
+
+/// An unstructured mesh whose coordinates and fields are stored in single precision.
+///
+/// It is built from a [`UMesh`] with [`From`], coordinates and fields being rounded to the
+/// nearest `f32`, and converted back to a [`UMesh`] without loss. Fields defined on a selection of
+/// elements are kept in double precision.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UMeshF32 {
+    coords: nd::ArcArray2<f32>,
+    fields: BTreeMap<ElementType, BTreeMap<String, nd::ArcArray<f32, nd::IxDyn>>>,
+    /// Connectivities, families and groups, with empty coordinates and no fields.
+    topology: UMesh,
+}
+
+impl UMeshF32 {
+    /// Returns a view of the coordinates array.
+    pub fn coords(&self) -> nd::ArrayView2<'_, f32> {
+        self.coords.view()
+    }
+
+    /// Returns a view of a field on the block of the given element type.
+    pub fn field(&self, et: ElementType, name: &str) -> Option<nd::ArrayViewD<'_, f32>> {
+        self.fields.get(&et)?.get(name).map(|f| f.view())
+    }
+
+    /// Returns the total number of elements.
+    pub fn num_elements(&self) -> usize {
+        self.topology.num_elements()
+    }
+
+    /// Returns the element types of the blocks of the mesh.
+    pub fn element_types(&self) -> impl Iterator<Item = &ElementType> {
+        self.topology.element_types()
+    }
+}
+
+impl From<&UMesh> for UMeshF32 {
+    fn from(mesh: &UMesh) -> Self {
+        let mut topology = mesh.clone();
+        topology.coords = nd::ArcArray2::zeros((mesh.coords.nrows(), 0));
+        topology.invalidate_cache();
+        let fields = topology
+            .element_blocks
+            .iter_mut()
+            .map(|(&et, block)| {
+                let fields = std::mem::take(&mut block.fields)
+                    .into_iter()
+                    .map(|(name, f)| (name, f.mapv(|x| x as f32).into_shared()))
+                    .collect();
+                (et, fields)
+            })
+            .collect();
+        Self {
+            coords: mesh.coords.mapv(|x| x as f32).into_shared(),
+            fields,
+            topology,
+        }
+    }
+}
+
+impl From<&UMeshF32> for UMesh {
+    fn from(mesh: &UMeshF32) -> Self {
+        let mut res = mesh.topology.clone();
+        res.coords = mesh.coords.mapv(f64::from).into_shared();
+        res.invalidate_cache();
+        for (et, fields) in &mesh.fields {
+            res.element_blocks.get_mut(et).unwrap().fields = fields
+                .iter()
+                .map(|(name, f)| (name.clone(), f.mapv(f64::from).into_shared()))
+                .collect();
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_umesh_f32_roundtrip() {
+        let mut mesh = me::make_imesh_2d(2);
+        let values = nd::arr1(&[0.0, 0.25, 0.5, 1.0]).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let single = UMeshF32::from(&mesh);
+        assert_eq!(single.num_elements(), 4);
+        assert_eq!(single.coords()[[8, 1]], 1.0);
+        assert_eq!(single.field(ElementType::QUAD4, "f").unwrap()[[1]], 0.25);
+        // Values exactly representable in single precision are kept
+        assert_eq!(UMesh::from(&single), mesh);
+
+        let mut rounded = mesh.clone();
+        rounded.coords_mut()[[0, 0]] = 0.1;
+        let back = UMesh::from(&UMeshF32::from(&rounded));
+        assert_eq!(back.coords()[[0, 0]], f64::from(0.1_f32));
+        assert_eq!(UMesh::from(&UMeshF32::from(&back)), back);
+    }
+}
@@ -0,0 +1,250 @@
+//! Chunked processing of meshes too large to be handled at once.
+//!
+//! Cells are yielded in chunks of consecutive cells of the same type, each chunk being a small
+//! [`UMesh`] sharing the coordinates of the whole mesh. Block-wise algorithms then run chunk by
+//! chunk and only their aggregated results are kept, e.g. with [`measure_stats`].
+//!
+//! VTKHDF files are read incrementally by [`read_blocks`]: only the coordinates are loaded at
+//! once. Other formats are read entirely before being split.
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use ndarray as nd;
+
+use crate::mesh::{ElementIds, ElementType, UMesh, UMeshView};
+use crate::tools::measure;
+
+/// Consecutive cells of one element type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellChunk {
+    /// Index of the first cell of the chunk among the cells of its type.
+    pub first: usize,
+    /// The cells of the chunk, with the coordinates of the whole mesh.
+    pub mesh: UMesh,
+}
+
+/// Items yielded by [`read_blocks`].
+pub type ChunkResult = Result<(ElementType, CellChunk), Box<dyn std::error::Error>>;
+
+/// Iterator over the cells of an in-memory mesh, by chunks. See [`chunks`].
+pub struct Chunks<M> {
+    mesh: M,
+    blocks: Vec<(ElementType, usize)>,
+    block: usize,
+    first: usize,
+    chunk_size: usize,
+}
+
+impl<M: Borrow<UMesh>> Iterator for Chunks<M> {
+    type Item = (ElementType, CellChunk);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(et, len)) = self.blocks.get(self.block) {
+            if self.first >= len {
+                self.block += 1;
+                self.first = 0;
+                continue;
+            }
+            let first = self.first;
+            self.first = (first + self.chunk_size).min(len);
+            let ids = ElementIds::from(BTreeMap::from([(et, (first..self.first).collect())]));
+            let mesh = self.mesh.borrow().extract(&ids, true);
+            return Some((et, CellChunk { first, mesh }));
+        }
+        None
+    }
+}
+
+/// Splits the cells of a mesh into chunks of at most `chunk_size` cells of the same type, with
+/// their fields.
+///
+/// The mesh can be given by reference or by value.
+pub fn chunks<M: Borrow<UMesh>>(mesh: M, chunk_size: usize) -> Chunks<M> {
+    let blocks = mesh
+        .borrow()
+        .blocks()
+        .map(|(&et, block)| (et, block.len()))
+        .collect();
+    Chunks {
+        mesh,
+        blocks,
+        block: 0,
+        first: 0,
+        chunk_size: chunk_size.max(1),
+    }
+}
+
+/// Reads the cells of a mesh file by chunks of at most `chunk_size` cells of the same type.
+///
+/// VTKHDF files are read incrementally, keeping only the coordinates and the current chunk in
+/// memory. Other formats are read entirely by [`crate::prelude::read`], then split with
+/// [`chunks`].
+pub fn read_blocks(
+    path: &Path,
+    chunk_size: usize,
+) -> Result<Box<dyn Iterator<Item = ChunkResult>>, Box<dyn std::error::Error>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        #[cfg(feature = "hdf5")]
+        "vtkhdf" | "h5" | "hdf5" => Ok(Box::new(crate::io::read_chunks(path, chunk_size)?)),
+        _ => {
+            let mesh = crate::io::read(path)?;
+            Ok(Box::new(chunks(mesh, chunk_size).map(Ok)))
+        }
+    }
+}
+
+/// Running statistics of values processed chunk by chunk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    /// Number of values.
+    pub count: usize,
+    /// Smallest value, infinite if there are none.
+    pub min: f64,
+    /// Largest value, negative infinite if there are none.
+    pub max: f64,
+    /// Sum of the values.
+    pub sum: f64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+        }
+    }
+}
+
+impl Stats {
+    /// Adds a value to the statistics.
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+    }
+
+    /// Mean of the values, or `None` if there are none.
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+impl Extend<f64> for Stats {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|x| self.push(x));
+    }
+}
+
+/// Runs a cell-wise algorithm on each chunk and aggregates its values per element type.
+///
+/// The algorithm returns one array of values per element type, as [`measure()`] does. Multi
+/// component values are aggregated all together. Processing stops at the first error.
+pub fn map_stats<E>(
+    chunks: impl IntoIterator<Item = Result<(ElementType, CellChunk), E>>,
+    mut algorithm: impl FnMut(UMeshView) -> BTreeMap<ElementType, nd::ArrayD<f64>>,
+) -> Result<BTreeMap<ElementType, Stats>, E> {
+    let mut res: BTreeMap<ElementType, Stats> = BTreeMap::new();
+    for chunk in chunks {
+        let (_, chunk) = chunk?;
+        for (et, values) in algorithm(chunk.mesh.view()) {
+            res.entry(et).or_default().extend(values.iter().copied());
+        }
+    }
+    Ok(res)
+}
+
+/// Statistics of the measures (lengths, areas or volumes) of the cells, per element type.
+pub fn measure_stats<E>(
+    chunks: impl IntoIterator<Item = Result<(ElementType, CellChunk), E>>,
+) -> Result<BTreeMap<ElementType, Stats>, E> {
+    map_stats(chunks, |mesh| {
+        let dim = mesh.topological_dimension();
+        measure(mesh, dim)
+            .into_iter()
+            .map(|(et, m)| (et, m.into_dyn()))
+            .collect()
+    })
+}
+
+/// Statistics of the values of a field, per element type.
+///
+/// Chunks whose cells do not carry the field are skipped.
+pub fn field_stats<E>(
+    chunks: impl IntoIterator<Item = Result<(ElementType, CellChunk), E>>,
+    name: &str,
+) -> Result<BTreeMap<ElementType, Stats>, E> {
+    map_stats(chunks, |mesh| {
+        mesh.blocks()
+            .filter_map(|(&et, block)| Some((et, block.fields.get(name)?.to_owned())))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementId;
+    use crate::mesh_examples as me;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_chunks() {
+        let mesh = me::make_mesh_2d_multi();
+        let all: Vec<(ElementType, CellChunk)> = chunks(&mesh, 1).collect();
+        assert_eq!(all.len(), 4);
+        assert!(all.iter().all(|(_, c)| c.mesh.num_elements() == 1));
+        let second_seg = all
+            .iter()
+            .find(|(et, c)| *et == ElementType::SEG2 && c.first == 1)
+            .unwrap();
+        assert_eq!(
+            second_seg
+                .1
+                .mesh
+                .element(ElementId::new(ElementType::SEG2, 0))
+                .connectivity,
+            &[1, 3]
+        );
+        assert_eq!(chunks(&mesh, 10).count(), 3);
+    }
+
+    #[test]
+    fn test_measure_and_field_stats() {
+        let mut mesh = me::make_imesh_2d(4);
+        let values = nd::Array1::from_iter((0..16).map(f64::from)).into_dyn();
+        mesh.assign_field("f", None, values.view()).unwrap();
+        let stats = measure_stats(chunks(&mesh, 3).map(Ok::<_, String>)).unwrap();
+        let quads = stats[&ElementType::QUAD4];
+        assert_eq!(quads.count, 16);
+        assert_relative_eq!(quads.sum, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(quads.max, 1.0 / 16.0, epsilon = 1e-12);
+
+        let stats = field_stats(chunks(mesh, 5).map(Ok::<_, String>), "f").unwrap();
+        let quads = stats[&ElementType::QUAD4];
+        assert_eq!((quads.min, quads.max), (0.0, 15.0));
+        assert_eq!(quads.mean(), Some(7.5));
+    }
+
+    #[test]
+    #[cfg(feature = "hdf5")]
+    fn test_read_blocks_hdfvtk() {
+        let path = std::path::PathBuf::from("test_read_blocks.vtkhdf");
+        let mesh = me::make_imesh_2d(3);
+        crate::io::write(&path, mesh.view()).unwrap();
+        let stats = measure_stats(read_blocks(&path, 4).unwrap());
+        std::fs::remove_file(path).unwrap();
+        let quads = stats.unwrap()[&ElementType::QUAD4];
+        assert_eq!(quads.count, 9);
+        assert_relative_eq!(quads.sum, 1.0, epsilon = 1e-12);
+    }
+}
@@ -0,0 +1,299 @@
+//! Example mesh generators for testing and demonstration.
+//!
+//! Besides small hand-written meshes, it provides parametric generators of meshes which often
+//! break mesh algorithms: randomly perturbed grids, hanging nodes, non-manifold surfaces and
+//! meshes holding every element type. [Proptest](https://docs.rs/proptest) strategies built on
+//! them let downstream crates fuzz their own algorithms.
+//!
+//! This module is only compiled with the `testing` feature.
+
+use ndarray as nd;
+use proptest::prelude::*;
+
+use crate::geometry::reference_nodes;
+use crate::prelude as mf;
+
+/// Creates a simple 2D mesh with a single QUAD4 element.
+pub fn make_mesh_2d_quad() -> mf::UMesh {
+    let coords =
+        nd::ArcArray2::from_shape_vec((4, 2), vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0])
+            .unwrap();
+    let mut mesh = mf::UMesh::new(coords);
+    mesh.add_regular_block(
+        mf::ElementType::QUAD4,
+        nd::arr2(&[[0, 1, 3, 2]]).to_shared(),
+        None,
+    );
+    mesh
+}
+
+/// Creates a simple 3D mesh with two SEG2 elements.
+pub fn make_mesh_3d_seg2() -> mf::UMesh {
+    let coords = nd::Array2::from_shape_vec((3, 1), vec![0.0, 1.0, 2.0]).unwrap();
+    let mut mesh = mf::UMesh::new(coords.into());
+    mesh.add_regular_block(
+        mf::ElementType::SEG2,
+        nd::arr2(&[[0, 1], [1, 2]]).to_shared(),
+        None,
+    );
+    mesh
+}
+
+/// Creates a 2D mesh with multiple element types:
+/// - Two SEG2 elements
+/// - One QUAD4 element
+/// - One PGON element
+pub fn make_mesh_2d_multi() -> mf::UMesh {
+    let coords = nd::Array2::from_shape_vec(
+        (5, 2),
+        vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.5, 0.5],
+    )
+    .unwrap();
+    let mut mesh = mf::UMesh::new(coords.into());
+    mesh.add_regular_block(
+        mf::ElementType::SEG2,
+        nd::arr2(&[[0, 1], [1, 3]]).to_shared(),
+        None,
+    );
+    mesh.add_regular_block(
+        mf::ElementType::QUAD4,
+        nd::arr2(&[[0, 1, 3, 2]]).to_shared(),
+        None,
+    );
+    mesh.add_element(mf::ElementType::PGON, &[0, 1, 4, 3, 2], None, None);
+    mesh
+}
+
+/// Creates a structured 2D mesh with `n x n` QUAD4 elements.
+pub fn make_imesh_2d(n: usize) -> mf::UMesh {
+    mf::RegularUMeshBuilder::new()
+        .add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect())
+        .add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect())
+        .build()
+}
+
+/// Creates a structured 3D mesh with `n x n x n` HEX8 elements.
+pub fn make_imesh_3d(n: usize) -> mf::UMesh {
+    mf::RegularUMeshBuilder::new()
+        .add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect())
+        .add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect())
+        .add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect())
+        .build()
+}
+
+/// Small deterministic pseudo-random generator (SplitMix64), so that generated meshes only depend
+/// on their seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `[-1, 1)`.
+    fn next_signed(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+}
+
+/// Creates a structured mesh of the unit square (`dim = 2`) or cube (`dim = 3`) with `n` cells
+/// per axis, whose inner nodes are moved randomly.
+///
+/// Each coordinate of an inner node moves by at most `amplitude` times the cell size, boundary
+/// nodes stay in place so that the mesh still covers the unit square or cube. Cells stay valid
+/// for an amplitude below 0.25. The same seed always gives the same mesh.
+///
+/// # Panics
+/// Panics if `dim` is not 1, 2 or 3.
+pub fn make_perturbed_imesh(dim: usize, n: usize, amplitude: f64, seed: u64) -> mf::UMesh {
+    assert!((1..=3).contains(&dim), "Grids have 1, 2 or 3 axes.");
+    let mut builder = mf::RegularUMeshBuilder::new();
+    for _ in 0..dim {
+        builder = builder.add_axis((0..=n).map(|k| (k as f64) / (n as f64)).collect());
+    }
+    let mut mesh = builder.build();
+    let mut rng = SplitMix64(seed);
+    let h = 1.0 / n as f64;
+    for mut node in mesh.coords_mut().outer_iter_mut() {
+        if node.iter().any(|&x| x == 0.0 || x == 1.0) {
+            continue;
+        }
+        node.iter_mut()
+            .for_each(|x| *x += amplitude * h * rng.next_signed());
+    }
+    mesh
+}
+
+/// Creates a non conforming 2D mesh with three QUAD4 elements: a unit square on the left, and
+/// its right neighbour split in two.
+///
+/// The node (1, 0.5) hangs on the right edge of the left square, which does not hold it.
+pub fn make_mesh_hanging_nodes() -> mf::UMesh {
+    let coords = nd::arr2(&[
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [2.0, 0.0],
+        [0.0, 1.0],
+        [1.0, 1.0],
+        [2.0, 1.0],
+        [1.0, 0.5],
+        [2.0, 0.5],
+    ]);
+    let mut mesh = mf::UMesh::new(coords.into_shared());
+    mesh.add_regular_block(
+        mf::ElementType::QUAD4,
+        nd::arr2(&[[0, 1, 4, 3], [1, 2, 7, 6], [6, 7, 5, 4]]).to_shared(),
+        None,
+    );
+    mesh
+}
+
+/// Creates a non-manifold 3D surface mesh:
+/// - Three QUAD4 elements sharing the edge between nodes 0 and 1, like the pages of a book
+/// - One TRI3 element touching the first QUAD4 at node 3 only
+pub fn make_mesh_non_manifold() -> mf::UMesh {
+    let coords = nd::arr2(&[
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 1.0, 1.0],
+        [-1.0, -1.0, 0.0],
+        [-1.0, -1.0, 1.0],
+        [2.0, 0.0, 2.0],
+        [1.0, 0.0, 2.0],
+    ]);
+    let mut mesh = mf::UMesh::new(coords.into_shared());
+    mesh.add_regular_block(
+        mf::ElementType::QUAD4,
+        nd::arr2(&[[0, 2, 3, 1], [0, 4, 5, 1], [0, 6, 7, 1]]).to_shared(),
+        None,
+    );
+    mesh.add_regular_block(
+        mf::ElementType::TRI3,
+        nd::arr2(&[[3, 8, 9]]).to_shared(),
+        None,
+    );
+    mesh
+}
+
+/// Creates a 3D mesh holding one element of each element type.
+///
+/// Regular elements are their reference element, and elements are translated along the x axis
+/// so that they do not touch each other. Poly elements are a SPLINE of four nodes, a pentagon
+/// and a tetrahedron described as a PHED. Cells are positively oriented and PHED faces point
+/// outward.
+pub fn make_mesh_all_types() -> mf::UMesh {
+    use mf::ElementType::*;
+    let mut coords: Vec<[f64; 3]> = Vec::new();
+    let mut elements: Vec<(mf::ElementType, Vec<usize>)> = Vec::new();
+    // Appends the nodes of an element, shifted along x
+    let mut place = |et: mf::ElementType, nodes: nd::ArrayView2<f64>| {
+        let shift = 3.0 * elements.len() as f64;
+        let start = coords.len();
+        for node in nodes.outer_iter() {
+            let mut x = [shift, 0.0, 0.0];
+            x.iter_mut().zip(node).for_each(|(x, n)| *x += n);
+            coords.push(x);
+        }
+        elements.push((et, (start..coords.len()).collect()));
+    };
+    for et in [
+        VERTEX, SEG2, SEG3, SEG4, TRI3, TRI6, TRI7, QUAD4, QUAD8, QUAD9, TET4, TET10, HEX8, HEX21,
+    ] {
+        place(et, reference_nodes(et).unwrap().view());
+    }
+    let spline = nd::arr2(&[[0.0, 0.0], [0.5, 0.5], [1.0, 0.5], [1.5, 0.0]]);
+    place(SPLINE, spline.view());
+    let pentagon = nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [1.5, 0.5], [1.0, 1.0], [0.0, 1.0]]);
+    place(PGON, pentagon.view());
+    place(PHED, reference_nodes(TET4).unwrap().view());
+
+    let mut mesh = mf::UMesh::new(nd::Array2::from(coords).into_shared());
+    for (et, mut nodes) in elements {
+        if et == PHED {
+            let [a, b, c, d] = nodes[..] else {
+                unreachable!()
+            };
+            let m = usize::MAX;
+            nodes = vec![a, c, b, m, a, b, d, m, b, c, d, m, a, d, c];
+        }
+        mesh.add_element(et, &nodes, None, None);
+    }
+    mesh
+}
+
+/// Strategy generating [perturbed](make_perturbed_imesh) structured meshes of dimension `dim`,
+/// with 1 to `max_n` cells per axis and valid cells.
+pub fn arb_perturbed_imesh(dim: usize, max_n: usize) -> impl Strategy<Value = mf::UMesh> {
+    (1..=max_n, 0.0..0.25, any::<u64>())
+        .prop_map(move |(n, amplitude, seed)| make_perturbed_imesh(dim, n, amplitude, seed))
+}
+
+/// Strategy generating 1 to `max_points` points of dimension `dim` in the unit square or cube,
+/// as an array of shape `[n_points, dim]`.
+pub fn arb_points(dim: usize, max_points: usize) -> impl Strategy<Value = nd::Array2<f64>> {
+    prop::collection::vec(prop::collection::vec(0.0..=1.0, dim), 1..=max_points).prop_map(
+        move |points| {
+            let n = points.len();
+            nd::Array2::from_shape_vec((n, dim), points.concat()).unwrap()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementId, ElementLike};
+    use crate::tools::{check_orientation, measure};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_perturbed_imesh() {
+        let mesh = make_perturbed_imesh(2, 4, 0.2, 42);
+        assert_eq!(mesh.num_elements(), 16);
+        assert_eq!(mesh.coords(), make_perturbed_imesh(2, 4, 0.2, 42).coords());
+        assert_ne!(mesh.coords(), make_perturbed_imesh(2, 4, 0.2, 43).coords());
+        assert_ne!(mesh.coords(), make_imesh_2d(4).coords());
+        let area: f64 = measure(mesh.view(), None)[&mf::ElementType::QUAD4].sum();
+        assert_abs_diff_eq!(area, 1.0, epsilon = 1e-12);
+        assert!(check_orientation(mesh.view(), 0.0).inverted.is_empty());
+    }
+
+    #[test]
+    fn test_special_meshes() {
+        let mesh = make_mesh_hanging_nodes();
+        let left = mesh.element(ElementId::new(mf::ElementType::QUAD4, 0));
+        assert!(!left.connectivity().contains(&6));
+
+        let mesh = make_mesh_non_manifold();
+        let sharing = mesh
+            .elements()
+            .filter(|e| e.connectivity().contains(&0) && e.connectivity().contains(&1))
+            .count();
+        assert_eq!(sharing, 3);
+
+        let mesh = make_mesh_all_types();
+        assert_eq!(mesh.blocks().count(), 17);
+        assert_eq!(mesh.num_elements(), 17);
+        assert!(check_orientation(mesh.view(), 0.0).inverted.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn perturbed_imesh_is_valid(mesh in arb_perturbed_imesh(3, 3)) {
+            prop_assert!(check_orientation(mesh.view(), 0.0).inverted.is_empty());
+        }
+
+        #[test]
+        fn points_are_in_unit_box(points in arb_points(2, 10)) {
+            prop_assert_eq!(points.ncols(), 2);
+            prop_assert!(points.iter().all(|&x| (0.0..=1.0).contains(&x)));
+        }
+    }
+}
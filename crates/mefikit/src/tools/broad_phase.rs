@@ -0,0 +1,146 @@
+//! Bounding boxes of elements and broad-phase search of the elements of two meshes that may
+//! intersect.
+//!
+//! The broad phase only compares bounding boxes: the pairs it finds are candidates that the
+//! narrow phase of intersection, overlap detection or remapping must then check exactly.
+
+use std::collections::BTreeMap;
+
+use ndarray as nd;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::mesh::{Dimension, ElementId, ElementLike, ElementType, UMeshView};
+
+/// Computes the axis aligned bounding box of each element of dimension `dim`, the topological
+/// dimension of the mesh by default.
+///
+/// Returns a map of element types to arrays of shape `[n, 2 * space_dimension]`, holding the
+/// minimum coordinates of each element then its maximum coordinates. Blocks and elements are
+/// processed in parallel with the `rayon` feature.
+pub fn compute_bboxes(
+    mesh: UMeshView,
+    dim: Option<Dimension>,
+) -> BTreeMap<ElementType, nd::Array2<f64>> {
+    let Some(dim) = dim.or(mesh.topological_dimension()) else {
+        return BTreeMap::new();
+    };
+    let space_dim = mesh.space_dimension();
+    mesh.par_blocks()
+        .filter(|(et, _)| et.dimension() == dim)
+        .map(|(&k, v)| {
+            let bboxes: Vec<([f64; 3], [f64; 3])> = v
+                .par_iter(mesh.coords.view())
+                .map(|e| {
+                    let mut bbox = ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]);
+                    // Faces of polyhedra are separated by usize::MAX
+                    for &n in e.connectivity().iter().filter(|&&n| n != usize::MAX) {
+                        for (j, &x) in mesh.coords.row(n).iter().enumerate() {
+                            bbox.0[j] = bbox.0[j].min(x);
+                            bbox.1[j] = bbox.1[j].max(x);
+                        }
+                    }
+                    bbox
+                })
+                .collect();
+            (
+                k,
+                nd::Array2::from_shape_fn((v.len(), 2 * space_dim), |(i, j)| match j < space_dim {
+                    true => bboxes[i].0[j],
+                    false => bboxes[i].1[j - space_dim],
+                }),
+            )
+        })
+        .collect()
+}
+
+/// Finds the pairs of elements of `a` and `b` whose bounding boxes intersect.
+///
+/// Only the elements of dimension `dim_a` of `a` and `dim_b` of `b` are paired, the topological
+/// dimensions of the meshes by default. Pairs are sorted by element of `a` then of `b`. The
+/// bounding boxes of `b` are located with [`UMeshBase::element_locator`], kept in the cache of `b`,
+/// so reusing the same `b` against several meshes only builds its tree once.
+///
+/// [`UMeshBase::element_locator`]: crate::mesh::UMeshBase::element_locator
+pub fn broad_phase(
+    a: UMeshView,
+    b: UMeshView,
+    dim_a: Option<Dimension>,
+    dim_b: Option<Dimension>,
+) -> Vec<(ElementId, ElementId)> {
+    let (Some(dim_a), Some(dim_b)) = (
+        dim_a.or(a.topological_dimension()),
+        dim_b.or(b.topological_dimension()),
+    ) else {
+        return Vec::new();
+    };
+    let locator = b.element_locator();
+    let boxes_a: Vec<(ElementId, _)> = a
+        .element_bounding_boxes()
+        .iter()
+        .filter(|(et, _)| et.dimension() == dim_a)
+        .flat_map(|(&et, boxes)| {
+            let ids = (0..boxes.len()).map(move |i| ElementId::new(et, i));
+            ids.zip(boxes)
+        })
+        .collect();
+    let candidates = |&(id_a, aabb): &(ElementId, _)| {
+        let mut ids_b: Vec<ElementId> = locator
+            .locate_in_envelope_intersecting(aabb)
+            .map(|r| r.data)
+            .filter(|id_b| id_b.element_type().dimension() == dim_b)
+            .collect();
+        ids_b.sort_unstable();
+        ids_b.into_iter().map(move |id_b| (id_a, id_b))
+    };
+
+    #[cfg(feature = "rayon")]
+    let pairs = boxes_a.par_iter().flat_map_iter(candidates);
+    #[cfg(not(feature = "rayon"))]
+    let pairs = boxes_a.iter().flat_map(candidates);
+    pairs.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_compute_bboxes() {
+        let mesh = me::make_mesh_2d_multi();
+        let bboxes = compute_bboxes(mesh.view(), None);
+        assert_eq!(bboxes.len(), 2);
+        assert_eq!(
+            bboxes[&ElementType::QUAD4],
+            nd::arr2(&[[0.0, 0.0, 1.0, 1.0]])
+        );
+        assert_eq!(
+            bboxes[&ElementType::PGON],
+            nd::arr2(&[[0.0, 0.0, 1.5, 1.0]])
+        );
+        let bboxes = compute_bboxes(mesh.view(), Some(Dimension::D1));
+        assert_eq!(
+            bboxes[&ElementType::SEG2],
+            nd::arr2(&[[0.0, 0.0, 1.0, 0.0], [1.0, 0.0, 1.0, 1.0]])
+        );
+    }
+
+    #[test]
+    fn test_broad_phase() {
+        let grid = me::make_imesh_2d(2);
+        let mesh = me::make_mesh_2d_multi();
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        let pairs = broad_phase(mesh.view(), grid.view(), Some(Dimension::D1), None);
+        // The segment from (1, 0) to (1, 1) touches the right column of the grid only
+        assert_eq!(pairs.len(), 4);
+        let seg = ElementId::new(ElementType::SEG2, 1);
+        let touched: Vec<_> = pairs.iter().filter(|p| p.0 == seg).map(|p| p.1).collect();
+        assert_eq!(touched, vec![quad(1), quad(3)]);
+        assert!(pairs.is_sorted());
+
+        let pairs = broad_phase(grid.view(), grid.view(), None, None);
+        // All the cells share the centre node
+        assert_eq!(pairs.len(), 16);
+    }
+}
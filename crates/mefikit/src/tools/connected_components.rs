@@ -24,7 +24,7 @@ pub fn compute_connected_components(
     link_dim: Option<Dimension>,
     with_fields: bool,
 ) -> Vec<UMesh> {
-    let graph = compute_neighbours_graph(mesh, src_dim, link_dim);
+    let graph = compute_neighbours_graph(mesh.view(), src_dim, link_dim);
     let compos = kosaraju_scc(&graph);
     #[cfg(feature = "rayon")]
     let res = compos
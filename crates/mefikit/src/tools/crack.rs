@@ -73,7 +73,7 @@ pub fn crack(mut mesh: UMesh, cut: UMeshView) -> UMesh {
     let nodes = cut.used_nodes();
     let index = mesh.select_ids(sel::nids(nodes.clone(), false));
     let mut near_mesh = mesh.extract(&index, true);
-    let (descending_mesh, f2c) = compute_sub_to_elem(&near_mesh, None, None);
+    let (descending_mesh, f2c) = compute_sub_to_elem(near_mesh.view(), None, None);
     // Throws if some element in cut is not in descending_mesh
     let cut_ids = find_equals(descending_mesh.view(), cut.view());
     let cut_c2c: Vec<[ElementId; 2]> = cut_ids
@@ -83,7 +83,7 @@ pub fn crack(mut mesh: UMesh, cut: UMeshView) -> UMesh {
         .map(|f_id| f2c[&f_id].clone().try_into().unwrap())
         .collect();
 
-    let mut near_c2c = compute_neighbours_graph(&near_mesh, None, None);
+    let mut near_c2c = compute_neighbours_graph(near_mesh.view(), None, None);
     for edge in &cut_c2c {
         near_c2c.remove_edge(edge[0], edge[1]);
     }
@@ -0,0 +1,206 @@
+//! Deformed shape of a mesh under a displacement field.
+//!
+//! Mechanical results are post-processed on the deformed mesh, with displacements amplified by a
+//! scale factor so that they are visible. [`max_displacement_before_inversion`] gives the largest
+//! factor keeping every element valid.
+
+use ndarray as nd;
+
+use crate::element_traits::ElementGeo;
+use crate::mesh::{ElementId, ElementLike, UMesh, UMeshView};
+use crate::trace;
+
+/// Largest scale factor tried before considering that a displacement never inverts an element.
+const MAX_SCALE: f64 = 1e12;
+/// Number of bisection steps on the scale factor, enough to reach the precision of `f64`.
+const BISECTION_STEPS: usize = 64;
+
+/// A displacement field, located at the nodes or at the cells of a mesh.
+#[derive(Clone, Copy, Debug)]
+pub enum Displacement<'a> {
+    /// One displacement per node, of shape `[n_nodes, space_dim]`.
+    Nodes(nd::ArrayView2<'a, f64>),
+    /// The name of a cell field of shape `[n_elem, space_dim]` on the cells of highest dimension.
+    /// The displacement of a node is the average of the values of the cells sharing it.
+    Cells(&'a str),
+}
+
+/// Computes the displacement of each node of the mesh, zero for nodes outside of the cells
+/// holding a cell field.
+fn node_displacements(
+    mesh: &UMeshView,
+    displacement: Displacement,
+) -> Result<nd::Array2<f64>, String> {
+    let shape = mesh.coords().dim();
+    match displacement {
+        Displacement::Nodes(values) => match values.dim() == shape {
+            true => Ok(values.to_owned()),
+            false => Err(format!(
+                "Node displacement has shape {:?}, expected {:?}.",
+                values.dim(),
+                shape
+            )),
+        },
+        Displacement::Cells(name) => {
+            let field = mesh
+                .field(name, None)
+                .ok_or(format!("No cell field named {name} in the mesh."))?;
+            let mut res = nd::Array2::<f64>::zeros(shape);
+            let mut count = vec![0usize; shape.0];
+            for (et, values) in &field.0 {
+                if values.shape() != [mesh.block(*et).unwrap().len(), shape.1] {
+                    return Err(format!(
+                        "Cell field {name} has shape {:?} on {et:?}, expected one vector per cell.",
+                        values.shape()
+                    ));
+                }
+                for (e, value) in values.outer_iter().enumerate() {
+                    let element = mesh.element(ElementId::new(*et, e));
+                    // Faces of polyhedra are separated by usize::MAX
+                    for &node in element.connectivity().iter().filter(|&&n| n != usize::MAX) {
+                        let mut dst = res.row_mut(node);
+                        dst += &value;
+                        count[node] += 1;
+                    }
+                }
+            }
+            for (mut row, &c) in res.outer_iter_mut().zip(&count) {
+                if c > 1 {
+                    row /= c as f64;
+                }
+            }
+            Ok(res)
+        }
+    }
+}
+
+/// Builds the deformed mesh, its nodes being moved by `scale` times the displacement.
+///
+/// Cell displacements are first averaged at the nodes, see [`Displacement::Cells`]. The deformed
+/// mesh keeps the connectivity, fields and groups of the original mesh. An error is returned if
+/// the displacement does not match the mesh.
+pub fn deform(mesh: UMeshView, displacement: Displacement, scale: f64) -> Result<UMesh, String> {
+    trace::span!("deform");
+    let displacements = node_displacements(&mesh, displacement)?;
+    let mut deformed = mesh.to_shared();
+    for (et, block) in mesh.blocks() {
+        let deformed_block = deformed.element_blocks.get_mut(et).unwrap();
+        deformed_block.fields = block
+            .fields
+            .iter()
+            .map(|(name, f)| (name.clone(), f.to_shared()))
+            .collect();
+        deformed_block.field_locations = block.field_locations.clone();
+        deformed_block.families = block.families.to_shared();
+        deformed_block.groups = block.groups.clone();
+    }
+    deformed.coords_mut().scaled_add(scale, &displacements);
+    Ok(deformed)
+}
+
+/// Computes the largest scale factor of the displacement for which [`deform`] inverts no
+/// element.
+///
+/// An element is inverted when one of its [vertex jacobians](ElementGeo::vertex_jacobians) is
+/// negative or null. Only the elements valid in the original mesh are checked, and poly
+/// elements or elements of a dimension lower than the space dimension are ignored. The factor is
+/// found by doubling then bisection, so an inversion happening only between two tried factors
+/// may be missed. Returns `f64::INFINITY` if the displacement never inverts an element.
+pub fn max_displacement_before_inversion(
+    mesh: UMeshView,
+    displacement: Displacement,
+) -> Result<f64, String> {
+    trace::span!("max_displacement_before_inversion");
+    let displacements = node_displacements(&mesh, displacement)?;
+    let is_valid = |jacobians: Vec<f64>| jacobians.iter().all(|&j| j > 0.0);
+    let checked: Vec<ElementId> = mesh
+        .elements()
+        .filter(|e| e.vertex_jacobians().is_some_and(is_valid))
+        .map(|e| e.id())
+        .collect();
+    if checked.is_empty() {
+        return Ok(f64::INFINITY);
+    }
+
+    let mut deformed = mesh.to_shared();
+    let mut inverts = |scale: f64| {
+        let mut coords = deformed.coords_mut();
+        coords.assign(&mesh.coords());
+        coords.scaled_add(scale, &displacements);
+        checked.iter().any(|&id| {
+            !deformed
+                .element(id)
+                .vertex_jacobians()
+                .is_some_and(is_valid)
+        })
+    };
+    let (mut low, mut high) = (0.0, 1.0);
+    while !inverts(high) {
+        if high > MAX_SCALE {
+            return Ok(f64::INFINITY);
+        }
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (low + high);
+        match inverts(mid) {
+            true => high = mid,
+            false => low = mid,
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementIds, ElementType};
+    use crate::mesh_examples as me;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_deform_nodes() {
+        let mut mesh = me::make_imesh_2d(1);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 0);
+        mesh.set_group("square", &ids);
+        // The corner (1, 1) moves toward the origin
+        let mut u = nd::Array2::<f64>::zeros((4, 2));
+        u.row_mut(3).fill(-1.0);
+
+        let deformed = deform(mesh.view(), Displacement::Nodes(u.view()), 0.25).unwrap();
+        assert_eq!(deformed.coords().row(3).to_vec(), vec![0.75, 0.75]);
+        assert_eq!(deformed.coords().row(1), mesh.coords().row(1));
+        assert_eq!(deformed.group_as_element_ids("square").len(), 1);
+        assert_eq!(mesh.coords().row(3).to_vec(), vec![1.0, 1.0]);
+
+        let scale = max_displacement_before_inversion(mesh.view(), Displacement::Nodes(u.view()));
+        assert_abs_diff_eq!(scale.unwrap(), 0.5, epsilon = 1e-12);
+        // A translation never inverts
+        let translation = nd::Array2::<f64>::ones((4, 2));
+        let scale =
+            max_displacement_before_inversion(mesh.view(), Displacement::Nodes(translation.view()));
+        assert_eq!(scale, Ok(f64::INFINITY));
+
+        let wrong = nd::Array2::<f64>::zeros((3, 2));
+        assert!(deform(mesh.view(), Displacement::Nodes(wrong.view()), 1.0).is_err());
+    }
+
+    #[test]
+    fn test_deform_cells() {
+        let mut mesh = me::make_imesh_2d(2);
+        let u = nd::arr2(&[[1.0, 0.0], [1.0, 0.0], [0.0, 0.0], [0.0, 0.0]]);
+        mesh.add_field(ElementType::QUAD4, "u", u.into_dyn().into_shared());
+
+        let deformed = deform(mesh.view(), Displacement::Cells("u"), 1.0).unwrap();
+        let coords = deformed.coords();
+        // Node 0 belongs to the first cell only, the centre node to the four cells
+        assert_eq!(coords.row(0).to_vec(), vec![1.0, 0.0]);
+        assert_eq!(coords.row(3).to_vec(), vec![0.5, 0.5]);
+        assert_eq!(coords.row(4).to_vec(), vec![1.0, 0.5]);
+        assert_eq!(coords.row(8).to_vec(), vec![1.0, 1.0]);
+        assert!(deformed.field("u", None).is_some());
+        assert!(deform(mesh.view(), Displacement::Cells("v"), 1.0).is_err());
+    }
+}
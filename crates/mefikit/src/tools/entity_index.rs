@@ -0,0 +1,148 @@
+//! Lookup of the elements sharing a subentity, from the nodes of the subentity.
+
+use itertools::Itertools;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+use smallvec::{SmallVec, smallvec};
+
+use crate::element_traits::{ElementTopo, SortedVecKey};
+use crate::mesh::{Dimension, Element, ElementId, ElementLike, UMeshView};
+
+type Sharing = SmallVec<[(ElementId, usize); 2]>;
+
+/// The subentities (faces in 3D, edges in 2D, ...) of the elements of a mesh, keyed by their
+/// nodes regardless of their order.
+///
+/// Each subentity maps to the elements having it, sorted, with the local index of the
+/// subentity among the subentities of each element. This is the lookup behind the neighbours
+/// graph, the parallel boundaries and the skin.
+#[derive(Clone, Debug, Default)]
+pub struct EntityIndex {
+    map: FxHashMap<SortedVecKey, Sharing>,
+}
+
+impl EntityIndex {
+    /// Indexes the subentities of dimension `target_dim` of the elements of dimension `src_dim`.
+    ///
+    /// By default `src_dim` is the topological dimension of the mesh and `target_dim` the
+    /// dimension below. Elements are scanned in parallel (requires `rayon` feature).
+    pub fn build(
+        mesh: UMeshView,
+        src_dim: Option<Dimension>,
+        target_dim: Option<Dimension>,
+    ) -> Self {
+        let Some(src_dim) = src_dim.or(mesh.topological_dimension()) else {
+            return Self::default();
+        };
+        let codim = match target_dim {
+            Some(t) => src_dim - t,
+            None => Dimension::D1,
+        };
+        let add_element = |mut map: FxHashMap<SortedVecKey, Sharing>, elem: Element| {
+            let subentities = elem.subentities(Some(codim));
+            let all_conns = subentities.iter().flat_map(|(_, conn)| conn.iter());
+            for (i, co) in all_conns.enumerate() {
+                map.entry(SortedVecKey::new(co.into()))
+                    .and_modify(|sharing| sharing.push((elem.id(), i)))
+                    .or_insert_with(|| smallvec![(elem.id(), i)]);
+            }
+            map
+        };
+
+        #[cfg(feature = "rayon")]
+        let mut map = mesh
+            .par_elements_of_dim(src_dim)
+            .fold(FxHashMap::default, add_element)
+            .reduce(FxHashMap::default, |mut a, b| {
+                for (key, sharing) in b {
+                    a.entry(key).or_default().extend(sharing);
+                }
+                a
+            });
+        #[cfg(not(feature = "rayon"))]
+        let mut map = mesh
+            .elements_of_dim(src_dim)
+            .fold(FxHashMap::default(), add_element);
+
+        // Sorted so that the index does not depend on the number of threads
+        map.values_mut().for_each(|sharing| sharing.sort_unstable());
+        Self { map }
+    }
+
+    /// Returns the number of distinct subentities.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if there is no subentity.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the elements having a subentity of the given nodes, in any order.
+    pub fn find(&self, nodes: &[usize]) -> Vec<ElementId> {
+        self.find_local(nodes).iter().map(|&(id, _)| id).collect()
+    }
+
+    /// Returns the elements having a subentity of the given nodes, with the local index of the
+    /// subentity in each of them.
+    pub fn find_local(&self, nodes: &[usize]) -> &[(ElementId, usize)] {
+        self.map
+            .get(&SortedVecKey::new(nodes.into()))
+            .map_or(&[][..], |sharing| sharing.as_slice())
+    }
+
+    /// Iterates over the subentities, in increasing order of their sorted nodes, with the
+    /// elements having them.
+    ///
+    /// The order does not depend on the hash map, so neither do the results built from it.
+    pub fn iter(&self) -> impl Iterator<Item = (&SortedVecKey, &[(ElementId, usize)])> {
+        self.map
+            .iter()
+            .sorted_unstable_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(key, sharing)| (key, sharing.as_slice()))
+    }
+}
+
+/// Indexes the faces of the elements of highest dimension of a mesh (edges in 2D).
+///
+/// See [`EntityIndex::build`]. Meshes keep such an index in their cache, see
+/// [`UMeshBase::face_index`](crate::mesh::UMeshBase::face_index).
+pub fn build_face_index(mesh: UMeshView) -> EntityIndex {
+    EntityIndex::build(mesh, None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementType;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_face_index() {
+        let mesh = me::make_imesh_2d(2);
+        let index = build_face_index(mesh.view());
+        // 2x2 quads: 12 edges, 4 of them inner
+        assert_eq!(index.len(), 12);
+        assert_eq!(index.iter().filter(|(_, s)| s.len() == 2).count(), 4);
+
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        assert_eq!(index.find(&[4, 1]), vec![quad(0), quad(1)]);
+        assert_eq!(index.find(&[0, 1]), vec![quad(0)]);
+        assert!(index.find(&[0, 4]).is_empty());
+        let (id, local) = index.find_local(&[1, 4])[1];
+        let edges = mesh.element(id).subentities(None);
+        let mut edge = edges[0].1.iter().nth(local).unwrap().to_vec();
+        edge.sort_unstable();
+        assert_eq!(edge, [1, 4]);
+
+        let keys: Vec<&SortedVecKey> = index.iter().map(|(key, _)| key).collect();
+        assert!(keys.is_sorted());
+
+        assert_eq!(mesh.face_index().len(), 12);
+        let vertices = EntityIndex::build(mesh.view(), None, Some(Dimension::D0));
+        assert_eq!(vertices.len(), 9);
+        assert_eq!(vertices.find(&[4]).len(), 4);
+    }
+}
@@ -1,5 +1,7 @@
-use crate::mesh::{ElementType, UMesh, UMeshView};
+use crate::mesh::{ElementLike, ElementType, UMesh, UMeshView};
 
+use itertools::Itertools;
+use nalgebra as na;
 use ndarray::{self as nd, ArrayView1, s};
 
 /// This is the most simple extrusion method.
@@ -323,6 +325,7 @@ pub fn extrude(mesh: UMeshView, along: &[f64]) -> UMesh {
     if along.len() == 1 {
         let mut extruded_mesh = mesh.to_shared();
         extruded_mesh.coords = new_coords.into_shared();
+        extruded_mesh.invalidate_cache();
         return extruded_mesh;
     }
     extrude_connectivity(mesh, along.len() - 1, new_coords)
@@ -336,6 +339,7 @@ pub fn extrude_parallel(mesh: UMeshView, along: nd::ArrayView2<'_, f64>) -> UMes
     if along.nrows() == 1 {
         let mut extruded_mesh = mesh.to_shared();
         extruded_mesh.coords = new_coords.into_shared();
+        extruded_mesh.invalidate_cache();
         return extruded_mesh;
     }
     extrude_connectivity(mesh, along.nrows() - 1, new_coords)
@@ -349,16 +353,164 @@ pub fn extrude_curv(mesh: UMeshView, along: nd::ArrayView2<'_, f64>) -> UMesh {
     if along.nrows() == 1 {
         let mut extruded_mesh = mesh.to_shared();
         extruded_mesh.coords = new_coords.into_shared();
+        extruded_mesh.invalidate_cache();
         return extruded_mesh;
     }
     extrude_connectivity(mesh, along.nrows() - 1, new_coords)
 }
 
+/// Coordinates of a node padded to 3D.
+fn point3(coords: nd::ArrayView2<'_, f64>, n: usize) -> na::Vector3<f64> {
+    let mut p = na::Vector3::zeros();
+    for (x, &c) in p.iter_mut().zip(coords.row(n)) {
+        *x = c;
+    }
+    p
+}
+
+/// Normals of the nodes of a surface, with the edges of its elements.
+type NodeNormals = (Vec<na::Vector3<f64>>, Vec<[usize; 2]>);
+
+/// Area weighted normals of the nodes of a surface, with the edges of its elements.
+///
+/// Normals of nodes not used by the surface are zero.
+fn node_normals(surface: &UMeshView) -> Result<NodeNormals, String> {
+    use ElementType::*;
+    let space_dim = surface.space_dimension();
+    let point = |n: usize| point3(surface.coords(), n);
+    let mut normals = vec![na::Vector3::zeros(); surface.coords().nrows()];
+    let mut edges = Vec::new();
+    for elem in surface.elements() {
+        let co = elem.connectivity();
+        let normal = match (elem.element_type(), space_dim) {
+            (SEG2, 2) => {
+                let t = point(co[1]) - point(co[0]);
+                na::Vector3::new(t.y, -t.x, 0.0)
+            }
+            (TRI3, 3) => (point(co[1]) - point(co[0])).cross(&(point(co[2]) - point(co[0]))) / 2.0,
+            (QUAD4, 3) => (point(co[2]) - point(co[0])).cross(&(point(co[3]) - point(co[1]))) / 2.0,
+            (et, d) => {
+                return Err(format!(
+                    "Normal extrusion of {et:?} elements in {d}D space is not supported"
+                ));
+            }
+        };
+        for &n in co {
+            normals[n] += normal;
+        }
+        edges.extend(co.iter().circular_tuple_windows().map(|(&a, &b)| [a, b]));
+    }
+    for normal in &mut normals {
+        *normal = normal.try_normalize(f64::EPSILON).unwrap_or_default();
+    }
+    Ok((normals, edges))
+}
+
+/// Extrudes a surface along its node normals into layers of cells, as in boundary layer meshes.
+///
+/// `thicknesses` are the thicknesses of the successive layers, starting from the surface.
+/// Segments in 2D give QUAD4 cells, and in 3D QUAD4 faces give HEX8 cells while TRI3 faces give
+/// prisms, stored as PHED cells. Element normals follow the right-hand rule: they point to the
+/// right of segments, and towards the side from which faces are seen counterclockwise. Node
+/// normals are the area weighted mean of the normals of their elements.
+///
+/// The first nodes of the result are the nodes of the surface, with the same numbering, followed
+/// by a copy of them per layer. Layers of a surface extracted from a volume, e.g. with
+/// [`compute_skin`](crate::tools::compute_skin), can be attached to the volume with
+/// [`UMesh::append`] and [`merge_nodes`](crate::tools::merge_nodes).
+///
+/// The total thickness is reduced at nodes where the normals of the two nodes of a surface edge
+/// converge, so that the edge keeps at least half of its length in the last layer. This prevents
+/// layers from folding in concave regions, but not layers of distant parts of the surface from
+/// colliding.
+///
+/// # Errors
+/// Returns an error if the surface has elements other than segments in 2D space, or triangles
+/// and quadrangles in 3D space.
+pub fn extrude_normal(surface: UMeshView, thicknesses: &[f64]) -> Result<UMesh, String> {
+    use ElementType::*;
+    let (normals, edges) = node_normals(&surface)?;
+    let coords = surface.coords();
+    let (n_nodes, space_dim) = coords.dim();
+
+    let total: f64 = thicknesses.iter().sum();
+    let mut heights = vec![total; n_nodes];
+    for [a, b] in edges {
+        let d = point3(coords, b) - point3(coords, a);
+        let closing = -(normals[b] - normals[a]).dot(&d);
+        if closing > 0.0 {
+            let max_height = 0.5 * d.norm_squared() / closing;
+            heights[a] = heights[a].min(max_height);
+            heights[b] = heights[b].min(max_height);
+        }
+    }
+
+    let mut new_coords = nd::Array2::zeros((n_nodes * (thicknesses.len() + 1), space_dim));
+    let mut offset = 0.0;
+    for k in 0..=thicknesses.len() {
+        for n in 0..n_nodes {
+            let scale = if total > 0.0 {
+                offset / total * heights[n]
+            } else {
+                0.0
+            };
+            for j in 0..space_dim {
+                new_coords[[k * n_nodes + n, j]] = coords[[n, j]] + scale * normals[n][j];
+            }
+        }
+        offset += thicknesses.get(k).copied().unwrap_or(0.0);
+    }
+
+    let mut extruded = UMesh::new(new_coords.into_shared());
+    let mut quads = Vec::new();
+    let mut hexas = Vec::new();
+    let (mut prisms, mut prism_offsets) = (Vec::new(), Vec::new());
+    for elem in surface.elements() {
+        let co = elem.connectivity();
+        for k in 0..thicknesses.len() {
+            let low: Vec<usize> = co.iter().map(|n| n + k * n_nodes).collect();
+            let high: Vec<usize> = co.iter().map(|n| n + (k + 1) * n_nodes).collect();
+            match elem.element_type() {
+                SEG2 => quads.extend([low[1], low[0], high[0], high[1]]),
+                QUAD4 => hexas.extend(low.iter().chain(&high)),
+                _ => {
+                    // Faces are oriented outwards
+                    let (a, b, c) = (low[0], low[1], low[2]);
+                    let (d, e, f) = (high[0], high[1], high[2]);
+                    let faces = [
+                        vec![a, c, b],
+                        vec![d, e, f],
+                        vec![a, b, e, d],
+                        vec![b, c, f, e],
+                        vec![c, a, d, f],
+                    ];
+                    prisms.extend(faces.join(&usize::MAX));
+                    prism_offsets.push(prisms.len());
+                }
+            }
+        }
+    }
+    for (et, conn, size) in [(QUAD4, quads, 4), (HEX8, hexas, 8)] {
+        if !conn.is_empty() {
+            let conn = nd::Array2::from_shape_vec((conn.len() / size, size), conn).unwrap();
+            extruded.add_regular_block(et, conn.into_shared(), None);
+        }
+    }
+    if !prisms.is_empty() {
+        extruded.add_poly_block(
+            PHED,
+            nd::ArcArray1::from(prisms),
+            nd::ArcArray1::from(prism_offsets),
+        );
+    }
+    Ok(extruded)
+}
+
 pub trait Extrudable {
     fn extrude(&self, along: &[f64]) -> UMesh;
     fn extrude_curv(&self, along: nd::ArrayView2<'_, f64>) -> UMesh;
     fn extrude_parallel(&self, along: nd::ArrayView2<'_, f64>) -> UMesh;
-    // fn extrude_grow_normal_dir(&self, along: &[f64]) -> UMesh;
+    fn extrude_normal(&self, thicknesses: &[f64]) -> Result<UMesh, String>;
     // fn extrude_grow_with_focal(&self, along: &[f64], focal: f64, normal: &[f64]);
 }
 
@@ -374,6 +526,10 @@ impl Extrudable for UMeshView<'_> {
     fn extrude_curv(&self, along: ndarray::ArrayView2<'_, f64>) -> UMesh {
         extrude_curv(self.clone(), along)
     }
+
+    fn extrude_normal(&self, thicknesses: &[f64]) -> Result<UMesh, String> {
+        extrude_normal(self.clone(), thicknesses)
+    }
 }
 
 impl Extrudable for UMesh {
@@ -388,12 +544,17 @@ impl Extrudable for UMesh {
     fn extrude_curv(&self, along: ndarray::ArrayView2<'_, f64>) -> UMesh {
         extrude_curv(self.view(), along)
     }
+
+    fn extrude_normal(&self, thicknesses: &[f64]) -> Result<UMesh, String> {
+        extrude_normal(self.view(), thicknesses)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mesh::{ElementType, UMesh};
+    use crate::mesh::{ElementId, ElementType, UMesh};
+    use approx::assert_abs_diff_eq;
     use ndarray as nd;
 
     #[test]
@@ -431,6 +592,69 @@ mod tests {
         assert_eq!(extruded.num_elements(), mesh.num_elements());
     }
 
+    #[test]
+    fn test_extrude_normal_2d() {
+        let coords = nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0]]).into_shared();
+        let mut line = UMesh::new(coords);
+        line.add_regular_block(
+            ElementType::SEG2,
+            nd::arr2(&[[0, 1], [1, 2]]).to_shared(),
+            None,
+        );
+        let layers = line.extrude_normal(&[0.1, 0.2]).unwrap();
+        assert_eq!(layers.coords().nrows(), 9);
+        assert_abs_diff_eq!(layers.coords()[[7, 1]], -0.3, epsilon = 1e-12);
+        // Counterclockwise quads below the line
+        assert_eq!(
+            layers
+                .element(ElementId::new(ElementType::QUAD4, 0))
+                .connectivity(),
+            [1, 0, 3, 4]
+        );
+        let areas = crate::tools::measure(layers.view(), None);
+        assert_abs_diff_eq!(areas[&ElementType::QUAD4].sum(), 0.6, epsilon = 1e-12);
+
+        // Layers are thinned in the concave corner at the origin
+        let coords = nd::arr2(&[[1.0, 0.0], [0.0, 0.0], [0.0, 1.0]]).into_shared();
+        let mut corner = UMesh::new(coords);
+        corner.add_regular_block(
+            ElementType::SEG2,
+            nd::arr2(&[[0, 1], [1, 2]]).to_shared(),
+            None,
+        );
+        let layers = corner.extrude_normal(&[2.0]).unwrap();
+        assert_abs_diff_eq!(layers.coords()[[4, 0]], 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(layers.coords()[[4, 1]], 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_extrude_normal_3d() {
+        let coords = nd::arr2(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [2.0, 0.0, 0.0],
+        ]);
+        let mut surface = UMesh::new(coords.into_shared());
+        surface.add_regular_block(
+            ElementType::QUAD4,
+            nd::arr2(&[[0, 1, 2, 3]]).to_shared(),
+            None,
+        );
+        surface.add_element(ElementType::TRI3, &[1, 4, 2], None, None);
+        let layers = surface.extrude_normal(&[0.5]).unwrap();
+        assert_eq!(layers.coords().row(5).to_vec(), vec![0.0, 0.0, 0.5]);
+        let volumes = crate::tools::measure(layers.view(), None);
+        assert_abs_diff_eq!(volumes[&ElementType::HEX8][0], 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(volumes[&ElementType::PHED][0], 0.25, epsilon = 1e-12);
+
+        assert!(surface.view().extrude_normal(&[]).is_ok());
+        let mut flat = UMesh::new(nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]).into_shared());
+        flat.add_element(ElementType::TRI3, &[0, 1, 2], None, None);
+        assert!(flat.extrude_normal(&[1.0]).is_err());
+    }
+
     #[test]
     fn test_extrude_coords_2d() {
         let coords = nd::arr2(&[[0.0], [1.0]]);
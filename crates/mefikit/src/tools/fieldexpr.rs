@@ -4,6 +4,7 @@
 //! field expressions using mathematical operations.
 
 use ndarray::{self as nd};
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::{
     ops::{Add, Div, Mul, Sub},
@@ -13,7 +14,7 @@ use std::{
 use crate::mesh::{Dimension, FieldArcD, FieldCowD, FieldOwnedD, UMesh, UMeshBase, UMeshView};
 
 /// An expression tree for field computations.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum FieldExpr {
     /// A broadcastable constant array.
     Array(nd::Array<f64, nd::IxDyn>),
@@ -43,7 +44,7 @@ pub enum FieldExpr {
 }
 
 /// Binary operations available in field expressions.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BinaryOp {
     /// Addition.
     Add,
@@ -58,7 +59,7 @@ pub enum BinaryOp {
 }
 
 /// Unary operations available in field expressions.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UnaryOp {
     /// Sine function.
     Sin,
@@ -1,4 +1,5 @@
-use crate::mesh::{ElementType, UMesh};
+use crate::mesh::{Dimension, ElementIds, ElementLike, ElementType, UMesh};
+use crate::tools::Descendable;
 use ndarray::{ArcArray2, Array2};
 
 /// Regular umesh builder (1d, 2d or 3d).
@@ -39,8 +40,14 @@ use ndarray::{ArcArray2, Array2};
 ///  +-----------+-----------+
 ///  0           1           2
 /// ```
+///
+/// The cells can be split into simplices with [`RegularUMeshBuilder::cell_type`], and the
+/// boundary elements can be added with one group per side with
+/// [`RegularUMeshBuilder::boundary_groups`].
 pub struct RegularUMeshBuilder {
     coords_grid: Vec<Vec<f64>>,
+    cell_type: Option<ElementType>,
+    boundary_groups: bool,
 }
 
 impl Default for RegularUMeshBuilder {
@@ -54,6 +61,8 @@ impl RegularUMeshBuilder {
     pub fn new() -> Self {
         Self {
             coords_grid: Vec::new(),
+            cell_type: None,
+            boundary_groups: false,
         }
     }
 
@@ -70,6 +79,134 @@ impl RegularUMeshBuilder {
         self
     }
 
+    /// Adds an axis of `n_cells` cells of equal size from `start` to `end`.
+    pub fn add_uniform_axis(self, start: f64, end: f64, n_cells: usize) -> Self {
+        self.add_geometric_axis(start, end, n_cells, 1.0)
+    }
+
+    /// Adds an axis of `n_cells` cells from `start` to `end`, each cell being `ratio` times
+    /// larger than the previous one.
+    ///
+    /// # Panics
+    /// Panics if `n_cells` is zero or `ratio` is not strictly positive.
+    pub fn add_geometric_axis(self, start: f64, end: f64, n_cells: usize, ratio: f64) -> Self {
+        assert!(n_cells > 0, "An axis needs at least one cell");
+        assert!(ratio > 0.0, "The growth ratio must be strictly positive");
+        // Cumulated relative sizes: sum of ratio^i for i < k
+        let mut weights = Vec::with_capacity(n_cells + 1);
+        let mut total = 0.0;
+        let mut size = 1.0;
+        weights.push(0.0);
+        for _ in 0..n_cells {
+            total += size;
+            size *= ratio;
+            weights.push(total);
+        }
+        let axis = weights
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == n_cells {
+                    end
+                } else {
+                    start + (end - start) * w / total
+                }
+            })
+            .collect();
+        self.add_axis(axis)
+    }
+
+    /// Adds an axis of `n_cells` cells from `start` to `end` with a geometric progression such
+    /// that the last cell is `bias` times larger than the first one.
+    pub fn add_biased_axis(self, start: f64, end: f64, n_cells: usize, bias: f64) -> Self {
+        let ratio = if n_cells > 1 {
+            bias.powf(1.0 / (n_cells - 1) as f64)
+        } else {
+            1.0
+        };
+        self.add_geometric_axis(start, end, n_cells, ratio)
+    }
+
+    /// Returns the coordinates of the grid points along each axis.
+    pub fn axes(&self) -> &[Vec<f64>] {
+        &self.coords_grid
+    }
+
+    /// Sets the type of the built cells.
+    ///
+    /// Supported types are SEG2 in 1D, QUAD4 (default) and TRI3 in 2D, HEX8 (default) and TET4 in
+    /// 3D. Each quadrangle is split into 2 triangles and each hexahedron into 6 tetrahedra, so
+    /// that the simplicial mesh is conformal.
+    pub fn cell_type(mut self, cell_type: ElementType) -> Self {
+        self.cell_type = Some(cell_type);
+        self
+    }
+
+    /// Whether to add the boundary elements to the mesh, with one group per side named `xmin`,
+    /// `xmax`, `ymin`, `ymax`, `zmin` and `zmax`.
+    pub fn boundary_groups(mut self, boundary_groups: bool) -> Self {
+        self.boundary_groups = boundary_groups;
+        self
+    }
+
+    /// Splits each quadrangle (or hexahedron) into triangles (or tetrahedra).
+    ///
+    /// The split uses the diagonal between the lowest and the highest node of each cell (Kuhn
+    /// triangulation), which is the same on both sides of a shared face.
+    fn simplexize(connectivity: &Array2<usize>) -> Array2<usize> {
+        const TRI3: [[usize; 3]; 2] = [[0, 1, 2], [0, 2, 3]];
+        const TET4: [[usize; 4]; 6] = [
+            [0, 1, 2, 6],
+            [0, 1, 6, 5],
+            [0, 3, 6, 2],
+            [0, 3, 7, 6],
+            [0, 4, 5, 6],
+            [0, 4, 6, 7],
+        ];
+        let split: Vec<&[usize]> = match connectivity.ncols() {
+            4 => TRI3.iter().map(|t| t.as_slice()).collect(),
+            8 => TET4.iter().map(|t| t.as_slice()).collect(),
+            _ => unreachable!(),
+        };
+        let n_nodes = split[0].len();
+        let connectivity: Vec<usize> = connectivity
+            .rows()
+            .into_iter()
+            .flat_map(|cell| {
+                split
+                    .iter()
+                    .flat_map(|s| s.iter().map(|&i| cell[i]).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        Array2::from_shape_vec((connectivity.len() / n_nodes, n_nodes), connectivity)
+            .expect("Failed to split the grid cells")
+    }
+
+    /// Adds the boundary elements and one group per side of the grid.
+    fn add_boundary_groups(&self, umesh: &mut UMesh) {
+        umesh.boundaries_update(None, None);
+        let dim = Dimension::try_from(self.coords_grid.len() as u8 - 1).unwrap();
+        let names = [["xmin", "xmax"], ["ymin", "ymax"], ["zmin", "zmax"]];
+        let mut groups: Vec<ElementIds> = vec![ElementIds::new(); 2 * self.coords_grid.len()];
+        for elem in umesh.elements_of_dim(dim) {
+            for (axis, values) in self.coords_grid.iter().enumerate() {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                let on = |v: f64| (0..elem.num_nodes()).all(|i| elem.coord(i)[axis] == v);
+                if on(min) {
+                    groups[2 * axis].add(elem.element_type(), elem.index());
+                }
+                if on(max) {
+                    groups[2 * axis + 1].add(elem.element_type(), elem.index());
+                }
+            }
+        }
+        for (i, ids) in groups.iter().enumerate() {
+            umesh.set_group(names[i / 2][i % 2], ids);
+        }
+    }
+
     /// Computes the element connectivity for the grid.
     fn compute_connectivity(&self) -> Array2<usize> {
         let num_axes = self.coords_grid.len();
@@ -174,25 +311,32 @@ impl RegularUMeshBuilder {
 
     /// Builds the mesh from the defined axes.
     ///
-    /// Creates a 1D (SEG2), 2D (QUAD4), or 3D (HEX8) mesh depending on
-    /// the number of axes added.
+    /// Creates a 1D (SEG2), 2D (QUAD4 or TRI3), or 3D (HEX8 or TET4) mesh depending on
+    /// the number of axes added and the chosen cell type.
+    ///
+    /// # Panics
+    /// Panics if the cell type is not supported for the number of axes.
     pub fn build(self) -> UMesh {
         let coords = self.compute_coords();
         let coords_dim = coords.shape()[1];
         let connectivity = self.compute_connectivity();
 
         let mut umesh = UMesh::new(ArcArray2::from(coords));
-        if coords_dim == 1 {
-            // 1D mesh
-            umesh.add_regular_block(ElementType::SEG2, connectivity.to_shared(), None);
-        } else if coords_dim == 2 {
-            // 2D mesh
-            umesh.add_regular_block(ElementType::QUAD4, connectivity.to_shared(), None);
-        } else if coords_dim == 3 {
-            // 3D mesh
-            umesh.add_regular_block(ElementType::HEX8, connectivity.to_shared(), None);
-        } else {
-            panic!("Unsupported number of dimensions for regular mesh");
+        let default_type = match coords_dim {
+            1 => ElementType::SEG2,
+            2 => ElementType::QUAD4,
+            3 => ElementType::HEX8,
+            _ => panic!("Unsupported number of dimensions for regular mesh"),
+        };
+        let cell_type = self.cell_type.unwrap_or(default_type);
+        let connectivity = match (coords_dim, cell_type) {
+            (_, et) if et == default_type => connectivity,
+            (2, ElementType::TRI3) | (3, ElementType::TET4) => Self::simplexize(&connectivity),
+            _ => panic!("Unsupported cell type {cell_type:?} for a {coords_dim}D regular mesh"),
+        };
+        umesh.add_regular_block(cell_type, connectivity.to_shared(), None);
+        if self.boundary_groups {
+            self.add_boundary_groups(&mut umesh);
         }
         umesh
     }
@@ -246,4 +390,67 @@ mod tests {
             &[4, 8]
         );
     }
+
+    #[test]
+    fn test_graded_axes() {
+        let mesh = RegularUMeshBuilder::new()
+            .add_geometric_axis(0.0, 7.0, 3, 2.0)
+            .build();
+        let x: Vec<f64> = mesh.coords().column(0).to_vec();
+        assert_eq!(x, vec![0.0, 1.0, 3.0, 7.0]);
+
+        let mesh = RegularUMeshBuilder::new()
+            .add_biased_axis(0.0, 5.0, 2, 4.0)
+            .add_uniform_axis(-1.0, 1.0, 4)
+            .build();
+        assert_eq!(mesh.coords().column(0).to_vec()[..3], [0.0, 1.0, 5.0]);
+        assert_eq!(
+            mesh.coords().column(1).to_vec()[..4],
+            [-1.0, -1.0, -1.0, -0.5]
+        );
+    }
+
+    #[test]
+    fn test_simplex_cells_and_boundary_groups() {
+        use crate::element_traits::ElementGeo;
+
+        let mesh = RegularUMeshBuilder::new()
+            .add_uniform_axis(0.0, 1.0, 2)
+            .add_uniform_axis(0.0, 1.0, 2)
+            .cell_type(ElementType::TRI3)
+            .boundary_groups(true)
+            .build();
+        assert_eq!(mesh.block(ElementType::TRI3).unwrap().len(), 8);
+        assert_eq!(mesh.group_as_element_ids("xmin").len(), 2);
+        assert_eq!(mesh.group_as_element_ids("ymax").len(), 2);
+
+        let mesh = RegularUMeshBuilder::new()
+            .add_uniform_axis(0.0, 1.0, 2)
+            .add_uniform_axis(0.0, 1.0, 2)
+            .add_uniform_axis(0.0, 1.0, 2)
+            .cell_type(ElementType::TET4)
+            .boundary_groups(true)
+            .build();
+        assert_eq!(mesh.block(ElementType::TET4).unwrap().len(), 48);
+        for tet in mesh.elements_of_dim(Dimension::D3) {
+            let p0 = tet.coord3(0);
+            let (a, b, c) = (tet.coord3(1) - p0, tet.coord3(2) - p0, tet.coord3(3) - p0);
+            assert!(a.dot(&b.cross(&c)) > 0.0);
+        }
+        // 6 faces of 4 quads, each split into 2 triangles
+        assert_eq!(mesh.block(ElementType::TRI3).unwrap().len(), 48);
+        for name in ["xmin", "xmax", "ymin", "ymax", "zmin", "zmax"] {
+            assert_eq!(mesh.group_as_element_ids(name).len(), 8);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unsupported_cell_type() {
+        RegularUMeshBuilder::new()
+            .add_axis(vec![0.0, 1.0])
+            .add_axis(vec![0.0, 1.0])
+            .cell_type(ElementType::TET4)
+            .build();
+    }
 }
@@ -0,0 +1,285 @@
+//! Transfer of fields between the non-matching meshes of an interface.
+//!
+//! Coupled solvers mesh both sides of a shared boundary independently, so that the nodes of the
+//! two surface meshes do not match. As in mortar methods, the cells of both meshes are split into
+//! simplices and each simplex of one side is projected on the plane, or the line, of the
+//! simplices of the other side it may touch. The measure of the intersection of the projection
+//! with the simplex weighs the transfer of values between the two cells.
+
+use std::collections::BTreeMap;
+
+use nalgebra as na;
+use ndarray::{self as nd, Axis};
+use rustc_hash::FxHashMap;
+
+use crate::element_traits::ElementGeo;
+use crate::mesh::{
+    Dimension, ElementId, ElementLike, ElementType, FieldBase, FieldOwnedD, UMeshView,
+};
+use crate::tools::broad_phase::broad_phase;
+use crate::tools::overlap::{SimplexOverlap, triangle_overlap};
+use crate::trace;
+
+/// Smallest cosine of the angle between two simplices whose overlap is measured. Simplices
+/// making a larger angle are on both sides of an edge or a corner of the interface.
+const MIN_NORMAL_COSINE: f64 = 0.5;
+
+fn vector(p: &[f64; 3]) -> na::Vector3<f64> {
+    na::Vector3::from(*p)
+}
+
+/// Length of the projection of the segment `s` on the line of the segment `t`, inside `t`.
+fn projected_segment_overlap(s: &[[f64; 3]], t: &[[f64; 3]]) -> f64 {
+    let origin = vector(&t[0]);
+    let direction = vector(&t[1]) - origin;
+    let other = vector(&s[1]) - vector(&s[0]);
+    let (length, other_length) = (direction.norm(), other.norm());
+    if length == 0.0
+        || other_length == 0.0
+        || direction.dot(&other).abs() < MIN_NORMAL_COSINE * length * other_length
+    {
+        return 0.0;
+    }
+    let along = |p: &[f64; 3]| (vector(p) - origin).dot(&direction) / length;
+    let (a, b) = (along(&s[0]), along(&s[1]));
+    (a.max(b).min(length) - a.min(b).max(0.0)).max(0.0)
+}
+
+/// Area of the projection of the triangle `s` on the plane of the triangle `t`, inside `t`.
+fn projected_triangle_overlap(s: &[[f64; 3]], t: &[[f64; 3]]) -> f64 {
+    let origin = vector(&t[0]);
+    let normal = (vector(&t[1]) - origin).cross(&(vector(&t[2]) - origin));
+    let other = (vector(&s[1]) - vector(&s[0])).cross(&(vector(&s[2]) - vector(&s[0])));
+    let (norm, other_norm) = (normal.norm(), other.norm());
+    if norm == 0.0
+        || other_norm == 0.0
+        || normal.dot(&other).abs() < MIN_NORMAL_COSINE * norm * other_norm
+    {
+        return 0.0;
+    }
+    let u = (vector(&t[1]) - origin).normalize();
+    let w = (normal / norm).cross(&u);
+    let in_plane = |p: &[f64; 3]| {
+        let d = vector(p) - origin;
+        [d.dot(&u), d.dot(&w), 0.0]
+    };
+    let s: Vec<[f64; 3]> = s.iter().map(in_plane).collect();
+    let t: Vec<[f64; 3]> = t.iter().map(in_plane).collect();
+    triangle_overlap(&s, &t)
+}
+
+/// Computes the intersection weights between the cells of two interface meshes.
+///
+/// Cells are the elements of the topological dimension of the meshes: segments in 2D space, or
+/// surface cells in 3D space (and in 2D space, where they are coplanar). Each pair of cells of
+/// `src` and `dst` whose projections intersect is reported with the length or area of the
+/// intersection of the projection of the `src` cell on the `dst` cell, sorted by cell of `src`
+/// then of `dst`. Cells making an angle larger than 60 degrees, on both sides of an edge of the
+/// interface, are not paired.
+///
+/// Cells are only paired if their bounding boxes intersect: both meshes must discretize the
+/// same interface, without gap between them.
+///
+/// # Errors
+/// Returns an error if the meshes do not have the same topological and space dimensions, or if
+/// their cells are neither segments in 2D space nor surface cells.
+pub fn interface_weights(
+    src: UMeshView,
+    dst: UMeshView,
+) -> Result<Vec<(ElementId, ElementId, f64)>, String> {
+    let dim = match (src.topological_dimension(), dst.topological_dimension()) {
+        (None, _) | (_, None) => return Ok(Vec::new()),
+        (Some(ds), Some(dd)) if ds == dd => ds,
+        _ => return Err("Interface meshes must have the same topological dimension".to_owned()),
+    };
+    let space_dim = src.space_dimension();
+    if dst.space_dimension() != space_dim {
+        return Err("Interface meshes must have the same space dimension".to_owned());
+    }
+    let overlap: SimplexOverlap = match (dim, space_dim) {
+        (Dimension::D1, 2) => projected_segment_overlap,
+        (Dimension::D2, 2 | 3) => projected_triangle_overlap,
+        _ => {
+            return Err(format!(
+                "Transfer between {dim:?} cells in {space_dim}D space is not supported"
+            ));
+        }
+    };
+
+    trace::span!("interface_weights");
+    let pairs = broad_phase(src.view(), dst.view(), Some(dim), Some(dim));
+    let simplices_dst: FxHashMap<ElementId, Vec<Vec<[f64; 3]>>> = dst
+        .elements_of_dim(dim)
+        .map(|e| (e.id(), e.simplex_coords()))
+        .collect();
+    let mut candidates = pairs.iter().peekable();
+    let mut res = Vec::new();
+    for cell in src.elements_of_dim(dim) {
+        let simplices = cell.simplex_coords();
+        while let Some(&(_, id_dst)) = candidates.next_if(|(id_src, _)| *id_src == cell.id()) {
+            let measure: f64 = simplices
+                .iter()
+                .flat_map(|s| simplices_dst[&id_dst].iter().map(move |t| overlap(s, t)))
+                .sum();
+            if measure > 0.0 {
+                res.push((cell.id(), id_dst, measure));
+            }
+        }
+    }
+    trace::debug!(
+        candidates = pairs.len(),
+        pairs = res.len(),
+        "interface weights computed"
+    );
+    Ok(res)
+}
+
+/// Transfers a cell field of `src` to the cells of `dst`, two non-matching meshes of the same
+/// interface.
+///
+/// The value of each cell of `dst` is the average of the values of the `src` cells it
+/// intersects, weighted by the measures of the intersections computed by
+/// [`interface_weights`]. Constant fields are transferred exactly, and so is the integral of the
+/// field when both meshes cover the same planar interface. Cells of `dst` intersecting no cell of
+/// `src` holding the field are set to NaN.
+///
+/// The field has shape `[n_cells, ...]` for each element type of `src`, and the result has the
+/// same shape for each cell type of `dst`.
+///
+/// # Errors
+/// Returns an error if the field is not a cell field of `src`, and the errors of
+/// [`interface_weights`].
+pub fn transfer_interface<S>(
+    field: &FieldBase<S, nd::IxDyn>,
+    src: UMeshView,
+    dst: UMeshView,
+) -> Result<FieldOwnedD, String>
+where
+    S: nd::Data<Elem = f64>,
+{
+    for (et, values) in &field.0 {
+        let len = src
+            .block(*et)
+            .ok_or(format!(
+                "Field is defined on {et:?} cells, absent from the source mesh"
+            ))?
+            .len();
+        if values.shape().first() != Some(&len) {
+            return Err(format!(
+                "Field has shape {:?} on {et:?} cells, expected one value per cell",
+                values.shape()
+            ));
+        }
+    }
+    let weights = interface_weights(src.view(), dst.view())?;
+
+    let value_shape = field.0.values().next().map_or(&[][..], |v| &v.shape()[1..]);
+    let dim = dst.topological_dimension();
+    let mut values: BTreeMap<ElementType, nd::ArrayD<f64>> = BTreeMap::new();
+    let mut totals: BTreeMap<ElementType, Vec<f64>> = BTreeMap::new();
+    for (&et, block) in dst.blocks().filter(|(et, _)| Some(et.dimension()) == dim) {
+        let shape: Vec<usize> = [block.len()].iter().chain(value_shape).copied().collect();
+        values.insert(et, nd::ArrayD::zeros(shape));
+        totals.insert(et, vec![0.0; block.len()]);
+    }
+    for (id_src, id_dst, w) in weights {
+        let Some(src_values) = field.0.get(&id_src.element_type()) else {
+            continue;
+        };
+        let et = id_dst.element_type();
+        values
+            .get_mut(&et)
+            .unwrap()
+            .index_axis_mut(Axis(0), id_dst.index())
+            .scaled_add(w, &src_values.index_axis(Axis(0), id_src.index()));
+        totals.get_mut(&et).unwrap()[id_dst.index()] += w;
+    }
+    for (et, values) in values.iter_mut() {
+        for (mut value, &total) in values.outer_iter_mut().zip(&totals[et]) {
+            if total > 0.0 {
+                value.mapv_inplace(|x| x / total);
+            } else {
+                value.fill(f64::NAN);
+            }
+        }
+    }
+    Ok(FieldBase::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::UMesh;
+    use crate::mesh_examples as me;
+    use approx::assert_abs_diff_eq;
+
+    /// A line of `n` segments from `(0, 0)` to `(1, 0)`.
+    fn line(n: usize) -> UMesh {
+        let coords = nd::Array2::from_shape_fn((n + 1, 2), |(i, j)| match j {
+            0 => i as f64 / n as f64,
+            _ => 0.0,
+        });
+        let mut mesh = UMesh::new(coords.into_shared());
+        let conn = nd::Array2::from_shape_fn((n, 2), |(i, j)| i + j);
+        mesh.add_regular_block(ElementType::SEG2, conn.into_shared(), None);
+        mesh
+    }
+
+    /// The unit square of the `z = 0` plane, in 3D space, split into `n x n` quadrangles.
+    fn plane(n: usize) -> UMesh {
+        let flat = me::make_imesh_2d(n);
+        let mut coords = nd::Array2::zeros((flat.coords().nrows(), 3));
+        coords.slice_mut(nd::s![.., ..2]).assign(&flat.coords());
+        let mut mesh = UMesh::new(coords.into_shared());
+        let conn = flat.regular_connectivity(ElementType::QUAD4).unwrap();
+        mesh.add_regular_block(ElementType::QUAD4, conn.to_shared(), None);
+        mesh
+    }
+
+    fn cell_field(et: ElementType, values: &[f64]) -> FieldOwnedD {
+        FieldBase::new(BTreeMap::from([(et, nd::arr1(values).into_dyn())]))
+    }
+
+    #[test]
+    fn test_transfer_lines() {
+        let (src, dst) = (line(2), line(3));
+        let weights = interface_weights(src.view(), dst.view()).unwrap();
+        assert_eq!(weights.len(), 4);
+        assert_abs_diff_eq!(weights[1].2, 1.0 / 6.0, epsilon = 1e-12);
+
+        let field = cell_field(ElementType::SEG2, &[1.0, 3.0]);
+        let res = transfer_interface(&field, src.view(), dst.view()).unwrap();
+        let res = &res.0[&ElementType::SEG2];
+        assert_eq!(res.shape(), &[3]);
+        for (&value, expected) in res.iter().zip([1.0, 2.0, 3.0]) {
+            assert_abs_diff_eq!(value, expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_transfer_surfaces() {
+        let (src, dst) = (plane(2), plane(3));
+        let field = cell_field(ElementType::QUAD4, &[0.0, 1.0, 2.0, 3.0]);
+        let res = transfer_interface(&field, src.view(), dst.view()).unwrap();
+        let res = &res.0[&ElementType::QUAD4];
+        assert_abs_diff_eq!(res[[4]], 1.5, epsilon = 1e-12);
+        // Both meshes cover the same square, so the integral is preserved
+        assert_abs_diff_eq!(res.sum() / 9.0, 1.5, epsilon = 1e-12);
+
+        // Mirrored cells are reversed, and only the cells over the source get a value
+        let mut mirrored = plane(3);
+        mirrored
+            .coords
+            .column_mut(0)
+            .mapv_inplace(|x| 2.0 - 2.0 * x);
+        let res = transfer_interface(&field, src.view(), mirrored.view()).unwrap();
+        let res = &res.0[&ElementType::QUAD4];
+        assert!(res[[0]].is_nan());
+        // The third cell spans x in [0, 2/3]: three quarters over the source cell 0, one over 1
+        assert_abs_diff_eq!(res[[2]], 0.25, epsilon = 1e-12);
+
+        assert!(transfer_interface(&field, src.view(), line(2).view()).is_err());
+        let wrong = cell_field(ElementType::QUAD4, &[0.0, 1.0]);
+        assert!(transfer_interface(&wrong, src.view(), plane(3).view()).is_err());
+    }
+}
@@ -1,136 +1,960 @@
-use crate::element_traits::ElementGeo;
-use crate::mesh::{Element, ElementId, ElementLike, ElementType};
+use std::collections::{BTreeMap, BTreeSet};
 
-use arrayvec::ArrayVec;
-use rstar::primitives::{GeomWithData, Line};
+use crate::element_traits::is_in::in_polygon;
+use crate::element_traits::{
+    ElementGeo, Intersection, Intersections, PointId, intersect_seg_seg_with,
+};
+use crate::geometry::predicates::Tolerances;
+use crate::mesh::{
+    Dimension, Element, ElementId, ElementIds, ElementLike, ElementType, FieldLocation, Regularity,
+    UMesh, UMeshView,
+};
+use crate::tools::progress::Monitor;
+use crate::trace;
 
-/// A wrapper struct representing a geometric line segment with associated element ID data.
+use itertools::Itertools;
+use nalgebra::Point2;
+use ndarray as nd;
+use rstar::primitives::{GeomWithData, Line, Rectangle};
+use rstar::{AABB, RTree};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+struct UndirectedEdge(usize, usize);
+
+impl UndirectedEdge {
+    fn new(a: usize, b: usize) -> Self {
+        if a < b { Self(a, b) } else { Self(b, a) }
+    }
+}
+
+/// Nodes of a cut mesh: the nodes of the original mesh followed by the new ones.
 ///
-/// The segment is defined by a `Line<[f64; 2]>` geometry and an `ElementId` for identification.
-#[allow(unused)]
-struct Segment(GeomWithData<Line<[f64; 2]>, ElementId>);
+/// A new node closer than the tolerance distance to an existing one is merged with it.
+struct NodeRegistry {
+    coords: Vec<[f64; 2]>,
+    tree: RTree<GeomWithData<[f64; 2], usize>>,
+    num_original: usize,
+    tol: Tolerances,
+}
+
+impl NodeRegistry {
+    fn new(coords: nd::ArrayView2<f64>, tol: Tolerances) -> Self {
+        let coords: Vec<[f64; 2]> = coords.rows().into_iter().map(|p| [p[0], p[1]]).collect();
+        let points = coords
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| GeomWithData::new(p, i))
+            .collect();
+        Self {
+            tree: RTree::bulk_load(points),
+            num_original: coords.len(),
+            coords,
+            tol,
+        }
+    }
 
-impl Segment {
-    #[allow(unused)]
-    pub fn new(p1: [f64; 2], p2: [f64; 2], eid: ElementId) -> Self {
-        Self(GeomWithData::new(Line { from: p1, to: p2 }, eid))
+    fn node(&mut self, p: [f64; 2]) -> usize {
+        let existing = self
+            .tree
+            .locate_within_distance(p, self.tol.distance * self.tol.distance)
+            .map(|n| n.data)
+            .min();
+        if let Some(n) = existing {
+            return n;
+        }
+        let n = self.coords.len();
+        self.coords.push(p);
+        self.tree.insert(GeomWithData::new(p, n));
+        n
     }
 
-    #[allow(unused)]
-    fn from(el: &Element) -> Self {
-        let p1: [f64; 2] = *el.coord2_ref(0);
-        let p2: [f64; 2] = *el.coord2_ref(1);
-        Self::new(p1, p2, el.id())
+    fn point(&self, n: usize) -> Point2<f64> {
+        self.coords[n].into()
     }
 }
 
-#[allow(unused)]
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
-struct DirectedEdge(usize, usize);
+/// Parameter of the projection of `p` on the line `(a, b)`, `0` at `a` and `1` at `b`.
+fn param(a: Point2<f64>, b: Point2<f64>, p: Point2<f64>) -> f64 {
+    (p - a).dot(&(b - a)) / (b - a).norm_squared()
+}
 
-#[allow(unused)]
-#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
-struct UndirectedEdge(usize, usize);
+fn signed_area(polygon: &[[f64; 2]]) -> f64 {
+    polygon
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| a[0] * b[1] - a[1] * b[0])
+        .sum::<f64>()
+        / 2.0
+}
 
-impl UndirectedEdge {
-    fn new(a: usize, b: usize) -> Self {
-        if a < b { Self(a, b) } else { Self(b, a) }
+/// Intersections of the edges of a 2D mesh with the segments of a 1D tool mesh.
+///
+/// Intersections are computed once per edge, so that the cells sharing an edge get the same new
+/// nodes.
+struct EdgeCutter {
+    nodes: NodeRegistry,
+    /// Nodes inserted inside each edge, ordered from its lower node to its higher node.
+    edge_nodes: FxHashMap<UndirectedEdge, Vec<usize>>,
+    /// End points of the tool segments.
+    segments: Vec<[[f64; 2]; 2]>,
+    /// Nodes found on each tool segment, with their parameter along the segment.
+    segment_nodes: Vec<Vec<(f64, usize)>>,
+}
+
+impl EdgeCutter {
+    fn new(
+        mesh: &UMeshView,
+        tool_mesh: &UMeshView,
+        tol: Tolerances,
+        monitor: &Monitor,
+    ) -> Result<Self, String> {
+        if mesh.space_dimension() != 2 || tool_mesh.space_dimension() != 2 {
+            return Err("Only meshes in a 2D space can be cut".to_owned());
+        }
+        if let Some(et) = mesh.element_types().find(|et| {
+            et.dimension() == Dimension::D2
+                && !matches!(
+                    et,
+                    ElementType::TRI3 | ElementType::QUAD4 | ElementType::PGON
+                )
+        }) {
+            return Err(format!("Cutting {et:?} elements is not supported"));
+        }
+        if let Some(et) = tool_mesh
+            .element_types()
+            .find(|et| et.dimension() == Dimension::D1 && **et != ElementType::SEG2)
+        {
+            return Err(format!("Cutting with {et:?} elements is not supported"));
+        }
+        trace::span!("intersect_edges");
+
+        let mut nodes = NodeRegistry::new(mesh.coords(), tol);
+        let segments: Vec<[[f64; 2]; 2]> = tool_mesh
+            .elements_of_dim(Dimension::D1)
+            .map(|seg| [*seg.coord2_ref(0), *seg.coord2_ref(1)])
+            .collect();
+        let tree = RTree::bulk_load(
+            segments
+                .iter()
+                .enumerate()
+                .map(|(i, &[a, b])| GeomWithData::new(Line::new(a, b), i))
+                .collect(),
+        );
+        trace::debug!(segments = segments.len(), "tool segments tree built");
+        let mut edge_nodes: FxHashMap<UndirectedEdge, Vec<usize>> = FxHashMap::default();
+        let mut segment_nodes = vec![Vec::new(); segments.len()];
+        let num_cells = mesh.num_elements_of_dim(Dimension::D2);
+        for (i, cell) in mesh.elements_of_dim(Dimension::D2).enumerate() {
+            monitor.step(i, num_cells, "intersecting edges")?;
+            for (&a, &b) in cell.connectivity().iter().circular_tuple_windows() {
+                let edge = UndirectedEdge::new(a, b);
+                if edge_nodes.contains_key(&edge) {
+                    continue;
+                }
+                let (p1, p2) = (nodes.point(edge.0), nodes.point(edge.1));
+                let mut inserted: Vec<(f64, usize)> = Vec::new();
+                for seg in
+                    tree.locate_in_envelope_intersecting(&AABB::from_corners(p1.into(), p2.into()))
+                {
+                    let [p3, p4] = segments[seg.data];
+                    let mut node = |i: Intersection| match i {
+                        Intersection::Existing(PointId::P1) => edge.0,
+                        Intersection::Existing(PointId::P2) => edge.1,
+                        Intersection::Existing(PointId::P3) => nodes.node(p3),
+                        Intersection::Existing(PointId::P4) => nodes.node(p4),
+                        Intersection::New(p) => nodes.node(p),
+                    };
+                    let found: Vec<usize> =
+                        match intersect_seg_seg_with(p1, p2, p3.into(), p4.into(), &tol) {
+                            Intersections::None => vec![],
+                            Intersections::One(i) => vec![node(i)],
+                            Intersections::Two(is) => is.map(&mut node).to_vec(),
+                            Intersections::Segment(ps) => {
+                                ps.map(Intersection::Existing).map(&mut node).to_vec()
+                            }
+                        };
+                    for n in found {
+                        let q = nodes.point(n);
+                        segment_nodes[seg.data].push((param(p3.into(), p4.into(), q), n));
+                        if n != edge.0 && n != edge.1 {
+                            inserted.push((param(p1, p2, q), n));
+                        }
+                    }
+                }
+                inserted.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let inserted = inserted.into_iter().map(|(_, n)| n).dedup().collect();
+                edge_nodes.insert(edge, inserted);
+            }
+        }
+        trace::debug!(
+            edges = edge_nodes.len(),
+            nodes_created = nodes.coords.len() - nodes.num_original,
+            "edges intersected"
+        );
+        Ok(Self {
+            nodes,
+            edge_nodes,
+            segments,
+            segment_nodes,
+        })
+    }
+
+    /// Nodes of a cell with the nodes inserted in its edges.
+    fn ring(&self, cell: &Element) -> Vec<usize> {
+        cell.connectivity()
+            .iter()
+            .circular_tuple_windows()
+            .flat_map(|(&a, &b)| {
+                let inserted = &self.edge_nodes[&UndirectedEdge::new(a, b)];
+                let inserted: Vec<usize> = match a < b {
+                    true => inserted.clone(),
+                    false => inserted.iter().rev().copied().collect(),
+                };
+                std::iter::once(a).chain(inserted)
+            })
+            .collect()
+    }
+}
+
+/// Faces of the planar graph made of the boundary of a cell and of the cuts inside it.
+///
+/// Cuts with a free end do not split the cell and are ignored. Faces are oriented as the cell.
+fn split_cell(ring: &[usize], cuts: &[[usize; 2]], nodes: &NodeRegistry) -> Vec<Vec<usize>> {
+    let mut graph: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    let edges = ring
+        .iter()
+        .copied()
+        .circular_tuple_windows()
+        .chain(cuts.iter().map(|&[a, b]| (a, b)));
+    for (a, b) in edges.filter(|(a, b)| a != b) {
+        graph.entry(a).or_default().insert(b);
+        graph.entry(b).or_default().insert(a);
+    }
+    // Cuts with a free end are removed node after node
+    let mut free: Vec<usize> = graph
+        .iter()
+        .filter(|(_, ns)| ns.len() < 2)
+        .map(|(&n, _)| n)
+        .collect();
+    while let Some(n) = free.pop() {
+        for m in graph.remove(&n).unwrap_or_default() {
+            let ns = graph.get_mut(&m).unwrap();
+            ns.remove(&n);
+            if ns.len() < 2 {
+                free.push(m);
+            }
+        }
     }
+    let is_cut = cuts
+        .iter()
+        .any(|[a, b]| graph.get(a).is_some_and(|ns| ns.contains(b)));
+    if !is_cut {
+        return vec![ring.to_vec()];
+    }
+
+    // Neighbours of each node, counterclockwise
+    let around: BTreeMap<usize, Vec<usize>> = graph
+        .iter()
+        .map(|(&n, ns)| {
+            let p = nodes.point(n);
+            let angle = |m: &usize| {
+                let v = nodes.point(*m) - p;
+                v[1].atan2(v[0])
+            };
+            let ns = ns
+                .iter()
+                .copied()
+                .sorted_by(|a, b| angle(a).total_cmp(&angle(b)))
+                .collect();
+            (n, ns)
+        })
+        .collect();
+    let coords = |face: &[usize]| face.iter().map(|&n| nodes.coords[n]).collect::<Vec<_>>();
+    let reversed = signed_area(&coords(ring)) < 0.0;
+    // Each half edge is followed by the next one turning clockwise, which walks the bounded faces
+    // counterclockwise and the outer face clockwise.
+    let mut visited = FxHashSet::default();
+    let mut faces = Vec::new();
+    for (&u, ns) in &around {
+        for &v in ns {
+            let mut face = Vec::new();
+            let (mut a, mut b) = (u, v);
+            while visited.insert((a, b)) {
+                face.push(a);
+                let ns = &around[&b];
+                let i = ns.iter().position(|&n| n == a).unwrap();
+                (a, b) = (b, ns[(i + ns.len() - 1) % ns.len()]);
+            }
+            if !face.is_empty() && signed_area(&coords(&face)) > 0.0 {
+                if reversed {
+                    face.reverse();
+                }
+                faces.push(face);
+            }
+        }
+    }
+    faces
 }
 
-impl From<DirectedEdge> for UndirectedEdge {
-    fn from(e: DirectedEdge) -> Self {
-        Self::new(e.0, e.1)
-    }
-}
-
-// impl From<UndirectedEdge> for DirectedEdge {
-//     fn from(e: UndirectedEdge) -> Self {
-//         Self(e.0, e.1)
-//     }
-// }
-
-#[allow(unused)]
-fn point_on_segment(p: [f64; 2], a: [f64; 2], b: [f64; 2], eps: f64) -> bool {
-    let cross = ((b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])).abs();
-    if cross > eps {
-        return false;
-    }
-    let dot = (p[0] - a[0]) * (b[0] - a[0]) + (p[1] - a[1]) * (b[1] - a[1]);
-    if dot < -eps {
-        return false;
-    }
-    let len_sq = (b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2);
-    if dot > len_sq + eps {
-        return false;
-    }
-    true
-}
-
-/// In the general case, there could be multiple intersections beween 1d elements (ex SEG2 and
-/// SEG3).
-///
-/// The implementation should be completly symmetric for it to be correct.
-#[allow(unused)]
-fn intersect_1d_elems(
-    e1: &Element,
-    e2: &Element,
-    _next_node_index: usize,
-) -> (ArrayVec<usize, 4>, ArrayVec<usize, 4>, Option<[f64; 2]>) {
-    match (e1.element_type(), e2.element_type()) {
-        (ElementType::SEG2, ElementType::SEG2) => {
-            let _seg1_nodes = [e1.connectivity()[0], e1.connectivity()[1]];
-            let _seg2_nodes = [e2.connectivity()[0], e2.connectivity()[1]];
-            let _p1 = *e1.coord2_ref(0);
-            let _p2 = *e1.coord2_ref(1);
-            let _p3 = *e2.coord2_ref(0);
-            let _p4 = *e2.coord2_ref(1);
-            todo!()
-            // intersect_seg_seg(seg1_nodes, seg2_nodes, p1, p2, p3, p4, next_node_index)
-        }
-        _ => todo!(),
-    }
-}
-
-// Cette méthode permet de découper un maillage 2d potentiellement non conforme avec un maillage
-// de segments propres (sans noeuds non fusionnés).
-// pub fn cut_2d_mesh_with_1d_mesh(mesh: &UMesh, tool_mesh: UMesh) -> Result<UMesh, String> {
-//     // tool_mesh.merge_nodes();
-//     let tool_mesh = tool_mesh.prepend_coords(mesh.coords());
-//
-//     let (m1d, mgraph) = compute_neighbours(mesh, Some(D2), None);
-//
-//     // build R*-tree with 1d tool mesh
-//     let segs: Vec<_> = tool_mesh
-//         .elements()
-//         .map(|seg| Segment::from(&seg).0)
-//         .collect();
-//     let cutter_tree = RTree::bulk_load(segs);
-//
-//     // Maintenant je travaille uniquement en terme de connectivité, j'utilise la table de
-//     // coordonnées suivante pour les identifiants de noeuds:
-//     // Table de mesh + table de tool_mesh après merge + nouveaux noeuds différents
-//     // Je créé la liste des segments de tool mesh découpés (avec les noeuds d'intersection)
-//     let mut e2int: HashMap<ElementId, Vec<[usize; 2]>> = HashMap::new();
-//     for el in mesh.elements_of_dim(D2) {
-//         let segs_in_elem: Vec<_> = cutter_tree
-//             .locate_in_envelope_intersecting(&el.to_aabb2())
-//             .collect();
-//         for (_, _, &eid) in mgraph.edges(el.id()) {
-//             // TODO: edge could be SEG3!
-//             let edge = m1d.element(eid);
-//             let _intersections: &_ = e2int.entry(eid).or_insert_with(|| {
-//                 let intersections = Vec::new();
-//                 for seg in &segs_in_elem {
-//                     let seg_elem = tool_mesh.element(seg.data);
-//                     // Calcul des intersections avec edge
-//                     // Une intersection est soit un Point, soit un Segment
-//                     let _intersection_coords = intersect_1d_elems(&edge, &seg_elem, 4);
-//                     todo!()
-//                 }
-//                 intersections
-//             });
-//         }
-//     }
-//     todo!()
-// }
+/// Builds the cut mesh from its cells and their parents in the original mesh.
+///
+/// Fields located at the cells are carried over from the parents when all the parents of a block
+/// have them, and so are groups and node groups. Values at the element nodes or integration points
+/// do not fit the split cells and are dropped. New nodes not used by any cell are dropped.
+fn build_cut_mesh(
+    mesh: &UMeshView,
+    nodes: NodeRegistry,
+    cells: Vec<(ElementType, Vec<usize>, ElementId)>,
+) -> (UMesh, BTreeMap<ElementType, Vec<ElementId>>) {
+    let used: BTreeSet<usize> = cells
+        .iter()
+        .flat_map(|(_, conn, _)| conn.iter().copied())
+        .filter(|&n| n >= nodes.num_original)
+        .collect();
+    let new_id: FxHashMap<usize, usize> = used
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| (n, nodes.num_original + i))
+        .collect();
+    let coords: Vec<f64> = (0..nodes.num_original)
+        .chain(used.iter().copied())
+        .flat_map(|n| nodes.coords[n])
+        .collect();
+    let coords = nd::Array2::from_shape_vec((coords.len() / 2, 2), coords).unwrap();
+    let mut res = UMesh::new(coords.into_shared());
+
+    let mut blocks: BTreeMap<ElementType, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    let mut parents: BTreeMap<ElementType, Vec<ElementId>> = BTreeMap::new();
+    for (et, conn, parent) in cells {
+        let (data, offsets) = blocks.entry(et).or_default();
+        data.extend(conn.iter().map(|n| *new_id.get(n).unwrap_or(n)));
+        offsets.push(data.len());
+        parents.entry(et).or_default().push(parent);
+    }
+    for (et, (data, offsets)) in blocks {
+        match et.regularity() {
+            Regularity::Regular => {
+                let conn =
+                    nd::Array2::from_shape_vec((offsets.len(), data.len() / offsets.len()), data)
+                        .unwrap();
+                res.add_regular_block(et, conn.into_shared(), None);
+            }
+            Regularity::Poly => {
+                res.add_poly_block(et, nd::ArcArray1::from(data), nd::ArcArray1::from(offsets))
+            }
+        }
+    }
+
+    for (et, ps) in &parents {
+        let parent_types: BTreeSet<ElementType> = ps.iter().map(|p| p.element_type()).collect();
+        let parent_blocks: Vec<_> = parent_types
+            .iter()
+            .map(|pet| mesh.block(*pet).unwrap())
+            .collect();
+        let names = parent_blocks[0].fields.keys().filter(|name| {
+            parent_blocks.iter().all(|b| {
+                b.fields.contains_key(*name) && b.field_location(name) == FieldLocation::Cells
+            })
+        });
+        for name in names {
+            let rows: Vec<_> = ps
+                .iter()
+                .map(|p| {
+                    mesh.block(p.element_type()).unwrap().fields[name]
+                        .index_axis(nd::Axis(0), p.index())
+                })
+                .collect();
+            let field = nd::stack(nd::Axis(0), &rows).unwrap();
+            res.element_blocks
+                .get_mut(et)
+                .unwrap()
+                .fields
+                .insert(name.clone(), field.into_shared());
+        }
+    }
+    for name in mesh.group_names() {
+        let group = mesh.group_as_element_ids(&name);
+        let mut ids = ElementIds::new();
+        for (&et, ps) in &parents {
+            for (i, &p) in ps.iter().enumerate() {
+                if group.contains(p) {
+                    ids.add(et, i);
+                }
+            }
+        }
+        if !ids.is_empty() {
+            res.set_group(&name, &ids);
+        }
+    }
+    res.node_groups = mesh.node_groups.clone();
+    (res, parents)
+}
+
+/// Cuts the faces of a 2D mesh along the segments of a 1D tool mesh (`cut_faces`).
+///
+/// Cells crossed by the tool mesh are split along it, and the new nodes are inserted in the edges
+/// of the neighbouring cells to keep the mesh conform. Resulting cells with 3 or 4 nodes are TRI3
+/// and QUAD4, the others are PGON. Parts of the tool mesh which do not cross a cell from side to
+/// side (a free end inside a cell) do not split it.
+///
+/// Only TRI3, QUAD4 and PGON cells can be cut, by SEG2 elements, in a 2D space. The tool mesh
+/// must be conform: its segments may only meet at their ends. New nodes closer than the tolerance
+/// distance to an existing node are merged with it. The tolerance is a plain distance or
+/// [`Tolerances`]. Nodes of the original mesh keep their numbering, new nodes come after them.
+///
+/// Returns the cut mesh, holding the 2D cells only, and the parent of each of its cells in the
+/// original mesh. Float fields located at the cells, groups and node groups are carried over.
+pub fn cut_2d_mesh_with_1d_mesh(
+    mesh: UMeshView,
+    tool_mesh: UMeshView,
+    tol: impl Into<Tolerances>,
+) -> Result<(UMesh, BTreeMap<ElementType, Vec<ElementId>>), String> {
+    cut_2d_mesh_with_1d_mesh_monitored(mesh, tool_mesh, tol, Monitor::new())
+}
+
+/// [`cut_2d_mesh_with_1d_mesh`] reporting its progress to a [`Monitor`].
+///
+/// # Errors
+/// Also returns an error if the monitor is cancelled.
+pub fn cut_2d_mesh_with_1d_mesh_monitored(
+    mesh: UMeshView,
+    tool_mesh: UMeshView,
+    tol: impl Into<Tolerances>,
+    monitor: Monitor,
+) -> Result<(UMesh, BTreeMap<ElementType, Vec<ElementId>>), String> {
+    trace::span!("cut_2d_mesh_with_1d_mesh");
+    let tol = tol.into();
+    let mut cutter = EdgeCutter::new(&mesh, &tool_mesh, tol, &monitor)?;
+
+    let cells: Vec<_> = mesh.elements_of_dim(Dimension::D2).collect();
+    let rings: Vec<Vec<usize>> = cells.iter().map(|cell| cutter.ring(cell)).collect();
+    let cell_tree = RTree::bulk_load(
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| GeomWithData::new(Rectangle::from_aabb(cell.to_aabb2()), i))
+            .collect(),
+    );
+
+    // Tool segments are split in pieces between the nodes found on them, then each piece is
+    // assigned to the cell it goes through.
+    let mut cuts: Vec<Vec<[usize; 2]>> = vec![Vec::new(); cells.len()];
+    for (s, &[p3, p4]) in cutter.segments.iter().enumerate() {
+        monitor.step(s, cutter.segments.len(), "cutting segments")?;
+        let length = (Point2::from(p4) - Point2::from(p3)).norm();
+        let mut points: Vec<(f64, Option<usize>, [f64; 2])> =
+            vec![(0.0, None, p3), (1.0, None, p4)];
+        points.extend(
+            cutter.segment_nodes[s]
+                .iter()
+                .map(|&(t, n)| (t, Some(n), cutter.nodes.coords[n])),
+        );
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points.dedup_by(|next, prev| {
+            let same = (next.0 - prev.0) * length <= tol.distance_at(length)
+                || (next.1.is_some() && next.1 == prev.1);
+            if same && prev.1.is_none() {
+                *prev = *next;
+            }
+            same
+        });
+        for (u, v) in points.into_iter().tuple_windows() {
+            let mid = [(u.2[0] + v.2[0]) / 2.0, (u.2[1] + v.2[1]) / 2.0];
+            for c in cell_tree.locate_all_at_point(&mid) {
+                let ring = &rings[c.data];
+                let on_boundary = match (u.1, v.1) {
+                    (Some(a), Some(b)) => ring
+                        .iter()
+                        .circular_tuple_windows()
+                        .any(|(&x, &y)| (x, y) == (a, b) || (x, y) == (b, a)),
+                    _ => false,
+                };
+                if on_boundary {
+                    continue;
+                }
+                let corners: Vec<[f64; 2]> = cells[c.data].coords2().copied().collect();
+                if in_polygon(&mid, &corners) {
+                    let a = u.1.unwrap_or_else(|| cutter.nodes.node(u.2));
+                    let b = v.1.unwrap_or_else(|| cutter.nodes.node(v.2));
+                    cuts[c.data].push([a, b]);
+                }
+            }
+        }
+    }
+
+    trace::debug!(
+        cuts = cuts.iter().map(Vec::len).sum::<usize>(),
+        nodes = cutter.nodes.coords.len(),
+        "tool segments assigned to cells"
+    );
+    let mut res = Vec::new();
+    for (i, ((cell, ring), cuts)) in cells.iter().zip(rings).zip(cuts).enumerate() {
+        monitor.step(i, cells.len(), "splitting cells")?;
+        if cuts.is_empty() && ring.len() == cell.num_nodes() {
+            res.push((cell.element_type(), ring, cell.id()));
+            continue;
+        }
+        for face in split_cell(&ring, &cuts, &cutter.nodes) {
+            let et = match face.len() {
+                3 => ElementType::TRI3,
+                4 => ElementType::QUAD4,
+                _ => ElementType::PGON,
+            };
+            res.push((et, face, cell.id()));
+        }
+    }
+    trace::debug!(cells = cells.len(), cut_cells = res.len(), "cells split");
+    Ok(build_cut_mesh(&mesh, cutter.nodes, res))
+}
+
+/// Inserts the intersection nodes of a 1D tool mesh in the edges of a 2D mesh (`cut_edges`).
+///
+/// Cells are not split: a cell with new nodes on its edges becomes a PGON, the others are left
+/// unchanged, so the result has the same number of cells as the original mesh. Its edges are
+/// conform with the tool mesh, which allows to cut it afterwards or to couple it with another
+/// mesh cut the same way. Tool nodes inside the cells are not inserted.
+///
+/// Requirements, node numbering and returned values are the ones of
+/// [`cut_2d_mesh_with_1d_mesh`].
+pub fn cut_edges(
+    mesh: UMeshView,
+    tool_mesh: UMeshView,
+    tol: impl Into<Tolerances>,
+) -> Result<(UMesh, BTreeMap<ElementType, Vec<ElementId>>), String> {
+    cut_edges_monitored(mesh, tool_mesh, tol, Monitor::new())
+}
+
+/// [`cut_edges`] reporting its progress to a [`Monitor`].
+///
+/// # Errors
+/// Also returns an error if the monitor is cancelled.
+pub fn cut_edges_monitored(
+    mesh: UMeshView,
+    tool_mesh: UMeshView,
+    tol: impl Into<Tolerances>,
+    monitor: Monitor,
+) -> Result<(UMesh, BTreeMap<ElementType, Vec<ElementId>>), String> {
+    trace::span!("cut_edges");
+    let cutter = EdgeCutter::new(&mesh, &tool_mesh, tol.into(), &monitor)?;
+    let cells = mesh
+        .elements_of_dim(Dimension::D2)
+        .map(|cell| {
+            let ring = cutter.ring(&cell);
+            let et = match ring.len() == cell.num_nodes() {
+                true => cell.element_type(),
+                false => ElementType::PGON,
+            };
+            (et, ring, cell.id())
+        })
+        .collect();
+    Ok(build_cut_mesh(&mesh, cutter.nodes, cells))
+}
+
+/// Linear segments of the 1D elements of a mesh, SEG3 being split at their middle node.
+fn linear_segments(mesh: &UMeshView) -> Result<Vec<[usize; 2]>, String> {
+    if mesh.space_dimension() != 2 {
+        return Err("Only meshes in a 2D space can be cut".to_owned());
+    }
+    mesh.elements_of_dim(Dimension::D1)
+        .map(|seg| {
+            let c = seg.connectivity();
+            match seg.element_type() {
+                ElementType::SEG2 => Ok(vec![[c[0], c[1]]]),
+                ElementType::SEG3 => Ok(vec![[c[0], c[2]], [c[2], c[1]]]),
+                et => Err(format!("Cutting {et:?} elements is not supported")),
+            }
+        })
+        .flatten_ok()
+        .collect()
+}
+
+/// Intersections between the segments of two 1D meshes.
+///
+/// Nodes of the first mesh keep their numbering, nodes of the second mesh are merged with them
+/// when closer than the tolerance distance.
+struct SegmentCutter {
+    nodes: NodeRegistry,
+    /// Segments of the first mesh followed by the ones of the second mesh.
+    segments: Vec<[usize; 2]>,
+    /// Nodes found on each segment, with their parameter along the segment.
+    segment_nodes: Vec<Vec<(f64, usize)>>,
+}
+
+impl SegmentCutter {
+    fn new(
+        a: &UMeshView,
+        b: &UMeshView,
+        tol: Tolerances,
+        monitor: &Monitor,
+    ) -> Result<Self, String> {
+        trace::span!("intersect_segments");
+        let segments_a = linear_segments(a)?;
+        let segments_b = linear_segments(b)?;
+        let mut nodes = NodeRegistry::new(a.coords(), tol);
+        let b_nodes: Vec<usize> = b
+            .coords()
+            .rows()
+            .into_iter()
+            .map(|p| nodes.node([p[0], p[1]]))
+            .collect();
+        let num_a = segments_a.len();
+        let segments: Vec<[usize; 2]> = segments_a
+            .into_iter()
+            .chain(segments_b.into_iter().map(|s| s.map(|n| b_nodes[n])))
+            .collect();
+        let tree = RTree::bulk_load(
+            segments[..num_a]
+                .iter()
+                .enumerate()
+                .map(|(i, &[n1, n2])| {
+                    GeomWithData::new(Line::new(nodes.coords[n1], nodes.coords[n2]), i)
+                })
+                .collect(),
+        );
+        let mut segment_nodes = vec![Vec::new(); segments.len()];
+        for (j, &[n3, n4]) in segments.iter().enumerate().skip(num_a) {
+            monitor.step(j - num_a, segments.len() - num_a, "intersecting segments")?;
+            let (p3, p4) = (nodes.point(n3), nodes.point(n4));
+            for seg in
+                tree.locate_in_envelope_intersecting(&AABB::from_corners(p3.into(), p4.into()))
+            {
+                let [n1, n2] = segments[seg.data];
+                let (p1, p2) = (nodes.point(n1), nodes.point(n2));
+                let mut node = |i: Intersection| match i {
+                    Intersection::Existing(PointId::P1) => n1,
+                    Intersection::Existing(PointId::P2) => n2,
+                    Intersection::Existing(PointId::P3) => n3,
+                    Intersection::Existing(PointId::P4) => n4,
+                    Intersection::New(p) => nodes.node(p),
+                };
+                let found: Vec<usize> = match intersect_seg_seg_with(p1, p2, p3, p4, &tol) {
+                    Intersections::None => vec![],
+                    Intersections::One(i) => vec![node(i)],
+                    Intersections::Two(is) => is.map(&mut node).to_vec(),
+                    Intersections::Segment(ps) => {
+                        ps.map(Intersection::Existing).map(&mut node).to_vec()
+                    }
+                };
+                for n in found {
+                    let q = nodes.point(n);
+                    segment_nodes[seg.data].push((param(p1, p2, q), n));
+                    segment_nodes[j].push((param(p3, p4, q), n));
+                }
+            }
+        }
+        trace::debug!(
+            segments = segments.len(),
+            nodes_created = nodes.coords.len() - nodes.num_original,
+            "segments intersected"
+        );
+        Ok(Self {
+            nodes,
+            segments,
+            segment_nodes,
+        })
+    }
+
+    /// Segments split at the intersection nodes, overlapping pieces being kept once.
+    fn pieces(&self) -> Vec<[usize; 2]> {
+        let mut seen = FxHashSet::default();
+        let mut pieces = Vec::new();
+        for (&[n1, n2], found) in self.segments.iter().zip(&self.segment_nodes) {
+            let inner = found
+                .iter()
+                .sorted_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|&(_, n)| n);
+            let points = std::iter::once(n1)
+                .chain(inner)
+                .chain(std::iter::once(n2))
+                .dedup();
+            for (u, v) in points.tuple_windows() {
+                if u != v && seen.insert(UndirectedEdge::new(u, v)) {
+                    pieces.push([u, v]);
+                }
+            }
+        }
+        pieces
+    }
+}
+
+/// Intersection points of two 1D meshes (`cut`).
+///
+/// Returns a mesh of VERTEX elements, one per intersection point. Overlapping segments give the
+/// ends of their common part. SEG3 elements are taken as two segments through their middle node.
+/// Intersection points closer than the tolerance distance are merged.
+///
+/// Only SEG2 and SEG3 elements in a 2D space are supported.
+pub fn cut_1d_1d(a: UMeshView, b: UMeshView, tol: impl Into<Tolerances>) -> Result<UMesh, String> {
+    cut_1d_1d_monitored(a, b, tol, Monitor::new())
+}
+
+/// [`cut_1d_1d`] reporting its progress to a [`Monitor`].
+///
+/// # Errors
+/// Also returns an error if the monitor is cancelled.
+pub fn cut_1d_1d_monitored(
+    a: UMeshView,
+    b: UMeshView,
+    tol: impl Into<Tolerances>,
+    monitor: Monitor,
+) -> Result<UMesh, String> {
+    let cutter = SegmentCutter::new(&a, &b, tol.into(), &monitor)?;
+    let points: BTreeSet<usize> = cutter
+        .segment_nodes
+        .iter()
+        .flatten()
+        .map(|&(_, n)| n)
+        .collect();
+    let coords: Vec<f64> = points
+        .iter()
+        .flat_map(|&n| cutter.nodes.coords[n])
+        .collect();
+    let coords = nd::Array2::from_shape_vec((points.len(), 2), coords).unwrap();
+    let mut res = UMesh::new(coords.into_shared());
+    if !points.is_empty() {
+        let vertices = nd::Array2::from_shape_fn((points.len(), 1), |(i, _)| i);
+        res.add_regular_block(ElementType::VERTEX, vertices.into_shared(), None);
+    }
+    Ok(res)
+}
+
+/// Conformal union of two 1D meshes (`cut_add`).
+///
+/// Segments of both meshes are split at their intersection points, and parts shared by both
+/// meshes are kept once, so the result is a SEG2 mesh conform to `a` and to `b`. SEG3 elements
+/// are taken as two segments through their middle node.
+///
+/// Nodes of `a` keep their numbering. Nodes of `b` and intersection points closer than the
+/// tolerance distance to an existing node are merged with it, the other ones come after the nodes
+/// of `a`.
+///
+/// Only SEG2 and SEG3 elements in a 2D space are supported.
+pub fn cut_add_1d_1d(
+    a: UMeshView,
+    b: UMeshView,
+    tol: impl Into<Tolerances>,
+) -> Result<UMesh, String> {
+    cut_add_1d_1d_monitored(a, b, tol, Monitor::new())
+}
+
+/// [`cut_add_1d_1d`] reporting its progress to a [`Monitor`].
+///
+/// # Errors
+/// Also returns an error if the monitor is cancelled.
+pub fn cut_add_1d_1d_monitored(
+    a: UMeshView,
+    b: UMeshView,
+    tol: impl Into<Tolerances>,
+    monitor: Monitor,
+) -> Result<UMesh, String> {
+    let cutter = SegmentCutter::new(&a, &b, tol.into(), &monitor)?;
+    let pieces = cutter.pieces();
+    let num_original = cutter.nodes.num_original;
+    let used: BTreeSet<usize> = pieces
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|&n| n >= num_original)
+        .collect();
+    let new_id: FxHashMap<usize, usize> = used
+        .iter()
+        .enumerate()
+        .map(|(i, &n)| (n, num_original + i))
+        .collect();
+    let coords: Vec<f64> = (0..num_original)
+        .chain(used.iter().copied())
+        .flat_map(|n| cutter.nodes.coords[n])
+        .collect();
+    let coords = nd::Array2::from_shape_vec((coords.len() / 2, 2), coords).unwrap();
+    let mut res = UMesh::new(coords.into_shared());
+    if !pieces.is_empty() {
+        let conn: Vec<usize> = pieces
+            .iter()
+            .flatten()
+            .map(|n| *new_id.get(n).unwrap_or(n))
+            .collect();
+        let conn = nd::Array2::from_shape_vec((pieces.len(), 2), conn).unwrap();
+        res.add_regular_block(ElementType::SEG2, conn.into_shared(), None);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_examples as me;
+    use approx::assert_relative_eq;
+
+    fn tool(points: &[[f64; 2]]) -> UMesh {
+        let coords = nd::Array2::from_shape_vec(
+            (points.len(), 2),
+            points.iter().flatten().copied().collect(),
+        )
+        .unwrap();
+        let mut tool = UMesh::new(coords.into_shared());
+        for i in 1..points.len() {
+            tool.add_element(ElementType::SEG2, &[i - 1, i], None, None);
+        }
+        tool
+    }
+
+    fn areas(mesh: &UMesh) -> Vec<f64> {
+        mesh.elements()
+            .map(|e| signed_area(&e.coords2().copied().collect::<Vec<_>>()))
+            .collect()
+    }
+
+    #[test]
+    fn test_cut_across() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add(ElementType::QUAD4, 0);
+        mesh.set_group("corner", &ids);
+        let values = nd::arr1(&[0.0, 1.0, 2.0, 3.0]).into_dyn().into_shared();
+        mesh.element_blocks
+            .get_mut(&ElementType::QUAD4)
+            .unwrap()
+            .fields
+            .insert("value".to_owned(), values);
+
+        let tool = tool(&[[-0.5, 0.25], [1.5, 0.25]]);
+        let (res, parents) = cut_2d_mesh_with_1d_mesh(mesh.view(), tool.view(), 1e-9).unwrap();
+        assert_eq!(res.coords().nrows(), 12);
+        let quads = res.block(ElementType::QUAD4).unwrap();
+        assert_eq!(quads.len(), 6);
+        assert!(res.block(ElementType::PGON).is_none());
+        let q = |i| ElementId::new(ElementType::QUAD4, i);
+        assert_eq!(
+            parents[&ElementType::QUAD4],
+            vec![q(0), q(0), q(1), q(1), q(2), q(3)]
+        );
+        // Cells keep the counterclockwise orientation of the grid
+        let areas = areas(&res);
+        assert!(areas.iter().all(|&a| a > 0.0));
+        assert_relative_eq!(areas.iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+        assert_eq!(
+            res.group_as_element_ids("corner").get(&ElementType::QUAD4),
+            Some(&vec![0, 1])
+        );
+        let values: Vec<f64> = quads.fields["value"].iter().copied().collect();
+        assert_eq!(values, vec![0.0, 0.0, 1.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_cut_free_end() {
+        let mesh = me::make_imesh_2d(2);
+        // Crosses the first cell and stops inside the second one
+        let tool = tool(&[[-0.5, 0.25], [0.75, 0.25]]);
+        let (res, parents) = cut_2d_mesh_with_1d_mesh(mesh.view(), tool.view(), 1e-9).unwrap();
+        assert_eq!(res.coords().nrows(), 11);
+        assert_eq!(res.block(ElementType::QUAD4).unwrap().len(), 4);
+        let pgons = res.block(ElementType::PGON).unwrap();
+        assert_eq!(pgons.len(), 1);
+        assert_eq!(pgons.element_connectivity(0).len(), 5);
+        assert_eq!(
+            parents[&ElementType::PGON],
+            vec![ElementId::new(ElementType::QUAD4, 1)]
+        );
+        // Cells keep the counterclockwise orientation of the grid
+        let areas = areas(&res);
+        assert!(areas.iter().all(|&a| a > 0.0));
+        assert_relative_eq!(areas.iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cut_polyline() {
+        let mesh = me::make_imesh_2d(2);
+        // Goes through the middle node and along an edge
+        let tool = tool(&[[0.0, 0.0], [0.5, 0.5], [1.0, 0.5], [1.2, 0.7]]);
+        let (res, parents) = cut_2d_mesh_with_1d_mesh(mesh.view(), tool.view(), 1e-9).unwrap();
+        assert_eq!(res.coords().nrows(), 9);
+        assert_eq!(res.block(ElementType::TRI3).unwrap().len(), 2);
+        assert_eq!(res.block(ElementType::QUAD4).unwrap().len(), 3);
+        assert_eq!(
+            parents[&ElementType::TRI3][0],
+            ElementId::new(ElementType::QUAD4, 0)
+        );
+        // Cells keep the counterclockwise orientation of the grid
+        let areas = areas(&res);
+        assert!(areas.iter().all(|&a| a > 0.0));
+        assert_relative_eq!(areas.iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cut_edges() {
+        let mesh = me::make_imesh_2d(2);
+        let tool = tool(&[[-0.5, 0.25], [0.75, 0.25], [1.5, 0.25]]);
+        let (res, parents) = cut_edges(mesh.view(), tool.view(), 1e-9).unwrap();
+        assert_eq!(res.coords().nrows(), 12);
+        assert_eq!(res.num_elements(), 4);
+        let q = |i| ElementId::new(ElementType::QUAD4, i);
+        assert_eq!(parents[&ElementType::PGON], vec![q(0), q(1)]);
+        assert_eq!(parents[&ElementType::QUAD4], vec![q(2), q(3)]);
+        let pgons = res.block(ElementType::PGON).unwrap();
+        assert_eq!(pgons.element_connectivity(0), &[0, 1, 9, 4, 3, 10]);
+        assert_eq!(pgons.element_connectivity(1), &[1, 2, 11, 5, 4, 9]);
+        assert_relative_eq!(areas(&res).iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cut_1d_1d() {
+        let a = tool(&[[0.0, 0.0], [2.0, 0.0]]);
+        let coords = nd::arr2(&[[1.0, -1.0], [1.0, 1.0], [0.0, 1.0], [2.0, 1.0]]);
+        let mut b = UMesh::new(coords.into_shared());
+        b.add_element(ElementType::SEG2, &[0, 1], None, None);
+        b.add_element(ElementType::SEG3, &[2, 3, 0], None, None);
+
+        let points = cut_1d_1d(a.view(), b.view(), 1e-9).unwrap();
+        assert_eq!(points.block(ElementType::VERTEX).unwrap().len(), 3);
+        let mut xs: Vec<f64> = points.coords().column(0).to_vec();
+        xs.sort_by(f64::total_cmp);
+        assert_eq!(xs, vec![0.5, 1.0, 1.5]);
+        assert!(points.coords().column(1).iter().all(|&y| y.abs() < 1e-12));
+
+        let union = cut_add_1d_1d(a.view(), b.view(), 1e-9).unwrap();
+        assert_eq!(union.coords().nrows(), 9);
+        assert_eq!(union.block(ElementType::SEG2).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_cut_1d_1d_overlap() {
+        let a = tool(&[[0.0, 0.0], [2.0, 0.0]]);
+        let b = tool(&[[3.0, 0.0], [1.0, 0.0]]);
+        let points = cut_1d_1d(a.view(), b.view(), 1e-9).unwrap();
+        assert_eq!(points.coords().nrows(), 2);
+
+        let union = cut_add_1d_1d(a.view(), b.view(), 1e-9).unwrap();
+        assert_eq!(union.coords().nrows(), 4);
+        let segs = union.block(ElementType::SEG2).unwrap();
+        assert_eq!(segs.len(), 3);
+        let lengths: f64 = union.elements().map(|e| e.measure2()).sum();
+        assert_relative_eq!(lengths, 3.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cut_monitored() {
+        let mesh = me::make_imesh_2d(4);
+        let tool = tool(&[[-0.5, 0.3], [1.5, 0.3]]);
+        let stages = std::sync::Mutex::new(BTreeSet::new());
+        let sink = |_: f64, stage: &str| {
+            stages.lock().unwrap().insert(stage.to_owned());
+        };
+        let monitor = Monitor::new().progress(&sink);
+        let (res, _) =
+            cut_2d_mesh_with_1d_mesh_monitored(mesh.view(), tool.view(), 1e-9, monitor).unwrap();
+        assert_eq!(res.num_elements(), 20);
+        assert_eq!(
+            stages.into_inner().unwrap(),
+            BTreeSet::from(
+                ["cutting segments", "intersecting edges", "splitting cells"].map(String::from)
+            )
+        );
+
+        let token = crate::tools::CancellationToken::new();
+        token.cancel();
+        let monitor = Monitor::new().cancellation(&token);
+        let res = cut_add_1d_1d_monitored(tool.view(), tool.view(), 1e-9, monitor);
+        assert_eq!(res, Err("Operation cancelled".to_owned()));
+    }
+}
@@ -1,6 +1,7 @@
 //! Geometric measurements for mesh elements.
 //!
-//! Computes element measures (length, area, volume) and stores them as fields.
+//! Computes element measures (length, area, volume) and centroids, and stores them as fields.
+//! Blocks and elements are processed in parallel with the `rayon` feature.
 
 use crate::element_traits::ElementGeo;
 use crate::mesh::ElementType;
@@ -11,7 +12,7 @@ use crate::mesh::{Dimension, UMeshView};
 use ndarray as nd;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Computes the geometric measure of each element in the mesh.
 ///
@@ -48,6 +49,54 @@ pub fn measure(mesh: UMeshView, dim: Option<Dimension>) -> BTreeMap<ElementType,
     .collect()
 }
 
+/// Computes the centroid of each element in the mesh, as the mean of its nodes.
+///
+/// Returns a map of element types to arrays of shape `[n, space_dimension]`.
+pub fn centroids(
+    mesh: UMeshView,
+    dim: Option<Dimension>,
+) -> BTreeMap<ElementType, nd::Array2<f64>> {
+    let dim = dim.unwrap_or_else(|| mesh.topological_dimension().unwrap());
+    let space_dim = mesh.space_dimension();
+    mesh.par_blocks()
+        .filter(|(et, _)| et.dimension() == dim)
+        .map(|(&k, v)| {
+            let centroids: Vec<[f64; 3]> = v
+                .par_iter(mesh.coords.view())
+                .map(|e| e.node_mean())
+                .collect();
+            (
+                k,
+                nd::Array2::from_shape_fn((v.len(), space_dim), |(i, j)| centroids[i][j]),
+            )
+        })
+        .collect()
+}
+
+/// Computes the total measure of the elements of each group, per element type.
+///
+/// Returns, for each group, the total length, area or volume of its elements of each type, as
+/// computed by [`measure`]. Types of different dimensions are reported separately, as their
+/// measures are not comparable.
+pub fn measure_by_group(mesh: UMeshView) -> BTreeMap<String, BTreeMap<ElementType, f64>> {
+    let dims: BTreeSet<Dimension> = mesh.element_types().map(|et| et.dimension()).collect();
+    let measures: BTreeMap<ElementType, nd::Array1<f64>> = dims
+        .into_iter()
+        .flat_map(|dim| measure(mesh.view(), Some(dim)))
+        .collect();
+    mesh.group_names()
+        .into_iter()
+        .map(|name| {
+            let ids = mesh.group_as_element_ids(&name);
+            let totals = ids
+                .iter_blocks()
+                .map(|(et, indices)| (*et, indices.iter().map(|&i| measures[et][i]).sum()))
+                .collect();
+            (name, totals)
+        })
+        .collect()
+}
+
 /// Trait for computing and storing element measures as fields.
 pub trait Measurable {
     /// Computes element measures and returns them as a field.
@@ -97,6 +146,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_centroids() {
+        let mesh = me::make_imesh_2d(2);
+        let centroids = centroids(mesh.view(), None);
+        let quads = &centroids[&ElementType::QUAD4];
+        assert_eq!(quads.shape(), &[4, 2]);
+        assert_abs_diff_eq!(quads[[0, 0]], 0.25);
+        assert_abs_diff_eq!(quads[[3, 1]], 0.75);
+        let measures = measure(mesh.view(), None);
+        assert_abs_diff_eq!(measures[&ElementType::QUAD4].sum(), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_measure_by_group() {
+        let mut mesh = crate::tools::RegularUMeshBuilder::new()
+            .add_uniform_axis(0.0, 2.0, 2)
+            .add_uniform_axis(0.0, 1.0, 2)
+            .boundary_groups(true)
+            .build();
+        let mut left = crate::mesh::ElementIds::new();
+        left.add_block(ElementType::QUAD4, vec![0, 2]);
+        mesh.set_group("left", &left);
+
+        let totals = measure_by_group(mesh.view());
+        assert_eq!(totals.len(), 5);
+        assert_abs_diff_eq!(totals["left"][&ElementType::QUAD4], 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(totals["xmin"][&ElementType::SEG2], 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(totals["ymax"][&ElementType::SEG2], 2.0, epsilon = 1e-12);
+        assert!(!totals["ymax"].contains_key(&ElementType::QUAD4));
+    }
+
     #[test]
     fn test_measure_update() {
         let mut mesh = me::make_mesh_2d_quad();
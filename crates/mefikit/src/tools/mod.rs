@@ -1,17 +1,31 @@
 //! Mesh manipulation tools and algorithms.
 //!
 //! This module provides various utilities for mesh operations including:
+//! - Element bounding boxes and broad-phase search
 //! - Connected component analysis
 //! - Mesh cracking (splitting shared nodes/faces)
 //! - Mesh extrusion (raising dimension)
+//! - Deformed shape under a displacement field
 //! - Field expressions and evaluation
+//! - Field transfer between non-matching interface meshes
 //! - Structured grid generation
 //! - Mesh intersection operations
 //! - Geometric measurements
 //! - Neighbor computation
+//! - Skin extraction with the parent cells of boundary faces
+//! - Reassembly of the parts of a distributed mesh
+//! - Region growing
+//! - Field sampling at points, along probe lines and on regular grids
 //! - Element selection
-//! - Node snapping
+//! - Node snapping and projection
+//! - Detection of mirror symmetries
+//! - Orientation and quality checks of cells
+//! - Overlap detection between meshes
+//! - Progress reporting and cancellation of long algorithms
+//! - Invariant checks after mesh operations
 
+/// Element bounding boxes and broad-phase search of possibly intersecting elements.
+pub mod broad_phase;
 /// Connected component analysis for meshes.
 pub mod connected_components;
 /// Crack along shared faces/nodes to separate mesh regions.
@@ -40,6 +54,10 @@ pub mod connected_components;
 ///
 /// - pour tous les noeuds dupliqués je récupère les éléments de dimension inférieure
 pub mod crack;
+/// Deformed shape of a mesh under a displacement field.
+pub mod deform;
+/// Lookup of the elements sharing a face, edge or vertex from its nodes.
+pub mod entity_index;
 /// Mesh extrusion to build a higher-dimensional mesh.
 ///
 /// This module builds a mesh of one dimension higher than the input mesh by extruding it.
@@ -49,6 +67,9 @@ pub mod extrude;
 pub mod fieldexpr;
 /// Structured grid generation utilities.
 pub mod grid;
+/// Field transfer between the non-matching meshes of an interface, weighted by the measures of
+/// their intersections.
+pub mod interface;
 /// Module for intersecting meshes.
 ///
 /// In this context, intersections operations can be separated in the following cases:
@@ -69,7 +90,8 @@ pub mod grid;
 ///   - cut_xor: Only keeping the domain covered by one of the meshes and intersecting them.
 ///
 /// The input meshes do not need to be clean (i.e. they can have unmerged nodes). They need to be
-/// conformed (i.e. no overlapping elements).
+/// conformed (i.e. no overlapping elements), which can be checked with
+/// [`overlap::detect_overlaps`].
 /// In all cases, the operation gives a "conformized without merging nodes" mesh. The user can
 /// choose to merge nodes after the operation if needed.
 ///
@@ -81,16 +103,50 @@ pub mod intersect;
 pub mod measure;
 /// Neighbor computation for mesh elements.
 pub mod neighbours;
+/// Nearest node, k nearest nodes and radius queries over mesh nodes.
+pub mod node_locator;
+/// Detection of inverted and badly shaped cells, and repair of their orientation.
+pub mod orientation;
+/// Detection of overlapping cells between two meshes.
+pub mod overlap;
+/// Progress reporting and cancellation of long running algorithms.
+pub mod progress;
+/// Reassembly of the parts of a distributed mesh into one global mesh.
+pub mod reassemble;
+/// Region growing from seed elements over face-adjacent elements.
+pub mod region_grow;
+/// Point location, interpolation, probe lines and sampling of fields on regular grids.
+pub mod sample;
 /// Element and node selection utilities.
 pub mod selector;
-/// Node snapping to merge nearby nodes.
+/// Skin of a mesh with the parent cell of each boundary face.
+pub mod skin;
+/// Node snapping: merging of nearby nodes and projection onto a target geometry.
 pub mod snap;
+/// Detection of the mirror symmetries of a mesh, with the node correspondence of each one.
+pub mod symmetry;
+/// Conservation invariants checked after mesh operations, in tests and debug builds.
+pub mod verify;
 
+pub use broad_phase::*;
 pub use connected_components::*;
 pub use crack::*;
+pub use deform::*;
+pub use entity_index::*;
 pub use extrude::*;
 pub use grid::*;
+pub use interface::*;
 pub use measure::*;
 pub use neighbours::*;
+pub use node_locator::*;
+pub use orientation::*;
+pub use overlap::*;
+pub use progress::*;
+pub use reassemble::*;
+pub use region_grow::*;
+pub use sample::*;
 pub use selector::*;
+pub use skin::*;
 pub use snap::*;
+pub use symmetry::*;
+pub use verify::*;
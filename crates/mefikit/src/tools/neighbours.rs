@@ -12,9 +12,10 @@ use smallvec::{SmallVec, smallvec};
 use std::collections::{HashMap, HashSet};
 
 use crate::element_traits::{ElementTopo, SortedVecKey};
-#[cfg(feature = "rayon")]
 use crate::mesh::ElementType;
-use crate::mesh::{Dimension, ElementId, ElementLike, UMesh};
+use crate::mesh::{Dimension, ElementId, ElementLike, UMesh, UMeshView};
+use crate::tools::EntityIndex;
+use crate::trace;
 
 /// This method is used to compute a subentity mesh in parallel.
 ///
@@ -25,16 +26,18 @@ use crate::mesh::{Dimension, ElementId, ElementLike, UMesh};
 /// same nodes, regardless of their order.
 /// The output graph is a element to element graph (from input mesh), using subentities as edges (weight in
 /// petgraph lang)
+///
+/// The result is the one of [`compute_neighbours`], whatever the number of threads.
 #[cfg(feature = "rayon")]
 pub fn par_compute_neighbours(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> (
     UMesh,
     UnGraphMap<ElementId, ElementId>, // element to element with subelem as edges
 ) {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
     // let mut subentities_hash: HashMap<SortedVecKey, [ElementId; 2]> =
     //     HashMap::with_capacity(self.coords.shape()[0]); // FaceId, ElemId
     let mut elem_to_elem: UnGraphMap<ElementId, ElementId> =
@@ -43,46 +46,64 @@ pub fn par_compute_neighbours(
     // is FaceId
     let mut neighbors: UMesh = UMesh::new(mesh.coords.to_shared());
 
-    type SubentityMap =
-        HashMap<SortedVecKey, (SmallVec<[ElementId; 2]>, SmallVec<[usize; 4]>, ElementType)>;
-
-    mesh.par_elements_of_dim(src_dim)
-        .fold(HashMap::new, |mut subentities_hash: SubentityMap, elem| {
-            for (et, conn) in elem.subentities(Some(codim)) {
-                for co in conn.iter() {
-                    let key = SortedVecKey::new(co.into());
-                    match subentities_hash.get_mut(&key) {
-                        // The subentity already exists
-                        Some((ids, _, _)) => ids.push(elem.id()),
-                        None => {
-                            subentities_hash.insert(key, (smallvec![elem.id()], co.into(), et));
-                        }
-                    }
-                }
+    // The first element generating a subentity with its local index, the type and connectivity
+    // of the subentity as seen from this element, and the elements sharing it
+    type Subentity = (
+        (ElementId, usize),
+        ElementType,
+        SmallVec<[usize; 4]>,
+        SmallVec<[ElementId; 2]>,
+    );
+    type SubentityMap = FxHashMap<SortedVecKey, Subentity>;
+
+    let sub_to_elem = mesh
+        .par_elements_of_dim(src_dim)
+        .fold(SubentityMap::default, |mut sub_to_elem, elem| {
+            let subentities = elem.subentities(Some(codim));
+            let all_conns = subentities
+                .iter()
+                .flat_map(|(et, conn)| conn.iter().map(move |co| (*et, co)));
+            for (i, (et, co)) in all_conns.enumerate() {
+                sub_to_elem
+                    .entry(SortedVecKey::new(co.into()))
+                    .and_modify(|(_, _, _, ids)| ids.push(elem.id()))
+                    .or_insert(((elem.id(), i), et, co.into(), smallvec![elem.id()]));
             }
-            subentities_hash
+            sub_to_elem
         })
-        .reduce(HashMap::new, |mut a, b| {
-            for (k, (ids, conn, et)) in b {
-                match a.get_mut(&k) {
-                    // The subentity already exists
-                    Some((existing_ids, _, _)) => existing_ids.extend(ids),
+        .reduce(SubentityMap::default, |mut a, b| {
+            for (key, (origin, et, conn, ids)) in b {
+                match a.get_mut(&key) {
+                    Some(sub) => {
+                        if origin < sub.0 {
+                            (sub.0, sub.1, sub.2) = (origin, et, conn);
+                        }
+                        sub.3.extend(ids);
+                    }
                     None => {
-                        a.insert(k, (ids, conn, et));
+                        a.insert(key, (origin, et, conn, ids));
                     }
                 }
             }
             a
-        })
-        .into_iter()
-        .for_each(|(_key, (ids, conn, et))| {
-            neighbors.add_element(et, conn.as_slice(), None, None);
-            let subentity_id = neighbors.block(et).unwrap().len() - 1;
-            let new_id = ElementId::new(et, subentity_id);
-            ids.iter().tuple_combinations().for_each(|(eid_a, eid_b)| {
-                elem_to_elem.add_edge(*eid_a, *eid_b, new_id);
-            });
         });
+    // Subentities are numbered in the order of the elements generating them, and their elements
+    // are sorted, so the result does not depend on the number of threads.
+    let mut subentities: Vec<Subentity> = sub_to_elem.into_values().collect();
+    subentities.par_sort_unstable_by_key(|sub| sub.0);
+
+    for elem in mesh.elements_of_dim(src_dim) {
+        elem_to_elem.add_node(elem.id());
+    }
+    for (_, et, conn, mut ids) in subentities {
+        neighbors.add_element(et, conn.as_slice(), None, None);
+        let subentity_id = neighbors.block(et).unwrap().len() - 1;
+        let new_id = ElementId::new(et, subentity_id);
+        ids.sort_unstable();
+        ids.iter().tuple_combinations().for_each(|(eid_a, eid_b)| {
+            elem_to_elem.add_edge(*eid_a, *eid_b, new_id);
+        });
+    }
 
     (neighbors, elem_to_elem)
 }
@@ -97,14 +118,15 @@ pub fn par_compute_neighbours(
 /// The output graph is a element to element graph (from input mesh), using subentities as edges (weight in
 /// petgraph lang)
 pub fn compute_neighbours(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> (
     UMesh,
     UnGraphMap<ElementId, ElementId>, // element to element with subelem as edges
 ) {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
+    trace::span!("compute_neighbours");
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
     let mut subentities_hashmap: FxHashMap<SortedVecKey, (ElementId, SmallVec<[ElementId; 2]>)> =
         HashMap::default();
     let mut neighbors: UMesh = UMesh::new(mesh.coords.to_shared());
@@ -133,6 +155,11 @@ pub fn compute_neighbours(
             }
         }
     }
+    trace::debug!(
+        elements = mesh.num_elements_of_dim(src_dim),
+        subentities = subentities_hashmap.len(),
+        "subentities computed"
+    );
     // Node is ElemId, edge is FaceId
     let mut elem_to_elem: UnGraphMap<ElementId, ElementId> =
         UnGraphMap::with_capacity(mesh.num_elements(), mesh.coords().nrows());
@@ -156,38 +183,24 @@ pub fn compute_neighbours(
 /// panick.  For performance reason, two subentities are considered the same if they have the
 /// same nodes, regardless of their order.
 /// The output graph is a element to element graph (from input mesh), using subentities as edges (weight in
-/// petgraph lang)
+/// petgraph lang). Elements sharing several subentities are linked by the last of them in the
+/// order of [`EntityIndex::iter`].
 pub fn compute_neighbours_graph(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> UnGraphMap<ElementId, SortedVecKey> {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
-    let mut subentities_hashmap: FxHashMap<SortedVecKey, SmallVec<[ElementId; 2]>> =
-        HashMap::default();
-
-    for elem in mesh.elements_of_dim(src_dim) {
-        for (_, conn) in elem.subentities(Some(codim)) {
-            for co in conn.iter() {
-                let key = SortedVecKey::new(co.into());
-
-                if let Some(eids) = subentities_hashmap.get_mut(&key) {
-                    eids.push(elem.id());
-                } else {
-                    subentities_hashmap.insert(key, smallvec![elem.id()]);
-                }
-            }
-        }
-    }
+    let (src_dim, target_dim, _) = compute_src_target_codim(&mesh, src_dim, target_dim);
+    let index = EntityIndex::build(mesh.view(), Some(src_dim), Some(target_dim));
     // Node is ElemId, edge is SortedVecKey
     let mut elem_to_elem: UnGraphMap<ElementId, SortedVecKey> =
         UnGraphMap::with_capacity(mesh.num_elements(), mesh.coords().nrows());
     for elem in mesh.elements_of_dim(src_dim) {
         elem_to_elem.add_node(elem.id());
     }
-    for (fkey, eids) in subentities_hashmap {
-        eids.iter().tuple_combinations().for_each(|(eid_a, eid_b)| {
-            elem_to_elem.add_edge(*eid_a, *eid_b, fkey.clone());
+    for (fkey, sharing) in index.iter() {
+        sharing.iter().tuple_combinations().for_each(|(a, b)| {
+            elem_to_elem.add_edge(a.0, b.0, fkey.clone());
         });
     }
 
@@ -195,7 +208,7 @@ pub fn compute_neighbours_graph(
 }
 
 fn compute_src_target_codim(
-    mesh: &UMesh,
+    mesh: &UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> (Dimension, Dimension, Dimension) {
@@ -224,11 +237,11 @@ fn compute_src_target_codim(
 /// The output graph is a element to element graph (from input mesh), using subentities as edges (weight in
 /// petgraph lang)
 pub fn compute_descending(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> UMesh {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
     let mut subentities_hash: FxHashSet<SortedVecKey> = HashSet::default(); // Face
     let mut neighbors: UMesh = UMesh::new(mesh.coords.to_shared());
 
@@ -250,11 +263,11 @@ pub fn compute_descending(
 
 /// This method is used to compute the descending_mesh and the map sub_elem_id to elem ids.
 pub fn compute_sub_to_elem(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> (UMesh, FxHashMap<ElementId, Vec<ElementId>>) {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
     let n_new_elem_guess = (u8::from(src_dim) as usize) * mesh.num_elements_of_dim(src_dim);
     let mut hash_to_subid: FxHashMap<SortedVecKey, ElementId> =
         HashMap::with_capacity_and_hasher(n_new_elem_guess, FxBuildHasher);
@@ -285,11 +298,11 @@ pub fn compute_sub_to_elem(
 
 /// This method is used to compute the descending_mesh and the map sub_elem_id to elem ids.
 pub fn compute_hashsub_to_elem(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> (UMesh, FxHashMap<SortedVecKey, Vec<ElementId>>) {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
     let n_new_elem_guess = (u8::from(src_dim) as usize) * mesh.num_elements_of_dim(src_dim);
     let mut sub_to_elem: FxHashMap<SortedVecKey, Vec<ElementId>> =
         HashMap::with_capacity_and_hasher(n_new_elem_guess, FxBuildHasher); // Face
@@ -317,56 +330,258 @@ pub fn compute_hashsub_to_elem(
 
 /// This method is used to compute the boundaries of a mesh.
 pub fn compute_boundaries(
-    mesh: &UMesh,
+    mesh: UMeshView,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> UMesh {
     compute_submesh_with_n_neighbours(mesh, 1, src_dim, target_dim)
 }
 
-/// This method is used to compute the boundaries of a mesh.
+/// This method is used to compute the subentities shared by exactly `n_neighbours` elements.
+///
+/// Subentities are added in the order of the elements generating them.
 pub fn compute_submesh_with_n_neighbours(
-    mesh: &UMesh,
+    mesh: UMeshView,
     n_neighbours: usize,
     src_dim: Option<Dimension>,
     target_dim: Option<Dimension>,
 ) -> UMesh {
-    let (src_dim, _, codim) = compute_src_target_codim(mesh, src_dim, target_dim);
-    let mut sub_to_elem: FxHashMap<SortedVecKey, (ElementId, usize)> = FxHashMap::default(); // Face
-    let mut neighbours: UMesh = UMesh::new(mesh.coords.to_shared());
+    let (src_dim, _, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
+    // The first element generating a subentity, with its local index, and the number of elements
+    // sharing it
+    let mut sub_to_elem: FxHashMap<SortedVecKey, ((ElementId, usize), usize)> =
+        FxHashMap::default(); // Face
 
     for elem in mesh.elements_of_dim(src_dim) {
-        for (_, conn) in elem.subentities(Some(codim)) {
-            for co in conn.iter() {
-                let key = SortedVecKey::new(co.into());
-                if let Some((_, n_elems)) = sub_to_elem.get_mut(&key) {
-                    // The subentity is already in the mesh
-                    *n_elems += 1;
-                } else {
-                    // The subentity is new, I keep track of the element which generates it so I
-                    // can generate it back again.
-                    sub_to_elem.insert(key.clone(), (elem.id(), 1));
-                }
+        let subentities = elem.subentities(Some(codim));
+        let all_conns = subentities.iter().flat_map(|(_, conn)| conn.iter());
+        for (i, co) in all_conns.enumerate() {
+            let key = SortedVecKey::new(co.into());
+            if let Some((_, n_elems)) = sub_to_elem.get_mut(&key) {
+                // The subentity is already in the mesh
+                *n_elems += 1;
+            } else {
+                // The subentity is new, I keep track of the element which generates it so I
+                // can generate it back again.
+                sub_to_elem.insert(key, ((elem.id(), i), 1));
             }
         }
     }
-    let eid_subhash_with_n_elems = sub_to_elem.into_iter().filter_map(|(k, (eid, n))| {
-        if n == n_neighbours {
-            Some((eid, k))
-        } else {
-            None
+    let mut origins: Vec<(ElementId, usize)> = sub_to_elem
+        .into_values()
+        .filter(|&(_, n)| n == n_neighbours)
+        .map(|(origin, _)| origin)
+        .collect();
+    origins.sort_unstable();
+    submesh_of_origins(&mesh, codim, origins)
+}
+
+/// Builds the mesh of the subentities given by their generating element and local index.
+fn submesh_of_origins(
+    mesh: &UMeshView,
+    codim: Dimension,
+    origins: Vec<(ElementId, usize)>,
+) -> UMesh {
+    let mut neighbours: UMesh = UMesh::new(mesh.coords.to_shared());
+    for (eid, i) in origins {
+        let subentities = mesh.element(eid).subentities(Some(codim));
+        let (et, co) = subentities
+            .iter()
+            .flat_map(|(et, conn)| conn.iter().map(move |co| (*et, co)))
+            .nth(i)
+            .unwrap();
+        neighbours.add_element(et, co, None, None);
+    }
+    neighbours
+}
+
+/// This method is used to compute the boundaries of a mesh in parallel.
+#[cfg(feature = "rayon")]
+pub fn par_compute_boundaries(
+    mesh: UMeshView,
+    src_dim: Option<Dimension>,
+    target_dim: Option<Dimension>,
+) -> UMesh {
+    par_compute_submesh_with_n_neighbours(mesh, 1, src_dim, target_dim)
+}
+
+/// This method is used to compute the subentities shared by exactly `n_neighbours` elements, in
+/// parallel.
+///
+/// As in the serial version, subentities are added in the order of the elements generating them,
+/// so the result does not depend on the number of threads.
+#[cfg(feature = "rayon")]
+pub fn par_compute_submesh_with_n_neighbours(
+    mesh: UMeshView,
+    n_neighbours: usize,
+    src_dim: Option<Dimension>,
+    target_dim: Option<Dimension>,
+) -> UMesh {
+    let (src_dim, target_dim, codim) = compute_src_target_codim(&mesh, src_dim, target_dim);
+    let index = EntityIndex::build(mesh.view(), Some(src_dim), Some(target_dim));
+    // The first element generating each subentity, with its local index
+    let mut origins: Vec<(ElementId, usize)> = index
+        .iter()
+        .filter(|(_, sharing)| sharing.len() == n_neighbours)
+        .map(|(_, sharing)| sharing[0])
+        .collect();
+    origins.par_sort_unstable();
+    submesh_of_origins(&mesh, codim, origins)
+}
+
+/// A face-adjacent element, with the subentity shared with the queried element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neighbour {
+    /// The neighbouring element.
+    pub id: ElementId,
+    /// Type of the shared subentity.
+    pub shared_type: ElementType,
+    /// Connectivity of the shared subentity, as seen from the queried element.
+    pub shared: Vec<usize>,
+    /// Index of the shared subentity among the subentities of the queried element.
+    pub local_index: usize,
+    /// Index of the shared subentity among the subentities of the neighbour.
+    pub neighbour_local_index: usize,
+}
+
+/// Codimension 1 subentities of an element, with their local index.
+fn local_subentities<'a>(
+    elem: &impl ElementTopo<'a>,
+) -> impl Iterator<Item = (usize, ElementType, SmallVec<[usize; 4]>)> {
+    elem.subentities(Some(Dimension::D1))
+        .into_iter()
+        .flat_map(|(et, conn)| {
+            conn.iter()
+                .map(|co| (et, SmallVec::from(co)))
+                .collect::<Vec<_>>()
+        })
+        .enumerate()
+        .map(|(i, (et, co))| (i, et, co))
+}
+
+/// Returns the elements sharing a subentity of codimension 1 (a face in 3D, an edge in 2D) with
+/// the given element.
+///
+/// Only elements of the same dimension are considered. An element sharing several subentities
+/// appears once per shared subentity. Subentities are matched by their nodes regardless of
+/// their order.
+pub fn element_neighbours(mesh: UMeshView, id: ElementId) -> Vec<Neighbour> {
+    let elem = mesh.element(id);
+    let faces: FxHashMap<SortedVecKey, (usize, ElementType, SmallVec<[usize; 4]>)> =
+        local_subentities(&elem)
+            .map(|(i, et, co)| (SortedVecKey::new(co.clone()), (i, et, co)))
+            .collect();
+    let nodes: FxHashSet<usize> = elem.connectivity().iter().copied().collect();
+    let mut res = Vec::new();
+    for other in mesh.elements_of_dim(id.element_type().dimension()) {
+        if other.id() == id || !other.connectivity().iter().any(|n| nodes.contains(n)) {
+            continue;
         }
-    });
-    for (eid, subhash) in eid_subhash_with_n_elems {
-        for (et, conn) in mesh.element(eid).subentities(Some(codim)) {
-            for co in conn.iter() {
-                if SortedVecKey::new(co.into()) == subhash {
-                    neighbours.add_element(et, co, None, None);
-                }
+        for (j, _, co) in local_subentities(&other) {
+            if let Some((i, et, shared)) = faces.get(&SortedVecKey::new(co)) {
+                res.push(Neighbour {
+                    id: other.id(),
+                    shared_type: *et,
+                    shared: shared.to_vec(),
+                    local_index: *i,
+                    neighbour_local_index: j,
+                });
             }
         }
     }
-    neighbours
+    res.sort_by_key(|n| (n.local_index, n.id));
+    res
+}
+
+/// Face adjacency of the elements of a mesh, in compressed sparse row (CSR) format.
+///
+/// Rows are the elements of the mesh topological dimension, numbered in [`Self::elements`]
+/// order. The neighbours of row `r` are stored, sorted, in `offsets[r]..offsets[r + 1]`,
+/// together with the local index of the shared subentity in row `r` and in the neighbour, which
+/// is what flux assembly needs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NeighbourGraph {
+    /// The element of each row, sorted.
+    pub elements: Vec<ElementId>,
+    /// Start of the neighbours of each row, with a last entry equal to the number of entries.
+    pub offsets: Vec<usize>,
+    /// Row of each neighbour.
+    pub neighbours: Vec<usize>,
+    /// Local index of the shared subentity in the row element.
+    pub local_indices: Vec<usize>,
+    /// Local index of the shared subentity in the neighbour.
+    pub neighbour_local_indices: Vec<usize>,
+}
+
+impl NeighbourGraph {
+    /// Returns the number of rows (elements).
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the graph has no row.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the row of an element, if it is in the graph.
+    pub fn row(&self, id: ElementId) -> Option<usize> {
+        self.elements.binary_search(&id).ok()
+    }
+
+    /// Returns the rows of the neighbours of a row.
+    pub fn neighbours(&self, row: usize) -> &[usize] {
+        &self.neighbours[self.offsets[row]..self.offsets[row + 1]]
+    }
+
+    /// Returns the local indices, in the row element, of the subentities shared with its
+    /// neighbours.
+    pub fn local_indices(&self, row: usize) -> &[usize] {
+        &self.local_indices[self.offsets[row]..self.offsets[row + 1]]
+    }
+}
+
+/// Computes the face adjacency of all the elements of the mesh topological dimension.
+///
+/// This is the bulk version of [`element_neighbours`]. Subentities shared by more than two
+/// elements (non manifold meshes) connect all of them pairwise.
+pub fn all_neighbours(mesh: UMeshView) -> NeighbourGraph {
+    let Some(dim) = mesh.topological_dimension() else {
+        return NeighbourGraph::default();
+    };
+    let elements: Vec<ElementId> = mesh.elements_of_dim(dim).map(|e| e.id()).sorted().collect();
+    let mut faces: FxHashMap<SortedVecKey, SmallVec<[(usize, usize); 2]>> = FxHashMap::default();
+    for elem in mesh.elements_of_dim(dim) {
+        let row = elements.binary_search(&elem.id()).unwrap();
+        for (i, _, co) in local_subentities(&elem) {
+            faces
+                .entry(SortedVecKey::new(co))
+                .or_default()
+                .push((row, i));
+        }
+    }
+    let mut entries: Vec<(usize, usize, usize, usize)> = Vec::new();
+    for sharing in faces.values() {
+        for (&(a, i), &(b, j)) in sharing.iter().tuple_combinations() {
+            entries.push((a, b, i, j));
+            entries.push((b, a, j, i));
+        }
+    }
+    entries.sort_unstable();
+    let mut offsets = vec![0; elements.len() + 1];
+    for &(row, ..) in &entries {
+        offsets[row + 1] += 1;
+    }
+    for r in 0..elements.len() {
+        offsets[r + 1] += offsets[r];
+    }
+    NeighbourGraph {
+        elements,
+        offsets,
+        neighbours: entries.iter().map(|e| e.1).collect(),
+        local_indices: entries.iter().map(|e| e.2).collect(),
+        neighbour_local_indices: entries.iter().map(|e| e.3).collect(),
+    }
 }
 
 /// Trait for computing subentity meshes and boundaries.
@@ -398,7 +613,7 @@ pub trait Descendable {
 impl Descendable for UMesh {
     type Output = UMesh;
     fn descend(&self, src_dim: Option<Dimension>, target_dim: Option<Dimension>) -> Self::Output {
-        compute_descending(self, src_dim, target_dim)
+        compute_descending(self.view(), src_dim, target_dim)
     }
     /// Compute the descending mesh of the source dimension to the target dimension. This mesh is
     /// added to the source mesh and if there are elements of the target dimension they are moved
@@ -408,7 +623,7 @@ impl Descendable for UMesh {
         src_dim: Option<Dimension>,
         target_dim: Option<Dimension>,
     ) -> Option<Self::Output> {
-        let descended_mesh = compute_descending(self, src_dim, target_dim);
+        let descended_mesh = compute_descending(self.view(), src_dim, target_dim);
         self.update(descended_mesh)
     }
     fn boundaries(
@@ -416,7 +631,7 @@ impl Descendable for UMesh {
         src_dim: Option<Dimension>,
         target_dim: Option<Dimension>,
     ) -> Self::Output {
-        compute_boundaries(self, src_dim, target_dim)
+        compute_boundaries(self.view(), src_dim, target_dim)
     }
     /// Compute the boundaries mesh of the source dimension to the target dimension. This mesh is
     /// added to the source mesh and if there are elements of the target dimension they are moved
@@ -426,7 +641,7 @@ impl Descendable for UMesh {
         src_dim: Option<Dimension>,
         target_dim: Option<Dimension>,
     ) -> Option<Self::Output> {
-        let new_mesh = compute_boundaries(self, src_dim, target_dim);
+        let new_mesh = compute_boundaries(self.view(), src_dim, target_dim);
         self.update(new_mesh)
     }
 }
@@ -453,7 +668,7 @@ mod tests {
     #[test]
     fn test_compute_neighbours() {
         let mesh = make_simple_quad_mesh();
-        let (submesh, graph) = compute_neighbours(&mesh, None, None);
+        let (submesh, graph) = compute_neighbours(mesh.view(), None, None);
         // The submesh should contain the edges (codim=1)
         assert!(submesh.num_elements() > 0);
         // Graph should have nodes (original elements)
@@ -463,19 +678,71 @@ mod tests {
     #[test]
     fn test_compute_descending() {
         let mesh = make_simple_quad_mesh();
-        let descended = compute_descending(&mesh, None, None);
+        let descended = compute_descending(mesh.view(), None, None);
         // Descending a 2D mesh by 1 gives edges
         assert!(descended.num_elements() > 0);
     }
 
+    #[test]
+    fn test_compute_neighbours_graph_weights() {
+        // Quads sharing an edge share two vertices, the graph keeps the largest one
+        let mesh = crate::mesh_examples::make_imesh_2d(2);
+        let graph = compute_neighbours_graph(mesh.view(), None, Some(Dimension::D0));
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        assert_eq!(graph.edge_count(), 6);
+        assert_eq!(
+            graph.edge_weight(quad(0), quad(1)),
+            Some(&SortedVecKey::new(smallvec![4]))
+        );
+        assert_eq!(
+            graph.edge_weight(quad(0), quad(3)),
+            Some(&SortedVecKey::new(smallvec![4]))
+        );
+    }
+
     #[test]
     fn test_compute_boundaries() {
         let mesh = make_simple_quad_mesh();
-        let boundaries = compute_boundaries(&mesh, None, None);
+        let boundaries = compute_boundaries(mesh.view(), None, None);
         // Boundaries should be edges on the boundary
         assert!(boundaries.num_elements() > 0);
     }
 
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_compute_boundaries() {
+        let mesh = crate::mesh_examples::make_imesh_3d(3);
+        let boundaries = par_compute_boundaries(mesh.view(), None, None);
+        let serial = compute_boundaries(mesh.view(), None, None);
+        assert_eq!(boundaries.num_elements(), 54);
+        assert_eq!(serial.num_elements(), 54);
+        assert_eq!(boundaries, serial);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_compute_neighbours_deterministic() {
+        let mesh = crate::mesh_examples::make_imesh_3d(4);
+        let (serial, serial_graph) = compute_neighbours(mesh.view(), None, None);
+        let edges = |graph: &UnGraphMap<ElementId, ElementId>| {
+            graph
+                .all_edges()
+                .map(|(a, b, &f)| (a.min(b), a.max(b), f))
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+        for threads in [1, 4] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let (par, par_graph) = pool.install(|| par_compute_neighbours(mesh.view(), None, None));
+            assert_eq!(par, serial);
+            assert_eq!(par_graph.node_count(), serial_graph.node_count());
+            assert_eq!(edges(&par_graph), edges(&serial_graph));
+        }
+    }
+
     #[test]
     fn test_descend_trait() {
         let mesh = make_simple_quad_mesh();
@@ -505,4 +772,58 @@ mod tests {
         // boundaries_update returns None when the mesh is new (not replaced)
         // Just verify it doesn't panic
     }
+
+    #[test]
+    fn test_compute_edges_3d() {
+        let mesh = crate::mesh_examples::make_imesh_3d(2);
+        let (edges, parents) =
+            compute_sub_to_elem(mesh.view(), Some(Dimension::D3), Some(Dimension::D1));
+        assert_eq!(edges.block(ElementType::SEG2).unwrap().len(), 54);
+        let inner_edges = parents.values().filter(|p| p.len() == 4).count();
+        assert_eq!(inner_edges, 6);
+        let descended = mesh.descend(Some(Dimension::D3), Some(Dimension::D1));
+        assert_eq!(descended.num_elements(), 54);
+    }
+
+    #[test]
+    fn test_element_neighbours() {
+        let mesh = crate::mesh_examples::make_imesh_2d(3);
+        let center = ElementId::new(ElementType::QUAD4, 4);
+        let neighbours = element_neighbours(mesh.view(), center);
+        assert_eq!(neighbours.len(), 4);
+        for n in &neighbours {
+            let shared = mesh.element(center).subentities(None)[0].1[n.local_index].to_vec();
+            assert_eq!(shared, n.shared);
+            let theirs =
+                mesh.element(n.id).subentities(None)[0].1[n.neighbour_local_index].to_vec();
+            assert_eq!(
+                SortedVecKey::new(shared.into()),
+                SortedVecKey::new(theirs.into())
+            );
+        }
+        let corner = ElementId::new(ElementType::QUAD4, 0);
+        assert_eq!(element_neighbours(mesh.view(), corner).len(), 2);
+    }
+
+    #[test]
+    fn test_all_neighbours() {
+        let mesh = crate::mesh_examples::make_imesh_2d(3);
+        let graph = all_neighbours(mesh.view());
+        assert_eq!(graph.len(), 9);
+        assert_eq!(graph.offsets.last(), Some(&24));
+        let center = graph.row(ElementId::new(ElementType::QUAD4, 4)).unwrap();
+        assert_eq!(graph.neighbours(center), &[1, 3, 5, 7]);
+        let expected: Vec<usize> = element_neighbours(mesh.view(), graph.elements[center])
+            .iter()
+            .map(|n| n.local_index)
+            .sorted()
+            .collect();
+        let local: Vec<usize> = graph
+            .local_indices(center)
+            .iter()
+            .copied()
+            .sorted()
+            .collect();
+        assert_eq!(local, expected);
+    }
 }
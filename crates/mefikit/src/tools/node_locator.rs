@@ -0,0 +1,117 @@
+use ndarray as nd;
+use rstar::RTree;
+use rstar::primitives::GeomWithData;
+
+use crate::mesh::UMeshView;
+
+type NodePoint = GeomWithData<[f64; 3], usize>;
+
+/// Pads coordinates of 1D and 2D points with zeros.
+fn point3(coords: &[f64]) -> [f64; 3] {
+    assert!(
+        coords.len() <= 3,
+        "Points should have at most 3 coordinates."
+    );
+    let mut p = [0.0; 3];
+    p[..coords.len()].copy_from_slice(coords);
+    p
+}
+
+/// A spatial tree over mesh nodes, answering nearest node, k nearest nodes and radius queries.
+///
+/// Nodes are located by their index in the coordinates array. Points of 1D and 2D meshes are
+/// handled as 3D points with zero padded coordinates, so query points have the space dimension
+/// of the mesh.
+pub struct NodeLocator {
+    tree: RTree<NodePoint>,
+}
+
+impl NodeLocator {
+    /// Builds a locator over all the rows of `coords`.
+    pub fn new(coords: nd::ArrayView2<f64>) -> Self {
+        Self::from_nodes(coords, 0..coords.nrows())
+    }
+
+    /// Builds a locator over the given rows of `coords` only.
+    pub fn from_nodes(coords: nd::ArrayView2<f64>, nodes: impl IntoIterator<Item = usize>) -> Self {
+        let points = nodes
+            .into_iter()
+            .map(|n| GeomWithData::new(point3(coords.row(n).as_slice().unwrap()), n))
+            .collect();
+        Self {
+            tree: RTree::bulk_load(points),
+        }
+    }
+
+    /// Builds a locator over the nodes used by the elements of a mesh.
+    pub fn from_used_nodes(mesh: &UMeshView) -> Self {
+        Self::from_nodes(mesh.coords(), mesh.used_nodes())
+    }
+
+    /// Returns the number of located nodes.
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns `true` if there is no node to locate.
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+
+    /// Returns the node closest to `point`, `None` if the locator is empty.
+    pub fn nearest(&self, point: &[f64]) -> Option<usize> {
+        self.tree.nearest_neighbor(&point3(point)).map(|p| p.data)
+    }
+
+    /// Returns the node closest to `point` if it is within distance `radius`.
+    pub fn nearest_within(&self, point: &[f64], radius: f64) -> Option<usize> {
+        let point = point3(point);
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&point)
+            .next()
+            .filter(|(_, d2)| *d2 <= radius * radius)
+            .map(|(p, _)| p.data)
+    }
+
+    /// Returns the `k` nodes closest to `point`, from the closest to the farthest.
+    pub fn k_nearest(&self, point: &[f64], k: usize) -> Vec<usize> {
+        self.tree
+            .nearest_neighbor_iter(&point3(point))
+            .take(k)
+            .map(|p| p.data)
+            .collect()
+    }
+
+    /// Returns the nodes within distance `radius` of `point`, sorted by index.
+    pub fn within_radius(&self, point: &[f64], radius: f64) -> Vec<usize> {
+        let mut nodes: Vec<usize> = self
+            .tree
+            .locate_within_distance(point3(point), radius * radius)
+            .map(|p| p.data)
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_node_locator() {
+        let mesh = me::make_imesh_2d(2);
+        let locator = NodeLocator::new(mesh.coords());
+        assert_eq!(locator.len(), 9);
+        assert_eq!(locator.nearest(&[0.45, 0.55]), Some(4));
+        assert_eq!(locator.nearest_within(&[0.45, 0.55], 0.01), None);
+        assert_eq!(locator.nearest_within(&[0.45, 0.55], 0.1), Some(4));
+        assert_eq!(locator.k_nearest(&[0.0, 0.1], 2), vec![0, 3]);
+        assert_eq!(locator.within_radius(&[0.5, 0.5], 0.5), vec![1, 3, 4, 5, 7]);
+
+        let corners = NodeLocator::from_nodes(mesh.coords(), [0, 2, 6, 8]);
+        assert_eq!(corners.nearest(&[0.4, 0.6]), Some(6));
+        assert!(NodeLocator::from_nodes(mesh.coords(), []).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+//! Detection of inverted and badly shaped cells, and repair of their orientation.
+//!
+//! A cell is inverted when its nodes are numbered in the reverse order of its reference element,
+//! which happens with some mesh generators or after a mirror transformation, and makes its
+//! signed measure negative. It is tangled when only a part of it is inverted, which no
+//! renumbering can repair.
+
+use std::collections::BTreeMap;
+
+use ndarray as nd;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::element_traits::ElementGeo;
+use crate::mesh::{ElementIds, ElementType, UMesh, UMeshView};
+use crate::topology::templates;
+use crate::trace;
+
+/// The orientation and quality of the cells of a mesh, computed by [`check_orientation`].
+///
+/// Only the cells whose dimension is the space dimension are checked, as the orientation of
+/// curves in 2D or of surfaces in 3D is not defined by their nodes alone.
+#[derive(Clone, Debug, Default)]
+pub struct OrientationCheck {
+    /// The signed measure of each checked cell, see [`ElementGeo::signed_measure`], NaN when the
+    /// element type has none.
+    pub signed_measures: BTreeMap<ElementType, nd::Array1<f64>>,
+    /// The jacobian ratio of each checked cell: the smallest of its
+    /// [vertex jacobians](ElementGeo::vertex_jacobians) divided by the largest absolute one. It is
+    /// 1 for an affine cell and negative for an inverted or tangled cell, NaN for poly cells.
+    pub quality: BTreeMap<ElementType, nd::Array1<f64>>,
+    /// The cells with a negative or null signed measure or vertex jacobian.
+    pub inverted: ElementIds,
+    /// The cells which are not inverted but whose quality is below the requested minimum.
+    pub poor: ElementIds,
+}
+
+/// Checks the orientation of the cells of a mesh and flags those of quality below
+/// `min_quality`.
+///
+/// Blocks and elements are processed in parallel with the `rayon` feature. Inverted cells can be
+/// repaired with [`fix_orientation`].
+pub fn check_orientation(mesh: UMeshView, min_quality: f64) -> OrientationCheck {
+    trace::span!("check_orientation");
+    let space_dim = mesh.space_dimension();
+    let blocks: Vec<(ElementType, Vec<(f64, f64)>)> = mesh
+        .par_blocks()
+        .filter(|(et, _)| u8::from(et.dimension()) as usize == space_dim)
+        .map(|(&et, block)| {
+            let values: Vec<(f64, f64)> = block
+                .par_iter(mesh.coords.view())
+                .map(|e| {
+                    let measure = e.signed_measure().unwrap_or(f64::NAN);
+                    (
+                        measure,
+                        e.vertex_jacobians().map_or(f64::NAN, jacobian_ratio),
+                    )
+                })
+                .collect();
+            (et, values)
+        })
+        .collect();
+
+    let mut check = OrientationCheck::default();
+    for (et, values) in blocks {
+        for (i, &(measure, quality)) in values.iter().enumerate() {
+            // NaN values compare false, poly cells only depend on their measure
+            if measure <= 0.0 || quality <= 0.0 {
+                check.inverted.add(et, i);
+            } else if quality < min_quality {
+                check.poor.add(et, i);
+            }
+        }
+        let (measures, quality): (Vec<f64>, Vec<f64>) = values.into_iter().unzip();
+        check.signed_measures.insert(et, nd::Array1::from(measures));
+        check.quality.insert(et, nd::Array1::from(quality));
+    }
+    trace::debug!(
+        inverted = check.inverted.len(),
+        poor = check.poor.len(),
+        "orientation checked"
+    );
+    check
+}
+
+/// Smallest jacobian divided by the largest absolute one, 0 for a degenerated cell.
+fn jacobian_ratio(jacobians: Vec<f64>) -> f64 {
+    let min = jacobians.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = jacobians.iter().fold(0.0, |max: f64, j| max.max(j.abs()));
+    match max > 0.0 {
+        true => min / max,
+        false => 0.0,
+    }
+}
+
+/// Repairs the inverted cells of a mesh by renumbering their nodes, and returns them.
+///
+/// Only the cells inverted as a whole are repaired: regular cells whose vertex jacobians are all
+/// negative, polygons and polyhedra whose signed measure is negative. Cells are renumbered by
+/// [`templates::reverse`], which also reverses the faces of polyhedra. Tangled and degenerated
+/// cells are left unchanged and remain reported by [`check_orientation`].
+pub fn fix_orientation(mesh: &mut UMesh) -> ElementIds {
+    trace::span!("fix_orientation");
+    let inverted = check_orientation(mesh.view(), 0.0).inverted;
+    let mut fixed = ElementIds::new();
+    for id in inverted.iter() {
+        let element = mesh.element(id);
+        let repairable = match id.element_type() {
+            ElementType::PGON | ElementType::PHED => element.signed_measure().unwrap() < 0.0,
+            _ => element
+                .vertex_jacobians()
+                .is_some_and(|jacobians| jacobians.iter().all(|&j| j < 0.0)),
+        };
+        if repairable {
+            templates::reverse(id.element_type(), mesh.element_mut(id).connectivity);
+            fixed.add(id.element_type(), id.index());
+        }
+    }
+    trace::debug!(fixed = fixed.len(), "orientation fixed");
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementId;
+    use crate::mesh_examples as me;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_check_orientation() {
+        let mut mesh = me::make_imesh_2d(2);
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        // Inverts the first cell and squeezes the last one
+        mesh.element_mut(quad(0)).connectivity.reverse();
+        mesh.coords_mut().row_mut(8).assign(&nd::arr1(&[0.8, 0.8]));
+
+        let check = check_orientation(mesh.view(), 0.5);
+        assert_eq!(check.inverted.iter().collect::<Vec<_>>(), vec![quad(0)]);
+        assert_eq!(check.poor.iter().collect::<Vec<_>>(), vec![quad(3)]);
+        let measures = &check.signed_measures[&ElementType::QUAD4];
+        assert_abs_diff_eq!(measures[0], -0.25, epsilon = 1e-12);
+        assert_abs_diff_eq!(measures[1], 0.25, epsilon = 1e-12);
+        let quality = &check.quality[&ElementType::QUAD4];
+        assert_abs_diff_eq!(quality[0], -1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(quality[1], 1.0, epsilon = 1e-12);
+        assert!(quality[3] > 0.0 && quality[3] < 0.5);
+
+        assert_eq!(fix_orientation(&mut mesh).len(), 1);
+        let check = check_orientation(mesh.view(), 0.5);
+        assert!(check.inverted.is_empty());
+        assert_abs_diff_eq!(
+            check.signed_measures[&ElementType::QUAD4][0],
+            0.25,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_fix_orientation_3d() {
+        let mut mesh = me::make_imesh_3d(1);
+        let hex = ElementId::new(ElementType::HEX8, 0);
+        mesh.element_mut(hex).connectivity.swap(1, 3);
+        mesh.element_mut(hex).connectivity.swap(5, 7);
+        assert!(check_orientation(mesh.view(), 0.0).inverted.contains(hex));
+        let fixed = fix_orientation(&mut mesh);
+        assert!(fixed.contains(hex));
+        assert_abs_diff_eq!(
+            check_orientation(mesh.view(), 0.0).signed_measures[&ElementType::HEX8][0],
+            1.0,
+            epsilon = 1e-12
+        );
+
+        // A bow tie is tangled, not inverted as a whole
+        let mut mesh = me::make_imesh_2d(1);
+        mesh.element_mut(ElementId::new(ElementType::QUAD4, 0))
+            .connectivity
+            .swap(2, 3);
+        assert_eq!(check_orientation(mesh.view(), 0.0).inverted.len(), 1);
+        assert!(fix_orientation(&mut mesh).is_empty());
+
+        // Quadratic cells are renumbered with their middle nodes
+        let coords = nd::arr2(&[
+            [0.0, 0.0],
+            [0.0, 1.0],
+            [1.0, 0.0],
+            [0.0, 0.5],
+            [0.5, 0.5],
+            [0.5, 0.0],
+        ]);
+        let mut mesh = UMesh::new(coords.into_shared());
+        let tri6 = nd::arr2(&[[0, 1, 2, 3, 4, 5]]);
+        mesh.add_regular_block(ElementType::TRI6, tri6.into_shared(), None);
+        assert_eq!(fix_orientation(&mut mesh).len(), 1);
+        let tri = ElementId::new(ElementType::TRI6, 0);
+        assert_eq!(mesh.element(tri).connectivity, &[0, 2, 1, 5, 4, 3]);
+    }
+}
@@ -0,0 +1,317 @@
+//! Detection of overlapping cells between two meshes.
+//!
+//! The intersection operations of [`crate::tools::intersect`] expect meshes without overlapping
+//! cells. [`detect_overlaps`] checks this precondition, between two meshes or between a mesh and
+//! itself. Cells are split into simplices, and the measure of the intersection of two cells is the
+//! sum of the measures of the intersections of their simplices, computed by clipping.
+//!
+//! Cells are assumed convex. Polygons and polyhedra are split in fans from their first node, see
+//! [`crate::element_traits::ElementTopo::to_simplexes`], whose simplices cover a non-convex cell
+//! only when it is star-shaped from that node. Otherwise, some simplices stick out of the cell or
+//! overlap each other, and the measures of the intersections are wrong.
+
+use crate::element_traits::ElementGeo;
+use crate::geometry::predicates::orient2d;
+use crate::mesh::{Dimension, Element, ElementId, ElementLike, UMeshView};
+use crate::tools::broad_phase::broad_phase;
+use crate::tools::progress::Monitor;
+use crate::trace;
+
+use nalgebra as na;
+use rustc_hash::FxHashMap;
+
+/// Measure of the intersection of two simplices, given by the coordinates of their nodes.
+pub(crate) type SimplexOverlap = fn(&[[f64; 3]], &[[f64; 3]]) -> f64;
+
+/// Returns the pairs of cells of `a` and `b` whose intersection measure exceeds `tol`.
+///
+/// Cells are the elements of the topological dimension of the meshes: areas of 2D cells are
+/// compared in 2D space, volumes of 3D cells in 3D space. Each pair is reported with the area or
+/// volume of the intersection, sorted by cell of `a` then of `b`. Cells sharing a face or a node
+/// only do not overlap.
+///
+/// Checking a mesh against itself reports every cell with itself, and any other pair is an
+/// overlap within the mesh.
+///
+/// Cells must be convex, see the [module documentation](self).
+///
+/// # Errors
+/// Returns an error if the meshes do not have the same topological and space dimensions, or if
+/// their cells are neither 2D cells in 2D space nor 3D cells in 3D space.
+pub fn detect_overlaps(
+    a: UMeshView,
+    b: UMeshView,
+    tol: f64,
+) -> Result<Vec<(ElementId, ElementId, f64)>, String> {
+    detect_overlaps_monitored(a, b, tol, Monitor::new())
+}
+
+/// [`detect_overlaps`] reporting its progress to a [`Monitor`].
+///
+/// # Errors
+/// Also returns an error if the monitor is cancelled.
+pub fn detect_overlaps_monitored(
+    a: UMeshView,
+    b: UMeshView,
+    tol: f64,
+    monitor: Monitor,
+) -> Result<Vec<(ElementId, ElementId, f64)>, String> {
+    let dim = match (a.topological_dimension(), b.topological_dimension()) {
+        (None, _) | (_, None) => return Ok(Vec::new()),
+        (Some(da), Some(db)) if da == db => da,
+        _ => return Err("Meshes must have the same topological dimension".to_owned()),
+    };
+    let space_dim = a.space_dimension();
+    if b.space_dimension() != space_dim {
+        return Err("Meshes must have the same space dimension".to_owned());
+    }
+    let overlap: SimplexOverlap = match (dim, space_dim) {
+        (Dimension::D2, 2) => triangle_overlap,
+        (Dimension::D3, 3) => tetrahedron_overlap,
+        _ => {
+            return Err(format!(
+                "Overlaps of {dim:?} cells in {space_dim}D space are not supported"
+            ));
+        }
+    };
+
+    trace::span!("detect_overlaps");
+    let pairs = broad_phase(a.view(), b.view(), Some(dim), Some(dim));
+    trace::debug!(candidates = pairs.len(), "broad phase done");
+    let cells_b: FxHashMap<ElementId, Cell> = b
+        .elements_of_dim(dim)
+        .map(|e| (e.id(), Cell::new(e)))
+        .collect();
+    let mut candidates = pairs.iter().peekable();
+    let mut res = Vec::new();
+    let num_cells = a.num_elements_of_dim(dim);
+    for (i, cell_a) in a.elements_of_dim(dim).map(Cell::new).enumerate() {
+        monitor.step(i, num_cells, "detecting overlaps")?;
+        while let Some(&(_, id_b)) = candidates.next_if(|(id_a, _)| *id_a == cell_a.id) {
+            let measure = cell_a.overlap(&cells_b[&id_b], overlap);
+            if measure > tol {
+                res.push((cell_a.id, id_b, measure));
+            }
+        }
+    }
+    trace::debug!(cells = num_cells, overlaps = res.len(), "narrow phase done");
+    Ok(res)
+}
+
+/// A cell split into simplices.
+struct Cell {
+    id: ElementId,
+    simplices: Vec<Vec<[f64; 3]>>,
+}
+
+impl Cell {
+    fn new(element: Element<'_>) -> Self {
+        Self {
+            id: element.id(),
+            simplices: element.simplex_coords(),
+        }
+    }
+
+    fn overlap(&self, other: &Cell, simplex_overlap: SimplexOverlap) -> f64 {
+        self.simplices
+            .iter()
+            .flat_map(|s| other.simplices.iter().map(move |t| simplex_overlap(s, t)))
+            .sum()
+    }
+}
+
+/// Clips a polygon to the half-space where `dist` is negative.
+///
+/// The points of the clipped polygon lying on the boundary of the half-space are appended to
+/// `on_boundary`.
+fn clip<const N: usize>(
+    polygon: &[[f64; N]],
+    dist: impl Fn(&[f64; N]) -> f64,
+    on_boundary: &mut Vec<[f64; N]>,
+) -> Vec<[f64; N]> {
+    let mut res = Vec::with_capacity(polygon.len() + 1);
+    for (i, p) in polygon.iter().enumerate() {
+        let q = &polygon[(i + 1) % polygon.len()];
+        let (dp, dq) = (dist(p), dist(q));
+        if dp <= 0.0 {
+            res.push(*p);
+        }
+        if dp == 0.0 {
+            on_boundary.push(*p);
+        }
+        if (dp < 0.0 && dq > 0.0) || (dp > 0.0 && dq < 0.0) {
+            let t = dp / (dp - dq);
+            let x = std::array::from_fn(|k| p[k] + t * (q[k] - p[k]));
+            res.push(x);
+            on_boundary.push(x);
+        }
+    }
+    res
+}
+
+/// Area of the intersection of two triangles of the `z = 0` plane.
+pub(crate) fn triangle_overlap(t1: &[[f64; 3]], t2: &[[f64; 3]]) -> f64 {
+    let mut polygon: Vec<[f64; 2]> = t1.iter().map(|p| [p[0], p[1]]).collect();
+    let mut t2: Vec<[f64; 2]> = t2.iter().map(|p| [p[0], p[1]]).collect();
+    match orient2d(&t2[0], &t2[1], &t2[2]) {
+        o if o < 0.0 => t2.reverse(),
+        0.0 => return 0.0,
+        _ => (),
+    }
+    for i in 0..3 {
+        let (p, q) = (t2[i], t2[(i + 1) % 3]);
+        polygon = clip(&polygon, |x| -orient2d(&p, &q, x), &mut Vec::new());
+        if polygon.len() < 3 {
+            return 0.0;
+        }
+    }
+    let area: f64 = (0..polygon.len())
+        .map(|i| {
+            let (p, q) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            p[0] * q[1] - p[1] * q[0]
+        })
+        .sum();
+    area.abs() / 2.0
+}
+
+/// Volume of the intersection of two tetrahedra.
+fn tetrahedron_overlap(t1: &[[f64; 3]], t2: &[[f64; 3]]) -> f64 {
+    const FACES: [[usize; 4]; 4] = [[1, 2, 3, 0], [0, 2, 3, 1], [0, 1, 3, 2], [0, 1, 2, 3]];
+    let v = |p: &[f64; 3]| na::Vector3::from(*p);
+    let mut faces: Vec<Vec<[f64; 3]>> = FACES
+        .iter()
+        .map(|f| vec![t1[f[0]], t1[f[1]], t1[f[2]]])
+        .collect();
+    for [i, j, k, l] in FACES {
+        let origin = v(&t2[i]);
+        let mut normal = (v(&t2[j]) - origin).cross(&(v(&t2[k]) - origin));
+        match normal.dot(&(v(&t2[l]) - origin)) {
+            o if o > 0.0 => normal = -normal,
+            0.0 => return 0.0,
+            _ => (),
+        }
+        let dist = |x: &[f64; 3]| normal.dot(&(v(x) - origin));
+        let mut cap = Vec::new();
+        // Faces lying in the plane are replaced by the cap, which contains their points
+        faces = faces
+            .iter()
+            .map(|f| clip(f, dist, &mut cap))
+            .filter(|f| f.len() >= 3 && f.iter().any(|x| dist(x) != 0.0))
+            .collect();
+        if cap.len() >= 3 {
+            faces.push(sorted_around(cap, &normal));
+        }
+        if faces.len() < 4 {
+            return 0.0;
+        }
+    }
+    convex_volume(&faces)
+}
+
+/// Sorts coplanar points of a convex polygon around their centroid.
+fn sorted_around(points: Vec<[f64; 3]>, normal: &na::Vector3<f64>) -> Vec<[f64; 3]> {
+    let n = points.len() as f64;
+    let center = points
+        .iter()
+        .fold(na::Vector3::zeros(), |c, p| c + na::Vector3::from(*p))
+        / n;
+    let u = points
+        .iter()
+        .map(|p| na::Vector3::from(*p) - center)
+        .max_by(|a, b| a.norm().total_cmp(&b.norm()))
+        .unwrap();
+    let w = normal.cross(&u);
+    let angle = |p: &[f64; 3]| {
+        let d = na::Vector3::from(*p) - center;
+        d.dot(&w).atan2(d.dot(&u))
+    };
+    let mut points = points;
+    points.sort_by(|p, q| angle(p).total_cmp(&angle(q)));
+    points
+}
+
+/// Volume of a convex polyhedron given by its faces, whatever their orientation.
+fn convex_volume(faces: &[Vec<[f64; 3]>]) -> f64 {
+    let n: usize = faces.iter().map(Vec::len).sum();
+    let center = faces
+        .iter()
+        .flatten()
+        .fold(na::Vector3::zeros(), |c, p| c + na::Vector3::from(*p))
+        / n as f64;
+    faces
+        .iter()
+        .flat_map(|f| {
+            let o = na::Vector3::from(f[0]) - center;
+            (1..f.len() - 1).map(move |i| {
+                let a = na::Vector3::from(f[i]) - center;
+                let b = na::Vector3::from(f[i + 1]) - center;
+                o.dot(&a.cross(&b)).abs() / 6.0
+            })
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementType, UMesh};
+    use crate::mesh_examples as me;
+    use crate::tools::RegularUMeshBuilder;
+    use approx::assert_relative_eq;
+
+    fn square(x0: f64, y0: f64, z0: Option<f64>) -> UMesh {
+        let builder = RegularUMeshBuilder::new()
+            .add_axis(vec![x0, x0 + 1.0])
+            .add_axis(vec![y0, y0 + 1.0]);
+        match z0 {
+            Some(z0) => builder.add_axis(vec![z0, z0 + 1.0]).build(),
+            None => builder.build(),
+        }
+    }
+
+    #[test]
+    fn test_detect_overlaps_2d() {
+        let mesh = me::make_imesh_2d(2);
+        let overlaps = detect_overlaps(mesh.view(), mesh.view(), 1e-12).unwrap();
+        assert_eq!(overlaps.len(), 4);
+        for (i, &(a, b, area)) in overlaps.iter().enumerate() {
+            assert_eq!(a, ElementId::new(ElementType::QUAD4, i));
+            assert_eq!(a, b);
+            assert_relative_eq!(area, 0.25, epsilon = 1e-12);
+        }
+
+        let shifted = square(0.5, 0.25, None);
+        let overlaps = detect_overlaps(mesh.view(), shifted.view(), 1e-12).unwrap();
+        let areas: Vec<f64> = overlaps.iter().map(|o| o.2).collect();
+        assert_eq!(overlaps.len(), 2);
+        assert_relative_eq!(areas[0], 0.125, epsilon = 1e-12);
+        assert_relative_eq!(areas[1], 0.25, epsilon = 1e-12);
+        assert_eq!(overlaps[1].0, ElementId::new(ElementType::QUAD4, 3));
+        let overlaps = detect_overlaps(mesh.view(), shifted.view(), 0.2).unwrap();
+        assert_eq!(overlaps.len(), 1);
+
+        // Touching meshes do not overlap
+        let touching = square(1.0, 0.0, None);
+        assert!(
+            detect_overlaps(mesh.view(), touching.view(), 1e-12)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_detect_overlaps_3d() {
+        let cube = square(0.0, 0.0, Some(0.0));
+        let overlaps = detect_overlaps(cube.view(), cube.view(), 1e-12).unwrap();
+        assert_eq!(overlaps.len(), 1);
+        assert_relative_eq!(overlaps[0].2, 1.0, epsilon = 1e-12);
+
+        let shifted = square(0.5, 0.0, Some(0.5));
+        let overlaps = detect_overlaps(cube.view(), shifted.view(), 1e-12).unwrap();
+        assert_eq!(overlaps.len(), 1);
+        assert_relative_eq!(overlaps[0].2, 0.25, epsilon = 1e-12);
+
+        let grid = me::make_imesh_2d(1);
+        assert!(detect_overlaps(cube.view(), grid.view(), 1e-12).is_err());
+    }
+}
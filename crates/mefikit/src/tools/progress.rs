@@ -0,0 +1,147 @@
+//! Progress reporting and cancellation of long running algorithms.
+//!
+//! Algorithms taking a [`Monitor`] report their progress to an optional [`ProgressSink`] and stop
+//! with a [`Cancelled`] error as soon as their optional [`CancellationToken`] is cancelled, e.g.
+//! from a GUI thread. Progress is reported by stage: the fraction goes from 0 to 1 within each
+//! named stage of the algorithm.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Receives the progress of an algorithm.
+///
+/// Any `Fn(f64, &str)` closure is a progress sink.
+pub trait ProgressSink: Sync {
+    /// Called with the fraction of the current stage done, between 0 and 1, and the stage name.
+    fn progress(&self, fraction: f64, stage: &str);
+}
+
+impl<F: Fn(f64, &str) + Sync> ProgressSink for F {
+    fn progress(&self, fraction: f64, stage: &str) {
+        self(fraction, stage)
+    }
+}
+
+/// A flag shared between an algorithm and its caller to cancel it.
+///
+/// Clones share the same flag, so one is given to the algorithm and another one kept to cancel
+/// it, possibly from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the algorithms using this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [`Self::cancel`] was called on this token or one of its clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The error returned by an algorithm which was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl From<Cancelled> for String {
+    fn from(cancelled: Cancelled) -> Self {
+        cancelled.to_string()
+    }
+}
+
+/// Progress sink and cancellation token given to an algorithm, both optional.
+///
+/// The default monitor reports nothing and is never cancelled.
+#[derive(Clone, Copy, Default)]
+pub struct Monitor<'a> {
+    sink: Option<&'a dyn ProgressSink>,
+    token: Option<&'a CancellationToken>,
+}
+
+impl<'a> Monitor<'a> {
+    /// Creates a monitor which reports nothing and is never cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports the progress to the given sink.
+    pub fn progress(mut self, sink: &'a dyn ProgressSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Stops the algorithm when the given token is cancelled.
+    pub fn cancellation(mut self, token: &'a CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Reports the fraction done of a stage, then fails if the algorithm was cancelled.
+    pub fn report(&self, fraction: f64, stage: &str) -> Result<(), Cancelled> {
+        if let Some(sink) = self.sink {
+            sink.progress(fraction, stage);
+        }
+        self.check()
+    }
+
+    /// Reports that `done` items out of `total` were processed in a stage.
+    ///
+    /// Meant to be called for each item: the sink is only called once per percent, while
+    /// cancellation is checked every time.
+    pub fn step(&self, done: usize, total: usize, stage: &str) -> Result<(), Cancelled> {
+        if self.sink.is_some() && done.is_multiple_of(total.div_ceil(100).max(1)) {
+            return self.report(done as f64 / total as f64, stage);
+        }
+        self.check()
+    }
+
+    /// Fails if the algorithm was cancelled.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.token.is_some_and(|t| t.is_cancelled()) {
+            true => Err(Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_monitor() {
+        let reports = Mutex::new(Vec::new());
+        let sink =
+            |fraction: f64, stage: &str| reports.lock().unwrap().push((fraction, stage.to_owned()));
+        let token = CancellationToken::new();
+        let monitor = Monitor::new().progress(&sink).cancellation(&token);
+        for i in 0..1000 {
+            monitor.step(i, 1000, "stage").unwrap();
+        }
+        monitor.report(1.0, "stage").unwrap();
+        token.clone().cancel();
+        assert_eq!(monitor.step(1, 1000, "stage"), Err(Cancelled));
+        assert_eq!(Monitor::new().check(), Ok(()));
+
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 101);
+        assert_eq!(reports[1], (0.01, "stage".to_owned()));
+    }
+}
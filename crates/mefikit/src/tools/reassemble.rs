@@ -0,0 +1,205 @@
+//! Reassembly of the parts of a distributed mesh into one global mesh.
+//!
+//! Each rank of a distributed computation holds a part of the global mesh, with a local
+//! numbering of its nodes and elements. Parts overlap at their interfaces: nodes on an interface,
+//! and ghost cells, are held by several parts. [`reassemble`] gathers the parts back into the
+//! global mesh, so that distributed results can be post-processed as one mesh.
+
+use std::collections::BTreeMap;
+
+use ndarray as nd;
+
+use crate::mesh::{ElementType, UMesh, UMeshView};
+use crate::trace;
+
+/// The global numbering of the nodes and elements of a part.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlobalMap {
+    /// The global index of each node of the part.
+    pub nodes: Vec<usize>,
+    /// The global index of each element of the part in the global block of its type.
+    pub elements: BTreeMap<ElementType, Vec<usize>>,
+}
+
+/// Merges parts of a mesh back into the global mesh, following their global numbering.
+///
+/// Nodes and elements held by several parts are only kept once, with the coordinates, the
+/// connectivity, the field values and the groups of the first part holding them. Global nodes and
+/// elements are numbered as in the maps, and a node belongs to a node group if it does in any
+/// part. Fields and groups are concatenated as in [`UMesh::append`].
+///
+/// An error is returned if the maps do not match the parts, or if a global node or element is
+/// held by no part.
+pub fn reassemble(parts: &[UMesh], global_maps: &[GlobalMap]) -> Result<UMesh, String> {
+    trace::span!("reassemble");
+    if parts.is_empty() || parts.len() != global_maps.len() {
+        return Err(format!(
+            "Expected one global map per part, got {} parts and {} maps.",
+            parts.len(),
+            global_maps.len()
+        ));
+    }
+    for (p, (part, map)) in parts.iter().zip(global_maps).enumerate() {
+        if map.nodes.len() != part.coords().nrows() {
+            return Err(format!(
+                "Part {p} has {} nodes but its map has {}.",
+                part.coords().nrows(),
+                map.nodes.len()
+            ));
+        }
+        for (et, block) in part.blocks() {
+            let n_mapped = map.elements.get(et).map_or(0, |ids| ids.len());
+            if n_mapped != block.len() {
+                return Err(format!(
+                    "Part {p} has {} {et:?} elements but its map has {n_mapped}.",
+                    block.len()
+                ));
+            }
+        }
+    }
+
+    // Parts are concatenated, then the first copy of each global element is extracted
+    let views: Vec<UMeshView> = parts.iter().map(|p| p.view()).collect();
+    let merged = UMesh::concat(&views);
+    let node_to_global: Vec<usize> = global_maps
+        .iter()
+        .flat_map(|m| m.nodes.iter().copied())
+        .collect();
+    let mut element_to_global: BTreeMap<ElementType, Vec<usize>> = BTreeMap::new();
+    for (part, map) in parts.iter().zip(global_maps) {
+        for &et in part.element_types() {
+            element_to_global
+                .entry(et)
+                .or_default()
+                .extend(&map.elements[&et]);
+        }
+    }
+
+    let n_nodes = node_to_global.iter().max().map_or(0, |&n| n + 1);
+    let mut coords = nd::Array2::<f64>::zeros((n_nodes, merged.space_dimension()));
+    let mut held = vec![false; n_nodes];
+    for (node, &global) in node_to_global.iter().enumerate() {
+        if !held[global] {
+            held[global] = true;
+            coords.row_mut(global).assign(&merged.coords().row(node));
+        }
+    }
+    if let Some(missing) = held.iter().position(|&h| !h) {
+        return Err(format!("Global node {missing} is held by no part."));
+    }
+
+    let mut global = UMesh::new(coords.into_shared());
+    for (&et, globals) in &element_to_global {
+        let n_elements = globals.iter().max().map_or(0, |&e| e + 1);
+        let mut first = vec![None; n_elements];
+        for (e, &g) in globals.iter().enumerate() {
+            if first[g].is_none() {
+                first[g] = Some(e);
+            }
+        }
+        let kept: Vec<usize> = match first.iter().position(Option::is_none) {
+            Some(missing) => {
+                return Err(format!(
+                    "Global {et:?} element {missing} is held by no part."
+                ));
+            }
+            None => first.into_iter().flatten().collect(),
+        };
+        let mut block = merged.element_blocks[&et].select(&kept);
+        block.connectivity.map_nodes(|n| node_to_global[n]);
+        global.element_blocks.insert(et, block);
+    }
+    for (name, nodes) in &merged.node_groups {
+        global.set_node_group(name, nodes.iter().map(|&n| node_to_global[n]));
+    }
+    trace::debug!(
+        parts = parts.len(),
+        nodes = global.coords().nrows(),
+        elements = global.num_elements(),
+        "parts reassembled"
+    );
+    Ok(global)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementId, ElementIds};
+    use crate::mesh_examples as me;
+
+    /// Splits the 2 x 2 grid in its bottom and top rows, which share the middle row of nodes.
+    fn split_imesh_2d() -> (UMesh, Vec<UMesh>, Vec<GlobalMap>) {
+        let mut mesh = me::make_imesh_2d(2);
+        let values = nd::arr1(&[0.0, 1.0, 2.0, 3.0]).into_dyn().into_shared();
+        mesh.add_field(ElementType::QUAD4, "f", values);
+        mesh.set_node_group("origin", [0]);
+        let mut parts = Vec::new();
+        let mut maps = Vec::new();
+        for cells in [[0, 1], [2, 3]] {
+            let mut ids = ElementIds::new();
+            cells.iter().for_each(|&c| ids.add(ElementType::QUAD4, c));
+            let mut part = mesh.extract(&ids, true);
+            let mut group = ElementIds::new();
+            group.add(ElementType::QUAD4, 1);
+            part.set_group("right", &group);
+            // Nodes are renumbered locally
+            let nodes = part.used_nodes();
+            part.prune_nodes();
+            parts.push(part);
+            maps.push(GlobalMap {
+                nodes,
+                elements: BTreeMap::from([(ElementType::QUAD4, cells.to_vec())]),
+            });
+        }
+        (mesh, parts, maps)
+    }
+
+    #[test]
+    fn test_reassemble() {
+        let (mesh, parts, maps) = split_imesh_2d();
+        assert_eq!(parts[0].coords().nrows(), 6);
+        let global = reassemble(&parts, &maps).unwrap();
+        assert_eq!(global.coords(), mesh.coords());
+        assert_eq!(
+            global.regular_connectivity(ElementType::QUAD4).unwrap(),
+            mesh.regular_connectivity(ElementType::QUAD4).unwrap()
+        );
+        assert_eq!(
+            global.field("f", None).unwrap().0[&ElementType::QUAD4],
+            mesh.field("f", None).unwrap().0[&ElementType::QUAD4]
+        );
+        assert_eq!(global.node_group("origin").unwrap().len(), 1);
+        let right: Vec<ElementId> = global.group_as_element_ids("right").iter().collect();
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        assert_eq!(right, vec![quad(1), quad(3)]);
+    }
+
+    #[test]
+    fn test_reassemble_ghosts() {
+        let (mesh, mut parts, mut maps) = split_imesh_2d();
+        // The first part also holds the cell 2 as a ghost
+        let ghost = parts[1].extract(
+            &ElementIds::from(BTreeMap::from([(ElementType::QUAD4, vec![0])])),
+            true,
+        );
+        let n_nodes = maps[0].nodes.len();
+        parts[0].append(ghost.view());
+        let ghost_nodes = maps[1].nodes.clone();
+        maps[0].nodes.extend(ghost_nodes);
+        maps[0]
+            .elements
+            .get_mut(&ElementType::QUAD4)
+            .unwrap()
+            .push(2);
+        assert_eq!(parts[0].coords().nrows(), n_nodes + 6);
+
+        let global = reassemble(&parts, &maps).unwrap();
+        assert_eq!(global.coords(), mesh.coords());
+        assert_eq!(global.num_elements(), 4);
+
+        maps[1].elements.get_mut(&ElementType::QUAD4).unwrap()[1] = 4;
+        assert!(reassemble(&parts, &maps).is_err());
+        maps.pop();
+        assert!(reassemble(&parts, &maps).is_err());
+    }
+}
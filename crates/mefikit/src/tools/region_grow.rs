@@ -0,0 +1,173 @@
+//! Region growing (flood fill) over face-adjacent elements.
+//!
+//! Starting from seed elements, the region grows to neighbouring elements as long as a
+//! criterion holds. This is used to select contiguous patches, for example a smooth part of a
+//! surface bounded by sharp edges.
+
+use std::collections::VecDeque;
+
+use nalgebra as na;
+use ndarray as nd;
+use rustc_hash::FxHashSet;
+
+use crate::element_traits::ElementGeo;
+use crate::mesh::{
+    Dimension, Element, ElementId, ElementIds, ElementIdsSet, ElementLike, UMeshView,
+};
+use crate::tools::compute_neighbours_graph;
+
+/// Criterion deciding whether the region may grow from an element to one of its neighbours.
+#[derive(Clone, Debug)]
+pub enum GrowCriterion {
+    /// The neighbour belongs to the given group.
+    InGroup(String),
+    /// The neighbour belongs to exactly the same groups as the element it is reached from.
+    SameGroups,
+    /// The neighbour has the same family as the element it is reached from.
+    SameFamily,
+    /// The neighbour's value of an element field lies in `[min, max]`.
+    ///
+    /// For non scalar fields, the euclidean norm of the element value is used.
+    FieldRange { name: String, min: f64, max: f64 },
+    /// The angle (in radians) between the normals of the two elements is at most the given
+    /// value, regardless of their orientation.
+    ///
+    /// Normals are only defined for segments in 2D and surface elements in 3D. Other elements
+    /// always satisfy this criterion.
+    NormalAngle(f64),
+    /// All the criteria hold.
+    All(Vec<GrowCriterion>),
+}
+
+impl GrowCriterion {
+    fn accepts(&self, mesh: &UMeshView, from: &Element, to: &Element) -> bool {
+        match self {
+            GrowCriterion::InGroup(name) => to.in_group(name),
+            GrowCriterion::SameGroups => from.groups() == to.groups(),
+            GrowCriterion::SameFamily => from.family == to.family,
+            GrowCriterion::FieldRange { name, min, max } => mesh
+                .block(to.element_type())
+                .and_then(|block| block.fields.get(name))
+                .map(|field| {
+                    let value = field.index_axis(nd::Axis(0), to.index());
+                    let value = match value.len() {
+                        1 => value.iter().next().copied().unwrap(),
+                        _ => value.iter().map(|x| x * x).sum::<f64>().sqrt(),
+                    };
+                    *min <= value && value <= *max
+                })
+                .unwrap_or(false),
+            GrowCriterion::NormalAngle(angle) => match (normal(from), normal(to)) {
+                (Some(a), Some(b)) => a.dot(&b).abs().min(1.0).acos() <= *angle,
+                _ => true,
+            },
+            GrowCriterion::All(criteria) => criteria.iter().all(|c| c.accepts(mesh, from, to)),
+        }
+    }
+}
+
+/// Unit normal of a segment in 2D or of a surface element in 3D, computed from its first nodes.
+fn normal(element: &Element) -> Option<na::Vector3<f64>> {
+    let n = match (element.dimension(), element.space_dimension()) {
+        (Dimension::D1, 2) => {
+            let t = element.coord2(1) - element.coord2(0);
+            na::Vector3::new(-t.y, t.x, 0.0)
+        }
+        (Dimension::D2, 3) => {
+            let p0 = element.coord3(0);
+            (element.coord3(1) - p0).cross(&(element.coord3(2) - p0))
+        }
+        _ => return None,
+    };
+    n.try_normalize(f64::EPSILON)
+}
+
+/// Grows a region from seed elements over face-adjacent elements while the criterion holds.
+///
+/// Adjacency is computed between elements of dimension `dim` (defaults to the topological
+/// dimension of the mesh) sharing a subentity of one dimension less. Seeds of another dimension
+/// are ignored. Seeds are always part of the result. To grow from a group, use
+/// [`UMeshBase::group_as_element_ids`](crate::mesh::UMeshBase::group_as_element_ids) as seeds.
+pub fn grow_region(
+    mesh: UMeshView,
+    seeds: &ElementIds,
+    dim: Option<Dimension>,
+    criterion: &GrowCriterion,
+) -> ElementIds {
+    grow_region_with(mesh.view(), seeds, dim, |from, to| {
+        criterion.accepts(&mesh, &mesh.element(from), &mesh.element(to))
+    })
+}
+
+/// Grows a region from seed elements over face-adjacent elements while `predicate(from, to)`
+/// holds.
+///
+/// This is the same as [`grow_region`] with a custom criterion.
+pub fn grow_region_with<P>(
+    mesh: UMeshView,
+    seeds: &ElementIds,
+    dim: Option<Dimension>,
+    predicate: P,
+) -> ElementIds
+where
+    P: Fn(ElementId, ElementId) -> bool,
+{
+    let graph = compute_neighbours_graph(mesh, dim, None);
+    let mut region: FxHashSet<ElementId> = FxHashSet::default();
+    let mut queue: VecDeque<ElementId> = VecDeque::new();
+    for eid in seeds.iter() {
+        if graph.contains_node(eid) && region.insert(eid) {
+            queue.push_back(eid);
+        }
+    }
+    while let Some(eid) = queue.pop_front() {
+        for neighbour in graph.neighbors(eid) {
+            if !region.contains(&neighbour) && predicate(eid, neighbour) {
+                region.insert(neighbour);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    region.into_iter().collect::<ElementIdsSet>().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementType;
+    use crate::mesh_examples as me;
+    use crate::tools::Descendable;
+
+    #[test]
+    fn test_grow_in_group() {
+        let mut mesh = me::make_imesh_2d(3);
+        let mut left = ElementIds::new();
+        left.add_block(ElementType::QUAD4, vec![0, 3, 6]);
+        mesh.set_group("left", &left);
+
+        let mut seeds = ElementIds::new();
+        seeds.add(ElementType::QUAD4, 3);
+        let criterion = GrowCriterion::InGroup("left".to_owned());
+        let region = grow_region(mesh.view(), &seeds, None, &criterion);
+        assert_eq!(region.get(&ElementType::QUAD4), Some(&vec![0, 3, 6]));
+
+        let region = grow_region(mesh.view(), &seeds, None, &GrowCriterion::SameFamily);
+        assert_eq!(region.get(&ElementType::QUAD4), Some(&vec![0, 3, 6]));
+
+        let region = grow_region_with(mesh.view(), &seeds, None, |_, _| true);
+        assert_eq!(region.len(), 9);
+    }
+
+    #[test]
+    fn test_grow_normal_angle() {
+        let mesh = me::make_imesh_3d(2);
+        let skin = mesh.boundaries(None, None);
+        let seed = skin.elements().next().unwrap().id();
+        let seeds: ElementIds = [seed].into_iter().collect::<ElementIdsSet>().into();
+
+        let region = grow_region(skin.view(), &seeds, None, &GrowCriterion::NormalAngle(0.1));
+        assert_eq!(region.len(), 4);
+        let region = grow_region(skin.view(), &seeds, None, &GrowCriterion::NormalAngle(1.6));
+        assert_eq!(region.len(), 24);
+    }
+}
@@ -0,0 +1,626 @@
+//! Sampling of fields at arbitrary points, along probe lines, and rasterization on regular grids.
+//!
+//! Points are located in the cells of the mesh with the element locator of its cache, then
+//! values are interpolated in the containing cell. Probing along a polyline gives values against
+//! arc length for line plots, sampling on a grid gives arrays indexed like images, for image
+//! based tools and quick visual checks.
+//!
+//! Where the meshes do not overlap exactly, or to map scattered data onto a mesh, values can be
+//! taken at the nearest node or averaged over the nearest nodes by inverse distance weighting,
+//! with the node locator of [`crate::tools::node_locator`].
+
+use ndarray as nd;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::element_traits::ElementGeo;
+use crate::geometry::shape_functions;
+use crate::mesh::{ElementId, ElementLike, ElementType, FieldLocation, FieldView, UMeshView};
+use crate::tools::{NodeLocator, RegularUMeshBuilder, centroids};
+use crate::trace;
+
+/// The values to sample, located at the nodes or at the cells of a mesh.
+#[derive(Clone, Debug)]
+pub enum Sampled<'a> {
+    /// Values at the nodes, of shape `[n_nodes, ...]`, interpolated with the shape functions of
+    /// the containing cell. Polygons take the mean value of their nodes.
+    Nodes(nd::ArrayViewD<'a, f64>),
+    /// The name of a cell field of shape `[n_elem, ...]` on the cells of highest dimension,
+    /// constant over each cell.
+    Cells(&'a str),
+}
+
+/// Values sampled at a set of points, with the points located in the mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Samples {
+    /// The sampled values, of shape `[...points, ...components]`, NaN outside of the mesh.
+    pub values: nd::ArrayD<f64>,
+    /// Whether each point lies in the mesh.
+    pub mask: nd::ArrayD<bool>,
+}
+
+/// A method interpolating values at points, see [`interpolate_with`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// Interpolation in the cell containing the point, as [`interpolate`]. It fails outside of
+    /// the mesh.
+    Cell,
+    /// The value of the nearest node, or of the cell of nearest centroid for cell values.
+    Nearest,
+    /// The mean of the values of the `k` nearest nodes, or cell centroids for cell values,
+    /// weighted by the inverse of their distance to the point raised to `power`. A point on a
+    /// node takes its value.
+    Idw { k: usize, power: f64 },
+}
+
+/// Values interpolated by [`interpolate_with`], with the method giving each of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interpolated {
+    /// The interpolated values, of shape `[n_points, ...]`, NaN where all the methods failed.
+    pub values: nd::ArrayD<f64>,
+    /// The method giving the value at each point, `None` where all the methods failed.
+    pub methods: Vec<Option<Interpolation>>,
+}
+
+/// Finds the cell containing each point, `None` for the points outside of the mesh.
+///
+/// Only the cells of the space dimension are searched, except polyhedra. A point on a face
+/// shared by several cells is given to the first of them in element id order.
+pub fn locate_points(mesh: UMeshView, points: nd::ArrayView2<f64>) -> Vec<Option<ElementId>> {
+    trace::span!("locate_points");
+    let space_dim = mesh.space_dimension();
+    let locator = mesh.element_locator();
+    let locate = |i: usize| {
+        let point = points.row(i).to_vec();
+        let mut padded = [0.0; 3];
+        padded[..space_dim].copy_from_slice(&point);
+        let mut candidates: Vec<ElementId> = locator
+            .locate_all_at_point(&padded)
+            .map(|r| r.data)
+            .filter(|id| {
+                let et = id.element_type();
+                u8::from(et.dimension()) as usize == space_dim && et != ElementType::PHED
+            })
+            .collect();
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .find(|&id| mesh.element(id).is_point_inside(&point))
+    };
+
+    #[cfg(feature = "rayon")]
+    let located = (0..points.nrows()).into_par_iter().map(locate);
+    #[cfg(not(feature = "rayon"))]
+    let located = (0..points.nrows()).map(locate);
+    located.collect()
+}
+
+/// Interpolates values at a set of points of shape `[n_points, space_dim]`.
+///
+/// The values have shape `[n_points, ...]`. An error is returned if the node values do not
+/// match the nodes of the mesh, or if the cell field does not exist on all the cells of highest
+/// dimension, with one value per cell and the same components in all the blocks.
+pub fn interpolate(
+    mesh: UMeshView,
+    sampled: Sampled,
+    points: nd::ArrayView2<f64>,
+) -> Result<Samples, String> {
+    check_points(&mesh, points)?;
+    let located = locate_points(mesh.view(), points);
+    let values = interpolate_located(&mesh, &sampled, points, &located)?;
+    let mask = nd::Array1::from_iter(located.iter().map(Option::is_some)).into_dyn();
+    Ok(Samples { values, mask })
+}
+
+fn check_points(mesh: &UMeshView, points: nd::ArrayView2<f64>) -> Result<(), String> {
+    match points.ncols() == mesh.space_dimension() {
+        true => Ok(()),
+        false => Err(format!(
+            "Points have {} coordinates, expected {}.",
+            points.ncols(),
+            mesh.space_dimension()
+        )),
+    }
+}
+
+/// Interpolates values at points already located by [`locate_points`].
+fn interpolate_located(
+    mesh: &UMeshView,
+    sampled: &Sampled,
+    points: nd::ArrayView2<f64>,
+    located: &[Option<ElementId>],
+) -> Result<nd::ArrayD<f64>, String> {
+    let cell_field = match sampled {
+        Sampled::Nodes(values) if values.shape().first() != Some(&mesh.coords().nrows()) => {
+            return Err(format!(
+                "Node values have shape {:?}, expected one value per node.",
+                values.shape()
+            ));
+        }
+        Sampled::Nodes(_) => None,
+        Sampled::Cells(name) => Some(cell_field(mesh, name)?),
+    };
+    let components: Vec<usize> = match (sampled, &cell_field) {
+        (Sampled::Nodes(values), _) => values.shape()[1..].to_vec(),
+        (_, Some(field)) => field
+            .0
+            .values()
+            .next()
+            .map_or(Vec::new(), |values| values.shape()[1..].to_vec()),
+        (_, None) => unreachable!(),
+    };
+
+    let mut shape = vec![points.nrows()];
+    shape.extend(&components);
+    let mut values = nd::ArrayD::from_elem(shape, f64::NAN);
+    for (i, id) in located.iter().enumerate() {
+        let Some(id) = *id else {
+            continue;
+        };
+        let mut dst = values.index_axis_mut(nd::Axis(0), i);
+        match (sampled, &cell_field) {
+            (Sampled::Nodes(node_values), _) => {
+                let element = mesh.element(id);
+                let conn = element.connectivity();
+                let weights = shape_functions_at(&element, &points.row(i).to_vec())
+                    .unwrap_or_else(|| vec![1.0 / conn.len() as f64; conn.len()]);
+                dst.fill(0.0);
+                for (&node, w) in conn.iter().zip(weights) {
+                    dst.scaled_add(w, &node_values.index_axis(nd::Axis(0), node));
+                }
+            }
+            // A cell outside of the blocks of the field keeps a NaN value
+            (_, Some(field)) => {
+                if let Some(block) = field.0.get(&id.element_type()) {
+                    dst.assign(&block.index_axis(nd::Axis(0), id.index()));
+                }
+            }
+            (_, None) => unreachable!(),
+        }
+    }
+    Ok(values)
+}
+
+/// The cell field sampled by [`Sampled::Cells`], checking it has one value per cell with the
+/// same components in all its blocks.
+fn cell_field<'a>(mesh: &'a UMeshView, name: &str) -> Result<FieldView<'a, nd::IxDyn>, String> {
+    let field = mesh
+        .topological_dimension()
+        .and_then(|dim| mesh.field(name, Some(dim)))
+        .ok_or(format!("No cell field named {name} in the mesh."))?;
+    if field.location() != FieldLocation::Cells {
+        return Err(format!("Field {name} is not located at the cells."));
+    }
+    let mut shapes = field.0.values().map(|values| &values.shape()[1..]);
+    let first = shapes.next().unwrap_or_default();
+    match shapes.all(|shape| shape == first) {
+        true => Ok(field),
+        false => Err(format!(
+            "Cell field {name} has different components in its blocks."
+        )),
+    }
+}
+
+/// Interpolates values at a set of points of shape `[n_points, space_dim]`, trying the given
+/// methods in order at each point.
+///
+/// The first method succeeding at a point gives its value: `[Cell, Nearest]` interpolates in the
+/// cells and takes the nearest node value outside of the mesh, `[Idw { k: 8, power: 2.0 }]` maps
+/// the scattered values of the nodes of a point cloud. The nearest and inverse distance methods
+/// search all the nodes, or the cells of highest dimension for cell values, and only fail on a
+/// mesh without any.
+///
+/// # Errors
+/// Returns an error if no method is given, and the errors of [`interpolate`].
+pub fn interpolate_with(
+    mesh: UMeshView,
+    sampled: Sampled,
+    points: nd::ArrayView2<f64>,
+    methods: &[Interpolation],
+) -> Result<Interpolated, String> {
+    trace::span!("interpolate_with");
+    check_points(&mesh, points)?;
+    if methods.is_empty() {
+        return Err("No interpolation method given.".to_owned());
+    }
+    let mut values: Option<nd::ArrayD<f64>> = None;
+    let mut sites: Option<ValueSites> = None;
+    let mut used = vec![None; points.nrows()];
+    for &method in methods {
+        let pending: Vec<usize> = (0..points.nrows()).filter(|&i| used[i].is_none()).collect();
+        if pending.is_empty() {
+            break;
+        }
+        let pending_points = points.select(nd::Axis(0), &pending);
+        let (method_values, found) = match method {
+            Interpolation::Cell => {
+                let located = locate_points(mesh.view(), pending_points.view());
+                let res = interpolate_located(&mesh, &sampled, pending_points.view(), &located)?;
+                (res, located.iter().map(Option::is_some).collect())
+            }
+            Interpolation::Nearest | Interpolation::Idw { .. } => {
+                // The sites and their locator are shared by all the methods using them
+                if sites.is_none() {
+                    sites = Some(value_sites(&mesh, &sampled)?);
+                }
+                let (k, power) = match method {
+                    Interpolation::Idw { k, power } => (k, power),
+                    _ => (1, 1.0),
+                };
+                inverse_distance(sites.as_ref().unwrap(), pending_points.view(), k, power)
+            }
+        };
+        let values = values.get_or_insert_with(|| {
+            let mut shape = method_values.shape().to_vec();
+            shape[0] = points.nrows();
+            nd::ArrayD::from_elem(shape, f64::NAN)
+        });
+        for (j, &i) in pending.iter().enumerate() {
+            if found[j] {
+                values
+                    .index_axis_mut(nd::Axis(0), i)
+                    .assign(&method_values.index_axis(nd::Axis(0), j));
+                used[i] = Some(method);
+            }
+        }
+    }
+    trace::debug!(
+        points = points.nrows(),
+        failed = used.iter().filter(|m| m.is_none()).count(),
+        "points interpolated"
+    );
+    Ok(Interpolated {
+        values: values.unwrap(),
+        methods: used,
+    })
+}
+
+/// The points carrying the sampled values, nodes or cell centroids, with their values and a
+/// locator over them.
+struct ValueSites {
+    points: nd::Array2<f64>,
+    values: nd::ArrayD<f64>,
+    locator: NodeLocator,
+}
+
+/// Gathers the points carrying the sampled values, checked as in [`interpolate`].
+fn value_sites(mesh: &UMeshView, sampled: &Sampled) -> Result<ValueSites, String> {
+    let (points, values) = match sampled {
+        Sampled::Nodes(values) if values.shape().first() != Some(&mesh.coords().nrows()) => {
+            return Err(format!(
+                "Node values have shape {:?}, expected one value per node.",
+                values.shape()
+            ));
+        }
+        Sampled::Nodes(values) => (mesh.coords().to_owned(), values.to_owned()),
+        Sampled::Cells(name) => {
+            let field = cell_field(mesh, name)?;
+            let centroids = centroids(mesh.view(), None);
+            let sites: Vec<nd::ArrayView2<f64>> =
+                field.0.keys().map(|et| centroids[et].view()).collect();
+            let values: Vec<nd::ArrayViewD<f64>> = field.0.values().map(|v| v.view()).collect();
+            (
+                nd::concatenate(nd::Axis(0), &sites).map_err(|e| e.to_string())?,
+                nd::concatenate(nd::Axis(0), &values).map_err(|e| e.to_string())?,
+            )
+        }
+    };
+    let locator = NodeLocator::new(points.view());
+    Ok(ValueSites {
+        points,
+        values,
+        locator,
+    })
+}
+
+/// Interpolates values at points by inverse distance weighting of the `k` nearest sites, and
+/// tells whether each point got a value.
+fn inverse_distance(
+    sites: &ValueSites,
+    points: nd::ArrayView2<f64>,
+    k: usize,
+    power: f64,
+) -> (nd::ArrayD<f64>, Vec<bool>) {
+    let mut shape = vec![points.nrows()];
+    shape.extend(&sites.values.shape()[1..]);
+    let mut values = nd::ArrayD::from_elem(shape, f64::NAN);
+    let mut found = vec![false; points.nrows()];
+    for (i, point) in points.outer_iter().enumerate() {
+        let point = point.to_vec();
+        let nearest = sites.locator.k_nearest(&point, k.max(1));
+        let distances: Vec<f64> = nearest
+            .iter()
+            .map(|&s| {
+                let d = &sites.points.row(s) - &nd::aview1(&point);
+                d.dot(&d).sqrt()
+            })
+            .collect();
+        let weights: Vec<f64> = match distances.iter().position(|&d| d == 0.0) {
+            Some(j) => (0..nearest.len())
+                .map(|l| if l == j { 1.0 } else { 0.0 })
+                .collect(),
+            None => distances.iter().map(|d| d.powf(-power)).collect(),
+        };
+        let total: f64 = weights.iter().sum();
+        if nearest.is_empty() || total <= 0.0 {
+            continue;
+        }
+        let mut dst = values.index_axis_mut(nd::Axis(0), i);
+        dst.fill(0.0);
+        for (&s, w) in nearest.iter().zip(weights) {
+            dst.scaled_add(w / total, &sites.values.index_axis(nd::Axis(0), s));
+        }
+        found[i] = true;
+    }
+    (values, found)
+}
+
+/// Values of the shape functions of an element at a point, `None` for poly elements.
+fn shape_functions_at<'a>(element: &impl ElementGeo<'a>, point: &[f64]) -> Option<Vec<f64>> {
+    let xi = element.to_reference(point)?;
+    let (values, _) = shape_functions(element.element_type(), &xi)?;
+    Some(values.to_vec())
+}
+
+/// Samples values on the points of a regular grid, given by the axes of a grid builder.
+///
+/// The grid must have one axis per space dimension. Values have shape `[nx, ny, nz, ...]`, the
+/// value at `[i, j, k]` being sampled at the point of coordinates `(x_i, y_j, z_k)`, and the
+/// mask has shape `[nx, ny, nz]`. A grid of given bounding box and resolution is built with
+/// [`RegularUMeshBuilder::add_uniform_axis`].
+pub fn sample_on_grid(
+    mesh: UMeshView,
+    sampled: Sampled,
+    grid: &RegularUMeshBuilder,
+) -> Result<Samples, String> {
+    trace::span!("sample_on_grid");
+    let axes = grid.axes();
+    let grid_shape: Vec<usize> = axes.iter().map(Vec::len).collect();
+    let n_points: usize = grid_shape.iter().product();
+    let points = nd::Array2::from_shape_fn((n_points, axes.len()), |(p, d)| {
+        // Last axis varies fastest, as the values array in standard layout
+        let stride: usize = grid_shape[d + 1..].iter().product();
+        axes[d][(p / stride) % grid_shape[d]]
+    });
+    let samples = interpolate(mesh, sampled, points.view())?;
+
+    let mut shape = grid_shape.clone();
+    shape.extend(&samples.values.shape()[1..]);
+    Ok(Samples {
+        values: samples.values.into_shape_with_order(shape).unwrap(),
+        mask: samples.mask.into_shape_with_order(grid_shape).unwrap(),
+    })
+}
+
+/// Where to probe a mesh.
+#[derive(Clone, Debug)]
+pub enum Probe<'a> {
+    /// A set of points of shape `[n_points, space_dim]`.
+    Points(nd::ArrayView2<'a, f64>),
+    /// A polyline through the given vertices, of shape `[n_vertices, space_dim]`, probed at the
+    /// given number of points evenly spaced along its length, both ends included.
+    Polyline(nd::ArrayView2<'a, f64>, usize),
+}
+
+/// Values probed along a set of points, computed by [`probe`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeLine {
+    /// The probed points, of shape `[n_points, space_dim]`.
+    pub points: nd::Array2<f64>,
+    /// The distance of each point to the first one, along the broken line through the points.
+    pub arc_length: nd::Array1<f64>,
+    /// The values of each probed field, of shape `[n_points, ...]`, NaN outside of the mesh.
+    pub values: Vec<nd::ArrayD<f64>>,
+    /// Whether each point lies in the mesh.
+    pub mask: nd::Array1<bool>,
+}
+
+/// Probes fields of a mesh at a set of points or along a polyline.
+///
+/// The points are located once for all the fields, whose values are returned in the order of
+/// `fields`. The arc length parameterizes the values for line plots.
+pub fn probe(mesh: UMeshView, probe: Probe, fields: &[Sampled]) -> Result<ProbeLine, String> {
+    trace::span!("probe");
+    let points = match probe {
+        Probe::Points(points) => points.to_owned(),
+        Probe::Polyline(vertices, _) if vertices.nrows() < 2 => {
+            return Err("A polyline needs at least two vertices.".to_owned());
+        }
+        Probe::Polyline(vertices, n_points) => resample_polyline(vertices, n_points),
+    };
+    check_points(&mesh, points.view())?;
+    let located = locate_points(mesh.view(), points.view());
+    let values: Vec<nd::ArrayD<f64>> = fields
+        .iter()
+        .map(|sampled| interpolate_located(&mesh, sampled, points.view(), &located))
+        .collect::<Result<_, _>>()?;
+    let mut arc_length = nd::Array1::zeros(points.nrows());
+    for i in 1..points.nrows() {
+        let step = &points.row(i) - &points.row(i - 1);
+        arc_length[i] = arc_length[i - 1] + step.dot(&step).sqrt();
+    }
+    Ok(ProbeLine {
+        mask: located.iter().map(Option::is_some).collect(),
+        points,
+        arc_length,
+        values,
+    })
+}
+
+/// Points evenly spaced along a polyline, both ends included.
+fn resample_polyline(vertices: nd::ArrayView2<f64>, n_points: usize) -> nd::Array2<f64> {
+    let mut lengths = vec![0.0];
+    for i in 1..vertices.nrows() {
+        let step = &vertices.row(i) - &vertices.row(i - 1);
+        lengths.push(lengths[i - 1] + step.dot(&step).sqrt());
+    }
+    let total = lengths.last().copied().unwrap_or(0.0);
+    let mut points = nd::Array2::zeros((n_points, vertices.ncols()));
+    for (k, mut point) in points.outer_iter_mut().enumerate() {
+        let s = match n_points {
+            1 => 0.0,
+            _ => total * k as f64 / (n_points - 1) as f64,
+        };
+        // Segment [i - 1, i] containing s, the last one for the end point
+        let i = lengths
+            .partition_point(|&l| l < s)
+            .clamp(1, vertices.nrows() - 1);
+        let length = lengths[i] - lengths[i - 1];
+        let t = match length > 0.0 {
+            true => (s - lengths[i - 1]) / length,
+            false => 0.0,
+        };
+        point.assign(&(&vertices.row(i - 1) * (1.0 - t) + &vertices.row(i) * t));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::UMesh;
+    use crate::mesh_examples as me;
+
+    #[test]
+    fn test_locate_points() {
+        let mesh = me::make_imesh_2d(2);
+        let quad = |i| Some(ElementId::new(ElementType::QUAD4, i));
+        let points = nd::arr2(&[[0.2, 0.2], [0.7, 0.2], [0.5, 0.5], [0.2, 0.9], [1.2, 0.5]]);
+        assert_eq!(
+            locate_points(mesh.view(), points.view()),
+            vec![quad(0), quad(1), quad(0), quad(2), None]
+        );
+    }
+
+    #[test]
+    fn test_interpolate_with() {
+        let mut mesh = me::make_imesh_2d(2);
+        let ids = nd::arr1(&[0.0, 1.0, 2.0, 3.0]);
+        mesh.add_field(ElementType::QUAD4, "id", ids.into_dyn().into_shared());
+        let x = mesh.coords().column(0).to_owned().into_dyn();
+        let points = nd::arr2(&[[0.2, 0.2], [1.2, 0.6], [1.3, 1.1]]);
+
+        let methods = [Interpolation::Cell, Interpolation::Nearest];
+        let res = interpolate_with(
+            mesh.view(),
+            Sampled::Nodes(x.view()),
+            points.view(),
+            &methods,
+        )
+        .unwrap();
+        approx::assert_abs_diff_eq!(res.values[[0]], 0.2, epsilon = 1e-12);
+        assert_eq!(res.values[[1]], 1.0);
+        assert_eq!(
+            res.methods,
+            vec![
+                Some(Interpolation::Cell),
+                Some(Interpolation::Nearest),
+                Some(Interpolation::Nearest)
+            ]
+        );
+        let res =
+            interpolate_with(mesh.view(), Sampled::Cells("id"), points.view(), &methods).unwrap();
+        assert_eq!(res.values, nd::arr1(&[0.0, 3.0, 3.0]).into_dyn());
+        let res = interpolate_with(
+            mesh.view(),
+            Sampled::Cells("id"),
+            points.view(),
+            &methods[..1],
+        )
+        .unwrap();
+        assert!(res.values[[1]].is_nan());
+        assert_eq!(res.methods[1], None);
+        assert!(
+            interpolate_with(mesh.view(), Sampled::Nodes(x.view()), points.view(), &[]).is_err()
+        );
+
+        // Scattered values, without cells
+        let cloud =
+            UMesh::new(nd::arr2(&[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]).into_shared());
+        let values = nd::arr1(&[0.0, 1.0, 2.0, 3.0]).into_dyn();
+        let points = nd::arr2(&[[0.5, 0.5], [1.0, 0.0], [0.9, 0.0]]);
+        let idw = [Interpolation::Idw { k: 4, power: 2.0 }];
+        let res = interpolate_with(
+            cloud.view(),
+            Sampled::Nodes(values.view()),
+            points.view(),
+            &idw,
+        )
+        .unwrap();
+        approx::assert_abs_diff_eq!(res.values[[0]], 1.5, epsilon = 1e-12);
+        assert_eq!(res.values[[1]], 1.0);
+        assert!(res.values[[2]] > 0.9 && res.values[[2]] < 1.5);
+        let res = interpolate_with(cloud.view(), Sampled::Cells("id"), points.view(), &idw);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_sample_on_grid() {
+        let mut mesh = me::make_imesh_2d(2);
+        let ids = nd::arr1(&[0.0, 1.0, 2.0, 3.0]);
+        mesh.add_field(ElementType::QUAD4, "id", ids.into_dyn().into_shared());
+        let grid = RegularUMeshBuilder::new()
+            .add_axis(vec![0.2, 0.7, 1.5])
+            .add_axis(vec![0.2, 0.7]);
+
+        let samples = sample_on_grid(mesh.view(), Sampled::Cells("id"), &grid).unwrap();
+        assert_eq!(samples.values.shape(), &[3, 2]);
+        assert_eq!(samples.values[[0, 0]], 0.0);
+        assert_eq!(samples.values[[1, 0]], 1.0);
+        assert_eq!(samples.values[[0, 1]], 2.0);
+        assert_eq!(samples.values[[1, 1]], 3.0);
+        assert!(samples.values[[2, 1]].is_nan());
+        assert_eq!(
+            samples.mask,
+            nd::arr2(&[[true, true], [true, true], [false, false]]).into_dyn()
+        );
+
+        // Linear node values are interpolated exactly
+        let x = mesh.coords().column(0).to_owned().into_dyn();
+        let samples = sample_on_grid(mesh.view(), Sampled::Nodes(x.view()), &grid).unwrap();
+        approx::assert_abs_diff_eq!(samples.values[[0, 1]], 0.2, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(samples.values[[1, 0]], 0.7, epsilon = 1e-12);
+        assert!(sample_on_grid(mesh.view(), Sampled::Cells("none"), &grid).is_err());
+
+        // Cell fields must have the same components in all the blocks
+        let tri = nd::arr2(&[[1, 2, 4]]).into_shared();
+        let values = nd::arr2(&[[0.0, 1.0]]).into_dyn().into_shared();
+        mesh.add_regular_block(
+            ElementType::TRI3,
+            tri,
+            Some([("id".to_owned(), values)].into()),
+        );
+        assert!(sample_on_grid(mesh.view(), Sampled::Cells("id"), &grid).is_err());
+        let cloud = UMesh::new(nd::arr2(&[[0.0, 0.0], [1.0, 1.0]]).into_shared());
+        assert!(sample_on_grid(cloud.view(), Sampled::Cells("id"), &grid).is_err());
+    }
+
+    #[test]
+    fn test_probe() {
+        let mut mesh = me::make_imesh_2d(2);
+        let ids = nd::arr1(&[0.0, 1.0, 2.0, 3.0]);
+        mesh.add_field(ElementType::QUAD4, "id", ids.into_dyn().into_shared());
+        let x = mesh.coords().column(0).to_owned().into_dyn();
+        let fields = [Sampled::Nodes(x.view()), Sampled::Cells("id")];
+
+        let line = nd::arr2(&[[0.0, 0.25], [0.5, 0.25], [1.0, 0.25]]);
+        let probed = probe(mesh.view(), Probe::Polyline(line.view(), 5), &fields).unwrap();
+        assert_eq!(probed.points.nrows(), 5);
+        for (k, &s) in probed.arc_length.iter().enumerate() {
+            approx::assert_abs_diff_eq!(s, 0.25 * k as f64, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(probed.values[0][[k]], 0.25 * k as f64, epsilon = 1e-12);
+        }
+        assert_eq!(
+            probed.values[1],
+            nd::arr1(&[0.0, 0.0, 0.0, 1.0, 1.0]).into_dyn()
+        );
+        assert!(probed.mask.iter().all(|&inside| inside));
+
+        let points = nd::arr2(&[[0.2, 0.2], [0.2, 0.6], [0.2, 1.6]]);
+        let probed = probe(mesh.view(), Probe::Points(points.view()), &fields[1..]).unwrap();
+        approx::assert_abs_diff_eq!(probed.arc_length[2], 1.4, epsilon = 1e-12);
+        assert_eq!(probed.mask, nd::arr1(&[true, true, false]));
+        assert_eq!(probed.values[0][[1]], 2.0);
+        assert!(probed.values[0][[2]].is_nan());
+    }
+}
@@ -1,8 +1,11 @@
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
 use crate::element_traits::ElementGeo;
 use crate::element_traits::is_in as geo;
-use crate::mesh::{ElementIdsSet, UMeshView};
+use crate::mesh::{BlockMask, UMeshView};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum CentroidSelection {
     BBox { min: [f64; 3], max: [f64; 3] }, // Axis aligned BBox
     Rect { min: [f64; 2], max: [f64; 2] }, // Axis aligned BBox
@@ -11,29 +14,25 @@ pub enum CentroidSelection {
 }
 
 impl CentroidSelection {
-    fn in_2d<'a, F0>(f: F0, view: &'a UMeshView<'a>, sel: ElementIdsSet) -> ElementIdsSet
+    fn in_2d<'a, F0>(f: F0, view: &'a UMeshView<'a>, sel: BlockMask) -> BlockMask
     where
         F0: Fn(&[f64; 2]) -> bool + Sync,
     {
-        sel.into_iter()
-            .filter(|&e_id| f(&view.element(e_id).centroid2()))
-            .collect()
+        sel.filter(|e_id| f(&view.element(e_id).centroid2()))
     }
-    fn in_3d<'a, F0>(f: F0, view: &'a UMeshView<'a>, sel: ElementIdsSet) -> ElementIdsSet
+    fn in_3d<'a, F0>(f: F0, view: &'a UMeshView<'a>, sel: BlockMask) -> BlockMask
     where
         F0: Fn(&[f64; 3]) -> bool + Sync,
     {
-        sel.into_iter()
-            .filter(|&e_id| f(&view.element(e_id).centroid3()))
-            .collect()
+        sel.filter(|e_id| f(&view.element(e_id).centroid3()))
     }
 
     pub fn in_sphere<'a>(
         p0: &[f64; 3],
         r: f64,
         view: &'a UMeshView<'a>,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_3d(
             |x| {
                 debug_assert_eq!(x.len(), 3);
@@ -47,8 +46,8 @@ impl CentroidSelection {
         p0: &[f64; 3],
         p1: &[f64; 3],
         view: &'a UMeshView<'a>,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_3d(
             |x| {
                 debug_assert_eq!(x.len(), 3);
@@ -59,12 +58,57 @@ impl CentroidSelection {
         )
     }
 
+    /// Keeps the nodes of `nodes` whose coordinates lie in this selection's shape.
+    pub fn filter_nodes(&self, view: &UMeshView, mut nodes: FxHashSet<usize>) -> FxHashSet<usize> {
+        let coords = view.coords();
+        let coord = |n: usize| coords.row(n).to_slice().unwrap();
+        match self {
+            Self::BBox { min, max } => nodes.retain(|&n| {
+                geo::in_aa_bbox(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 3 components."),
+                    min,
+                    max,
+                )
+            }),
+            Self::Rect { min, max } => nodes.retain(|&n| {
+                geo::in_aa_rectangle(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 2 components."),
+                    min,
+                    max,
+                )
+            }),
+            Self::Sphere { center, r2 } => nodes.retain(|&n| {
+                geo::in_sphere(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 3 components."),
+                    center,
+                    *r2,
+                )
+            }),
+            Self::Circle { center, r2 } => nodes.retain(|&n| {
+                geo::in_circle(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 2 components."),
+                    center,
+                    *r2,
+                )
+            }),
+        }
+        nodes
+    }
+
     pub fn in_circle<'a>(
         p0: &[f64; 2],
         r: f64,
         view: &'a UMeshView<'a>,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_2d(
             |x| {
                 debug_assert_eq!(x.len(), 2);
@@ -79,8 +123,8 @@ impl CentroidSelection {
         p0: &[f64; 2],
         p1: &[f64; 2],
         view: &'a UMeshView<'a>,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_2d(
             |x| {
                 debug_assert_eq!(x.len(), 2);
@@ -108,7 +152,7 @@ mod tests {
         };
         let ids = mesh.select_ids(Selection::CentroidSelection(selection));
         // Quad centroid is at (0.5, 0.5) which is within radius 0.5
-        assert!(ids.len() > 0);
+        assert!(!ids.is_empty());
     }
 
     #[test]
@@ -119,7 +163,7 @@ mod tests {
             r2: 0.5,
         };
         let ids = mesh.select_ids(Selection::CentroidSelection(selection));
-        assert!(ids.len() > 0);
+        assert!(!ids.is_empty());
     }
 
     #[test]
@@ -130,7 +174,7 @@ mod tests {
             max: [1.0, 1.0],
         });
         let ids = mesh.select_ids(selection);
-        assert!(ids.len() > 0);
+        assert!(!ids.is_empty());
     }
 
     #[test]
@@ -141,6 +185,6 @@ mod tests {
             max: [1.0, 1.0],
         });
         let ids = mesh.select_ids(selection);
-        assert!(ids.len() > 0);
+        assert!(!ids.is_empty());
     }
 }
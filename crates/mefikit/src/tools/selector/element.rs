@@ -1,8 +1,9 @@
 use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
 
-use crate::mesh::{Dimension, ElementIds, ElementIdsSet, ElementType};
+use crate::mesh::{BlockMask, Dimension, ElementIds, ElementType};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ElementSelection {
     Types(Vec<ElementType>),
     InIds(ElementIds),
@@ -10,31 +11,18 @@ pub enum ElementSelection {
 }
 
 impl ElementSelection {
-    pub fn select_types(types: &[ElementType], mut sel: ElementIdsSet) -> ElementIdsSet {
-        let sel_types: Vec<_> = sel.keys().collect();
+    pub fn select_types(types: &[ElementType], mut sel: BlockMask) -> BlockMask {
         let types_to_match: FxHashSet<_> = types.iter().collect();
-        for k in sel_types {
-            if !types_to_match.contains(&k) {
-                sel.remove_type(k);
-            }
-        }
+        sel.retain_types(|k| types_to_match.contains(&k));
         sel
     }
-    pub fn select_dimensions(dims: &[Dimension], mut sel: ElementIdsSet) -> ElementIdsSet {
-        let mut key_toremove = Vec::new();
-        for k in sel.keys() {
-            if !dims.contains(&k.dimension()) {
-                key_toremove.push(k);
-            }
-        }
-        for k in key_toremove {
-            sel.remove_type(k);
-        }
+    pub fn select_dimensions(dims: &[Dimension], mut sel: BlockMask) -> BlockMask {
+        sel.retain_types(|k| dims.contains(&k.dimension()));
         sel
     }
-    pub fn select_ids(ids: ElementIds, mut sel: ElementIdsSet) -> ElementIdsSet {
-        let ids: ElementIdsSet = ids.into();
-        sel.intersection(&ids);
+    pub fn select_ids(ids: &ElementIds, mut sel: BlockMask) -> BlockMask {
+        let ids = BlockMask::from_ids(ids, sel.sizes());
+        sel &= &ids;
         sel
     }
 }
@@ -44,14 +32,13 @@ mod tests {
     use super::*;
     use crate::mesh::ElementType;
 
+    fn sizes() -> [(ElementType, usize); 2] {
+        [(ElementType::QUAD4, 2), (ElementType::SEG2, 2)]
+    }
+
     #[test]
     fn test_select_types() {
-        let mut ids = ElementIds::new();
-        ids.add(ElementType::QUAD4, 0);
-        ids.add(ElementType::SEG2, 0);
-        ids.add(ElementType::SEG2, 1);
-
-        let sel: ElementIdsSet = ids.into();
+        let sel = BlockMask::full(sizes());
         let result = ElementSelection::select_types(&[ElementType::QUAD4], sel);
         assert!(result.contains_type(ElementType::QUAD4));
         assert!(!result.contains_type(ElementType::SEG2));
@@ -59,11 +46,7 @@ mod tests {
 
     #[test]
     fn test_select_dimensions() {
-        let mut ids = ElementIds::new();
-        ids.add(ElementType::QUAD4, 0);
-        ids.add(ElementType::SEG2, 0);
-
-        let sel: ElementIdsSet = ids.into();
+        let sel = BlockMask::full(sizes());
         let result = ElementSelection::select_dimensions(&[Dimension::D1], sel);
         assert!(result.contains_type(ElementType::SEG2));
         assert!(!result.contains_type(ElementType::QUAD4));
@@ -71,16 +54,12 @@ mod tests {
 
     #[test]
     fn test_select_ids() {
-        let mut ids = ElementIds::new();
-        ids.add(ElementType::QUAD4, 0);
-        ids.add(ElementType::QUAD4, 1);
-        ids.add(ElementType::SEG2, 0);
+        let sel = BlockMask::full(sizes());
 
         let mut filter_ids = ElementIds::new();
         filter_ids.add(ElementType::QUAD4, 0);
 
-        let sel: ElementIdsSet = ids.into();
-        let result = ElementSelection::select_ids(filter_ids, sel);
+        let result = ElementSelection::select_ids(&filter_ids, sel);
         assert_eq!(result.len(), 1);
     }
 }
@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use crate::tools::fieldexpr::FieldExpr;
 use std::sync::Arc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum FieldSelection {
     Gt(Arc<FieldExpr>, Arc<FieldExpr>),
     Geq(Arc<FieldExpr>, Arc<FieldExpr>),
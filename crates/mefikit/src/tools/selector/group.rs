@@ -1,6 +1,8 @@
-use crate::mesh::{ElementIdsSet, ElementLike, UMeshView};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+use crate::mesh::{BlockMask, ElementLike, UMeshView};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum GroupSelection {
     IncludeGroup(String),
     ExcludeGroup(String),
@@ -9,24 +11,16 @@ pub enum GroupSelection {
 }
 
 impl GroupSelection {
-    pub fn include_group(group: &str, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
-        sel.into_iter()
-            .filter(|&eid| view.element(eid).in_group(group))
-            .collect()
+    pub fn include_group(group: &str, view: &UMeshView, sel: BlockMask) -> BlockMask {
+        sel.filter(|eid| view.element(eid).in_group(group))
     }
-    pub fn exclude_group(group: &str, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
-        sel.into_iter()
-            .filter(|&eid| !view.element(eid).in_group(group))
-            .collect()
+    pub fn exclude_group(group: &str, view: &UMeshView, sel: BlockMask) -> BlockMask {
+        sel.filter(|eid| !view.element(eid).in_group(group))
     }
-    pub fn include_family(family: usize, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
-        sel.into_iter()
-            .filter(|&eid| *view.element(eid).family == family)
-            .collect()
+    pub fn include_family(family: usize, view: &UMeshView, sel: BlockMask) -> BlockMask {
+        sel.filter(|eid| *view.element(eid).family == family)
     }
-    pub fn exclude_family(family: usize, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
-        sel.into_iter()
-            .filter(|&eid| *view.element(eid).family != family)
-            .collect()
+    pub fn exclude_family(family: usize, view: &UMeshView, sel: BlockMask) -> BlockMask {
+        sel.filter(|eid| *view.element(eid).family != family)
     }
 }
@@ -8,7 +8,11 @@ mod element;
 mod field;
 mod group;
 mod node;
+mod parse;
 pub mod selection;
+mod selection_set;
 
+pub use parse::SelectionParseError;
 pub use selection as sel;
 pub use selection::{MeshSelect, Selection};
+pub use selection_set::SelectionSet;
@@ -1,12 +1,13 @@
 use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
 
 use crate::element_traits::ElementGeo;
 use crate::element_traits::is_in as geo;
-use crate::mesh::ElementIdsSet;
+use crate::mesh::BlockMask;
 use crate::mesh::ElementLike;
 use crate::mesh::UMeshView;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum NodeSelection {
     BBox {
         all: bool,
@@ -35,24 +36,20 @@ pub enum NodeSelection {
 }
 
 impl NodeSelection {
-    fn all_in<F0>(f: F0, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet
+    fn all_in<F0>(f: F0, view: &UMeshView, sel: BlockMask) -> BlockMask
     where
         F0: Fn(&[f64]) -> bool + Sync,
     {
-        sel.into_iter()
-            .filter(|&e_id| view.element(e_id).coords().all(&f))
-            .collect()
+        sel.filter(|e_id| view.element(e_id).coords().all(&f))
     }
 
-    fn any_in<F0>(f: F0, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet
+    fn any_in<F0>(f: F0, view: &UMeshView, sel: BlockMask) -> BlockMask
     where
         F0: Fn(&[f64]) -> bool + Sync,
     {
-        sel.into_iter()
-            .filter(|&eid| view.element(eid).coords().any(&f))
-            .collect()
+        sel.filter(|eid| view.element(eid).coords().any(&f))
     }
-    fn in_shape<F0>(all: bool, f: F0, view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet
+    fn in_shape<F0>(all: bool, f: F0, view: &UMeshView, sel: BlockMask) -> BlockMask
     where
         F0: Fn(&[f64]) -> bool + Sync,
     {
@@ -67,8 +64,8 @@ impl NodeSelection {
         p0: &[f64; 3],
         r: f64,
         view: &UMeshView,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_shape(
             all,
             |x| {
@@ -89,8 +86,8 @@ impl NodeSelection {
         p0: &[f64; 3],
         p1: &[f64; 3],
         view: &UMeshView,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_shape(
             all,
             |x| {
@@ -110,8 +107,8 @@ impl NodeSelection {
         p0: &[f64; 2],
         r: f64,
         view: &UMeshView,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_shape(
             all,
             |x| {
@@ -132,8 +129,8 @@ impl NodeSelection {
         p0: &[f64; 2],
         p1: &[f64; 2],
         view: &UMeshView,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+        sel: BlockMask,
+    ) -> BlockMask {
         Self::in_shape(
             all,
             |x| {
@@ -148,49 +145,87 @@ impl NodeSelection {
             sel,
         )
     }
-    fn any_id_in(nodes_ids: &[usize], view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
+    fn any_id_in(nodes_ids: &[usize], view: &UMeshView, sel: BlockMask) -> BlockMask {
         if nodes_ids.len() < 50 {
-            sel.into_iter()
-                .filter(|&e_id| {
-                    nodes_ids
-                        .iter()
-                        .any(|n| view.element(e_id).connectivity().contains(n))
-                })
-                .collect()
+            sel.filter(|e_id| {
+                nodes_ids
+                    .iter()
+                    .any(|n| view.element(e_id).connectivity().contains(n))
+            })
         } else {
             let mut nodes_ids: Vec<usize> = nodes_ids.to_vec();
             nodes_ids.sort_unstable();
 
-            sel.into_iter()
-                .filter(|&e_id| {
-                    view.element(e_id)
-                        .connectivity()
-                        .iter()
-                        .any(|n| nodes_ids.binary_search(n).is_ok())
-                })
-                .collect()
+            sel.filter(|e_id| {
+                view.element(e_id)
+                    .connectivity()
+                    .iter()
+                    .any(|n| nodes_ids.binary_search(n).is_ok())
+            })
         }
     }
 
-    fn all_id_in(nodes_ids: &[usize], view: &UMeshView, sel: ElementIdsSet) -> ElementIdsSet {
+    fn all_id_in(nodes_ids: &[usize], view: &UMeshView, sel: BlockMask) -> BlockMask {
         let nodes_ids: FxHashSet<usize> = nodes_ids.iter().cloned().collect();
 
-        sel.into_iter()
-            .filter(|&e_id| {
-                view.element(e_id)
-                    .connectivity()
-                    .iter()
-                    .all(|n| nodes_ids.contains(n))
-            })
-            .collect()
+        sel.filter(|e_id| {
+            view.element(e_id)
+                .connectivity()
+                .iter()
+                .all(|n| nodes_ids.contains(n))
+        })
     }
 
-    pub fn id_in(
-        all: bool,
-        nodes_ids: &[usize],
-        view: &UMeshView,
-        sel: ElementIdsSet,
-    ) -> ElementIdsSet {
+    /// Keeps the nodes of `nodes` matching this selection, regardless of the `all` flag.
+    pub fn filter_nodes(&self, view: &UMeshView, mut nodes: FxHashSet<usize>) -> FxHashSet<usize> {
+        let coords = view.coords();
+        let coord = |n: usize| coords.row(n).to_slice().unwrap();
+        match self {
+            Self::BBox { min, max, .. } => nodes.retain(|&n| {
+                geo::in_aa_bbox(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 3 components."),
+                    min,
+                    max,
+                )
+            }),
+            Self::Rect { min, max, .. } => nodes.retain(|&n| {
+                geo::in_aa_rectangle(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 2 components."),
+                    min,
+                    max,
+                )
+            }),
+            Self::Sphere { center, r, .. } => nodes.retain(|&n| {
+                geo::in_sphere(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 3 components."),
+                    center,
+                    *r,
+                )
+            }),
+            Self::Circle { center, r, .. } => nodes.retain(|&n| {
+                geo::in_circle(
+                    coord(n)
+                        .try_into()
+                        .expect("Coords should have 2 components."),
+                    center,
+                    *r,
+                )
+            }),
+            Self::Ids { ids, .. } => {
+                let ids: FxHashSet<usize> = ids.iter().cloned().collect();
+                nodes.retain(|n| ids.contains(n));
+            }
+        }
+        nodes
+    }
+
+    pub fn id_in(all: bool, nodes_ids: &[usize], view: &UMeshView, sel: BlockMask) -> BlockMask {
         if all {
             Self::all_id_in(nodes_ids, view, sel)
         } else {
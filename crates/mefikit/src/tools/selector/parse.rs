@@ -0,0 +1,641 @@
+//! Text syntax for selections, see [`Selection::parse`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::mesh::{Dimension, ElementType};
+
+use super::selection::{self as sel, Selection};
+
+/// An error raised when a selection string cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectionParseError {
+    /// Byte offset of the offending token in the input.
+    pub position: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for SelectionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectionParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    And,
+    Or,
+    Xor,
+    Not,
+    Cmp(Cmp),
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Neq,
+    Lt,
+    Leq,
+    Gt,
+    Geq,
+}
+
+impl fmt::Display for Cmp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Cmp::Eq => "==",
+            Cmp::Neq => "!=",
+            Cmp::Lt => "<",
+            Cmp::Leq => "<=",
+            Cmp::Gt => ">",
+            Cmp::Geq => ">=",
+        };
+        f.write_str(op)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "'{s}'"),
+            Token::Number(x) => write!(f, "'{x}'"),
+            Token::Str(s) => write!(f, "string '{s}'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Xor => write!(f, "'^'"),
+            Token::Not => write!(f, "'!'"),
+            Token::Cmp(c) => write!(f, "'{c}'"),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+fn error<T>(position: usize, message: impl Into<String>) -> Result<T, SelectionParseError> {
+    Err(SelectionParseError {
+        position,
+        message: message.into(),
+    })
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, SelectionParseError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ',' => Token::Comma,
+            '^' => Token::Xor,
+            '&' if next == Some('&') => {
+                i += 1;
+                Token::And
+            }
+            '|' if next == Some('|') => {
+                i += 1;
+                Token::Or
+            }
+            '=' if next == Some('=') => {
+                i += 1;
+                Token::Cmp(Cmp::Eq)
+            }
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::Cmp(Cmp::Neq)
+            }
+            '!' => Token::Not,
+            '<' | '>' => {
+                let or_equal = next == Some('=');
+                if or_equal {
+                    i += 1;
+                }
+                Token::Cmp(match (c, or_equal) {
+                    ('<', false) => Cmp::Lt,
+                    ('<', true) => Cmp::Leq,
+                    ('>', false) => Cmp::Gt,
+                    _ => Cmp::Geq,
+                })
+            }
+            '\'' | '"' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end].1 != c {
+                    end += 1;
+                }
+                if end == chars.len() {
+                    return error(pos, "Unterminated string");
+                }
+                i = end;
+                Token::Str(chars[start..end].iter().map(|&(_, c)| c).collect())
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' => {
+                let mut end = i + 1;
+                while end < chars.len() {
+                    let (_, d) = chars[end];
+                    let exponent_sign =
+                        (d == '-' || d == '+') && matches!(chars[end - 1].1, 'e' | 'E');
+                    if d.is_ascii_digit() || d == '.' || d == 'e' || d == 'E' || exponent_sign {
+                        end += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let text: String = chars[i..end].iter().map(|&(_, c)| c).collect();
+                i = end - 1;
+                match text.parse() {
+                    Ok(x) => Token::Number(x),
+                    Err(_) => return error(pos, format!("Invalid number '{text}'")),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i + 1;
+                while end < chars.len() && (chars[end].1.is_alphanumeric() || chars[end].1 == '_') {
+                    end += 1;
+                }
+                let text: String = chars[i..end].iter().map(|&(_, c)| c).collect();
+                i = end - 1;
+                Token::Ident(text)
+            }
+            c => return error(pos, format!("Unexpected character '{c}'")),
+        };
+        tokens.push((token, pos));
+        i += 1;
+    }
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+fn element_type(name: &str) -> Option<ElementType> {
+    use ElementType::*;
+    Some(match name.to_uppercase().as_str() {
+        "VERTEX" => VERTEX,
+        "SEG2" => SEG2,
+        "SEG3" => SEG3,
+        "SEG4" => SEG4,
+        "SPLINE" => SPLINE,
+        "TRI3" => TRI3,
+        "TRI6" => TRI6,
+        "TRI7" => TRI7,
+        "QUAD4" => QUAD4,
+        "QUAD8" => QUAD8,
+        "QUAD9" => QUAD9,
+        "PGON" => PGON,
+        "TET4" => TET4,
+        "TET10" => TET10,
+        "HEX8" => HEX8,
+        "HEX21" => HEX21,
+        "PHED" => PHED,
+        _ => return None,
+    })
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    current: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.current].1
+    }
+
+    fn advance(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.current].clone();
+        if token.0 != Token::End {
+            self.current += 1;
+        }
+        token
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, SelectionParseError> {
+        error(
+            self.position(),
+            format!("Expected {expected}, found {}", self.peek()),
+        )
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), SelectionParseError> {
+        if *self.peek() == token {
+            self.advance();
+            Ok(())
+        } else {
+            self.unexpected(&token.to_string())
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SelectionParseError> {
+        match self.peek() {
+            Token::Ident(s) if s == keyword => {
+                self.advance();
+                Ok(())
+            }
+            _ => self.unexpected(&format!("'{keyword}'")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, SelectionParseError> {
+        match self.peek() {
+            &Token::Number(x) => {
+                self.advance();
+                Ok(x)
+            }
+            _ => self.unexpected("a number"),
+        }
+    }
+
+    fn integer(&mut self) -> Result<usize, SelectionParseError> {
+        let position = self.position();
+        let x = self.number()?;
+        if x < 0.0 || x.fract() != 0.0 {
+            return error(
+                position,
+                format!("Expected a non negative integer, found {x}"),
+            );
+        }
+        Ok(x as usize)
+    }
+
+    fn string(&mut self) -> Result<String, SelectionParseError> {
+        match self.peek() {
+            Token::Str(s) => {
+                let s = s.clone();
+                self.advance();
+                Ok(s)
+            }
+            _ => self.unexpected("a quoted string"),
+        }
+    }
+
+    /// Parses a comma separated list of items between `open` and `close`.
+    fn list<T, F>(
+        &mut self,
+        open: Token,
+        close: Token,
+        mut item: F,
+    ) -> Result<Vec<T>, SelectionParseError>
+    where
+        F: FnMut(&mut Self) -> Result<T, SelectionParseError>,
+    {
+        self.expect(open)?;
+        let mut items = vec![item(self)?];
+        while *self.peek() == Token::Comma {
+            self.advance();
+            items.push(item(self)?);
+        }
+        self.expect(close)?;
+        Ok(items)
+    }
+
+    fn point<const N: usize>(&mut self) -> Result<[f64; N], SelectionParseError> {
+        let position = self.position();
+        let coords = self.list(Token::LParen, Token::RParen, Self::number)?;
+        coords.try_into().or_else(|c: Vec<f64>| {
+            error(
+                position,
+                format!("Expected a point with {N} coordinates, found {}", c.len()),
+            )
+        })
+    }
+
+    fn expr(&mut self) -> Result<Selection, SelectionParseError> {
+        let mut left = self.xor()?;
+        while *self.peek() == Token::Or {
+            self.advance();
+            left = left | self.xor()?;
+        }
+        Ok(left)
+    }
+
+    fn xor(&mut self) -> Result<Selection, SelectionParseError> {
+        let mut left = self.and()?;
+        while *self.peek() == Token::Xor {
+            self.advance();
+            left = left ^ self.and()?;
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Selection, SelectionParseError> {
+        let mut left = self.unary()?;
+        while *self.peek() == Token::And {
+            self.advance();
+            left = left & self.unary()?;
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Selection, SelectionParseError> {
+        match self.peek() {
+            Token::Not => {
+                self.advance();
+                Ok(!self.unary()?)
+            }
+            Token::LParen => {
+                self.advance();
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Selection, SelectionParseError> {
+        let (token, position) = self.advance();
+        let Token::Ident(name) = token else {
+            return error(position, format!("Expected a selection, found {token}"));
+        };
+        match name.as_str() {
+            "dim" => {
+                let dims = self.in_or_eq(Self::dimension)?;
+                Ok(sel::dimensions(dims))
+            }
+            "type" => {
+                let types = self.in_or_eq(Self::element_type)?;
+                Ok(sel::types(types))
+            }
+            "group" => {
+                self.expect(Token::LParen)?;
+                let group = self.string()?;
+                self.expect(Token::RParen)?;
+                Ok(sel::group(&group))
+            }
+            "family" => {
+                self.expect(Token::LParen)?;
+                let family = self.integer()?;
+                self.expect(Token::RParen)?;
+                Ok(sel::family(family))
+            }
+            "field" => {
+                self.expect(Token::LParen)?;
+                let field = self.string()?;
+                self.expect(Token::RParen)?;
+                let cmp = match self.advance() {
+                    (Token::Cmp(cmp), _) => cmp,
+                    (token, position) => {
+                        return error(
+                            position,
+                            format!("Expected a comparison operator, found {token}"),
+                        );
+                    }
+                };
+                let value = self.number()?;
+                Ok(match cmp {
+                    Cmp::Eq => sel::eq(&field, value),
+                    Cmp::Neq => sel::neq(&field, value),
+                    Cmp::Lt => sel::lt(&field, value),
+                    Cmp::Leq => sel::leq(&field, value),
+                    Cmp::Gt => sel::gt(&field, value),
+                    Cmp::Geq => sel::geq(&field, value),
+                })
+            }
+            "centroid" => {
+                self.expect_keyword("in")?;
+                self.shape(None)
+            }
+            "all" | "any" => {
+                self.expect_keyword("nodes")?;
+                self.expect_keyword("in")?;
+                self.shape(Some(name == "all"))
+            }
+            "nodes" => {
+                self.expect_keyword("in")?;
+                self.shape(Some(false))
+            }
+            _ => error(position, format!("Unknown selection '{name}'")),
+        }
+    }
+
+    /// Parses `== item` or `in [item, ...]`.
+    fn in_or_eq<T, F>(&mut self, mut item: F) -> Result<Vec<T>, SelectionParseError>
+    where
+        F: FnMut(&mut Self) -> Result<T, SelectionParseError>,
+    {
+        match self.peek() {
+            Token::Cmp(Cmp::Eq) => {
+                self.advance();
+                Ok(vec![item(self)?])
+            }
+            Token::Ident(s) if s == "in" => {
+                self.advance();
+                self.list(Token::LBracket, Token::RBracket, item)
+            }
+            _ => self.unexpected("'==' or 'in'"),
+        }
+    }
+
+    fn dimension(&mut self) -> Result<Dimension, SelectionParseError> {
+        let position = self.position();
+        let dim = self.integer()?;
+        match u8::try_from(dim).map(Dimension::try_from) {
+            Ok(Ok(dim)) => Ok(dim),
+            _ => error(position, format!("Invalid dimension {dim}")),
+        }
+    }
+
+    fn element_type(&mut self) -> Result<ElementType, SelectionParseError> {
+        let (token, position) = self.advance();
+        match &token {
+            Token::Ident(name) => match element_type(name) {
+                Some(et) => Ok(et),
+                None => error(position, format!("Unknown element type '{name}'")),
+            },
+            _ => error(position, format!("Expected an element type, found {token}")),
+        }
+    }
+
+    /// Parses a shape, for centroids (`nodes == None`) or nodes (`nodes == Some(all)`).
+    fn shape(&mut self, nodes: Option<bool>) -> Result<Selection, SelectionParseError> {
+        let (token, position) = self.advance();
+        let Token::Ident(name) = token else {
+            return error(position, format!("Expected a shape, found {token}"));
+        };
+        match name.as_str() {
+            "bbox" => {
+                self.expect(Token::LParen)?;
+                let min = self.point::<3>()?;
+                self.expect(Token::Comma)?;
+                let max = self.point::<3>()?;
+                self.expect(Token::RParen)?;
+                Ok(match nodes {
+                    Some(all) => sel::nbbox(min, max, all),
+                    None => sel::bbox(min, max),
+                })
+            }
+            "rect" => {
+                self.expect(Token::LParen)?;
+                let min = self.point::<2>()?;
+                self.expect(Token::Comma)?;
+                let max = self.point::<2>()?;
+                self.expect(Token::RParen)?;
+                Ok(match nodes {
+                    Some(all) => sel::nrect(min, max, all),
+                    None => sel::rect(min, max),
+                })
+            }
+            "sphere" => {
+                self.expect(Token::LParen)?;
+                let center = self.point::<3>()?;
+                self.expect(Token::Comma)?;
+                let r = self.number()?;
+                self.expect(Token::RParen)?;
+                Ok(match nodes {
+                    Some(all) => sel::nsphere(center, r, all),
+                    None => sel::sphere(center, r),
+                })
+            }
+            "circle" => {
+                self.expect(Token::LParen)?;
+                let center = self.point::<2>()?;
+                self.expect(Token::Comma)?;
+                let r = self.number()?;
+                self.expect(Token::RParen)?;
+                Ok(match nodes {
+                    Some(all) => sel::ncircle(center, r, all),
+                    None => sel::circle(center, r),
+                })
+            }
+            "ids" => {
+                let Some(all) = nodes else {
+                    return error(position, "Element ids cannot be selected by centroid");
+                };
+                let ids = self.list(Token::LParen, Token::RParen, Self::integer)?;
+                Ok(sel::nids(ids, all))
+            }
+            _ => error(position, format!("Unknown shape '{name}'")),
+        }
+    }
+}
+
+impl Selection {
+    /// Parses a selection from its text form.
+    ///
+    /// This lets selections come from configuration files or the Python and command line layers,
+    /// for example:
+    ///
+    /// ```text
+    /// dim == 2 && group('wall') && centroid in bbox((0, 0, 0), (1, 1, 1))
+    /// ```
+    ///
+    /// Grammar, from lowest to highest precedence:
+    ///
+    /// ```text
+    /// expr  := xor ('||' xor)*
+    /// xor   := and ('^' and)*
+    /// and   := unary ('&&' unary)*
+    /// unary := '!' unary | '(' expr ')' | atom
+    /// atom  := 'dim' '==' INT | 'dim' 'in' '[' INT (',' INT)* ']'
+    ///        | 'type' '==' TYPE | 'type' 'in' '[' TYPE (',' TYPE)* ']'
+    ///        | 'group' '(' STRING ')' | 'family' '(' INT ')'
+    ///        | 'field' '(' STRING ')' CMP NUMBER
+    ///        | 'centroid' 'in' shape
+    ///        | ['all' | 'any'] 'nodes' 'in' (shape | 'ids' '(' INT (',' INT)* ')')
+    /// shape := 'bbox' '(' point3 ',' point3 ')' | 'rect' '(' point2 ',' point2 ')'
+    ///        | 'sphere' '(' point3 ',' NUMBER ')' | 'circle' '(' point2 ',' NUMBER ')'
+    /// CMP   := '==' | '!=' | '<' | '<=' | '>' | '>='
+    /// ```
+    ///
+    /// Sphere and circle take a center and a radius. Node selections default to `any`.
+    ///
+    /// On failure, the returned [`SelectionParseError`] holds the byte offset of the offending
+    /// token.
+    pub fn parse(input: &str) -> Result<Self, SelectionParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            current: 0,
+        };
+        let selection = parser.expr()?;
+        match parser.peek() {
+            Token::End => Ok(selection),
+            _ => parser.unexpected("end of input"),
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = SelectionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementIds;
+    use crate::mesh_examples as me;
+    use crate::tools::MeshSelect;
+
+    #[test]
+    fn test_parse_and_select() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0, 1, 2]);
+        mesh.set_group("wall", &ids);
+
+        let selection =
+            Selection::parse("dim==2 && group('wall') && centroid in rect((0,0),(1, 0.5))")
+                .unwrap();
+        let eids = mesh.select_ids(selection);
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![0, 1]));
+
+        let selection: Selection = "!(type in [QUAD4, TRI3]) || all nodes in circle((0, 0), 0.8)"
+            .parse()
+            .unwrap();
+        let eids = mesh.select_ids(selection);
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![0]));
+
+        let selection = Selection::parse("family(0) ^ nodes in ids(0, 8)").unwrap();
+        let eids = mesh.select_ids(selection);
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![0]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Selection::parse("dim == 2 && group(wall)").unwrap_err();
+        assert_eq!(err.position, 18);
+        assert!(err.message.contains("quoted string"));
+
+        let err = Selection::parse("centroid in bbox((0, 0), (1, 1, 1))").unwrap_err();
+        assert_eq!(err.position, 17);
+
+        let err = Selection::parse("type == QUAD5").unwrap_err();
+        assert_eq!(err.message, "Unknown element type 'QUAD5'");
+
+        let err = Selection::parse("dim == 2 dim == 3").unwrap_err();
+        assert_eq!(err.position, 9);
+
+        assert!(Selection::parse("field('T') > 1e-3 && (dim == 3").is_err());
+        assert!(Selection::parse("group('a").is_err());
+    }
+}
@@ -2,10 +2,17 @@
 
 use std::ops::{BitAnd, BitOr, BitXor, Not, Sub};
 use std::sync::Arc;
+#[cfg(not(any(feature = "rayon", target_arch = "wasm32")))]
 use std::thread;
 
-use crate::mesh::{Dimension, ElementIds, ElementIdsSet, ElementType, UMesh, UMeshView};
-use crate::tools::fieldexpr::Evaluable;
+use ndarray as nd;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::{
+    BlockMask, Dimension, ElementIds, ElementLike, ElementType, UMesh, UMeshBase, UMeshView,
+};
+use crate::tools::fieldexpr::{Evaluable, arr, field};
 
 use super::centroid::CentroidSelection;
 use super::element::ElementSelection;
@@ -17,15 +24,15 @@ pub use super::field::Comparable;
 
 /// Trait for selection objects that can filter element IDs.
 pub trait Select {
-    /// Applies the selection to the given mesh view, filtering the element ID set.
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids: ElementIdsSet) -> ElementIdsSet;
+    /// Applies the selection to the given mesh view, filtering the selected elements mask.
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids: BlockMask) -> BlockMask;
 }
 
 /// A selection expression for querying mesh elements.
 ///
 /// Selections can be combined using boolean operators (AND, OR, XOR, NOT)
 /// to build complex queries.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Selection {
     /// Selection based on element type or dimension.
     ElementSelection(ElementSelection),
@@ -44,7 +51,7 @@ pub enum Selection {
 }
 
 /// Boolean operators for combining selections.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum BooleanOp {
     /// Logical AND (intersection).
     And,
@@ -57,7 +64,7 @@ pub enum BooleanOp {
 }
 
 /// A binary boolean expression combining two selections.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BinarayExpr {
     pub operator: BooleanOp,
     pub left: Arc<Selection>,
@@ -65,7 +72,7 @@ pub struct BinarayExpr {
 }
 
 /// A negation expression wrapping a selection.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NotExpr(pub Arc<Selection>);
 
 impl Selection {
@@ -250,8 +257,58 @@ pub fn ids(eids: ElementIds) -> Selection {
     Selection::ElementSelection(ElementSelection::InIds(eids))
 }
 
+/// Creates a selection for elements belonging to a group.
+pub fn group(name: &str) -> Selection {
+    Selection::GroupSelection(GroupSelection::IncludeGroup(name.to_owned()))
+}
+
+/// Creates a selection for elements not belonging to a group.
+pub fn not_group(name: &str) -> Selection {
+    Selection::GroupSelection(GroupSelection::ExcludeGroup(name.to_owned()))
+}
+
+/// Creates a selection for elements of a family.
+pub fn family(id: usize) -> Selection {
+    Selection::GroupSelection(GroupSelection::IncludeFamily(id))
+}
+
+/// Creates a selection for elements not in a family.
+pub fn not_family(id: usize) -> Selection {
+    Selection::GroupSelection(GroupSelection::ExcludeFamily(id))
+}
+
+/// Creates a selection for elements where a field is greater than a value.
+pub fn gt(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(field(name).gt(arr(nd::arr0(value))))
+}
+
+/// Creates a selection for elements where a field is greater than or equal to a value.
+pub fn geq(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(field(name).geq(arr(nd::arr0(value))))
+}
+
+/// Creates a selection for elements where a field is lower than a value.
+pub fn lt(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(field(name).lt(arr(nd::arr0(value))))
+}
+
+/// Creates a selection for elements where a field is lower than or equal to a value.
+pub fn leq(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(field(name).leq(arr(nd::arr0(value))))
+}
+
+/// Creates a selection for elements where a field equals a value.
+pub fn eq(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(Comparable::eq(field(name), arr(nd::arr0(value))))
+}
+
+/// Creates a selection for elements where a field differs from a value.
+pub fn neq(name: &str, value: f64) -> Selection {
+    Selection::FieldSelection(field(name).neq(arr(nd::arr0(value))))
+}
+
 impl Select for Selection {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self {
             Self::ElementSelection(elemt_expr) => elemt_expr.select(view, eids_in),
             Self::NodeSelection(nodes_expr) => nodes_expr.select(view, eids_in),
@@ -323,17 +380,17 @@ impl Not for Selection {
 // Leaf operations
 
 impl Select for ElementSelection {
-    fn select<'a>(&'a self, _view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, _view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self {
             Self::Types(types) => Self::select_types(types.as_slice(), eids_in),
             Self::Dimensions(dims) => Self::select_dimensions(dims.as_slice(), eids_in),
-            Self::InIds(ids) => Self::select_ids(ids.clone(), eids_in),
+            Self::InIds(ids) => Self::select_ids(ids, eids_in),
         }
     }
 }
 
 impl Select for NodeSelection {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self {
             Self::BBox { all, min, max } => Self::in_bbox(*all, min, max, view, eids_in),
             Self::Rect { all, min, max } => Self::in_rectangle(*all, min, max, view, eids_in),
@@ -345,7 +402,7 @@ impl Select for NodeSelection {
 }
 
 impl Select for GroupSelection {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self {
             Self::IncludeGroup(name) => Self::include_group(name, view, eids_in),
             Self::ExcludeGroup(name) => Self::exclude_group(name, view, eids_in),
@@ -356,7 +413,7 @@ impl Select for GroupSelection {
 }
 
 impl Select for FieldSelection {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, mut eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, mut eids_in: BlockMask) -> BlockMask {
         let eids = match self {
             Self::Gt(expr1, expr2) => {
                 let f1 = expr1.evaluate(view, None);
@@ -389,67 +446,102 @@ impl Select for FieldSelection {
                 f1.neq(f2)
             }
         };
-        eids_in.intersection(&eids.into());
+        eids_in &= &BlockMask::from_ids(&eids, eids_in.sizes());
         eids_in
     }
 }
 
 impl Select for NotExpr {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, mut eids_in: ElementIdsSet) -> ElementIdsSet {
-        let all_ids: ElementIdsSet = ElementIdsSet(
-            view.blocks()
-                .map(|(k, v)| (*k, (0..v.len()).collect()))
-                .collect(),
-        );
-        let not_sel = self.0.select(view, all_ids);
-        // let mut not_sel = all_ids;
-        // not_sel.difference(&sel);
-        // sel0.intersection(&not_sel);
-        eids_in.difference(&not_sel);
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, mut eids_in: BlockMask) -> BlockMask {
+        if eids_in.is_empty() {
+            return eids_in;
+        }
+        // Selections only filter their input, so the complement can be taken against the input
+        // instead of the full per-block index.
+        let not_sel = self.0.select(view, eids_in.clone());
+        eids_in -= &not_sel;
         eids_in
     }
 }
 
 impl Select for BinarayExpr {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self.operator {
             BooleanOp::And => {
-                if self.left.weight() < self.right.weight() {
-                    let selection = self.left.select(view, eids_in);
-                    self.right.select(view, selection)
+                let (first, second) = if self.left.weight() < self.right.weight() {
+                    (&self.left, &self.right)
                 } else {
-                    let selection = self.right.select(view, eids_in);
-                    self.left.select(view, selection)
+                    (&self.right, &self.left)
+                };
+                let selection = first.select(view, eids_in);
+                if selection.is_empty() {
+                    return selection;
                 }
+                second.select(view, selection)
             }
             BooleanOp::Or => {
-                let (mut sel1, sel2) = thread::scope(move |s| {
-                    let eids_clone = eids_in.clone();
-                    let h1 = s.spawn(|| self.left.select(view, eids_clone));
-                    let h2 = s.spawn(|| self.right.select(view, eids_in));
-                    (h1.join().unwrap(), h2.join().unwrap())
-                });
-                sel1.union(&sel2);
+                let (mut sel1, sel2) = self.select_both(view, eids_in);
+                sel1 |= &sel2;
                 sel1
             }
             BooleanOp::Xor => {
-                let mut sel1 = self.left.select(view, eids_in.clone());
-                let sel2 = self.right.select(view, eids_in);
-                sel1.symmetric_difference(&sel2);
+                let (mut sel1, sel2) = self.select_both(view, eids_in);
+                sel1 ^= &sel2;
                 sel1
             }
             BooleanOp::Diff => {
-                let mut sel1 = self.left.select(view, eids_in.clone());
-                let sel2 = self.right.select(view, eids_in);
-                sel1.difference(&sel2);
+                let (mut sel1, sel2) = self.select_both(view, eids_in);
+                sel1 -= &sel2;
                 sel1
             }
         }
     }
 }
 
+impl BinarayExpr {
+    /// Evaluates both independent branches concurrently.
+    #[cfg(feature = "rayon")]
+    fn select_both<'a>(
+        &'a self,
+        view: &'a UMeshView<'a>,
+        eids_in: BlockMask,
+    ) -> (BlockMask, BlockMask) {
+        let eids_clone = eids_in.clone();
+        rayon::join(
+            || self.left.select(view, eids_clone),
+            || self.right.select(view, eids_in),
+        )
+    }
+
+    /// Evaluates both independent branches concurrently.
+    #[cfg(not(any(feature = "rayon", target_arch = "wasm32")))]
+    fn select_both<'a>(
+        &'a self,
+        view: &'a UMeshView<'a>,
+        eids_in: BlockMask,
+    ) -> (BlockMask, BlockMask) {
+        thread::scope(move |s| {
+            let eids_clone = eids_in.clone();
+            let h1 = s.spawn(|| self.left.select(view, eids_clone));
+            let h2 = s.spawn(|| self.right.select(view, eids_in));
+            (h1.join().unwrap(), h2.join().unwrap())
+        })
+    }
+
+    /// Evaluates both branches one after the other, as threads cannot be spawned in WASM.
+    #[cfg(all(not(feature = "rayon"), target_arch = "wasm32"))]
+    fn select_both<'a>(
+        &'a self,
+        view: &'a UMeshView<'a>,
+        eids_in: BlockMask,
+    ) -> (BlockMask, BlockMask) {
+        let left = self.left.select(view, eids_in.clone());
+        (left, self.right.select(view, eids_in))
+    }
+}
+
 impl Select for CentroidSelection {
-    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: ElementIdsSet) -> ElementIdsSet {
+    fn select<'a>(&'a self, view: &'a UMeshView<'a>, eids_in: BlockMask) -> BlockMask {
         match self {
             Self::BBox { min, max } => Self::in_bbox(min, max, view, eids_in),
             Self::Rect { min, max } => Self::in_rectangle(min, max, view, eids_in),
@@ -459,27 +551,107 @@ impl Select for CentroidSelection {
     }
 }
 
+impl Selection {
+    /// Evaluates the selection in node mode, filtering the `nodes_in` node set.
+    ///
+    /// Geometric leaves filter node coordinates directly, element leaves select the nodes of the
+    /// elements they select, and boolean operators combine node sets.
+    fn select_nodes<'a>(
+        &'a self,
+        view: &'a UMeshView<'a>,
+        mut nodes_in: FxHashSet<usize>,
+    ) -> FxHashSet<usize> {
+        match self {
+            Self::NodeSelection(nodes_expr) => nodes_expr.filter_nodes(view, nodes_in),
+            Self::CentroidSelection(centroid) => centroid.filter_nodes(view, nodes_in),
+            Self::ElementSelection(_) | Self::GroupSelection(_) | Self::FieldSelection(_) => {
+                let eids = self.select(view, full_index(view));
+                let selected: FxHashSet<usize> = eids
+                    .iter()
+                    .flat_map(|eid| view.element(eid).connectivity().to_vec())
+                    .collect();
+                nodes_in.retain(|n| selected.contains(n));
+                nodes_in
+            }
+            Self::NotExpr(NotExpr(inner)) => {
+                let not_sel = inner.select_nodes(view, nodes_in.clone());
+                nodes_in.retain(|n| !not_sel.contains(n));
+                nodes_in
+            }
+            Self::BinarayExpr(BinarayExpr {
+                operator,
+                left,
+                right,
+            }) => {
+                let left = left.select_nodes(view, nodes_in.clone());
+                match operator {
+                    BooleanOp::And => right.select_nodes(view, left),
+                    BooleanOp::Or => {
+                        let right = right.select_nodes(view, nodes_in);
+                        left.union(&right).copied().collect()
+                    }
+                    BooleanOp::Xor => {
+                        let right = right.select_nodes(view, nodes_in);
+                        left.symmetric_difference(&right).copied().collect()
+                    }
+                    BooleanOp::Diff => {
+                        let right = right.select_nodes(view, nodes_in);
+                        left.difference(&right).copied().collect()
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// All the elements of the mesh.
+pub(crate) fn full_index(view: &UMeshView) -> BlockMask {
+    BlockMask::full(view.blocks().map(|(k, v)| (*k, v.len())))
+}
+
 /// Trait for applying selections to meshes.
+///
+/// It is implemented for all mesh flavours, so that selections also run on views of borrowed
+/// or foreign data.
 pub trait MeshSelect {
     /// Returns the element IDs matching the selection expression.
     fn select_ids(&self, expr: Selection) -> ElementIds;
 
-    /// Returns matching element IDs and extracts a sub-mesh.
-    fn select(&self, expr: Selection, with_fields: bool) -> (ElementIds, Self);
-}
+    /// Returns the sorted node IDs matching the selection expression.
+    ///
+    /// Geometric leaves (node and centroid selections) filter node coordinates directly, while
+    /// element leaves (types, dimensions, groups, fields) select the nodes belonging to the
+    /// selected elements. All the nodes of the mesh are candidates, including unused ones.
+    fn select_nodes(&self, expr: Selection) -> Vec<usize>;
 
-impl MeshSelect for UMesh {
+    /// Returns matching element IDs and extracts a sub-mesh.
+    ///
+    /// The sub-mesh owns its data, see [`UMeshBase::extract_to_owned`]. Coordinates are only
+    /// shared with a [`UMesh`], they are copied from views.
+    fn select(&self, expr: Selection, with_fields: bool) -> (ElementIds, UMesh);
+}
+
+impl<N, C, F, G> MeshSelect for UMeshBase<N, C, F, G>
+where
+    N: nd::Data<Elem = f64>,
+    C: nd::Data<Elem = usize>,
+    F: nd::Data<Elem = f64>,
+    G: nd::Data<Elem = usize>,
+{
     fn select_ids(&self, expr: Selection) -> ElementIds {
-        let index: ElementIdsSet = ElementIdsSet(
-            self.blocks()
-                .map(|(k, v)| (*k, (0..v.len()).collect()))
-                .collect(),
-        );
-        expr.select(&self.view(), index).into()
+        let view = self.view();
+        expr.select(&view, full_index(&view)).into()
     }
-    fn select(&self, expr: Selection, with_fields: bool) -> (ElementIds, Self) {
+    fn select_nodes(&self, expr: Selection) -> Vec<usize> {
+        let view = self.view();
+        let nodes = (0..view.coords().nrows()).collect();
+        let mut nodes: Vec<usize> = expr.select_nodes(&view, nodes).into_iter().collect();
+        nodes.sort_unstable();
+        nodes
+    }
+    fn select(&self, expr: Selection, with_fields: bool) -> (ElementIds, UMesh) {
         let eids = self.select_ids(expr);
-        let extracted = self.extract(&eids, with_fields);
+        let extracted = self.extract_to_owned(&eids, with_fields);
         (eids, extracted)
     }
 }
@@ -508,6 +680,22 @@ mod tests {
         assert_eq!(mesh_sel.num_elements(), 1);
     }
 
+    #[test]
+    fn test_view_selection() {
+        let mesh = me::make_imesh_2d(2);
+        let view = mesh.view();
+        let expr = rect([0.0, 0.0], [0.5, 1.0]);
+        let ids = view.select_ids(expr.clone());
+        assert_eq!(ids, mesh.select_ids(expr.clone()));
+        assert_eq!(
+            view.select_nodes(expr.clone()),
+            mesh.select_nodes(expr.clone())
+        );
+        let (view_ids, extracted) = view.select(expr, true);
+        assert_eq!(view_ids, ids);
+        assert_eq!(extracted, mesh.extract(&ids, true));
+    }
+
     #[test]
     fn test_umesh_measure() {
         let mut mesh = RegularUMeshBuilder::new()
@@ -521,4 +709,69 @@ mod tests {
         let eids = mesh.select_ids(Selection::FieldSelection(expr));
         assert_eq!(eids.len(), 62)
     }
+
+    #[test]
+    fn test_umesh_field_and_group_selection() {
+        let mut mesh = me::make_imesh_2d(2);
+        let temp = nd::arr1(&[250.0, 310.0, 320.0, 300.0])
+            .into_dyn()
+            .into_shared();
+        mesh.update_field(
+            "temp",
+            crate::mesh::FieldBase::new([(ElementType::QUAD4, temp)].into()),
+            None,
+        );
+        let mut ids = ElementIds::new();
+        ids.add_block(ElementType::QUAD4, vec![0, 1]);
+        mesh.set_group("inlet", &ids);
+
+        let eids = mesh.select_ids(gt("temp", 300.0) & group("inlet"));
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![1]));
+        let eids = mesh.select_ids(geq("temp", 300.0) & not_group("inlet"));
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![2, 3]));
+        let eids = mesh.select_ids(eq("temp", 300.0) | lt("temp", 260.0));
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![0, 3]));
+        let eids = mesh.select_ids(family(0));
+        assert_eq!(eids.get(&ElementType::QUAD4), Some(&vec![2, 3]));
+    }
+
+    #[test]
+    fn test_umesh_not_xor_selection() {
+        use ElementType::*;
+        let mesh = me::make_imesh_2d(2);
+        let left = rect([0.0, 0.0], [0.5, 1.0]);
+        let bottom = rect([0.0, 0.0], [1.0, 0.5]);
+
+        let eids = mesh.select_ids(!left.clone());
+        assert_eq!(eids.get(&QUAD4), Some(&vec![1, 3]));
+        let eids = mesh.select_ids(left.clone() ^ bottom.clone());
+        assert_eq!(eids.get(&QUAD4), Some(&vec![1, 2]));
+        let eids = mesh.select_ids(!(left.clone() ^ bottom.clone()) & types(vec![QUAD4]));
+        assert_eq!(eids.get(&QUAD4), Some(&vec![0, 3]));
+        let eids = mesh.select_ids(!left.clone() & !bottom.clone());
+        assert_eq!(eids.get(&QUAD4), Some(&vec![3]));
+        let eids = mesh.select_ids(!types(vec![QUAD4]) & left);
+        assert!(eids.is_empty());
+        let eids = mesh.select_ids(!types(vec![SEG2]) | bottom);
+        assert_eq!(eids.len(), 4);
+    }
+
+    #[test]
+    fn test_umesh_select_nodes() {
+        let mut mesh = me::make_imesh_2d(2);
+        let mut last = ElementIds::new();
+        last.add(ElementType::QUAD4, 3);
+        mesh.set_group("last", &last);
+
+        let nodes = mesh.select_nodes(nrect([-0.1, -0.1], [0.6, 0.6], true));
+        assert_eq!(nodes, vec![0, 1, 3, 4]);
+        let nodes = mesh.select_nodes(types(vec![ElementType::QUAD4]));
+        assert_eq!(nodes.len(), 9);
+        let nodes = mesh.select_nodes(group("last") & !nids(vec![8], false));
+        assert_eq!(nodes, vec![4, 5, 7]);
+        let nodes = mesh.select_nodes(!rect([-0.1, -0.1], [0.6, 1.1]));
+        assert_eq!(nodes, vec![2, 5, 8]);
+        let nodes = mesh.select_nodes(group("last") ^ nids(vec![0, 4], false));
+        assert_eq!(nodes, vec![0, 5, 7, 8]);
+    }
 }
@@ -0,0 +1,162 @@
+//! Named, reusable selection results.
+
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mesh::{BlockMask, ElementIds, UMesh};
+
+use super::selection::{MeshSelect, Selection, full_index};
+
+/// The result of a selection together with the expression it comes from.
+///
+/// A selection set can be saved and loaded (it is serializable), re-evaluated against a
+/// modified mesh, inverted and combined with other sets. Combining sets also combines their
+/// expressions, so the result can still be re-evaluated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelectionSet {
+    expr: Selection,
+    ids: ElementIds,
+}
+
+impl SelectionSet {
+    /// Evaluates the expression on the mesh.
+    pub fn new(mesh: &UMesh, expr: Selection) -> Self {
+        let ids = mesh.select_ids(expr.clone());
+        Self { expr, ids }
+    }
+
+    /// The expression this set comes from.
+    pub fn expr(&self) -> &Selection {
+        &self.expr
+    }
+
+    /// The selected element ids.
+    pub fn ids(&self) -> &ElementIds {
+        &self.ids
+    }
+
+    /// Consumes the set and returns the selected element ids.
+    pub fn into_ids(self) -> ElementIds {
+        self.ids
+    }
+
+    /// Returns the total number of selected elements.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if no element is selected.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Re-evaluates the expression on a (possibly modified) mesh.
+    ///
+    /// Returns `true` if the selected ids changed.
+    pub fn reevaluate(&mut self, mesh: &UMesh) -> bool {
+        let ids = mesh.select_ids(self.expr.clone());
+        let changed = ids != self.ids;
+        self.ids = ids;
+        changed
+    }
+
+    /// Returns the set of the mesh elements which are not in this set.
+    pub fn invert(&self, mesh: &UMesh) -> Self {
+        let view = mesh.view();
+        let mask = full_index(&view);
+        let selected = BlockMask::from_ids(&self.ids, mask.sizes());
+        Self {
+            expr: !self.expr.clone(),
+            ids: (!selected).into(),
+        }
+    }
+
+    fn combine<F>(&self, other: &Self, expr: Selection, op: F) -> Self
+    where
+        F: FnOnce(&mut ElementIds, &ElementIds),
+    {
+        let mut ids = self.ids.clone();
+        op(&mut ids, &other.ids);
+        Self { expr, ids }
+    }
+
+    /// Elements in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let expr = self.expr.clone() | other.expr.clone();
+        self.combine(other, expr, ElementIds::union)
+    }
+
+    /// Elements in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let expr = self.expr.clone() & other.expr.clone();
+        self.combine(other, expr, ElementIds::intersection)
+    }
+
+    /// Elements in this set but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let expr = self.expr.clone() - other.expr.clone();
+        self.combine(other, expr, ElementIds::difference)
+    }
+
+    /// Elements in exactly one of the sets.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let expr = self.expr.clone() ^ other.expr.clone();
+        self.combine(other, expr, ElementIds::symmetric_difference)
+    }
+
+    /// Saves the set to a JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Loads a set from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let set = serde_json::from_reader(file)?;
+        Ok(set)
+    }
+}
+
+impl From<SelectionSet> for ElementIds {
+    fn from(set: SelectionSet) -> Self {
+        set.ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementType;
+    use crate::mesh_examples as me;
+    use crate::tools::sel;
+
+    #[test]
+    fn test_selection_set() {
+        let mesh = me::make_imesh_2d(2);
+        let left = SelectionSet::new(&mesh, sel::rect([0.0, 0.0], [0.5, 1.0]));
+        let bottom = SelectionSet::new(&mesh, sel::rect([0.0, 0.0], [1.0, 0.5]));
+        assert_eq!(left.ids().get(&ElementType::QUAD4), Some(&vec![0, 2]));
+
+        let right = left.invert(&mesh);
+        assert_eq!(right.ids().get(&ElementType::QUAD4), Some(&vec![1, 3]));
+        let xor = left.symmetric_difference(&bottom);
+        assert_eq!(xor.ids().get(&ElementType::QUAD4), Some(&vec![1, 2]));
+        assert_eq!(left.union(&bottom).len(), 3);
+        assert_eq!(left.intersection(&bottom).len(), 1);
+        assert_eq!(left.difference(&bottom).len(), 1);
+
+        let json = serde_json::to_string(&xor).unwrap();
+        let mut read: SelectionSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.ids(), xor.ids());
+        assert!(!read.reevaluate(&mesh));
+
+        let mut mesh = mesh;
+        mesh.add_element(ElementType::QUAD4, &[1, 2, 5, 4], None, None);
+        assert!(read.reevaluate(&mesh));
+        assert_eq!(read.ids().get(&ElementType::QUAD4), Some(&vec![1, 2, 4]));
+    }
+}
@@ -0,0 +1,118 @@
+//! Skin of a mesh, with the parent cell of each boundary face.
+//!
+//! Boundary conditions are applied on the faces of the boundary, but solvers and coupling layers
+//! assemble them on the cells: they need, for each boundary face, the cell it bounds and the
+//! index of the face in that cell. [`compute_skin`] gives this information together with the
+//! boundary mesh, which [`compute_boundaries`](crate::tools::compute_boundaries) does not.
+
+use std::collections::BTreeMap;
+
+use crate::element_traits::ElementTopo;
+use crate::mesh::{ElementId, ElementIds, ElementType, UMesh, UMeshView};
+use crate::trace;
+
+/// The boundary faces of a mesh and their parent cells.
+#[derive(Clone, Debug)]
+pub struct Skin {
+    /// The boundary faces (edges in 2D), belonging to the groups of their parent cells.
+    pub mesh: UMesh,
+    /// For each block of the skin mesh, the parent cell of each face with the local index of
+    /// the face among the faces of the parent.
+    pub parents: BTreeMap<ElementType, Vec<(ElementId, usize)>>,
+}
+
+impl Skin {
+    /// Returns the parent cell of a skin face, with the local index of the face in it.
+    pub fn parent(&self, face: ElementId) -> Option<(ElementId, usize)> {
+        self.parents
+            .get(&face.element_type())
+            .and_then(|parents| parents.get(face.index()))
+            .copied()
+    }
+}
+
+/// Computes the skin of a mesh: the faces of its cells of highest dimension (edges in 2D) which
+/// belong to a single cell.
+///
+/// Faces are oriented as seen from their parent cell and are ordered by parent cell, then by
+/// local index. Each face belongs to the groups of its parent cell. Fields are not transferred.
+pub fn compute_skin(mesh: UMeshView) -> Skin {
+    trace::span!("compute_skin");
+    let mut faces: Vec<(ElementId, usize)> = mesh
+        .face_index()
+        .iter()
+        .filter(|(_, sharing)| sharing.len() == 1)
+        .map(|(_, sharing)| sharing[0])
+        .collect();
+    faces.sort_unstable();
+
+    let mut skin = UMesh::new(mesh.coords().to_shared());
+    let mut parents: BTreeMap<ElementType, Vec<(ElementId, usize)>> = BTreeMap::new();
+    for (parent, local) in faces {
+        let subentities = mesh.element(parent).subentities(None);
+        let (et, co) = subentities
+            .iter()
+            .flat_map(|(et, conn)| conn.iter().map(move |co| (*et, co)))
+            .nth(local)
+            .unwrap();
+        skin.add_element(et, co, None, None);
+        parents.entry(et).or_default().push((parent, local));
+    }
+
+    for name in mesh.group_names() {
+        let group = mesh.group_as_element_ids(&name);
+        let mut ids = ElementIds::new();
+        for (&et, block_parents) in &parents {
+            for (i, &(parent, _)) in block_parents.iter().enumerate() {
+                if group.contains(parent) {
+                    ids.add(et, i);
+                }
+            }
+        }
+        if !ids.is_empty() {
+            skin.set_group(&name, &ids);
+        }
+    }
+    trace::debug!(faces = skin.num_elements(), "skin computed");
+    Skin {
+        mesh: skin,
+        parents,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::ElementLike;
+    use crate::mesh_examples as me;
+    use crate::tools::Descendable;
+
+    #[test]
+    fn test_compute_skin() {
+        let mut mesh = me::make_imesh_2d(2);
+        let quad = |i| ElementId::new(ElementType::QUAD4, i);
+        let mut left = ElementIds::new();
+        left.add_block(ElementType::QUAD4, vec![0, 2]);
+        mesh.set_group("left", &left);
+
+        let skin = compute_skin(mesh.view());
+        assert_eq!(skin.mesh.num_elements(), 8);
+        assert_eq!(skin.parents[&ElementType::SEG2].len(), 8);
+        assert_eq!(skin.mesh.group_as_element_ids("left").len(), 4);
+        let boundaries = mesh.boundaries(None, None);
+        assert_eq!(boundaries.num_elements(), skin.mesh.num_elements());
+
+        for face in skin.mesh.elements() {
+            let (parent, local) = skin.parent(face.id()).unwrap();
+            let edges = mesh.element(parent).subentities(None);
+            let edge = edges[0].1.iter().nth(local).unwrap();
+            assert_eq!(edge, face.connectivity());
+            assert_eq!(face.in_group("left"), left.contains(parent));
+        }
+        assert_eq!(
+            skin.parent(ElementId::new(ElementType::SEG2, 0)),
+            Some((quad(0), 0))
+        );
+        assert_eq!(skin.parent(ElementId::new(ElementType::SEG2, 8)), None);
+    }
+}
@@ -1,92 +1,54 @@
-use crate::mesh::{ElementLike, IndirectIndexOwned, UMesh, UMeshView};
+use crate::geometry::predicates::Tolerances;
+use crate::mesh::{Dimension, ElementLike, ElementType, IndirectIndexOwned, UMesh, UMeshView};
+use crate::tools::NodeLocator;
+use crate::trace;
 
 use itertools::Itertools;
 use nalgebra as na;
-use rstar::{RTree, primitives::GeomWithData};
-
-fn snap_dim_n<const T: usize>(subject: &mut UMesh, reference: UMeshView, eps: f64) {
-    let ref_points: Vec<[f64; T]> = reference
-        .used_nodes()
-        .into_iter()
-        .map(|i| {
-            reference
-                .coords()
-                .row(i)
-                .to_slice()
-                .unwrap()
-                .try_into()
-                .unwrap()
-        })
-        .collect();
-    let rtree = RTree::bulk_load(ref_points);
-    for node in subject.used_nodes() {
-        let coord: &mut [f64; T] = subject
-            .coords
-            .row_mut(node)
-            .into_slice()
-            .unwrap()
-            .try_into()
-            .unwrap();
-        let closest_points = rtree.locate_within_distance(*coord, f64::powi(eps, 2));
-        let (_, closest) = closest_points
-            .into_iter()
-            .fold((f64::INFINITY, None), |acc, &p| {
-                let (min_d2, closest_p) = acc;
-                let na_p = p.into();
-                let na_coord = (*coord).into();
-                let d2 = na::distance_squared(&na_p, &na_coord);
-                if d2 < min_d2 {
-                    (d2, Some(p))
-                } else {
-                    (min_d2, closest_p)
-                }
-            });
-        if let Some(c) = closest {
-            coord.copy_from_slice(&c)
-        }
-    }
-}
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{AABB, RTree};
+use rustc_hash::FxHashMap;
 
 /// Snap coords of subject mesh onto used nodes of reference.
 ///
 /// Be careful, the method could produce degenerated elements if eps is not lower than half the
 /// smallest distance between two points from the same element.
 pub fn snap(subject: &mut UMesh, reference: UMeshView, eps: f64) {
-    match subject.coords().ncols() {
-        // 1 => snap_dim_n::<1>(subject, reference, eps),
-        2 => snap_dim_n::<2>(subject, reference, eps),
-        3 => snap_dim_n::<3>(subject, reference, eps),
-        _ => panic!("Could not snap the mesh because of its dimension."),
+    trace::span!("snap");
+    subject.invalidate_cache();
+    let locator = NodeLocator::from_used_nodes(&reference);
+    for node in subject.used_nodes() {
+        let mut coord = subject.coords.row_mut(node);
+        if let Some(closest) = locator.nearest_within(coord.as_slice().unwrap(), eps) {
+            coord.assign(&reference.coords().row(closest));
+        }
     }
 }
 
 //TODO: replace Vec<Vec<usize>> with proper IndirectIndex type.
 // This would allow for cache friendly linear search of data.
 
-fn duplicates_dim_n<const T: usize>(mesh: UMeshView, eps: f64) -> IndirectIndexOwned<usize> {
+/// Groups of used nodes closer than `eps`, each group being sorted.
+pub fn duplicates(mesh: UMeshView, eps: f64) -> IndirectIndexOwned<usize> {
     let used_nodes = mesh.used_nodes();
-    let points: Vec<GeomWithData<[f64; T], usize>> = used_nodes
-        .iter()
-        .map(|&i| {
-            GeomWithData::new(
-                mesh.coords().row(i).to_slice().unwrap().try_into().unwrap(),
-                i,
-            )
-        })
-        .collect();
-    let mut rtree = RTree::bulk_load(points);
+    let locator = NodeLocator::from_nodes(mesh.coords(), used_nodes.iter().copied());
+    let coords = mesh.coords();
+    let mut grouped = vec![false; coords.nrows()];
     let mut res = IndirectIndexOwned::new();
     for &node in &used_nodes {
-        let coord: [f64; T] = mesh
-            .coords()
-            .row(node)
-            .as_slice()
-            .unwrap()
-            .try_into()
-            .unwrap();
-        // Points are drained so they are not counted twice
-        let closest_points = rtree.drain_within_distance(coord, f64::powi(eps, 2));
-        let node_group: Vec<usize> = closest_points.map(|p| p.data).sorted_unstable().collect();
+        if grouped[node] {
+            continue;
+        }
+        // Grouped nodes are skipped so they are not counted twice
+        let coord = coords.row(node);
+        let node_group: Vec<usize> = locator
+            .within_radius(coord.as_slice().unwrap(), eps)
+            .into_iter()
+            .filter(|&n| !grouped[n])
+            .collect();
+        for &n in &node_group {
+            grouped[n] = true;
+        }
         if node_group.len() > 1 {
             res.push(&node_group);
         }
@@ -94,15 +56,6 @@ fn duplicates_dim_n<const T: usize>(mesh: UMeshView, eps: f64) -> IndirectIndexO
     res
 }
 
-pub fn duplicates(mesh: UMeshView, eps: f64) -> IndirectIndexOwned<usize> {
-    match mesh.coords().ncols() {
-        1 => duplicates_dim_n::<1>(mesh, eps),
-        2 => duplicates_dim_n::<2>(mesh, eps),
-        3 => duplicates_dim_n::<3>(mesh, eps),
-        _ => panic!("Could not find duplicates because of invalid dimension."),
-    }
-}
-
 fn find_group(n: &usize, nodes: &[usize], groups: &[usize]) -> Option<usize> {
     match nodes.binary_search(n) {
         Ok(i) => Some(groups[i]),
@@ -112,10 +65,14 @@ fn find_group(n: &usize, nodes: &[usize], groups: &[usize]) -> Option<usize> {
 
 /// Merge close nodes.
 ///
+/// Nodes closer than the tolerance distance, given as a plain distance or as [`Tolerances`], are
+/// merged.
+///
 /// Be careful, this method can produce degenerated elements if used with an epsilon greater than
 /// the distance between two nodes of the same element.
-pub fn merge_nodes(mesh: &mut UMesh, eps: f64) {
-    let dups = duplicates(mesh.view(), eps);
+pub fn merge_nodes(mesh: &mut UMesh, tol: impl Into<Tolerances>) {
+    trace::span!("merge_nodes");
+    let dups = duplicates(mesh.view(), tol.into().distance);
     let sorted_nodes_dup: Vec<(usize, usize)> = dups
         .iter()
         .enumerate()
@@ -124,6 +81,11 @@ pub fn merge_nodes(mesh: &mut UMesh, eps: f64) {
         .collect();
     let sorted_nodes: Vec<usize> = sorted_nodes_dup.iter().map(|t| t.0).collect();
     let sorted_grps: Vec<usize> = sorted_nodes_dup.iter().map(|t| t.1).collect();
+    trace::debug!(
+        groups = dups.len(),
+        nodes = sorted_nodes.len(),
+        "duplicate nodes found"
+    );
     // Here the idea is to go once throught each element and to renumber all nodes presents in
     // duplicates to the first node of the duplicates group.
     // I suppose that the number of duplicates is small in front of the number of elements so I
@@ -138,6 +100,337 @@ pub fn merge_nodes(mesh: &mut UMesh, eps: f64) {
             }
         }
     }
+    mesh.renumber_node_groups(|n| match find_group(&n, &sorted_nodes, &sorted_grps) {
+        Some(grp) => Some(dups[grp][0]),
+        None => Some(n),
+    });
+}
+
+/// Options of [`project_nodes`].
+///
+/// Nodes are projected onto the closest point of the target if it is within `max_distance`.
+/// With feature preservation, the sharp edges and corners of the target are found from a
+/// feature angle, and nodes closer than the feature distance to a corner (or else to a feature
+/// edge) are projected onto it rather than onto the closest point. This keeps the corners and
+/// sharp edges of the target in the snapped mesh.
+#[derive(Clone, Copy, Debug)]
+pub struct SnapOptions {
+    max_distance: f64,
+    features: Option<(f64, f64)>,
+}
+
+impl Default for SnapOptions {
+    fn default() -> Self {
+        Self::new(f64::INFINITY)
+    }
+}
+
+impl SnapOptions {
+    /// Creates options projecting nodes within `max_distance` of the target, without feature
+    /// preservation.
+    pub fn new(max_distance: f64) -> Self {
+        Self {
+            max_distance,
+            features: None,
+        }
+    }
+
+    /// Sets the maximum distance between a node and its projection.
+    pub fn max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Enables feature preservation.
+    ///
+    /// An edge of the target surface is a feature edge if it bounds a single face, more than
+    /// two faces, or two faces whose normals make an angle larger than `angle` (in radians).
+    /// Line elements of the target are feature edges too. A corner is the end of a feature line,
+    /// a node shared by more than two feature edges, or a kink of more than `angle` between two
+    /// feature edges. Nodes closer than `distance` to a corner or a feature edge are projected
+    /// onto it.
+    pub fn preserve_features(mut self, angle: f64, distance: f64) -> Self {
+        self.features = Some((angle, distance));
+        self
+    }
+}
+
+type Vector3 = na::Vector3<f64>;
+
+/// A point, a segment or a triangle of the target geometry.
+enum Primitive {
+    Point(Vector3),
+    Segment(Vector3, Vector3),
+    Triangle(Vector3, Vector3, Vector3),
+}
+
+impl Primitive {
+    fn vertices(&self) -> Vec<Vector3> {
+        match *self {
+            Primitive::Point(a) => vec![a],
+            Primitive::Segment(a, b) => vec![a, b],
+            Primitive::Triangle(a, b, c) => vec![a, b, c],
+        }
+    }
+
+    fn closest_point(&self, p: &Vector3) -> Vector3 {
+        match *self {
+            Primitive::Point(a) => a,
+            Primitive::Segment(a, b) => closest_on_segment(p, &a, &b),
+            Primitive::Triangle(a, b, c) => closest_on_triangle(p, &a, &b, &c),
+        }
+    }
+}
+
+fn closest_on_segment(p: &Vector3, a: &Vector3, b: &Vector3) -> Vector3 {
+    let ab = b - a;
+    let len2 = ab.norm_squared();
+    if len2 == 0.0 {
+        return *a;
+    }
+    let t = ((p - a).dot(&ab) / len2).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Closest point of a triangle, found from the Voronoi region of `p` (Ericson, Real-Time
+/// Collision Detection, 5.1.5).
+fn closest_on_triangle(p: &Vector3, a: &Vector3, b: &Vector3, c: &Vector3) -> Vector3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 >= d3 && d5 >= d6 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = va + vb + vc;
+    if denom == 0.0 {
+        // Degenerated triangle
+        return [(a, b), (b, c), (c, a)]
+            .into_iter()
+            .map(|(u, v)| closest_on_segment(p, u, v))
+            .min_by(|u, v| (u - p).norm_squared().total_cmp(&(v - p).norm_squared()))
+            .unwrap();
+    }
+    a + ab * (vb / denom) + ac * (vc / denom)
+}
+
+/// Primitives indexed by their bounding boxes.
+struct Locator {
+    primitives: Vec<Primitive>,
+    rtree: RTree<GeomWithData<Rectangle<[f64; 3]>, usize>>,
+}
+
+impl Locator {
+    fn new(primitives: Vec<Primitive>) -> Self {
+        let boxes = primitives
+            .iter()
+            .enumerate()
+            .map(|(i, prim)| {
+                let vertices: Vec<[f64; 3]> = prim.vertices().iter().map(|v| (*v).into()).collect();
+                GeomWithData::new(Rectangle::from_aabb(AABB::from_points(&vertices)), i)
+            })
+            .collect();
+        Self {
+            primitives,
+            rtree: RTree::bulk_load(boxes),
+        }
+    }
+
+    /// Closest point of the primitives within `max_distance` of `p`.
+    fn closest_point(&self, p: &Vector3, max_distance: f64) -> Option<Vector3> {
+        let envelope = AABB::<[f64; 3]>::from_corners(
+            p.map(|x| x - max_distance).into(),
+            p.map(|x| x + max_distance).into(),
+        );
+        self.rtree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|b| self.primitives[b.data].closest_point(p))
+            .map(|q| ((q - p).norm(), q))
+            .filter(|(d, _)| *d <= max_distance)
+            .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+            .map(|(_, q)| q)
+    }
+}
+
+/// Nodes of an element on its geometrical corners (high order nodes are ignored).
+fn corner_nodes(et: ElementType, connectivity: &[usize]) -> &[usize] {
+    use ElementType::*;
+    match et {
+        SEG2 | SEG3 | SEG4 => &connectivity[..2],
+        TRI3 | TRI6 | TRI7 => &connectivity[..3],
+        QUAD4 | QUAD8 | QUAD9 => &connectivity[..4],
+        _ => connectivity,
+    }
+}
+
+/// Target geometry split into corners, feature edges and all of its primitives.
+struct SnapTarget {
+    corners: Locator,
+    edges: Locator,
+    all: Locator,
+}
+
+impl SnapTarget {
+    fn new(target: UMeshView, feature_angle: Option<f64>) -> Self {
+        let target_coords = target.coords();
+        let point = |n: usize| {
+            let mut p = Vector3::zeros();
+            let coords = target_coords.row(n);
+            p.as_mut_slice()[..coords.len()]
+                .iter_mut()
+                .zip(coords)
+                .for_each(|(x, c)| *x = *c);
+            p
+        };
+        let mut all = Vec::new();
+        let mut lines: Vec<[usize; 2]> = Vec::new();
+        for elem in target.elements_of_dim(Dimension::D1) {
+            let nodes = corner_nodes(elem.element_type(), elem.connectivity());
+            for (&a, &b) in nodes.iter().tuple_windows() {
+                all.push(Primitive::Segment(point(a), point(b)));
+                lines.push([a, b]);
+            }
+        }
+        let mut face_edges: FxHashMap<[usize; 2], Vec<Vector3>> = FxHashMap::default();
+        for elem in target.elements_of_dim(Dimension::D2) {
+            let nodes = corner_nodes(elem.element_type(), elem.connectivity());
+            // Newell's normal of the polygon
+            let normal = nodes
+                .iter()
+                .circular_tuple_windows()
+                .map(|(&a, &b)| point(a).cross(&point(b)))
+                .sum::<Vector3>()
+                .normalize();
+            for i in 1..nodes.len() - 1 {
+                all.push(Primitive::Triangle(
+                    point(nodes[0]),
+                    point(nodes[i]),
+                    point(nodes[i + 1]),
+                ));
+            }
+            for (&a, &b) in nodes.iter().circular_tuple_windows() {
+                face_edges
+                    .entry([a.min(b), a.max(b)])
+                    .or_default()
+                    .push(normal);
+            }
+        }
+        let Some(angle) = feature_angle else {
+            return Self {
+                corners: Locator::new(Vec::new()),
+                edges: Locator::new(Vec::new()),
+                all: Locator::new(all),
+            };
+        };
+        let sharp = |u: &Vector3, v: &Vector3| u.angle(v) > angle;
+        let features = face_edges
+            .into_iter()
+            .filter(|(_, normals)| normals.len() != 2 || sharp(&normals[0], &normals[1]))
+            .map(|(edge, _)| edge)
+            .sorted_unstable()
+            .chain(lines);
+        let mut node_edges: FxHashMap<usize, Vec<Vector3>> = FxHashMap::default();
+        let mut edges = Vec::new();
+        for [a, b] in features {
+            let (pa, pb) = (point(a), point(b));
+            node_edges.entry(a).or_default().push(pb - pa);
+            node_edges.entry(b).or_default().push(pa - pb);
+            edges.push(Primitive::Segment(pa, pb));
+        }
+        let corners = node_edges
+            .into_iter()
+            .filter(|(_, dirs)| dirs.len() != 2 || sharp(&dirs[0], &-dirs[1]))
+            .map(|(n, _)| n)
+            .sorted_unstable()
+            .map(|n| Primitive::Point(point(n)))
+            .collect();
+        Self {
+            corners: Locator::new(corners),
+            edges: Locator::new(edges),
+            all: Locator::new(all),
+        }
+    }
+}
+
+/// Projects nodes of a mesh onto a target line or surface mesh.
+///
+/// Each node of `nodes` is moved in place to its closest point on the 1D and 2D elements of
+/// `target` (high order elements are taken as linear ones), if this point is within the maximum
+/// distance of `options`. Corners and sharp edges of the target can be preserved, see
+/// [`SnapOptions`]. Returns the nodes which could not be snapped, their coordinates being left
+/// unchanged.
+///
+/// Be careful, the method could produce degenerated or inverted elements if nodes of the same
+/// element are projected onto the same point.
+pub fn project_nodes(
+    mesh: &mut UMesh,
+    nodes: &[usize],
+    target: UMeshView,
+    options: &SnapOptions,
+) -> Vec<usize> {
+    let space_dim = mesh.space_dimension();
+    assert_eq!(
+        space_dim,
+        target.space_dimension(),
+        "The mesh and the target must have the same space dimension"
+    );
+    trace::span!("project_nodes");
+    let target = SnapTarget::new(target, options.features.map(|(angle, _)| angle));
+    trace::debug!("target trees built");
+    mesh.invalidate_cache();
+    let mut unsnapped = Vec::new();
+    for &node in nodes {
+        let mut coord = mesh.coords.row_mut(node);
+        let mut p = Vector3::zeros();
+        p.as_mut_slice()[..space_dim]
+            .iter_mut()
+            .zip(coord.iter())
+            .for_each(|(x, c)| *x = *c);
+        let projected = match options.features {
+            Some((_, distance)) => target
+                .corners
+                .closest_point(&p, distance.min(options.max_distance))
+                .or_else(|| {
+                    target
+                        .edges
+                        .closest_point(&p, distance.min(options.max_distance))
+                }),
+            None => None,
+        }
+        .or_else(|| target.all.closest_point(&p, options.max_distance));
+        match projected {
+            Some(q) => coord.iter_mut().zip(q.iter()).for_each(|(c, x)| *c = *x),
+            None => unsnapped.push(node),
+        }
+    }
+    trace::debug!(
+        nodes = nodes.len(),
+        unsnapped = unsnapped.len(),
+        "nodes projected"
+    );
+    unsnapped
 }
 
 pub trait NodeDuplicates {
@@ -159,7 +452,9 @@ impl NodeDuplicates for UMesh {
 mod tests {
     use super::*;
     use crate::mesh::{ElementType, UMesh};
+    use approx::assert_abs_diff_eq;
     use ndarray as nd;
+    use std::f64::consts::PI;
 
     #[test]
     fn test_snap_2d() {
@@ -231,4 +526,98 @@ mod tests {
         mesh.merge_nodes(0.01);
         // Just verify it doesn't panic
     }
+
+    #[test]
+    fn test_project_nodes_2d() {
+        let mut mesh = crate::mesh_examples::make_imesh_2d(2);
+        let coords = nd::arr2(&[[-1.0, 1.1], [2.0, 1.1]]);
+        let mut line = UMesh::new(coords.into_shared());
+        line.add_regular_block(ElementType::SEG2, nd::arr2(&[[0, 1]]).to_shared(), None);
+
+        let top: Vec<usize> = (0..mesh.coords().nrows())
+            .filter(|&n| mesh.coords()[[n, 1]] == 1.0)
+            .collect();
+        let bottom = (0..mesh.coords().nrows())
+            .find(|&n| mesh.coords()[[n, 1]] == 0.0)
+            .unwrap();
+        let nodes: Vec<usize> = top.iter().copied().chain([bottom]).collect();
+        let unsnapped = project_nodes(&mut mesh, &nodes, line.view(), &SnapOptions::new(0.2));
+        assert_eq!(unsnapped, vec![bottom]);
+        for &n in &top {
+            assert!((mesh.coords()[[n, 1]] - 1.1).abs() < 1e-12);
+        }
+        assert_eq!(mesh.coords()[[bottom, 1]], 0.0);
+    }
+
+    #[test]
+    fn test_project_nodes_features() {
+        let coords = nd::arr2(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]);
+        let mut square = UMesh::new(coords.into_shared());
+        square.add_regular_block(
+            ElementType::QUAD4,
+            nd::arr2(&[[0, 1, 2, 3]]).to_shared(),
+            None,
+        );
+        let subject_coords = nd::arr2(&[
+            [0.95, 0.5, 0.05],
+            [0.97, 0.98, 0.05],
+            [0.5, 0.5, 0.1],
+            [0.5, 0.5, 2.0],
+        ]);
+        let subject = UMesh::new(subject_coords.into_shared());
+
+        let mut mesh = subject.clone();
+        let unsnapped = project_nodes(
+            &mut mesh,
+            &[0, 1, 2, 3],
+            square.view(),
+            &SnapOptions::new(1.0),
+        );
+        assert_eq!(unsnapped, vec![3]);
+        let expected = nd::arr2(&[
+            [0.95, 0.5, 0.0],
+            [0.97, 0.98, 0.0],
+            [0.5, 0.5, 0.0],
+            [0.5, 0.5, 2.0],
+        ]);
+        assert_abs_diff_eq!(
+            mesh.coords().as_slice().unwrap(),
+            expected.as_slice().unwrap(),
+            epsilon = 1e-12
+        );
+
+        let mut mesh = subject.clone();
+        let options = SnapOptions::new(1.0).preserve_features(PI / 6.0, 0.1);
+        let unsnapped = project_nodes(&mut mesh, &[0, 1, 2, 3], square.view(), &options);
+        assert_eq!(unsnapped, vec![3]);
+        let expected = nd::arr2(&[
+            [1.0, 0.5, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.5, 0.5, 0.0],
+            [0.5, 0.5, 2.0],
+        ]);
+        assert_abs_diff_eq!(
+            mesh.coords().as_slice().unwrap(),
+            expected.as_slice().unwrap(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_closest_on_triangle() {
+        let a = Vector3::new(0.0, 0.0, 0.0);
+        let b = Vector3::new(1.0, 0.0, 0.0);
+        let c = Vector3::new(0.0, 1.0, 0.0);
+        let q = |x: f64, y: f64, z: f64| closest_on_triangle(&Vector3::new(x, y, z), &a, &b, &c);
+        assert_eq!(q(-1.0, -1.0, 1.0), a);
+        assert_eq!(q(2.0, -0.5, 0.0), b);
+        assert_eq!(q(0.5, -1.0, 0.0), Vector3::new(0.5, 0.0, 0.0));
+        assert_eq!(q(1.0, 1.0, 0.0), Vector3::new(0.5, 0.5, 0.0));
+        assert!((q(0.2, 0.3, -4.0) - Vector3::new(0.2, 0.3, 0.0)).norm() < 1e-12);
+    }
 }
@@ -0,0 +1,271 @@
+//! Detection of the mirror symmetries of a mesh.
+//!
+//! A mesh is symmetric with respect to a plane (a line in 2D) when the mirror image of each node
+//! is a node, within a tolerance, and the image of each element is an element of the same type,
+//! whatever the order of its nodes. The node correspondence lets a solver keep half of the mesh
+//! and rebuild the other half, or check that a field is symmetric.
+//!
+//! The plane of a symmetry goes through the centroid of the nodes and its normal is a principal
+//! direction of the nodes, so [`detect_symmetry`] only tries these planes. When the principal
+//! directions are ambiguous, as for a square or a cube, the planes orthogonal to the coordinate
+//! axes are tried too, and other planes can be checked with [`mirror_node_map`].
+
+use nalgebra as na;
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+use crate::element_traits::{ElementEquality, SortedVecKey, node_set_key};
+use crate::mesh::{ElementId, ElementLike, ElementType, UMeshView};
+use crate::tools::NodeLocator;
+use crate::trace;
+
+/// Largest cosine of the angle between two candidate normals considered as different planes.
+const SAME_PLANE_COSINE: f64 = 1.0 - 1e-9;
+
+/// A mirror symmetry of a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symmetry {
+    /// A point of the plane, the centroid of the nodes.
+    pub origin: [f64; 3],
+    /// The unit normal of the plane.
+    pub normal: [f64; 3],
+    /// The image of each node by the mirror, nodes on the plane being their own image.
+    pub node_map: Vec<usize>,
+    /// Whether each element group and each node group is its own image.
+    pub preserves_groups: bool,
+}
+
+/// The elements of a mesh keyed by their type and their nodes regardless of their order, see
+/// [`ElementEquality::node_set_key`], and the locator of its nodes.
+struct MeshIndex {
+    elements: FxHashMap<(ElementType, SortedVecKey), ElementId>,
+    locator: NodeLocator,
+}
+
+impl MeshIndex {
+    fn new(mesh: &UMeshView) -> Self {
+        Self {
+            elements: mesh
+                .elements()
+                .map(|e| ((e.element_type(), e.node_set_key()), e.id()))
+                .collect(),
+            locator: NodeLocator::new(mesh.coords()),
+        }
+    }
+
+    /// The image of an element whose nodes are mapped by `node_map`, if it is an element.
+    fn image(&self, et: ElementType, nodes: &[usize], node_map: &[usize]) -> Option<ElementId> {
+        // Poly elements separate their faces with usize::MAX, kept as is
+        let nodes: SmallVec<[usize; 4]> = nodes
+            .iter()
+            .map(|&n| node_map.get(n).copied().unwrap_or(n))
+            .collect();
+        self.elements.get(&(et, node_set_key(&nodes))).copied()
+    }
+}
+
+fn point3(p: &[f64]) -> na::Vector3<f64> {
+    let mut x = na::Vector3::zeros();
+    x.as_mut_slice()[..p.len()].copy_from_slice(p);
+    x
+}
+
+/// Maps each node of a mesh to its mirror image through the plane (or the line, in 2D) going
+/// through `origin` and orthogonal to `normal`.
+///
+/// Returns `None` if the mesh is not symmetric: the image of a node is farther than `tol` from
+/// every node, or the image of an element is not an element of the same type.
+///
+/// # Panics
+/// Panics if the normal is null.
+pub fn mirror_node_map(
+    mesh: UMeshView,
+    origin: &[f64],
+    normal: &[f64],
+    tol: f64,
+) -> Option<Vec<usize>> {
+    mirror(
+        &mesh,
+        &MeshIndex::new(&mesh),
+        point3(origin),
+        point3(normal),
+        tol,
+    )
+}
+
+fn mirror(
+    mesh: &UMeshView,
+    index: &MeshIndex,
+    origin: na::Vector3<f64>,
+    normal: na::Vector3<f64>,
+    tol: f64,
+) -> Option<Vec<usize>> {
+    let normal =
+        na::Unit::try_new(normal, f64::EPSILON).expect("The mirror normal must not be null");
+    let space_dim = mesh.space_dimension();
+    let node_map = mesh
+        .coords()
+        .rows()
+        .into_iter()
+        .map(|p| {
+            let p = point3(&p.to_vec());
+            let q = p - 2.0 * (p - origin).dot(&normal) * normal.into_inner();
+            index
+                .locator
+                .nearest_within(&q.as_slice()[..space_dim], tol)
+        })
+        .collect::<Option<Vec<usize>>>()?;
+    // Close nodes may have the same image
+    if node_map.iter().enumerate().any(|(i, &j)| node_map[j] != i) {
+        return None;
+    }
+    mesh.elements()
+        .all(|e| {
+            index
+                .image(e.element_type(), e.connectivity(), &node_map)
+                .is_some()
+        })
+        .then_some(node_map)
+}
+
+/// Checks that each group of a mesh is its own image by a symmetry.
+fn preserves_groups(mesh: &UMeshView, index: &MeshIndex, node_map: &[usize]) -> bool {
+    let elements = mesh.group_names().into_iter().all(|name| {
+        let ids = mesh.group_as_element_ids(&name);
+        ids.iter().all(|id| {
+            let element = mesh.element(id);
+            let image = index.image(id.element_type(), element.connectivity(), node_map);
+            image.is_some_and(|image| ids.contains(image))
+        })
+    });
+    elements
+        && mesh.node_group_names().all(|name| {
+            let nodes = mesh.node_group(name).unwrap();
+            nodes.iter().all(|&n| nodes.contains(&node_map[n]))
+        })
+}
+
+/// Detects the mirror symmetries of a mesh.
+///
+/// The candidate planes (lines in 2D) go through the centroid of the nodes, orthogonal to the
+/// principal directions of the nodes and to the coordinate axes. A candidate is a symmetry if
+/// the image of each node is a node closer than `tol`, and the image of each element is an
+/// element (see [`mirror_node_map`]). Symmetries are returned in the order of the candidates,
+/// principal directions first, with the node correspondence and whether the groups are
+/// symmetric too.
+///
+/// All the symmetries are found when the principal directions are unique, which is the case
+/// unless the nodes spread equally in several directions: the diagonals of a square are not
+/// candidates, for instance.
+pub fn detect_symmetry(mesh: UMeshView, tol: f64) -> Vec<Symmetry> {
+    trace::span!("detect_symmetry");
+    let space_dim = mesh.space_dimension();
+    let points: Vec<na::Vector3<f64>> = mesh
+        .coords()
+        .rows()
+        .into_iter()
+        .map(|p| point3(&p.to_vec()))
+        .collect();
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let centroid = points.iter().sum::<na::Vector3<f64>>() / points.len() as f64;
+    let covariance: na::Matrix3<f64> = points
+        .iter()
+        .map(|p| (p - centroid) * (p - centroid).transpose())
+        .sum();
+    let eigen = covariance.symmetric_eigen();
+    let principal = eigen.eigenvectors.column_iter().map(|v| v.into_owned());
+    let axes = (0..3).map(na::Vector3::ith_axis).map(|v| v.into_inner());
+
+    let index = MeshIndex::new(&mesh);
+    let mut normals: Vec<na::Vector3<f64>> = Vec::new();
+    let mut res = Vec::new();
+    for mut normal in principal.chain(axes) {
+        // Normals out of the space of the mesh, such as z in 2D, are no mirror
+        normal.as_mut_slice()[space_dim..].fill(0.0);
+        if normal.norm() < 0.5 {
+            continue;
+        }
+        let normal = normal.normalize();
+        if normals
+            .iter()
+            .any(|n| n.dot(&normal).abs() > SAME_PLANE_COSINE)
+        {
+            continue;
+        }
+        normals.push(normal);
+        if let Some(node_map) = mirror(&mesh, &index, centroid, normal, tol) {
+            res.push(Symmetry {
+                origin: centroid.into(),
+                normal: normal.into(),
+                preserves_groups: preserves_groups(&mesh, &index, &node_map),
+                node_map,
+            });
+        }
+    }
+    trace::debug!(symmetries = res.len(), "symmetries detected");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builders::pattern;
+    use crate::mesh::{ElementIds, UMesh};
+    use crate::mesh_examples as me;
+    use approx::assert_abs_diff_eq;
+    use ndarray as nd;
+
+    /// A right triangle, symmetric with respect to no plane, mirrored through `x = 0`.
+    fn arrow() -> UMesh {
+        let coords = nd::arr2(&[[0.5, 0.0], [1.5, 0.0], [0.5, 2.0]]);
+        let mut mesh = UMesh::new(coords.into_shared());
+        mesh.add_regular_block(
+            ElementType::TRI3,
+            nd::arr2(&[[0, 1, 2]]).into_shared(),
+            None,
+        );
+        mesh
+    }
+
+    #[test]
+    fn test_mirror_node_map() {
+        let mesh = me::make_imesh_2d(2);
+        let node_map = mirror_node_map(mesh.view(), &[0.5, 0.0], &[1.0, 0.0], 1e-9).unwrap();
+        assert_eq!(node_map, [2, 1, 0, 5, 4, 3, 8, 7, 6]);
+        // The diagonals of the square are symmetry lines, missed by detect_symmetry
+        let diagonal = mirror_node_map(mesh.view(), &[0.5, 0.5], &[1.0, -1.0], 1e-9).unwrap();
+        assert_eq!(diagonal[1], 3);
+        assert!(mirror_node_map(mesh.view(), &[0.25, 0.0], &[1.0, 0.0], 1e-9).is_none());
+        assert!(mirror_node_map(arrow().view(), &[1.0, 0.0], &[1.0, 0.0], 1e-9).is_none());
+    }
+
+    #[test]
+    fn test_detect_symmetry() {
+        assert!(detect_symmetry(arrow().view(), 1e-9).is_empty());
+        let mirrored =
+            pattern::mirror(&arrow(), &[0.0, 0.0], &[1.0, 0.0], &[], Some(1e-9)).unwrap();
+        let symmetries = detect_symmetry(mirrored.view(), 1e-9);
+        assert_eq!(symmetries.len(), 1);
+        let symmetry = &symmetries[0];
+        assert_abs_diff_eq!(symmetry.normal[0].abs(), 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(symmetry.origin[0], 0.0, epsilon = 1e-12);
+        assert!(symmetry.preserves_groups);
+
+        // A cube has the three planes orthogonal to the axes, the left group breaks one
+        let mut cube = me::make_imesh_3d(2);
+        let mut left = ElementIds::new();
+        for i in [0, 2, 4, 6] {
+            left.add(ElementType::HEX8, i);
+        }
+        cube.set_group("left", &left);
+        let symmetries = detect_symmetry(cube.view(), 1e-9);
+        assert_eq!(symmetries.len(), 3);
+        let broken: Vec<&Symmetry> = symmetries.iter().filter(|s| !s.preserves_groups).collect();
+        assert_eq!(broken.len(), 1);
+        assert_abs_diff_eq!(broken[0].normal[0].abs(), 1.0, epsilon = 1e-12);
+        let node_map = &broken[0].node_map;
+        assert!(node_map.iter().enumerate().all(|(i, &j)| node_map[j] == i));
+    }
+}
@@ -0,0 +1,288 @@
+//! Invariants that mesh algorithms must preserve, checked after an operation.
+//!
+//! Each check returns an error describing the violation, so that it can be asserted in tests of
+//! mefikit or of downstream crates, and in debug builds with
+//! [`debug_verify!`](crate::debug_verify):
+//!
+//! ```ignore
+//! let cracked = crack(mesh.clone(), cut.view());
+//! mefikit::debug_verify!(verify_measure_preserved(mesh.view(), cracked.view(), 1e-12));
+//! ```
+//!
+//! Conservation is checked on the cells of highest dimension, summing every block of that
+//! dimension whatever its element type, up to a relative tolerance.
+
+use ndarray as nd;
+
+use crate::mesh::UMeshView;
+use crate::tools::{compute_boundaries, measure};
+use crate::trace;
+
+/// Panics if an invariant check fails, in debug builds only.
+///
+/// Takes an expression returning a `Result<(), String>`, such as the checks of
+/// [`crate::tools::verify`]. It is not evaluated in release builds.
+#[macro_export]
+macro_rules! debug_verify {
+    ($check:expr) => {
+        if cfg!(debug_assertions) {
+            if let Err(violation) = $check {
+                panic!("Mesh invariant violated: {violation}");
+            }
+        }
+    };
+}
+
+/// Total measure of the cells of highest dimension, summed over all their blocks, 0 for an empty
+/// mesh.
+fn total_measure(mesh: &UMeshView) -> f64 {
+    match mesh.topological_dimension() {
+        Some(dim) => measure(mesh.view(), Some(dim))
+            .values()
+            .map(|m| m.sum())
+            .sum(),
+        None => 0.0,
+    }
+}
+
+/// Checks that two meshes cover the same length, area or volume, as after a renumbering, a
+/// conformization or a cut.
+///
+/// Meshes must have the same topological dimension. The measures of the cells of that dimension
+/// are summed over all their blocks, and both sums must be equal up to `rel_tol` times the
+/// largest of them.
+pub fn verify_measure_preserved(
+    before: UMeshView,
+    after: UMeshView,
+    rel_tol: f64,
+) -> Result<(), String> {
+    trace::span!("verify_measure_preserved");
+    if before.topological_dimension() != after.topological_dimension() {
+        return Err(format!(
+            "Topological dimension changed from {:?} to {:?}.",
+            before.topological_dimension(),
+            after.topological_dimension()
+        ));
+    }
+    let (m_before, m_after) = (total_measure(&before), total_measure(&after));
+    match (m_before - m_after).abs() <= rel_tol * m_before.abs().max(m_after.abs()) {
+        true => Ok(()),
+        false => Err(format!(
+            "Total measure changed from {m_before} to {m_after}."
+        )),
+    }
+}
+
+/// Integral of a cell field over the cells of highest dimension, summed over all their blocks,
+/// and integral of its absolute value, per component.
+fn field_integral(
+    mesh: &UMeshView,
+    name: &str,
+) -> Result<(nd::ArrayD<f64>, nd::ArrayD<f64>), String> {
+    let field = mesh
+        .field(name, None)
+        .ok_or(format!("No cell field named {name} in the mesh."))?;
+    let measures = measure(mesh.view(), None);
+    let mut integral: Option<(nd::ArrayD<f64>, nd::ArrayD<f64>)> = None;
+    for (et, values) in &field.0 {
+        let shape = &values.shape()[1..];
+        let (total, total_abs) =
+            integral.get_or_insert_with(|| (nd::ArrayD::zeros(shape), nd::ArrayD::zeros(shape)));
+        if total.shape() != shape {
+            return Err(format!(
+                "Cell field {name} has components of shape {shape:?} on {et:?}, expected {:?}.",
+                total.shape()
+            ));
+        }
+        for (value, &m) in values.outer_iter().zip(&measures[et]) {
+            total.scaled_add(m, &value);
+            total_abs.zip_mut_with(&value, |a, &v| *a += (m * v).abs());
+        }
+    }
+    integral.ok_or(format!("Cell field {name} is defined on no cell."))
+}
+
+/// Checks that the integral of a cell field is the same on two meshes, as after a conservative
+/// remap.
+///
+/// The field `name` must be defined on every block of highest dimension of both meshes, with the
+/// same components, and is integrated over all these blocks. The integrals of each component
+/// must be equal up to `rel_tol` times the integral of the absolute value of the field, so that
+/// cancellations do not make the check too strict.
+pub fn verify_integral_preserved(
+    before: UMeshView,
+    after: UMeshView,
+    name: &str,
+    rel_tol: f64,
+) -> Result<(), String> {
+    trace::span!("verify_integral_preserved");
+    let (i_before, abs_before) = field_integral(&before, name)?;
+    let (i_after, abs_after) = field_integral(&after, name)?;
+    if i_before.shape() != i_after.shape() {
+        return Err(format!(
+            "Field {name} has components of shape {:?} before and {:?} after.",
+            i_before.shape(),
+            i_after.shape()
+        ));
+    }
+    for (((b, a), abs_b), abs_a) in i_before
+        .iter()
+        .zip(&i_after)
+        .zip(&abs_before)
+        .zip(&abs_after)
+    {
+        if (b - a).abs() > rel_tol * abs_b.max(*abs_a) {
+            return Err(format!(
+                "Integral of field {name} changed from {i_before} to {i_after}."
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the boundary of the boundary of a mesh is empty.
+///
+/// The boundary of a mesh is made of the faces of its cells of highest dimension shared by a
+/// single cell (see [`compute_boundaries`]). It is closed for any mesh whose faces are shared by
+/// at most two cells, so an error reveals faces shared by more cells with inconsistent
+/// neighbours, usually built by a faulty algorithm. Meshes of dimension lower than 2 have no
+/// boundary of boundary and always pass.
+pub fn verify_boundary_of_boundary_empty(mesh: UMeshView) -> Result<(), String> {
+    trace::span!("verify_boundary_of_boundary_empty");
+    match mesh.topological_dimension() {
+        Some(dim) if u8::from(dim) >= 2 => {}
+        _ => return Ok(()),
+    }
+    let boundary = compute_boundaries(mesh, None, None);
+    if boundary.num_elements() == 0 {
+        return Ok(());
+    }
+    let boundary_of_boundary = compute_boundaries(boundary.view(), None, None);
+    trace::debug!(
+        boundary = boundary.num_elements(),
+        boundary_of_boundary = boundary_of_boundary.num_elements(),
+        "boundaries computed"
+    );
+    match boundary_of_boundary.num_elements() {
+        0 => Ok(()),
+        n => Err(format!(
+            "The boundary of the mesh is not closed, its own boundary has {n} elements."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{Dimension, ElementType, UMesh};
+    use crate::mesh_examples as me;
+    use crate::tools::intersect::cut_2d_mesh_with_1d_mesh;
+    use crate::tools::{extrude, merge_nodes, transfer_interface};
+
+    fn with_field(mut mesh: UMesh, value: f64) -> UMesh {
+        let n = mesh.num_elements();
+        let values = nd::Array2::from_elem((n, 2), value);
+        mesh.add_field(ElementType::QUAD4, "f", values.into_dyn().into_shared());
+        mesh
+    }
+
+    #[test]
+    fn test_verify_measure_preserved() {
+        let mesh = me::make_imesh_2d(2);
+        let perturbed = me::make_perturbed_imesh(2, 3, 0.2, 1);
+        assert!(verify_measure_preserved(mesh.view(), perturbed.view(), 1e-12).is_ok());
+        let hanging = me::make_mesh_hanging_nodes();
+        assert!(verify_measure_preserved(mesh.view(), hanging.view(), 1e-12).is_err());
+        let cube = me::make_imesh_3d(1);
+        assert!(verify_measure_preserved(mesh.view(), cube.view(), 1e-12).is_err());
+        debug_verify!(verify_measure_preserved(mesh.view(), mesh.view(), 0.0));
+    }
+
+    #[test]
+    fn test_verify_integral_preserved() {
+        let coarse = with_field(me::make_imesh_2d(2), 1.0);
+        let fine = with_field(me::make_imesh_2d(4), 1.0);
+        assert!(verify_integral_preserved(coarse.view(), fine.view(), "f", 1e-12).is_ok());
+        let doubled = with_field(me::make_imesh_2d(4), 2.0);
+        assert!(verify_integral_preserved(coarse.view(), doubled.view(), "f", 1e-12).is_err());
+        assert!(verify_integral_preserved(coarse.view(), fine.view(), "g", 1e-12).is_err());
+    }
+
+    fn tool(points: &[[f64; 2]]) -> UMesh {
+        let coords = nd::Array2::from_shape_vec((points.len(), 2), points.concat()).unwrap();
+        let mut tool = UMesh::new(coords.into_shared());
+        for i in 1..points.len() {
+            tool.add_element(ElementType::SEG2, &[i - 1, i], None, None);
+        }
+        tool
+    }
+
+    #[test]
+    fn test_cut_preserves_measure_and_integral() {
+        let mesh = with_field(me::make_imesh_2d(3), 1.5);
+        let tool = tool(&[[-0.5, 0.1], [0.5, 0.6], [1.5, 0.45]]);
+        let (cut, _) = cut_2d_mesh_with_1d_mesh(mesh.view(), tool.view(), 1e-9).unwrap();
+        assert!(cut.block(ElementType::PGON).is_some());
+        assert!(verify_measure_preserved(mesh.view(), cut.view(), 1e-12).is_ok());
+        assert!(verify_integral_preserved(mesh.view(), cut.view(), "f", 1e-12).is_ok());
+        assert!(verify_boundary_of_boundary_empty(cut.view()).is_ok());
+    }
+
+    #[test]
+    fn test_merge_nodes_preserves_measure() {
+        let mesh = me::make_imesh_2d(2);
+        let mut moved = mesh.clone();
+        moved.coords_mut().column_mut(0).mapv_inplace(|x| x + 1.0);
+        let mut appended = mesh.clone();
+        appended.append(moved.view());
+        let mut merged = appended.clone();
+        merge_nodes(&mut merged, 1e-9);
+        assert!(merged.used_nodes().len() < appended.used_nodes().len());
+        assert!(verify_measure_preserved(appended.view(), merged.view(), 1e-12).is_ok());
+    }
+
+    #[test]
+    fn test_transfer_preserves_integral() {
+        let src = me::make_imesh_2d(2);
+        let values = nd::arr2(&[[0.0, 1.0], [1.0, 2.0], [2.0, -3.0], [3.0, 4.0]]);
+        let mut field_src = src.clone();
+        field_src.add_field(ElementType::QUAD4, "f", values.into_dyn().into_shared());
+        let field = field_src.field("f", None).unwrap();
+        let mut dst = me::make_imesh_2d(3);
+        let transferred = transfer_interface(&field, src.view(), dst.view()).unwrap();
+        let values = transferred.0[&ElementType::QUAD4].clone();
+        dst.add_field(ElementType::QUAD4, "f", values.into_shared());
+        assert!(verify_integral_preserved(field_src.view(), dst.view(), "f", 1e-12).is_ok());
+    }
+
+    #[test]
+    fn test_extrude_boundary_of_boundary_empty() {
+        let extruded = extrude(me::make_imesh_2d(3).view(), &[0.0, 0.5, 1.0]);
+        assert_eq!(extruded.topological_dimension(), Some(Dimension::D3));
+        assert!(verify_boundary_of_boundary_empty(extruded.view()).is_ok());
+    }
+
+    #[test]
+    fn test_verify_boundary_of_boundary_empty() {
+        let mesh = me::make_imesh_3d(2);
+        assert!(verify_boundary_of_boundary_empty(mesh.view()).is_ok());
+        assert!(verify_boundary_of_boundary_empty(me::make_mesh_hanging_nodes().view()).is_ok());
+
+        // Three triangles share the edge (0, 1), and two of them are glued by a fourth one: the
+        // edge (0, 4) is the only boundary edge at node 0
+        let coords = nd::arr2(&[
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, -1.0, 0.0],
+        ]);
+        let mut mesh = UMesh::new(coords.into_shared());
+        mesh.add_regular_block(
+            ElementType::TRI3,
+            nd::arr2(&[[0, 1, 2], [0, 1, 3], [0, 1, 4], [0, 2, 3]]).to_shared(),
+            None,
+        );
+        assert!(verify_boundary_of_boundary_empty(mesh.view()).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Node numbering conventions of the mesh formats.
+//!
+//! Formats agree on the elements but not on the order of their nodes: MED numbers the corners of
+//! tetrahedra and hexahedra in the opposite orientation, while Gmsh and CGNS number the middle
+//! nodes of quadratic elements differently. mefikit numbers the nodes as VTK does, and the
+//! connectivities read or written in another convention are renumbered with [`permutation`] at
+//! the boundary, see [`convert_convention`].
+//!
+//! HEX21 elements are numbered as the 20 nodes hexahedra of each convention, followed by their
+//! center. Element types missing from a convention keep the VTK numbering.
+//!
+//! Only the MEDCoupling layout of [`crate::medcoupling`] renumbers its connectivities for now:
+//! the files read and written by [`crate::io`] are numbered as VTK, and the connectivities
+//! exchanged with other libraries are converted by the caller.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ndarray as nd;
+
+use crate::mesh::{ElementId, ElementType, FieldLocation, UMesh};
+use crate::trace;
+
+/// A node numbering convention.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Convention {
+    /// The numbering of VTK, used by mefikit.
+    #[default]
+    Vtk,
+    /// The numbering of MED files and MEDCoupling.
+    Med,
+    /// The numbering of Gmsh.
+    Gmsh,
+    /// The numbering of CGNS.
+    Cgns,
+}
+
+impl FromStr for Convention {
+    type Err = String;
+
+    /// Parses a convention name, case insensitively.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "vtk" => Ok(Convention::Vtk),
+            "med" | "medcoupling" => Ok(Convention::Med),
+            "gmsh" | "msh" => Ok(Convention::Gmsh),
+            "cgns" => Ok(Convention::Cgns),
+            _ => Err(format!("Unknown numbering convention: {name}")),
+        }
+    }
+}
+
+impl fmt::Display for Convention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Convention::Vtk => "vtk",
+            Convention::Med => "med",
+            Convention::Gmsh => "gmsh",
+            Convention::Cgns => "cgns",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Node `i` of an element in the VTK numbering is node `order[i]` of the element in
+/// `convention`, or `None` when both numberings are the same.
+fn from_vtk(convention: Convention, et: ElementType) -> Option<&'static [usize]> {
+    use ElementType::*;
+    match (convention, et) {
+        (Convention::Med, TET4) => Some(&[0, 2, 1, 3]),
+        (Convention::Med, TET10) => Some(&[0, 2, 1, 3, 6, 5, 4, 7, 9, 8]),
+        (Convention::Med, HEX8) => Some(&[0, 3, 2, 1, 4, 7, 6, 5]),
+        (Convention::Med, HEX21) => Some(&[
+            0, 3, 2, 1, 4, 7, 6, 5, 11, 10, 9, 8, 15, 14, 13, 12, 16, 19, 18, 17, 20,
+        ]),
+        (Convention::Gmsh, TET10) => Some(&[0, 1, 2, 3, 4, 5, 6, 7, 9, 8]),
+        (Convention::Gmsh, HEX21) => Some(&[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 11, 13, 9, 16, 18, 19, 17, 10, 12, 14, 15, 20,
+        ]),
+        (Convention::Cgns, HEX21) => Some(&[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 16, 17, 18, 19, 12, 13, 14, 15, 20,
+        ]),
+        _ => None,
+    }
+}
+
+/// Returns the permutation renumbering the nodes of an element type from the convention `from`
+/// to the convention `to`: node `i` of an element in `to` is node `order[i]` of the element in
+/// `from`.
+///
+/// Returns `None` when both conventions number the element type the same way, as for all poly
+/// elements.
+pub fn permutation(et: ElementType, from: Convention, to: Convention) -> Option<Vec<usize>> {
+    let n = et.num_nodes()?;
+    let identity: Vec<usize> = (0..n).collect();
+    let from = from_vtk(from, et).unwrap_or(&identity);
+    let to = from_vtk(to, et).unwrap_or(&identity);
+    let mut order = vec![0; n];
+    for (&i, &j) in to.iter().zip(from) {
+        order[i] = j;
+    }
+    (order != identity).then_some(order)
+}
+
+/// Renumbers the nodes of the elements of a mesh from the convention `from` to the convention
+/// `to`.
+///
+/// The elements and their groups are left in place, only their connectivities are renumbered,
+/// with the values of the fields at the element nodes. Meshes are numbered with [`Convention::Vtk`] in mefikit: a mesh built from the
+/// connectivities of another convention is converted with `from` being this convention, and a
+/// mesh is converted to `to` before handing its connectivities to a library using another one.
+pub fn convert_convention(mesh: &mut UMesh, from: Convention, to: Convention) {
+    trace::span!("convert_convention");
+    let types: Vec<ElementType> = mesh.element_types().copied().collect();
+    for et in types {
+        let Some(order) = permutation(et, from, to) else {
+            continue;
+        };
+        for i in 0..mesh.block(et).unwrap().len() {
+            let connectivity = mesh.element_mut(ElementId::new(et, i)).connectivity;
+            let nodes = connectivity.to_vec();
+            for (dst, &src) in connectivity.iter_mut().zip(&order) {
+                *dst = nodes[src];
+            }
+        }
+        let block = mesh.element_blocks.get_mut(&et).unwrap();
+        for (name, values) in block.fields.iter_mut() {
+            if block.field_locations.get(name) == Some(&FieldLocation::ElementNodes) {
+                *values = values.select(nd::Axis(1), &order).into_shared();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::{ElementLike, FieldBase};
+    use crate::mesh_examples as me;
+    use crate::tools::check_orientation;
+
+    #[test]
+    fn test_permutation() {
+        use Convention::*;
+        assert_eq!(
+            permutation(ElementType::TET4, Vtk, Med),
+            Some(vec![0, 2, 1, 3])
+        );
+        assert_eq!(permutation(ElementType::QUAD8, Vtk, Gmsh), None);
+        assert_eq!(permutation(ElementType::PHED, Vtk, Med), None);
+        assert_eq!(permutation(ElementType::HEX21, Cgns, Cgns), None);
+
+        // The middle of the edge from node 3 to node 0 is node 11 in VTK, 9 in Gmsh
+        let gmsh = permutation(ElementType::HEX21, Vtk, Gmsh).unwrap();
+        assert_eq!(gmsh[9], 11);
+        // The middle of the edge from node 0 to node 4 is node 16 in VTK, 12 in CGNS
+        assert_eq!(permutation(ElementType::HEX21, Vtk, Cgns).unwrap()[12], 16);
+        let nodes: Vec<usize> = (0..21).collect();
+        for from in [Med, Gmsh, Cgns] {
+            let to_vtk = permutation(ElementType::HEX21, from, Vtk).unwrap();
+            let from_vtk = permutation(ElementType::HEX21, Vtk, from).unwrap();
+            let round_trip: Vec<usize> = to_vtk.iter().map(|&i| from_vtk[i]).collect();
+            assert_eq!(round_trip, nodes, "{from}");
+        }
+        let med_to_gmsh = permutation(ElementType::TET10, Med, Gmsh).unwrap();
+        assert_eq!(med_to_gmsh, [0, 2, 1, 3, 6, 5, 4, 7, 8, 9]);
+        assert_eq!("MSH".parse(), Ok(Gmsh));
+        assert!("abaqus".parse::<Convention>().is_err());
+    }
+
+    #[test]
+    fn test_convert_convention() {
+        let mut mesh = me::make_imesh_3d(2);
+        let nodes = mesh.elements().next().unwrap().connectivity().to_vec();
+        let hex = mesh.regular_connectivity(ElementType::HEX8).unwrap();
+        let values = hex.mapv(|n| n as f64).into_dyn().into_shared();
+        let field = FieldBase::new([(ElementType::HEX8, values)].into())
+            .with_location(FieldLocation::ElementNodes);
+        mesh.update_field("nodes", field, None);
+        convert_convention(&mut mesh, Convention::Vtk, Convention::Med);
+        let med = mesh.elements().next().unwrap().connectivity().to_vec();
+        assert_eq!(med[1], nodes[3]);
+        // The values at the element nodes follow their nodes
+        let hex = mesh.regular_connectivity(ElementType::HEX8).unwrap();
+        let values = &mesh.block(ElementType::HEX8).unwrap().fields["nodes"];
+        assert_eq!(values, &hex.mapv(|n| n as f64).into_dyn());
+        // MED cells are inverted in the VTK numbering
+        assert_eq!(check_orientation(mesh.view(), 0.0).inverted.len(), 8);
+
+        convert_convention(&mut mesh, Convention::Med, Convention::Vtk);
+        assert_eq!(mesh.elements().next().unwrap().connectivity(), &nodes[..]);
+        assert!(check_orientation(mesh.view(), 0.0).inverted.is_empty());
+    }
+}
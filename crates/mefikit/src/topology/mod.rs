@@ -0,0 +1,15 @@
+//! Topological tables of the element types.
+//!
+//! This module gathers the local numbering of the subentities of the element types, and the
+//! templates splitting them into simplices or refining them, used by
+//! [`crate::element_traits::ElementTopo`] and the shape functions. It also gives the node
+//! numbering conventions of the mesh formats, to renumber connectivities at the boundary with
+//! other libraries.
+
+/// Node numbering conventions of the mesh formats.
+pub mod conventions;
+/// Subdivision and decomposition tables of the regular element types.
+pub mod templates;
+
+pub use conventions::{Convention, convert_convention};
+pub use templates::{Refinement, Template};
@@ -0,0 +1,521 @@
+//! Subdivision and decomposition tables of the regular element types.
+//!
+//! Every table giving elements by the local indices of the nodes of a parent element is defined
+//! here: the subentities (faces, edges and vertices) of each element type, their split into
+//! simplices, their reversal and their uniform refinement. Local indices follow the node ordering of the
+//! connectivity, described in [`crate::geometry::shape`].
+//!
+//! The edges of TET4 and HEX8 are sorted by their first node, while the edges of quadratic
+//! elements follow the order of their middle nodes. The faces of TET10, HEX8 and HEX21 are
+//! oriented towards the inside of the element, while those of TET4 are not consistently
+//! oriented.
+
+use ndarray as nd;
+
+use crate::mesh::{Dimension, ElementType, Regularity};
+
+/// Elements given by the local indices of their nodes in a parent element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Template {
+    /// The type of the elements.
+    pub element_type: ElementType,
+    /// The local indices of the nodes of each element.
+    pub nodes: &'static [&'static [usize]],
+}
+
+impl Template {
+    /// Returns the connectivity of the elements, for a parent element of connectivity
+    /// `connectivity`, with shape `[n_elements, n_nodes]`.
+    pub fn apply(&self, connectivity: &[usize]) -> nd::Array2<usize> {
+        nd::Array2::from_shape_fn((self.nodes.len(), self.nodes[0].len()), |(i, j)| {
+            connectivity[self.nodes[i][j]]
+        })
+    }
+}
+
+/// The uniform refinement of an element into children of the same type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Refinement {
+    /// The nodes added to the element, each being the average of the given local nodes. They are
+    /// numbered after the nodes of the element, in this order.
+    pub new_nodes: &'static [&'static [usize]],
+    /// The children, whose local nodes index the nodes of the element then the new nodes.
+    pub children: Template,
+}
+
+const VERTEX_NODES: &[&[usize]] = &[&[0]];
+const SEG2_NODES: &[&[usize]] = &[&[0, 1]];
+const SEG3_NODES: &[&[usize]] = &[&[0, 1, 2]];
+const SEG4_NODES: &[&[usize]] = &[&[0, 1, 2, 3]];
+const TRI3_NODES: &[&[usize]] = &[&[0, 1, 2]];
+const TRI6_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5]];
+const TRI7_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5, 6]];
+const QUAD4_NODES: &[&[usize]] = &[&[0, 1, 2, 3]];
+const QUAD8_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5, 6, 7]];
+const QUAD9_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5, 6, 7, 8]];
+const TET4_NODES: &[&[usize]] = &[&[0, 1, 2, 3]];
+const TET10_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]];
+const HEX8_NODES: &[&[usize]] = &[&[0, 1, 2, 3, 4, 5, 6, 7]];
+const HEX21_NODES: &[&[usize]] = &[&[
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+]];
+
+const CORNERS_2: &[&[usize]] = &[&[0], &[1]];
+const CORNERS_3: &[&[usize]] = &[&[0], &[1], &[2]];
+const CORNERS_4: &[&[usize]] = &[&[0], &[1], &[2], &[3]];
+const CORNERS_8: &[&[usize]] = &[&[0], &[1], &[2], &[3], &[4], &[5], &[6], &[7]];
+
+const TRI3_EDGES: &[&[usize]] = &[&[0, 1], &[1, 2], &[2, 0]];
+const TRI6_EDGES: &[&[usize]] = &[&[0, 1, 3], &[1, 2, 4], &[2, 0, 5]];
+const QUAD4_EDGES: &[&[usize]] = &[&[0, 1], &[1, 2], &[2, 3], &[3, 0]];
+const QUAD8_EDGES: &[&[usize]] = &[&[0, 1, 4], &[1, 2, 5], &[2, 3, 6], &[3, 0, 7]];
+
+const TET4_FACES: &[&[usize]] = &[&[0, 1, 2], &[1, 2, 3], &[2, 3, 0], &[3, 0, 1]];
+const TET4_EDGES: &[&[usize]] = &[&[0, 1], &[0, 2], &[0, 3], &[1, 2], &[1, 3], &[2, 3]];
+const TET10_FACES: &[&[usize]] = &[
+    &[0, 1, 2, 4, 5, 6],
+    &[0, 3, 1, 7, 8, 4],
+    &[1, 3, 2, 8, 9, 5],
+    &[2, 3, 0, 9, 7, 6],
+];
+const TET10_EDGES: &[&[usize]] = &[
+    &[0, 1, 4],
+    &[1, 2, 5],
+    &[2, 0, 6],
+    &[0, 3, 7],
+    &[1, 3, 8],
+    &[2, 3, 9],
+];
+
+const HEX8_FACES: &[&[usize]] = &[
+    &[0, 1, 2, 3],
+    &[0, 3, 7, 4],
+    &[0, 4, 5, 1],
+    &[1, 5, 6, 2],
+    &[2, 6, 7, 3],
+    &[4, 7, 6, 5],
+];
+const HEX8_EDGES: &[&[usize]] = &[
+    &[0, 1],
+    &[0, 3],
+    &[0, 4],
+    &[1, 2],
+    &[1, 5],
+    &[2, 3],
+    &[2, 6],
+    &[3, 7],
+    &[4, 5],
+    &[4, 7],
+    &[5, 6],
+    &[6, 7],
+];
+// Nodes 8 to 19 of HEX21 are the middles of the edges, as for a 20 nodes hexahedron, and node 20
+// is the center.
+const HEX21_FACES: &[&[usize]] = &[
+    &[0, 1, 2, 3, 8, 9, 10, 11],
+    &[0, 3, 7, 4, 11, 19, 15, 16],
+    &[0, 4, 5, 1, 16, 12, 17, 8],
+    &[1, 5, 6, 2, 17, 13, 18, 9],
+    &[2, 6, 7, 3, 18, 14, 19, 10],
+    &[4, 7, 6, 5, 15, 14, 13, 12],
+];
+const HEX21_EDGES: &[&[usize]] = &[
+    &[0, 1, 8],
+    &[1, 2, 9],
+    &[2, 3, 10],
+    &[3, 0, 11],
+    &[4, 5, 12],
+    &[5, 6, 13],
+    &[6, 7, 14],
+    &[7, 4, 15],
+    &[0, 4, 16],
+    &[1, 5, 17],
+    &[2, 6, 18],
+    &[3, 7, 19],
+];
+
+const QUAD_SIMPLICES: &[&[usize]] = &[&[0, 1, 3], &[2, 3, 1]];
+const HEX_SIMPLICES: &[&[usize]] = &[
+    &[0, 1, 3, 4],
+    &[2, 3, 1, 6],
+    &[7, 6, 4, 3],
+    &[5, 4, 6, 1],
+    &[4, 6, 1, 3],
+];
+
+// The new nodes of the refinements are numbered as the nodes of the quadratic elements: the
+// middles of the edges, then the centers of the faces and of the element.
+const SEG2_NEW_NODES: &[&[usize]] = &[&[0, 1]];
+const SEG2_CHILDREN: &[&[usize]] = &[&[0, 2], &[2, 1]];
+const TRI3_CHILDREN: &[&[usize]] = &[&[0, 3, 5], &[3, 1, 4], &[5, 4, 2], &[3, 4, 5]];
+const QUAD4_NEW_NODES: &[&[usize]] = &[&[0, 1], &[1, 2], &[2, 3], &[3, 0], &[0, 1, 2, 3]];
+const QUAD4_CHILDREN: &[&[usize]] = &[&[0, 4, 8, 7], &[4, 1, 5, 8], &[8, 5, 2, 6], &[7, 8, 6, 3]];
+const TET4_NEW_NODES: &[&[usize]] = &[&[0, 1], &[1, 2], &[2, 0], &[0, 3], &[1, 3], &[2, 3]];
+// The octahedron left by the corners is split around its diagonal from node 6 to node 8.
+const TET4_CHILDREN: &[&[usize]] = &[
+    &[0, 4, 6, 7],
+    &[4, 1, 5, 8],
+    &[6, 5, 2, 9],
+    &[7, 8, 9, 3],
+    &[6, 8, 4, 5],
+    &[6, 8, 5, 9],
+    &[6, 8, 9, 7],
+    &[6, 8, 7, 4],
+];
+const HEX8_NEW_NODES: &[&[usize]] = &[
+    &[0, 1],
+    &[1, 2],
+    &[2, 3],
+    &[3, 0],
+    &[4, 5],
+    &[5, 6],
+    &[6, 7],
+    &[7, 4],
+    &[0, 4],
+    &[1, 5],
+    &[2, 6],
+    &[3, 7],
+    &[0, 1, 2, 3],
+    &[0, 3, 7, 4],
+    &[0, 4, 5, 1],
+    &[1, 5, 6, 2],
+    &[2, 6, 7, 3],
+    &[4, 7, 6, 5],
+    &[0, 1, 2, 3, 4, 5, 6, 7],
+];
+const HEX8_CHILDREN: &[&[usize]] = &[
+    &[0, 8, 20, 11, 16, 22, 26, 21],
+    &[8, 1, 9, 20, 22, 17, 23, 26],
+    &[20, 9, 2, 10, 26, 23, 18, 24],
+    &[11, 20, 10, 3, 21, 26, 24, 19],
+    &[16, 22, 26, 21, 4, 12, 25, 15],
+    &[22, 17, 23, 26, 12, 5, 13, 25],
+    &[26, 23, 18, 24, 25, 13, 6, 14],
+    &[21, 26, 24, 19, 15, 25, 14, 7],
+];
+
+// The reversals keep the first node, except for segments, and map the middle nodes as their
+// edges.
+const SEG2_REVERSAL: &[usize] = &[1, 0];
+const SEG3_REVERSAL: &[usize] = &[1, 0, 2];
+const SEG4_REVERSAL: &[usize] = &[1, 0, 3, 2];
+const TRI3_REVERSAL: &[usize] = &[0, 2, 1];
+const TRI6_REVERSAL: &[usize] = &[0, 2, 1, 5, 4, 3];
+const TRI7_REVERSAL: &[usize] = &[0, 2, 1, 5, 4, 3, 6];
+const QUAD4_REVERSAL: &[usize] = &[0, 3, 2, 1];
+const QUAD8_REVERSAL: &[usize] = &[0, 3, 2, 1, 7, 6, 5, 4];
+const QUAD9_REVERSAL: &[usize] = &[0, 3, 2, 1, 7, 6, 5, 4, 8];
+const TET4_REVERSAL: &[usize] = &[0, 2, 1, 3];
+const TET10_REVERSAL: &[usize] = &[0, 2, 1, 3, 6, 5, 4, 7, 9, 8];
+const HEX8_REVERSAL: &[usize] = &[0, 3, 2, 1, 4, 7, 6, 5];
+const HEX21_REVERSAL: &[usize] = &[
+    0, 3, 2, 1, 4, 7, 6, 5, 11, 10, 9, 8, 15, 14, 13, 12, 16, 19, 18, 17, 20,
+];
+
+fn template(element_type: ElementType, nodes: &'static [&'static [usize]]) -> Option<Template> {
+    Some(Template {
+        element_type,
+        nodes,
+    })
+}
+
+/// Returns the subentities of codimension `codim` of an element type.
+///
+/// The subentity of codimension 0 is the element itself, and those of the dimension of the
+/// element are its corners, as VERTEX elements. Returns `None` for poly elements, whose
+/// subentities depend on their connectivity, and if `codim` is larger than the dimension of the
+/// element.
+pub fn subentities(et: ElementType, codim: Dimension) -> Option<Template> {
+    use ElementType::*;
+    if et.regularity() == Regularity::Poly || codim > et.dimension() {
+        return None;
+    }
+    if codim == Dimension::D0 {
+        let nodes = match et {
+            VERTEX => VERTEX_NODES,
+            SEG2 => SEG2_NODES,
+            SEG3 => SEG3_NODES,
+            SEG4 => SEG4_NODES,
+            TRI3 => TRI3_NODES,
+            TRI6 => TRI6_NODES,
+            TRI7 => TRI7_NODES,
+            QUAD4 => QUAD4_NODES,
+            QUAD8 => QUAD8_NODES,
+            QUAD9 => QUAD9_NODES,
+            TET4 => TET4_NODES,
+            TET10 => TET10_NODES,
+            HEX8 => HEX8_NODES,
+            HEX21 => HEX21_NODES,
+            SPLINE | PGON | PHED => unreachable!(),
+        };
+        return template(et, nodes);
+    }
+    if codim == et.dimension() {
+        let corners = match et {
+            SEG2 | SEG3 | SEG4 => CORNERS_2,
+            TRI3 | TRI6 | TRI7 => CORNERS_3,
+            QUAD4 | QUAD8 | QUAD9 | TET4 | TET10 => CORNERS_4,
+            _ => CORNERS_8,
+        };
+        return template(VERTEX, corners);
+    }
+    match (et, codim) {
+        (TRI3, Dimension::D1) => template(SEG2, TRI3_EDGES),
+        (TRI6 | TRI7, Dimension::D1) => template(SEG3, TRI6_EDGES),
+        (QUAD4, Dimension::D1) => template(SEG2, QUAD4_EDGES),
+        (QUAD8 | QUAD9, Dimension::D1) => template(SEG3, QUAD8_EDGES),
+        (TET4, Dimension::D1) => template(TRI3, TET4_FACES),
+        (TET4, Dimension::D2) => template(SEG2, TET4_EDGES),
+        (TET10, Dimension::D1) => template(TRI6, TET10_FACES),
+        (TET10, Dimension::D2) => template(SEG3, TET10_EDGES),
+        (HEX8, Dimension::D1) => template(QUAD4, HEX8_FACES),
+        (HEX8, Dimension::D2) => template(SEG2, HEX8_EDGES),
+        (HEX21, Dimension::D1) => template(QUAD8, HEX21_FACES),
+        (HEX21, Dimension::D2) => template(SEG3, HEX21_EDGES),
+        _ => None,
+    }
+}
+
+/// Returns the edges of an element type: its 1D subentities, or itself for a 1D element.
+pub fn edges(et: ElementType) -> Option<Template> {
+    let dim = u8::from(et.dimension());
+    subentities(et, dim.checked_sub(1)?.try_into().ok()?)
+}
+
+/// Returns the faces of an element type: its 2D subentities, or itself for a 2D element.
+pub fn faces(et: ElementType) -> Option<Template> {
+    let dim = u8::from(et.dimension());
+    subentities(et, dim.checked_sub(2)?.try_into().ok()?)
+}
+
+/// Returns the decomposition of an element type into simplices of the same dimension.
+///
+/// Only the corners of quadratic elements are used. Quadrangles are split along the diagonal
+/// from node 1 to node 3 and hexahedra into five tetrahedra, which are valid decompositions of
+/// convex elements. The simplices have the orientation of the element. Returns `None` for poly
+/// elements.
+pub fn simplices(et: ElementType) -> Option<Template> {
+    use ElementType::*;
+    match et {
+        VERTEX => template(VERTEX, VERTEX_NODES),
+        SEG2 | SEG3 | SEG4 => template(SEG2, SEG2_NODES),
+        TRI3 | TRI6 | TRI7 => template(TRI3, TRI3_NODES),
+        QUAD4 | QUAD8 | QUAD9 => template(TRI3, QUAD_SIMPLICES),
+        TET4 | TET10 => template(TET4, TET4_NODES),
+        HEX8 | HEX21 => template(TET4, HEX_SIMPLICES),
+        SPLINE | PGON | PHED => None,
+    }
+}
+
+/// Returns the node permutation reversing the orientation of an element type.
+///
+/// The reversed element has the nodes `connectivity[p]` for each `p` of the permutation. Returns
+/// `None` for poly elements, see [`reverse`].
+pub fn reversed(et: ElementType) -> Option<&'static [usize]> {
+    use ElementType::*;
+    match et {
+        VERTEX => Some(&[0]),
+        SEG2 => Some(SEG2_REVERSAL),
+        SEG3 => Some(SEG3_REVERSAL),
+        SEG4 => Some(SEG4_REVERSAL),
+        TRI3 => Some(TRI3_REVERSAL),
+        TRI6 => Some(TRI6_REVERSAL),
+        TRI7 => Some(TRI7_REVERSAL),
+        QUAD4 => Some(QUAD4_REVERSAL),
+        QUAD8 => Some(QUAD8_REVERSAL),
+        QUAD9 => Some(QUAD9_REVERSAL),
+        TET4 => Some(TET4_REVERSAL),
+        TET10 => Some(TET10_REVERSAL),
+        HEX8 => Some(HEX8_REVERSAL),
+        HEX21 => Some(HEX21_REVERSAL),
+        SPLINE | PGON | PHED => None,
+    }
+}
+
+/// Reverses the orientation of an element by renumbering its connectivity in place.
+///
+/// Regular elements are renumbered by [`reversed`], splines and polygons are read backwards and
+/// each face of a polyhedron is reversed.
+pub fn reverse(et: ElementType, connectivity: &mut [usize]) {
+    match (et, reversed(et)) {
+        (_, Some(permutation)) => {
+            let nodes = connectivity.to_vec();
+            for (dst, &src) in connectivity.iter_mut().zip(permutation) {
+                *dst = nodes[src];
+            }
+        }
+        (ElementType::PHED, None) => connectivity
+            .split_mut(|&n| n == usize::MAX)
+            .for_each(|face| face.reverse()),
+        (_, None) => connectivity.reverse(),
+    }
+}
+
+/// Returns the uniform refinement of a linear element type.
+///
+/// Segments are split in 2, triangles and quadrangles in 4, and tetrahedra and hexahedra in 8
+/// children, with the orientation of their parent. The new nodes of triangles, quadrangles and
+/// tetrahedra are numbered as the extra nodes of TRI6, QUAD9 and TET10, and those of hexahedra as
+/// the middle nodes of HEX21 followed by the centers of the faces, then of the element. Returns
+/// `None` for quadratic and poly elements.
+pub fn refinement(et: ElementType) -> Option<Refinement> {
+    use ElementType::*;
+    let (new_nodes, children) = match et {
+        VERTEX => (&[][..], VERTEX_NODES),
+        SEG2 => (SEG2_NEW_NODES, SEG2_CHILDREN),
+        TRI3 => (TRI3_EDGES, TRI3_CHILDREN),
+        QUAD4 => (QUAD4_NEW_NODES, QUAD4_CHILDREN),
+        TET4 => (TET4_NEW_NODES, TET4_CHILDREN),
+        HEX8 => (HEX8_NEW_NODES, HEX8_CHILDREN),
+        _ => return None,
+    };
+    Some(Refinement {
+        new_nodes,
+        children: Template {
+            element_type: et,
+            nodes: children,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::quadrature::ReferenceShape;
+    use crate::geometry::reference_nodes;
+    use ElementType::*;
+    use approx::assert_abs_diff_eq;
+    use nalgebra as na;
+
+    const TYPES: [ElementType; 14] = [
+        VERTEX, SEG2, SEG3, SEG4, TRI3, TRI6, TRI7, QUAD4, QUAD8, QUAD9, TET4, TET10, HEX8, HEX21,
+    ];
+
+    /// Signed measure of the simplices of the elements of `template`, on the nodes `nodes`.
+    fn simplex_measures(template: &Template, nodes: &nd::Array2<f64>) -> Vec<f64> {
+        let dim = nodes.ncols();
+        let simplices = simplices(template.element_type).unwrap();
+        let factorial: f64 = (1..=dim).map(|k| k as f64).product();
+        template
+            .nodes
+            .iter()
+            .flat_map(|element| simplices.apply(element))
+            .collect::<Vec<_>>()
+            .chunks(dim + 1)
+            .map(|s| {
+                let edges =
+                    na::DMatrix::from_fn(dim, dim, |i, j| nodes[[s[i + 1], j]] - nodes[[s[0], j]]);
+                edges.determinant() / factorial
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_subentities() {
+        for et in TYPES {
+            let n_nodes = et.num_nodes().unwrap();
+            let dim = u8::from(et.dimension());
+            for codim in 0..=dim {
+                let t = subentities(et, codim.try_into().unwrap()).unwrap();
+                assert_eq!(u8::from(t.element_type.dimension()), dim - codim, "{et:?}");
+                for nodes in t.nodes {
+                    assert_eq!(Some(nodes.len()), t.element_type.num_nodes(), "{et:?}");
+                    assert!(nodes.iter().all(|&n| n < n_nodes), "{et:?}");
+                }
+            }
+            assert_eq!(subentities(et, Dimension::D0).unwrap().element_type, et);
+        }
+        assert_eq!(edges(HEX21).unwrap().nodes[4], &[4, 5, 12]);
+        assert_eq!(faces(TET10).unwrap().element_type, TRI6);
+        assert_eq!(faces(QUAD8).unwrap().element_type, QUAD8);
+        assert!(edges(VERTEX).is_none());
+        assert!(subentities(PGON, Dimension::D1).is_none());
+        assert!(subentities(TRI3, Dimension::D3).is_none());
+
+        let conn = subentities(QUAD9, Dimension::D1)
+            .unwrap()
+            .apply(&[9, 8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(conn.row(3).to_vec(), vec![6, 9, 2]);
+    }
+
+    #[test]
+    fn test_simplices() {
+        for et in TYPES.into_iter().filter(|&et| et != VERTEX) {
+            let nodes = reference_nodes(et).unwrap();
+            let measures = simplex_measures(&subentities(et, Dimension::D0).unwrap(), &nodes);
+            assert!(measures.iter().all(|&m| m > 0.0), "{et:?}");
+            let measure = ReferenceShape::of(et).unwrap().measure();
+            assert_abs_diff_eq!(measures.iter().sum::<f64>(), measure, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_reversed() {
+        for et in TYPES.into_iter().filter(|&et| et != VERTEX) {
+            let permutation = reversed(et).unwrap();
+            let nodes = reference_nodes(et)
+                .unwrap()
+                .select(nd::Axis(0), permutation);
+            let measures = simplex_measures(&subentities(et, Dimension::D0).unwrap(), &nodes);
+            assert!(measures.iter().all(|&m| m < 0.0), "{et:?}");
+            // The middle nodes are still the middles of the reversed edges
+            for edge in edges(et).unwrap().nodes.iter().filter(|e| e.len() == 3) {
+                let middle = (&nodes.row(edge[0]) + &nodes.row(edge[1])) / 2.0;
+                for (&x, &y) in middle.iter().zip(nodes.row(edge[2])) {
+                    assert_abs_diff_eq!(x, y, epsilon = 1e-12);
+                }
+            }
+        }
+        assert!(reversed(PGON).is_none());
+
+        let mut quad = [5, 6, 7, 8];
+        reverse(QUAD4, &mut quad);
+        assert_eq!(quad, [5, 8, 7, 6]);
+        let mut pgon = [0, 1, 2, 3, 4];
+        reverse(PGON, &mut pgon);
+        assert_eq!(pgon, [4, 3, 2, 1, 0]);
+        let mut phed = [0, 1, 2, usize::MAX, 0, 3, 1];
+        reverse(PHED, &mut phed);
+        assert_eq!(phed, [2, 1, 0, usize::MAX, 1, 3, 0]);
+    }
+
+    #[test]
+    fn test_refinement() {
+        let quadratic = [
+            (SEG2, SEG3),
+            (TRI3, TRI6),
+            (QUAD4, QUAD9),
+            (TET4, TET10),
+            (HEX8, HEX21),
+        ];
+        for (et, quadratic) in quadratic {
+            let refinement = refinement(et).unwrap();
+            let mut nodes = reference_nodes(et).unwrap();
+            for new in refinement.new_nodes {
+                let sum = new
+                    .iter()
+                    .fold(nd::Array1::zeros(nodes.ncols()), |sum, &n| {
+                        sum + nodes.row(n)
+                    });
+                let new = sum / new.len() as f64;
+                nodes.push_row(new.view()).unwrap();
+            }
+            // The new nodes are the middle nodes of the quadratic element, HEX21 has no face
+            // centers
+            let expected = reference_nodes(quadratic).unwrap();
+            let n_common = if et == HEX8 { 20 } else { expected.nrows() };
+            let common = nodes.slice(nd::s![..n_common, ..]);
+            for (&x, &y) in common.iter().zip(expected.slice(nd::s![..n_common, ..])) {
+                assert_abs_diff_eq!(x, y, epsilon = 1e-12);
+            }
+
+            // The children have the orientation of the element and fill it
+            let measures = simplex_measures(&refinement.children, &nodes);
+            assert!(measures.iter().all(|&m| m > 0.0), "{et:?}");
+            let measure = ReferenceShape::of(et).unwrap().measure();
+            assert_abs_diff_eq!(measures.iter().sum::<f64>(), measure, epsilon = 1e-12);
+        }
+        assert!(refinement(TRI6).is_none());
+        assert!(refinement(PHED).is_none());
+    }
+}
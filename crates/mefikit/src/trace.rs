@@ -0,0 +1,28 @@
+//! Instrumentation of the algorithms with the `tracing` crate.
+//!
+//! With the `tracing` feature, the main phases of the algorithms (tree build, narrow phase, node
+//! merge, etc.) are wrapped in debug spans, and debug events count the elements processed and the
+//! nodes created. Without it, these macros expand to nothing and their arguments are not
+//! evaluated.
+
+/// Enters a debug span until the end of the current scope.
+///
+/// Takes the arguments of `tracing::debug_span!`.
+macro_rules! span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+/// Emits a debug event.
+///
+/// Takes the arguments of `tracing::debug!`.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub(crate) use {debug, span};
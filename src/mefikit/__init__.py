@@ -2,7 +2,7 @@ import importlib.util
 
 from . import data as data
 from . import io
-from .mefipy import UMesh, build_cmesh, sel
+from .mefipy import MefikitError, MefikitIOError, UMesh, build_cmesh, builders, sel
 
 
 def has(name: str) -> bool:
@@ -13,4 +13,12 @@ if has("meshio") and has("medcoupling") and has("pyvista"):
     io.install_conversions()
 del io
 
-__all__ = ("UMesh", "build_cmesh", "data", "sel")
+__all__ = (
+    "MefikitError",
+    "MefikitIOError",
+    "UMesh",
+    "build_cmesh",
+    "builders",
+    "data",
+    "sel",
+)
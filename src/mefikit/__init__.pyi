@@ -1,4 +1,4 @@
-from collections.abc import Sequence
+from collections.abc import Iterator, Sequence
 from typing import TypeAlias
 
 import medcoupling as mc
@@ -14,15 +14,48 @@ Array2F: TypeAlias = npt.NDArray[np.float64]
 Array1U: TypeAlias = npt.NDArray[np.uintp]
 Array2U: TypeAlias = npt.NDArray[np.uintp]
 ArrayDynF: TypeAlias = npt.NDArray[np.float64]
+ArrayDynI: TypeAlias = npt.NDArray[np.int64]
+ArrayDynB: TypeAlias = npt.NDArray[np.bool_]
+
+# Integer, boolean or categorical (one label per element) values.
+TypedFieldData: TypeAlias = ArrayDynI | ArrayDynB | list[str]
 
 # Connectivity:
 # - Regular: (n_elem, n_nodes_per_elem)
 # - Poly: (data, offsets)
 Connectivity: TypeAlias = Array2U | tuple[Array1U, Array1U]
 
+# MEDCoupling layout: coords, levels (mesh_dimension, connectivity, connectivity_index,
+# families and fields of each dimension), node_families and groups.
+McArrays: TypeAlias = dict
+
 class PySelection: ...
 class PyField: ...
 
+class Element:
+    """An element of a mesh, read from the mesh on each access."""
+
+    @property
+    def element_type(self) -> str: ...
+    @property
+    def index(self) -> int: ...
+    @property
+    def connectivity(self) -> Array1U: ...
+    @property
+    def coords(self) -> Array2F: ...
+    @property
+    def groups(self) -> list[str]: ...
+    @property
+    def family(self) -> int: ...
+    def centroid(self) -> Array1F: ...
+    def measure(self) -> float: ...
+
+class MefikitError(ValueError):
+    """Invalid input: unknown element type, invalid dimension, bad selection, etc."""
+
+class MefikitIOError(OSError):
+    """Failure to read or write a mesh file."""
+
 def build_cmesh(*args: Sequence[Sequence[float]]) -> UMesh: ...
 
 class UMesh:
@@ -33,15 +66,39 @@ class UMesh:
     def coords(self) -> Array2F: ...
     def block_types(self) -> list[str]: ...
     def blocks(self) -> dict[str, Connectivity]: ...
-    def fields(self) -> dict[str, dict[str, ArrayDynF]]: ...
+    def element(self, et: str, index: int) -> Element: ...
+    def __iter__(self) -> Iterator[Element]: ...
+    def node_groups(self) -> dict[str, Array1U]: ...
+    def set_node_group(self, name: str, nodes: Sequence[int]) -> None: ...
+    def fields(self, copy: bool = ...) -> dict[str, dict[str, ArrayDynF]]: ...
+    def field(
+        self, name: str, dim: int | None = ..., copy: bool = ...
+    ) -> dict[str, ArrayDynF] | None: ...
+    def set_field(self, name: str, et: str, values: ArrayDynF) -> None: ...
+    def typed_fields(self) -> dict[str, dict[str, TypedFieldData]]: ...
+    def update_typed_field(self, name: str, field: dict[str, TypedFieldData]) -> None: ...
 
     # --- serialization ---
 
     def to_json(self) -> str: ...
     def to_json_pretty(self) -> str: ...
     @staticmethod
-    def read(path: str) -> UMesh: ...
-    def write(self, path: str) -> None: ...
+    def read(
+        path: str,
+        format: str | None = ...,
+        fields: Sequence[str] | None = ...,
+        time_step: int | None = ...,
+    ) -> UMesh: ...
+    def write(
+        self,
+        path: str,
+        format: str | None = ...,
+        compression: int | None = ...,
+        fields: Sequence[str] | None = ...,
+    ) -> None: ...
+    def to_mc_arrays(self) -> McArrays: ...
+    @staticmethod
+    def from_mc_arrays(arrays: McArrays) -> UMesh: ...
 
     # --- mesh construction ---
 
@@ -51,6 +108,10 @@ class UMesh:
         block: Array2U,
         fields: dict[str, ArrayDynF] | None = ...,
     ) -> None: ...
+    def add_poly_block(self, et: str, data: Array1U, offsets: Array1U) -> None: ...
+    def add_element(
+        self, et: str, connectivity: Sequence[int], family: int | None = ...
+    ) -> tuple[str, int]: ...
 
     # --- topology operations ---
 
@@ -95,15 +156,54 @@ class UMesh:
     def extrude_parallel(self, along: Array2F) -> UMesh: ...
     def extrude_curv(self, along: Array2F) -> UMesh: ...
 
+    # --- intersection & analysis ---
+
+    def cut(
+        self, tool: UMesh, tol: float = ...
+    ) -> tuple[UMesh, dict[str, list[tuple[str, int]]]]: ...
+    def cut_edges(
+        self, tool: UMesh, tol: float = ...
+    ) -> tuple[UMesh, dict[str, list[tuple[str, int]]]]: ...
+    def intersect_1d(self, other: UMesh, tol: float = ...) -> UMesh: ...
+    def fuse_1d(self, other: UMesh, tol: float = ...) -> UMesh: ...
+    def detect_overlaps(
+        self, other: UMesh | None = ..., tol: float = ...
+    ) -> list[tuple[tuple[str, int], tuple[str, int], float]]: ...
+    def centroids(self, dim: int | None = ...) -> dict[str, Array2F]: ...
+    def grow_region(
+        self,
+        seeds: dict[str, Array1U],
+        dim: int | None = ...,
+        group: str | None = ...,
+        same_groups: bool = ...,
+        same_family: bool = ...,
+        field_range: tuple[str, float, float] | None = ...,
+        max_angle: float | None = ...,
+    ) -> dict[str, Array1U]: ...
+    def project_nodes(
+        self,
+        nodes: Sequence[int],
+        target: UMesh,
+        max_distance: float = ...,
+        feature_angle: float | None = ...,
+        feature_distance: float = ...,
+    ) -> tuple[UMesh, Array1U]: ...
+
     # --- selection & evaluation ---
 
-    def select(self, expr: PySelection, with_fields: bool = ...) -> UMesh: ...
+    def select(self, expr: PySelection | str, with_fields: bool = ...) -> UMesh: ...
+    def select_ids(self, expr: PySelection | str) -> dict[str, Array1U]: ...
+    def select_nodes(self, expr: PySelection | str) -> Array1U: ...
+    def select_with_ids(
+        self, expr: PySelection | str, with_fields: bool = ...
+    ) -> tuple[dict[str, Array1U], UMesh]: ...
     def eval(self, expr: PyField) -> dict[str, ArrayDynF]: ...
     def eval_update(self, name: str, expr: PyField) -> None: ...
 
     # --- misc ---
 
     def __str__(self) -> str: ...
+    def __repr__(self) -> str: ...
 
     # --- conversions ---
 
@@ -111,4 +211,12 @@ class UMesh:
         self, dim: str | int | None = None, with_fields: Sequence[str] | bool = True
     ) -> pv.UnstructuredGrid: ...
     def to_mc(self, lev: int | None = None) -> mc.MEDCouplingUMesh: ...
-    def to_meshio(self) -> mio.Mesh: ...
+    def to_mc_fields(
+        self, lev: int | None = None
+    ) -> dict[str, mc.MEDCouplingFieldDouble]: ...
+    def to_mc_file(self, name: str = "mf_UMesh") -> mc.MEDFileUMesh: ...
+    @staticmethod
+    def from_mc(m: mc.MEDCouplingUMesh | mc.MEDFileUMesh) -> UMesh: ...
+    def to_meshio(self, with_fields: Sequence[str] | bool = True) -> mio.Mesh: ...
+    @staticmethod
+    def from_meshio(m: mio.Mesh) -> UMesh: ...
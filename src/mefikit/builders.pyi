@@ -0,0 +1,60 @@
+from collections.abc import Callable, Mapping, Sequence
+from typing import Literal
+
+import numpy as np
+import numpy.typing as npt
+
+from . import UMesh
+
+Point3 = tuple[float, float, float] | Sequence[float]
+FieldKind = Literal["scalar", "vector", "pseudovector", "tensor"]
+
+def disk(radius: float, n_circ: int = ..., n_radial: int = ...) -> UMesh: ...
+def annulus(
+    r_in: float, r_out: float, n_circ: int = ..., n_radial: int = ...
+) -> UMesh: ...
+def cylinder_shell(
+    radius: float, height: float, n_circ: int = ..., n_height: int = ...
+) -> UMesh: ...
+def cylinder(
+    radius: float,
+    height: float,
+    n_circ: int = ...,
+    n_radial: int = ...,
+    n_height: int = ...,
+) -> UMesh: ...
+def sphere(radius: float, subdivisions: int = ...) -> UMesh: ...
+def spherical_shell(
+    r_in: float, r_out: float, n: int = ..., n_layers: int = ...
+) -> UMesh: ...
+def box_surface(min: Point3, max: Point3, n: Sequence[int] = ...) -> UMesh: ...
+def voxels(
+    image: npt.NDArray[np.uint8] | npt.NDArray[np.float64],
+    threshold: float,
+    spacing: Point3 = ...,
+    origin: Point3 = ...,
+) -> UMesh: ...
+def implicit(
+    f: Callable[[float, float, float], float],
+    bbox: tuple[Point3, Point3],
+    resolution: Sequence[int] = ...,
+    solid: bool = ...,
+) -> UMesh: ...
+def linear_pattern(
+    mesh: UMesh, direction: Sequence[float], n: int, merge_eps: float | None = ...
+) -> UMesh: ...
+def circular_pattern(
+    mesh: UMesh,
+    origin: Sequence[float],
+    axis: Point3,
+    n: int,
+    merge_eps: float | None = ...,
+    fields: Mapping[str, FieldKind] | None = ...,
+) -> UMesh: ...
+def mirror(
+    mesh: UMesh,
+    origin: Sequence[float],
+    normal: Sequence[float],
+    merge_eps: float | None = ...,
+    fields: Mapping[str, FieldKind] | None = ...,
+) -> UMesh: ...
@@ -1,36 +1,44 @@
 from __future__ import annotations
 
+import warnings
 from collections.abc import Sequence
 
 import numpy as np
-import numpy.typing as npt
 
 type_order = [
     "VERTEX",
     "SEG2",
     "SEG3",
+    "SEG4",
+    "SPLINE",
     "TRI3",
     "TRI6",
+    "TRI7",
     "QUAD4",
     "QUAD8",
+    "QUAD9",
+    "PGON",
     "TET4",
     "TET10",
     "HEX8",
-    "HEX20",
+    "HEX21",
+    "PHED",
 ]
 
 meshio_to_mefikit_type = {
     "vertex": "VERTEX",
     "line": "SEG2",
     "line3": "SEG3",
+    "line4": "SEG4",
     "triangle": "TRI3",
     "triangle6": "TRI6",
+    "triangle7": "TRI7",
     "quad": "QUAD4",
     "quad8": "QUAD8",
+    "quad9": "QUAD9",
     "tetra": "TET4",
     "tetra10": "TET10",
     "hexahedron": "HEX8",
-    "hexahedron20": "HEX20",
 }
 mefikit_to_meshio_type = {v: k for k, v in meshio_to_mefikit_type.items()}
 
@@ -39,14 +47,20 @@ mf_types_dim = {
     "VERTEX": 0,
     "SEG2": 1,
     "SEG3": 1,
+    "SEG4": 1,
+    "SPLINE": 1,
     "TRI3": 2,
     "TRI6": 2,
+    "TRI7": 2,
     "QUAD4": 2,
     "QUAD8": 2,
+    "QUAD9": 2,
+    "PGON": 2,
     "TET4": 3,
     "TET10": 3,
     "HEX8": 3,
-    "HEX20": 3,
+    "HEX21": 3,
+    "PHED": 3,
 }
 
 
@@ -54,85 +68,279 @@ mf_types_num_node = {
     "VERTEX": 1,
     "SEG2": 2,
     "SEG3": 3,
+    "SEG4": 4,
     "TRI3": 3,
     "TRI6": 6,
+    "TRI7": 7,
     "QUAD4": 4,
     "QUAD8": 8,
+    "QUAD9": 9,
     "TET4": 4,
     "TET10": 10,
     "HEX8": 8,
-    "HEX20": 20,
+    "HEX21": 21,
 }
 
+# Separator of the faces in the connectivity of PHED elements
+FACE_SEPARATOR = np.iinfo(np.uintp).max
+
+
+def _selected_fields(mesh, with_fields: Sequence[str] | bool) -> dict:
+    if not with_fields:
+        return {}
+    fields = mesh.fields()
+    if not isinstance(with_fields, bool):
+        fields = {n: f for n, f in fields.items() if n in with_fields}
+    return fields
+
+
+def _field_values(field: dict, et: str, n_elem: int) -> np.ndarray:
+    """Values of a field on a block, NaN if the field is not defined on it."""
+    if et in field:
+        return field[et]
+    shape = next(iter(field.values())).shape[1:]
+    return np.full((n_elem, *shape), np.nan)
+
+
+def _node_groups_masks(mesh) -> dict[str, np.ndarray]:
+    """Node groups as point data masks, 1 for the nodes of the group and 0 elsewhere."""
+    n_nodes = mesh.coords().shape[0]
+    masks = {}
+    for name, nodes in mesh.node_groups().items():
+        mask = np.zeros(n_nodes, dtype=np.uint8)
+        mask[nodes] = 1
+        masks[name] = mask
+    return masks
+
+
+def _poly_cells(data: np.ndarray, offsets: np.ndarray) -> list[np.ndarray]:
+    """Connectivities of the elements of a poly block."""
+    return np.split(data, offsets[:-1])
+
+
+def _phed_faces(conn: np.ndarray) -> list[np.ndarray]:
+    """Faces of a PHED connectivity."""
+    faces = np.split(conn, np.flatnonzero(conn == FACE_SEPARATOR))
+    faces = [f[f != FACE_SEPARATOR] for f in faces]
+    return [f for f in faces if len(f)]
+
+
+def _meshio_blocks(et: str, conn):
+    """meshio cell blocks of a mefikit block, with the indices of their elements in it.
+
+    PGON and PHED blocks give one cell block per number of nodes, as meshio expects.
+    """
+    if et == "PGON":
+        cells = _poly_cells(*conn)
+        sizes = np.array([len(c) for c in cells])
+        for n in np.unique(sizes):
+            ids = np.flatnonzero(sizes == n)
+            yield "polygon", np.stack([cells[i] for i in ids]), ids
+    elif et == "PHED":
+        cells = [_phed_faces(c) for c in _poly_cells(*conn)]
+        sizes = np.array([len(np.unique(np.concatenate(c))) for c in cells])
+        for n in np.unique(sizes):
+            ids = np.flatnonzero(sizes == n)
+            yield f"polyhedron{n}", [cells[i] for i in ids], ids
+    elif et in mefikit_to_meshio_type:
+        yield mefikit_to_meshio_type[et], conn, np.arange(len(conn))
+    else:
+        raise ValueError(f"{et} elements have no meshio cell type")
+
+
+def _mefikit_type(meshio_type: str) -> str:
+    """mefikit element type of a meshio cell type."""
+    if meshio_type in meshio_to_mefikit_type:
+        return meshio_to_mefikit_type[meshio_type]
+    if meshio_type.startswith("polygon"):
+        return "PGON"
+    if meshio_type.startswith("polyhedron"):
+        return "PHED"
+    raise ValueError(f"Unsupported meshio cell type: {meshio_type!r}")
+
+
+def _poly_block(et: str, cells: list) -> tuple[np.ndarray, np.ndarray]:
+    """Data and offsets of a poly block, from meshio cells."""
+    if et == "PHED":
+        sep = [FACE_SEPARATOR]
+        cells = [
+            np.concatenate([np.append(np.asarray(f, np.uintp), sep) for f in c])
+            for c in cells
+        ]
+    conns = [np.asarray(c, dtype=np.uintp) for c in cells]
+    offsets = np.cumsum([len(c) for c in conns], dtype=np.uintp)
+    return np.concatenate(conns), offsets
+
 
 def install_conversions():
     import medcoupling as mc
     import meshio
     import pyvista as pv
 
-    from mefikit import UMesh
-
-    def to_meshio(self: UMesh) -> meshio.Mesh:
-        blocks = self.blocks()  # type: dict
-
-        cells = {mefikit_to_meshio_type[t]: b for (t, b) in blocks.items()}
+    from mefikit import MefikitError, UMesh
 
+    def to_meshio(
+        self: UMesh, with_fields: Sequence[str] | bool = True
+    ) -> meshio.Mesh:
+        blocks = self.blocks()
+        fields = _selected_fields(self, with_fields)
+        cells = []
+        cell_data = {f: [] for f in fields}
+        for et in type_order:
+            if et not in blocks:
+                continue
+            conn = blocks[et]
+            n_elem = len(conn[1]) if isinstance(conn, tuple) else len(conn)
+            try:
+                meshio_blocks = list(_meshio_blocks(et, conn))
+            except ValueError as e:
+                raise MefikitError(str(e)) from None
+            for meshio_type, data, ids in meshio_blocks:
+                cells.append((meshio_type, data))
+                for f, v in fields.items():
+                    cell_data[f].append(_field_values(v, et, n_elem)[ids])
         return meshio.Mesh(
             self.coords(),
             cells,
+            point_data=_node_groups_masks(self),
+            cell_data=cell_data,
+            point_sets=self.node_groups(),
         )
 
-    def to_mc(self: UMesh, lev=None) -> mc.MEDCouplingUMesh:
-        mf_types_mc_id = {
-            "VERTEX": 0,
-            "SEG2": 1,
-            "SEG3": 2,
-            "TRI3": 3,
-            "TRI6": 6,
-            "QUAD4": 4,
-            "QUAD8": 8,
-            "TET4": 14,
-            "TET10": 20,
-            "HEX8": 18,
-            "HEX20": 30,
-        }
-
-        def _mf_reg_to_mc_connectivity(et: str, conn: npt.NDArray[np.uintp]):
-            num_nodes = conn.shape[1]
-            n_elem = conn.shape[0]
-
-            new_connectivity = np.insert(
-                conn.flatten(), np.arange(n_elem) * num_nodes, mf_types_mc_id[et]
-            )
-            offsets = np.arange(n_elem, dtype=int) * (num_nodes + 1)
-            return new_connectivity, offsets
-
-        blocks = self.blocks()
-        coords = self.coords()
+    def from_meshio(m: meshio.Mesh) -> UMesh:
+        points = np.asarray(m.points, dtype=float)
+        res = UMesh(points)
+        conns = {}
+        cell_data = {}
+        for i, block in enumerate(m.cells):
+            try:
+                et = _mefikit_type(block.type)
+            except ValueError as e:
+                raise MefikitError(str(e)) from None
+            if et in ("PGON", "PHED"):
+                conns.setdefault(et, []).extend(block.data)
+            else:
+                conns.setdefault(et, []).append(
+                    np.asarray(block.data, dtype=np.uintp)
+                )
+            for f, values in m.cell_data.items():
+                values = np.asarray(values[i], dtype=float)
+                cell_data.setdefault(et, {}).setdefault(f, []).append(values)
+        for et, conn in conns.items():
+            fields = {f: np.concatenate(v) for f, v in cell_data.get(et, {}).items()}
+            if et in ("PGON", "PHED"):
+                res.add_poly_block(et, *_poly_block(et, conn))
+                for f, values in fields.items():
+                    res.set_field(f, et, values)
+            else:
+                res.add_regular_block(et, np.concatenate(conn), fields)
+        # Node groups are read from the point sets only, their masks are skipped
+        for name, nodes in m.point_sets.items():
+            res.set_node_group(name, np.asarray(nodes, dtype=int).tolist())
+        for name in m.point_data:
+            if name not in m.point_sets:
+                warnings.warn(
+                    f"Point data {name!r} is dropped: mefikit has no node fields",
+                    stacklevel=2,
+                )
+        return res
 
-        mc_conn = np.array([], dtype=int)
-        mc_offset = np.array([], dtype=int)
+    def _mc_level(coords: mc.DataArrayDouble, level: dict, name: str):
+        res = mc.MEDCouplingUMesh(name, int(level["mesh_dimension"]))
+        res.setCoords(coords)
+        res.setConnectivity(
+            mc.DataArrayInt(level["connectivity"]),
+            mc.DataArrayInt(level["connectivity_index"]),
+        )
+        return res
 
+    def _mc_level_of_dim(arrays: dict, lev: int | None) -> dict:
+        levels = {level["mesh_dimension"]: level for level in arrays["levels"]}
         if lev is None:
-            lev = max(mf_types_dim[et] for et in blocks)
+            lev = max(levels)
+        if lev not in levels:
+            raise MefikitError(f"The mesh has no element of dimension {lev}")
+        return levels[lev]
 
-        for et in type_order:
-            if et not in blocks or mf_types_dim[et] != lev:
-                continue
+    def to_mc(self: UMesh, lev=None) -> mc.MEDCouplingUMesh:
+        arrays = self.to_mc_arrays()
+        level = _mc_level_of_dim(arrays, lev)
+        return _mc_level(mc.DataArrayDouble(arrays["coords"]), level, "mf_UMesh")
+
+    def to_mc_fields(self: UMesh, lev=None) -> dict[str, mc.MEDCouplingFieldDouble]:
+        arrays = self.to_mc_arrays()
+        level = _mc_level_of_dim(arrays, lev)
+        mesh = _mc_level(mc.DataArrayDouble(arrays["coords"]), level, "mf_UMesh")
+        res = {}
+        for name, values in level["fields"].items():
+            field = mc.MEDCouplingFieldDouble(mc.ON_CELLS, mc.ONE_TIME)
+            field.setName(name)
+            field.setMesh(mesh)
+            field.setArray(mc.DataArrayDouble(values))
+            field.checkConsistencyLight()
+            res[name] = field
+        return res
 
-            conn, offset = _mf_reg_to_mc_connectivity(et, blocks[et])
-            mc_conn = np.hstack((mc_conn, conn), dtype=int)
-            # offsets the offset
-            if len(mc_offset) > 0:
-                offset += mc_offset[-1]
-                offset = offset[1:]
-            mc_offset = np.r_[mc_offset, offset]
-        res = mc.MEDCouplingUMesh()
-        res.setCoords(mc.DataArrayDouble(coords))
-        res.setConnectivity(mc.DataArrayInt(mc_conn), mc.DataArrayInt(mc_offset))
-        res.setName("mf_UMesh")
+    def to_mc_file(self: UMesh, name: str = "mf_UMesh") -> mc.MEDFileUMesh:
+        arrays = self.to_mc_arrays()
+        coords = mc.DataArrayDouble(arrays["coords"])
+        res = mc.MEDFileUMesh()
+        res.setName(name)
+        res.setCoords(coords)
+        top = max((lv["mesh_dimension"] for lv in arrays["levels"]), default=0)
+        for level in arrays["levels"]:
+            rel = level["mesh_dimension"] - top
+            res.setMeshAtLevel(rel, _mc_level(coords, level, name))
+            res.setFamilyFieldArr(rel, mc.DataArrayInt(level["families"]))
+        res.setFamilyFieldArr(1, mc.DataArrayInt(arrays["node_families"]))
+        res.addFamily("FAMILLE_ZERO", 0)
+        for f in sorted({int(f) for ids in arrays["groups"].values() for f in ids}):
+            res.addFamily(f"FAM_{f}", f)
+        for group, ids in arrays["groups"].items():
+            res.setFamiliesIdsOnGroup(group, [int(f) for f in ids])
         return res
 
+    def _mc_ids(array) -> np.ndarray:
+        if array is None:
+            return np.zeros(0, dtype=np.int64)
+        return np.asarray(array.toNumPyArray(), dtype=np.int64).ravel()
+
+    def _mc_level_arrays(m: mc.MEDCouplingUMesh, families) -> dict:
+        return {
+            "mesh_dimension": m.getMeshDimension(),
+            "connectivity": _mc_ids(m.getNodalConnectivity()),
+            "connectivity_index": _mc_ids(m.getNodalConnectivityIndex()),
+            "families": _mc_ids(families),
+            "fields": {},
+        }
+
+    def from_mc(m: mc.MEDCouplingUMesh | mc.MEDFileUMesh) -> UMesh:
+        coords = m.getCoords()
+        if isinstance(m, mc.MEDFileUMesh):
+            levels = [
+                _mc_level_arrays(m.getMeshAtLevel(rel), m.getFamilyFieldAtLevel(rel))
+                for rel in m.getNonEmptyLevels()
+            ]
+            node_families = _mc_ids(m.getFamilyFieldAtLevel(1))
+            groups = {
+                g: [int(f) for f in m.getFamiliesIdsOnGroup(g)]
+                for g in m.getGroupsNames()
+            }
+        else:
+            levels = [_mc_level_arrays(m, None)]
+            node_families = _mc_ids(None)
+            groups = {}
+        n_components = coords.getNumberOfComponents()
+        coords = np.asarray(coords.toNumPyArray(), dtype=float)
+        return UMesh.from_mc_arrays(
+            {
+                "coords": coords.reshape(-1, n_components),
+                "levels": levels,
+                "node_families": node_families,
+                "groups": groups,
+            }
+        )
+
     def to_pyvista(
         self: UMesh,
         dim: str | int | None = None,
@@ -140,30 +348,36 @@ def install_conversions():
     ) -> pv.UnstructuredGrid:
         blocks = self.blocks()
         coords = self.coords()
-        if not with_fields:
-            fields = {}
-        else:
-            fields = self.fields()
-            if not isinstance(with_fields, bool):
-                fields = {n: f for n, f in fields.items() if n in with_fields}
+        fields = _selected_fields(self, with_fields)
 
         mf_types_to_pv = {
             "VERTEX": pv.CellType.VERTEX,
             "SEG2": pv.CellType.LINE,
             "SEG3": pv.CellType.QUADRATIC_EDGE,
+            "SEG4": pv.CellType.CUBIC_LINE,
             "TRI3": pv.CellType.TRIANGLE,
             "TRI6": pv.CellType.QUADRATIC_TRIANGLE,
+            "TRI7": pv.CellType.BIQUADRATIC_TRIANGLE,
             "QUAD4": pv.CellType.QUAD,
             "QUAD8": pv.CellType.QUADRATIC_QUAD,
+            "QUAD9": pv.CellType.BIQUADRATIC_QUAD,
+            "PGON": pv.CellType.POLYGON,
             "TET4": pv.CellType.TETRA,
             "TET10": pv.CellType.QUADRATIC_TETRA,
             "HEX8": pv.CellType.HEXAHEDRON,
-            "HEX20": pv.CellType.QUADRATIC_HEXAHEDRON,
         }
 
         if dim is None:
             dim = max(mf_types_dim[et] for et in blocks)
 
+        def _mf_poly_to_pv_connectivity(et: str, conn: tuple):
+            cells = _poly_cells(*conn)
+            new_connectivity = np.concatenate(
+                [np.append(len(c), c.astype(int)) for c in cells]
+            )
+            elems_type = np.array([mf_types_to_pv[et]] * len(cells))
+            return new_connectivity, elems_type
+
         def _mf_reg_to_pv_connectivity(et: str, conn: np.ndarray):
             num_nodes = conn.shape[1]
             n_elem = conn.shape[0]
@@ -181,16 +395,23 @@ def install_conversions():
             if et not in blocks or (dim != "all" and mf_types_dim[et] != dim):
                 continue
 
-            conn, et_types = _mf_reg_to_pv_connectivity(et, blocks[et])
+            if et not in mf_types_to_pv:
+                raise MefikitError(f"{et} elements have no pyvista cell type")
+            if et == "PGON":
+                conn, et_types = _mf_poly_to_pv_connectivity(et, blocks[et])
+            else:
+                conn, et_types = _mf_reg_to_pv_connectivity(et, blocks[et])
             conns.append(conn)
             et_typess.append(et_types)
             for f, v in fields.items():
-                fields_dict[f].append(v[et])
+                fields_dict[f].append(_field_values(v, et, len(et_types)))
 
         pv_conn = np.hstack(conns, dtype=int)
         pv_et_types = np.hstack(et_typess, dtype=int)
 
-        pv_fields_dict = {f: np.hstack(fields_dict[f], dtype=float) for f in fields}
+        pv_fields_dict = {
+            f: np.concatenate(fields_dict[f], dtype=float) for f in fields
+        }
 
         if coords.shape[1] == 1:
             pv_coords = np.hstack((coords, np.zeros((coords.shape[0], 2))))
@@ -202,8 +423,14 @@ def install_conversions():
         res = pv.UnstructuredGrid(pv_conn, pv_et_types, pv_coords)
         for f, v in pv_fields_dict.items():
             res.cell_data[f] = v
+        for name, mask in _node_groups_masks(self).items():
+            res.point_data[name] = mask.astype(bool)
         return res
 
     UMesh.to_meshio = to_meshio
+    UMesh.from_meshio = staticmethod(from_meshio)
     UMesh.to_mc = to_mc
+    UMesh.to_mc_fields = to_mc_fields
+    UMesh.to_mc_file = to_mc_file
+    UMesh.from_mc = staticmethod(from_mc)
     UMesh.to_pyvista = to_pyvista
@@ -0,0 +1,56 @@
+import mefikit as mf
+import numpy as np
+import pytest
+
+
+def test_primitives():
+    disk = mf.builders.disk(1.0, 8, 1)
+    assert disk.blocks()["QUAD4"].shape == (12, 4)
+    assert mf.builders.sphere(2.0, 1).blocks()["TRI3"].shape == (80, 3)
+    assert mf.builders.cylinder(1.0, 2.0, 8, 1, 2).blocks()["HEX8"].shape == (24, 8)
+    with pytest.raises(mf.MefikitError):
+        mf.builders.disk(1.0, 2, 1)
+    with pytest.raises(mf.MefikitError, match="n_height"):
+        mf.builders.cylinder_shell(1.0, 2.0, 8, 0)
+    with pytest.raises(mf.MefikitError):
+        mf.builders.box_surface([0.0] * 3, [1.0] * 3, [1, 0, 1])
+
+
+def test_voxels_and_implicit():
+    image = np.zeros((2, 2, 1), dtype=np.uint8)
+    image[0, 0, 0] = 3
+    image[1, 1, 0] = 1
+    mesh = mf.builders.voxels(image, 1.0)
+    assert mesh.blocks()["HEX8"].shape == (2, 8)
+    assert list(mesh.field("value")["HEX8"]) == [3.0, 1.0]
+
+    def ball(x, y, z):
+        return x * x + y * y + z * z - 1.0
+
+    surface = mf.builders.implicit(ball, ([-1.5] * 3, [1.5] * 3), [10, 10, 10])
+    assert len(surface.blocks()["TRI3"]) > 0
+
+    def failing(x, y, z):
+        raise RuntimeError("boom")
+
+    with pytest.raises(RuntimeError, match="boom"):
+        mf.builders.implicit(failing, ([-1.5] * 3, [1.5] * 3))
+
+
+def test_patterns():
+    square = mf.build_cmesh([0.0, 1.0], [0.0, 1.0])
+    row = mf.builders.linear_pattern(square, [1.0, 0.0], 3, merge_eps=1e-9)
+    assert row.blocks()["QUAD4"].shape == (3, 4)
+    assert row.coords().shape == (8, 2)
+    mirrored = mf.builders.mirror(square, [0.0, 0.0], [1.0, 0.0], merge_eps=1e-9)
+    assert mirrored.coords().shape == (6, 2)
+    with pytest.raises(mf.MefikitError):
+        mf.builders.mirror(square, [0.0, 0.0], [0.0, 0.0])
+
+    square.set_field("u", "QUAD4", np.array([[1.0, 2.0]]))
+    mirrored = mf.builders.mirror(
+        square, [0.0, 0.0], [1.0, 0.0], fields={"u": "vector"}
+    )
+    assert mirrored.field("u")["QUAD4"].tolist() == [[1.0, 2.0], [-1.0, 2.0]]
+    with pytest.raises(mf.MefikitError):
+        mf.builders.mirror(square, [0.0, 0.0], [1.0, 0.0], fields={"u": "matrix"})
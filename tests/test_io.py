@@ -1,3 +1,8 @@
+import mefikit as mf
+import numpy as np
+import pytest
+
+
 def test_to_mc_umesh3(umesh3):
     assert umesh3.to_mc()
 
@@ -12,3 +17,90 @@ def test_to_pv_umesh3(umesh3):
 
 def test_to_pv_umesh2(umesh2):
     assert umesh2.to_pyvista()
+
+
+def test_meshio_round_trip(umesh2):
+    n_cells = umesh2.blocks()["QUAD4"].shape[0]
+    umesh2.set_field("f", "QUAD4", np.arange(n_cells, dtype=float))
+    umesh2.set_node_group("left", [0, 1])
+    m = umesh2.to_meshio()
+    assert list(m.cell_data["f"][0]) == list(range(n_cells))
+    assert list(m.point_data["left"][:3]) == [1, 1, 0]
+
+    back = mf.UMesh.from_meshio(m)
+    assert (back.coords() == umesh2.coords()).all()
+    assert (back.blocks()["QUAD4"] == umesh2.blocks()["QUAD4"]).all()
+    assert list(back.field("f")["QUAD4"]) == list(range(n_cells))
+    assert list(back.node_groups()["left"]) == [0, 1]
+
+
+def test_meshio_types():
+    mesh = mf.UMesh(np.array([[0, 0], [1, 0], [2, 1], [1, 2], [0, 1]], dtype=float))
+    mesh.add_poly_block(
+        "PGON",
+        np.array([0, 1, 2, 3, 4, 0, 1, 3], dtype=np.uint),
+        np.array([5, 8], dtype=np.uint),
+    )
+    mesh.set_field("f", "PGON", np.array([5.0, 3.0]))
+    m = mesh.to_meshio()
+    # One meshio block per number of nodes
+    assert [len(b.data[0]) for b in m.cells] == [3, 5]
+    assert [list(v) for v in m.cell_data["f"]] == [[3.0], [5.0]]
+
+    back = mf.UMesh.from_meshio(m)
+    data, offsets = back.blocks()["PGON"]
+    assert list(data) == [0, 1, 3, 0, 1, 2, 3, 4]
+    assert list(offsets) == [3, 8]
+    assert list(back.field("f")["PGON"]) == [3.0, 5.0]
+
+    mesh = mf.UMesh(np.zeros((21, 3)))
+    mesh.add_regular_block("HEX21", np.arange(21, dtype=np.uint).reshape((1, 21)))
+    with pytest.raises(mf.MefikitError):
+        mesh.to_meshio()
+
+
+def test_meshio_point_data():
+    import meshio
+
+    m = meshio.Mesh(
+        np.zeros((3, 2)),
+        [("triangle", np.array([[0, 1, 2]]))],
+        point_data={"mask": np.array([1, 1, 0])},
+        point_sets={"left": np.array([0, 1])},
+    )
+    with pytest.warns(UserWarning, match="mask"):
+        back = mf.UMesh.from_meshio(m)
+    assert list(back.node_groups()) == ["left"]
+
+
+def test_pv_data(umesh2):
+    umesh2.set_node_group("left", [0])
+    grid = umesh2.to_pyvista()
+    assert grid.point_data["left"][0]
+    assert not grid.point_data["left"][1]
+
+
+def test_read_write_options(umesh2, tmp_path):
+    n_cells = umesh2.blocks()["QUAD4"].shape[0]
+    umesh2.set_field("a", "QUAD4", np.zeros(n_cells))
+    umesh2.set_field("b", "QUAD4", np.ones(n_cells))
+    path = str(tmp_path / "mesh.txt")
+    with pytest.raises(mf.MefikitIOError):
+        umesh2.write(path)
+    with pytest.raises(mf.MefikitError):
+        umesh2.write(path, format="med")
+    umesh2.write(path, format="json", fields=["a"])
+
+    back = mf.UMesh.read(path, format="json")
+    assert set(back.fields()) == {"a"}
+    assert not mf.UMesh.read(path, format="json", fields=[]).fields()
+    with pytest.raises(mf.MefikitIOError):
+        mf.UMesh.read(path, format="json", time_step=1)
+
+
+def test_write_compressed(umesh2, tmp_path):
+    path = str(tmp_path / "mesh.vtkhdf")
+    umesh2.write(path, compression=6)
+    assert (mf.UMesh.read(path).coords()[:, :2] == umesh2.coords()).all()
+    with pytest.raises(mf.MefikitIOError):
+        umesh2.write(str(tmp_path / "mesh.json"), compression=6)
@@ -1,4 +1,6 @@
 import mefikit as mf
+import numpy as np
+import pytest
 
 
 def test_instance(umesh3):
@@ -7,4 +9,148 @@ def test_instance(umesh3):
 
 def test_print(umesh3):
     print(umesh3)
-    assert umesh3.__str__().startswith("""UMeshBase {\n    coords:""")
+    assert str(umesh3).startswith("UMesh: 10 nodes in 3D, 6 elements\n")
+    assert "    HEX8: 1\n" in str(umesh3)
+    counts = "TRI3: 3, QUAD4: 2, HEX8: 1"
+    assert repr(umesh3) == f"<UMesh: 10 nodes in 3D, 6 elements ({counts})>"
+
+
+def test_fields(umesh3):
+    umesh3.set_field("f", "HEX8", np.array([[1.0, 2.0]]))
+    field = umesh3.field("f")
+    assert list(field) == ["HEX8"]
+    assert field["HEX8"].shape == (1, 2)
+    # Fields are read-only views of the mesh memory
+    assert not field["HEX8"].flags.writeable
+    with pytest.raises(ValueError):
+        field["HEX8"][0, 0] = 3.0
+    copy = umesh3.field("f", copy=True)["HEX8"]
+    copy[0, 0] = 3.0
+    assert umesh3.fields()["f"]["HEX8"][0, 0] == 1.0
+    # Views stay valid when the field is replaced
+    umesh3.set_field("f", "HEX8", np.array([[5.0, 6.0]]))
+    assert field["HEX8"][0, 1] == 2.0
+    assert umesh3.field("f")["HEX8"][0, 1] == 6.0
+    assert umesh3.field("g") is None
+    with pytest.raises(ValueError):
+        umesh3.set_field("g", "TRI3", np.zeros(2))
+    with pytest.raises(KeyError):
+        umesh3.set_field("g", "TET4", np.zeros(1))
+
+
+def test_select(umesh3):
+    ids = umesh3.select_ids("dim == 2")
+    assert sorted(ids) == ["QUAD4", "TRI3"]
+    assert list(ids["TRI3"]) == [0, 1, 2]
+    ids, quads = umesh3.select_with_ids(mf.sel.types(["QUAD4"]))
+    assert list(ids) == ["QUAD4"]
+    assert quads.block_types() == ["QUAD4"]
+    same = umesh3.select(mf.sel.parse("type == QUAD4"))
+    assert same.block_types() == ["QUAD4"]
+    assert list(umesh3.select_nodes("nodes in ids(1, 2)")) == [1, 2]
+    with pytest.raises(mf.MefikitError):
+        umesh3.select("dim ==")
+
+
+def test_errors(umesh3, tmp_path):
+    with pytest.raises(mf.MefikitError, match="'TRI4'"):
+        umesh3.set_field("f", "TRI4", np.zeros(3))
+    with pytest.raises(mf.MefikitError, match="dimension 4"):
+        umesh3.descend(4)
+    with pytest.raises(ValueError):
+        mf.sel.types(["HEX9"])
+    labels = np.zeros(3, dtype=np.int64)
+    with pytest.raises(mf.MefikitError, match="missing the QUAD4 block"):
+        umesh3.update_typed_field("t", {"TRI3": labels})
+    with pytest.raises(mf.MefikitError, match="2 QUAD4 elements"):
+        umesh3.update_typed_field("t", {"TRI3": labels, "QUAD4": labels})
+    missing = tmp_path / "missing.vtk"
+    with pytest.raises(mf.MefikitIOError, match="missing.vtk"):
+        mf.UMesh.read(str(missing))
+    with pytest.raises(OSError):
+        umesh3.write(str(tmp_path / "mesh.unknown"))
+
+
+def segments(coords):
+    mesh = mf.UMesh(np.array(coords, dtype=float))
+    mesh.add_regular_block("SEG2", np.array([[0, 1]], dtype=np.uint))
+    return mesh
+
+
+def test_algorithms(umesh2):
+    tool = segments([[1.5, -1.0], [1.5, 2.0]])
+    cut, parents = umesh2.cut(tool)
+    assert sum(len(p) for p in parents.values()) > 196
+    assert all(et == "QUAD4" for p in parents.values() for et, _ in p)
+    with pytest.raises(mf.MefikitError):
+        tool.cut(umesh2)
+
+    other = segments([[0.0, 0.0], [2.0, 2.0]])
+    cross = segments([[0.0, 2.0], [2.0, 0.0]])
+    assert other.intersect_1d(cross).blocks()["VERTEX"].shape == (1, 1)
+    assert other.fuse_1d(cross).blocks()["SEG2"].shape == (4, 2)
+
+    overlaps = umesh2.detect_overlaps()
+    assert len(overlaps) == 196
+    assert all(a == b for a, b, _ in overlaps)
+    assert umesh2.centroids()["QUAD4"].shape == (196, 2)
+    region = umesh2.grow_region({"QUAD4": np.array([0], dtype=np.uint)})
+    assert len(region["QUAD4"]) == 196
+
+    target = segments([[0.5, -1.0], [0.5, 1.0]])
+    projected, unsnapped = umesh2.project_nodes([0], target)
+    assert list(projected.coords()[0]) == [0.5, 0.0]
+    assert len(unsnapped) == 0
+
+
+def test_elements(umesh2):
+    quad = umesh2.element("QUAD4", 0)
+    assert quad.element_type == "QUAD4"
+    assert quad.connectivity.shape == (4,)
+    assert quad.coords.shape == (4, 2)
+    assert np.allclose(quad.centroid(), quad.coords.mean(axis=0))
+    assert quad.measure() == pytest.approx(umesh2.measure()["QUAD4"][0])
+    assert quad.groups == []
+    assert sum(1 for _ in umesh2) == len(umesh2.blocks()["QUAD4"])
+    with pytest.raises(IndexError):
+        umesh2.element("QUAD4", 1000)
+
+
+def test_add_poly_and_elements():
+    mesh = mf.UMesh(np.array([[0, 0], [1, 0], [2, 1], [1, 2], [0, 1]], dtype=float))
+    mesh.add_poly_block(
+        "PGON", np.array([0, 1, 2, 3, 4], dtype=np.uint), np.array([5], dtype=np.uint)
+    )
+    assert mesh.add_element("PGON", [0, 1, 3], family=2) == ("PGON", 1)
+    assert mesh.add_element("SEG2", [0, 1]) == ("SEG2", 0)
+    data, offsets = mesh.blocks()["PGON"]
+    assert list(offsets) == [5, 8]
+    assert mesh.element("PGON", 1).family == 2
+    with pytest.raises(mf.MefikitError):
+        mesh.add_element("TRI3", [0, 1])
+    with pytest.raises(mf.MefikitError):
+        mesh.add_element("SEG2", [0, 9])
+    with pytest.raises(mf.MefikitError):
+        mesh.add_poly_block("QUAD4", np.zeros(4, dtype=np.uint), np.array([4]))
+
+
+def test_mc_arrays(umesh3):
+    umesh3.set_field("f", "HEX8", np.array([2.0]))
+    umesh3.set_node_group("first", [0])
+    arrays = umesh3.to_mc_arrays()
+    assert [lv["mesh_dimension"] for lv in arrays["levels"]] == [3, 2]
+    hexa, faces = arrays["levels"]
+    assert list(hexa["connectivity"]) == [18, 0, 3, 2, 1, 4, 7, 6, 5]
+    assert list(hexa["connectivity_index"]) == [0, 9]
+    assert hexa["fields"]["f"].shape == (1, 1)
+    assert list(faces["connectivity_index"]) == [0, 4, 8, 12, 17, 22]
+    assert list(arrays["node_families"][:2]) == [1, 0]
+    assert arrays["groups"] == {"first": [1]}
+
+    back = mf.UMesh.from_mc_arrays(arrays)
+    assert back.block_types() == umesh3.block_types()
+    assert back.field("f")["HEX8"][0] == 2.0
+    assert list(back.node_groups()["first"]) == [0]
+    arrays["levels"][0]["connectivity"][0] = 99
+    with pytest.raises(mf.MefikitError):
+        mf.UMesh.from_mc_arrays(arrays)